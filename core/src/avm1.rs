@@ -3,15 +3,18 @@ use crate::avm1::object::{search_prototype, stage_object};
 use crate::context::UpdateContext;
 use crate::prelude::*;
 use gc_arena::{Collect, GcCell, MutationContext};
+use instant::Instant;
+use std::time::Duration;
 
 use swf::avm1::read::Reader;
 
 use crate::display_object::DisplayObject;
+use crate::events::{ClipEvent, ClipEventMask};
 use crate::tag_utils::SwfSlice;
 
 #[cfg(test)]
 #[macro_use]
-mod test_utils;
+pub(crate) mod test_utils;
 
 pub mod activation;
 mod callable_value;
@@ -114,10 +117,34 @@ pub struct Avm1<'gc> {
     /// is raised. This defaults to 256 but can be changed per movie.
     max_recursion_depth: u16,
 
+    /// The maximum amount of actions that can be executed in a single update before the
+    /// user is asked whether to abort the script, or `None` for no limit.
+    max_actions_per_update: Option<u64>,
+
+    /// The amount of actions executed in the current update, since the user last chose to
+    /// keep running a slow script.
+    actions_executed: u64,
+
+    /// When the user last chose to keep running a slow script in the current update.
+    #[collect(require_static)]
+    execution_extended_at: Option<Instant>,
+
     /// Whether a Mouse listener has been registered.
     /// Used to prevent scrolling on web.
     has_mouse_listener: bool,
 
+    /// The propagating clip events that `MovieClip.prototype` has a handler
+    /// method for, as of the last dispatched clip event. Every clip inherits
+    /// these handlers, so none of them can skip those events.
+    #[collect(require_static)]
+    prototype_clip_events: ClipEventMask,
+
+    /// Objects whose `__resolve` handler is currently running.
+    ///
+    /// A lookup miss on any of these objects will not call `__resolve` again,
+    /// so that handlers which access `this` don't recurse forever.
+    resolving: Vec<Object<'gc>>,
+
     #[cfg(feature = "avm_debug")]
     pub debug_output: bool,
 }
@@ -142,7 +169,12 @@ impl<'gc> Avm1<'gc> {
             ],
             halted: false,
             max_recursion_depth: 255,
+            max_actions_per_update: None,
+            actions_executed: 0,
+            execution_extended_at: None,
             has_mouse_listener: false,
+            prototype_clip_events: ClipEventMask::empty(),
+            resolving: vec![],
 
             #[cfg(feature = "avm_debug")]
             debug_output: false,
@@ -369,6 +401,31 @@ impl<'gc> Avm1<'gc> {
         self.has_mouse_listener
     }
 
+    /// The propagating clip events that every clip inherits a handler for.
+    pub fn prototype_clip_events(&self) -> ClipEventMask {
+        self.prototype_clip_events
+    }
+
+    /// Check whether `MovieClip.prototype` has a handler method for a clip
+    /// event that is about to be dispatched.
+    pub fn update_prototype_clip_events(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        event: ClipEvent,
+    ) {
+        let mut events = ClipEventMask::empty();
+        if let Some(name) = event.method_name() {
+            let movie_clip_proto = context.avm1.prototypes().movie_clip;
+            let mut activation = Activation::from_stub(
+                context.reborrow(),
+                ActivationIdentifier::root("[Clip Event Prototypes]"),
+            );
+            if movie_clip_proto.has_property(&mut activation, name) {
+                events = event.mask();
+            }
+        }
+        context.avm1.prototype_clip_events = events;
+    }
+
     /// Halts the AVM, preventing execution of any further actions.
     ///
     /// If the AVM is currently evaluating an action, it will continue until it realizes that it has
@@ -400,6 +457,12 @@ impl<'gc> Avm1<'gc> {
         value
     }
 
+    /// Obtain the current contents of the operand stack, bottom-most value
+    /// first.
+    pub fn stack(&self) -> &[Value<'gc>] {
+        &self.stack
+    }
+
     /// Obtain the value of `_global`.
     pub fn global_object(&self) -> Value<'gc> {
         Value::Object(self.globals)
@@ -423,6 +486,55 @@ impl<'gc> Avm1<'gc> {
         self.max_recursion_depth = max_recursion_depth
     }
 
+    pub fn max_actions_per_update(&self) -> Option<u64> {
+        self.max_actions_per_update
+    }
+
+    pub fn set_max_actions_per_update(&mut self, max_actions_per_update: Option<u64>) {
+        self.max_actions_per_update = max_actions_per_update
+    }
+
+    /// Give scripts a new execution budget at the start of an update.
+    pub fn reset_execution_budget(&mut self) {
+        self.actions_executed = 0;
+        self.execution_extended_at = None;
+    }
+
+    /// Give a slow script a new execution budget, after the user chose to
+    /// keep running it.
+    pub fn extend_execution_budget(&mut self) {
+        self.actions_executed = 0;
+        self.execution_extended_at = Some(Instant::now());
+    }
+
+    /// Count an executed action against the execution budget.
+    ///
+    /// Returns `true` if the script has run for longer than `max_execution_duration`, or has
+    /// executed more than `max_actions_per_update` actions. To keep this cheap, the time is
+    /// only checked every few thousand actions.
+    pub fn count_action(
+        &mut self,
+        update_start: Instant,
+        max_execution_duration: Duration,
+    ) -> bool {
+        const CHECK_INTERVAL: u64 = 2000;
+
+        self.actions_executed += 1;
+        if self
+            .max_actions_per_update
+            .map_or(false, |max| self.actions_executed > max)
+        {
+            return true;
+        }
+
+        if self.actions_executed % CHECK_INTERVAL != 0 {
+            return false;
+        }
+
+        let start = self.execution_extended_at.unwrap_or(update_start);
+        start.elapsed() >= max_execution_duration
+    }
+
     pub fn broadcaster_functions(&self) -> BroadcasterFunctions<'gc> {
         self.broadcaster_functions
     }
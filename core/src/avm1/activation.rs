@@ -1,6 +1,7 @@
 use crate::avm1::callable_value::CallableValue;
 use crate::avm1::error::Error;
 use crate::avm1::function::{Avm1Function, ExecutionReason, FunctionObject};
+use crate::avm1::object::stage_object::level_id_from_name;
 use crate::avm1::object::{Object, TObject};
 use crate::avm1::property::Attribute;
 use crate::avm1::scope::Scope;
@@ -8,9 +9,12 @@ use crate::avm1::{
     fscommand, globals, scope, skip_actions, start_drag, AvmString, ScriptObject, Value,
 };
 use crate::backend::navigator::{NavigationMethod, RequestOptions};
+use crate::config::StageQuality;
 use crate::context::UpdateContext;
 use crate::display_object::{DisplayObject, MovieClip, TDisplayObject, TDisplayObjectContainer};
 use crate::ecma_conversions::f64_to_wrapping_u32;
+use crate::execution_position;
+use crate::loader::LoadManager;
 use crate::tag_utils::SwfSlice;
 use crate::vminterface::Instantiator;
 use crate::{avm_error, avm_warn};
@@ -223,9 +227,6 @@ pub struct Activation<'a, 'gc: 'a, 'gc_context: 'a> {
     /// This can be changed with `tellTarget` (via `ActionSetTarget` and `ActionSetTarget2`).
     target_clip: Option<DisplayObject<'gc>>,
 
-    /// Amount of actions performed since the last timeout check
-    actions_since_timeout_check: u16,
-
     /// Whether the base clip was removed when we started this frame.
     base_clip_unloaded: bool,
 
@@ -270,7 +271,6 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             callee,
             arguments,
             local_registers: None,
-            actions_since_timeout_check: 0,
         }
     }
 
@@ -295,7 +295,6 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             callee: self.callee,
             arguments: self.arguments,
             local_registers: self.local_registers,
-            actions_since_timeout_check: 0,
         }
     }
 
@@ -331,7 +330,6 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             callee: None,
             arguments: None,
             local_registers: None,
-            actions_since_timeout_check: 0,
         }
     }
 
@@ -431,8 +429,10 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     pub fn run_actions(&mut self, code: SwfSlice) -> Result<ReturnType<'gc>, Error<'gc>> {
         let mut read = Reader::new(&code.movie.data()[code.start..], self.swf_version());
+        let _position = execution_position::enter_avm1_actions();
 
         loop {
+            execution_position::set_avm1_action(read.get_ref());
             let result = self.do_action(&code, &mut read);
             match result {
                 Ok(FrameControl::Return(return_type)) => break Ok(return_type),
@@ -448,12 +448,18 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         data: &'b SwfSlice,
         reader: &mut Reader<'b>,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        self.actions_since_timeout_check += 1;
-        if self.actions_since_timeout_check >= 2000 {
-            self.actions_since_timeout_check = 0;
-            if self.context.update_start.elapsed() >= self.context.max_execution_duration {
+        let update_start = self.context.update_start;
+        let max_execution_duration = self.context.max_execution_duration;
+        if self
+            .context
+            .avm1
+            .count_action(update_start, max_execution_duration)
+        {
+            // Like Flash Player, let the user decide whether to abort a slow script.
+            if self.context.ui.display_slow_script_prompt() {
                 return Err(Error::ExecutionTimeout);
             }
+            self.context.avm1.extend_execution_budget();
         }
 
         if reader.get_ref().as_ptr() as usize >= data.as_ref().as_ptr_range().end as usize {
@@ -801,14 +807,28 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(FrameControl::Continue)
     }
 
-    fn action_call_function(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
-        let fn_name_value = self.context.avm1.pop();
-        let fn_name = fn_name_value.coerce_to_string(self)?;
-        let num_args = self.context.avm1.pop().coerce_to_f64(self)? as usize; // TODO(Herschel): max arg count?
+    /// Pop the argument count of a call, and then the arguments themselves.
+    ///
+    /// Flash never pops more arguments than are on the stack, so a bogus
+    /// count (negative, NaN or larger than the stack) only takes what's there.
+    fn pop_arguments(&mut self) -> Result<Vec<Value<'gc>>, Error<'gc>> {
+        let num_args = self.context.avm1.pop().coerce_to_f64(self)?;
+        let num_args = if num_args > 0.0 {
+            (num_args as usize).min(self.context.avm1.stack().len())
+        } else {
+            0
+        };
         let mut args = Vec::with_capacity(num_args);
         for _ in 0..num_args {
             args.push(self.context.avm1.pop());
         }
+        Ok(args)
+    }
+
+    fn action_call_function(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
+        let fn_name_value = self.context.avm1.pop();
+        let fn_name = fn_name_value.coerce_to_string(self)?;
+        let args = self.pop_arguments()?;
 
         let variable = self.get_variable(&fn_name)?;
 
@@ -830,11 +850,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn action_call_method(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
         let method = self.context.avm1.pop();
         let object_val = self.context.avm1.pop();
-        let num_args = self.context.avm1.pop().coerce_to_f64(self)? as usize; // TODO(Herschel): max arg count?
-        let mut args = Vec::with_capacity(num_args);
-        for _ in 0..num_args {
-            args.push(self.context.avm1.pop());
-        }
+        let args = self.pop_arguments()?;
 
         // Can not call method on undefined/null.
         if matches!(object_val, Value::Undefined | Value::Null) {
@@ -1205,7 +1221,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             *self.context.time_offset += 1;
         }
 
-        let time = self.context.navigator.time_since_launch().as_millis() as u32;
+        let time = self.context.time_since_launch().as_millis() as u32;
         self.context
             .avm1
             .push(time.wrapping_add(*self.context.time_offset));
@@ -1232,27 +1248,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         let url = url.to_string_lossy(self.encoding());
         if target.starts_with("_level") && target.len() > 6 {
             match target[6..].parse::<i32>() {
-                Ok(level_id) => {
-                    let fetch = self.context.navigator.fetch(&url, RequestOptions::get());
-                    let level = self.resolve_level(level_id);
-
-                    if url.is_empty() {
-                        //Blank URL on movie loads = unload!
-                        if let Some(mut mc) = level.as_movie_clip() {
-                            mc.replace_with_movie(self.context.gc_context, None)
-                        }
-                    } else {
-                        let process = self.context.load_manager.load_movie_into_clip(
-                            self.context.player.clone().unwrap(),
-                            level,
-                            fetch,
-                            url,
-                            None,
-                            None,
-                        );
-                        self.context.navigator.spawn_future(process);
-                    }
-                }
+                Ok(level_id) => self.load_movie_into_level(level_id, &url, RequestOptions::get()),
                 Err(e) => avm_warn!(
                     self,
                     "Couldn't parse level id {} for action_get_url: {}",
@@ -1267,9 +1263,9 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             let fsargs = target;
             fscommand::handle(fscommand, fsargs, self)?;
         } else {
+            let requester = self.base_clip();
             self.context
-                .navigator
-                .navigate_to_url(url.to_owned(), Some(target.to_owned()), None);
+                .navigate_to_url(requester, url.to_owned(), Some(target.to_owned()), None);
         }
 
         Ok(FrameControl::Continue)
@@ -1316,7 +1312,8 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     Cow::Borrowed(&url),
                     NavigationMethod::from_send_vars_method(swf_method),
                 );
-                let fetch = self.context.navigator.fetch(&url, opts);
+                let requester = self.base_clip();
+                let fetch = self.context.fetch_data(requester, &url, opts);
                 let process = self.context.load_manager.load_form_into_object(
                     self.context.player.clone().unwrap(),
                     target_obj,
@@ -1340,12 +1337,13 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                         mc.replace_with_movie(self.context.gc_context, None)
                     }
                 } else {
-                    let fetch = self.context.navigator.fetch(&url, opts);
-                    let process = self.context.load_manager.load_movie_into_clip(
-                        self.context.player.clone().unwrap(),
+                    let requester = self.base_clip();
+                    let process = LoadManager::load_movie_into_clip(
+                        &mut self.context,
                         clip_target,
-                        fetch,
-                        url.to_string(),
+                        requester,
+                        &url,
+                        opts,
                         None,
                         None,
                     );
@@ -1353,27 +1351,20 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                 }
             }
             return Ok(FrameControl::Continue);
-        } else if window_target.starts_with("_level") && url.len() > 6 {
+        } else if window_target.starts_with("_level") && window_target.len() > 6 {
             // target of `_level#` indicates a `loadMovieNum` call.
             match window_target[6..].parse::<i32>() {
                 Ok(level_id) => {
-                    let fetch = self.context.navigator.fetch(&url, RequestOptions::get());
-                    let level = self.resolve_level(level_id);
-
-                    let process = self.context.load_manager.load_movie_into_clip(
-                        self.context.player.clone().unwrap(),
-                        level,
-                        fetch,
-                        url.to_string(),
-                        None,
-                        None,
+                    let (url, opts) = self.locals_into_request_options(
+                        Cow::Borrowed(&url),
+                        NavigationMethod::from_send_vars_method(swf_method),
                     );
-                    self.context.navigator.spawn_future(process);
+                    self.load_movie_into_level(level_id, &url, opts);
                 }
                 Err(e) => avm_warn!(
                     self,
                     "Couldn't parse level id {} for action_get_url_2: {}",
-                    url,
+                    window_target,
                     e
                 ),
             }
@@ -1385,7 +1376,9 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                 None => None,
             };
 
-            self.context.navigator.navigate_to_url(
+            let requester = self.base_clip();
+            self.context.navigate_to_url(
+                requester,
                 url.to_string(),
                 Some(window_target.to_string()),
                 vars,
@@ -1686,11 +1679,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn action_new_method(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
         let method_name = self.context.avm1.pop();
         let object_val = self.context.avm1.pop();
-        let num_args = self.context.avm1.pop().coerce_to_f64(self)? as usize;
-        let mut args = Vec::with_capacity(num_args);
-        for _ in 0..num_args {
-            args.push(self.context.avm1.pop());
-        }
+        let args = self.pop_arguments()?;
 
         // Can not call method on undefined/null.
         if matches!(object_val, Value::Undefined | Value::Null) {
@@ -1719,11 +1708,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn action_new_object(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
         let fn_name_val = self.context.avm1.pop();
         let fn_name = fn_name_val.coerce_to_string(self)?;
-        let num_args = self.context.avm1.pop().coerce_to_f64(self)? as usize;
-        let mut args = Vec::with_capacity(num_args);
-        for _ in 0..num_args {
-            args.push(self.context.avm1.pop());
-        }
+        let args = self.pop_arguments()?;
 
         let name_value: Value<'gc> = self.resolve(&fn_name)?.into();
         let constructor = name_value.coerce_to_object(self);
@@ -1864,14 +1849,23 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     fn action_set_property(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
         let value = self.context.avm1.pop();
-        let prop_index = self.context.avm1.pop().coerce_to_u32(self)? as usize;
+        let prop_index = self.context.avm1.pop().into_number_v1() as usize;
         let path = self.context.avm1.pop();
         if let Some(target) = self.target_clip() {
             if let Some(clip) = self.resolve_target_display_object(target, path, true)? {
                 let display_properties = self.context.avm1.display_properties;
                 let props = display_properties.read();
                 if let Some(property) = props.get_by_index(prop_index) {
-                    property.set(self, clip, value)?;
+                    // A value that can't be coerced leaves the property unchanged,
+                    // rather than aborting the script.
+                    if let Err(e) = property.set(self, clip, value) {
+                        if e.is_halting() {
+                            return Err(e);
+                        }
+                        avm_warn!(self, "SetProperty: Couldn't set property {}", prop_index);
+                    }
+                } else {
+                    avm_warn!(self, "SetProperty: Invalid property index {}", prop_index);
                 }
             } else {
                 avm_warn!(self, "SetProperty: Invalid target");
@@ -2149,7 +2143,13 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     }
 
     fn toggle_quality(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
-        // TODO(Herschel): Noop for now? Could chang anti-aliasing on render backend.
+        // Toggles between low and high quality, like `_highquality`.
+        let stage = self.context.stage;
+        let quality = match stage.quality() {
+            StageQuality::Low => StageQuality::High,
+            _ => StageQuality::Low,
+        };
+        stage.set_quality(&mut self.context, quality);
         Ok(FrameControl::Continue)
     }
 
@@ -2258,6 +2258,14 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                 Ok(FrameControl::Continue)
             }
 
+            // Flash limits how deeply `with` blocks may be nested. A block
+            // that would go past the limit is skipped without running any of
+            // its actions.
+            _ if self.scope().with_depth() >= self.max_with_depth() => {
+                avm_warn!(self, "With: Too many nested with blocks");
+                Ok(FrameControl::Continue)
+            }
+
             value => {
                 // Note that primitives get boxed at this point.
                 let object = value.coerce_to_object(self);
@@ -2273,6 +2281,16 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         }
     }
 
+    /// The maximum number of `with` scopes that may be on the scope chain at
+    /// once.
+    fn max_with_depth(&self) -> usize {
+        if self.swf_version < 6 {
+            7
+        } else {
+            15
+        }
+    }
+
     fn action_try(
         &mut self,
         try_block: &TryBlock,
@@ -2286,33 +2304,28 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
         if let Some((catch_vars, actions)) = &try_block.catch {
             if let Err(Error::ThrownValue(value)) = &result {
-                let mut activation = Activation::from_action(
-                    self.context.reborrow(),
-                    self.id.child("[Catch]"),
-                    self.swf_version,
-                    self.scope,
-                    self.constant_pool,
-                    self.base_clip,
-                    self.this,
-                    self.callee,
-                    self.arguments,
-                );
+                let value = value.to_owned();
 
+                // The catch block runs in the same activation as the try
+                // block, so that it shares its local registers and target.
                 match catch_vars {
-                    CatchVar::Var(name) => activation.set_variable(
-                        &name.to_str_lossy(activation.encoding()),
-                        value.to_owned(),
-                    )?,
-                    CatchVar::Register(id) => {
-                        activation.set_current_register(*id, value.to_owned())
+                    CatchVar::Var(name) => {
+                        self.set_variable(&name.to_str_lossy(self.encoding()), value)?
                     }
+                    CatchVar::Register(id) => self.set_current_register(*id, value),
                 }
 
-                result =
-                    activation.run_actions(parent_data.to_unbounded_subslice(actions).unwrap());
+                result = self.run_actions(parent_data.to_unbounded_subslice(actions).unwrap());
             }
         }
 
+        // Halting errors abort the whole action list, so there's no point in
+        // running the finally block.
+        let result = match result {
+            Err(error) if error.is_halting() => return Err(error),
+            result => result,
+        };
+
         if let Some(actions) = try_block.finally {
             if let ReturnType::Explicit(value) =
                 self.run_actions(parent_data.to_unbounded_subslice(actions).unwrap())?
@@ -2495,6 +2508,9 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     ///
     /// A target path always resolves via the display list. It can look
     /// at the prototype chain, but not the scope chain.
+    ///
+    /// A relative path may start with `_levelN` to address another level,
+    /// such as `_level1/clip`; this resolves to `None` if the level is empty.
     pub fn resolve_target_path(
         &mut self,
         root: DisplayObject<'gc>,
@@ -2519,6 +2535,9 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
         let case_sensitive = self.is_case_sensitive();
 
+        // `_levelN` may only appear at the start of a relative path.
+        let mut first_token = !is_slash_path;
+
         // Iterate through each token in the path.
         while !path.is_empty() {
             // Skip any number of leading :
@@ -2571,6 +2590,14 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     self.this_cell().into()
                 } else if first_element && name == "_root" {
                     self.root_object()?
+                } else if let Some(level_id) =
+                    level_id_from_name(name, case_sensitive).filter(|_| first_token)
+                {
+                    if let Some(level) = self.context.stage.child_by_depth(level_id) {
+                        level.object()
+                    } else {
+                        return Ok(None);
+                    }
                 } else {
                     // Get the value from the object.
                     // Resolves display object instances first, then local variables.
@@ -2582,13 +2609,14 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     {
                         child.object()
                     } else {
-                        object.get(&name, self).unwrap()
+                        object.get(&name, self)?
                     }
                 }
             };
 
             // `this`/`_root` can only be the first element in the path.
             first_element = false;
+            first_token = false;
 
             // Resolve the value to an object while traversing the path.
             object = if let Value::Object(o) = val {
@@ -2804,6 +2832,32 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(())
     }
 
+    /// Load a movie into a level, as with `loadMovieNum`.
+    ///
+    /// The level is created if it is empty. A blank URL unloads the level
+    /// instead.
+    fn load_movie_into_level(&mut self, level_id: i32, url: &str, options: RequestOptions) {
+        if url.is_empty() {
+            let stage = self.context.stage;
+            stage.unload_level(&mut self.context, level_id);
+            return;
+        }
+
+        let requester = self.base_clip();
+        let level = self.resolve_level(level_id);
+
+        let process = LoadManager::load_movie_into_clip(
+            &mut self.context,
+            level,
+            requester,
+            url,
+            options,
+            None,
+            None,
+        );
+        self.context.navigator.spawn_future(process);
+    }
+
     /// Resolve a level by ID.
     ///
     /// If the level does not exist, then it will be created and instantiated
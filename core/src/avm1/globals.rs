@@ -34,14 +34,18 @@ pub mod gradient_bevel_filter;
 pub mod gradient_glow_filter;
 mod key;
 mod load_vars;
+mod local_connection;
 mod math;
 mod matrix;
 pub(crate) mod mouse;
 pub(crate) mod movie_clip;
 mod movie_clip_loader;
+mod net_connection;
+pub(crate) mod net_stream;
 pub(crate) mod number;
 mod object;
 mod point;
+mod print_job;
 mod rectangle;
 mod selection;
 pub(crate) mod shared_object;
@@ -543,6 +547,17 @@ pub fn create_globals<'gc>(
     let movie_clip_proto: Object<'gc> =
         movie_clip::create_proto(gc_context, object_proto, function_proto);
 
+    let local_connection_proto: Object<'gc> =
+        local_connection::create_proto(gc_context, object_proto, function_proto);
+
+    let net_connection_proto: Object<'gc> =
+        net_connection::create_proto(gc_context, object_proto, function_proto);
+    let net_stream_proto: Object<'gc> =
+        net_stream::create_proto(gc_context, object_proto, function_proto);
+
+    let print_job_proto: Object<'gc> =
+        print_job::create_proto(gc_context, object_proto, function_proto);
+
     let sound_proto: Object<'gc> = sound::create_proto(gc_context, object_proto, function_proto);
 
     let text_field_proto: Object<'gc> =
@@ -651,6 +666,34 @@ pub fn create_globals<'gc>(
         movie_clip_proto,
     );
 
+    let local_connection = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(local_connection::constructor),
+        constructor_to_fn!(local_connection::constructor),
+        Some(function_proto),
+        local_connection_proto,
+    );
+    let net_connection = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(net_connection::constructor),
+        constructor_to_fn!(net_connection::constructor),
+        Some(function_proto),
+        net_connection_proto,
+    );
+    let net_stream = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(net_stream::constructor),
+        constructor_to_fn!(net_stream::constructor),
+        Some(function_proto),
+        net_stream_proto,
+    );
+    let print_job = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(print_job::constructor),
+        constructor_to_fn!(print_job::constructor),
+        Some(function_proto),
+        print_job_proto,
+    );
     let sound = FunctionObject::constructor(
         gc_context,
         Executable::Native(sound::constructor),
@@ -973,6 +1016,30 @@ pub fn create_globals<'gc>(
         movie_clip_loader.into(),
         Attribute::DONT_ENUM,
     );
+    globals.define_value(
+        gc_context,
+        "LocalConnection",
+        local_connection.into(),
+        Attribute::DONT_ENUM,
+    );
+    globals.define_value(
+        gc_context,
+        "NetConnection",
+        net_connection.into(),
+        Attribute::DONT_ENUM,
+    );
+    globals.define_value(
+        gc_context,
+        "NetStream",
+        net_stream.into(),
+        Attribute::DONT_ENUM,
+    );
+    globals.define_value(
+        gc_context,
+        "PrintJob",
+        print_job.into(),
+        Attribute::DONT_ENUM,
+    );
     globals.define_value(gc_context, "Sound", sound.into(), Attribute::DONT_ENUM);
     globals.define_value(
         gc_context,
@@ -1116,6 +1183,20 @@ pub fn create_globals<'gc>(
         )),
         Attribute::DONT_ENUM,
     );
+    // `$version` is a global, so that every timeline can read it, and not
+    // only the root one that it's also defined on.
+    globals.add_property(
+        gc_context,
+        "$version",
+        FunctionObject::function(
+            gc_context,
+            Executable::Native(system_capabilities::get_version),
+            Some(function_proto),
+            function_proto,
+        ),
+        None,
+        Attribute::DONT_ENUM,
+    );
     globals.force_set_function(
         "isFinite",
         is_finite,
@@ -1294,14 +1294,15 @@ pub fn load_bitmap<'gc>(
         .unwrap_or(&Value::Undefined)
         .coerce_to_string(activation)?;
 
-    let library = &*activation.context.library;
+    let target = activation.target_clip_or_root()?;
+    let level0 = activation.context.stage.root_clip();
 
-    let movie = activation.target_clip_or_root()?.movie();
+    let library = &*activation.context.library;
 
     let renderer = &mut activation.context.renderer;
 
-    let character = movie
-        .and_then(|m| library.library_for_movie(m))
+    let character = library
+        .library_for_export(target, level0, name.as_str())
         .and_then(|l| l.character_by_export_name(name.as_str()));
 
     if let Some(Character::Bitmap(bitmap_object)) = character {
@@ -2,6 +2,7 @@
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
+use crate::avm1::object::bevel_filter::BevelFilterType;
 use crate::avm1::property::Attribute;
 use crate::avm1::{Object, ScriptObject, TObject, Value};
 use gc_arena::MutationContext;
@@ -268,6 +269,340 @@ pub fn clone<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Converts an AVM1 filter object into the filter that is applied when a
+/// display object is rendered.
+///
+/// Returns `None` if the object isn't a filter, or is a filter that can't be
+/// rendered, such as a `DisplacementMapFilter`.
+pub fn avm1_to_filter(object: Object<'_>) -> Option<swf::Filter> {
+    if let Some(filter) = object.as_blur_filter_object() {
+        return Some(swf::Filter::BlurFilter(Box::new(swf::BlurFilter {
+            blur_x: filter.blur_x(),
+            blur_y: filter.blur_y(),
+            num_passes: num_passes(filter.quality()),
+        })));
+    }
+
+    if let Some(filter) = object.as_glow_filter_object() {
+        return Some(swf::Filter::GlowFilter(Box::new(swf::GlowFilter {
+            color: color(filter.color() as u32, filter.alpha()),
+            blur_x: filter.blur_x(),
+            blur_y: filter.blur_y(),
+            strength: filter.strength() as f32,
+            is_inner: filter.inner(),
+            is_knockout: filter.knockout(),
+            num_passes: num_passes(filter.quality()),
+        })));
+    }
+
+    if let Some(filter) = object.as_drop_shadow_filter_object() {
+        // TODO: `hideObject` has no equivalent in SWF filters, and is ignored.
+        return Some(swf::Filter::DropShadowFilter(Box::new(
+            swf::DropShadowFilter {
+                color: color(filter.color(), filter.alpha()),
+                blur_x: filter.blur_x(),
+                blur_y: filter.blur_y(),
+                angle: filter.angle().to_radians(),
+                distance: filter.distance(),
+                strength: filter.strength() as f32,
+                is_inner: filter.inner(),
+                is_knockout: filter.knockout(),
+                num_passes: num_passes(filter.quality()),
+            },
+        )));
+    }
+
+    if let Some(filter) = object.as_bevel_filter_object() {
+        let (is_inner, is_on_top) = bevel_flags(filter.get_type());
+        return Some(swf::Filter::BevelFilter(Box::new(swf::BevelFilter {
+            shadow_color: color(filter.shadow_color(), filter.shadow_alpha()),
+            highlight_color: color(filter.highlight_color(), filter.highlight_alpha()),
+            blur_x: filter.blur_x(),
+            blur_y: filter.blur_y(),
+            angle: filter.angle().to_radians(),
+            distance: filter.distance(),
+            strength: filter.strength() as f32,
+            is_inner,
+            is_knockout: filter.knockout(),
+            is_on_top,
+            num_passes: num_passes(filter.quality()),
+        })));
+    }
+
+    if let Some(filter) = object.as_gradient_glow_filter_object() {
+        let (is_inner, is_on_top) = bevel_flags(filter.get_type());
+        return Some(swf::Filter::GradientGlowFilter(Box::new(
+            swf::GradientGlowFilter {
+                colors: gradient(&filter.colors(), &filter.alphas(), &filter.ratios()),
+                blur_x: filter.blur_x(),
+                blur_y: filter.blur_y(),
+                angle: filter.angle().to_radians(),
+                distance: filter.distance(),
+                strength: filter.strength() as f32,
+                is_inner,
+                is_knockout: filter.knockout(),
+                is_on_top,
+                num_passes: num_passes(filter.quality()),
+            },
+        )));
+    }
+
+    if let Some(filter) = object.as_gradient_bevel_filter_object() {
+        let (is_inner, is_on_top) = bevel_flags(filter.get_type());
+        return Some(swf::Filter::GradientBevelFilter(Box::new(
+            swf::GradientBevelFilter {
+                colors: gradient(&filter.colors(), &filter.alphas(), &filter.ratios()),
+                blur_x: filter.blur_x(),
+                blur_y: filter.blur_y(),
+                angle: filter.angle().to_radians(),
+                distance: filter.distance(),
+                strength: filter.strength() as f32,
+                is_inner,
+                is_knockout: filter.knockout(),
+                is_on_top,
+                num_passes: num_passes(filter.quality()),
+            },
+        )));
+    }
+
+    if let Some(filter) = object.as_color_matrix_filter_object() {
+        return Some(swf::Filter::ColorMatrixFilter(Box::new(
+            swf::ColorMatrixFilter {
+                matrix: filter.matrix(),
+            },
+        )));
+    }
+
+    if let Some(filter) = object.as_convolution_filter_object() {
+        return Some(swf::Filter::ConvolutionFilter(Box::new(
+            swf::ConvolutionFilter {
+                num_matrix_rows: filter.matrix_y(),
+                num_matrix_cols: filter.matrix_x(),
+                matrix: filter.matrix(),
+                divisor: filter.divisor(),
+                bias: filter.bias(),
+                default_color: color(filter.color(), filter.alpha()),
+                is_clamped: filter.clamp(),
+                is_preserve_alpha: filter.preserve_alpha(),
+            },
+        )));
+    }
+
+    None
+}
+
+/// Creates an AVM1 filter object from a filter applied to a display object.
+pub fn filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    filter: &swf::Filter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let prototypes = &activation.context.avm1.prototypes;
+    match filter {
+        swf::Filter::BlurFilter(filter) => {
+            let constructor = prototypes.blur_filter_constructor;
+            constructor.construct(
+                activation,
+                &[
+                    filter.blur_x.into(),
+                    filter.blur_y.into(),
+                    filter.num_passes.into(),
+                ],
+            )
+        }
+        swf::Filter::GlowFilter(filter) => {
+            let constructor = prototypes.glow_filter_constructor;
+            let object = constructor.construct(
+                activation,
+                &[
+                    filter.color.to_rgb().into(),
+                    alpha(&filter.color).into(),
+                    filter.blur_x.into(),
+                    filter.blur_y.into(),
+                    filter.strength.into(),
+                    filter.num_passes.into(),
+                ],
+            )?;
+            if let Value::Object(object) = object {
+                object.set("inner", filter.is_inner.into(), activation)?;
+                object.set("knockout", filter.is_knockout.into(), activation)?;
+            }
+            Ok(object)
+        }
+        swf::Filter::DropShadowFilter(filter) => {
+            let constructor = prototypes.drop_shadow_filter_constructor;
+            constructor.construct(
+                activation,
+                &[
+                    filter.distance.into(),
+                    filter.angle.to_degrees().into(),
+                    filter.color.to_rgb().into(),
+                    alpha(&filter.color).into(),
+                    filter.blur_x.into(),
+                    filter.blur_y.into(),
+                    filter.strength.into(),
+                    filter.num_passes.into(),
+                    filter.is_inner.into(),
+                    filter.is_knockout.into(),
+                ],
+            )
+        }
+        swf::Filter::BevelFilter(filter) => {
+            let constructor = prototypes.bevel_filter_constructor;
+            let type_: &str = bevel_type(filter.is_inner, filter.is_on_top).into();
+            constructor.construct(
+                activation,
+                &[
+                    filter.distance.into(),
+                    filter.angle.to_degrees().into(),
+                    filter.highlight_color.to_rgb().into(),
+                    alpha(&filter.highlight_color).into(),
+                    filter.shadow_color.to_rgb().into(),
+                    alpha(&filter.shadow_color).into(),
+                    filter.blur_x.into(),
+                    filter.blur_y.into(),
+                    filter.strength.into(),
+                    filter.num_passes.into(),
+                    type_.into(),
+                    filter.is_knockout.into(),
+                ],
+            )
+        }
+        swf::Filter::GradientGlowFilter(filter) => {
+            let constructor = prototypes.gradient_glow_filter_constructor;
+            let type_: &str = bevel_type(filter.is_inner, filter.is_on_top).into();
+            let (colors, alphas, ratios) = gradient_arrays(activation, &filter.colors);
+            constructor.construct(
+                activation,
+                &[
+                    filter.distance.into(),
+                    filter.angle.to_degrees().into(),
+                    colors.into(),
+                    alphas.into(),
+                    ratios.into(),
+                    filter.blur_x.into(),
+                    filter.blur_y.into(),
+                    filter.strength.into(),
+                    filter.num_passes.into(),
+                    type_.into(),
+                    filter.is_knockout.into(),
+                ],
+            )
+        }
+        swf::Filter::GradientBevelFilter(filter) => {
+            let constructor = prototypes.gradient_bevel_filter_constructor;
+            let type_: &str = bevel_type(filter.is_inner, filter.is_on_top).into();
+            let (colors, alphas, ratios) = gradient_arrays(activation, &filter.colors);
+            constructor.construct(
+                activation,
+                &[
+                    filter.distance.into(),
+                    filter.angle.to_degrees().into(),
+                    colors.into(),
+                    alphas.into(),
+                    ratios.into(),
+                    filter.blur_x.into(),
+                    filter.blur_y.into(),
+                    filter.strength.into(),
+                    filter.num_passes.into(),
+                    type_.into(),
+                    filter.is_knockout.into(),
+                ],
+            )
+        }
+        swf::Filter::ColorMatrixFilter(filter) => {
+            let constructor = prototypes.color_matrix_filter_constructor;
+            let matrix = array(activation, filter.matrix.iter().map(|v| (*v).into()));
+            constructor.construct(activation, &[matrix.into()])
+        }
+        swf::Filter::ConvolutionFilter(filter) => {
+            let constructor = prototypes.convolution_filter_constructor;
+            let matrix = array(activation, filter.matrix.iter().map(|v| (*v).into()));
+            constructor.construct(
+                activation,
+                &[
+                    filter.num_matrix_cols.into(),
+                    filter.num_matrix_rows.into(),
+                    matrix.into(),
+                    filter.divisor.into(),
+                    filter.bias.into(),
+                    filter.is_preserve_alpha.into(),
+                    filter.is_clamped.into(),
+                    filter.default_color.to_rgb().into(),
+                    alpha(&filter.default_color).into(),
+                ],
+            )
+        }
+    }
+}
+
+fn num_passes(quality: i32) -> u8 {
+    quality.max(0).min(15) as u8
+}
+
+fn color(rgb: u32, alpha: f64) -> swf::Color {
+    swf::Color::from_rgb(rgb, (alpha.max(0.0).min(1.0) * 255.0).round() as u8)
+}
+
+fn alpha(color: &swf::Color) -> f64 {
+    f64::from(color.a) / 255.0
+}
+
+/// Returns the `is_inner` and `is_on_top` flags of a bevel type.
+fn bevel_flags(type_: BevelFilterType) -> (bool, bool) {
+    match type_ {
+        BevelFilterType::Inner => (true, false),
+        BevelFilterType::Outer => (false, false),
+        BevelFilterType::Full => (false, true),
+    }
+}
+
+fn bevel_type(is_inner: bool, is_on_top: bool) -> BevelFilterType {
+    if is_on_top {
+        BevelFilterType::Full
+    } else if is_inner {
+        BevelFilterType::Inner
+    } else {
+        BevelFilterType::Outer
+    }
+}
+
+fn gradient(colors: &[u32], alphas: &[f64], ratios: &[u8]) -> Vec<swf::GradientRecord> {
+    colors
+        .iter()
+        .zip(alphas)
+        .zip(ratios)
+        .map(|((rgb, alpha), ratio)| swf::GradientRecord {
+            ratio: *ratio,
+            color: color(*rgb, *alpha),
+        })
+        .collect()
+}
+
+/// Returns the `colors`, `alphas` and `ratios` arrays of a gradient filter.
+fn gradient_arrays<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    records: &[swf::GradientRecord],
+) -> (Object<'gc>, Object<'gc>, Object<'gc>) {
+    let colors = array(activation, records.iter().map(|r| r.color.to_rgb().into()));
+    let alphas = array(activation, records.iter().map(|r| alpha(&r.color).into()));
+    let ratios = array(activation, records.iter().map(|r| r.ratio.into()));
+    (colors, alphas, ratios)
+}
+
+fn array<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    values: impl Iterator<Item = Value<'gc>>,
+) -> Object<'gc> {
+    let array = ScriptObject::array(
+        activation.context.gc_context,
+        Some(activation.context.avm1.prototypes.array),
+    );
+    for (index, value) in values.enumerate() {
+        array.set_array_element(index, value, activation.context.gc_context);
+    }
+    array.into()
+}
+
 pub fn create_proto<'gc>(
     gc_context: MutationContext<'gc, '_>,
     proto: Object<'gc>,
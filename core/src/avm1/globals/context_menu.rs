@@ -166,7 +166,10 @@ pub fn make_context_menu_state<'gc>(
     let mut result = context_menu::ContextMenuState::new();
 
     let root_mc = activation.context.stage.root_clip().as_movie_clip();
-    let builtin_items = {
+    // With `Stage.showMenu = false`, only the custom items are shown.
+    let builtin_items = if !activation.context.stage.show_menu() {
+        vec![]
+    } else {
         let is_multiframe_movie = root_mc.map(|mc| mc.total_frames() > 1).unwrap_or(false);
         let mut names = if is_multiframe_movie {
             vec![
@@ -4,7 +4,10 @@ use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::object::date_object::DateObject;
 use crate::avm1::property::Attribute;
 use crate::avm1::{AvmString, Object, TObject, Value};
-use chrono::{DateTime, Datelike, Duration, FixedOffset, LocalResult, TimeZone, Timelike, Utc};
+use crate::backend::locale::LocalTimeZone;
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, Offset, TimeZone, Timelike, Utc,
+};
 use gc_arena::{Collect, MutationContext};
 use num_traits::ToPrimitive;
 
@@ -16,7 +19,7 @@ macro_rules! implement_local_getters {
                 |activation: &mut Activation<'_, 'gc, '_>, this, _args| -> Result<Value<'gc>, Error<'gc>> {
                     if let Some(this) = this.as_date_object() {
                         if let Some(date) = this.date_time() {
-                            let local = date.with_timezone(&activation.context.locale.get_timezone());
+                            let local = date.with_timezone(&activation.context.timezone());
                             Ok($fn(&local).into())
                         } else {
                             Ok(f64::NAN.into())
@@ -521,12 +524,13 @@ impl<'builder, 'activation_a, 'gc, 'gc_context, T: TimeZone>
                 + Duration::seconds(second)
                 + Duration::milliseconds(millisecond);
 
-            if let LocalResult::Single(Some(result)) = current
-                .timezone()
-                .ymd_opt(year, (month + 1) as u32, 1)
-                .and_hms_opt(0, 0, 0)
-                .map(|date| date.checked_add_signed(duration))
-            {
+            // The local time is calculated first, so that any daylight saving
+            // time is looked up at the final time rather than at the start of
+            // the month.
+            let local = NaiveDate::from_ymd_opt(year, (month + 1) as u32, 1)?
+                .and_hms(0, 0, 0)
+                .checked_add_signed(duration)?;
+            if let LocalResult::Single(result) = self.timezone.from_local_datetime(&local) {
                 return Some(result.with_timezone(&Utc));
             }
         }
@@ -559,12 +563,12 @@ fn constructor<'gc>(
     let timestamp = args.get(0).unwrap_or(&Value::Undefined);
     if timestamp != &Value::Undefined {
         if args.len() > 1 {
-            let timezone = activation.context.locale.get_timezone();
+            let timezone = activation.context.timezone();
 
             // We need a starting value to adjust from.
             this.set_date_time(
                 activation.context.gc_context,
-                Some(timezone.ymd(0, 1, 1).and_hms(0, 0, 0).into()),
+                Some(timezone.ymd(0, 1, 1).and_hms(0, 0, 0).with_timezone(&Utc)),
             );
 
             DateAdjustment::new(activation, &timezone)
@@ -592,7 +596,7 @@ fn constructor<'gc>(
     } else {
         this.set_date_time(
             activation.context.gc_context,
-            Some(activation.context.locale.get_current_date_time()),
+            Some(activation.context.current_date_time()),
         )
     }
 
@@ -637,7 +641,7 @@ fn to_string<'gc>(
     let date = this.date_time();
 
     if let Some(date) = date {
-        let local = date.with_timezone(&activation.context.locale.get_timezone());
+        let local = date.with_timezone(&activation.context.timezone());
         Ok(AvmString::new(
             activation.context.gc_context,
             local.format("%a %b %-d %T GMT%z %-Y").to_string(),
@@ -654,12 +658,12 @@ fn get_timezone_offset<'gc>(
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     let date = if let Some(date) = this.date_time() {
-        date.with_timezone(&activation.context.locale.get_timezone())
+        date.with_timezone(&activation.context.timezone())
     } else {
         return Ok(f64::NAN.into());
     };
 
-    let seconds = date.offset().utc_minus_local() as f32;
+    let seconds = date.offset().fix().utc_minus_local() as f32;
     let minutes = seconds / 60.0;
     Ok(minutes.into())
 }
@@ -673,7 +677,7 @@ fn set_date<'gc>(
         this.set_date_time(activation.context.gc_context, None);
         Ok(f64::NAN.into())
     } else {
-        let timezone = activation.context.locale.get_timezone();
+        let timezone = activation.context.timezone();
         let timestamp = DateAdjustment::new(activation, &timezone)
             .day(args.get(0))?
             .apply(this);
@@ -702,7 +706,7 @@ fn set_year<'gc>(
     this: DateObject<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let timezone = activation.context.locale.get_timezone();
+    let timezone = activation.context.timezone();
     let timestamp = DateAdjustment::new(activation, &timezone)
         .year(args.get(0))?
         .adjust_year(|year| {
@@ -721,7 +725,7 @@ fn set_hours<'gc>(
     this: DateObject<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let timezone = activation.context.locale.get_timezone();
+    let timezone = activation.context.timezone();
     let timestamp = DateAdjustment::new(activation, &timezone)
         .hour(args.get(0))?
         .apply(this);
@@ -747,7 +751,7 @@ fn set_milliseconds<'gc>(
     this: DateObject<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let timezone = activation.context.locale.get_timezone();
+    let timezone = activation.context.timezone();
     let timestamp = DateAdjustment::new(activation, &timezone)
         .millisecond(args.get(0))?
         .apply(this);
@@ -770,7 +774,7 @@ fn set_minutes<'gc>(
     this: DateObject<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let timezone = activation.context.locale.get_timezone();
+    let timezone = activation.context.timezone();
     let timestamp = DateAdjustment::new(activation, &timezone)
         .minute_or(args.get(0), -2147483648.0)?
         .apply(this);
@@ -795,7 +799,7 @@ fn set_month<'gc>(
     this: DateObject<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let timezone = activation.context.locale.get_timezone();
+    let timezone = activation.context.timezone();
     let timestamp = DateAdjustment::new(activation, &timezone)
         .month_or(args.get(0), 0.0)?
         .day_opt(args.get(1))?
@@ -820,7 +824,7 @@ fn set_seconds<'gc>(
     this: DateObject<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let timezone = activation.context.locale.get_timezone();
+    let timezone = activation.context.timezone();
     let timestamp = DateAdjustment::new(activation, &timezone)
         .second(args.get(0))?
         .apply(this);
@@ -864,7 +868,7 @@ fn set_full_year<'gc>(
     this: DateObject<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let timezone = activation.context.locale.get_timezone();
+    let timezone = activation.context.timezone();
     let timestamp = DateAdjustment::new(activation, &timezone)
         .year(args.get(0))?
         .month_opt(args.get(1))?
@@ -917,14 +921,14 @@ pub fn create_proto<'gc>(
         object,
         Some(fn_proto),
         "getDate" => Datelike::day,
-        "getDay" => |date: &DateTime<FixedOffset>| date.weekday().num_days_from_sunday(),
+        "getDay" => |date: &DateTime<LocalTimeZone>| date.weekday().num_days_from_sunday(),
         "getFullYear" => Datelike::year,
         "getHours" => Timelike::hour,
         "getMilliseconds" => DateTime::timestamp_subsec_millis,
         "getMinutes" => Timelike::minute,
         "getMonth" => Datelike::month0,
         "getSeconds" => Timelike::second,
-        "getYear" => |date: &DateTime<FixedOffset>| date.year() - 1900
+        "getYear" => |date: &DateTime<LocalTimeZone>| date.year() - 1900
     );
 
     implement_utc_getters!(
@@ -3,10 +3,12 @@
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::{Executable, FunctionObject};
+use crate::avm1::globals::bitmap_filter::{avm1_to_filter, filter_to_avm1};
 use crate::avm1::property::Attribute;
 use crate::avm1::{AvmString, Object, ScriptObject, TObject, Value};
 use crate::display_object::{DisplayObject, Lists, TDisplayObject, TDisplayObjectContainer};
 use gc_arena::MutationContext;
+use swf::{BlendMode, Color};
 
 /// Depths used/returned by ActionScript are offset by this amount from depths used inside the SWF/by the VM.
 /// The depth of objects placed on the timeline in the Flash IDE start from 0 in the SWF,
@@ -41,6 +43,51 @@ macro_rules! with_display_object {
     }};
 }
 
+macro_rules! with_display_object_props {
+    (
+        $obj:ident, $gc:ident, $fn_proto:ident, $attributes:expr,
+        $($name:literal => [$get:ident, $set:ident],)*
+    ) => {
+        $(
+            $obj.add_property(
+                $gc,
+                $name,
+                FunctionObject::function(
+                    $gc,
+                    Executable::Native(
+                        |activation: &mut Activation<'_, 'gc, '_>, this, _args| -> Result<Value<'gc>, Error<'gc>> {
+                            if let Some(display_object) = this.as_display_object() {
+                                return $get(display_object, activation);
+                            }
+                            Ok(Value::Undefined)
+                        } as crate::avm1::function::NativeFunction<'gc>
+                    ),
+                    Some($fn_proto),
+                    $fn_proto
+                ),
+                Some(FunctionObject::function(
+                    $gc,
+                    Executable::Native(
+                        |activation: &mut Activation<'_, 'gc, '_>, this, args| -> Result<Value<'gc>, Error<'gc>> {
+                            if let Some(display_object) = this.as_display_object() {
+                                let value = args
+                                    .get(0)
+                                    .unwrap_or(&Value::Undefined)
+                                    .clone();
+                                $set(display_object, activation, value)?;
+                            }
+                            Ok(Value::Undefined)
+                        } as crate::avm1::function::NativeFunction<'gc>
+                    ),
+                    Some($fn_proto),
+                    $fn_proto
+                )),
+                Attribute::DONT_DELETE | Attribute::DONT_ENUM | $attributes,
+            );
+        )*
+    };
+}
+
 /// Add common display object prototype methods to the given prototype.
 pub fn define_display_object_proto<'gc>(
     gc_context: MutationContext<'gc, '_>,
@@ -110,6 +157,15 @@ pub fn define_display_object_proto<'gc>(
         )),
         Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
     );
+
+    // These properties were added in Flash Player 8.
+    with_display_object_props!(
+        object, gc_context, fn_proto, Attribute::VERSION_8,
+        "filters" => [filters, set_filters],
+        "blendMode" => [blend_mode, set_blend_mode],
+        "cacheAsBitmap" => [cache_as_bitmap, set_cache_as_bitmap],
+        "opaqueBackground" => [opaque_background, set_opaque_background],
+    );
 }
 
 pub fn get_parent<'gc>(
@@ -203,6 +259,153 @@ pub fn overwrite_parent<'gc>(
     Ok(Value::Undefined)
 }
 
+fn filters<'gc>(
+    this: DisplayObject<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    // The filters are copied, so that changing them has no effect until they
+    // are set again.
+    let filters = this.filters().to_vec();
+    let array = ScriptObject::array(
+        activation.context.gc_context,
+        Some(activation.context.avm1.prototypes.array),
+    );
+    for (index, filter) in filters.iter().enumerate() {
+        let filter = filter_to_avm1(activation, filter)?;
+        array.set_array_element(index, filter, activation.context.gc_context);
+    }
+    Ok(array.into())
+}
+
+fn set_filters<'gc>(
+    this: DisplayObject<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    if let Value::Object(array) = value {
+        let filters = (0..array.length())
+            .filter_map(|index| match array.array_element(index) {
+                Value::Object(filter) => avm1_to_filter(filter),
+                _ => None,
+            })
+            .collect();
+        this.set_filters(activation.context.gc_context, filters);
+    }
+    Ok(())
+}
+
+fn blend_mode<'gc>(
+    this: DisplayObject<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = match this.blend_mode() {
+        BlendMode::Normal => "normal",
+        BlendMode::Layer => "layer",
+        BlendMode::Multiply => "multiply",
+        BlendMode::Screen => "screen",
+        BlendMode::Lighten => "lighten",
+        BlendMode::Darken => "darken",
+        BlendMode::Difference => "difference",
+        BlendMode::Add => "add",
+        BlendMode::Subtract => "subtract",
+        BlendMode::Invert => "invert",
+        BlendMode::Alpha => "alpha",
+        BlendMode::Erase => "erase",
+        BlendMode::Overlay => "overlay",
+        BlendMode::HardLight => "hardlight",
+    };
+    Ok(name.into())
+}
+
+fn set_blend_mode<'gc>(
+    this: DisplayObject<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    // Blend modes can be set by name, or by their index in the list of names.
+    let blend_mode = match value {
+        Value::Number(index) => match index as i32 {
+            1 => Some(BlendMode::Normal),
+            2 => Some(BlendMode::Layer),
+            3 => Some(BlendMode::Multiply),
+            4 => Some(BlendMode::Screen),
+            5 => Some(BlendMode::Lighten),
+            6 => Some(BlendMode::Darken),
+            7 => Some(BlendMode::Difference),
+            8 => Some(BlendMode::Add),
+            9 => Some(BlendMode::Subtract),
+            10 => Some(BlendMode::Invert),
+            11 => Some(BlendMode::Alpha),
+            12 => Some(BlendMode::Erase),
+            13 => Some(BlendMode::Overlay),
+            14 => Some(BlendMode::HardLight),
+            _ => None,
+        },
+        Value::String(name) => match name.as_str() {
+            "normal" => Some(BlendMode::Normal),
+            "layer" => Some(BlendMode::Layer),
+            "multiply" => Some(BlendMode::Multiply),
+            "screen" => Some(BlendMode::Screen),
+            "lighten" => Some(BlendMode::Lighten),
+            "darken" => Some(BlendMode::Darken),
+            "difference" => Some(BlendMode::Difference),
+            "add" => Some(BlendMode::Add),
+            "subtract" => Some(BlendMode::Subtract),
+            "invert" => Some(BlendMode::Invert),
+            "alpha" => Some(BlendMode::Alpha),
+            "erase" => Some(BlendMode::Erase),
+            "overlay" => Some(BlendMode::Overlay),
+            "hardlight" => Some(BlendMode::HardLight),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    if let Some(blend_mode) = blend_mode {
+        this.set_blend_mode(activation.context.gc_context, blend_mode);
+    }
+    Ok(())
+}
+
+fn cache_as_bitmap<'gc>(
+    this: DisplayObject<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.is_bitmap_cached().into())
+}
+
+fn set_cache_as_bitmap<'gc>(
+    this: DisplayObject<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let cache_as_bitmap = value.as_bool(activation.swf_version());
+    this.set_bitmap_cached(activation.context.gc_context, cache_as_bitmap);
+    Ok(())
+}
+
+fn opaque_background<'gc>(
+    this: DisplayObject<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this
+        .opaque_background()
+        .map_or(Value::Undefined, |color| color.to_rgb().into()))
+}
+
+fn set_opaque_background<'gc>(
+    this: DisplayObject<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let color = match value {
+        Value::Undefined | Value::Null => None,
+        value => Some(Color::from_rgb(value.coerce_to_u32(activation)?, 255)),
+    };
+    this.set_opaque_background(activation.context.gc_context, color);
+    Ok(())
+}
+
 pub fn remove_display_object<'gc>(
     this: DisplayObject<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
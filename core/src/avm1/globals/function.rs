@@ -36,29 +36,10 @@ pub fn call<'gc>(
     func: Object<'gc>,
     myargs: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let this = match myargs.get(0) {
-        Some(Value::Undefined) | Some(Value::Null) | None => activation.context.avm1.globals,
-        Some(this_val) => this_val.coerce_to_object(activation),
-    };
-    let empty = [];
-    let args = match myargs.len() {
-        0 => &empty,
-        1 => &empty,
-        _ => &myargs[1..],
-    };
+    let this = this_argument(activation, myargs.get(0));
+    let args = myargs.get(1..).unwrap_or_default();
 
-    match func.as_executable() {
-        Some(exec) => exec.exec(
-            "[Anonymous]",
-            activation,
-            this,
-            None,
-            args,
-            ExecutionReason::FunctionCall,
-            func,
-        ),
-        _ => Ok(Value::Undefined),
-    }
+    call_with_this(activation, func, this, args)
 }
 
 /// Implements `Function.prototype.apply`
@@ -67,31 +48,62 @@ pub fn apply<'gc>(
     func: Object<'gc>,
     myargs: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let this = match myargs.get(0) {
-        Some(Value::Undefined) | Some(Value::Null) | None => activation.context.avm1.globals,
-        Some(this_val) => this_val.coerce_to_object(activation),
-    };
-    let args_object = myargs.get(1).cloned().unwrap_or(Value::Undefined);
-    let length = match args_object {
-        Value::Object(a) => a.get("length", activation)?.coerce_to_f64(activation)? as usize,
-        _ => 0,
+    let this = this_argument(activation, myargs.get(0));
+    let args = match myargs.get(1) {
+        Some(Value::Object(args_object)) => {
+            // Any object with a `length` works here, such as `arguments`.
+            let length = args_object
+                .get("length", activation)?
+                .coerce_to_f64(activation)?;
+            let length = if length.is_finite() && length > 0.0 {
+                length as usize
+            } else {
+                0
+            };
+
+            let mut args = Vec::new();
+            for i in 0..length {
+                args.push(args_object.get(&i.to_string(), activation)?);
+            }
+            args
+        }
+        _ => Vec::new(),
     };
 
-    let mut child_args = Vec::with_capacity(length);
-    while child_args.len() < length {
-        let args = args_object.coerce_to_object(activation);
-        let next_arg = args.get(&format!("{}", child_args.len()), activation)?;
+    call_with_this(activation, func, this, &args)
+}
 
-        child_args.push(next_arg);
+/// Resolve the `this` argument of `call` and `apply`.
+///
+/// An undefined or missing `this` calls the function on `_global`, and
+/// primitives are boxed.
+fn this_argument<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<&Value<'gc>>,
+) -> Object<'gc> {
+    match this {
+        Some(Value::Undefined) | Some(Value::Null) | None => activation.context.avm1.globals,
+        Some(this) => this.coerce_to_object(activation),
     }
+}
 
+/// Invoke a function with an explicit `this` and argument list.
+///
+/// The function is its own `arguments.callee`, and the caller is whichever
+/// function is currently running. Objects that aren't functions do nothing.
+fn call_with_this<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    func: Object<'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
     match func.as_executable() {
         Some(exec) => exec.exec(
             "[Anonymous]",
             activation,
             this,
             None,
-            &child_args,
+            args,
             ExecutionReason::FunctionCall,
             func,
         ),
@@ -250,7 +250,9 @@ fn send<'gc>(
     }
 
     if let Some(window) = window {
-        activation.context.navigator.navigate_to_url(
+        let requester = activation.base_clip();
+        activation.context.navigate_to_url(
+            requester,
             url.to_string(),
             Some(window.to_string()),
             Some((method, form_values)),
@@ -328,7 +330,10 @@ fn spawn_load_var_fetch<'gc>(
         (Cow::Borrowed(url.as_str()), RequestOptions::get())
     };
 
-    let fetch = activation.context.navigator.fetch(&url, request_options);
+    let requester = activation.base_clip();
+    let fetch = activation
+        .context
+        .fetch_data(requester, &url, request_options);
     let process = activation.context.load_manager.load_form_into_load_vars(
         activation.context.player.clone().unwrap(),
         loader_object,
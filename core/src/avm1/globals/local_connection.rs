@@ -0,0 +1,182 @@
+//! AVM1 LocalConnection object
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute;
+use crate::avm1::{AvmString, Object, ScriptObject, TObject, Value};
+use crate::avm_warn;
+use crate::context::ActionType;
+use crate::display_object::TDisplayObject;
+use crate::external::Value as ExternalValue;
+use crate::local_connection::{LocalConnectionMessage, LocalConnections};
+use gc_arena::MutationContext;
+
+/// The methods of `LocalConnection` itself, which can't be called with `send`.
+const RESERVED_METHODS: &[&str] = &[
+    "send",
+    "connect",
+    "close",
+    "domain",
+    "allowDomain",
+    "allowInsecureDomain",
+    "onStatus",
+];
+
+/// Implements `LocalConnection`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.into())
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "connect",
+        connect,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "send",
+        send,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "close",
+        close,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "domain",
+        domain,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+/// The domain of the movie that is running the current code.
+fn movie_domain(activation: &Activation<'_, '_, '_>) -> String {
+    let movie = activation.base_clip().movie();
+    LocalConnections::domain_for_url(movie.as_ref().and_then(|movie| movie.url()))
+}
+
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = match args.get(0) {
+        Some(Value::String(name)) if !name.is_empty() && !name.contains(':') => *name,
+        _ => return Ok(false.into()),
+    };
+
+    if activation.context.local_connections.is_connected(this) {
+        return Ok(false.into());
+    }
+
+    let domain = movie_domain(activation);
+    let name = LocalConnections::qualify_name(&name, &domain, false);
+    Ok(activation
+        .context
+        .local_connections
+        .connect(name, this, domain)
+        .into())
+}
+
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let (name, method_name) = match (args.get(0), args.get(1)) {
+        (Some(Value::String(name)), Some(Value::String(method_name)))
+            if !name.is_empty() && !method_name.is_empty() =>
+        {
+            (*name, *method_name)
+        }
+        _ => return Ok(false.into()),
+    };
+
+    if RESERVED_METHODS.contains(&method_name.as_str()) {
+        avm_warn!(
+            activation,
+            "LocalConnection.send: {} can't be called remotely",
+            method_name
+        );
+        return Ok(false.into());
+    }
+
+    let mut message_args = Vec::with_capacity(args.len().saturating_sub(2));
+    for arg in args.iter().skip(2) {
+        message_args.push(ExternalValue::from_avm1(activation, *arg)?);
+    }
+
+    let sender_domain = movie_domain(activation);
+    let message = LocalConnectionMessage {
+        connection_name: LocalConnections::qualify_name(&name, &sender_domain, true),
+        method_name: method_name.to_string(),
+        sender_domain,
+        args: message_args,
+    };
+    let delivered = LocalConnections::send(activation, message);
+
+    let info = ScriptObject::object(
+        activation.context.gc_context,
+        Some(activation.context.avm1.prototypes.object),
+    );
+    let level = if delivered { "status" } else { "error" };
+    info.define_value(
+        activation.context.gc_context,
+        "level",
+        level.into(),
+        Attribute::empty(),
+    );
+    activation.context.action_queue.queue_actions(
+        activation.base_clip(),
+        ActionType::Method {
+            object: this,
+            name: "onStatus",
+            args: vec![info.into()],
+        },
+        false,
+    );
+
+    Ok(true.into())
+}
+
+fn close<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    activation.context.local_connections.close(this);
+    Ok(Value::Undefined)
+}
+
+fn domain<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let domain = movie_domain(activation);
+    Ok(AvmString::new(activation.context.gc_context, domain).into())
+}
@@ -3,6 +3,7 @@ use crate::avm1::error::Error;
 use crate::avm1::object::Object;
 use crate::avm1::property::Attribute;
 use crate::avm1::{ScriptObject, TObject, Value};
+use crate::flash_math;
 use gc_arena::MutationContext;
 use rand::Rng;
 
@@ -33,12 +34,11 @@ fn atan2<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(y) = args.get(0) {
         if let Some(x) = args.get(1) {
-            return Ok(y
-                .coerce_to_f64(activation)?
-                .atan2(x.coerce_to_f64(activation)?)
-                .into());
+            let y = y.coerce_to_f64(activation)?;
+            let x = x.coerce_to_f64(activation)?;
+            return Ok(flash_math::atan2(y, x).into());
         } else {
-            return Ok(y.coerce_to_f64(activation)?.atan2(0.0).into());
+            return Ok(flash_math::atan2(y.coerce_to_f64(activation)?, 0.0).into());
         }
     }
     Ok(f64::NAN.into())
@@ -52,10 +52,7 @@ fn pow<'gc>(
     if let Some(y) = args.get(0) {
         if let Some(x) = args.get(1) {
             let x = x.coerce_to_f64(activation)?;
-            if x.is_nan() {
-                return Ok(f64::NAN.into());
-            }
-            return Ok(y.coerce_to_f64(activation)?.powf(x).into());
+            return Ok(flash_math::pow(y.coerce_to_f64(activation)?, x).into());
         }
     }
     Ok(f64::NAN.into())
@@ -68,10 +65,7 @@ fn round<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(x) = args.get(0) {
         let x = x.coerce_to_f64(activation)?;
-        // Note that Flash Math.round always rounds toward infinity,
-        // unlike Rust f32::round which rounds away from zero.
-        let ret = (x + 0.5).floor();
-        return Ok(ret.into());
+        return Ok(flash_math::round(x).into());
     }
     Ok(f64::NAN.into())
 }
@@ -406,7 +400,10 @@ mod tests {
             [Value::Undefined] => f64::NAN,
             ["5"] => f64::NAN,
             [1.0, 2.0] => 1.0,
-            [3.0, 2.0, 1.0] => 9.0
+            [3.0, 2.0, 1.0] => 9.0,
+            [1.0, f64::INFINITY] => f64::NAN,
+            [-1.0, f64::NEG_INFINITY] => f64::NAN,
+            [-2.0, 0.5] => f64::NAN
         },
         [5, 6] => {
             [1.0, Value::Null] => 1.0,
@@ -14,6 +14,7 @@ use crate::display_object::{
     Bitmap, DisplayObject, EditText, MovieClip, TDisplayObject, TDisplayObjectContainer,
 };
 use crate::ecma_conversions::f64_to_wrapping_i32;
+use crate::loader::LoadManager;
 use crate::prelude::*;
 use crate::shape_utils::DrawCommand;
 use crate::tag_utils::SwfSlice;
@@ -56,14 +57,17 @@ macro_rules! with_movie_clip {
 }
 
 macro_rules! with_movie_clip_props {
-    ($obj:ident, $gc:ident, $fn_proto:ident, $($name:literal => [$get:ident $(, $set:ident)*],)*) => {
+    (
+        $obj:ident, $gc:ident, $fn_proto:ident, $attributes:expr,
+        $($name:literal => [$get:ident $(, $set:ident)*],)*
+    ) => {
         $(
             $obj.add_property(
                 $gc,
                 $name,
                 with_movie_clip_props!(getter $gc, $fn_proto, $get),
                 with_movie_clip_props!(setter $gc, $fn_proto, $($set),*),
-                Attribute::DONT_DELETE | Attribute::DONT_ENUM,
+                Attribute::DONT_DELETE | Attribute::DONT_ENUM | $attributes,
             );
         )*
     };
@@ -220,7 +224,7 @@ pub fn create_proto<'gc>(
     );
 
     with_movie_clip_props!(
-        object, gc_context, fn_proto,
+        object, gc_context, fn_proto, Attribute::empty(),
         "transform" => [transform, set_transform],
         "enabled" => [enabled, set_enabled],
         "focusEnabled" => [focus_enabled, set_focus_enabled],
@@ -228,6 +232,12 @@ pub fn create_proto<'gc>(
         "useHandCursor" => [use_hand_cursor, set_use_hand_cursor],
     );
 
+    // This property was added in Flash Player 8.
+    with_movie_clip_props!(
+        object, gc_context, fn_proto, Attribute::VERSION_8,
+        "scale9Grid" => [scale_9_grid, set_scale_9_grid],
+    );
+
     object.into()
 }
 
@@ -600,11 +610,12 @@ fn attach_movie<'gc>(
         return Ok(Value::Undefined);
     }
 
+    let level0 = activation.context.stage.root_clip();
     if let Ok(new_clip) = activation
         .context
         .library
-        .library_for_movie(movie_clip.movie().unwrap())
-        .ok_or_else(|| "Movie is missing!".into())
+        .library_for_export(movie_clip.into(), level0, &export_name)
+        .ok_or_else(|| "Export is missing!".into())
         .and_then(|l| l.instantiate_by_export_name(&export_name, activation.context.gc_context))
     {
         // Set name and attach to parent.
@@ -631,7 +642,7 @@ fn attach_movie<'gc>(
 }
 
 fn create_empty_movie_clip<'gc>(
-    movie_clip: MovieClip<'gc>,
+    mut movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
@@ -658,6 +669,13 @@ fn create_empty_movie_clip<'gc>(
         .unwrap();
     let new_clip = MovieClip::new(SwfSlice::empty(swf_movie), activation.context.gc_context);
 
+    // Any child already at this depth is removed, and receives its unload
+    // events, before the new clip takes its place.
+    let prev_child = movie_clip.child_by_depth(depth);
+    if let Some(prev_child) = prev_child {
+        movie_clip.remove_child(&mut activation.context, prev_child, Lists::all());
+    }
+
     // Set name and attach to parent.
     new_clip.set_name(activation.context.gc_context, &new_instance_name);
     movie_clip.replace_at_depth(&mut activation.context, new_clip.into(), depth);
@@ -669,6 +687,12 @@ fn create_empty_movie_clip<'gc>(
         true,
     );
 
+    // Before SWF7, replacing an existing child returns `undefined` instead of
+    // the new clip.
+    if prev_child.is_some() && activation.swf_version() < 7 {
+        return Ok(Value::Undefined);
+    }
+
     Ok(new_clip.object())
 }
 
@@ -775,13 +799,23 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
         return Ok(Value::Undefined);
     }
 
-    if let Ok(new_clip) = activation
-        .context
-        .library
-        .library_for_movie(movie_clip.movie().unwrap())
-        .ok_or_else(|| "Movie is missing!".into())
-        .and_then(|l| l.instantiate_by_id(movie_clip.id(), activation.context.gc_context))
-    {
+    // The duplicate is a fresh instance of the clip's symbol, so it starts at
+    // frame 1 no matter which frame the original is on. Clips without a symbol,
+    // such as ones from `createEmptyMovieClip` or `loadMovie`, are duplicated
+    // as empty clips.
+    let movie = movie_clip.movie().unwrap();
+    let new_clip = if movie_clip.id() != 0 {
+        activation
+            .context
+            .library
+            .library_for_movie(movie)
+            .ok_or_else(|| "Movie is missing!".into())
+            .and_then(|l| l.instantiate_by_id(movie_clip.id(), activation.context.gc_context))
+    } else {
+        Ok(MovieClip::new(SwfSlice::empty(movie), activation.context.gc_context).into())
+    };
+
+    if let Ok(new_clip) = new_clip {
         // Set name and attach to parent.
         new_clip.set_name(activation.context.gc_context, &new_instance_name);
         parent.replace_at_depth(&mut activation.context, new_clip, depth);
@@ -792,10 +826,21 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
             activation.context.gc_context,
             &*movie_clip.color_transform(),
         );
+
+        // The clip event handlers are copied, and run again for the duplicate,
+        // as are any shapes drawn with the drawing API.
         new_clip.as_movie_clip().unwrap().set_clip_actions(
             activation.context.gc_context,
             movie_clip.clip_actions().to_vec(),
         );
+        let drawing = movie_clip
+            .as_drawing(activation.context.gc_context)
+            .map(|drawing| drawing.clone());
+        if let (Some(drawing), Some(mut new_drawing)) =
+            (drawing, new_clip.as_drawing(activation.context.gc_context))
+        {
+            *new_drawing = drawing;
+        }
         // TODO: Any other properties we should copy...?
         // Definitely not ScriptObject properties.
 
@@ -821,9 +866,18 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
 
 fn get_bytes_loaded<'gc>(
     movie_clip: MovieClip<'gc>,
-    _activation: &mut Activation<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    // A clip that is the target of a pending `loadMovie` reports the progress of that load.
+    if let Some(progress) = activation
+        .context
+        .load_manager
+        .movie_load_progress(movie_clip.into())
+    {
+        return Ok(progress.bytes_loaded().into());
+    }
+
     let bytes_loaded = if movie_clip.is_swf() {
         movie_clip
             .movie()
@@ -837,9 +891,17 @@ fn get_bytes_loaded<'gc>(
 
 fn get_bytes_total<'gc>(
     movie_clip: MovieClip<'gc>,
-    _activation: &mut Activation<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(progress) = activation
+        .context
+        .load_manager
+        .movie_load_progress(movie_clip.into())
+    {
+        return Ok(progress.bytes_total().into());
+    }
+
     // For a loaded SWF, returns the uncompressed size of the SWF.
     // Otherwise, returns the size of the tag list in the clip's DefineSprite tag.
     let bytes_total = if movie_clip.is_swf() {
@@ -894,9 +956,11 @@ fn get_next_highest_depth<'gc>(
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if activation.swf_version() >= 7 {
+        // Only depths that scripts can attach clips to count. Timeline objects
+        // are below this window, and objects moved past it are ignored.
         let depth = std::cmp::max(
             movie_clip
-                .highest_depth(Depth::MAX)
+                .highest_depth(AVM_MAX_DEPTH + 1)
                 .unwrap_or(0)
                 .wrapping_sub(AVM_DEPTH_BIAS - 1),
             0,
@@ -912,6 +976,11 @@ fn goto_and_play<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    if let [scene, frame, ..] = args {
+        if let Some(scene) = named_scene(movie_clip, activation, scene)? {
+            return goto_scene_frame(movie_clip, activation, scene, frame, false);
+        }
+    }
     goto_frame(movie_clip, activation, args, false, 0)
 }
 
@@ -920,9 +989,69 @@ fn goto_and_stop<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    if let [scene, frame, ..] = args {
+        if let Some(scene) = named_scene(movie_clip, activation, scene)? {
+            return goto_scene_frame(movie_clip, activation, scene, frame, true);
+        }
+    }
     goto_frame(movie_clip, activation, args, true, 0)
 }
 
+/// The name and first frame of the scene that the first argument of
+/// `gotoAndPlay` or `gotoAndStop` names, if it names one of the clip's scenes.
+///
+/// Objects are never taken as scene names, so that they are only coerced once
+/// if the call falls back to `goto_frame`.
+fn named_scene<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    scene: &Value<'gc>,
+) -> Result<Option<(AvmString<'gc>, u16)>, Error<'gc>> {
+    if let Value::Object(_) = scene {
+        return Ok(None);
+    }
+    let scene = scene.coerce_to_string(activation)?;
+    Ok(movie_clip
+        .scene_label_to_number(&scene)
+        .map(|start| (scene, start)))
+}
+
+/// Implements the `gotoAndPlay(scene, frame)` and `gotoAndStop(scene, frame)`
+/// overloads, where the frame number or label is looked up within the scene.
+fn goto_scene_frame<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    (scene, scene_start): (AvmString<'gc>, u16),
+    frame: &Value<'gc>,
+    stop: bool,
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Frame numbers count from the start of the scene; gotoing <= 0 has no effect.
+    let scene_frame = |frame: i32| {
+        if frame > 0 {
+            let frame = frame.saturating_add(i32::from(scene_start) - 1);
+            Some(frame.min(i32::from(u16::MAX)) as u16)
+        } else {
+            None
+        }
+    };
+
+    let frame = match frame {
+        Value::Number(n) if n.fract() == 0.0 => scene_frame(f64_to_wrapping_i32(*n)),
+        frame => {
+            let frame = frame.coerce_to_string(activation)?;
+            match frame.parse().map(f64_to_wrapping_i32) {
+                Ok(frame) => scene_frame(frame),
+                Err(_) => movie_clip.frame_label_to_number_in_scene(&frame, &scene),
+            }
+        }
+    };
+
+    if let Some(frame) = frame {
+        movie_clip.goto_frame(&mut activation.context, frame, stop);
+    }
+    Ok(Value::Undefined)
+}
+
 pub fn goto_frame<'gc>(
     movie_clip: MovieClip<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
@@ -1238,10 +1367,10 @@ pub fn get_url<'gc>(
         };
         let vars_method = method.map(|m| (m, activation.locals_into_form_values()));
 
+        let requester = activation.base_clip();
         activation
             .context
-            .navigator
-            .navigate_to_url(url.to_string(), window, vars_method);
+            .navigate_to_url(requester, url.to_string(), window, vars_method);
     }
 
     Ok(Value::Undefined)
@@ -1290,12 +1419,13 @@ fn load_movie<'gc>(
     let method = args.get(1).cloned().unwrap_or(Value::Undefined);
     let method = NavigationMethod::from_method_str(&method.coerce_to_string(activation)?);
     let (url, opts) = activation.locals_into_request_options(Cow::Borrowed(&url), method);
-    let fetch = activation.context.navigator.fetch(&url, opts);
-    let process = activation.context.load_manager.load_movie_into_clip(
-        activation.context.player.clone().unwrap(),
+    let requester = activation.base_clip();
+    let process = LoadManager::load_movie_into_clip(
+        &mut activation.context,
         DisplayObject::MovieClip(target),
-        fetch,
-        url.to_string(),
+        requester,
+        &url,
+        opts,
         None,
         None,
     );
@@ -1315,7 +1445,8 @@ fn load_variables<'gc>(
     let method = args.get(1).cloned().unwrap_or(Value::Undefined);
     let method = NavigationMethod::from_method_str(&method.coerce_to_string(activation)?);
     let (url, opts) = activation.locals_into_request_options(Cow::Borrowed(&url), method);
-    let fetch = activation.context.navigator.fetch(&url, opts);
+    let requester = activation.base_clip();
+    let fetch = activation.context.fetch_data(requester, &url, opts);
     let target = target.object().coerce_to_object(activation);
     let process = activation.context.load_manager.load_form_into_object(
         activation.context.player.clone().unwrap(),
@@ -1333,6 +1464,12 @@ fn unload_movie<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    // Unloading a level removes it, the same as `unloadMovieNum`.
+    if let Some(stage) = target.parent().and_then(|parent| parent.as_stage()) {
+        stage.unload_level(&mut activation.context, target.depth());
+        return Ok(Value::Undefined);
+    }
+
     target.unload(&mut activation.context);
     target.replace_with_movie(activation.context.gc_context, None);
 
@@ -1427,3 +1564,53 @@ fn set_use_hand_cursor<'gc>(
     this.set_use_hand_cursor(&mut activation.context, use_hand_cursor);
     Ok(())
 }
+
+fn scale_9_grid<'gc>(
+    this: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let grid = this.scaling_grid();
+    if !grid.valid {
+        return Ok(Value::Undefined);
+    }
+
+    let args = [
+        grid.x_min.to_pixels().into(),
+        grid.y_min.to_pixels().into(),
+        grid.width().to_pixels().into(),
+        grid.height().to_pixels().into(),
+    ];
+    let constructor = activation.context.avm1.prototypes.rectangle_constructor;
+    let rectangle = constructor.construct(activation, &args)?;
+    Ok(rectangle)
+}
+
+fn set_scale_9_grid<'gc>(
+    this: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let grid = match value {
+        Value::Undefined | Value::Null => BoundingBox::default(),
+        value => {
+            let rectangle = value.coerce_to_object(activation);
+            let x = rectangle.get("x", activation)?.coerce_to_f64(activation)?;
+            let y = rectangle.get("y", activation)?.coerce_to_f64(activation)?;
+            let width = rectangle
+                .get("width", activation)?
+                .coerce_to_f64(activation)?;
+            let height = rectangle
+                .get("height", activation)?
+                .coerce_to_f64(activation)?;
+            BoundingBox {
+                x_min: Twips::from_pixels(x),
+                y_min: Twips::from_pixels(y),
+                x_max: Twips::from_pixels(x + width),
+                y_max: Twips::from_pixels(y + height),
+                valid: true,
+            }
+        }
+    };
+    this.set_scaling_grid(activation.context.gc_context, grid);
+    Ok(())
+}
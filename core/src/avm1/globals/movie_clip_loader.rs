@@ -9,6 +9,7 @@ use crate::avm1::property::Attribute;
 use crate::avm1::{Object, Value};
 use crate::backend::navigator::RequestOptions;
 use crate::display_object::{DisplayObject, TDisplayObject};
+use crate::loader::LoadManager;
 use gc_arena::MutationContext;
 
 pub fn constructor<'gc>(
@@ -45,15 +46,13 @@ pub fn load_clip<'gc>(
             .as_display_object()
             .and_then(|dobj| dobj.as_movie_clip())
         {
-            let fetch = activation
-                .context
-                .navigator
-                .fetch(&url, RequestOptions::get());
-            let process = activation.context.load_manager.load_movie_into_clip(
-                activation.context.player.clone().unwrap(),
+            let requester = activation.base_clip();
+            let process = LoadManager::load_movie_into_clip(
+                &mut activation.context,
                 DisplayObject::MovieClip(movieclip),
-                fetch,
-                url.to_string(),
+                requester,
+                &url,
+                RequestOptions::get(),
                 None,
                 Some(this),
             );
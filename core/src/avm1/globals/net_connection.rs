@@ -0,0 +1,109 @@
+//! AVM1 NetConnection object
+//! TODO: RTMP connections
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute;
+use crate::avm1::{Object, ScriptObject, TObject, Value};
+use crate::avm_warn;
+use crate::context::ActionType;
+use gc_arena::MutationContext;
+
+/// Implements `NetConnection`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    this.define_value(
+        activation.context.gc_context,
+        "isConnected",
+        false.into(),
+        Attribute::DONT_ENUM,
+    );
+
+    Ok(this.into())
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "connect",
+        connect,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "close",
+        close,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Only "connecting" to `null` is supported, which is used for progressive
+    // downloads of FLV files over HTTP.
+    let connected = matches!(args.get(0), Some(Value::Null));
+    this.set("isConnected", connected.into(), activation)?;
+
+    if !connected {
+        avm_warn!(
+            activation,
+            "NetConnection.connect: Connecting to a server is not yet supported"
+        );
+
+        let info = ScriptObject::object(
+            activation.context.gc_context,
+            Some(activation.context.avm1.prototypes.object),
+        );
+        info.define_value(
+            activation.context.gc_context,
+            "code",
+            "NetConnection.Connect.Failed".into(),
+            Attribute::empty(),
+        );
+        info.define_value(
+            activation.context.gc_context,
+            "level",
+            "error".into(),
+            Attribute::empty(),
+        );
+
+        activation.context.action_queue.queue_actions(
+            activation.context.stage.root_clip(),
+            ActionType::Method {
+                object: this,
+                name: "onStatus",
+                args: vec![Value::Object(info.into())],
+            },
+            false,
+        );
+    }
+
+    Ok(connected.into())
+}
+
+fn close<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    this.set("isConnected", false.into(), activation)?;
+
+    Ok(Value::Undefined)
+}
@@ -0,0 +1,280 @@
+//! AVM1 NetStream object
+//! TODO: Buffering, RTMP streams, attachAudio
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::function::{Executable, FunctionObject};
+use crate::avm1::object::net_stream_object::NetStreamObject;
+use crate::avm1::property::Attribute;
+use crate::avm1::{AvmString, Object, TObject, Value};
+use crate::avm_warn;
+use crate::flv::ScriptDataValue;
+use crate::streams::NetStream;
+use gc_arena::MutationContext;
+
+/// Implements `NetStream`
+pub fn constructor<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // The `NetConnection` passed in is only needed for RTMP, which we don't
+    // support.
+    this.define_value(
+        activation.context.gc_context,
+        "bufferTime",
+        0.1.into(),
+        Attribute::DONT_ENUM,
+    );
+
+    Ok(this.into())
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let object = NetStreamObject::empty_net_stream(gc_context, Some(proto));
+
+    for (name, getter) in [
+        ("time", time as _),
+        ("bytesLoaded", bytes_loaded as _),
+        ("bytesTotal", bytes_total as _),
+    ]
+    .iter()
+    {
+        object.add_property(
+            gc_context,
+            name,
+            FunctionObject::function(
+                gc_context,
+                Executable::Native(*getter),
+                Some(fn_proto),
+                fn_proto,
+            ),
+            None,
+            Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        );
+    }
+
+    object.as_script_object().unwrap().force_set_function(
+        "close",
+        close,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.as_script_object().unwrap().force_set_function(
+        "pause",
+        pause,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.as_script_object().unwrap().force_set_function(
+        "play",
+        play,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.as_script_object().unwrap().force_set_function(
+        "seek",
+        seek,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.as_script_object().unwrap().force_set_function(
+        "setBufferTime",
+        set_buffer_time,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+fn net_stream<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    method: &str,
+) -> Option<NetStream<'gc>> {
+    let stream = this.as_net_stream_object().map(|object| object.stream());
+    if stream.is_none() {
+        avm_warn!(activation, "NetStream.{}: this is not a NetStream", method);
+    }
+
+    stream
+}
+
+fn close<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(stream) = net_stream(activation, this, "close") {
+        stream.close(&mut activation.context);
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn pause<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(stream) = net_stream(activation, this, "pause") {
+        // With no arguments, `pause` toggles playback.
+        let paused = match args.get(0) {
+            None | Some(Value::Undefined) => None,
+            Some(paused) => Some(paused.as_bool(activation.swf_version())),
+        };
+        stream.pause(&mut activation.context, paused);
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn play<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(stream) = net_stream(activation, this, "play") {
+        let url = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation)?;
+        let requester = activation.base_clip();
+        stream.play(&mut activation.context, requester, &url);
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn seek<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(stream) = net_stream(activation, this, "seek") {
+        let offset = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_f64(activation)?;
+        if offset.is_finite() {
+            stream.seek(&mut activation.context, offset);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn set_buffer_time<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // Streams are only played once they are fully loaded, so the buffer time
+    // has no effect.
+    let buffer_time = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_f64(activation)?;
+    this.set("bufferTime", buffer_time.into(), activation)?;
+
+    Ok(Value::Undefined)
+}
+
+fn time<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(
+        net_stream(activation, this, "time")
+            .map_or(Value::Undefined, |stream| stream.time().into()),
+    )
+}
+
+fn bytes_loaded<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(net_stream(activation, this, "bytesLoaded")
+        .map_or(Value::Undefined, |stream| stream.bytes_loaded().into()))
+}
+
+fn bytes_total<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(net_stream(activation, this, "bytesTotal")
+        .map_or(Value::Undefined, |stream| stream.bytes_total().into()))
+}
+
+/// Convert a value from an FLV script data tag, such as `onMetaData`, into
+/// an AVM1 value.
+pub fn script_data_to_value<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: &ScriptDataValue,
+) -> Value<'gc> {
+    match value {
+        ScriptDataValue::Number(n) => Value::Number(*n),
+        ScriptDataValue::Boolean(b) => Value::Bool(*b),
+        ScriptDataValue::String(s) => AvmString::new(activation.context.gc_context, s).into(),
+        ScriptDataValue::Null => Value::Null,
+        ScriptDataValue::Undefined => Value::Undefined,
+        ScriptDataValue::Object(properties) | ScriptDataValue::EcmaArray(properties) => {
+            let obj_proto = activation.context.avm1.prototypes.object;
+            if let Ok(obj) = obj_proto.create_bare_object(activation, obj_proto) {
+                for (name, value) in properties {
+                    let value = script_data_to_value(activation, value);
+                    obj.define_value(
+                        activation.context.gc_context,
+                        name,
+                        value,
+                        Attribute::empty(),
+                    );
+                }
+                obj.into()
+            } else {
+                Value::Undefined
+            }
+        }
+        ScriptDataValue::StrictArray(values) => {
+            let array_constructor = activation.context.avm1.prototypes.array_constructor;
+            if let Ok(Value::Object(obj)) =
+                array_constructor.construct(activation, &[Value::Number(values.len() as f64)])
+            {
+                for (i, value) in values.iter().enumerate() {
+                    let value = script_data_to_value(activation, value);
+                    obj.set_array_element(i, value, activation.context.gc_context);
+                }
+                obj.into()
+            } else {
+                Value::Undefined
+            }
+        }
+        ScriptDataValue::Date(time) => {
+            let date_constructor = activation.context.avm1.prototypes.date_constructor;
+            if let Ok(Value::Object(obj)) =
+                date_constructor.construct(activation, &[Value::Number(*time)])
+            {
+                obj.into()
+            } else {
+                Value::Undefined
+            }
+        }
+    }
+}
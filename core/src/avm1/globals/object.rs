@@ -207,7 +207,7 @@ fn watch<'gc>(
     Ok(true.into())
 }
 
-/// Implements `Object.prototype.unmwatch`
+/// Implements `Object.prototype.unwatch`
 fn unwatch<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     this: Object<'gc>,
@@ -305,6 +305,11 @@ pub fn fill_proto<'gc>(
 /// This is an undocumented function that allows ActionScript 2.0 classes to
 /// declare the property flags of a given property. It's not part of
 /// `Object.prototype`, and I suspect that's a deliberate omission.
+///
+/// The properties to change are given as an array or a comma-separated list
+/// of names, or `null` for every property of the object. The flags to set
+/// are applied after the flags to clear. See `Attribute` for their values;
+/// bits without a meaning are ignored.
 pub fn as_set_prop_flags<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _: Object<'gc>,
@@ -347,22 +352,15 @@ pub fn as_set_prop_flags<'gc>(
     let set_flags = args
         .get(2)
         .unwrap_or(&Value::Number(0.0))
-        .coerce_to_f64(activation)? as u8;
+        .coerce_to_i32(activation)? as u16;
     let set_attributes = Attribute::from_bits_truncate(set_flags);
 
     let clear_flags = args
         .get(3)
         .unwrap_or(&Value::Number(0.0))
-        .coerce_to_f64(activation)? as u8;
+        .coerce_to_i32(activation)? as u16;
     let clear_attributes = Attribute::from_bits_truncate(clear_flags);
 
-    if set_attributes.bits() != set_flags || clear_attributes.bits() != clear_flags {
-        avm_warn!(
-            activation,
-            "ASSetPropFlags: Unimplemented support for flags > 7"
-        );
-    }
-
     match properties {
         Some(properties) => {
             for prop_name in properties {
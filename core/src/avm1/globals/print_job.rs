@@ -0,0 +1,362 @@
+//! AVM1 PrintJob object
+//!
+//! Pages are drawn as soon as they are added, as in Flash Player, and handed
+//! to the UI backend to be printed when the job is sent.
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::object::print_job_object::PrintJobObject;
+use crate::avm1::property::Attribute;
+use crate::avm1::{Object, TObject, Value};
+use crate::backend::render::{Bitmap, BitmapFormat};
+use crate::context::{RenderContext, UpdateContext};
+use crate::prelude::*;
+use crate::transform::{Transform, TransformStack};
+use gc_arena::MutationContext;
+
+/// The size of the paper that pages are printed on, in points: US Letter.
+const PAPER_SIZE: (f64, f64) = (612.0, 792.0);
+
+/// The margin that printers leave around each page, in points.
+const PAGE_MARGIN: f64 = 18.0;
+
+/// Implements `PrintJob`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.into())
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let object = PrintJobObject::empty_print_job(gc_context, Some(proto));
+    let script_object = object.as_script_object().unwrap();
+
+    script_object.force_set_function(
+        "start",
+        start,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    script_object.force_set_function(
+        "addPage",
+        add_page,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    script_object.force_set_function(
+        "send",
+        send,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+/// Implements `PrintJob.start`.
+///
+/// There is no print dialog; the job always starts, unless it already has,
+/// and reports the size of the paper it will be printed on.
+fn start<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let job = match this.as_print_job_object() {
+        Some(job) if !job.is_started() => job,
+        _ => return Ok(false.into()),
+    };
+    job.start(activation.context.gc_context);
+
+    let (paper_width, paper_height) = PAPER_SIZE;
+    for (name, value) in [
+        ("paperWidth", paper_width),
+        ("paperHeight", paper_height),
+        ("pageWidth", paper_width - 2.0 * PAGE_MARGIN),
+        ("pageHeight", paper_height - 2.0 * PAGE_MARGIN),
+    ]
+    .iter()
+    {
+        this.set(name, (*value).into(), activation)?;
+    }
+    this.set("orientation", "portrait".into(), activation)?;
+
+    Ok(true.into())
+}
+
+/// Implements `PrintJob.addPage`.
+fn add_page<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let job = match this.as_print_job_object() {
+        Some(job) if job.is_started() => job,
+        _ => return Ok(false.into()),
+    };
+
+    // The target is either a level number or a clip.
+    let target = match args.get(0) {
+        Some(Value::Number(level)) => activation.context.stage.child_by_depth(*level as i32),
+        Some(target) => {
+            let start = activation.target_clip_or_root()?;
+            activation.resolve_target_display_object(start, target.clone(), false)?
+        }
+        None => None,
+    };
+    let target = match target {
+        Some(target) => target,
+        None => return Ok(false.into()),
+    };
+
+    // The area to print is in the coordinates of the target, and defaults to
+    // all of it.
+    let area = match args.get(1) {
+        Some(Value::Object(area)) => {
+            let mut edge = |name| -> Result<Twips, Error<'gc>> {
+                let value = area.get(name, activation)?.coerce_to_f64(activation)?;
+                let value = if value.is_finite() { value } else { 0.0 };
+                Ok(Twips::from_pixels(value))
+            };
+            BoundingBox {
+                valid: true,
+                x_min: edge("xMin")?,
+                y_min: edge("yMin")?,
+                x_max: edge("xMax")?,
+                y_max: edge("yMax")?,
+            }
+        }
+        _ => target.bounds_with_transform(&Matrix::identity()),
+    };
+
+    // A clip can be printed at another frame than the one it's showing.
+    let frame = match (args.get(3), target.as_movie_clip()) {
+        (Some(frame), Some(clip)) => {
+            let frame = frame.coerce_to_f64(activation)?;
+            if frame >= 1.0 && frame <= f64::from(clip.total_frames()) {
+                Some((clip, frame as u16))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let page = match frame {
+        Some((clip, frame)) if frame != clip.current_frame() => {
+            // The clip is moved there and back without running any scripts,
+            // as for a graphic symbol following its parent's timeline.
+            let current_frame = clip.current_frame();
+            clip.run_goto(target, &mut activation.context, frame, true);
+            let page = render_page(&mut activation.context, target, &area);
+            clip.run_goto(target, &mut activation.context, current_frame, true);
+            page
+        }
+        _ => render_page(&mut activation.context, target, &area),
+    };
+
+    match page {
+        Some(page) => {
+            job.add_page(activation.context.gc_context, page);
+            Ok(true.into())
+        }
+        None => Ok(false.into()),
+    }
+}
+
+/// Implements `PrintJob.send`.
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(job) = this.as_print_job_object() {
+        let pages = job.finish(activation.context.gc_context);
+        if !pages.is_empty() {
+            activation.context.ui.print(pages);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Draw an area of a display object, with its top-left corner at the
+/// top-left of the page. Anything past the edges of the page is cut off.
+///
+/// The page is drawn in tiles the size of the viewport, which is all that
+/// render backends can capture at once.
+///
+/// Returns `None` if the area is empty or the renderer can't capture frames.
+fn render_page<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    target: DisplayObject<'gc>,
+    area: &BoundingBox,
+) -> Option<Bitmap> {
+    let (paper_width, paper_height) = PAPER_SIZE;
+    let width = (area.x_max - area.x_min)
+        .to_pixels()
+        .ceil()
+        .min(paper_width - 2.0 * PAGE_MARGIN);
+    let height = (area.y_max - area.y_min)
+        .to_pixels()
+        .ceil()
+        .min(paper_height - 2.0 * PAGE_MARGIN);
+    if !area.valid || width < 1.0 || height < 1.0 {
+        return None;
+    }
+    let (width, height) = (width as u32, height as u32);
+
+    let (tile_width, tile_height) = context.stage.viewport_size();
+    if tile_width == 0 || tile_height == 0 {
+        return None;
+    }
+
+    // The target applies its own transform as it's drawn, so start from its
+    // inverse.
+    let mut matrix = *target.matrix();
+    matrix.invert();
+
+    let mut page = vec![0; width as usize * height as usize * 4];
+    for tile_y in (0..height).step_by(tile_height as usize) {
+        for tile_x in (0..width).step_by(tile_width as usize) {
+            let offset = Matrix::translate(
+                Twips::new(-area.x_min.get()) - Twips::from_pixels(tile_x.into()),
+                Twips::new(-area.y_min.get()) - Twips::from_pixels(tile_y.into()),
+            );
+            let tile = render_tile(context, target, offset * matrix)?;
+            paste(&mut page, width, &tile, tile_x, tile_y);
+        }
+    }
+
+    Some(Bitmap {
+        width,
+        height,
+        data: BitmapFormat::Rgba(page),
+    })
+}
+
+/// Capture a single frame of a display object, drawn with the given matrix
+/// instead of its own, on white.
+fn render_tile<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    target: DisplayObject<'gc>,
+    matrix: Matrix,
+) -> Option<Bitmap> {
+    if !context.renderer.begin_capture() {
+        return None;
+    }
+    context.renderer.set_dirty_rect(None);
+
+    let mut transform_stack = TransformStack::new();
+    transform_stack.push(&Transform {
+        matrix,
+        color_transform: Default::default(),
+    });
+
+    context.stage.begin_render(context.gc_context);
+    context.renderer.begin_frame(Color::from_rgb(0xffffff, 255));
+    let mut render_context = RenderContext {
+        renderer: &mut *context.renderer,
+        ui: &mut *context.ui,
+        library: &*context.library,
+        transform_stack: &mut transform_stack,
+        stage: context.stage,
+        focus_tracker: context.focus_tracker,
+        clip_depth_stack: vec![],
+        allow_mask: true,
+        debug_overlay: None,
+        frame_interpolation: None,
+        dirty_rect: None,
+        cull: false,
+        drew_untracked_content: false,
+    };
+    target.render(&mut render_context);
+    context.renderer.end_frame();
+
+    context.renderer.end_capture()
+}
+
+/// Copy a tile into an RGBA page `page_width` pixels wide, with its top-left
+/// corner at `(x, y)`. Whatever falls outside of the page is cut off.
+fn paste(page: &mut [u8], page_width: u32, tile: &Bitmap, x: u32, y: u32) {
+    let page_width = page_width as usize;
+    let page_height = page.len() / 4 / page_width.max(1);
+    let (x, y) = (x as usize, y as usize);
+    let width = (tile.width as usize).min(page_width.saturating_sub(x));
+    let height = (tile.height as usize).min(page_height.saturating_sub(y));
+
+    for row in 0..height {
+        let page_start = ((y + row) * page_width + x) * 4;
+        let page_row = &mut page[page_start..page_start + width * 4];
+        let tile_start = row * tile.width as usize;
+        match &tile.data {
+            BitmapFormat::Rgba(data) => {
+                page_row.copy_from_slice(&data[tile_start * 4..(tile_start + width) * 4]);
+            }
+            BitmapFormat::Rgb(data) => {
+                let tile_row = &data[tile_start * 3..(tile_start + width) * 3];
+                for (page_pixel, rgb) in page_row.chunks_exact_mut(4).zip(tile_row.chunks_exact(3))
+                {
+                    page_pixel.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(width: u32, height: u32, value: u8) -> Bitmap {
+        Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(vec![value; width as usize * height as usize * 4]),
+        }
+    }
+
+    #[test]
+    fn tiles_fill_the_page() {
+        // A 5x3 page, drawn in 2x2 tiles.
+        let mut page = vec![0; 5 * 3 * 4];
+        let mut value = 1;
+        for y in (0..3).step_by(2) {
+            for x in (0..5).step_by(2) {
+                paste(&mut page, 5, &tile(2, 2, value), x, y);
+                value += 1;
+            }
+        }
+
+        let pixels: Vec<u8> = page.chunks_exact(4).map(|pixel| pixel[0]).collect();
+        #[rustfmt::skip]
+        assert_eq!(pixels, [
+            1, 1, 2, 2, 3,
+            1, 1, 2, 2, 3,
+            4, 4, 5, 5, 6,
+        ]);
+    }
+
+    #[test]
+    fn rgb_tiles_are_opaque() {
+        let mut page = vec![0; 2 * 4];
+        let rgb = Bitmap {
+            width: 1,
+            height: 1,
+            data: BitmapFormat::Rgb(vec![10, 20, 30]),
+        };
+        paste(&mut page, 2, &rgb, 1, 0);
+        assert_eq!(page, [0, 0, 0, 0, 10, 20, 30, 255]);
+    }
+}
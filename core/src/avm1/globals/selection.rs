@@ -2,7 +2,7 @@ use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::globals::as_broadcaster::BroadcasterFunctions;
 use crate::avm1::property::Attribute;
-use crate::avm1::{Object, ScriptObject, TDisplayObject, TObject, Value};
+use crate::avm1::{AvmString, Object, ScriptObject, TDisplayObject, TObject, Value};
 use crate::display_object::{EditText, TextSelection};
 use gc_arena::MutationContext;
 
@@ -98,9 +98,10 @@ pub fn get_focus<'gc>(
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    // The focus is given as the path of the focused object, not the object itself.
     let focus = activation.context.focus_tracker.get();
     match focus {
-        Some(focus) => Ok(focus.object()),
+        Some(focus) => Ok(AvmString::new(activation.context.gc_context, focus.path()).into()),
         None => Ok(Value::Null),
     }
 }
@@ -111,24 +112,36 @@ pub fn set_focus<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     let tracker = activation.context.focus_tracker;
-    match args.get(0) {
+    let target = match args.get(0) {
         Some(Value::Null) | Some(Value::Undefined) => {
             tracker.set(None, &mut activation.context);
-            Ok(true.into())
+            return Ok(true.into());
         }
-        Some(Value::Object(obj)) => {
-            if let Some(display_object) = obj.as_display_object() {
-                if display_object.is_focusable() {
-                    tracker.set(Some(display_object), &mut activation.context);
+        // The object to focus can also be given by its path, such as "_root.name_txt".
+        Some(target @ Value::Object(_)) | Some(target @ Value::String(_)) => {
+            let start_clip = activation.target_clip_or_root()?;
+            activation.resolve_target_display_object(start_clip, *target, false)?
+        }
+        _ => return Ok(false.into()),
+    };
+
+    if let Some(display_object) = target {
+        if display_object.is_focusable() {
+            tracker.set(Some(display_object), &mut activation.context);
+
+            // Focusing a text field selects all of its text, ready to be typed over.
+            if let Some(text) = display_object.as_edit_text() {
+                if text.is_selectable() {
+                    let selection = TextSelection::for_range(0, text.text_length());
+                    text.set_selection(Some(selection), activation.context.gc_context);
                 }
-                // [NA] Note: The documentation says true is success and false is failure,
-                // but from testing this seems to be opposite.
-                Ok(false.into())
-            } else {
-                Ok(true.into())
             }
         }
-        _ => Ok(false.into()),
+        // [NA] Note: The documentation says true is success and false is failure,
+        // but from testing this seems to be opposite.
+        Ok(false.into())
+    } else {
+        Ok(true.into())
     }
 }
 
@@ -6,6 +6,7 @@ use crate::avm1::property::Attribute;
 use crate::avm1::{AvmString, Object, TObject, Value};
 use crate::avm_warn;
 use crate::display_object::TDisplayObject;
+use crate::site_settings::{SiteSettings, StorageUsage};
 use flash_lso::types::Value as AmfValue;
 use flash_lso::types::{AMFVersion, Element, Lso};
 use gc_arena::MutationContext;
@@ -565,6 +566,11 @@ pub fn clear<'gc>(
 
     activation.context.storage.remove_key(&name);
 
+    let site = SiteSettings::site_for_shared_object(&name);
+    let mut usage = StorageUsage::load(activation.context.storage, site);
+    usage.set(&name, 0);
+    usage.save(activation.context.storage, site);
+
     Ok(Value::Undefined)
 }
 
@@ -589,7 +595,7 @@ pub fn connect<'gc>(
 pub fn flush<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     let data = this.get("data", activation)?.coerce_to_object(activation);
 
@@ -610,7 +616,34 @@ pub fn flush<'gc>(
 
     let bytes = flash_lso::write::write_to_bytes(&mut lso).unwrap_or_default();
 
-    Ok(activation.context.storage.put(&name, &bytes).into())
+    // `minDiskSpace` asks for room for the object to grow into.
+    let min_disk_space = match args.get(0) {
+        Some(value) => value.coerce_to_i32(activation)?.max(0) as usize,
+        None => 0,
+    };
+    let site = SiteSettings::site_for_shared_object(&name);
+    let mut usage = StorageUsage::load(activation.context.storage, site);
+    let total = usage.total_with(&name, bytes.len().max(min_disk_space));
+    if !activation.context.site_settings.allows_storage(total) {
+        // Flash Player would ask the user for more space here, and report
+        // their answer with `onStatus`. There is no such prompt yet, so the
+        // request stays pending.
+        avm_warn!(
+            activation,
+            "SharedObject.flush: {} would exceed the storage limit of {}",
+            name,
+            site
+        );
+        return Ok("pending".into());
+    }
+
+    if !activation.context.storage.put(&name, &bytes) {
+        return Ok(false.into());
+    }
+
+    usage.set(&name, bytes.len());
+    usage.save(activation.context.storage, site);
+    Ok(true.into())
 }
 
 pub fn get_size<'gc>(
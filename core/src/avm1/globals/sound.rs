@@ -202,32 +202,24 @@ fn attach_sound<'gc>(
     let name = args.get(0).unwrap_or(&Value::Undefined);
     if let Some(sound_object) = this.as_sound_object() {
         let name = name.coerce_to_string(activation)?;
-        let movie = sound_object
-            .owner()
-            .unwrap_or_else(|| activation.context.stage.root_clip())
-            .movie();
-        if let Some(movie) = movie {
-            if let Some(Character::Sound(sound)) = activation
-                .context
-                .library
-                .library_for_movie_mut(movie)
-                .character_by_export_name(&name)
-            {
-                sound_object.set_sound(activation.context.gc_context, Some(*sound));
-                sound_object.set_duration(
-                    activation.context.gc_context,
-                    activation.context.audio.get_sound_duration(*sound),
-                );
-                sound_object.set_position(activation.context.gc_context, 0);
-            } else {
-                avm_warn!(activation, "Sound.attachSound: Sound '{}' not found", name);
-            }
-        } else {
-            avm_warn!(
-                activation,
-                "Sound.attachSound: Cannot attach Sound '{}' without a library to reference",
-                name
+        // Sounds without an owner belong to `_level0`, even in loaded movies.
+        let level0 = activation.context.stage.root_clip();
+        let owner = sound_object.owner().unwrap_or(level0);
+        if let Some(Character::Sound(sound)) = activation
+            .context
+            .library
+            .library_for_export(owner, level0, &name)
+            .and_then(|library| library.character_by_export_name(&name))
+        {
+            let sound = *sound;
+            sound_object.set_sound(activation.context.gc_context, Some(sound));
+            sound_object.set_duration(
+                activation.context.gc_context,
+                activation.context.audio.get_sound_duration(sound),
             );
+            sound_object.set_position(activation.context.gc_context, 0);
+        } else {
+            avm_warn!(activation, "Sound.attachSound: Sound '{}' not found", name);
         }
     } else {
         avm_warn!(activation, "Sound.attachSound: this is not a Sound");
@@ -537,29 +529,19 @@ fn stop<'gc>(
         if let Some(name) = args.get(0) {
             // Usage 1: Stop all instances of a particular sound, using the name parameter.
             let name = name.coerce_to_string(activation)?;
-            let movie = sound
-                .owner()
-                .unwrap_or_else(|| activation.context.stage.root_clip())
-                .movie();
-            if let Some(movie) = movie {
-                if let Some(Character::Sound(sound)) = activation
-                    .context
-                    .library
-                    .library_for_movie_mut(movie)
-                    .character_by_export_name(&name)
-                {
-                    // Stop all sounds with the given name.
-                    let sound = *sound;
-                    activation.context.stop_sounds_with_handle(sound);
-                } else {
-                    avm_warn!(activation, "Sound.stop: Sound '{}' not found", name);
-                }
+            let level0 = activation.context.stage.root_clip();
+            let owner = sound.owner().unwrap_or(level0);
+            if let Some(Character::Sound(sound)) = activation
+                .context
+                .library
+                .library_for_export(owner, level0, &name)
+                .and_then(|library| library.character_by_export_name(&name))
+            {
+                // Stop all sounds with the given name.
+                let sound = *sound;
+                activation.context.stop_sounds_with_handle(sound);
             } else {
-                avm_warn!(
-                    activation,
-                    "Sound.stop: Cannot stop Sound '{}' without a library to reference",
-                    name
-                )
+                avm_warn!(activation, "Sound.stop: Sound '{}' not found", name);
             }
         } else if let Some(owner) = sound.owner() {
             // Usage 2: Stop all sound running within a given clip.
@@ -30,25 +30,6 @@ impl fmt::Display for CpuArchitecture {
     }
 }
 
-/// Available type of sandbox for a given SWF
-pub enum SandboxType {
-    Remote,
-    LocalWithFile,
-    LocalWithNetwork,
-    LocalTrusted,
-}
-
-impl fmt::Display for SandboxType {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt.write_str(match self {
-            SandboxType::Remote => "remote",
-            SandboxType::LocalWithFile => "localWithFile",
-            SandboxType::LocalWithNetwork => "localWithNetwork",
-            SandboxType::LocalTrusted => "localTrusted",
-        })
-    }
-}
-
 /// The available host operating systems
 pub enum OperatingSystem {
     WindowsXp,
@@ -266,8 +247,6 @@ pub struct SystemProperties {
     pub manufacturer: Manufacturer,
     /// The os of the host
     pub os: OperatingSystem,
-    /// The type of the player sandbox
-    pub sandbox_type: SandboxType,
     /// The cpu architecture of the platform
     pub cpu_architecture: CpuArchitecture,
     /// The highest supported h264 decoder level
@@ -393,7 +372,6 @@ impl Default for SystemProperties {
             dpi: 1_f32,
             manufacturer: Manufacturer::Linux,
             os: OperatingSystem::Linux,
-            sandbox_type: SandboxType::LocalTrusted,
             cpu_architecture: CpuArchitecture::X86,
             idc_level: "5.1".into(),
         }
@@ -433,7 +411,7 @@ pub fn show_settings<'gc>(
 
     avm_warn!(
         activation,
-        "System.showSettings({:?}) not not implemented",
+        "System.showSettings({:?}) not implemented",
         panel
     );
     Ok(Value::Undefined)
@@ -55,11 +55,11 @@ fn get_sandbox_type<'gc>(
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    Ok(AvmString::new(
-        activation.context.gc_context,
-        activation.context.system.sandbox_type.to_string(),
-    )
-    .into())
+    let sandbox_type = match activation.base_clip().movie() {
+        Some(movie) => activation.context.security.sandbox_type(&movie),
+        None => return Ok(Value::Undefined),
+    };
+    Ok(AvmString::new(activation.context.gc_context, sandbox_type.to_string()).into())
 }
 
 fn get_choose_local_swf_path<'gc>(
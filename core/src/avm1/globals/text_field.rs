@@ -127,11 +127,16 @@ pub fn create_proto<'gc>(
         "backgroundColor" => [background_color, set_background_color],
         "border" => [border, set_border],
         "borderColor" => [border_color, set_border_color],
+        "bottomScroll" => [bottom_scroll],
         "embedFonts" => [embed_fonts, set_embed_fonts],
+        "hscroll" => [hscroll, set_hscroll],
         "html" => [html, set_html],
         "htmlText" => [html_text, set_html_text],
         "length" => [length],
+        "maxhscroll" => [maxhscroll],
+        "maxscroll" => [maxscroll],
         "multiline" => [multiline, set_multiline],
+        "scroll" => [scroll, set_scroll],
         "selectable" => [selectable, set_selectable],
         "text" => [text, set_text],
         "textColor" => [text_color, set_text_color],
@@ -503,6 +508,62 @@ pub fn text_height<'gc>(
     Ok(round_down_to_pixel(metrics.1).to_pixels().into())
 }
 
+pub fn scroll<'gc>(
+    this: EditText<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.scroll().into())
+}
+
+pub fn set_scroll<'gc>(
+    this: EditText<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let scroll = value.coerce_to_f64(activation)?;
+    // Out-of-range values (including negative ones and NaN) are clamped.
+    this.set_scroll(scroll as usize, activation.context.gc_context);
+    Ok(())
+}
+
+pub fn maxscroll<'gc>(
+    this: EditText<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.maxscroll().into())
+}
+
+pub fn bottom_scroll<'gc>(
+    this: EditText<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.bottom_scroll().into())
+}
+
+pub fn hscroll<'gc>(
+    this: EditText<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.hscroll().into())
+}
+
+pub fn set_hscroll<'gc>(
+    this: EditText<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let hscroll = value.coerce_to_f64(activation)?;
+    this.set_hscroll(hscroll, activation.context.gc_context);
+    Ok(())
+}
+
+pub fn maxhscroll<'gc>(
+    this: EditText<'gc>,
+    _activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(this.maxhscroll().into())
+}
+
 pub fn multiline<'gc>(
     this: EditText<'gc>,
     _activation: &mut Activation<'_, 'gc, '_>,
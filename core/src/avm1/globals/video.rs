@@ -3,9 +3,11 @@
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::globals::display_object;
-use crate::avm1::object::Object;
+use crate::avm1::object::{Object, TObject};
+use crate::avm1::property::Attribute;
 use crate::avm1::value::Value;
 use crate::avm1::ScriptObject;
+use crate::display_object::TDisplayObject;
 use gc_arena::MutationContext;
 
 /// Implements `Video`
@@ -22,9 +24,46 @@ pub fn create_proto<'gc>(
     proto: Object<'gc>,
     fn_proto: Object<'gc>,
 ) -> Object<'gc> {
-    let object = ScriptObject::object(gc_context, Some(proto));
+    let mut object = ScriptObject::object(gc_context, Some(proto));
 
     display_object::define_display_object_proto(gc_context, object, fn_proto);
 
+    object.force_set_function(
+        "attachVideo",
+        attach_video,
+        gc_context,
+        Attribute::DONT_DELETE | Attribute::READ_ONLY | Attribute::DONT_ENUM,
+        Some(fn_proto),
+    );
+
     object.into()
 }
+
+/// Implements `Video.attachVideo`
+///
+/// Only `NetStream` sources are supported. Passing `null` detaches the
+/// current source.
+pub fn attach_video<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let video = match this.as_display_object().and_then(|dobj| dobj.as_video()) {
+        Some(video) => video,
+        None => return Ok(Value::Undefined),
+    };
+
+    let net_stream = match args.get(0) {
+        Some(Value::Object(source)) => match source.as_net_stream_object() {
+            Some(net_stream) => Some(net_stream.stream()),
+            None => {
+                log::warn!("Video.attachVideo: Camera sources are not yet supported");
+                None
+            }
+        },
+        _ => None,
+    };
+    video.attach_net_stream(activation.context.gc_context, net_stream);
+
+    Ok(Value::Undefined)
+}
@@ -1096,8 +1096,11 @@ fn spawn_xml_fetch<'gc>(
 
     this.set("loaded", false.into(), activation)?;
 
-    let fetch = activation.context.navigator.fetch(&url, request_options);
     let target_clip = activation.target_clip_or_root()?;
+    let requester = activation.base_clip();
+    let fetch = activation
+        .context
+        .fetch_data(requester, &url, request_options);
     // given any defined loader object, sends the request. Will load into LoadVars if given.
     let process = if let Some(node) = loader_object.as_xml_node() {
         activation.context.load_manager.load_xml_into_node(
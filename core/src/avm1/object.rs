@@ -20,11 +20,13 @@ use crate::avm1::object::drop_shadow_filter::DropShadowFilterObject;
 use crate::avm1::object::glow_filter::GlowFilterObject;
 use crate::avm1::object::gradient_bevel_filter::GradientBevelFilterObject;
 use crate::avm1::object::gradient_glow_filter::GradientGlowFilterObject;
+use crate::avm1::object::net_stream_object::NetStreamObject;
+use crate::avm1::object::print_job_object::PrintJobObject;
 use crate::avm1::object::transform_object::TransformObject;
 use crate::avm1::object::xml_attributes_object::XmlAttributesObject;
 use crate::avm1::object::xml_idmap_object::XmlIdMapObject;
 use crate::avm1::object::xml_object::XmlObject;
-use crate::avm1::{ScriptObject, SoundObject, StageObject, Value};
+use crate::avm1::{AvmString, ScriptObject, SoundObject, StageObject, Value};
 use crate::avm_warn;
 use crate::display_object::DisplayObject;
 use crate::xml::XmlNode;
@@ -46,6 +48,8 @@ pub mod drop_shadow_filter;
 pub mod glow_filter;
 pub mod gradient_bevel_filter;
 pub mod gradient_glow_filter;
+pub mod net_stream_object;
+pub mod print_job_object;
 pub mod script_object;
 pub mod shared_object;
 pub mod sound_object;
@@ -86,6 +90,8 @@ pub mod xml_object;
         GradientGlowFilterObject(GradientGlowFilterObject<'gc>),
         DateObject(DateObject<'gc>),
         BitmapData(BitmapDataObject<'gc>),
+        NetStreamObject(NetStreamObject<'gc>),
+        PrintJobObject(PrintJobObject<'gc>),
     }
 )]
 pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy {
@@ -113,7 +119,10 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
         if self.has_own_property(activation, name) {
             self.get_local(name, activation, (*self).into())
         } else {
-            Ok(search_prototype(self.proto(), name, activation, (*self).into())?.0)
+            match search_prototype(self.proto(), name, activation, (*self).into())? {
+                (value, Some(_)) => Ok(value),
+                (_, None) => resolve_missing_property(name, activation, (*self).into()),
+            }
         }
     }
 
@@ -172,12 +181,18 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
         args: &[Value<'gc>],
         activation: &mut Activation<'_, 'gc, '_>,
     ) -> Result<Value<'gc>, Error<'gc>> {
-        let (method, base_proto) = search_prototype(
+        let (method, base_proto) = match search_prototype(
             Value::Object((*self).into()),
             name,
             activation,
             (*self).into(),
-        )?;
+        )? {
+            (method, Some(base_proto)) => (method, Some(base_proto)),
+            (_, None) => (
+                resolve_missing_property(name, activation, (*self).into())?,
+                None,
+            ),
+        };
 
         if method.is_primitive() {
             avm_warn!(activation, "Object method {} is not callable", name);
@@ -509,6 +524,16 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
         None
     }
 
+    /// Get the underlying `NetStreamObject`, if it exists
+    fn as_net_stream_object(&self) -> Option<NetStreamObject<'gc>> {
+        None
+    }
+
+    /// Get the underlying `PrintJobObject`, if it exists
+    fn as_print_job_object(&self) -> Option<PrintJobObject<'gc>> {
+        None
+    }
+
     /// Get the underlying `BitmapDataObject`, if it exists
     fn as_bitmap_data_object(&self) -> Option<BitmapDataObject<'gc>> {
         None
@@ -606,3 +631,41 @@ pub fn search_prototype<'gc>(
 
     Ok((Value::Undefined, None))
 }
+
+/// Handle a property lookup that missed both an object and its prototypes.
+///
+/// Since SWF 6, objects may define (or inherit) a `__resolve` method, which
+/// is called with the name of the missing property, and whose return value is
+/// used in place of `undefined`. Lookups of `__resolve` itself never go
+/// through this, and neither do lookups on an object whose `__resolve` is
+/// already running, so a handler that reads `this[name]` doesn't recurse.
+pub fn resolve_missing_property<'gc>(
+    name: &str,
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    if activation.swf_version() < 6
+        || name == "__resolve"
+        || activation
+            .context
+            .avm1
+            .resolving
+            .iter()
+            .any(|o| Object::ptr_eq(*o, this))
+    {
+        return Ok(Value::Undefined);
+    }
+
+    let resolve = match search_prototype(Value::Object(this), "__resolve", activation, this)? {
+        (Value::Object(resolve), Some(_)) => resolve,
+        _ => return Ok(Value::Undefined),
+    };
+
+    let name = AvmString::new(activation.context.gc_context, name.to_string());
+
+    activation.context.avm1.resolving.push(this);
+    let result = resolve.call("__resolve", activation, this, None, &[name.into()]);
+    activation.context.avm1.resolving.pop();
+
+    result
+}
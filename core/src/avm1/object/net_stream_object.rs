@@ -0,0 +1,72 @@
+//! AVM1 object type to represent NetStream objects.
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::{Object, ScriptObject, TObject};
+use crate::impl_custom_object;
+use crate::streams::NetStream;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::fmt;
+
+/// A NetStreamObject that is tied to a stream played by the player.
+#[derive(Clone, Copy, Collect)]
+#[collect(no_drop)]
+pub struct NetStreamObject<'gc>(GcCell<'gc, NetStreamObjectData<'gc>>);
+
+#[derive(Collect)]
+#[collect(no_drop)]
+pub struct NetStreamObjectData<'gc> {
+    /// The underlying script object.
+    base: ScriptObject<'gc>,
+
+    /// The stream played by this object.
+    stream: NetStream<'gc>,
+}
+
+impl fmt::Debug for NetStreamObject<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let this = self.0.read();
+        f.debug_struct("NetStreamObject")
+            .field("stream", &this.stream)
+            .finish()
+    }
+}
+
+impl<'gc> NetStreamObject<'gc> {
+    pub fn empty_net_stream(
+        gc_context: MutationContext<'gc, '_>,
+        proto: Option<Object<'gc>>,
+    ) -> NetStreamObject<'gc> {
+        let stream = NetStream::new(gc_context);
+        let object = NetStreamObject(GcCell::allocate(
+            gc_context,
+            NetStreamObjectData {
+                base: ScriptObject::object(gc_context, proto),
+                stream,
+            },
+        ));
+        stream.set_avm1_object(gc_context, object.into());
+
+        object
+    }
+
+    pub fn stream(self) -> NetStream<'gc> {
+        self.0.read().stream
+    }
+}
+
+impl<'gc> TObject<'gc> for NetStreamObject<'gc> {
+    impl_custom_object!(base);
+
+    fn create_bare_object(
+        &self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        this: Object<'gc>,
+    ) -> Result<Object<'gc>, Error<'gc>> {
+        Ok(NetStreamObject::empty_net_stream(activation.context.gc_context, Some(this)).into())
+    }
+
+    fn as_net_stream_object(&self) -> Option<NetStreamObject<'gc>> {
+        Some(*self)
+    }
+}
@@ -0,0 +1,89 @@
+//! AVM1 object type to represent PrintJob objects.
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::{Object, ScriptObject, TObject};
+use crate::backend::render::Bitmap;
+use crate::impl_custom_object;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::fmt;
+
+/// A PrintJob, which collects pages until it is sent to the printer.
+#[derive(Clone, Copy, Collect)]
+#[collect(no_drop)]
+pub struct PrintJobObject<'gc>(GcCell<'gc, PrintJobObjectData<'gc>>);
+
+#[derive(Collect)]
+#[collect(no_drop)]
+pub struct PrintJobObjectData<'gc> {
+    /// The underlying script object.
+    base: ScriptObject<'gc>,
+
+    /// Whether `start` has been called, and `send` hasn't yet.
+    started: bool,
+
+    /// The pages added so far.
+    #[collect(require_static)]
+    pages: Vec<Bitmap>,
+}
+
+impl fmt::Debug for PrintJobObject<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let this = self.0.read();
+        f.debug_struct("PrintJobObject")
+            .field("started", &this.started)
+            .field("pages", &this.pages.len())
+            .finish()
+    }
+}
+
+impl<'gc> PrintJobObject<'gc> {
+    pub fn empty_print_job(
+        gc_context: MutationContext<'gc, '_>,
+        proto: Option<Object<'gc>>,
+    ) -> PrintJobObject<'gc> {
+        PrintJobObject(GcCell::allocate(
+            gc_context,
+            PrintJobObjectData {
+                base: ScriptObject::object(gc_context, proto),
+                started: false,
+                pages: Vec::new(),
+            },
+        ))
+    }
+
+    pub fn is_started(self) -> bool {
+        self.0.read().started
+    }
+
+    pub fn start(self, gc_context: MutationContext<'gc, '_>) {
+        self.0.write(gc_context).started = true;
+    }
+
+    pub fn add_page(self, gc_context: MutationContext<'gc, '_>, page: Bitmap) {
+        self.0.write(gc_context).pages.push(page);
+    }
+
+    /// End the job, and return the pages added to it.
+    pub fn finish(self, gc_context: MutationContext<'gc, '_>) -> Vec<Bitmap> {
+        let mut write = self.0.write(gc_context);
+        write.started = false;
+        std::mem::take(&mut write.pages)
+    }
+}
+
+impl<'gc> TObject<'gc> for PrintJobObject<'gc> {
+    impl_custom_object!(base);
+
+    fn create_bare_object(
+        &self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        this: Object<'gc>,
+    ) -> Result<Object<'gc>, Error<'gc>> {
+        Ok(PrintJobObject::empty_print_job(activation.context.gc_context, Some(this)).into())
+    }
+
+    fn as_print_job_object(&self) -> Option<PrintJobObject<'gc>> {
+        Some(*self)
+    }
+}
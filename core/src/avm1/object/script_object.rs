@@ -1,6 +1,7 @@
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::{Executable, ExecutionReason, FunctionObject, NativeFunction};
+use crate::avm1::object::search_prototype;
 use crate::avm1::property::{Attribute, Property};
 use crate::avm1::property_map::{Entry, PropertyMap};
 use crate::avm1::{AvmString, Object, ObjectPtr, TObject, Value};
@@ -22,6 +23,12 @@ pub enum ArrayStorage<'gc> {
 pub struct Watcher<'gc> {
     callback: Object<'gc>,
     user_data: Value<'gc>,
+
+    /// Whether the callback is currently running.
+    ///
+    /// Assigning to the watched property from inside the callback does not
+    /// call it again.
+    running: bool,
 }
 
 impl<'gc> Watcher<'gc> {
@@ -29,6 +36,7 @@ impl<'gc> Watcher<'gc> {
         Self {
             callback,
             user_data,
+            running: false,
         }
     }
 
@@ -247,8 +255,16 @@ impl<'gc> ScriptObject<'gc> {
         base_proto: Option<Object<'gc>>,
     ) -> Result<(), Error<'gc>> {
         if name == "__proto__" {
+            // A prototype chain that loops back on itself is never created.
+            let mut proto = value;
+            while let Value::Object(this_proto) = proto {
+                if Object::ptr_eq(this_proto, this) {
+                    return Ok(());
+                }
+                proto = this_proto.proto();
+            }
             self.0.write(activation.context.gc_context).prototype = value;
-        } else if let Ok(index) = name.parse::<usize>() {
+        } else if let Some(index) = parse_array_index(name) {
             self.set_array_element(index, value.to_owned(), activation.context.gc_context);
         } else if !name.is_empty() {
             if name == "length" {
@@ -266,11 +282,13 @@ impl<'gc> ScriptObject<'gc> {
             //Before actually inserting a new property, we need to crawl the
             //prototype chain for virtual setters, which kind of break how
             //ECMAScript `[[Set]]` is supposed to work...
+            let swf_version = activation.swf_version();
             let is_vacant = !self
                 .0
                 .read()
                 .values
-                .contains_key(name, activation.is_case_sensitive());
+                .get(name, activation.is_case_sensitive())
+                .map_or(false, |property| property.allow_swf_version(swf_version));
             let mut worked = false;
 
             if is_vacant {
@@ -310,12 +328,18 @@ impl<'gc> ScriptObject<'gc> {
                     .read()
                     .watchers
                     .get(name, activation.is_case_sensitive())
+                    .filter(|watcher| !watcher.running)
                     .cloned();
                 let mut return_value = Ok(());
                 if let Some(watcher) = watcher {
-                    let old_value = self.get(name, activation)?;
-                    value = match watcher.call(activation, name, old_value, value, this, base_proto)
-                    {
+                    // A missing property's old value is `undefined`, without
+                    // asking `__resolve`.
+                    let (old_value, _) = search_prototype((*self).into(), name, activation, this)?;
+                    self.set_watcher_running(activation, name, true);
+                    let result = watcher.call(activation, name, old_value, value, this, base_proto);
+                    self.set_watcher_running(activation, name, false);
+
+                    value = match result {
                         Ok(value) => value,
                         Err(Error::ThrownValue(error)) => {
                             return_value = Err(Error::ThrownValue(error));
@@ -331,7 +355,18 @@ impl<'gc> ScriptObject<'gc> {
                     .values
                     .entry(name, activation.is_case_sensitive())
                 {
-                    Entry::Occupied(mut entry) => entry.get_mut().set(value),
+                    Entry::Occupied(mut entry) => {
+                        if entry.get_mut().allow_swf_version(swf_version) {
+                            entry.get_mut().set(value)
+                        } else {
+                            // A property hidden from this version is replaced.
+                            entry.insert(Property::Stored {
+                                value,
+                                attributes: Attribute::empty(),
+                            });
+                            None
+                        }
+                    }
                     Entry::Vacant(entry) => {
                         entry.insert(Property::Stored {
                             value,
@@ -362,6 +397,23 @@ impl<'gc> ScriptObject<'gc> {
 
         Ok(())
     }
+
+    /// Mark the watcher of a property as running or not.
+    fn set_watcher_running(
+        &self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        name: &str,
+        running: bool,
+    ) {
+        if let Some(watcher) = self
+            .0
+            .write(activation.context.gc_context)
+            .watchers
+            .get_mut(name, activation.is_case_sensitive())
+        {
+            watcher.running = running;
+        }
+    }
 }
 
 impl<'gc> TObject<'gc> for ScriptObject<'gc> {
@@ -385,11 +437,13 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
 
         let mut getter = None;
 
+        let swf_version = activation.swf_version();
         if let Some(value) = self
             .0
             .read()
             .values
             .get(name, activation.is_case_sensitive())
+            .filter(|property| property.allow_swf_version(swf_version))
         {
             match value {
                 Property::Virtual { get, .. } => getter = Some(get.to_owned()),
@@ -492,9 +546,10 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
     ///
     /// Returns false if the property cannot be deleted.
     fn delete(&self, activation: &mut Activation<'_, 'gc, '_>, name: &str) -> bool {
+        let swf_version = activation.swf_version();
         let mut object = self.0.write(activation.context.gc_context);
         if let Some(prop) = object.values.get(name, activation.is_case_sensitive()) {
-            if prop.can_delete() {
+            if prop.can_delete() && prop.allow_swf_version(swf_version) {
                 object.values.remove(name, activation.is_case_sensitive());
                 return true;
             }
@@ -632,10 +687,12 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
         if name == "__proto__" {
             return true;
         }
+        let swf_version = activation.swf_version();
         self.0
             .read()
             .values
-            .contains_key(name, activation.is_case_sensitive())
+            .get(name, activation.is_case_sensitive())
+            .map_or(false, |property| property.allow_swf_version(swf_version))
     }
 
     fn has_own_virtual(&self, activation: &mut Activation<'_, 'gc, '_>, name: &str) -> bool {
@@ -645,7 +702,7 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
             .values
             .get(name, activation.is_case_sensitive())
         {
-            slot.is_virtual()
+            slot.is_virtual() && slot.allow_swf_version(activation.swf_version())
         } else {
             false
         }
@@ -659,7 +716,7 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
             .values
             .get(name, activation.is_case_sensitive())
         {
-            prop.is_enumerable()
+            prop.is_enumerable() && prop.allow_swf_version(activation.swf_version())
         } else {
             false
         }
@@ -674,17 +731,19 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
         };
         let mut out_keys = vec![];
         let object = self.0.read();
+        let swf_version = activation.swf_version();
 
-        // Prototype keys come first.
+        // Prototype keys come first, unless they're shadowed by our own.
         out_keys.extend(proto_keys.into_iter().filter(|k| {
             !object
                 .values
-                .contains_key(k, activation.is_case_sensitive())
+                .get(k, activation.is_case_sensitive())
+                .map_or(false, |p| p.allow_swf_version(swf_version))
         }));
 
         // Then our own keys.
         out_keys.extend(self.0.read().values.iter().filter_map(move |(k, p)| {
-            if p.is_enumerable() {
+            if p.is_enumerable() && p.allow_swf_version(swf_version) {
                 Some(k.to_string())
             } else {
                 None
@@ -817,6 +876,19 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
     }
 }
 
+/// The array index that a property name refers to, if any.
+///
+/// Only names that are the canonical string form of an index do, so that
+/// keys like `"01"` or `"+1"` stay distinct properties.
+fn parse_array_index(name: &str) -> Option<usize> {
+    let index = name.parse::<usize>().ok()?;
+    if index.to_string() == name {
+        Some(index)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -840,7 +912,10 @@ mod tests {
     use crate::focus_tracker::FocusTracker;
     use crate::library::Library;
     use crate::loader::LoadManager;
+    use crate::local_connection::LocalConnections;
     use crate::prelude::*;
+    use crate::site_settings::SiteSettings;
+    use crate::streams::StreamManager;
     use crate::tag_utils::{SwfMovie, SwfSlice};
     use crate::vminterface::Instantiator;
     use gc_arena::rootless_arena;
@@ -890,11 +965,15 @@ mod tests {
                 player: None,
                 load_manager: &mut LoadManager::new(),
                 system: &mut SystemProperties::default(),
+                security: &mut Default::default(),
                 instance_counter: &mut 0,
                 storage: &mut MemoryStorageBackend::default(),
                 shared_objects: &mut HashMap::new(),
                 unbound_text_fields: &mut Vec::new(),
+                queued_videos: &mut Vec::new(),
                 timers: &mut Timers::new(),
+                stream_manager: &mut StreamManager::new(),
+                local_connections: &mut LocalConnections::new(),
                 current_context_menu: &mut None,
                 needs_render: &mut false,
                 avm1: &mut avm1,
@@ -906,6 +985,8 @@ mod tests {
                 times_get_time_called: 0,
                 time_offset: &mut 0,
                 frame_rate: &mut frame_rate,
+                site_settings: &SiteSettings::default(),
+                virtual_clock: &None,
             };
             context.stage.replace_at_depth(&mut context, root, 0);
 
@@ -1131,4 +1212,84 @@ mod tests {
             assert!(!keys.contains(&"virtual_hidden".to_string()));
         })
     }
+
+    #[test]
+    fn test_watcher() {
+        with_object(6, |activation, object| {
+            // Assigning the watched property from its watcher doesn't recurse.
+            let callback = FunctionObject::function(
+                activation.context.gc_context,
+                Executable::Native(|activation, this, args| {
+                    this.set("watched", "inner".into(), activation)?;
+                    Ok(args.get(2).cloned().unwrap_or(Value::Undefined))
+                }),
+                None,
+                activation.context.avm1.prototypes.function,
+            );
+            object.set_watcher(
+                activation,
+                activation.context.gc_context,
+                "watched".into(),
+                callback,
+                Value::Undefined,
+            );
+            object.set("watched", "outer".into(), activation).unwrap();
+            assert_eq!(object.get("watched", activation).unwrap(), "outer".into());
+
+            // The old value of a missing property doesn't come from `__resolve`.
+            let resolve = FunctionObject::function(
+                activation.context.gc_context,
+                Executable::Native(|_avm, _this, _args| Ok("Resolved!".into())),
+                None,
+                activation.context.avm1.prototypes.function,
+            );
+            let old_value = FunctionObject::function(
+                activation.context.gc_context,
+                Executable::Native(|_avm, _this, args| {
+                    Ok(args.get(1).cloned().unwrap_or(Value::Null))
+                }),
+                None,
+                activation.context.avm1.prototypes.function,
+            );
+            object.set("__resolve", resolve.into(), activation).unwrap();
+            object.set_watcher(
+                activation,
+                activation.context.gc_context,
+                "missing".into(),
+                old_value,
+                Value::Undefined,
+            );
+            object.set("missing", "New!".into(), activation).unwrap();
+            assert_eq!(object.get("missing", activation).unwrap(), Value::Undefined);
+        })
+    }
+
+    #[test]
+    fn test_non_canonical_index_keys() {
+        with_object(0, |activation, object| {
+            object.set("1", "one".into(), activation).unwrap();
+            object.set("01", "zero one".into(), activation).unwrap();
+            object.set("+1", "plus one".into(), activation).unwrap();
+
+            assert_eq!(object.get("1", activation).unwrap(), "one".into());
+            assert_eq!(object.get("01", activation).unwrap(), "zero one".into());
+            assert_eq!(object.get("+1", activation).unwrap(), "plus one".into());
+        })
+    }
+
+    #[test]
+    fn test_proto_cycle() {
+        with_object(0, |activation, object| {
+            let child = ScriptObject::object(activation.context.gc_context, Some(object));
+            let original_proto = object.proto();
+
+            object
+                .set("__proto__", Value::Object(child.into()), activation)
+                .unwrap();
+            assert_eq!(object.proto(), original_proto);
+
+            object.set("__proto__", Value::Null, activation).unwrap();
+            assert_eq!(object.proto(), Value::Null);
+        })
+    }
 }
@@ -3,13 +3,17 @@
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::Executable;
-use crate::avm1::object::search_prototype;
+use crate::avm1::object::{resolve_missing_property, search_prototype};
 use crate::avm1::property::Attribute;
 use crate::avm1::property_map::PropertyMap;
 use crate::avm1::{AvmString, Object, ObjectPtr, ScriptObject, TDisplayObject, TObject, Value};
 use crate::avm_warn;
+use crate::config::StageQuality;
 use crate::context::UpdateContext;
-use crate::display_object::{DisplayObject, EditText, MovieClip, TDisplayObjectContainer};
+use crate::display_object::{
+    invalidate_clip_event_interest, DisplayObject, EditText, MovieClip, TDisplayObjectContainer,
+};
+use crate::events::ClipEventMask;
 use crate::string_utils::swf_string_eq;
 use crate::types::Percent;
 use gc_arena::{Collect, GcCell, MutationContext};
@@ -96,6 +100,20 @@ impl<'gc> StageObject<'gc> {
 
     /// Clears all text field bindings from this stage object, and places the textfields on the unbound list.
     /// This is called when the object is removed from the stage.
+    /// Let the clip know that a property was written that could change which
+    /// clip events it handles, such as `onMouseMove` or `__proto__`.
+    fn note_clip_event_property(self, gc_context: MutationContext<'gc, '_>, name: &str) {
+        let display_object = self.0.read().display_object;
+        if let Some(clip) = display_object.as_movie_clip() {
+            let events = ClipEventMask::from_method_name(name);
+            if !events.is_empty() {
+                clip.add_script_clip_events(gc_context, events);
+            } else if name == "__proto__" {
+                invalidate_clip_event_interest(display_object, gc_context);
+            }
+        }
+    }
+
     pub fn unregister_text_field_bindings(self, context: &mut UpdateContext<'_, 'gc, '_>) {
         for binding in self
             .0
@@ -121,24 +139,29 @@ impl<'gc> StageObject<'gc> {
         context: &mut UpdateContext<'_, 'gc, '_>,
         case_sensitive: bool,
     ) -> Option<Value<'gc>> {
-        if let Some(slice) = name.get(0..name.len().min(6)) {
-            let is_level = if case_sensitive {
-                slice == "_level"
-            } else {
-                slice.eq_ignore_ascii_case("_level")
-            };
-            if is_level {
-                if let Some(level_id) = name.get(6..).and_then(|v| v.parse::<i32>().ok()) {
-                    let level = context
-                        .stage
-                        .child_by_depth(level_id)
-                        .map(|o| o.object())
-                        .unwrap_or(Value::Undefined);
-                    return Some(level);
-                }
-            }
-        }
+        let level_id = level_id_from_name(name, case_sensitive)?;
+        let level = context
+            .stage
+            .child_by_depth(level_id)
+            .map(|o| o.object())
+            .unwrap_or(Value::Undefined);
+        Some(level)
+    }
+}
 
+/// Parse the ID out of a level name such as `_level1`.
+///
+/// Returns `None` if `name` is not a valid level name.
+pub fn level_id_from_name(name: &str, case_sensitive: bool) -> Option<i32> {
+    let slice = name.get(0..name.len().min(6))?;
+    let is_level = if case_sensitive {
+        slice == "_level"
+    } else {
+        slice.eq_ignore_ascii_case("_level")
+    };
+    if is_level {
+        name.get(6..).and_then(|v| v.parse::<i32>().ok())
+    } else {
         None
     }
 }
@@ -191,10 +214,15 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
             let val = property.get(activation, obj.display_object)?;
             Ok(val)
         } else {
+            drop(obj);
+
             // 5) Prototype
-            Ok(search_prototype(self.proto(), name, activation, (*self).into())?.0)
+            match search_prototype(self.proto(), name, activation, (*self).into())? {
+                (value, Some(_)) => Ok(value),
+                // 6) __resolve
+                (_, None) => resolve_missing_property(name, activation, (*self).into()),
+            }
         }
-        // 6) TODO: __resolve?
     }
 
     fn get_local(
@@ -232,6 +260,8 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
         let display_object = obj.display_object;
         drop(obj);
 
+        self.note_clip_event_property(activation.context.gc_context, name);
+
         if base.has_own_property(activation, name) {
             // 1) Actual properties on the underlying object
             base.internal_set(
@@ -298,6 +328,7 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
 
     fn set_proto(&self, gc_context: MutationContext<'gc, '_>, prototype: Value<'gc>) {
         self.0.read().base.set_proto(gc_context, prototype);
+        self.note_clip_event_property(gc_context, "__proto__");
     }
 
     fn define_value(
@@ -310,7 +341,8 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
         self.0
             .read()
             .base
-            .define_value(gc_context, name, value, attributes)
+            .define_value(gc_context, name, value, attributes);
+        self.note_clip_event_property(gc_context, name);
     }
 
     fn set_attributes(
@@ -339,7 +371,8 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
         self.0
             .read()
             .base
-            .add_property(gc_context, name, get, set, attributes)
+            .add_property(gc_context, name, get, set, attributes);
+        self.note_clip_event_property(gc_context, name);
     }
 
     fn add_property_with_case(
@@ -354,7 +387,8 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
         self.0
             .read()
             .base
-            .add_property_with_case(activation, gc_context, name, get, set, attributes)
+            .add_property_with_case(activation, gc_context, name, get, set, attributes);
+        self.note_clip_event_property(gc_context, name);
     }
 
     fn set_watcher(
@@ -544,6 +578,7 @@ impl<'gc> DisplayProperty<'gc> {
         (self.get)(activation, this)
     }
 
+    /// Set the property, or do nothing if it is read-only.
     pub fn set(
         &self,
         activation: &mut Activation<'_, 'gc, '_>,
@@ -713,9 +748,13 @@ fn current_frame<'gc>(
 }
 
 fn total_frames<'gc>(
-    _activation: &mut Activation<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc, '_>,
     this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
+    if is_loading(activation, this) {
+        return Ok(0.into());
+    }
+
     Ok(this
         .as_movie_clip()
         .map(MovieClip::total_frames)
@@ -723,6 +762,18 @@ fn total_frames<'gc>(
         .unwrap_or(Value::Undefined))
 }
 
+/// Whether a clip is the target of a `loadMovie` that hasn't finished yet.
+///
+/// Such a clip still holds its old contents, but reports no frames so that
+/// preloaders wait for the new movie.
+fn is_loading<'gc>(activation: &mut Activation<'_, 'gc, '_>, this: DisplayObject<'gc>) -> bool {
+    activation
+        .context
+        .load_manager
+        .movie_load_progress(this)
+        .is_some()
+}
+
 fn alpha<'gc>(
     _activation: &mut Activation<'_, 'gc, '_>,
     this: DisplayObject<'gc>,
@@ -833,9 +884,13 @@ fn target<'gc>(
 }
 
 fn frames_loaded<'gc>(
-    _activation: &mut Activation<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc, '_>,
     this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
+    if is_loading(activation, this) {
+        return Ok(0.into());
+    }
+
     Ok(this
         .as_movie_clip()
         .map(MovieClip::frames_loaded)
@@ -862,10 +917,12 @@ fn set_name<'gc>(
 
 fn drop_target<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
-    _this: DisplayObject<'gc>,
+    this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Unimplemented property _droptarget");
-    Ok("".into())
+    Ok(match this.drop_target() {
+        Some(target) => AvmString::new(activation.context.gc_context, target.slash_path()).into(),
+        None => "".into(),
+    })
 }
 
 fn url<'gc>(
@@ -884,33 +941,72 @@ fn high_quality<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Unimplemented property _highquality");
-    Ok(1.into())
+    let high_quality = match activation.context.stage.quality() {
+        StageQuality::Best => 2,
+        StageQuality::High => 1,
+        StageQuality::Medium | StageQuality::Low => 0,
+    };
+    Ok(high_quality.into())
 }
 
 fn set_high_quality<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
-    _val: Value<'gc>,
+    val: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    avm_warn!(activation, "Unimplemented property _highquality");
+    let val = val.coerce_to_f64(activation)?;
+    if !val.is_nan() {
+        let quality = match val as i32 {
+            2 => StageQuality::Best,
+            1 => StageQuality::High,
+            _ => StageQuality::Low,
+        };
+        let stage = activation.context.stage;
+        stage.set_quality(&mut activation.context, quality);
+    }
     Ok(())
 }
 
+/// Whether `_focusrect` is the global setting of the stage, rather than an
+/// override for a single object. Before SWF6, and on the root of a level, it
+/// always is.
+fn is_global_focus_rect<'gc>(
+    activation: &Activation<'_, 'gc, '_>,
+    this: DisplayObject<'gc>,
+) -> bool {
+    activation.swf_version() < 6
+        || this
+            .parent()
+            .map_or(true, |parent| parent.as_stage().is_some())
+}
+
 fn focus_rect<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
-    _this: DisplayObject<'gc>,
+    this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Unimplemented property _focusrect");
-    Ok(Value::Null)
+    if is_global_focus_rect(activation, this) {
+        Ok(activation.context.stage.focus_rect().into())
+    } else {
+        Ok(this.focus_rect().map_or(Value::Null, Value::from))
+    }
 }
 
 fn set_focus_rect<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
-    _this: DisplayObject<'gc>,
-    _val: Value<'gc>,
+    this: DisplayObject<'gc>,
+    val: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    avm_warn!(activation, "Unimplemented property _focusrect");
+    if is_global_focus_rect(activation, this) {
+        let focus_rect = val.as_bool(activation.swf_version());
+        let stage = activation.context.stage;
+        stage.set_focus_rect(activation.context.gc_context, focus_rect);
+    } else {
+        let focus_rect = match val {
+            Value::Undefined | Value::Null => None,
+            val => Some(val.as_bool(activation.swf_version())),
+        };
+        this.set_focus_rect(activation.context.gc_context, focus_rect);
+    }
     Ok(())
 }
 
@@ -935,16 +1031,19 @@ fn quality<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Unimplemented property _quality");
-    Ok("HIGH".into())
+    Ok(activation.context.stage.quality().as_str().into())
 }
 
 fn set_quality<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: DisplayObject<'gc>,
-    _val: Value<'gc>,
+    val: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    avm_warn!(activation, "Unimplemented property _quality");
+    // Unknown qualities are ignored.
+    if let Ok(quality) = val.coerce_to_string(activation)?.parse() {
+        let stage = activation.context.stage;
+        stage.set_quality(&mut activation.context, quality);
+    }
     Ok(())
 }
 
@@ -11,10 +11,44 @@ bitflags! {
     /// The values are significant and should match the order used by `object::as_set_prop_flags`.
     #[derive(Collect)]
     #[collect(require_static)]
-    pub struct Attribute: u8 {
+    pub struct Attribute: u16 {
         const DONT_ENUM   = 1 << 0;
         const DONT_DELETE = 1 << 1;
         const READ_ONLY   = 1 << 2;
+
+        /// The property only exists for movies of SWF version 6 and up.
+        const VERSION_6   = 1 << 7;
+
+        /// The property only exists for movies of SWF version 7 and up.
+        const VERSION_7   = 1 << 10;
+
+        /// The property only exists for movies of SWF version 8 and up.
+        const VERSION_8   = 1 << 12;
+
+        /// The property only exists for movies of SWF version 9 and up.
+        const VERSION_9   = 1 << 13;
+
+        /// The property only exists for movies of SWF version 10 and up.
+        const VERSION_10  = 1 << 14;
+    }
+}
+
+impl Attribute {
+    /// The lowest SWF version that can see a property with these attributes.
+    pub fn min_swf_version(self) -> u8 {
+        if self.contains(Attribute::VERSION_10) {
+            10
+        } else if self.contains(Attribute::VERSION_9) {
+            9
+        } else if self.contains(Attribute::VERSION_8) {
+            8
+        } else if self.contains(Attribute::VERSION_7) {
+            7
+        } else if self.contains(Attribute::VERSION_6) {
+            6
+        } else {
+            0
+        }
     }
 }
 
@@ -107,6 +141,13 @@ impl<'gc> Property<'gc> {
     pub fn is_virtual(&self) -> bool {
         matches!(self, Property::Virtual { .. })
     }
+
+    /// Whether this property exists for movies of the given SWF version.
+    ///
+    /// Properties hidden from a version act as if they didn't exist at all.
+    pub fn allow_swf_version(&self, swf_version: u8) -> bool {
+        swf_version >= self.attributes().min_swf_version()
+    }
 }
 
 impl fmt::Debug for Property<'_> {
@@ -197,6 +197,25 @@ impl<'gc> Scope<'gc> {
         }
     }
 
+    /// Returns how many `with` scopes are on this scope chain.
+    ///
+    /// Closures don't capture `with` scopes, so within a function this only
+    /// counts the blocks that function has opened itself.
+    pub fn with_depth(&self) -> usize {
+        let mut depth = (self.class == ScopeClass::With) as usize;
+        let mut parent = self.parent;
+
+        while let Some(scope) = parent {
+            let scope = scope.read();
+            if scope.class == ScopeClass::With {
+                depth += 1;
+            }
+            parent = scope.parent;
+        }
+
+        depth
+    }
+
     /// Returns a reference to the current local scope object.
     pub fn locals(&self) -> &Object<'gc> {
         &self.values
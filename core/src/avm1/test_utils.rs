@@ -16,7 +16,10 @@ use crate::display_object::{MovieClip, Stage, TDisplayObject};
 use crate::focus_tracker::FocusTracker;
 use crate::library::Library;
 use crate::loader::LoadManager;
+use crate::local_connection::LocalConnections;
 use crate::prelude::*;
+use crate::site_settings::SiteSettings;
+use crate::streams::StreamManager;
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::vminterface::Instantiator;
 use gc_arena::{rootless_arena, MutationContext};
@@ -65,11 +68,15 @@ where
             player: None,
             load_manager: &mut LoadManager::new(),
             system: &mut SystemProperties::default(),
+            security: &mut Default::default(),
             instance_counter: &mut 0,
             storage: &mut MemoryStorageBackend::default(),
             shared_objects: &mut HashMap::new(),
             unbound_text_fields: &mut Vec::new(),
+            queued_videos: &mut Vec::new(),
             timers: &mut Timers::new(),
+            stream_manager: &mut StreamManager::new(),
+            local_connections: &mut LocalConnections::new(),
             current_context_menu: &mut None,
             needs_render: &mut false,
             avm1: &mut avm1,
@@ -82,6 +89,8 @@ where
             time_offset: &mut 0,
             audio_manager: &mut AudioManager::new(),
             frame_rate: &mut frame_rate,
+            site_settings: &SiteSettings::default(),
+            virtual_clock: &None,
         };
         context.stage.replace_at_depth(&mut context, root, 0);
 
@@ -1,6 +1,22 @@
+use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
+use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::test_utils::with_avm;
-use crate::avm1::TObject;
+use crate::avm1::{Avm1, AvmString, Object, ScriptObject, TObject, Value};
+use crate::backend::navigator::FetchProgress;
+use crate::character::Character;
+use crate::display_object::{Button, EditText, MovieClip};
+use crate::events::{ClipEvent, ClipEventMask};
+use crate::library::MovieLibrary;
+use crate::loader::{LoadManager, Loader, LoaderStatus};
+use crate::player::Player;
+use crate::prelude::*;
+use crate::tag_utils::{SwfMovie, SwfSlice};
+use crate::vminterface::Instantiator;
+use std::sync::Arc;
+use swf::avm1::types::{Action, Value as SwfValue};
+use swf::avm1::write::Writer;
+use swf::SwfStr;
 
 #[test]
 fn locals_into_form_values() {
@@ -23,3 +39,1070 @@ fn locals_into_form_values() {
         Ok(())
     });
 }
+
+/// Place an empty movie clip with an instance name into another clip.
+fn add_child_clip<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    parent: DisplayObject<'gc>,
+    name: &str,
+    depth: Depth,
+) -> DisplayObject<'gc> {
+    let child = MovieClip::new(
+        SwfSlice::empty(parent.movie().unwrap()),
+        activation.context.gc_context,
+    );
+    place_child_clip(activation, parent, child, name, depth)
+}
+
+/// Place a movie clip with an instance name into another clip.
+fn place_child_clip<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    parent: DisplayObject<'gc>,
+    child: MovieClip<'gc>,
+    name: &str,
+    depth: Depth,
+) -> DisplayObject<'gc> {
+    let child: DisplayObject<'gc> = child.into();
+    parent
+        .as_container()
+        .unwrap()
+        .replace_at_depth(&mut activation.context, child, depth);
+    child.set_depth(activation.context.gc_context, depth);
+    child.set_parent(activation.context.gc_context, Some(parent));
+    child.set_name(activation.context.gc_context, name);
+    child.post_instantiation(
+        &mut activation.context,
+        child,
+        None,
+        Instantiator::Movie,
+        false,
+    );
+    child
+}
+
+#[test]
+fn unload_levels() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        activation.resolve_level(1);
+        activation.resolve_level(2);
+
+        let stage = activation.context.stage;
+        stage.unload_level(&mut activation.context, 1);
+        assert!(stage.child_by_depth(1).is_none());
+        assert!(stage.child_by_depth(2).is_some());
+
+        // Unloading `_level0` unloads every level, but keeps an empty root.
+        stage.unload_level(&mut activation.context, 0);
+        assert!(stage.child_by_depth(0).is_some());
+        assert!(stage.child_by_depth(2).is_none());
+
+        Ok(())
+    });
+}
+
+/// Add up `this.base` and all of the arguments.
+fn sum_from_base<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let mut sum = this.get("base", activation)?.coerce_to_f64(activation)?;
+    for arg in args {
+        sum += arg.coerce_to_f64(activation)?;
+    }
+    Ok(sum.into())
+}
+
+#[test]
+fn function_call_and_apply() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        let gc_context = activation.context.gc_context;
+        let sum = FunctionObject::function(
+            gc_context,
+            Executable::Native(sum_from_base),
+            Some(activation.context.avm1.prototypes().function),
+            activation.context.avm1.prototypes().object,
+        );
+        let this: Object<'_> = ScriptObject::object(gc_context, None).into();
+        this.set("base", 100.into(), activation)?;
+        let call = sum.get("call", activation)?;
+        let apply = sum.get("apply", activation)?;
+
+        let args = [this.into(), 1.into(), 2.into()];
+        assert_eq!(call.call("call", activation, sum, None, &args)?, 103.into());
+
+        let array =
+            ScriptObject::array(gc_context, Some(activation.context.avm1.prototypes().array));
+        array.set_array_element(0, 1.into(), gc_context);
+        array.set_array_element(1, 2.into(), gc_context);
+        let args = [this.into(), array.into()];
+        assert_eq!(
+            apply.call("apply", activation, sum, None, &args)?,
+            103.into()
+        );
+
+        // Anything with a length can be applied, and a missing `this` is `_global`.
+        let array_like: Object<'_> = ScriptObject::object(gc_context, None).into();
+        array_like.set("length", 1.into(), activation)?;
+        array_like.set("0", 5.into(), activation)?;
+        activation
+            .context
+            .avm1
+            .global_object_cell()
+            .set("base", 10.into(), activation)?;
+        let args = [Value::Undefined, array_like.into()];
+        assert_eq!(
+            apply.call("apply", activation, sum, None, &args)?,
+            15.into()
+        );
+
+        let args = [this.into(), "not an array".into()];
+        assert_eq!(
+            apply.call("apply", activation, sum, None, &args)?,
+            100.into()
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn changes_mark_cached_bitmaps_dirty() {
+    with_avm(8, |activation, root| -> Result<(), Error> {
+        let root_clip = root.as_display_object().unwrap();
+        let outer = add_child_clip(activation, root_clip, "outer", 1);
+        let inner = add_child_clip(activation, outer, "inner", 1);
+        let clean = || {
+            for object in &[root_clip, outer, inner] {
+                object.set_bitmap_cache_dirty(false);
+            }
+        };
+
+        // Moving an object redraws the bitmaps of its ancestors, but not its own.
+        clean();
+        let object = inner.object().coerce_to_object(activation);
+        object.set("_x", 10.into(), activation)?;
+        assert!(!inner.is_bitmap_cache_dirty());
+        assert!(outer.is_bitmap_cache_dirty());
+        assert!(root_clip.is_bitmap_cache_dirty());
+
+        // Changing the children of an object redraws its own bitmap too.
+        clean();
+        add_child_clip(activation, inner, "child", 1);
+        assert!(inner.is_bitmap_cache_dirty());
+        assert!(outer.is_bitmap_cache_dirty());
+
+        // Untouched branches keep their bitmaps.
+        clean();
+        add_child_clip(activation, root_clip, "sibling", 2);
+        assert!(!outer.is_bitmap_cache_dirty());
+        assert!(root_clip.is_bitmap_cache_dirty());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn copied_display_properties_keep_cache_as_bitmap_flag() {
+    with_avm(8, |activation, root| -> Result<(), Error> {
+        let root_clip = root.as_display_object().unwrap();
+        let filtered = add_child_clip(activation, root_clip, "filtered", 1);
+        let copy = add_child_clip(activation, root_clip, "copy", 2);
+        let gc_context = activation.context.gc_context;
+
+        // Filters cache an object as a bitmap without setting its flag.
+        let blur = swf::BlurFilter {
+            blur_x: 4.0,
+            blur_y: 4.0,
+            num_passes: 1,
+        };
+        filtered.set_filters(gc_context, vec![swf::Filter::BlurFilter(Box::new(blur))]);
+        assert!(filtered.is_bitmap_cached());
+        assert!(!filtered.cache_as_bitmap_flag());
+
+        copy.copy_display_properties_from(gc_context, filtered);
+        assert!(copy.is_bitmap_cached());
+        filtered.set_filters(gc_context, vec![]);
+        copy.set_filters(gc_context, vec![]);
+        assert!(!copy.is_bitmap_cached());
+
+        filtered.set_bitmap_cached(gc_context, true);
+        copy.copy_display_properties_from(gc_context, filtered);
+        assert!(copy.cache_as_bitmap_flag());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn exports_are_found_in_the_movies_an_object_was_loaded_into() {
+    fn is<'gc>(found: Option<&MovieLibrary<'gc>>, expected: &MovieLibrary<'gc>) -> bool {
+        found.map_or(false, |found| std::ptr::eq(found, expected))
+    }
+
+    with_avm(7, |activation, root| -> Result<(), Error> {
+        let gc_context = activation.context.gc_context;
+        let root_clip = root.as_display_object().unwrap();
+        let root_movie = activation.context.swf.clone();
+        let loaded_movie = Arc::new(SwfMovie::empty(7));
+        let other_movie = Arc::new(SwfMovie::empty(7));
+
+        // Both the root movie and the loaded one export `shared`.
+        for (movie, exports) in &[
+            (&root_movie, &["root_only", "shared"]),
+            (&loaded_movie, &["loaded_only", "shared"]),
+        ] {
+            let library = activation
+                .context
+                .library
+                .library_for_movie_mut((*movie).clone());
+            for (id, name) in exports.iter().enumerate() {
+                let id = id as CharacterId + 1;
+                let symbol = MovieClip::new(SwfSlice::empty((*movie).clone()), gc_context);
+                library.register_character(id, Character::MovieClip(symbol));
+                library.register_export(id, name);
+            }
+        }
+        activation
+            .context
+            .library
+            .library_for_movie_mut(other_movie.clone());
+
+        // A clip of the loaded movie, inside the clip it was loaded into.
+        let holder = add_child_clip(activation, root_clip, "holder", 1);
+        let loaded = MovieClip::new(SwfSlice::empty(loaded_movie.clone()), gc_context);
+        let loaded = place_child_clip(activation, holder, loaded, "loaded", 1);
+        // A clip of another movie, outside of `_level0`.
+        let orphan: DisplayObject<'_> =
+            MovieClip::new(SwfSlice::empty(other_movie), gc_context).into();
+
+        let library = &activation.context.library;
+        let root_library = library.library_for_movie(root_movie).unwrap();
+        let loaded_library = library.library_for_movie(loaded_movie).unwrap();
+        let find = |object, name| library.library_for_export(object, root_clip, name);
+
+        // An object's own movie comes first, then the movies it is inside of.
+        assert!(is(find(loaded, "loaded_only"), loaded_library));
+        assert!(is(find(loaded, "shared"), loaded_library));
+        assert!(is(find(loaded, "root_only"), root_library));
+        assert!(is(find(holder, "shared"), root_library));
+        assert!(find(holder, "loaded_only").is_none());
+
+        // Objects outside of `_level0` fall back to its exports.
+        assert!(is(find(orphan, "root_only"), root_library));
+        assert!(find(orphan, "loaded_only").is_none());
+        assert!(find(orphan, "missing").is_none());
+
+        Ok(())
+    });
+}
+
+/// Remember the progress that `onLoadProgress` was broadcast with.
+fn record_load_progress<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    assert_eq!(args.get(0), Some(&"onLoadProgress".into()));
+    let progress = (
+        args.get(2).cloned().unwrap_or(Value::Undefined),
+        args.get(3).cloned().unwrap_or(Value::Undefined),
+    );
+    this.set("loaded", progress.0, activation)?;
+    this.set("total", progress.1, activation)?;
+    Ok(Value::Undefined)
+}
+
+#[test]
+fn movie_loads_report_progress_as_data_arrives() {
+    /// Report the progress of every load, and take the progress that was
+    /// broadcast, if any.
+    fn report_progress<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        broadcaster: Object<'gc>,
+    ) -> Result<(Value<'gc>, Value<'gc>), Error<'gc>> {
+        LoadManager::report_movie_progress(&mut activation.context);
+        let loaded = broadcaster.get("loaded", activation)?;
+        let total = broadcaster.get("total", activation)?;
+        broadcaster.delete(activation, "loaded");
+        broadcaster.delete(activation, "total");
+        Ok((loaded, total))
+    }
+
+    with_avm(7, |activation, root| -> Result<(), Error> {
+        let gc_context = activation.context.gc_context;
+        let broadcaster: Object<'_> = ScriptObject::object(gc_context, None).into();
+        let broadcast_message = FunctionObject::function(
+            gc_context,
+            Executable::Native(record_load_progress),
+            Some(activation.context.avm1.prototypes().function),
+            activation.context.avm1.prototypes().object,
+        );
+        broadcaster.set("broadcastMessage", broadcast_message.into(), activation)?;
+
+        let progress = Arc::new(FetchProgress::default());
+        progress.set_bytes_total(100);
+        activation.context.load_manager.add_loader(Loader::Movie {
+            self_handle: None,
+            target_clip: root.as_display_object().unwrap(),
+            target_broadcaster: Some(broadcaster),
+            loader_status: LoaderStatus::Pending,
+            progress: progress.clone(),
+            reported_bytes_loaded: 0,
+        });
+
+        let nothing = (Value::Undefined, Value::Undefined);
+        assert_eq!(report_progress(activation, broadcaster)?, nothing);
+
+        progress.receive(&[0; 40]);
+        assert_eq!(
+            report_progress(activation, broadcaster)?,
+            (40.into(), 100.into())
+        );
+        assert_eq!(report_progress(activation, broadcaster)?, nothing);
+
+        // The loader reports the whole movie itself, once it's parsed.
+        progress.receive(&[0; 60]);
+        assert_eq!(report_progress(activation, broadcaster)?, nothing);
+
+        Ok(())
+    });
+}
+
+/// Count the `data` events that a clip is sent in `this.data_events`.
+fn count_data_events<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let count = match this.get("data_events", activation)? {
+        Value::Number(count) => count,
+        _ => 0.0,
+    };
+    this.set("data_events", (count + 1.0).into(), activation)?;
+    Ok(Value::Undefined)
+}
+
+/// Place a clip into `root` that counts the `data` events it's sent.
+fn add_data_event_counter<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    root: Object<'gc>,
+) -> Result<(DisplayObject<'gc>, Object<'gc>), Error<'gc>> {
+    let root_clip = root.as_display_object().unwrap();
+    let clip = add_child_clip(activation, root_clip, "target", 1);
+    let object = clip.object().coerce_to_object(activation);
+    let on_data = FunctionObject::function(
+        activation.context.gc_context,
+        Executable::Native(count_data_events),
+        Some(activation.context.avm1.prototypes().function),
+        activation.context.avm1.prototypes().object,
+    );
+    object.set("onData", on_data.into(), activation)?;
+    Ok((clip, object))
+}
+
+#[test]
+fn movie_loads_send_data_events_as_data_arrives() {
+    /// Report the progress of every load, and count the `data` events sent.
+    fn data_events<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        object: Object<'gc>,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        LoadManager::report_movie_progress(&mut activation.context);
+        Player::run_actions(&mut activation.context);
+        object.get("data_events", activation)
+    }
+
+    with_avm(7, |activation, root| -> Result<(), Error> {
+        let (clip, object) = add_data_event_counter(activation, root)?;
+        let progress = Arc::new(FetchProgress::default());
+        progress.set_bytes_total(100);
+        activation.context.load_manager.add_loader(Loader::Movie {
+            self_handle: None,
+            target_clip: clip,
+            target_broadcaster: None,
+            loader_status: LoaderStatus::Pending,
+            progress: progress.clone(),
+            reported_bytes_loaded: 0,
+        });
+
+        // Each part of the movie that arrives is reported once.
+        assert_eq!(data_events(activation, object)?, Value::Undefined);
+        progress.receive(&[0; 40]);
+        assert_eq!(data_events(activation, object)?, 1.into());
+        assert_eq!(data_events(activation, object)?, 1.into());
+        progress.receive(&[0; 20]);
+        assert_eq!(data_events(activation, object)?, 2.into());
+
+        // The loader sends the last event itself, once the movie is parsed.
+        progress.receive(&[0; 40]);
+        assert_eq!(data_events(activation, object)?, 2.into());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn form_loads_send_a_data_event_to_clips() {
+    with_avm(7, |activation, root| -> Result<(), Error> {
+        let (_clip, object) = add_data_event_counter(activation, root)?;
+        let handle = activation.context.load_manager.add_loader(Loader::Form {
+            self_handle: None,
+            target_object: object,
+        });
+
+        Loader::form_loaded(&mut activation.context, handle, b"a=1&b=two").unwrap();
+        Player::run_actions(&mut activation.context);
+        assert_eq!(object.get("a", activation)?, "1".into());
+        assert_eq!(object.get("b", activation)?, "two".into());
+        assert_eq!(object.get("data_events", activation)?, 1.into());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn as_set_prop_flags() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        let globals = activation.context.avm1.global_object_cell();
+        let as_set_prop_flags = globals.get("ASSetPropFlags", activation)?;
+        let object: Object<'_> = ScriptObject::object(activation.context.gc_context, None).into();
+        for name in &["a", "b", "c"] {
+            object.set(name, 1.into(), activation)?;
+        }
+
+        // Hide and protect two of the properties.
+        let args = [object.into(), "a,b".into(), 7.into(), 0.into()];
+        as_set_prop_flags.call("ASSetPropFlags", activation, globals, None, &args)?;
+        assert_eq!(object.get_keys(activation), vec!["c".to_string()]);
+        object.set("a", 2.into(), activation)?;
+        assert_eq!(object.get("a", activation)?, 1.into());
+        assert!(!object.delete(activation, "b"));
+
+        // Clear read-only from every property.
+        let args = [object.into(), Value::Null, 0.into(), 4.into()];
+        as_set_prop_flags.call("ASSetPropFlags", activation, globals, None, &args)?;
+        object.set("a", 2.into(), activation)?;
+        assert_eq!(object.get("a", activation)?, 2.into());
+        assert_eq!(object.get_keys(activation), vec!["c".to_string()]);
+
+        // Properties for SWF 7 and up don't exist in a SWF 6 movie, until
+        // they're replaced.
+        let args = [object.into(), "c".into(), 1024.into(), 0.into()];
+        as_set_prop_flags.call("ASSetPropFlags", activation, globals, None, &args)?;
+        assert!(!object.has_own_property(activation, "c"));
+        assert_eq!(object.get("c", activation)?, Value::Undefined);
+        assert!(object.get_keys(activation).is_empty());
+        object.set("c", 3.into(), activation)?;
+        assert_eq!(object.get("c", activation)?, 3.into());
+        assert_eq!(object.get_keys(activation), vec!["c".to_string()]);
+
+        Ok(())
+    });
+}
+
+/// Run a block of bytecode, and return the value it left on top of the stack.
+fn run_bytecode<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    actions: &[Action],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let mut data = Vec::new();
+    let mut writer = Writer::new(&mut data, activation.swf_version());
+    for action in actions {
+        writer.write_action(action).unwrap();
+    }
+    data.push(0); // ActionEnd
+
+    let swf = activation.context.swf.clone();
+    let movie = Arc::new(swf.from_movie_and_subdata(data, &swf));
+    activation.run_actions(SwfSlice::from(movie))?;
+    Ok(activation.context.avm1.pop())
+}
+
+/// Return the number of arguments, or `this.name` when called with none.
+fn count_args<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if args.is_empty() {
+        this.get("name", activation)
+    } else {
+        Ok(args.len().into())
+    }
+}
+
+/// Define a global object `obj` with a `method` that counts its arguments.
+fn define_counting_object<'gc>(activation: &mut Activation<'_, 'gc, '_>) -> Result<(), Error<'gc>> {
+    let gc_context = activation.context.gc_context;
+    let count = FunctionObject::function(
+        gc_context,
+        Executable::Native(count_args),
+        Some(activation.context.avm1.prototypes().function),
+        activation.context.avm1.prototypes().object,
+    );
+    let object: Object<'_> = ScriptObject::object(gc_context, None).into();
+    object.set("name", "obj".into(), activation)?;
+    object.set("method", count.into(), activation)?;
+    let globals = activation.context.avm1.global_object_cell();
+    globals.set("obj", object.into(), activation)?;
+    globals.set("count", count.into(), activation)?;
+    Ok(())
+}
+
+fn swf_str(s: &str) -> SwfValue<'_> {
+    SwfValue::Str(SwfStr::from_utf8_str(s))
+}
+
+#[test]
+fn call_method_name_from_register() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        define_counting_object(activation)?;
+
+        let result = run_bytecode(
+            activation,
+            &[
+                Action::Push(vec![swf_str("method")]),
+                Action::StoreRegister(1),
+                Action::Pop,
+                Action::Push(vec![SwfValue::Int(7), SwfValue::Int(1), swf_str("obj")]),
+                Action::GetVariable,
+                Action::Push(vec![SwfValue::Register(1)]),
+                Action::CallMethod,
+            ],
+        )?;
+        assert_eq!(result, 1.into());
+
+        // An unset register is an undefined name, which calls the object itself.
+        let result = run_bytecode(
+            activation,
+            &[
+                Action::Push(vec![SwfValue::Int(7), SwfValue::Int(1), swf_str("count")]),
+                Action::GetVariable,
+                Action::Push(vec![SwfValue::Register(2)]),
+                Action::CallMethod,
+            ],
+        )?;
+        assert_eq!(result, 1.into());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn call_method_with_empty_name() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        define_counting_object(activation)?;
+
+        let result = run_bytecode(
+            activation,
+            &[
+                Action::Push(vec![
+                    SwfValue::Int(7),
+                    SwfValue::Int(8),
+                    SwfValue::Int(2),
+                    swf_str("count"),
+                ]),
+                Action::GetVariable,
+                Action::Push(vec![swf_str("")]),
+                Action::CallMethod,
+            ],
+        )?;
+        assert_eq!(result, 2.into());
+
+        // Methods of undefined and null aren't called at all.
+        let result = run_bytecode(
+            activation,
+            &[
+                Action::Push(vec![SwfValue::Int(0), SwfValue::Null, swf_str("method")]),
+                Action::CallMethod,
+            ],
+        )?;
+        assert_eq!(result, Value::Undefined);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn call_with_bogus_argument_counts() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        define_counting_object(activation)?;
+
+        // Only the arguments on the stack are taken.
+        let result = run_bytecode(
+            activation,
+            &[
+                Action::Push(vec![
+                    SwfValue::Int(7),
+                    SwfValue::Int(8),
+                    SwfValue::Double(1e15),
+                    swf_str("obj"),
+                ]),
+                Action::GetVariable,
+                Action::Push(vec![swf_str("method")]),
+                Action::CallMethod,
+            ],
+        )?;
+        assert_eq!(result, 2.into());
+
+        // Negative and NaN counts are no arguments.
+        for count in &[SwfValue::Int(-5), SwfValue::Double(f64::NAN)] {
+            let result = run_bytecode(
+                activation,
+                &[
+                    Action::Push(vec![count.clone(), swf_str("obj")]),
+                    Action::GetVariable,
+                    Action::Push(vec![swf_str("method")]),
+                    Action::CallMethod,
+                ],
+            )?;
+            assert_eq!(result, "obj".into());
+
+            let result = run_bytecode(
+                activation,
+                &[
+                    Action::Push(vec![count.clone(), swf_str("count")]),
+                    Action::CallFunction,
+                ],
+            )?;
+            assert_eq!(result, Value::Undefined);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn constant_pool_out_of_range() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        let result = run_bytecode(
+            activation,
+            &[
+                Action::ConstantPool(vec![SwfStr::from_utf8_str("a")]),
+                Action::Push(vec![SwfValue::ConstantPool(0)]),
+            ],
+        )?;
+        assert_eq!(result, "a".into());
+
+        let result = run_bytecode(
+            activation,
+            &[
+                Action::ConstantPool(vec![SwfStr::from_utf8_str("a")]),
+                Action::Push(vec![SwfValue::ConstantPool(1)]),
+            ],
+        )?;
+        assert_eq!(result, Value::Undefined);
+
+        Ok(())
+    });
+}
+
+/// Set `this.base` to 1.
+fn set_base<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    this.set("base", 1.into(), activation)?;
+    Ok(Value::Undefined)
+}
+
+/// Define `function Sub() { super(); this.sub = this.base + 1; }`, extending
+/// a native `Base` class whose constructor sets `this.base` to 1.
+fn define_sub_class<'gc>(activation: &mut Activation<'_, 'gc, '_>) -> Result<(), Error<'gc>> {
+    let gc_context = activation.context.gc_context;
+    let base_prototype = ScriptObject::object(
+        gc_context,
+        Some(activation.context.avm1.prototypes().movie_clip),
+    );
+    let base = FunctionObject::function(
+        gc_context,
+        Executable::Native(set_base),
+        Some(activation.context.avm1.prototypes().function),
+        base_prototype.into(),
+    );
+    let globals = activation.context.avm1.global_object_cell();
+    globals.set("Base", base.into(), activation)?;
+
+    let mut body = Vec::new();
+    let mut writer = Writer::new(&mut body, activation.swf_version());
+    for action in &[
+        Action::Push(vec![SwfValue::Int(0), swf_str("super")]),
+        Action::GetVariable,
+        Action::Push(vec![SwfValue::Undefined]),
+        Action::CallMethod,
+        Action::Pop,
+        Action::Push(vec![swf_str("this")]),
+        Action::GetVariable,
+        Action::Push(vec![swf_str("sub"), swf_str("this")]),
+        Action::GetVariable,
+        Action::Push(vec![swf_str("base")]),
+        Action::GetMember,
+        Action::Push(vec![SwfValue::Int(1)]),
+        Action::Add2,
+        Action::SetMember,
+    ] {
+        writer.write_action(action).unwrap();
+    }
+
+    run_bytecode(
+        activation,
+        &[
+            Action::DefineFunction {
+                name: SwfStr::from_utf8_str("Sub"),
+                params: vec![],
+                actions: &body,
+            },
+            Action::Push(vec![swf_str("Sub")]),
+            Action::GetVariable,
+            Action::Push(vec![swf_str("Base")]),
+            Action::GetVariable,
+            Action::Extends,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Call `Object.registerClass(export_name, class)`.
+fn register_class<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    export_name: &str,
+    class: Value<'gc>,
+) -> Result<(), Error<'gc>> {
+    let globals = activation.context.avm1.global_object_cell();
+    let object = globals
+        .get("Object", activation)?
+        .coerce_to_object(activation);
+    let export_name = AvmString::new(activation.context.gc_context, export_name);
+    object.call_method("registerClass", &[export_name.into(), class], activation)?;
+    Ok(())
+}
+
+/// Attach the symbol exported as `export_name` to `root`.
+fn attach<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    root: Object<'gc>,
+    export_name: &str,
+    depth: i32,
+) -> Result<Object<'gc>, Error<'gc>> {
+    let gc_context = activation.context.gc_context;
+    let args = [
+        AvmString::new(gc_context, export_name).into(),
+        AvmString::new(gc_context, format!("inst{}", depth)).into(),
+        depth.into(),
+    ];
+    Ok(root
+        .call_method("attachMovie", &args, activation)?
+        .coerce_to_object(activation))
+}
+
+#[test]
+fn registered_class_calls_super() {
+    with_avm(7, |activation, root| -> Result<(), Error> {
+        // Export an empty clip from the root movie as `Sym`.
+        let movie = activation.context.swf.clone();
+        let symbol = MovieClip::new_with_data(
+            activation.context.gc_context,
+            1,
+            SwfSlice::empty(movie.clone()),
+            1,
+        );
+        let library = activation.context.library.library_for_movie_mut(movie);
+        library.register_character(1, Character::MovieClip(symbol));
+        library.register_export(1, "Sym");
+
+        define_sub_class(activation)?;
+        let globals = activation.context.avm1.global_object_cell();
+        let sub = globals.get("Sub", activation)?;
+        register_class(activation, "Sym", sub)?;
+
+        // The constructor chain runs as soon as the clip is attached.
+        let inst = attach(activation, root, "Sym", 1)?;
+        assert_eq!(inst.get("base", activation)?, 1.into());
+        assert_eq!(inst.get("sub", activation)?, 2.into());
+
+        // Registering `null` unlinks the class again.
+        register_class(activation, "Sym", Value::Null)?;
+        let plain = attach(activation, root, "Sym", 2)?;
+        assert_eq!(plain.get("sub", activation)?, Value::Undefined);
+
+        Ok(())
+    });
+}
+
+/// Export an empty button as `Btn` and a text field as `Txt` from the root
+/// movie, and register `Sub` for both.
+fn export_button_and_text_field<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+) -> Result<(), Error<'gc>> {
+    let movie = activation.context.swf.clone();
+    let button = Button::from_swf_tag(
+        &swf::Button {
+            id: 1,
+            is_track_as_menu: false,
+            records: vec![],
+            actions: vec![],
+        },
+        &SwfSlice::empty(movie.clone()),
+        activation.context.library,
+        activation.context.gc_context,
+    );
+    let text_field = EditText::from_swf_tag(
+        &mut activation.context,
+        movie.clone(),
+        swf::EditText {
+            id: 2,
+            bounds: Default::default(),
+            font_id: None,
+            font_class_name: None,
+            height: None,
+            color: None,
+            max_length: None,
+            layout: None,
+            variable_name: "".into(),
+            initial_text: None,
+            is_word_wrap: false,
+            is_multiline: false,
+            is_password: false,
+            is_read_only: true,
+            is_auto_size: false,
+            is_selectable: true,
+            has_border: false,
+            was_static: false,
+            is_html: false,
+            is_device_font: false,
+        },
+    );
+    let library = activation.context.library.library_for_movie_mut(movie);
+    library.register_character(1, Character::Button(button));
+    library.register_export(1, "Btn");
+    library.register_character(2, Character::EditText(text_field));
+    library.register_export(2, "Txt");
+
+    define_sub_class(activation)?;
+    let globals = activation.context.avm1.global_object_cell();
+    let sub = globals.get("Sub", activation)?;
+    register_class(activation, "Btn", sub)?;
+    register_class(activation, "Txt", sub)?;
+    Ok(())
+}
+
+#[test]
+fn registered_class_applies_to_buttons_and_text_fields() {
+    with_avm(9, |activation, root| -> Result<(), Error> {
+        export_button_and_text_field(activation)?;
+
+        // Both are constructed as soon as they are attached.
+        for (export_name, depth) in &[("Btn", 1), ("Txt", 2)] {
+            let inst = attach(activation, root, export_name, *depth)?;
+            assert_eq!(inst.get("base", activation)?, 1.into());
+            assert_eq!(inst.get("sub", activation)?, 2.into());
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn registered_class_ignores_buttons_and_text_fields_before_swf9() {
+    with_avm(8, |activation, root| -> Result<(), Error> {
+        export_button_and_text_field(activation)?;
+
+        for (export_name, depth) in &[("Btn", 1), ("Txt", 2)] {
+            let inst = attach(activation, root, export_name, *depth)?;
+            assert_eq!(inst.get("sub", activation)?, Value::Undefined);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn interpolation_skips_placed_and_teleported_objects() {
+    with_avm(8, |activation, root| -> Result<(), Error> {
+        let root_clip = root.as_display_object().unwrap();
+        let clip = add_child_clip(activation, root_clip, "clip", 1);
+        let gc_context = activation.context.gc_context;
+
+        // A clip placed this frame is drawn where it ends up.
+        clip.set_x(gc_context, 10.0);
+        assert!(!clip.start_interpolation(gc_context));
+        assert_eq!(clip.interpolate_from(), None);
+
+        clip.set_x(gc_context, 20.0);
+        clip.set_y(gc_context, 15.0);
+        assert!(clip.start_interpolation(gc_context));
+        assert_eq!(
+            clip.interpolate_from(),
+            Some((Twips::from_pixels(10.0), Twips::zero()))
+        );
+
+        // A clip that isn't moved again stops moving.
+        assert!(!clip.start_interpolation(gc_context));
+
+        // Coming back to where it was isn't a move.
+        clip.set_x(gc_context, 30.0);
+        clip.set_x(gc_context, 20.0);
+        assert!(!clip.start_interpolation(gc_context));
+
+        // A clip that jumps across the stage isn't smeared across the jump.
+        clip.set_x(gc_context, 1000.0);
+        assert!(!clip.start_interpolation(gc_context));
+        clip.set_x(gc_context, 1010.0);
+        assert!(clip.start_interpolation(gc_context));
+
+        // Neither is a clip whose whole matrix is replaced, or that is placed
+        // again.
+        clip.set_x(gc_context, 1020.0);
+        let matrix = *clip.matrix();
+        clip.set_matrix(gc_context, &matrix);
+        assert!(!clip.start_interpolation(gc_context));
+        clip.set_x(gc_context, 1030.0);
+        clip.set_place_frame(gc_context, 1);
+        assert!(!clip.start_interpolation(gc_context));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn execution_list_iteration_follows_changes() {
+    with_avm(8, |activation, root| -> Result<(), Error> {
+        let root_clip = root.as_display_object().unwrap();
+        let parent = add_child_clip(activation, root_clip, "parent", 1);
+        let mut container = parent.as_container().unwrap();
+        let a = add_child_clip(activation, parent, "a", 1);
+        let b = add_child_clip(activation, parent, "b", 2);
+        let c = add_child_clip(activation, parent, "c", 3);
+        let d = add_child_clip(activation, parent, "d", 4);
+
+        // Children run in the reverse of the order they were added in.
+        let order: Vec<_> = container.iter_execution_list().collect();
+        assert_eq!(order.len(), 4);
+        for (child, expected) in order.into_iter().zip([d, c, b, a].iter()) {
+            assert!(DisplayObject::ptr_eq(child, *expected));
+        }
+
+        let mut iter = container.iter_execution_list();
+        assert!(DisplayObject::ptr_eq(iter.next().unwrap(), d));
+
+        // Children removed before they are reached are skipped, removing the
+        // child that just ran doesn't lose the iterator's place, and children
+        // added along the way wait for the next pass.
+        container.remove_child(&mut activation.context, b, Lists::all());
+        let e = add_child_clip(activation, parent, "e", 5);
+        container.remove_child(&mut activation.context, d, Lists::all());
+        assert!(DisplayObject::ptr_eq(iter.next().unwrap(), c));
+        assert!(DisplayObject::ptr_eq(iter.next().unwrap(), a));
+        assert!(iter.next().is_none());
+
+        let order: Vec<_> = container.iter_execution_list().collect();
+        assert_eq!(order.len(), 3);
+        for (child, expected) in order.into_iter().zip([e, c, a].iter()) {
+            assert!(DisplayObject::ptr_eq(child, *expected));
+        }
+
+        Ok(())
+    });
+}
+
+/// Count the calls of a method in `this.calls`.
+fn count_calls<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let count = match this.get("calls", activation)? {
+        Value::Number(count) => count,
+        _ => 0.0,
+    };
+    this.set("calls", (count + 1.0).into(), activation)?;
+    Ok(Value::Undefined)
+}
+
+#[test]
+fn clip_events_only_propagate_to_clips_that_handle_them() {
+    fn interest<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        object: DisplayObject<'gc>,
+    ) -> ClipEventMask {
+        object.clip_event_interest(&mut activation.context)
+    }
+
+    with_avm(8, |activation, root| -> Result<(), Error> {
+        let root_clip = root.as_display_object().unwrap();
+        let parent = add_child_clip(activation, root_clip, "parent", 1);
+        let child = add_child_clip(activation, parent, "child", 1);
+        let other = add_child_clip(activation, root_clip, "other", 2);
+        assert_eq!(interest(activation, parent), ClipEventMask::empty());
+
+        // A handler assigned by a script makes the clip and its ancestors
+        // interested in the event.
+        let handler = FunctionObject::function(
+            activation.context.gc_context,
+            Executable::Native(count_calls),
+            Some(activation.context.avm1.prototypes().function),
+            activation.context.avm1.prototypes().object,
+        );
+        let child_object = child.object().coerce_to_object(activation);
+        child_object.set("onMouseMove", handler.into(), activation)?;
+        assert_eq!(interest(activation, parent), ClipEventMask::MOUSE_MOVE);
+        assert!(interest(activation, root_clip).contains(ClipEventMask::MOUSE_MOVE));
+        assert_eq!(interest(activation, other), ClipEventMask::empty());
+
+        root_clip.handle_clip_event(&mut activation.context, ClipEvent::MouseMove);
+        Player::run_actions(&mut activation.context);
+        assert_eq!(child_object.get("calls", activation)?, 1.into());
+
+        // Clips of a registered class may inherit any handler from it.
+        let movie_clip_proto = activation.context.avm1.prototypes().movie_clip;
+        let class_proto: Object<'_> =
+            ScriptObject::object(activation.context.gc_context, Some(movie_clip_proto)).into();
+        let other_object = other.object().coerce_to_object(activation);
+        other_object.set("__proto__", class_proto.into(), activation)?;
+        assert_eq!(interest(activation, other), ClipEventMask::METHODS);
+
+        // Removing the clip with the handler leaves its parent uninterested.
+        parent
+            .as_container()
+            .unwrap()
+            .remove_child(&mut activation.context, child, Lists::all());
+        assert_eq!(interest(activation, parent), ClipEventMask::empty());
+
+        // Handlers on `MovieClip.prototype` apply to every clip.
+        movie_clip_proto.set("onMouseDown", handler.into(), activation)?;
+        Avm1::update_prototype_clip_events(&mut activation.context, ClipEvent::MouseDown);
+        assert_eq!(
+            activation.context.avm1.prototype_clip_events(),
+            ClipEventMask::MOUSE_DOWN
+        );
+        Avm1::update_prototype_clip_events(&mut activation.context, ClipEvent::MouseUp);
+        assert_eq!(
+            activation.context.avm1.prototype_clip_events(),
+            ClipEventMask::empty()
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn version_is_global() {
+    with_avm(6, |activation, _root| -> Result<(), Error> {
+        let expected = activation
+            .context
+            .system
+            .get_version_string(activation.context.avm1);
+
+        // The test timeline isn't a root movie, so this has to come from
+        // `_global`.
+        let version: Value = activation.get_variable("$version")?.into();
+        assert_eq!(
+            version.coerce_to_string(activation)?.as_str(),
+            expected.as_str()
+        );
+
+        Ok(())
+    });
+}
@@ -4,7 +4,6 @@ use crate::avm2::globals::SystemPrototypes;
 use crate::avm2::method::Method;
 use crate::avm2::object::EventObject;
 use crate::avm2::script::{Script, TranslationUnit};
-use crate::avm2::string::AvmString;
 use crate::context::UpdateContext;
 use crate::tag_utils::SwfSlice;
 use gc_arena::{Collect, MutationContext};
@@ -42,6 +41,7 @@ mod slot;
 mod string;
 mod traits;
 mod value;
+mod vector;
 
 pub use crate::avm2::activation::Activation;
 pub use crate::avm2::array::ArrayStorage;
@@ -49,9 +49,10 @@ pub use crate::avm2::domain::Domain;
 pub use crate::avm2::events::Event;
 pub use crate::avm2::names::{Namespace, QName};
 pub use crate::avm2::object::{ArrayObject, Object, ScriptObject, StageObject, TObject};
+pub use crate::avm2::string::AvmString;
 pub use crate::avm2::value::Value;
 
-const BROADCAST_WHITELIST: [&str; 3] = ["enterFrame", "exitFrame", "frameConstructed"];
+const BROADCAST_WHITELIST: [&str; 4] = ["enterFrame", "exitFrame", "frameConstructed", "render"];
 
 /// Boxed error alias.
 ///
@@ -59,6 +60,22 @@ const BROADCAST_WHITELIST: [&str; 3] = ["enterFrame", "exitFrame", "frameConstru
 /// with a proper Avm2Error enum.
 pub type Error = Box<dyn std::error::Error>;
 
+/// An error raised by an ActionScript `throw`.
+///
+/// Boxed errors cannot hold garbage-collected values, so the thrown value
+/// itself is held by the interpreter (see `Avm2::thrown_value`) until an
+/// exception handler catches it.
+#[derive(Debug)]
+pub struct ThrownValue;
+
+impl std::fmt::Display for ThrownValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Uncaught ActionScript exception")
+    }
+}
+
+impl std::error::Error for ThrownValue {}
+
 /// The state of an AVM2 interpreter.
 #[derive(Collect)]
 #[collect(no_drop)]
@@ -82,6 +99,12 @@ pub struct Avm2<'gc> {
     /// collector does not support weak references.
     broadcast_list: HashMap<AvmString<'gc>, Vec<Object<'gc>>>,
 
+    /// The value most recently thrown by ActionScript code.
+    ///
+    /// This is set whenever a `ThrownValue` error is raised, and taken once
+    /// the exception is caught or escapes to the player.
+    thrown_value: Option<Value<'gc>>,
+
     #[cfg(feature = "avm_debug")]
     pub debug_output: bool,
 }
@@ -96,6 +119,7 @@ impl<'gc> Avm2<'gc> {
             globals,
             system_prototypes: None,
             broadcast_list: HashMap::new(),
+            thrown_value: None,
 
             #[cfg(feature = "avm_debug")]
             debug_output: false,
@@ -123,16 +147,12 @@ impl<'gc> Avm2<'gc> {
         let mut init_activation = Activation::from_script(context.reborrow(), script)?;
 
         let (method, scope) = script.init();
-        match method {
-            Method::Native(nf) => {
-                nf(&mut init_activation, Some(scope), &[])?;
-            }
-            Method::Entry(_) => {
-                init_activation.run_stack_frame_for_script(script)?;
-            }
+        let result = match method {
+            Method::Native(nf) => nf(&mut init_activation, Some(scope), &[]).map(|_| ()),
+            Method::Entry(_) => init_activation.run_stack_frame_for_script(script),
         };
 
-        Ok(())
+        Self::forget_uncaught(context, result)
     }
 
     /// Dispatch an event on an object.
@@ -143,12 +163,26 @@ impl<'gc> Avm2<'gc> {
         event: Event<'gc>,
         target: Object<'gc>,
     ) -> Result<bool, Error> {
-        use crate::avm2::events::dispatch_event;
         let event_proto = context.avm2.system_prototypes.as_ref().unwrap().event;
         let event_object = EventObject::from_event(context.gc_context, Some(event_proto), event);
+
+        Self::dispatch_event_object(context, event_object, target)
+    }
+
+    /// Dispatch an already constructed event object on an object, such as an
+    /// instance of a subclass of `Event`.
+    ///
+    /// The `bool` parameter reads true if the event was cancelled.
+    pub fn dispatch_event_object(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        event: Object<'gc>,
+        target: Object<'gc>,
+    ) -> Result<bool, Error> {
+        use crate::avm2::events::dispatch_event;
         let mut activation = Activation::from_nothing(context.reborrow());
+        let result = dispatch_event(&mut activation, target, event);
 
-        dispatch_event(&mut activation, target, event_object)
+        Self::forget_uncaught(context, result)
     }
 
     /// Add an object to the broadcast list.
@@ -229,14 +263,29 @@ impl<'gc> Avm2<'gc> {
         context: &mut UpdateContext<'_, 'gc, '_>,
     ) -> Result<(), Error> {
         let mut evt_activation = Activation::from_nothing(context.reborrow());
-        callable.call(
+        let result = callable.call(
             reciever,
             args,
             &mut evt_activation,
             reciever.and_then(|r| r.proto()),
-        )?;
+        );
 
-        Ok(())
+        Self::forget_uncaught(context, result.map(|_| ()))
+    }
+
+    /// Drop any value thrown by a script that nothing caught.
+    ///
+    /// Once an exception has escaped to the player, no handler will ever take
+    /// its value, and it must not be mistaken for a later exception's.
+    fn forget_uncaught<T>(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        result: Result<T, Error>,
+    ) -> Result<T, Error> {
+        if result.is_err() {
+            context.avm2.thrown_value = None;
+        }
+
+        result
     }
 
     /// Load an ABC file embedded in a `SwfSlice`.
@@ -289,6 +338,17 @@ impl<'gc> Avm2<'gc> {
         value
     }
 
+    /// Retrieve a value from the operand stack without popping it, counting
+    /// down from the top-most value.
+    fn peek(&self, index: usize) -> Value<'gc> {
+        self.stack
+            .len()
+            .checked_sub(index + 1)
+            .and_then(|index| self.stack.get(index))
+            .cloned()
+            .unwrap_or(Value::Undefined)
+    }
+
     fn pop_args(&mut self, arg_count: u32) -> Vec<Value<'gc>> {
         let mut args = Vec::with_capacity(arg_count as usize);
         args.resize(arg_count as usize, Value::Undefined);
@@ -318,3 +378,35 @@ impl<'gc> Avm2<'gc> {
     #[cfg(not(feature = "avm_debug"))]
     pub const fn set_show_debug_output(&self, _visible: bool) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::avm2::object::FunctionObject;
+
+    fn throw_string<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        _this: Option<Object<'gc>>,
+        _args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error> {
+        activation.context.avm2.thrown_value = Some("uncaught".into());
+        Err(ThrownValue.into())
+    }
+
+    #[test]
+    fn uncaught_values_are_dropped() {
+        with_avm(9, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let mc = activation.context.gc_context;
+            let fn_proto = ScriptObject::bare_object(mc);
+            let callable = FunctionObject::from_builtin(mc, throw_string, fn_proto);
+
+            let result =
+                Avm2::run_stack_frame_for_callable(callable, None, &[], &mut activation.context);
+
+            assert!(result.unwrap_err().is::<ThrownValue>());
+            assert!(activation.context.avm2.thrown_value.is_none());
+            Ok(())
+        });
+    }
+}
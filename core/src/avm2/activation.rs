@@ -2,27 +2,29 @@
 
 use crate::avm2::array::ArrayStorage;
 use crate::avm2::class::Class;
+use crate::avm2::globals::{error, vector, xml};
 use crate::avm2::method::BytecodeMethod;
 use crate::avm2::method::Method;
 use crate::avm2::names::{Multiname, Namespace, QName};
 use crate::avm2::object::{
-    ArrayObject, ByteArrayObject, FunctionObject, NamespaceObject, ScriptObject,
+    ArrayObject, ByteArrayObject, DictionaryObject, FunctionObject, NamespaceObject, ScriptObject,
 };
 use crate::avm2::object::{Object, TObject};
 use crate::avm2::scope::Scope;
 use crate::avm2::script::Script;
 use crate::avm2::string::AvmString;
 use crate::avm2::value::Value;
-use crate::avm2::{value, Avm2, Error};
+use crate::avm2::{value, Avm2, Error, ThrownValue};
 use crate::context::UpdateContext;
+use crate::execution_position;
 use crate::swf::extensions::ReadSwfExt;
 use gc_arena::{Gc, GcCell, MutationContext};
 use smallvec::SmallVec;
 use std::convert::TryInto;
 use swf::avm2::read::Reader;
 use swf::avm2::types::{
-    Class as AbcClass, Index, Method as AbcMethod, Multiname as AbcMultiname,
-    Namespace as AbcNamespace, Op,
+    Class as AbcClass, Exception as AbcException, Index, Method as AbcMethod,
+    Multiname as AbcMultiname, Namespace as AbcNamespace, Op,
 };
 
 /// Represents a particular register set.
@@ -505,6 +507,45 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Multiname::from_abc_multiname(method.translation_unit(), index, self)
     }
 
+    /// Pop the dictionary and object key of a late-bound property access off
+    /// the stack.
+    ///
+    /// Dictionaries name properties with objects by the object's identity, so
+    /// those accesses can't go through multiname resolution, which would
+    /// coerce the key to a string. If the multiname doesn't take its name
+    /// from the stack, the name isn't an object, or the object being accessed
+    /// isn't a dictionary, the stack is left untouched.
+    fn pop_dictionary_key(
+        &mut self,
+        method: Gc<'gc, BytecodeMethod<'gc>>,
+        index: Index<AbcMultiname>,
+    ) -> Option<(DictionaryObject<'gc>, Object<'gc>)> {
+        let abc = method.translation_unit().abc();
+        let is_late_named = matches!(
+            (index.0 as usize)
+                .checked_sub(1)
+                .and_then(|index| abc.constant_pool.multinames.get(index)),
+            Some(AbcMultiname::MultinameL { .. }) | Some(AbcMultiname::MultinameLA { .. })
+        );
+        if !is_late_named {
+            return None;
+        }
+
+        let key = match self.context.avm2.peek(0) {
+            Value::Object(key) => key,
+            _ => return None,
+        };
+        let dictionary = match self.context.avm2.peek(1) {
+            Value::Object(object) => object.as_dictionary_object()?,
+            _ => return None,
+        };
+
+        self.context.avm2.pop();
+        self.context.avm2.pop();
+
+        Some((dictionary, key))
+    }
+
     /// Retrieve a static, or non-runtime, multiname from the current constant
     /// pool.
     fn pool_multiname_static(
@@ -548,16 +589,118 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         let body = body?;
         let mut reader = Reader::new(&body.code);
 
+        // Exception handlers start with the operand and scope stacks as they
+        // were when this frame started executing.
+        let stack_depth = self.context.avm2.stack.len();
+        let scope = self.scope;
+        let _position = execution_position::enter_avm2_method();
+
         loop {
+            let instruction_start = reader.pos(&body.code);
+            execution_position::set_avm2_op(method.abc_method, instruction_start);
             let result = self.do_next_opcode(method, &mut reader, &body.code);
             match result {
                 Ok(FrameControl::Return(value)) => break Ok(value),
                 Ok(FrameControl::Continue) => {}
-                Err(e) => break Err(e),
+                Err(e) => {
+                    let (target, thrown) = match self.find_exception_handler(
+                        method,
+                        &body.exceptions,
+                        instruction_start,
+                        e,
+                    ) {
+                        Ok(handler) => handler,
+                        Err(e) => break Err(e),
+                    };
+
+                    self.context.avm2.stack.truncate(stack_depth);
+                    self.scope = scope;
+                    self.context.avm2.push(thrown);
+
+                    let target = (target as usize).min(body.code.len());
+                    reader = Reader::new(&body.code[target..]);
+                }
             }
         }
     }
 
+    /// Find the exception handler in a method body that catches an error
+    /// raised by the instruction at `offset`.
+    ///
+    /// Returns the offset of the handler's code, along with the value it
+    /// catches. Errors raised by the interpreter itself are caught as `Error`
+    /// objects. If no handler applies, the error is returned so that it may
+    /// propagate to the calling frame.
+    fn find_exception_handler(
+        &mut self,
+        method: Gc<'gc, BytecodeMethod<'gc>>,
+        exceptions: &[AbcException],
+        offset: usize,
+        error: Error,
+    ) -> Result<(u32, Value<'gc>), Error> {
+        let offset = offset as u32;
+        let covers = |e: &AbcException| e.from_offset <= offset && offset < e.to_offset;
+        if !exceptions.iter().any(covers) {
+            return Err(error);
+        }
+
+        // Scripts that run for too long are terminated outright.
+        if self.context.update_start.elapsed() >= self.context.max_execution_duration {
+            return Err(error);
+        }
+
+        let is_thrown_value = error.is::<ThrownValue>();
+        let thrown = if is_thrown_value {
+            self.context
+                .avm2
+                .thrown_value
+                .take()
+                .unwrap_or(Value::Undefined)
+        } else {
+            let error_proto = self.context.avm2.prototypes().error;
+            error::construct_error(self, error_proto, &error.to_string(), 0)?.into()
+        };
+
+        for exception in exceptions.iter().filter(|e| covers(e)) {
+            if exception.type_name.0 != 0 {
+                let type_name = self.pool_multiname_static(
+                    method,
+                    exception.type_name,
+                    self.context.gc_context,
+                )?;
+                let type_object = if let Some(scope) = self.scope() {
+                    scope
+                        .write(self.context.gc_context)
+                        .resolve(&type_name, self)?
+                } else {
+                    None
+                };
+
+                let is_caught = match (type_object, thrown.coerce_to_object(self)) {
+                    (Some(type_object), Ok(thrown)) => {
+                        let type_object = type_object.coerce_to_object(self)?;
+                        thrown.is_instance_of(self, type_object, true)?
+                    }
+                    _ => false,
+                };
+
+                if !is_caught {
+                    continue;
+                }
+            }
+
+            return Ok((exception.target_offset, thrown));
+        }
+
+        // Nothing here catches the value, so hold on to it for the calling
+        // frame's handlers.
+        if is_thrown_value {
+            self.context.avm2.thrown_value = Some(thrown);
+        }
+
+        Err(error)
+    }
+
     /// Run a single action from a given action reader.
     fn do_next_opcode<'b>(
         &mut self,
@@ -633,6 +776,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                 Op::GetGlobalSlot { index } => self.op_get_global_slot(index),
                 Op::SetGlobalSlot { index } => self.op_set_global_slot(index),
                 Op::Construct { num_args } => self.op_construct(num_args),
+                Op::ApplyType { num_types } => self.op_apply_type(num_types),
                 Op::ConstructProp { index, num_args } => {
                     self.op_construct_prop(method, index, num_args)
                 }
@@ -643,7 +787,12 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                 Op::NewClass { index } => self.op_new_class(method, index),
                 Op::NewArray { num_args } => self.op_new_array(num_args),
                 Op::CoerceA => self.op_coerce_a(),
+                Op::CoerceB => self.op_coerce_b(),
+                Op::CoerceD => self.op_coerce_d(),
+                Op::CoerceI => self.op_coerce_i(),
+                Op::CoerceO => self.op_coerce_o(),
                 Op::CoerceS => self.op_coerce_s(),
+                Op::CoerceU => self.op_coerce_u(),
                 Op::ConvertB => self.op_convert_b(),
                 Op::ConvertI => self.op_convert_i(),
                 Op::ConvertD => self.op_convert_d(),
@@ -712,6 +861,8 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                 Op::AsTypeLate => self.op_as_type_late(),
                 Op::InstanceOf => self.op_instance_of(),
                 Op::Label => Ok(FrameControl::Continue),
+                Op::Throw => self.op_throw(),
+                Op::NewCatch { index } => self.op_new_catch(method, index),
                 Op::Debug {
                     is_local_register,
                     register_name,
@@ -750,7 +901,9 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             };
 
             if let Err(e) = result {
-                log::error!("AVM2 error: {}", e);
+                if !e.is::<ThrownValue>() {
+                    log::error!("AVM2 error: {}", e);
+                }
                 return Err(e);
             }
             result
@@ -1083,6 +1236,14 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(FrameControl::Continue)
     }
 
+    fn op_throw(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let thrown = self.context.avm2.pop();
+
+        self.context.avm2.thrown_value = Some(thrown);
+
+        Err(ThrownValue.into())
+    }
+
     fn op_return_value(&mut self) -> Result<FrameControl<'gc>, Error> {
         let return_value = self.context.avm2.pop();
 
@@ -1098,6 +1259,13 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         method: Gc<'gc, BytecodeMethod<'gc>>,
         index: Index<AbcMultiname>,
     ) -> Result<FrameControl<'gc>, Error> {
+        if let Some((dictionary, key)) = self.pop_dictionary_key(method, index) {
+            self.context
+                .avm2
+                .push(dictionary.get_property_by_object(key));
+            return Ok(FrameControl::Continue);
+        }
+
         let multiname = self.pool_multiname(method, index)?;
         let mut object = self.context.avm2.pop().coerce_to_object(self)?;
 
@@ -1129,6 +1297,11 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         index: Index<AbcMultiname>,
     ) -> Result<FrameControl<'gc>, Error> {
         let value = self.context.avm2.pop();
+        if let Some((dictionary, key)) = self.pop_dictionary_key(method, index) {
+            dictionary.set_property_by_object(key, value, self.context.gc_context);
+            return Ok(FrameControl::Continue);
+        }
+
         let multiname = self.pool_multiname(method, index)?;
         let mut object = self.context.avm2.pop().coerce_to_object(self)?;
 
@@ -1176,6 +1349,12 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         method: Gc<'gc, BytecodeMethod<'gc>>,
         index: Index<AbcMultiname>,
     ) -> Result<FrameControl<'gc>, Error> {
+        if let Some((dictionary, key)) = self.pop_dictionary_key(method, index) {
+            dictionary.delete_property_by_object(key, self.context.gc_context);
+            self.context.avm2.push(true);
+            return Ok(FrameControl::Continue);
+        }
+
         let multiname = self.pool_multiname(method, index)?;
         let object = self.context.avm2.pop().coerce_to_object(self)?;
 
@@ -1255,8 +1434,16 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     fn op_in(&mut self) -> Result<FrameControl<'gc>, Error> {
         let obj = self.context.avm2.pop().coerce_to_object(self)?;
-        let name = self.context.avm2.pop().coerce_to_string(self)?;
+        let name = self.context.avm2.pop();
 
+        if let (Some(dictionary), Value::Object(key)) = (obj.as_dictionary_object(), &name) {
+            self.context
+                .avm2
+                .push(dictionary.has_property_by_object(*key));
+            return Ok(FrameControl::Continue);
+        }
+
+        let name = name.coerce_to_string(self)?;
         let qname = QName::new(Namespace::public(), name);
         let has_prop = obj.has_property(&qname)?;
 
@@ -1436,6 +1623,18 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(FrameControl::Continue)
     }
 
+    /// Implements `Op::ApplyType`
+    fn op_apply_type(&mut self, num_types: u32) -> Result<FrameControl<'gc>, Error> {
+        let params = self.context.avm2.pop_args(num_types);
+        let base = self.context.avm2.pop().coerce_to_object(self)?;
+
+        let applied = vector::apply_type(self, base, &params)?;
+
+        self.context.avm2.push(applied);
+
+        Ok(FrameControl::Continue)
+    }
+
     fn op_construct_prop(
         &mut self,
         method: Gc<'gc, BytecodeMethod<'gc>>,
@@ -1489,6 +1688,36 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(FrameControl::Continue)
     }
 
+    fn op_new_catch(
+        &mut self,
+        method: Gc<'gc, BytecodeMethod<'gc>>,
+        index: Index<AbcException>,
+    ) -> Result<FrameControl<'gc>, Error> {
+        let body: Result<_, Error> = method
+            .body()
+            .ok_or_else(|| "Cannot execute non-native method without body".into());
+        let exception: Result<_, Error> = body?
+            .exceptions
+            .get(index.0 as usize)
+            .ok_or_else(|| format!("Unknown exception handler {}", index.0).into());
+
+        // The catch scope holds the caught value in its first slot.
+        let mut catch_scope = ScriptObject::bare_object(self.context.gc_context);
+        let variable_name = exception?.variable_name.0;
+        if variable_name != 0 {
+            let name = QName::from_abc_multiname(
+                method.translation_unit(),
+                Index::new(variable_name),
+                self.context.gc_context,
+            )?;
+            catch_scope.install_slot(self.context.gc_context, name, 1, Value::Undefined);
+        }
+
+        self.context.avm2.push(catch_scope);
+
+        Ok(FrameControl::Continue)
+    }
+
     fn op_new_object(&mut self, num_args: u32) -> Result<FrameControl<'gc>, Error> {
         let mut object = ScriptObject::object(
             self.context.gc_context,
@@ -1587,6 +1816,55 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(FrameControl::Continue)
     }
 
+    fn op_coerce_b(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.context.avm2.pop().coerce_to_boolean();
+
+        self.context.avm2.push(value);
+
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_coerce_d(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.context.avm2.pop().coerce_to_number(self)?;
+
+        self.context.avm2.push(Value::Number(value));
+
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_coerce_i(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.context.avm2.pop().coerce_to_i32(self)?;
+
+        self.context.avm2.push(Value::Integer(value));
+
+        Ok(FrameControl::Continue)
+    }
+
+    /// Implements `Op::CoerceO`.
+    ///
+    /// Unlike `convert_o`, this never throws: `undefined` becomes `null`, and
+    /// every other value is left alone.
+    fn op_coerce_o(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.context.avm2.pop();
+
+        let coerced = match value {
+            Value::Undefined => Value::Null,
+            _ => value,
+        };
+
+        self.context.avm2.push(coerced);
+
+        Ok(FrameControl::Continue)
+    }
+
+    fn op_coerce_u(&mut self) -> Result<FrameControl<'gc>, Error> {
+        let value = self.context.avm2.pop().coerce_to_u32(self)?;
+
+        self.context.avm2.push(Value::Unsigned(value));
+
+        Ok(FrameControl::Continue)
+    }
+
     fn op_coerce_s(&mut self) -> Result<FrameControl<'gc>, Error> {
         let value = self.context.avm2.pop();
 
@@ -1611,7 +1889,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn op_convert_i(&mut self) -> Result<FrameControl<'gc>, Error> {
         let value = self.context.avm2.pop().coerce_to_i32(self)?;
 
-        self.context.avm2.push(Value::Number(value.into()));
+        self.context.avm2.push(Value::Integer(value));
 
         Ok(FrameControl::Continue)
     }
@@ -1635,7 +1913,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn op_convert_u(&mut self) -> Result<FrameControl<'gc>, Error> {
         let value = self.context.avm2.pop().coerce_to_u32(self)?;
 
-        self.context.avm2.push(Value::Number(value.into()));
+        self.context.avm2.push(Value::Unsigned(value));
 
         Ok(FrameControl::Continue)
     }
@@ -1700,7 +1978,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         let value2 = self.context.avm2.pop().coerce_to_i32(self)?;
         let value1 = self.context.avm2.pop().coerce_to_i32(self)?;
 
-        self.context.avm2.push(value1 + value2);
+        self.context.avm2.push(value1.wrapping_add(value2));
 
         Ok(FrameControl::Continue)
     }
@@ -1751,7 +2029,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn op_declocal_i(&mut self, index: u32) -> Result<FrameControl<'gc>, Error> {
         let value = self.local_register(index)?.coerce_to_i32(self)?;
 
-        self.set_local_register(index, value - 1, self.context.gc_context)?;
+        self.set_local_register(index, value.wrapping_sub(1), self.context.gc_context)?;
 
         Ok(FrameControl::Continue)
     }
@@ -1767,7 +2045,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn op_decrement_i(&mut self) -> Result<FrameControl<'gc>, Error> {
         let value = self.context.avm2.pop().coerce_to_i32(self)?;
 
-        self.context.avm2.push(value - 1);
+        self.context.avm2.push(value.wrapping_sub(1));
 
         Ok(FrameControl::Continue)
     }
@@ -1792,7 +2070,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn op_inclocal_i(&mut self, index: u32) -> Result<FrameControl<'gc>, Error> {
         let value = self.local_register(index)?.coerce_to_i32(self)?;
 
-        self.set_local_register(index, value + 1, self.context.gc_context)?;
+        self.set_local_register(index, value.wrapping_add(1), self.context.gc_context)?;
 
         Ok(FrameControl::Continue)
     }
@@ -1808,7 +2086,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn op_increment_i(&mut self) -> Result<FrameControl<'gc>, Error> {
         let value = self.context.avm2.pop().coerce_to_i32(self)?;
 
-        self.context.avm2.push(value + 1);
+        self.context.avm2.push(value.wrapping_add(1));
 
         Ok(FrameControl::Continue)
     }
@@ -1826,6 +2104,9 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         let value2 = self.context.avm2.pop().coerce_to_number(self)?;
         let value1 = self.context.avm2.pop().coerce_to_number(self)?;
 
+        // Rust's `%` is `fmod`, which already has the ECMA-262 semantics: the
+        // result takes the sign of the dividend (so `-0 % 1` is `-0`), and a
+        // zero or NaN divisor, or an infinite dividend, gives NaN.
         self.context.avm2.push(value1 % value2);
 
         Ok(FrameControl::Continue)
@@ -1844,7 +2125,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         let value2 = self.context.avm2.pop().coerce_to_i32(self)?;
         let value1 = self.context.avm2.pop().coerce_to_i32(self)?;
 
-        self.context.avm2.push(value1 * value2);
+        self.context.avm2.push(value1.wrapping_mul(value2));
 
         Ok(FrameControl::Continue)
     }
@@ -1860,7 +2141,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     fn op_negate_i(&mut self) -> Result<FrameControl<'gc>, Error> {
         let value1 = self.context.avm2.pop().coerce_to_i32(self)?;
 
-        self.context.avm2.push(-value1);
+        self.context.avm2.push(value1.wrapping_neg());
 
         Ok(FrameControl::Continue)
     }
@@ -1887,7 +2168,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         let value2 = self.context.avm2.pop().coerce_to_i32(self)?;
         let value1 = self.context.avm2.pop().coerce_to_i32(self)?;
 
-        self.context.avm2.push(value1 - value2);
+        self.context.avm2.push(value1.wrapping_sub(value2));
 
         Ok(FrameControl::Continue)
     }
@@ -2222,39 +2503,45 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     fn op_has_next(&mut self) -> Result<FrameControl<'gc>, Error> {
         let cur_index = self.context.avm2.pop().coerce_to_u32(self)?;
-        let object = self.context.avm2.pop().coerce_to_object(self)?;
+        let object = self.context.avm2.pop();
 
-        let next_index = cur_index + 1;
+        let next_index = match object {
+            Value::Undefined | Value::Null => None,
+            object => object.coerce_to_object(self)?.get_next_enumerant(cur_index),
+        };
 
-        if object.get_enumerant_name(next_index).is_some() {
-            self.context.avm2.push(next_index);
-        } else {
-            self.context.avm2.push(0.0);
-        }
+        self.context.avm2.push(next_index.unwrap_or(0));
 
         Ok(FrameControl::Continue)
     }
 
+    /// Advance a `for..in` loop to the next enumerant of an object or its
+    /// prototype chain.
+    ///
+    /// Once an object runs out of enumerants, enumeration continues from the
+    /// first enumerant of its prototype, until the end of the chain, where
+    /// both registers are cleared.
     fn op_has_next_2(
         &mut self,
         object_register: u32,
         index_register: u32,
     ) -> Result<FrameControl<'gc>, Error> {
         let cur_index = self.local_register(index_register)?.coerce_to_u32(self)?;
-        let mut object = Some(
-            self.local_register(object_register)?
-                .coerce_to_object(self)?,
-        );
+        let mut object = match self.local_register(object_register)? {
+            Value::Undefined | Value::Null => None,
+            object => Some(object.coerce_to_object(self)?),
+        };
 
-        let mut next_index = cur_index + 1;
+        let mut next_index = cur_index;
 
         while let Some(cur_object) = object {
-            if cur_object.get_enumerant_name(next_index).is_none() {
-                next_index = 1;
-                object = cur_object.proto();
-            } else {
+            if let Some(index) = cur_object.get_next_enumerant(next_index) {
+                next_index = index;
                 break;
             }
+
+            next_index = 0;
+            object = cur_object.proto();
         }
 
         if object.is_none() {
@@ -2273,12 +2560,10 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     }
 
     fn op_next_name(&mut self) -> Result<FrameControl<'gc>, Error> {
-        let cur_index = self.context.avm2.pop().coerce_to_number(self)?;
+        let cur_index = self.context.avm2.pop().coerce_to_u32(self)?;
         let object = self.context.avm2.pop().coerce_to_object(self)?;
 
-        let name = object
-            .get_enumerant_name(cur_index as u32)
-            .map(|n| n.local_name().into());
+        let name = object.get_enumerant_name(cur_index);
 
         self.context.avm2.push(name.unwrap_or(Value::Undefined));
 
@@ -2286,15 +2571,10 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     }
 
     fn op_next_value(&mut self) -> Result<FrameControl<'gc>, Error> {
-        let cur_index = self.context.avm2.pop().coerce_to_number(self)?;
-        let mut object = self.context.avm2.pop().coerce_to_object(self)?;
+        let cur_index = self.context.avm2.pop().coerce_to_u32(self)?;
+        let object = self.context.avm2.pop().coerce_to_object(self)?;
 
-        let name = object.get_enumerant_name(cur_index as u32);
-        let value = if let Some(name) = name {
-            object.get_property(object, &name, self)?
-        } else {
-            Value::Undefined
-        };
+        let value = object.get_enumerant_value(cur_index, self)?;
 
         self.context.avm2.push(value);
 
@@ -2306,7 +2586,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         method: Gc<'gc, BytecodeMethod<'gc>>,
         type_name_index: Index<AbcMultiname>,
     ) -> Result<FrameControl<'gc>, Error> {
-        let value = self.context.avm2.pop().coerce_to_object(self)?;
+        let value = self.context.avm2.pop();
 
         let multiname =
             self.pool_multiname_static(method, type_name_index, self.context.gc_context)?;
@@ -2326,6 +2606,15 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         });
         let type_object = found?.coerce_to_object(self)?;
 
+        // `null` and `undefined` are not instances of any type.
+        let value = match value {
+            Value::Undefined | Value::Null => {
+                self.context.avm2.push(false);
+                return Ok(FrameControl::Continue);
+            }
+            value => value.coerce_to_object(self)?,
+        };
+
         let is_instance_of = value.is_instance_of(self, type_object, true)?;
         self.context.avm2.push(is_instance_of);
 
@@ -2334,7 +2623,13 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     fn op_is_type_late(&mut self) -> Result<FrameControl<'gc>, Error> {
         let type_object = self.context.avm2.pop().coerce_to_object(self)?;
-        let value = self.context.avm2.pop().coerce_to_object(self)?;
+        let value = match self.context.avm2.pop() {
+            Value::Undefined | Value::Null => {
+                self.context.avm2.push(false);
+                return Ok(FrameControl::Continue);
+            }
+            value => value.coerce_to_object(self)?,
+        };
 
         let is_instance_of = value.is_instance_of(self, type_object, true)?;
 
@@ -2348,7 +2643,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         method: Gc<'gc, BytecodeMethod<'gc>>,
         type_name_index: Index<AbcMultiname>,
     ) -> Result<FrameControl<'gc>, Error> {
-        let value = self.context.avm2.pop().coerce_to_object(self)?;
+        let value = self.context.avm2.pop();
 
         let multiname =
             self.pool_multiname_static(method, type_name_index, self.context.gc_context)?;
@@ -2372,6 +2667,14 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             return Err("TypeError: The right-hand side of operator must be a class.".into());
         }
 
+        let value = match value {
+            Value::Undefined | Value::Null => {
+                self.context.avm2.push(Value::Null);
+                return Ok(FrameControl::Continue);
+            }
+            value => value.coerce_to_object(self)?,
+        };
+
         let is_instance_of = value.is_instance_of(self, class, true)?;
 
         if is_instance_of {
@@ -2385,12 +2688,20 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     fn op_as_type_late(&mut self) -> Result<FrameControl<'gc>, Error> {
         let class = self.context.avm2.pop().coerce_to_object(self)?;
-        let value = self.context.avm2.pop().coerce_to_object(self)?;
+        let value = self.context.avm2.pop();
 
         if class.as_class().is_none() {
             return Err("TypeError: The right-hand side of operator must be a class.".into());
         }
 
+        let value = match value {
+            Value::Undefined | Value::Null => {
+                self.context.avm2.push(Value::Null);
+                return Ok(FrameControl::Continue);
+            }
+            value => value.coerce_to_object(self)?,
+        };
+
         let is_instance_of = value.is_instance_of(self, class, true)?;
 
         if is_instance_of {
@@ -2404,7 +2715,13 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     fn op_instance_of(&mut self) -> Result<FrameControl<'gc>, Error> {
         let type_object = self.context.avm2.pop().coerce_to_object(self)?;
-        let value = self.context.avm2.pop().coerce_to_object(self)?;
+        let value = match self.context.avm2.pop() {
+            Value::Undefined | Value::Null => {
+                self.context.avm2.push(false);
+                return Ok(FrameControl::Continue);
+            }
+            value => value.coerce_to_object(self)?,
+        };
 
         let is_instance_of = value.is_instance_of(self, type_object, false)?;
 
@@ -2460,20 +2777,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     /// Implements `Op::EscXAttr`
     fn op_esc_xattr(&mut self) -> Result<FrameControl<'gc>, Error> {
         let s = self.context.avm2.pop().coerce_to_string(self)?;
-
-        // Implementation of `EscapeAttributeValue` from ECMA-357(10.2.1.2)
-        let mut r = String::new();
-        for c in s.chars() {
-            match c {
-                '"' => r += "&quot;",
-                '<' => r += "&lt;",
-                '&' => r += "&amp;",
-                '\u{000A}' => r += "&#xA;",
-                '\u{000D}' => r += "&#xD;",
-                '\u{0009}' => r += "&#x9;",
-                _ => r.push(c),
-            }
-        }
+        let r = xml::escape_attribute_value(&s);
         self.context
             .avm2
             .push(AvmString::new(self.context.gc_context, r));
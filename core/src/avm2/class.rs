@@ -87,6 +87,12 @@ fn do_trait_lookup<'gc>(
 ) -> Result<(), Error> {
     for trait_entry in all_traits {
         if name == trait_entry.name() {
+            // Private traits are only visible to the class that declares
+            // them, so a subclass declaring the same name doesn't override it.
+            if name.namespace().is_private() {
+                known_traits.clear();
+            }
+
             for known_trait in known_traits.iter() {
                 match (&trait_entry.kind(), &known_trait.kind()) {
                     (TraitKind::Getter { .. }, TraitKind::Setter { .. }) => continue,
@@ -175,6 +181,11 @@ impl<'gc> Class<'gc> {
         self.protected_namespace = Some(ns)
     }
 
+    /// Get the namespace that protected traits of this class are stored into.
+    pub fn protected_namespace(&self) -> Option<&Namespace<'gc>> {
+        self.protected_namespace.as_ref()
+    }
+
     /// Construct a class from a `TranslationUnit` and its class index.
     ///
     /// The returned class will be allocated, but no traits will be loaded. The
@@ -406,6 +417,26 @@ impl<'gc> Class<'gc> {
         }
     }
     #[inline(never)]
+    pub fn define_public_builtin_class_properties(
+        &mut self,
+        items: &[(&'static str, Option<NativeMethod>, Option<NativeMethod>)],
+    ) {
+        for &(name, getter, setter) in items {
+            if let Some(getter) = getter {
+                self.define_class_trait(Trait::from_getter(
+                    QName::new(Namespace::public(), name),
+                    Method::from_builtin(getter),
+                ));
+            }
+            if let Some(setter) = setter {
+                self.define_class_trait(Trait::from_setter(
+                    QName::new(Namespace::public(), name),
+                    Method::from_builtin(setter),
+                ));
+            }
+        }
+    }
+    #[inline(never)]
     pub fn define_public_builtin_instance_properties(
         &mut self,
         items: &[(&'static str, Option<NativeMethod>, Option<NativeMethod>)],
@@ -533,6 +564,15 @@ impl<'gc> Class<'gc> {
         do_trait_lookup_by_slot(id, &self.instance_traits)
     }
 
+    /// Rename all instance traits of this class with a given name.
+    pub fn rename_instance_traits(&mut self, from: &QName<'gc>, to: &QName<'gc>) {
+        for trait_entry in self.instance_traits.iter_mut() {
+            if trait_entry.name() == from {
+                *trait_entry = trait_entry.clone().with_name(to.clone());
+            }
+        }
+    }
+
     /// Determines if this class provides a given trait on its instances.
     pub fn has_instance_trait(&self, name: &QName<'gc>) -> bool {
         for trait_entry in self.instance_traits.iter() {
@@ -581,4 +621,24 @@ impl<'gc> Class<'gc> {
     pub fn is_sealed(&self) -> bool {
         self.attributes.0.contains(ClassAttributes::SEALED)
     }
+
+    /// Determine if this class is final (cannot be subclassed)
+    pub fn is_final(&self) -> bool {
+        self.attributes.0.contains(ClassAttributes::FINAL)
+    }
+
+    /// Determine if this class is an interface.
+    pub fn is_interface(&self) -> bool {
+        self.attributes.0.contains(ClassAttributes::INTERFACE)
+    }
+
+    /// Get this class's instance traits.
+    pub fn instance_traits(&self) -> &[Trait<'gc>] {
+        &self.instance_traits
+    }
+
+    /// Get this class's class traits.
+    pub fn class_traits(&self) -> &[Trait<'gc>] {
+        &self.class_traits
+    }
 }
@@ -6,19 +6,22 @@ use crate::avm2::domain::Domain;
 use crate::avm2::method::NativeMethod;
 use crate::avm2::names::{Namespace, QName};
 use crate::avm2::object::{
-    implicit_deriver, ArrayObject, ByteArrayObject, DomainObject, FunctionObject, NamespaceObject,
-    Object, PrimitiveObject, ScriptObject, StageObject, TObject, XmlObject,
+    implicit_deriver, ArrayObject, ByteArrayObject, DictionaryObject, DomainObject, FunctionObject,
+    NamespaceObject, Object, PrimitiveObject, ScriptObject, StageObject, TObject, VectorObject,
+    XmlObject,
 };
 use crate::avm2::scope::Scope;
 use crate::avm2::script::Script;
 use crate::avm2::string::AvmString;
 use crate::avm2::value::Value;
+use crate::avm2::vector::VectorType;
 use crate::avm2::Error;
 use gc_arena::{Collect, GcCell, MutationContext};
 
 mod array;
 mod boolean;
 mod class;
+pub mod error;
 mod flash;
 mod function;
 mod global_scope;
@@ -30,7 +33,8 @@ mod object;
 mod regexp;
 mod string;
 mod r#uint;
-mod xml;
+pub mod vector;
+pub mod xml;
 mod xml_list;
 
 const NS_RUFFLE_INTERNAL: &str = "https://ruffle.rs/AS3/impl/";
@@ -93,6 +97,7 @@ pub struct SystemPrototypes<'gc> {
     pub uint: Object<'gc>,
     pub namespace: Object<'gc>,
     pub array: Object<'gc>,
+    pub error: Object<'gc>,
     pub movieclip: Object<'gc>,
     pub framelabel: Object<'gc>,
     pub scene: Object<'gc>,
@@ -110,6 +115,13 @@ pub struct SystemPrototypes<'gc> {
     pub loaderinfo: Object<'gc>,
     pub bytearray: Object<'gc>,
     pub stage: Object<'gc>,
+    pub stage3d: Object<'gc>,
+    pub errorevent: Object<'gc>,
+    pub stageorientationevent: Object<'gc>,
+    pub vector_int: Object<'gc>,
+    pub vector_uint: Object<'gc>,
+    pub vector_number: Object<'gc>,
+    pub vector_object: Object<'gc>,
 }
 
 impl<'gc> SystemPrototypes<'gc> {
@@ -138,6 +150,7 @@ impl<'gc> SystemPrototypes<'gc> {
             uint: empty,
             namespace: empty,
             array: empty,
+            error: empty,
             movieclip: empty,
             framelabel: empty,
             scene: empty,
@@ -155,6 +168,13 @@ impl<'gc> SystemPrototypes<'gc> {
             loaderinfo: empty,
             bytearray: empty,
             stage: empty,
+            stage3d: empty,
+            errorevent: empty,
+            stageorientationevent: empty,
+            vector_int: empty,
+            vector_uint: empty,
+            vector_number: empty,
+            vector_object: empty,
         }
     }
 }
@@ -317,6 +337,15 @@ fn bytearray_deriver<'gc>(
     ByteArrayObject::derive(base_proto, activation.context.gc_context, class, scope)
 }
 
+fn dictionary_deriver<'gc>(
+    base_proto: Object<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    class: GcCell<'gc, Class<'gc>>,
+    scope: Option<GcCell<'gc, Scope<'gc>>>,
+) -> Result<Object<'gc>, Error> {
+    DictionaryObject::derive(base_proto, activation.context.gc_context, class, scope)
+}
+
 fn stage_deriver<'gc>(
     base_proto: Object<'gc>,
     activation: &mut Activation<'_, 'gc, '_>,
@@ -463,6 +492,53 @@ pub fn load_player_globals<'gc>(
         domain,
         script,
     )?;
+    class(
+        activation,
+        vector::create_class(mc),
+        implicit_deriver,
+        domain,
+        script,
+    )?;
+    for &(name, value_type) in vector::SPECIALIZATIONS {
+        let proto = class(
+            activation,
+            vector::create_specialized_class(mc, name),
+            |base_proto, activation, class, scope| {
+                VectorObject::derive(
+                    base_proto,
+                    activation.context.gc_context,
+                    class,
+                    scope,
+                    value_type,
+                )
+            },
+            domain,
+            script,
+        )?;
+
+        match value_type {
+            VectorType::Int => sp.vector_int = proto,
+            VectorType::Uint => sp.vector_uint = proto,
+            VectorType::Number => sp.vector_number = proto,
+            VectorType::Object => sp.vector_object = proto,
+        }
+    }
+    sp.error = class(
+        activation,
+        error::create_class(mc),
+        implicit_deriver,
+        domain,
+        script,
+    )?;
+    for &name in error::NATIVE_ERROR_CLASSES {
+        class(
+            activation,
+            error::create_native_error_class(mc, name),
+            implicit_deriver,
+            domain,
+            script,
+        )?;
+    }
 
     // At this point we have to hide the fact that we had to create the player
     // globals scope *before* the `Object` class
@@ -571,6 +647,48 @@ pub fn load_player_globals<'gc>(
         domain,
         script,
     )?;
+    class(
+        activation,
+        flash::events::textevent::create_class(mc),
+        flash::events::event::event_deriver,
+        domain,
+        script,
+    )?;
+    activation
+        .context
+        .avm2
+        .system_prototypes
+        .as_mut()
+        .unwrap()
+        .errorevent = class(
+        activation,
+        flash::events::errorevent::create_class(mc),
+        flash::events::event::event_deriver,
+        domain,
+        script,
+    )?;
+    activation
+        .context
+        .avm2
+        .system_prototypes
+        .as_mut()
+        .unwrap()
+        .stageorientationevent = class(
+        activation,
+        flash::events::stageorientationevent::create_class(mc),
+        flash::events::event::event_deriver,
+        domain,
+        script,
+    )?;
+    // package `flash.external`
+    class(
+        activation,
+        flash::external::external_interface::create_class(mc),
+        implicit_deriver,
+        domain,
+        script,
+    )?;
+
     // package `flash.utils`
     activation
         .context
@@ -593,6 +711,13 @@ pub fn load_player_globals<'gc>(
         domain,
         script,
     )?;
+    class(
+        activation,
+        flash::utils::dictionary::create_class(mc),
+        dictionary_deriver,
+        domain,
+        script,
+    )?;
 
     function(
         mc,
@@ -603,6 +728,33 @@ pub fn load_player_globals<'gc>(
         domain,
         script,
     )?;
+    function(
+        mc,
+        "flash.utils",
+        "getQualifiedClassName",
+        flash::utils::get_qualified_class_name,
+        fn_proto,
+        domain,
+        script,
+    )?;
+    function(
+        mc,
+        "flash.utils",
+        "getDefinitionByName",
+        flash::utils::get_definition_by_name,
+        fn_proto,
+        domain,
+        script,
+    )?;
+    function(
+        mc,
+        "flash.utils",
+        "describeType",
+        flash::utils::describe_type,
+        fn_proto,
+        domain,
+        script,
+    )?;
 
     // package `flash.display`
     activation
@@ -793,6 +945,42 @@ pub fn load_player_globals<'gc>(
         domain,
         script,
     )?;
+    activation
+        .context
+        .avm2
+        .system_prototypes
+        .as_mut()
+        .unwrap()
+        .stage3d = class(
+        activation,
+        flash::display::stage3d::create_class(mc),
+        implicit_deriver,
+        domain,
+        script,
+    )?;
+
+    // package `flash.display3D`
+    class(
+        activation,
+        flash::display3d::context3d::create_class(mc),
+        implicit_deriver,
+        domain,
+        script,
+    )?;
+    class(
+        activation,
+        flash::display3d::context3dprofile::create_class(mc),
+        implicit_deriver,
+        domain,
+        script,
+    )?;
+    class(
+        activation,
+        flash::display3d::context3drendermode::create_class(mc),
+        implicit_deriver,
+        domain,
+        script,
+    )?;
 
     // package `flash.geom`
     activation
@@ -0,0 +1,240 @@
+//! `Error` and its native subclasses
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::Class;
+use crate::avm2::globals::NS_RUFFLE_INTERNAL;
+use crate::avm2::method::{Method, NativeMethod};
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::string::AvmString;
+use crate::avm2::traits::Trait;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use gc_arena::{GcCell, MutationContext};
+
+/// The names of all native error classes, in addition to `Error` itself.
+pub const NATIVE_ERROR_CLASSES: &[&str] = &[
+    "ArgumentError",
+    "DefinitionError",
+    "EvalError",
+    "RangeError",
+    "ReferenceError",
+    "SecurityError",
+    "SyntaxError",
+    "TypeError",
+    "URIError",
+    "VerifyError",
+];
+
+/// Implements `Error`'s instance constructor.
+pub fn instance_init<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        // `Object`'s constructor does nothing, and errors raised by the
+        // interpreter are constructed outside of any constructor chain, so we
+        // deliberately do not supercall here.
+        let message = match args.get(0) {
+            Some(Value::Undefined) | None => "".into(),
+            Some(message) => message.coerce_to_string(activation)?,
+        };
+        let id = args
+            .get(1)
+            .cloned()
+            .unwrap_or_else(|| 0.into())
+            .coerce_to_i32(activation)?;
+
+        // Each native error class names itself; user-defined subclasses
+        // inherit the name of the nearest native error class.
+        let mut name: &'static str = "Error";
+        let mut proto = this.proto();
+        while let Some(p) = proto {
+            if let Some(class) = p.as_class() {
+                let class_name = class.read().name().clone();
+                if class_name.namespace().is_public() {
+                    if let Some(native) = NATIVE_ERROR_CLASSES
+                        .iter()
+                        .find(|n| **n == class_name.local_name())
+                    {
+                        name = *native;
+                        break;
+                    }
+                }
+            }
+            proto = p.proto();
+        }
+
+        this.set_property(
+            this,
+            &QName::new(Namespace::public(), "message"),
+            message.into(),
+            activation,
+        )?;
+        this.set_property(
+            this,
+            &QName::new(Namespace::public(), "name"),
+            name.into(),
+            activation,
+        )?;
+        this.set_property(
+            this,
+            &QName::new(Namespace::private(NS_RUFFLE_INTERNAL), "errorID"),
+            id.into(),
+            activation,
+        )?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements the instance constructor of native subclasses of `Error`.
+pub fn native_error_init<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        activation.super_init(this, args)?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Error`'s class constructor.
+pub fn class_init<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Implements `Error.errorID`.
+pub fn error_id<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        return this.get_property(
+            this,
+            &QName::new(Namespace::private(NS_RUFFLE_INTERNAL), "errorID"),
+            activation,
+        );
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Error.getStackTrace`.
+///
+/// Stack traces are only available in debugger players, so this always
+/// returns `null`.
+pub fn get_stack_trace<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Null)
+}
+
+/// Implements `Error.toString`.
+pub fn to_string<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        let name = this
+            .get_property(this, &QName::new(Namespace::public(), "name"), activation)?
+            .coerce_to_string(activation)?;
+        let message = this
+            .get_property(
+                this,
+                &QName::new(Namespace::public(), "message"),
+                activation,
+            )?
+            .coerce_to_string(activation)?;
+
+        if message.is_empty() {
+            return Ok(name.into());
+        }
+
+        return Ok(AvmString::new(
+            activation.context.gc_context,
+            format!("{}: {}", name, message),
+        )
+        .into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Construct a new error object from an error class prototype, as if the
+/// class had been constructed with the given message and error ID.
+///
+/// This is used to construct errors raised by the interpreter itself.
+pub fn construct_error<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    proto: Object<'gc>,
+    message: &str,
+    id: i32,
+) -> Result<Object<'gc>, Error> {
+    let message = AvmString::new(activation.context.gc_context, message.to_string());
+    let args = [message.into(), id.into()];
+    let error = proto.construct(activation, &args)?;
+
+    instance_init(activation, Some(error), &args)?;
+
+    Ok(error)
+}
+
+/// Construct `Error`'s class.
+pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>> {
+    let class = Class::new(
+        QName::new(Namespace::public(), "Error"),
+        Some(QName::new(Namespace::public(), "Object").into()),
+        Method::from_builtin(instance_init),
+        Method::from_builtin(class_init),
+        mc,
+    );
+
+    let mut write = class.write(mc);
+
+    write.define_instance_trait(Trait::from_slot(
+        QName::new(Namespace::public(), "message"),
+        QName::new(Namespace::public(), "String").into(),
+        None,
+    ));
+    write.define_instance_trait(Trait::from_slot(
+        QName::new(Namespace::public(), "name"),
+        QName::new(Namespace::public(), "String").into(),
+        None,
+    ));
+
+    const PUBLIC_INSTANCE_PROPERTIES: &[(&str, Option<NativeMethod>, Option<NativeMethod>)] =
+        &[("errorID", Some(error_id), None)];
+    write.define_public_builtin_instance_properties(PUBLIC_INSTANCE_PROPERTIES);
+
+    const PUBLIC_INSTANCE_METHODS: &[(&str, NativeMethod)] =
+        &[("getStackTrace", get_stack_trace), ("toString", to_string)];
+    write.define_public_builtin_instance_methods(PUBLIC_INSTANCE_METHODS);
+
+    class
+}
+
+/// Construct the class of one of the native subclasses of `Error`.
+pub fn create_native_error_class<'gc>(
+    mc: MutationContext<'gc, '_>,
+    name: &'static str,
+) -> GcCell<'gc, Class<'gc>> {
+    Class::new(
+        QName::new(Namespace::public(), name),
+        Some(QName::new(Namespace::public(), "Error").into()),
+        Method::from_builtin(native_error_init),
+        Method::from_builtin(class_init),
+        mc,
+    )
+}
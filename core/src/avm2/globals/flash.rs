@@ -1,7 +1,9 @@
 //! `flash` namespace
 
 pub mod display;
+pub mod display3d;
 pub mod events;
+pub mod external;
 pub mod geom;
 pub mod media;
 pub mod system;
@@ -15,6 +15,7 @@ pub mod scene;
 pub mod shape;
 pub mod sprite;
 pub mod stage;
+pub mod stage3d;
 pub mod stagealign;
 pub mod stagedisplaystate;
 pub mod stagequality;
@@ -4,10 +4,11 @@ use crate::avm2::activation::Activation;
 use crate::avm2::class::Class;
 use crate::avm2::method::{Method, NativeMethod};
 use crate::avm2::names::{Namespace, QName};
-use crate::avm2::object::{LoaderInfoObject, Object, TObject};
+use crate::avm2::object::{LoaderInfoObject, Object, ScriptObject, TObject};
 use crate::avm2::string::AvmString;
 use crate::avm2::value::Value;
 use crate::avm2::Error;
+use crate::bounding_box::BoundingBox;
 use crate::display_object::{DisplayObject, HitTestOptions, TDisplayObject};
 use crate::types::{Degrees, Percent};
 use crate::vminterface::Instantiator;
@@ -585,6 +586,88 @@ pub fn loader_info<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `scale9Grid`'s getter.
+pub fn scale_9_grid<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let grid = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|dobj| dobj.as_movie_clip())
+        .map(|mc| mc.scaling_grid())
+        .filter(|grid| grid.valid);
+
+    if let Some(grid) = grid {
+        //TODO: This should be a `flash.geom.Rectangle`, once we have one.
+        let mut rectangle = ScriptObject::object(
+            activation.context.gc_context,
+            activation.avm2().prototypes().object,
+        );
+        for &(name, value) in &[
+            ("x", grid.x_min.to_pixels()),
+            ("y", grid.y_min.to_pixels()),
+            ("width", grid.width().to_pixels()),
+            ("height", grid.height().to_pixels()),
+        ] {
+            rectangle.set_property(
+                rectangle,
+                &QName::new(Namespace::public(), name),
+                value.into(),
+                activation,
+            )?;
+        }
+
+        return Ok(rectangle.into());
+    }
+
+    Ok(Value::Null)
+}
+
+/// Implements `scale9Grid`'s setter.
+pub fn set_scale_9_grid<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mc) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|dobj| dobj.as_movie_clip())
+    {
+        let grid = match args.get(0).cloned().unwrap_or(Value::Undefined) {
+            Value::Undefined | Value::Null => BoundingBox::default(),
+            value => {
+                let mut rectangle = value.coerce_to_object(activation)?;
+                let mut get = |name| -> Result<f64, Error> {
+                    rectangle
+                        .get_property(
+                            rectangle,
+                            &QName::new(Namespace::public(), name),
+                            activation,
+                        )?
+                        .coerce_to_number(activation)
+                };
+                let x = get("x")?;
+                let y = get("y")?;
+                let width = get("width")?;
+                let height = get("height")?;
+
+                BoundingBox {
+                    x_min: Twips::from_pixels(x),
+                    y_min: Twips::from_pixels(y),
+                    x_max: Twips::from_pixels(x + width),
+                    y_max: Twips::from_pixels(y + height),
+                    valid: true,
+                }
+            }
+        };
+
+        mc.set_scaling_grid(activation.context.gc_context, grid);
+    }
+
+    Ok(Value::Undefined)
+}
+
 /// Construct `DisplayObject`'s class.
 pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>> {
     let class = Class::new(
@@ -614,6 +697,7 @@ pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>
         ("mouseX", Some(mouse_x), None),
         ("mouseY", Some(mouse_y), None),
         ("loaderInfo", Some(loader_info), None),
+        ("scale9Grid", Some(scale_9_grid), Some(set_scale_9_grid)),
     ];
     write.define_public_builtin_instance_properties(PUBLIC_INSTANCE_PROPERTIES);
 
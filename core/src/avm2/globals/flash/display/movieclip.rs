@@ -13,6 +13,7 @@ use crate::avm2::Error;
 use crate::display_object::{MovieClip, Scene, TDisplayObject};
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use gc_arena::{GcCell, MutationContext};
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 /// Implements `flash.display.MovieClip`'s instance constructor.
@@ -56,6 +57,8 @@ pub fn class_init<'gc>(
 
 /// Implements `addFrameScript`, an undocumented method of `MovieClip` used to
 /// specify what methods of a clip's class run on which frames.
+///
+/// Each frame has at most one script; a `null` script removes it.
 pub fn add_frame_script<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     this: Option<Object<'gc>>,
@@ -66,8 +69,19 @@ pub fn add_frame_script<'gc>(
         .and_then(|dobj| dobj.as_movie_clip())
     {
         for (frame_id, callable) in args.chunks_exact(2).map(|s| (s[0].clone(), s[1].clone())) {
-            let frame_id = frame_id.coerce_to_u32(activation)? as u16 + 1;
-            let callable = callable.coerce_to_object(activation)?;
+            // Frame indices are zero-based, and no clip has a frame past the
+            // range of frame numbers.
+            let frame_id = match u16::try_from(frame_id.coerce_to_u32(activation)?)
+                .ok()
+                .and_then(|frame_id| frame_id.checked_add(1))
+            {
+                Some(frame_id) => frame_id,
+                None => continue,
+            };
+            let callable = match callable {
+                Value::Null | Value::Undefined => None,
+                callable => Some(callable.coerce_to_object(activation)?),
+            };
 
             mc.register_frame_script(frame_id, callable, &mut activation.context);
         }
@@ -149,6 +163,16 @@ pub fn current_label<'gc>(
     Ok(Value::Undefined)
 }
 
+/// The scene that a clip without any scenes of its own implicitly plays,
+/// covering its whole timeline.
+fn whole_timeline_scene(mc: MovieClip<'_>) -> Scene {
+    Scene {
+        name: "".to_string(),
+        start: 1,
+        length: mc.total_frames(),
+    }
+}
+
 /// Given a scene, produce its name, length, and a list of frame labels.
 ///
 /// The intended purpose of this output is to be sent directly into the
@@ -166,15 +190,21 @@ fn labels_for_scene<'gc>(
     let mut frame_labels = Vec::new();
     let frame_label_proto = activation.context.avm2.prototypes().framelabel;
 
-    for (name, frame) in mc.labels_in_range(*scene_start, scene_start + scene_length) {
-        let name: Value<'gc> = AvmString::new(activation.context.gc_context, name).into();
-        let local_frame = frame - scene_start + 1;
-        let args = [name, local_frame.into()];
-        let frame_label = frame_label_proto.construct(activation, &args)?;
+    let scene_end = scene_start
+        .saturating_add(*scene_length)
+        .min(mc.total_frames().saturating_add(1));
+    for frame in *scene_start..scene_end {
+        // A frame may have several labels, each with its own `FrameLabel`.
+        for name in mc.labels_on_frame(frame) {
+            let name: Value<'gc> = AvmString::new(activation.context.gc_context, name).into();
+            let local_frame = frame - scene_start + 1;
+            let args = [name, local_frame.into()];
+            let frame_label = frame_label_proto.construct(activation, &args)?;
 
-        framelabel::instance_init(activation, Some(frame_label), &args)?;
+            framelabel::instance_init(activation, Some(frame_label), &args)?;
 
-        frame_labels.push(Some(frame_label.into()));
+            frame_labels.push(Some(frame_label.into()));
+        }
     }
 
     Ok((
@@ -198,11 +228,9 @@ pub fn current_labels<'gc>(
         .and_then(|o| o.as_display_object())
         .and_then(|dobj| dobj.as_movie_clip())
     {
-        let scene = mc.current_scene().unwrap_or_else(|| Scene {
-            name: "".to_string(),
-            start: 0,
-            length: mc.total_frames(),
-        });
+        let scene = mc
+            .current_scene()
+            .unwrap_or_else(|| whole_timeline_scene(mc));
         return Ok(labels_for_scene(activation, mc, &scene)?.2.into());
     }
 
@@ -219,11 +247,9 @@ pub fn current_scene<'gc>(
         .and_then(|o| o.as_display_object())
         .and_then(|dobj| dobj.as_movie_clip())
     {
-        let scene = mc.current_scene().unwrap_or_else(|| Scene {
-            name: "".to_string(),
-            start: 0,
-            length: mc.total_frames(),
-        });
+        let scene = mc
+            .current_scene()
+            .unwrap_or_else(|| whole_timeline_scene(mc));
         let (scene_name, scene_length, scene_labels) = labels_for_scene(activation, mc, &scene)?;
         let scene_proto = activation.context.avm2.prototypes().scene;
         let args = [
@@ -255,12 +281,8 @@ pub fn scenes<'gc>(
         let mut scene_objects = Vec::new();
         let mut mc_scenes = mc.scenes();
 
-        if mc.scenes().is_empty() {
-            mc_scenes.push(Scene {
-                name: "".to_string(),
-                start: 0,
-                length: mc.total_frames(),
-            });
+        if mc_scenes.is_empty() {
+            mc_scenes.push(whole_timeline_scene(mc));
         }
 
         for scene in mc_scenes {
@@ -379,46 +401,59 @@ pub fn goto_frame<'gc>(
     stop: bool,
 ) -> Result<(), Error> {
     let frame_or_label = args.get(0).cloned().unwrap_or(Value::Null);
+    let scene_name = match args.get(1).cloned().unwrap_or(Value::Null) {
+        Value::Null | Value::Undefined => None,
+        v => Some(v.coerce_to_string(activation)?),
+    };
 
-    let scene = match args.get(1).cloned().unwrap_or(Value::Null) {
-        Value::Null => None,
-        v => mc
-            .scene_label_to_number(&v.coerce_to_string(activation)?)
-            .map(|v| v.saturating_sub(1)),
-    }
-    .unwrap_or(0) as u32;
+    // Frame numbers are relative to the given scene, or to the current one
+    // if none was given.
+    let scene_start = match &scene_name {
+        Some(scene_name) => mc.scene_label_to_number(scene_name).ok_or_else(|| {
+            format!(
+                "ArgumentError: Error #2108: Scene {} was not found.",
+                scene_name
+            )
+        })?,
+        None => mc.current_scene().map_or(1, |scene| scene.start),
+    };
+    let scene_offset = i64::from(scene_start.saturating_sub(1));
     let frame = match frame_or_label {
-        Value::Integer(i) => i as u32 + scene,
-        Value::Unsigned(i) => i + scene,
+        Value::Integer(i) => i64::from(i) + scene_offset,
+        Value::Unsigned(i) => i64::from(i) + scene_offset,
+        Value::Number(n) => (n as i64).saturating_add(scene_offset),
         frame_or_label => {
             let frame_or_label = frame_or_label.coerce_to_string(activation)?;
-            if let Ok(frame) = frame_or_label.parse::<u32>() {
-                frame + scene
-            } else {
-                if let Some(scene) = args.get(1).cloned() {
-                    //If the user specified a scene, we need to validate that
-                    //the requested frame exists within that scene.
-                    let scene = scene.coerce_to_string(activation)?;
-                    if !mc.frame_exists_within_scene(&frame_or_label, &scene) {
-                        return Err(format!(
+            if let Ok(frame) = frame_or_label.parse::<i64>() {
+                frame.saturating_add(scene_offset)
+            } else if let Some(scene_name) = &scene_name {
+                //If the user specified a scene, the label must be within it.
+                mc.frame_label_to_number_in_scene(&frame_or_label, scene_name)
+                    .ok_or_else(|| {
+                        format!(
                             "ArgumentError: Frame label {} not found in scene {}",
-                            frame_or_label, scene
+                            frame_or_label, scene_name
                         )
-                        .into());
-                    }
-                }
-
-                mc.frame_label_to_number(&frame_or_label).ok_or_else(|| {
-                    format!(
-                        "ArgumentError: {} is not a valid frame label.",
-                        frame_or_label
-                    )
-                })? as u32
+                    })?
+                    .into()
+            } else {
+                mc.frame_label_to_number(&frame_or_label)
+                    .ok_or_else(|| {
+                        format!(
+                            "ArgumentError: {} is not a valid frame label.",
+                            frame_or_label
+                        )
+                    })?
+                    .into()
             }
         }
     };
 
-    mc.goto_frame(&mut activation.context, frame as u16, stop);
+    // Frames past the end of the clip are clamped to its last frame by the
+    // goto itself, so only the range of frame numbers is checked here.
+    let frame = u16::try_from(frame.max(1)).unwrap_or(u16::MAX);
+
+    mc.goto_frame(&mut activation.context, frame, stop);
 
     Ok(())
 }
@@ -574,3 +609,154 @@ pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>
 
     class
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::avm2::ScriptObject;
+    use swf::{DefineSceneAndFrameLabelData, FileAttributes, FrameLabelData, SwfStr, Tag};
+
+    /// A movie of six empty frames in two scenes of three frames, with a
+    /// frame label on the second frame of each, and a second label on the
+    /// second frame of the first scene.
+    fn scene_movie(is_action_script_3: bool) -> Arc<SwfMovie> {
+        fn label(frame_num: u32, label: &'static str) -> FrameLabelData<'static> {
+            FrameLabelData {
+                frame_num,
+                label: SwfStr::from_utf8_str(label),
+            }
+        }
+        let mut tags = vec![
+            Tag::FileAttributes(FileAttributes {
+                use_direct_blit: false,
+                use_gpu: false,
+                has_metadata: false,
+                is_action_script_3,
+                use_network_sandbox: false,
+            }),
+            Tag::DefineSceneAndFrameLabelData(DefineSceneAndFrameLabelData {
+                scenes: vec![label(0, "a"), label(3, "b")],
+                frame_labels: vec![label(1, "in_a"), label(1, "Also_A"), label(4, "in_b")],
+            }),
+        ];
+        tags.extend((0..6).map(|_| Tag::ShowFrame));
+
+        let movie = swf::Swf {
+            header: swf::Header {
+                compression: swf::Compression::None,
+                version: 9,
+                uncompressed_length: 0,
+                stage_size: Default::default(),
+                frame_rate: 24.0,
+                num_frames: 6,
+            },
+            tags,
+        };
+        let mut data = Vec::new();
+        swf::write_swf(&movie, &mut data).unwrap();
+        Arc::new(SwfMovie::from_data(&data, None, None).unwrap())
+    }
+
+    /// Run `gotoAndStop` with the given arguments, returning the frame the
+    /// clip ends up on.
+    fn goto_and_stop<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        mc: MovieClip<'gc>,
+        args: &[Value<'gc>],
+    ) -> Result<u16, Error> {
+        goto_frame(activation, mc, args, true)?;
+        Ok(mc.current_frame())
+    }
+
+    #[test]
+    fn goto_frames_relative_to_scenes() {
+        with_avm(
+            9,
+            |avm1_activation, _root| -> Result<(), crate::avm1::Error> {
+                let mut activation = Activation::from_nothing(avm1_activation.context.reborrow());
+                let activation = &mut activation;
+                let mc = MovieClip::from_movie(activation.context.gc_context, scene_movie(true));
+                mc.preload(&mut activation.context);
+
+                let cases: &[(&[Value<'_>], u16)] = &[
+                    (&[1.into(), "b".into()], 4),
+                    // Without a scene, frames are relative to the current one.
+                    (&[2.into()], 5),
+                    (&["3".into()], 6),
+                    (&[1.into(), "a".into()], 1),
+                    (&["in_b".into(), "b".into()], 5),
+                    (&["in_a".into()], 2),
+                    // Frame numbers out of range are clamped instead of wrapping.
+                    (&[Value::Number(65537.0)], 6),
+                    (&[Value::Number(1e300)], 6),
+                    (&[(-1).into()], 1),
+                ];
+                for (args, frame) in cases {
+                    assert_eq!(goto_and_stop(activation, mc, args).unwrap(), *frame);
+                }
+
+                let error = goto_and_stop(activation, mc, &[1.into(), "c".into()]).unwrap_err();
+                assert!(error.to_string().starts_with("ArgumentError"));
+                let error =
+                    goto_and_stop(activation, mc, &["in_a".into(), "b".into()]).unwrap_err();
+                assert!(error.to_string().starts_with("ArgumentError"));
+                assert_eq!(mc.current_frame(), 1);
+
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn frame_scripts_are_replaced_and_removed() {
+        with_avm(
+            9,
+            |avm1_activation, _root| -> Result<(), crate::avm1::Error> {
+                let context = &mut avm1_activation.context;
+                let mc = MovieClip::from_movie(context.gc_context, scene_movie(true));
+                let first = ScriptObject::bare_object(context.gc_context);
+                let second = ScriptObject::bare_object(context.gc_context);
+
+                mc.register_frame_script(2, Some(first), context);
+                assert!(matches!(mc.frame_script(2), Some(s) if Object::ptr_eq(s, first)));
+                assert!(mc.frame_script(1).is_none());
+
+                mc.register_frame_script(2, Some(second), context);
+                assert!(matches!(mc.frame_script(2), Some(s) if Object::ptr_eq(s, second)));
+
+                mc.register_frame_script(2, None, context);
+                assert!(mc.frame_script(2).is_none());
+
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn labels_are_case_sensitive_in_avm2_only() {
+        with_avm(
+            9,
+            |avm1_activation, _root| -> Result<(), crate::avm1::Error> {
+                for &is_action_script_3 in &[false, true] {
+                    let context = &mut avm1_activation.context;
+                    let movie = scene_movie(is_action_script_3);
+                    let mc = MovieClip::from_movie(context.gc_context, movie);
+                    mc.preload(context);
+
+                    assert_eq!(mc.frame_label_to_number("in_a"), Some(2));
+                    assert_eq!(mc.frame_label_to_number("Also_A"), Some(2));
+                    let other_case = if is_action_script_3 { None } else { Some(2) };
+                    assert_eq!(mc.frame_label_to_number("IN_A"), other_case);
+                    assert_eq!(mc.frame_label_to_number_in_scene("ALSO_A", "a"), other_case);
+
+                    // Labels keep their casing, in the order they were defined.
+                    assert_eq!(mc.labels_on_frame(2), vec!["in_a", "Also_A"]);
+                    assert!(mc.labels_on_frame(3).is_empty());
+                }
+
+                Ok(())
+            },
+        );
+    }
+}
@@ -2,14 +2,18 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::class::{Class, ClassAttributes};
+use crate::avm2::globals::flash::display::stage3d;
+use crate::avm2::globals::NS_RUFFLE_INTERNAL;
 use crate::avm2::method::{Method, NativeMethod};
 use crate::avm2::names::{Namespace, QName};
-use crate::avm2::object::{Object, TObject};
+use crate::avm2::object::{Object, TObject, VectorObject};
 use crate::avm2::string::AvmString;
 use crate::avm2::traits::Trait;
 use crate::avm2::value::Value;
+use crate::avm2::vector::{VectorStorage, VectorType};
 use crate::avm2::Error;
 use crate::display_object::TDisplayObject;
+use crate::player::clamp_frame_rate;
 use gc_arena::{GcCell, MutationContext};
 use swf::Color;
 
@@ -445,7 +449,8 @@ pub fn set_frame_rate<'gc>(
         .cloned()
         .unwrap_or(Value::Undefined)
         .coerce_to_number(activation)?;
-    *activation.context.frame_rate = new_frame_rate;
+    *activation.context.frame_rate =
+        clamp_frame_rate(new_frame_rate, *activation.context.frame_rate);
 
     Ok(Value::Undefined)
 }
@@ -584,14 +589,95 @@ pub fn allows_full_screen_interactive<'gc>(
 }
 
 /// Implement `quality`'s getter
-///
-/// TODO: This is a stub.
 pub fn quality<'gc>(
-    _activation: &mut Activation<'_, 'gc, '_>,
+    activation: &mut Activation<'_, 'gc, '_>,
     _this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error> {
-    Ok("HIGH".into())
+    Ok(activation.context.stage.quality().as_str().into())
+}
+
+/// Implement `quality`'s setter
+///
+/// Unknown qualities are ignored.
+pub fn set_quality<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Ok(quality) = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?
+        .parse()
+    {
+        activation
+            .context
+            .stage
+            .set_quality(&mut activation.context, quality);
+    }
+    Ok(Value::Undefined)
+}
+
+/// Implement `invalidate`.
+///
+/// This requests a `render` event before the stage is next drawn.
+pub fn invalidate<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let stage = activation.context.stage;
+    stage.invalidate(activation.context.gc_context);
+
+    Ok(Value::Undefined)
+}
+
+/// Implement `stage3Ds`.
+///
+/// The same `Stage3D`s are returned every time, in a new vector.
+pub fn stage3ds<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        // Lazily create the `Stage3D`s in a hidden property.
+        let name = QName::new(Namespace::private(NS_RUFFLE_INTERNAL), "stage3Ds");
+        let stage3ds = match this.get_property(this, &name, activation)? {
+            Value::Undefined | Value::Null => {
+                let mut stage3ds = VectorStorage::new(0, false, VectorType::Object);
+                for _ in 0..stage3d::STAGE3D_COUNT {
+                    stage3ds.push(stage3d::create_stage3d(activation)?.into())?;
+                }
+                let stage3ds: Value<'gc> = VectorObject::from_vector(
+                    stage3ds,
+                    activation.context.avm2.prototypes().vector_object,
+                    activation.context.gc_context,
+                )
+                .into();
+                this.set_property(this, &name, stage3ds.clone(), activation)?;
+                stage3ds
+            }
+            stage3ds => stage3ds,
+        };
+
+        let stage3ds = stage3ds.coerce_to_object(activation)?;
+        if let Some(vector) = stage3ds.as_vector_storage() {
+            let mut copy = vector.empty_like();
+            for stage3d in vector.iter() {
+                copy.push(stage3d)?;
+            }
+            return Ok(VectorObject::from_vector(
+                copy,
+                activation.context.avm2.prototypes().vector_object,
+                activation.context.gc_context,
+            )
+            .into());
+        }
+    }
+
+    Ok(Value::Undefined)
 }
 
 /// Construct `Stage`'s class.
@@ -690,9 +776,20 @@ pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>
             Some(allows_full_screen_interactive),
             None,
         ),
-        ("quality", Some(quality), None),
+        ("quality", Some(quality), Some(set_quality)),
+        ("stage3Ds", Some(stage3ds), None),
     ];
     write.define_public_builtin_instance_properties(PUBLIC_INSTANCE_PROPERTIES);
 
+    const PUBLIC_INSTANCE_METHODS: &[(&str, NativeMethod)] = &[("invalidate", invalidate)];
+    write.define_public_builtin_instance_methods(PUBLIC_INSTANCE_METHODS);
+
+    // Slot for lazy-initialized `Stage3D`s.
+    write.define_instance_trait(Trait::from_slot(
+        QName::new(Namespace::private(NS_RUFFLE_INTERNAL), "stage3Ds"),
+        QName::new(Namespace::private(NS_RUFFLE_INTERNAL), "BareObject").into(),
+        None,
+    ));
+
     class
 }
@@ -0,0 +1,174 @@
+//! `flash.display.Stage3D` builtin/prototype
+//!
+//! Ruffle has no Stage3D renderer, so every request for a `Context3D` fails
+//! with an `ErrorEvent`, as it does in Flash Player when hardware acceleration
+//! is unavailable. Content that handles the error can then fall back to
+//! drawing with the display list.
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::{Class, ClassAttributes};
+use crate::avm2::method::{Method, NativeMethod};
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::traits::Trait;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::context::ActionType;
+use gc_arena::{GcCell, MutationContext};
+
+/// The number of `Stage3D`s that `Stage.stage3Ds` lists, as in desktop
+/// Flash Player.
+pub const STAGE3D_COUNT: usize = 4;
+
+/// The ID of the error that a failed `requestContext3D` reports.
+const CONTEXT3D_NOT_AVAILABLE: i32 = 3702;
+
+/// Implements `flash.display.Stage3D`'s instance constructor.
+pub fn instance_init<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Err("Stage3D cannot be constructed directly.".into())
+}
+
+/// Implements `flash.display.Stage3D`'s class constructor.
+pub fn class_init<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Create a new `Stage3D`.
+pub fn create_stage3d<'gc>(activation: &mut Activation<'_, 'gc, '_>) -> Result<Object<'gc>, Error> {
+    let proto = activation.context.avm2.prototypes().stage3d;
+    let stage3d = proto.construct(activation, &[])?;
+
+    // `Stage3D`'s own constructor refuses to run, so skip to the
+    // `EventDispatcher` one.
+    let mut dispatcher_proto = proto
+        .proto()
+        .ok_or("Attempted to construct a Stage3D without a superclass")?;
+    let constructor = dispatcher_proto
+        .get_property(
+            dispatcher_proto,
+            &QName::new(Namespace::public(), "constructor"),
+            activation,
+        )?
+        .coerce_to_object(activation)?;
+    constructor.call(Some(stage3d), &[], activation, Some(dispatcher_proto))?;
+
+    Ok(stage3d)
+}
+
+/// Implements `context3D`'s getter.
+pub fn context3d<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Null)
+}
+
+/// Implements `Stage3D.requestContext3D` and
+/// `Stage3D.requestContext3DMatchingProfiles`.
+///
+/// Both fail by dispatching an `ErrorEvent` once the current script has
+/// finished, so that listeners added after the request still hear of it.
+pub fn request_context3d<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        log::warn!("Stage3D was requested, but is not supported; reporting it as unavailable");
+        activation
+            .context
+            .stage
+            .set_stage3d_requested(activation.context.gc_context);
+
+        let args = [
+            "error".into(),
+            false.into(),
+            false.into(),
+            "Error #3702: Context3D not available.".into(),
+            CONTEXT3D_NOT_AVAILABLE.into(),
+        ];
+        let mut proto = activation.context.avm2.prototypes().errorevent;
+        let event = proto.construct(activation, &args)?;
+        let constructor = proto
+            .get_property(
+                proto,
+                &QName::new(Namespace::public(), "constructor"),
+                activation,
+            )?
+            .coerce_to_object(activation)?;
+        constructor.call(Some(event), &args, activation, Some(proto))?;
+
+        let dispatch_event = this
+            .get_property(
+                this,
+                &QName::new(Namespace::public(), "dispatchEvent"),
+                activation,
+            )?
+            .coerce_to_object(activation)?;
+        let root = activation.context.stage.root_clip();
+        activation.context.action_queue.queue_actions(
+            root,
+            ActionType::Callable2 {
+                callable: dispatch_event,
+                reciever: Some(this),
+                args: vec![event.into()],
+            },
+            false,
+        );
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Construct `Stage3D`'s class.
+pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>> {
+    let class = Class::new(
+        QName::new(Namespace::package("flash.display"), "Stage3D"),
+        Some(QName::new(Namespace::package("flash.events"), "EventDispatcher").into()),
+        Method::from_builtin(instance_init),
+        Method::from_builtin(class_init),
+        mc,
+    );
+
+    let mut write = class.write(mc);
+
+    write.set_attributes(ClassAttributes::SEALED);
+
+    const PUBLIC_INSTANCE_PROPERTIES: &[(&str, Option<NativeMethod>, Option<NativeMethod>)] =
+        &[("context3D", Some(context3d), None)];
+    write.define_public_builtin_instance_properties(PUBLIC_INSTANCE_PROPERTIES);
+
+    const PUBLIC_INSTANCE_METHODS: &[(&str, NativeMethod)] = &[
+        ("requestContext3D", request_context3d),
+        ("requestContext3DMatchingProfiles", request_context3d),
+    ];
+    write.define_public_builtin_instance_methods(PUBLIC_INSTANCE_METHODS);
+
+    // The position and visibility of a `Stage3D` are kept, but have no effect.
+    write.define_instance_trait(Trait::from_slot(
+        QName::new(Namespace::public(), "x"),
+        QName::new(Namespace::public(), "Number").into(),
+        Some(0.0.into()),
+    ));
+    write.define_instance_trait(Trait::from_slot(
+        QName::new(Namespace::public(), "y"),
+        QName::new(Namespace::public(), "Number").into(),
+        Some(0.0.into()),
+    ));
+    write.define_instance_trait(Trait::from_slot(
+        QName::new(Namespace::public(), "visible"),
+        QName::new(Namespace::public(), "Boolean").into(),
+        Some(true.into()),
+    ));
+
+    class
+}
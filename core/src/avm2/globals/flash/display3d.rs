@@ -0,0 +1,5 @@
+//! `flash.display3D` namespace
+
+pub mod context3d;
+pub mod context3dprofile;
+pub mod context3drendermode;
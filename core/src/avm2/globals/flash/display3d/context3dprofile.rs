@@ -0,0 +1,59 @@
+//! `flash.display3D.Context3DProfile` builtin/prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::{Class, ClassAttributes};
+use crate::avm2::method::Method;
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::Object;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use gc_arena::{GcCell, MutationContext};
+
+/// Implements `flash.display3D.Context3DProfile`'s instance constructor.
+pub fn instance_init<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        activation.super_init(this, &[])?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `flash.display3D.Context3DProfile`'s class constructor.
+pub fn class_init<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Construct `Context3DProfile`'s class.
+pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>> {
+    let class = Class::new(
+        QName::new(Namespace::package("flash.display3D"), "Context3DProfile"),
+        Some(QName::new(Namespace::package(""), "Object").into()),
+        Method::from_builtin(instance_init),
+        Method::from_builtin(class_init),
+        mc,
+    );
+
+    let mut write = class.write(mc);
+
+    write.set_attributes(ClassAttributes::SEALED | ClassAttributes::FINAL);
+
+    const CONSTANTS: &[(&str, &str)] = &[
+        ("BASELINE", "baseline"),
+        ("BASELINE_CONSTRAINED", "baselineConstrained"),
+        ("BASELINE_EXTENDED", "baselineExtended"),
+        ("STANDARD", "standard"),
+        ("STANDARD_CONSTRAINED", "standardConstrained"),
+        ("STANDARD_EXTENDED", "standardExtended"),
+    ];
+    write.define_public_constant_string_class_traits(CONSTANTS);
+
+    class
+}
@@ -1,5 +1,8 @@
 //! `flash.events` namespace
 
+pub mod errorevent;
 pub mod event;
 pub mod eventdispatcher;
 pub mod ieventdispatcher;
+pub mod stageorientationevent;
+pub mod textevent;
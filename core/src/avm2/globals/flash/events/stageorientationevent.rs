@@ -0,0 +1,118 @@
+//! `flash.events.StageOrientationEvent` builtin/prototype
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::{Class, ClassAttributes};
+use crate::avm2::globals::NS_RUFFLE_INTERNAL;
+use crate::avm2::method::{Method, NativeMethod};
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject};
+use crate::avm2::traits::Trait;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use gc_arena::{GcCell, MutationContext};
+
+/// Implements `flash.events.StageOrientationEvent`'s instance constructor.
+pub fn instance_init<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        activation.super_init(this, args.get(0..3).unwrap_or(args))?;
+
+        for (index, name) in ["beforeOrientation", "afterOrientation"].iter().enumerate() {
+            let orientation = match args.get(3 + index).cloned().unwrap_or(Value::Null) {
+                Value::Null | Value::Undefined => Value::Null,
+                orientation => orientation.coerce_to_string(activation)?.into(),
+            };
+            this.set_property(
+                this,
+                &QName::new(Namespace::private(NS_RUFFLE_INTERNAL), *name),
+                orientation,
+                activation,
+            )?;
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `flash.events.StageOrientationEvent`'s class constructor.
+pub fn class_init<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Implements `beforeOrientation`'s getter.
+pub fn before_orientation<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        return this.get_property(
+            this,
+            &QName::new(Namespace::private(NS_RUFFLE_INTERNAL), "beforeOrientation"),
+            activation,
+        );
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `afterOrientation`'s getter.
+pub fn after_orientation<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(mut this) = this {
+        return this.get_property(
+            this,
+            &QName::new(Namespace::private(NS_RUFFLE_INTERNAL), "afterOrientation"),
+            activation,
+        );
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Construct `StageOrientationEvent`'s class.
+pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>> {
+    let class = Class::new(
+        QName::new(Namespace::package("flash.events"), "StageOrientationEvent"),
+        Some(QName::new(Namespace::package("flash.events"), "Event").into()),
+        Method::from_builtin(instance_init),
+        Method::from_builtin(class_init),
+        mc,
+    );
+
+    let mut write = class.write(mc);
+
+    write.set_attributes(ClassAttributes::SEALED);
+
+    const PUBLIC_INSTANCE_PROPERTIES: &[(&str, Option<NativeMethod>, Option<NativeMethod>)] = &[
+        ("beforeOrientation", Some(before_orientation), None),
+        ("afterOrientation", Some(after_orientation), None),
+    ];
+    write.define_public_builtin_instance_properties(PUBLIC_INSTANCE_PROPERTIES);
+
+    for name in &["beforeOrientation", "afterOrientation"] {
+        write.define_instance_trait(Trait::from_slot(
+            QName::new(Namespace::private(NS_RUFFLE_INTERNAL), *name),
+            QName::new(Namespace::public(), "String").into(),
+            None,
+        ));
+    }
+
+    const CONSTANTS: &[(&str, &str)] = &[
+        ("ORIENTATION_CHANGE", "orientationChange"),
+        ("ORIENTATION_CHANGING", "orientationChanging"),
+    ];
+    write.define_public_constant_string_class_traits(CONSTANTS);
+
+    class
+}
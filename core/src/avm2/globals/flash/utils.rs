@@ -1,8 +1,16 @@
 //! `flash.utils` namespace
 
-use crate::avm2::{Activation, Error, Object, Value};
+use crate::avm2::class::Class;
+use crate::avm2::globals::xml::escape_attribute_value;
+use crate::avm2::names::{Multiname, QName};
+use crate::avm2::string::AvmString;
+use crate::avm2::traits::TraitKind;
+use crate::avm2::{Activation, Error, Object, TObject, Value};
+use gc_arena::{GcCell, MutationContext};
+use std::fmt::Write;
 
 pub mod bytearray;
+pub mod dictionary;
 pub mod endian;
 
 /// Implements `flash.utils.getTimer`
@@ -11,5 +19,331 @@ pub fn get_timer<'gc>(
     _this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error> {
-    Ok((activation.context.navigator.time_since_launch().as_millis() as u32).into())
+    Ok((activation.context.time_since_launch().as_millis() as u32).into())
+}
+
+/// Implements `flash.utils.getQualifiedClassName`
+pub fn get_qualified_class_name<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let value = args.get(0).cloned().unwrap_or(Value::Undefined);
+
+    Ok(qualified_class_name(activation.context.gc_context, &value).into())
+}
+
+/// Implements `flash.utils.getDefinitionByName`
+pub fn get_definition_by_name<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let name = args
+        .get(0)
+        .cloned()
+        .unwrap_or(Value::Undefined)
+        .coerce_to_string(activation)?;
+    let qname = QName::from_qualified_name(&name, activation.context.gc_context);
+
+    // Definitions are looked up in the caller's application domain.
+    let domain = activation
+        .scope()
+        .and_then(|scope| scope.read().globals().as_application_domain())
+        .unwrap_or_else(|| activation.context.avm2.global_domain());
+    let (qname, mut defined_script) =
+        domain.get_defining_script(&qname.into())?.ok_or_else(|| {
+            format!(
+                "ReferenceError: Error #1065: Variable {} is not defined.",
+                name
+            )
+        })?;
+    let mut globals = defined_script.globals(&mut activation.context)?;
+
+    globals.get_property(globals, &qname, activation)
+}
+
+/// Implements `flash.utils.describeType`
+///
+/// The description is built from the traits of the value's class and its
+/// superclasses. Only public traits are described, and metadata and method
+/// signatures are left out.
+///
+/// TODO: This should return an `XML` object, but as we don't implement E4X
+/// yet, the XML is returned as a string.
+pub fn describe_type<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let value = args.get(0).cloned().unwrap_or(Value::Undefined);
+    let mc = activation.context.gc_context;
+    let name = qualified_class_name(mc, &value);
+
+    let mut object = match value {
+        Value::Undefined | Value::Null => {
+            let xml = format!(
+                "<type name=\"{}\" isDynamic=\"false\" isFinal=\"true\" isStatic=\"false\"/>",
+                name
+            );
+            return Ok(AvmString::new(mc, xml).into());
+        }
+        value => value.coerce_to_object(activation)?,
+    };
+
+    let mut xml = String::new();
+    match class_of(object) {
+        Some((class, true)) => {
+            let prototype = object
+                .get_property(object, &QName::dynamic_name("prototype"), activation)?
+                .coerce_to_object(activation)?;
+            let instance_chain = class_chain(prototype);
+            let class_constr_chain = class_chain(activation.avm2().prototypes().class);
+
+            write!(
+                xml,
+                "<type name=\"{}\" base=\"Class\" \
+                 isDynamic=\"true\" isFinal=\"true\" isStatic=\"true\">",
+                escape_attribute_value(&name)
+            )?;
+            write_extends_classes(&mut xml, &class_constr_chain, mc)?;
+            write_traits(&mut xml, &[class], true, mc)?;
+            write!(xml, "<factory type=\"{}\">", escape_attribute_value(&name))?;
+            write_extends_classes(&mut xml, instance_chain.get(1..).unwrap_or(&[]), mc)?;
+            write_interfaces(&mut xml, class, mc)?;
+            write_traits(&mut xml, &instance_chain, false, mc)?;
+            xml.push_str("</factory></type>");
+        }
+        Some((class, false)) => {
+            let chain = class_chain(object);
+            let class_read = class.read();
+            write!(xml, "<type name=\"{}\"", escape_attribute_value(&name))?;
+            if let Some(base) = chain.get(1) {
+                let base = base.read().name().to_qualified_name(mc);
+                write!(xml, " base=\"{}\"", escape_attribute_value(&base))?;
+            }
+            write!(
+                xml,
+                " isDynamic=\"{}\" isFinal=\"{}\" isStatic=\"false\">",
+                !class_read.is_sealed(),
+                class_read.is_final()
+            )?;
+            drop(class_read);
+
+            write_extends_classes(&mut xml, chain.get(1..).unwrap_or(&[]), mc)?;
+            write_interfaces(&mut xml, class, mc)?;
+            write_traits(&mut xml, &chain, false, mc)?;
+            xml.push_str("</type>");
+        }
+        None => {
+            write!(
+                xml,
+                "<type name=\"{}\" isDynamic=\"true\" isFinal=\"false\" isStatic=\"false\"/>",
+                escape_attribute_value(&name)
+            )?;
+        }
+    }
+
+    Ok(AvmString::new(mc, xml).into())
+}
+
+/// Get the fully qualified name of the class of a value.
+fn qualified_class_name<'gc>(mc: MutationContext<'gc, '_>, value: &Value<'gc>) -> AvmString<'gc> {
+    match value {
+        Value::Undefined => "void".into(),
+        Value::Null => "null".into(),
+        Value::Bool(_) => "Boolean".into(),
+        Value::Number(n) if f64::from(*n as i32) == *n => "int".into(),
+        Value::Number(_) => "Number".into(),
+        Value::Integer(_) => "int".into(),
+        Value::Unsigned(u) if *u <= i32::MAX as u32 => "int".into(),
+        Value::Unsigned(_) => "uint".into(),
+        Value::String(_) => "String".into(),
+        Value::Object(object) => class_of(*object)
+            .map(|(class, _)| class.read().name().to_qualified_name(mc))
+            .unwrap_or_else(|| "Object".into()),
+    }
+}
+
+/// Get the class of an object, and whether the object is the class itself
+/// rather than an instance of it.
+fn class_of<'gc>(object: Object<'gc>) -> Option<(GcCell<'gc, Class<'gc>>, bool)> {
+    if let Some(class) = object.as_class() {
+        // Class constructors are the only objects with a class of their own
+        // that can be called; the others are prototypes.
+        return Some((class, object.as_executable().is_some()));
+    }
+
+    object.as_proto_class().map(|class| (class, false))
+}
+
+/// Collect the classes on the prototype chain of an object, starting with
+/// the object's own class.
+fn class_chain<'gc>(object: Object<'gc>) -> Vec<GcCell<'gc, Class<'gc>>> {
+    let mut chain: Vec<GcCell<'gc, Class<'gc>>> = Vec::new();
+    let mut my_proto = Some(object);
+
+    while let Some(proto) = my_proto {
+        if let Some(class) = proto.as_class() {
+            if !chain
+                .last()
+                .map(|c| GcCell::ptr_eq(*c, class))
+                .unwrap_or(false)
+            {
+                chain.push(class);
+            }
+        }
+
+        my_proto = proto.proto();
+    }
+
+    chain
+}
+
+/// Get the fully qualified name of a type, as named by a trait.
+fn type_name<'gc>(multiname: &Multiname<'gc>, mc: MutationContext<'gc, '_>) -> AvmString<'gc> {
+    match (multiname.namespace_set().next(), multiname.local_name()) {
+        (Some(ns), Some(name)) => QName::new(ns.clone(), name).to_qualified_name(mc),
+        (None, Some(name)) => name,
+        (_, None) => "*".into(),
+    }
+}
+
+fn write_extends_classes<'gc>(
+    xml: &mut String,
+    chain: &[GcCell<'gc, Class<'gc>>],
+    mc: MutationContext<'gc, '_>,
+) -> Result<(), Error> {
+    for class in chain {
+        let name = class.read().name().to_qualified_name(mc);
+        write!(
+            xml,
+            "<extendsClass type=\"{}\"/>",
+            escape_attribute_value(&name)
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_interfaces<'gc>(
+    xml: &mut String,
+    class: GcCell<'gc, Class<'gc>>,
+    mc: MutationContext<'gc, '_>,
+) -> Result<(), Error> {
+    for interface in class.read().interfaces() {
+        let name = type_name(interface, mc);
+        write!(
+            xml,
+            "<implementsInterface type=\"{}\"/>",
+            escape_attribute_value(&name)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// A public trait, as it appears in the output of `describeType`.
+enum Description<'gc> {
+    Constant(AvmString<'gc>),
+    Variable(AvmString<'gc>),
+    Accessor { read: bool, write: bool },
+    Method,
+}
+
+/// Describe the public traits of a chain of classes, starting with the most
+/// derived class.
+///
+/// Traits that are overridden by a subclass are only described once, as
+/// declared by the subclass.
+fn write_traits<'gc>(
+    xml: &mut String,
+    chain: &[GcCell<'gc, Class<'gc>>],
+    is_static: bool,
+    mc: MutationContext<'gc, '_>,
+) -> Result<(), Error> {
+    let mut descriptions: Vec<(AvmString<'gc>, AvmString<'gc>, Description<'gc>)> = Vec::new();
+
+    for class in chain {
+        let class = class.read();
+        let declared_by = class.name().to_qualified_name(mc);
+        let traits = if is_static {
+            class.class_traits()
+        } else {
+            class.instance_traits()
+        };
+
+        for trait_entry in traits {
+            if !trait_entry.name().namespace().is_public() {
+                continue;
+            }
+
+            let name = trait_entry.name().local_name();
+            let (read, write) = match trait_entry.kind() {
+                TraitKind::Getter { .. } => (true, false),
+                TraitKind::Setter { .. } => (false, true),
+                _ => (false, false),
+            };
+
+            let existing = descriptions.iter_mut().find(|(n, _, _)| *n == name);
+            if let Some((_, _, description)) = existing {
+                // A getter and setter pair may be split across classes.
+                if let Description::Accessor { read: r, write: w } = description {
+                    *r |= read;
+                    *w |= write;
+                }
+
+                continue;
+            }
+
+            let description = match trait_entry.kind() {
+                TraitKind::Slot { type_name: t, .. } => Description::Variable(type_name(t, mc)),
+                TraitKind::Const { type_name: t, .. } => Description::Constant(type_name(t, mc)),
+                TraitKind::Class { .. } => Description::Constant("Class".into()),
+                TraitKind::Getter { .. } | TraitKind::Setter { .. } => {
+                    Description::Accessor { read, write }
+                }
+                TraitKind::Method { .. } | TraitKind::Function { .. } => Description::Method,
+            };
+            descriptions.push((name, declared_by, description));
+        }
+    }
+
+    for (name, declared_by, description) in descriptions {
+        let name = escape_attribute_value(&name);
+        let declared_by = escape_attribute_value(&declared_by);
+        match description {
+            Description::Constant(type_name) => write!(
+                xml,
+                "<constant name=\"{}\" type=\"{}\"/>",
+                name,
+                escape_attribute_value(&type_name)
+            )?,
+            Description::Variable(type_name) => write!(
+                xml,
+                "<variable name=\"{}\" type=\"{}\"/>",
+                name,
+                escape_attribute_value(&type_name)
+            )?,
+            Description::Accessor { read, write } => {
+                let access = match (read, write) {
+                    (true, true) => "readwrite",
+                    (true, false) => "readonly",
+                    _ => "writeonly",
+                };
+                write!(
+                    xml,
+                    "<accessor name=\"{}\" access=\"{}\" declaredBy=\"{}\"/>",
+                    name, access, declared_by
+                )?
+            }
+            Description::Method => write!(
+                xml,
+                "<method name=\"{}\" declaredBy=\"{}\"/>",
+                name, declared_by
+            )?,
+        }
+    }
+
+    Ok(())
 }
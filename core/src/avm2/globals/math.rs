@@ -7,6 +7,7 @@ use crate::avm2::names::{Namespace, QName};
 use crate::avm2::object::Object;
 use crate::avm2::value::Value;
 use crate::avm2::Error;
+use crate::flash_math;
 use gc_arena::{GcCell, MutationContext};
 use rand::Rng;
 
@@ -99,10 +100,7 @@ fn round<'gc>(
 ) -> Result<Value<'gc>, Error> {
     if let Some(x) = args.get(0) {
         let x = x.coerce_to_number(activation)?;
-        // Note that Flash Math.round always rounds toward infinity,
-        // unlike Rust f32::round which rounds away from zero.
-        let ret = (x + 0.5).floor();
-        return Ok(ret.into());
+        return Ok(flash_math::round(x).into());
     }
     Ok(f64::NAN.into())
 }
@@ -120,7 +118,7 @@ fn atan2<'gc>(
         .get(1)
         .unwrap_or(&Value::Undefined)
         .coerce_to_number(activation)?;
-    Ok(f64::atan2(y, x).into())
+    Ok(flash_math::atan2(y, x).into())
 }
 
 fn max<'gc>(
@@ -170,7 +168,7 @@ fn pow<'gc>(
         .get(1)
         .unwrap_or(&Value::Undefined)
         .coerce_to_number(activation)?;
-    Ok(f64::powf(n, p).into())
+    Ok(flash_math::pow(n, p).into())
 }
 
 pub fn random<'gc>(
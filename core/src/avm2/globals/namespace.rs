@@ -2,20 +2,47 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::class::Class;
-use crate::avm2::method::Method;
+use crate::avm2::method::{Method, NativeMethod};
 use crate::avm2::names::{Namespace, QName};
-use crate::avm2::object::Object;
+use crate::avm2::object::{Object, TObject};
 use crate::avm2::value::Value;
 use crate::avm2::Error;
 use gc_arena::{GcCell, MutationContext};
 
 /// Implements `Namespace`'s instance initializer.
+///
+/// Namespaces can be constructed from a URI, another namespace, or a prefix
+/// and a URI. Prefixes are only meaningful to XML and are not retained.
 pub fn instance_init<'gc>(
-    _activation: &mut Activation<'_, 'gc, '_>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error> {
-    Err("Namespace constructor is a stub.".into())
+    if let Some(this) = this {
+        activation.super_init(this, &[])?;
+
+        let uri_value = args.get(1).or_else(|| args.get(0)).cloned();
+        let namespace = match uri_value {
+            None | Some(Value::Undefined) => Namespace::public(),
+            Some(Value::Object(object)) if object.as_namespace().is_some() => {
+                object.as_namespace().unwrap().clone()
+            }
+            Some(uri) => {
+                let uri = uri.coerce_to_string(activation)?;
+                if uri.is_empty() {
+                    Namespace::public()
+                } else {
+                    Namespace::Namespace(uri)
+                }
+            }
+        };
+
+        if let Some(mut this_namespace) = this.as_namespace_mut(activation.context.gc_context) {
+            *this_namespace = namespace;
+        }
+    }
+
+    Ok(Value::Undefined)
 }
 
 /// Implements `Namespace`'s class initializer.
@@ -27,13 +54,55 @@ pub fn class_init<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `Namespace.uri`
+pub fn uri<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if let Some(namespace) = this.as_namespace() {
+            return Ok(namespace.as_uri().into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Namespace.prefix`
+///
+/// Only the public namespace has a known prefix, which is empty.
+pub fn prefix<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if let Some(namespace) = this.as_namespace() {
+            if namespace.as_uri().is_empty() {
+                return Ok("".into());
+            }
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 /// Construct `Namespace`'s class.
 pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>> {
-    Class::new(
+    let class = Class::new(
         QName::new(Namespace::public(), "Namespace"),
         Some(QName::new(Namespace::public(), "Object").into()),
         Method::from_builtin(instance_init),
         Method::from_builtin(class_init),
         mc,
-    )
+    );
+
+    let mut write = class.write(mc);
+
+    const PUBLIC_INSTANCE_PROPERTIES: &[(&str, Option<NativeMethod>, Option<NativeMethod>)] =
+        &[("uri", Some(uri), None), ("prefix", Some(prefix), None)];
+    write.define_public_builtin_instance_properties(PUBLIC_INSTANCE_PROPERTIES);
+
+    class
 }
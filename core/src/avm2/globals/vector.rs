@@ -0,0 +1,551 @@
+//! `Vector` and its specializations
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::{Class, ClassAttributes};
+use crate::avm2::globals::array::resolve_index;
+use crate::avm2::method::{Method, NativeMethod};
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::{Object, TObject, VectorObject};
+use crate::avm2::string::AvmString;
+use crate::avm2::value::Value;
+use crate::avm2::vector::{VectorStorage, VectorType};
+use crate::avm2::Error;
+use gc_arena::{GcCell, MutationContext};
+
+/// The package that `Vector` and its specializations live in.
+pub const VECTOR_PACKAGE: &str = "__AS3__.vec";
+
+/// The names of each specialization of `Vector`, along with the type of value
+/// they hold.
+pub const SPECIALIZATIONS: &[(&str, VectorType)] = &[
+    ("Vector$int", VectorType::Int),
+    ("Vector$uint", VectorType::Uint),
+    ("Vector$double", VectorType::Number),
+    ("Vector$object", VectorType::Object),
+];
+
+/// Implements the instance constructor of the unspecialized `Vector` class.
+pub fn instance_init<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Err("TypeError: Vector must be given a type parameter before it can be constructed".into())
+}
+
+/// Implements the instance constructor of the specializations of `Vector`.
+pub fn specialized_instance_init<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        activation.super_init(this, &[])?;
+
+        let length = args
+            .get(0)
+            .cloned()
+            .unwrap_or_else(|| 0.into())
+            .coerce_to_u32(activation)?;
+        let is_fixed = args.get(1).map(|v| v.coerce_to_boolean()).unwrap_or(false);
+
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            *vector = VectorStorage::new(length as usize, is_fixed, vector.value_type());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector`'s class constructor.
+pub fn class_init<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    Ok(Value::Undefined)
+}
+
+/// Apply type parameters to `Vector`, yielding the class of the matching
+/// specialization.
+///
+/// This implements the `applytype` opcode. `Vector` is the only parameterized
+/// type, and it takes exactly one type parameter. Vectors of the numeric
+/// types each have their own specialization; all other vectors are
+/// `Vector$object`s, which do not check the types of their elements.
+pub fn apply_type<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    base: Object<'gc>,
+    params: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let is_vector = base
+        .as_class()
+        .map(|c| *c.read().name() == QName::new(Namespace::package(VECTOR_PACKAGE), "Vector"))
+        .unwrap_or(false);
+    if !is_vector {
+        return Err("TypeError: Only Vector may be given type parameters".into());
+    }
+
+    if params.len() != 1 {
+        return Err(format!(
+            "TypeError: Vector takes 1 type parameter, but {} were given",
+            params.len()
+        )
+        .into());
+    }
+
+    let param_name = match &params[0] {
+        Value::Null | Value::Undefined => None,
+        param => param
+            .coerce_to_object(activation)?
+            .as_class()
+            .map(|c| c.read().name().clone()),
+    };
+
+    let sp = activation.context.avm2.prototypes();
+    let mut proto = match param_name {
+        Some(name) if name == QName::new(Namespace::public(), "int") => sp.vector_int,
+        Some(name) if name == QName::new(Namespace::public(), "uint") => sp.vector_uint,
+        Some(name) if name == QName::new(Namespace::public(), "Number") => sp.vector_number,
+        _ => sp.vector_object,
+    };
+
+    proto.get_property(
+        proto,
+        &QName::new(Namespace::public(), "constructor"),
+        activation,
+    )
+}
+
+/// Construct a new vector with the same specialization as another one.
+fn build_vector<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    like: Object<'gc>,
+    vector: VectorStorage<'gc>,
+) -> Result<Value<'gc>, Error> {
+    let proto = like
+        .proto()
+        .ok_or("Attempted to construct a Vector without a prototype")?;
+
+    Ok(VectorObject::from_vector(vector, proto, activation.context.gc_context).into())
+}
+
+/// Coerce a list of values to the type held by a vector.
+fn coerce_values<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    value_type: VectorType,
+    values: impl IntoIterator<Item = Value<'gc>>,
+) -> Result<Vec<Value<'gc>>, Error> {
+    values
+        .into_iter()
+        .map(|v| value_type.coerce(v, activation))
+        .collect()
+}
+
+/// Implements `Vector.length`'s getter
+pub fn length<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if let Some(vector) = this.as_vector_storage() {
+            return Ok(vector.length().into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.length`'s setter
+pub fn set_length<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let length = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_u32(activation)?;
+
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            vector.set_length(length as usize)?;
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.fixed`'s getter
+pub fn fixed<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if let Some(vector) = this.as_vector_storage() {
+            return Ok(vector.is_fixed().into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.fixed`'s setter
+pub fn set_fixed<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let is_fixed = args.get(0).unwrap_or(&Value::Undefined).coerce_to_boolean();
+
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            vector.set_is_fixed(is_fixed);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.concat`
+pub fn concat<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let (mut new_vector, value_type) = match this.as_vector_storage() {
+            Some(vector) => {
+                let mut new_vector = vector.empty_like();
+                for value in vector.iter() {
+                    new_vector.push(value)?;
+                }
+
+                (new_vector, vector.value_type())
+            }
+            None => return Ok(Value::Undefined),
+        };
+
+        for arg in args {
+            let other = arg.coerce_to_object(activation)?;
+            let values: Vec<Value<'gc>> = match other.as_vector_storage() {
+                Some(other_vector) => other_vector.iter().collect(),
+                None => return Err("TypeError: Vector.concat only accepts Vectors".into()),
+            };
+
+            for value in coerce_values(activation, value_type, values)? {
+                new_vector.push(value)?;
+            }
+        }
+
+        return build_vector(activation, this, new_vector);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.join`
+pub fn join<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    let mut separator = args.get(0).cloned().unwrap_or(Value::Undefined);
+    if separator == Value::Undefined {
+        separator = ",".into();
+    }
+
+    if let Some(this) = this {
+        let values: Vec<Value<'gc>> = match this.as_vector_storage() {
+            Some(vector) => vector.iter().collect(),
+            None => return Ok(Value::Undefined),
+        };
+
+        let separator = separator.coerce_to_string(activation)?;
+        let mut accum = Vec::with_capacity(values.len());
+
+        for value in values {
+            if matches!(value, Value::Undefined) || matches!(value, Value::Null) {
+                accum.push("".into());
+            } else {
+                accum.push(value.coerce_to_string(activation)?.to_string());
+            }
+        }
+
+        return Ok(AvmString::new(activation.context.gc_context, accum.join(&separator)).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.toString`
+pub fn to_string<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    join(activation, this, &[",".into()])
+}
+
+/// Implements `Vector.indexOf`
+pub fn index_of<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let value_type = match this.as_vector_storage() {
+            Some(vector) => vector.value_type(),
+            None => return Ok(Value::Undefined),
+        };
+        let search_val =
+            value_type.coerce(args.get(0).cloned().unwrap_or(Value::Undefined), activation)?;
+        let length = this.as_vector_storage().unwrap().length();
+        let from = resolve_index(
+            activation,
+            args.get(1).cloned().unwrap_or_else(|| 0.into()),
+            length,
+        )?;
+
+        for (i, val) in this.as_vector_storage().unwrap().iter().enumerate() {
+            if i >= from && val == search_val {
+                return Ok(i.into());
+            }
+        }
+
+        return Ok((-1).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.lastIndexOf`
+pub fn last_index_of<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let value_type = match this.as_vector_storage() {
+            Some(vector) => vector.value_type(),
+            None => return Ok(Value::Undefined),
+        };
+        let search_val =
+            value_type.coerce(args.get(0).cloned().unwrap_or(Value::Undefined), activation)?;
+        let length = this.as_vector_storage().unwrap().length();
+        let from = resolve_index(
+            activation,
+            args.get(1).cloned().unwrap_or_else(|| i32::MAX.into()),
+            length,
+        )?;
+
+        for (i, val) in this.as_vector_storage().unwrap().iter().enumerate().rev() {
+            if i <= from && val == search_val {
+                return Ok(i.into());
+            }
+        }
+
+        return Ok((-1).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.pop`
+pub fn pop<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            return vector.pop();
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.push`
+pub fn push<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let value_type = match this.as_vector_storage() {
+            Some(vector) => vector.value_type(),
+            None => return Ok(Value::Undefined),
+        };
+        let values = coerce_values(activation, value_type, args.iter().cloned())?;
+
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            for value in values {
+                vector.push(value)?;
+            }
+
+            return Ok(vector.length().into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.reverse`
+pub fn reverse<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            vector.reverse();
+
+            return Ok(this.into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.shift`
+pub fn shift<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            return vector.shift();
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.unshift`
+pub fn unshift<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let value_type = match this.as_vector_storage() {
+            Some(vector) => vector.value_type(),
+            None => return Ok(Value::Undefined),
+        };
+        let values = coerce_values(activation, value_type, args.iter().cloned())?;
+
+        if let Some(mut vector) = this.as_vector_storage_mut(activation.context.gc_context) {
+            for value in values.into_iter().rev() {
+                vector.unshift(value)?;
+            }
+
+            return Ok(vector.length().into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Vector.slice`
+pub fn slice<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error> {
+    if let Some(this) = this {
+        let length = match this.as_vector_storage() {
+            Some(vector) => vector.length(),
+            None => return Ok(Value::Undefined),
+        };
+        let start = resolve_index(
+            activation,
+            args.get(0).cloned().unwrap_or_else(|| 0.into()),
+            length,
+        )?;
+        let end = resolve_index(
+            activation,
+            args.get(1).cloned().unwrap_or_else(|| 0xFFFFFF.into()),
+            length,
+        )?;
+
+        let vector = this.as_vector_storage().unwrap();
+        let mut new_vector = vector.empty_like();
+        for value in vector.iter().take(end).skip(start) {
+            new_vector.push(value)?;
+        }
+        drop(vector);
+
+        return build_vector(activation, this, new_vector);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Define the methods and properties shared by `Vector` and each of its
+/// specializations.
+fn define_vector_traits<'gc>(class: &mut Class<'gc>) {
+    class.set_attributes(ClassAttributes::FINAL);
+
+    const PUBLIC_INSTANCE_METHODS: &[(&str, NativeMethod)] = &[("toString", to_string)];
+    class.define_public_builtin_instance_methods(PUBLIC_INSTANCE_METHODS);
+
+    const PUBLIC_INSTANCE_PROPERTIES: &[(&str, Option<NativeMethod>, Option<NativeMethod>)] = &[
+        ("length", Some(length), Some(set_length)),
+        ("fixed", Some(fixed), Some(set_fixed)),
+    ];
+    class.define_public_builtin_instance_properties(PUBLIC_INSTANCE_PROPERTIES);
+
+    const AS3_INSTANCE_METHODS: &[(&str, NativeMethod)] = &[
+        ("concat", concat),
+        ("join", join),
+        ("indexOf", index_of),
+        ("lastIndexOf", last_index_of),
+        ("pop", pop),
+        ("push", push),
+        ("reverse", reverse),
+        ("shift", shift),
+        ("unshift", unshift),
+        ("slice", slice),
+    ];
+    class.define_as3_builtin_instance_methods(AS3_INSTANCE_METHODS);
+}
+
+/// Construct the unspecialized `Vector` class.
+///
+/// This class cannot be constructed; it exists to be given a type parameter
+/// with `applytype`.
+pub fn create_class<'gc>(mc: MutationContext<'gc, '_>) -> GcCell<'gc, Class<'gc>> {
+    let class = Class::new(
+        QName::new(Namespace::package(VECTOR_PACKAGE), "Vector"),
+        Some(QName::new(Namespace::public(), "Object").into()),
+        Method::from_builtin(instance_init),
+        Method::from_builtin(class_init),
+        mc,
+    );
+
+    define_vector_traits(&mut class.write(mc));
+
+    class
+}
+
+/// Construct the class of one of the specializations of `Vector`.
+pub fn create_specialized_class<'gc>(
+    mc: MutationContext<'gc, '_>,
+    name: &'static str,
+) -> GcCell<'gc, Class<'gc>> {
+    let class = Class::new(
+        QName::new(Namespace::package(VECTOR_PACKAGE), name),
+        Some(QName::new(Namespace::public(), "Object").into()),
+        Method::from_builtin(specialized_instance_init),
+        Method::from_builtin(class_init),
+        mc,
+    );
+
+    define_vector_traits(&mut class.write(mc));
+
+    class
+}
@@ -9,6 +9,26 @@ use crate::avm2::value::Value;
 use crate::avm2::Error;
 use gc_arena::{GcCell, MutationContext};
 
+/// Escape a string for use as an XML attribute value.
+///
+/// This implements `EscapeAttributeValue` from ECMA-357 (10.2.1.2).
+pub fn escape_attribute_value(s: &str) -> String {
+    let mut r = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => r += "&quot;",
+            '<' => r += "&lt;",
+            '&' => r += "&amp;",
+            '\u{000A}' => r += "&#xA;",
+            '\u{000D}' => r += "&#xD;",
+            '\u{0009}' => r += "&#x9;",
+            _ => r.push(c),
+        }
+    }
+
+    r
+}
+
 /// Implements `XML`'s instance initializer.
 pub fn instance_init<'gc>(
     _activation: &mut Activation<'_, 'gc, '_>,
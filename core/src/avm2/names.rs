@@ -9,6 +9,13 @@ use swf::avm2::types::{
     Index, Multiname as AbcMultiname, Namespace as AbcNamespace, NamespaceSet as AbcNamespaceSet,
 };
 
+/// How many parameterized type names may be stacked on one another before a
+/// multiname is rejected.
+///
+/// Real code only ever names a plain type as the base of a parameterized one;
+/// this just stops malicious constant pools from recursing forever.
+const MAX_TYPENAME_DEPTH: usize = 16;
+
 /// Represents the name of a namespace.
 #[derive(Clone, Collect, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[collect(no_drop)]
@@ -90,6 +97,10 @@ impl<'gc> Namespace<'gc> {
         matches!(self, Self::Private(_))
     }
 
+    pub fn is_protected(&self) -> bool {
+        matches!(self, Self::Protected(_))
+    }
+
     pub fn is_dynamic(&self) -> bool {
         self.is_public() || self.is_any()
     }
@@ -190,6 +201,34 @@ impl<'gc> QName<'gc> {
         }
     }
 
+    /// Given a fully qualified class name, parse it as a `QName`.
+    ///
+    /// This accepts both the `package::Name` form produced by
+    /// `getQualifiedClassName` and the dotted form used by symbol classes.
+    pub fn from_qualified_name(name: &str, mc: MutationContext<'gc, '_>) -> Self {
+        match &name.rsplitn(2, "::").collect::<Vec<&str>>()[..] {
+            [local_name, package_name] => Self {
+                ns: Namespace::Package(AvmString::new(mc, package_name.to_string())),
+                name: AvmString::new(mc, local_name.to_string()),
+            },
+            _ => Self::from_symbol_class(name, mc)
+                .unwrap_or_else(|| Self::dynamic_name(AvmString::new(mc, name.to_string()))),
+        }
+    }
+
+    /// Convert this `QName` to a fully qualified name, in the `package::Name`
+    /// form used by `getQualifiedClassName`.
+    ///
+    /// Names in the public namespace are returned as-is.
+    pub fn to_qualified_name(&self, mc: MutationContext<'gc, '_>) -> AvmString<'gc> {
+        let uri = self.ns.as_uri();
+        if uri.is_empty() {
+            self.name
+        } else {
+            AvmString::new(mc, format!("{}::{}", uri, self.name))
+        }
+    }
+
     pub fn local_name(&self) -> AvmString<'gc> {
         self.name
     }
@@ -321,6 +360,11 @@ impl<'gc> Multiname<'gc> {
                     name: Some(name),
                 }
             }
+            AbcMultiname::TypeName { base_type, .. } => Self::from_abc_multiname_static(
+                translation_unit,
+                base_type.clone(),
+                activation.context.gc_context,
+            )?,
         })
     }
 
@@ -332,6 +376,17 @@ impl<'gc> Multiname<'gc> {
         translation_unit: TranslationUnit<'gc>,
         multiname_index: Index<AbcMultiname>,
         mc: MutationContext<'gc, '_>,
+    ) -> Result<Self, Error> {
+        Self::from_abc_multiname_static_nested(translation_unit, multiname_index, mc, 0)
+    }
+
+    /// Read a static multiname that is the base type of `depth` parameterized
+    /// type names.
+    fn from_abc_multiname_static_nested(
+        translation_unit: TranslationUnit<'gc>,
+        multiname_index: Index<AbcMultiname>,
+        mc: MutationContext<'gc, '_>,
+        depth: usize,
     ) -> Result<Self, Error> {
         let actual_index: Result<usize, Error> =
             (multiname_index.0 as usize).checked_sub(1).ok_or_else(|| {
@@ -367,6 +422,25 @@ impl<'gc> Multiname<'gc> {
                 ns: Self::abc_namespace_set(translation_unit, namespace_set.clone(), mc)?,
                 name: translation_unit.pool_string_option(name.0, mc)?,
             },
+            // Parameterized types (i.e. `Vector.<T>`) are looked up by the
+            // name of their base type; the type parameters only matter when
+            // the type is applied at runtime with `applytype`.
+            AbcMultiname::TypeName { base_type, .. } => {
+                if depth >= MAX_TYPENAME_DEPTH {
+                    return Err(format!(
+                        "Multiname {} nests too many parameterized types",
+                        multiname_index.0
+                    )
+                    .into());
+                }
+
+                Self::from_abc_multiname_static_nested(
+                    translation_unit,
+                    base_type.clone(),
+                    mc,
+                    depth + 1,
+                )?
+            }
             _ => return Err(format!("Multiname {} is not static", multiname_index.0).into()),
         })
     }
@@ -406,3 +480,111 @@ impl<'gc> From<QName<'gc>> for Multiname<'gc> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::avm2::Domain;
+    use std::rc::Rc;
+    use swf::avm2::types::{AbcFile, ConstantPool};
+
+    /// Build a translation unit whose constant pool holds the given
+    /// multinames, and the strings `Vector` and `int`.
+    fn translation_unit<'gc>(
+        multinames: Vec<AbcMultiname>,
+        mc: MutationContext<'gc, '_>,
+    ) -> TranslationUnit<'gc> {
+        let abc = AbcFile {
+            major_version: 46,
+            minor_version: 16,
+            constant_pool: ConstantPool {
+                ints: vec![],
+                uints: vec![],
+                doubles: vec![],
+                strings: vec!["Vector".to_string(), "int".to_string()],
+                namespaces: vec![],
+                namespace_sets: vec![],
+                multinames,
+            },
+            methods: vec![],
+            metadata: vec![],
+            instances: vec![],
+            classes: vec![],
+            scripts: vec![],
+            method_bodies: vec![],
+        };
+
+        TranslationUnit::from_abc(Rc::new(abc), Domain::global_domain(mc), mc)
+    }
+
+    #[test]
+    fn typenames_resolve_to_their_base_type() {
+        with_avm(9, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let mc = activation.context.gc_context;
+            let unit = translation_unit(
+                vec![
+                    AbcMultiname::QName {
+                        namespace: Index::new(0),
+                        name: Index::new(1),
+                    },
+                    AbcMultiname::QName {
+                        namespace: Index::new(0),
+                        name: Index::new(2),
+                    },
+                    AbcMultiname::TypeName {
+                        base_type: Index::new(1),
+                        parameters: vec![Index::new(2)],
+                    },
+                ],
+                mc,
+            );
+
+            let name = Multiname::from_abc_multiname_static(unit, Index::new(3), mc).unwrap();
+            assert_eq!(name.local_name().as_deref(), Some("Vector"));
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn cyclic_typenames_are_rejected() {
+        with_avm(9, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let mc = activation.context.gc_context;
+            let unit = translation_unit(
+                vec![
+                    AbcMultiname::TypeName {
+                        base_type: Index::new(2),
+                        parameters: vec![],
+                    },
+                    AbcMultiname::TypeName {
+                        base_type: Index::new(1),
+                        parameters: vec![],
+                    },
+                ],
+                mc,
+            );
+
+            assert!(Multiname::from_abc_multiname_static(unit, Index::new(1), mc).is_err());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn qualified_names_round_trip() {
+        with_avm(9, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let mc = activation.context.gc_context;
+            let sprite = QName::new(Namespace::package("flash.display"), "Sprite");
+            let object = QName::new(Namespace::public(), "Object");
+
+            // Symbol classes name their package with dots instead.
+            for name in &["flash.display::Sprite", "flash.display.Sprite"] {
+                assert_eq!(QName::from_qualified_name(name, mc), sprite, "{}", name);
+            }
+            assert_eq!(QName::from_qualified_name("Object", mc), object);
+
+            assert_eq!(&*sprite.to_qualified_name(mc), "flash.display::Sprite");
+            assert_eq!(&*object.to_qualified_name(mc), "Object");
+            Ok(())
+        });
+    }
+}
@@ -13,17 +13,20 @@ use crate::avm2::scope::Scope;
 use crate::avm2::string::AvmString;
 use crate::avm2::traits::{Trait, TraitKind};
 use crate::avm2::value::{Hint, Value};
+use crate::avm2::vector::VectorStorage;
 use crate::avm2::Error;
 use crate::display_object::DisplayObject;
 use gc_arena::{Collect, GcCell, MutationContext};
 use ruffle_macros::enum_trait_object;
 use std::cell::{Ref, RefMut};
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 
 mod array_object;
 mod bytearray_object;
 mod custom_object;
+mod dictionary_object;
 mod dispatch_object;
 mod domain_object;
 mod event_object;
@@ -34,10 +37,12 @@ mod primitive_object;
 mod regexp_object;
 mod script_object;
 mod stage_object;
+mod vector_object;
 mod xml_object;
 
 pub use crate::avm2::object::array_object::ArrayObject;
 pub use crate::avm2::object::bytearray_object::ByteArrayObject;
+pub use crate::avm2::object::dictionary_object::DictionaryObject;
 pub use crate::avm2::object::dispatch_object::DispatchObject;
 pub use crate::avm2::object::domain_object::DomainObject;
 pub use crate::avm2::object::event_object::EventObject;
@@ -48,6 +53,7 @@ pub use crate::avm2::object::primitive_object::PrimitiveObject;
 pub use crate::avm2::object::regexp_object::RegExpObject;
 pub use crate::avm2::object::script_object::ScriptObject;
 pub use crate::avm2::object::stage_object::StageObject;
+pub use crate::avm2::object::vector_object::VectorObject;
 pub use crate::avm2::object::xml_object::XmlObject;
 
 /// Represents an object that can be directly interacted with by the AVM2
@@ -69,6 +75,8 @@ pub use crate::avm2::object::xml_object::XmlObject;
         RegExpObject(RegExpObject<'gc>),
         ByteArrayObject(ByteArrayObject<'gc>),
         LoaderInfoObject(LoaderInfoObject<'gc>),
+        VectorObject(VectorObject<'gc>),
+        DictionaryObject(DictionaryObject<'gc>),
     }
 )]
 pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy {
@@ -343,6 +351,12 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
                 if self.has_property(&qname)? {
                     return Ok(Some(qname));
                 }
+
+                if ns.is_protected() {
+                    if let Some(qname) = resolve_inherited_protected(self.into(), &qname)? {
+                        return Ok(Some(qname));
+                    }
+                }
             } else {
                 return Ok(None);
             }
@@ -439,14 +453,46 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
 
     /// Retrieve a given enumerable name by index.
     ///
-    /// Enumerants are listed by index, starting from zero. A value of `None`
-    /// indicates that no enumerant with that index, or any greater index,
-    /// exists. (In other words, it means stop.)
+    /// Enumerants are listed by index, starting from one. A value of `None`
+    /// indicates that no enumerant with that index exists.
     ///
     /// Objects are responsible for maintaining a consistently ordered and
     /// indexed list of enumerable names which can be queried by this
-    /// mechanism.
-    fn get_enumerant_name(&self, index: u32) -> Option<QName<'gc>>;
+    /// mechanism. Names are usually strings, but array-like objects name
+    /// their elements by number, as `nextname` does in Flash Player.
+    fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>>;
+
+    /// Retrieve the index of the next enumerant after `last_index`.
+    ///
+    /// This is how `hasnext` and `hasnext2` step through an object. Passing
+    /// an index of zero yields the first enumerant. A value of `None`
+    /// indicates that there are no further enumerants.
+    ///
+    /// Objects whose enumerants have holes in them, such as sparse arrays,
+    /// must override this to skip over the holes.
+    fn get_next_enumerant(&self, last_index: u32) -> Option<u32> {
+        let index = last_index.checked_add(1)?;
+
+        self.get_enumerant_name(index).map(|_| index)
+    }
+
+    /// Retrieve the value of a given enumerant by index.
+    ///
+    /// Enumerants that do not exist yield `undefined`.
+    fn get_enumerant_value(
+        &self,
+        index: u32,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        match self.get_enumerant_name(index) {
+            Some(name) => {
+                let name = QName::new(Namespace::public(), name.coerce_to_string(activation)?);
+                let mut object: Object<'gc> = (*self).into();
+                object.get_property(object, &name, activation)
+            }
+            None => Ok(Value::Undefined),
+        }
+    }
 
     /// Determine if a property is currently enumerable.
     ///
@@ -852,7 +898,8 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
     /// The given object should be the prototype we are checking against this
     /// object. Its prototype will be searched in the
     /// prototype chain of this object. If `check_interfaces` is enabled, then
-    /// the interfaces listed on each prototype will also be checked.
+    /// the interfaces listed on each prototype, and the interfaces they
+    /// extend, will also be checked.
     fn has_prototype_in_chain(
         &self,
         type_proto: Object<'gc>,
@@ -866,12 +913,13 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
                 return Ok(true);
             }
 
-            if check_interfaces {
-                for interface in proto.interfaces() {
-                    if Object::ptr_eq(interface, type_proto) {
-                        return Ok(true);
-                    }
-                }
+            if check_interfaces
+                && proto
+                    .interfaces()
+                    .into_iter()
+                    .any(|interface| interface_extends(interface, type_proto))
+            {
+                return Ok(true);
             }
 
             my_proto = proto.proto()
@@ -891,10 +939,12 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
     /// This only yields `None` for bare objects.
     fn as_proto_class(&self) -> Option<GcCell<'gc, Class<'gc>>> {
         let mut class = self.as_class();
+        let mut my_proto = self.proto();
 
         while class.is_none() {
-            if let Some(proto) = self.proto() {
+            if let Some(proto) = my_proto {
                 class = proto.as_class();
+                my_proto = proto.proto();
             } else {
                 return None;
             }
@@ -913,11 +963,30 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
         None
     }
 
+    /// Unwrap this object's `Namespace` mutably, if the object is a boxed
+    /// namespace.
+    fn as_namespace_mut(&self, _mc: MutationContext<'gc, '_>) -> Option<RefMut<Namespace<'gc>>> {
+        None
+    }
+
     /// Unwrap this object as array storage.
     fn as_array_storage(&self) -> Option<Ref<ArrayStorage<'gc>>> {
         None
     }
 
+    /// Unwrap this object as vector storage.
+    fn as_vector_storage(&self) -> Option<Ref<VectorStorage<'gc>>> {
+        None
+    }
+
+    /// Unwrap this object as mutable vector storage.
+    fn as_vector_storage_mut(
+        &self,
+        _mc: MutationContext<'gc, '_>,
+    ) -> Option<RefMut<VectorStorage<'gc>>> {
+        None
+    }
+
     /// Unwrap this object as bytearray.
     fn as_bytearray(&self) -> Option<Ref<ByteArrayStorage>> {
         None
@@ -931,6 +1000,11 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
         None
     }
 
+    /// Unwrap this object as a dictionary.
+    fn as_dictionary_object(&self) -> Option<DictionaryObject<'gc>> {
+        None
+    }
+
     /// Unwrap this object as mutable array storage.
     fn as_array_storage_mut(
         &self,
@@ -998,6 +1072,60 @@ pub trait TObject<'gc>: 'gc + Collect + Debug + Into<Object<'gc>> + Clone + Copy
 
 pub enum ObjectPtr {}
 
+/// Resolve a name in the protected namespace of a class to a protected trait
+/// inherited from one of its superclasses.
+///
+/// Subclasses access the protected traits of their superclasses through their
+/// own protected namespace, but the traits are stored under the protected
+/// namespace of the class that declared them.
+fn resolve_inherited_protected<'gc>(
+    object: Object<'gc>,
+    name: &QName<'gc>,
+) -> Result<Option<QName<'gc>>, Error> {
+    let mut found_class = false;
+    let mut proto = Some(object);
+    while let Some(p) = proto {
+        if let Some(class) = p.as_class() {
+            let protected_ns = class.read().protected_namespace().cloned();
+            if let Some(protected_ns) = protected_ns {
+                if !found_class {
+                    found_class = protected_ns == *name.namespace();
+                } else {
+                    let super_name = QName::new(protected_ns, name.local_name());
+                    if object.has_property(&super_name)? {
+                        return Ok(Some(super_name));
+                    }
+                }
+            }
+        }
+
+        proto = p.proto();
+    }
+
+    Ok(None)
+}
+
+/// Determine if an interface prototype is, or extends, another one.
+///
+/// Each interface is only checked once, so interfaces that (illegally) extend
+/// one another don't loop forever.
+fn interface_extends<'gc>(interface: Object<'gc>, type_proto: Object<'gc>) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![interface];
+
+    while let Some(interface) = pending.pop() {
+        if Object::ptr_eq(interface, type_proto) {
+            return true;
+        }
+
+        if visited.insert(interface) {
+            pending.extend(interface.interfaces());
+        }
+    }
+
+    false
+}
+
 impl<'gc> Object<'gc> {
     pub fn ptr_eq(a: Object<'gc>, b: Object<'gc>) -> bool {
         a.as_ptr() == b.as_ptr()
@@ -1017,3 +1145,30 @@ impl<'gc> Hash for Object<'gc> {
         self.as_ptr().hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+
+    #[test]
+    fn cyclic_interfaces_terminate() {
+        with_avm(9, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let mc = activation.context.gc_context;
+            let first = ScriptObject::bare_object(mc);
+            let second = ScriptObject::bare_object(mc);
+            let unrelated = ScriptObject::bare_object(mc);
+            first.set_interfaces(mc, vec![second]);
+            second.set_interfaces(mc, vec![first]);
+
+            let proto = ScriptObject::bare_object(mc);
+            proto.set_interfaces(mc, vec![first]);
+            let object = ScriptObject::object(mc, proto);
+
+            assert!(object.has_prototype_in_chain(second, true).unwrap());
+            assert!(!object.has_prototype_in_chain(second, false).unwrap());
+            assert!(!object.has_prototype_in_chain(unrelated, true).unwrap());
+            Ok(())
+        });
+    }
+}
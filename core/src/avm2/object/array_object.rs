@@ -205,6 +205,82 @@ impl<'gc> TObject<'gc> for ArrayObject<'gc> {
         self.0.read().base.resolve_any_trait(local_name)
     }
 
+    /// Arrays enumerate their elements in index order, skipping holes,
+    /// followed by their dynamic properties.
+    fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>> {
+        let read = self.0.read();
+        let length = read.array.length() as u32;
+        let true_index = index.checked_sub(1)?;
+
+        if true_index < length {
+            return read
+                .array
+                .get(true_index as usize)
+                .map(|_| true_index.into());
+        }
+
+        read.base
+            .get_enumerant_name(index - length)
+            .map(|name| name.local_name().into())
+    }
+
+    fn get_next_enumerant(&self, last_index: u32) -> Option<u32> {
+        let read = self.0.read();
+        let length = read.array.length() as u32;
+        let mut index = last_index.checked_add(1)?;
+
+        while index <= length {
+            if read.array.get(index as usize - 1).is_some() {
+                return Some(index);
+            }
+
+            index += 1;
+        }
+
+        read.base.get_enumerant_name(index - length).map(|_| index)
+    }
+
+    fn get_enumerant_value(
+        &self,
+        index: u32,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let read = self.0.read();
+        let length = read.array.length() as u32;
+        let true_index = match index.checked_sub(1) {
+            Some(true_index) => true_index,
+            None => return Ok(Value::Undefined),
+        };
+
+        if true_index < length {
+            return Ok(read
+                .array
+                .get(true_index as usize)
+                .unwrap_or(Value::Undefined));
+        }
+
+        let name = read.base.get_enumerant_name(index - length);
+        drop(read);
+
+        match name {
+            Some(name) => {
+                let mut object: Object<'gc> = (*self).into();
+                object.get_property(object, &name, activation)
+            }
+            None => Ok(Value::Undefined),
+        }
+    }
+
+    fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
+        if name.namespace().is_public() {
+            if let Ok(index) = name.local_name().parse::<usize>() {
+                return self.0.read().array.get(index).is_some();
+            }
+        }
+
+        self.0.read().base.property_is_enumerable(name)
+    }
+
     fn to_string(&self, _mc: MutationContext<'gc, '_>) -> Result<Value<'gc>, Error> {
         Ok(Value::Object(Object::from(*self)))
     }
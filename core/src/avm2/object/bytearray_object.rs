@@ -201,6 +201,18 @@ impl<'gc> TObject<'gc> for ByteArrayObject<'gc> {
         self.0.read().base.resolve_any_trait(local_name)
     }
 
+    fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>> {
+        self.0
+            .read()
+            .base
+            .get_enumerant_name(index)
+            .map(|name| name.local_name().into())
+    }
+
+    fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
+        self.0.read().base.property_is_enumerable(name)
+    }
+
     fn construct(
         &self,
         activation: &mut Activation<'_, 'gc, '_>,
@@ -88,6 +88,18 @@ macro_rules! impl_avm2_custom_object_properties {
         ) -> Result<Option<Namespace<'gc>>, Error> {
             self.0.read().$field.resolve_any_trait(local_name)
         }
+
+        fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>> {
+            self.0
+                .read()
+                .$field
+                .get_enumerant_name(index)
+                .map(|name| name.local_name().into())
+        }
+
+        fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
+            self.0.read().$field.property_is_enumerable(name)
+        }
     };
 }
 
@@ -176,14 +188,6 @@ macro_rules! impl_avm2_custom_object {
             self.0.write(mc).$field.set_proto(proto)
         }
 
-        fn get_enumerant_name(&self, index: u32) -> Option<QName<'gc>> {
-            self.0.read().$field.get_enumerant_name(index)
-        }
-
-        fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
-            self.0.read().$field.property_is_enumerable(name)
-        }
-
         fn set_local_property_is_enumerable(
             &self,
             mc: MutationContext<'gc, '_>,
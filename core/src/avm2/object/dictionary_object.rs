@@ -0,0 +1,284 @@
+//! Object representation for `flash.utils.Dictionary`
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::Class;
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::script_object::{ScriptObjectClass, ScriptObjectData};
+use crate::avm2::object::{Object, ObjectPtr, TObject};
+use crate::avm2::scope::Scope;
+use crate::avm2::string::AvmString;
+use crate::avm2::traits::Trait;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use crate::impl_avm2_custom_object;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::collections::HashMap;
+
+/// An object that can use other objects as property names.
+///
+/// Properties named with primitives are stored as ordinary dynamic
+/// properties. Properties named with objects are keyed by the object's
+/// identity, rather than by its string value.
+#[derive(Clone, Collect, Debug, Copy)]
+#[collect(no_drop)]
+pub struct DictionaryObject<'gc>(GcCell<'gc, DictionaryObjectData<'gc>>);
+
+#[derive(Clone, Collect, Debug)]
+#[collect(no_drop)]
+pub struct DictionaryObjectData<'gc> {
+    /// Base script object
+    base: ScriptObjectData<'gc>,
+
+    /// Values keyed by object identity.
+    object_space: HashMap<Object<'gc>, Value<'gc>>,
+
+    /// The keys of `object_space`, in the order they were added in.
+    ///
+    /// Object keys are enumerated in this order, before any dynamic
+    /// properties.
+    object_keys: Vec<Object<'gc>>,
+}
+
+impl<'gc> DictionaryObject<'gc> {
+    /// Instantiate a dictionary subclass.
+    pub fn derive(
+        base_proto: Object<'gc>,
+        mc: MutationContext<'gc, '_>,
+        class: GcCell<'gc, Class<'gc>>,
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+    ) -> Result<Object<'gc>, Error> {
+        let base = ScriptObjectData::base_new(
+            Some(base_proto),
+            ScriptObjectClass::InstancePrototype(class, scope),
+        );
+
+        Ok(Self::from_base(mc, base))
+    }
+
+    /// Construct an empty dictionary.
+    pub fn empty_object(
+        mc: MutationContext<'gc, '_>,
+        base_proto: Option<Object<'gc>>,
+    ) -> Object<'gc> {
+        let base = ScriptObjectData::base_new(base_proto, ScriptObjectClass::NoClass);
+
+        Self::from_base(mc, base)
+    }
+
+    fn from_base(mc: MutationContext<'gc, '_>, base: ScriptObjectData<'gc>) -> Object<'gc> {
+        DictionaryObject(GcCell::allocate(
+            mc,
+            DictionaryObjectData {
+                base,
+                object_space: HashMap::new(),
+                object_keys: Vec::new(),
+            },
+        ))
+        .into()
+    }
+
+    /// Retrieve the value stored under an object key.
+    pub fn get_property_by_object(self, name: Object<'gc>) -> Value<'gc> {
+        self.0
+            .read()
+            .object_space
+            .get(&name)
+            .cloned()
+            .unwrap_or(Value::Undefined)
+    }
+
+    /// Store a value under an object key.
+    pub fn set_property_by_object(
+        self,
+        name: Object<'gc>,
+        value: Value<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) {
+        let mut write = self.0.write(mc);
+        if write.object_space.insert(name, value).is_none() {
+            write.object_keys.push(name);
+        }
+    }
+
+    /// Remove an object key.
+    ///
+    /// Like deleting any other dynamic property, this succeeds even if the
+    /// key wasn't there.
+    pub fn delete_property_by_object(self, name: Object<'gc>, mc: MutationContext<'gc, '_>) {
+        let mut write = self.0.write(mc);
+        if write.object_space.remove(&name).is_some() {
+            write.object_keys.retain(|key| !Object::ptr_eq(*key, name));
+        }
+    }
+
+    /// Determine if a value is stored under an object key.
+    pub fn has_property_by_object(self, name: Object<'gc>) -> bool {
+        self.0.read().object_space.contains_key(&name)
+    }
+}
+
+impl<'gc> TObject<'gc> for DictionaryObject<'gc> {
+    impl_avm2_custom_object!(base);
+
+    fn get_property_local(
+        self,
+        receiver: Object<'gc>,
+        name: &QName<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let read = self.0.read();
+        let rv = read.base.get_property_local(receiver, name, activation)?;
+
+        drop(read);
+
+        rv.resolve(activation)
+    }
+
+    fn set_property_local(
+        self,
+        receiver: Object<'gc>,
+        name: &QName<'gc>,
+        value: Value<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        let mut write = self.0.write(activation.context.gc_context);
+        let rv = write
+            .base
+            .set_property_local(receiver, name, value, activation)?;
+
+        drop(write);
+
+        rv.resolve(activation)?;
+
+        Ok(())
+    }
+
+    fn init_property_local(
+        self,
+        receiver: Object<'gc>,
+        name: &QName<'gc>,
+        value: Value<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        let mut write = self.0.write(activation.context.gc_context);
+        let rv = write
+            .base
+            .init_property_local(receiver, name, value, activation)?;
+
+        drop(write);
+
+        rv.resolve(activation)?;
+
+        Ok(())
+    }
+
+    fn is_property_overwritable(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        name: &QName<'gc>,
+    ) -> bool {
+        self.0.write(gc_context).base.is_property_overwritable(name)
+    }
+
+    fn delete_property(&self, gc_context: MutationContext<'gc, '_>, name: &QName<'gc>) -> bool {
+        self.0.write(gc_context).base.delete_property(name)
+    }
+
+    fn has_own_property(self, name: &QName<'gc>) -> Result<bool, Error> {
+        self.0.read().base.has_own_property(name)
+    }
+
+    fn resolve_any(self, local_name: AvmString<'gc>) -> Result<Option<Namespace<'gc>>, Error> {
+        self.0.read().base.resolve_any(local_name)
+    }
+
+    fn resolve_any_trait(
+        self,
+        local_name: AvmString<'gc>,
+    ) -> Result<Option<Namespace<'gc>>, Error> {
+        self.0.read().base.resolve_any_trait(local_name)
+    }
+
+    /// Dictionaries enumerate their object keys, in the order they were
+    /// added, followed by their dynamic properties.
+    fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>> {
+        let read = self.0.read();
+        let num_keys = read.object_keys.len() as u32;
+        let true_index = index.checked_sub(1)?;
+
+        if true_index < num_keys {
+            return Some(read.object_keys[true_index as usize].into());
+        }
+
+        read.base
+            .get_enumerant_name(index - num_keys)
+            .map(|name| name.local_name().into())
+    }
+
+    fn get_enumerant_value(
+        &self,
+        index: u32,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let read = self.0.read();
+        let num_keys = read.object_keys.len() as u32;
+        let true_index = match index.checked_sub(1) {
+            Some(true_index) => true_index,
+            None => return Ok(Value::Undefined),
+        };
+
+        if true_index < num_keys {
+            let key = read.object_keys[true_index as usize];
+            return Ok(read
+                .object_space
+                .get(&key)
+                .cloned()
+                .unwrap_or(Value::Undefined));
+        }
+
+        let name = read.base.get_enumerant_name(index - num_keys);
+        drop(read);
+
+        match name {
+            Some(name) => {
+                let mut object: Object<'gc> = (*self).into();
+                object.get_property(object, &name, activation)
+            }
+            None => Ok(Value::Undefined),
+        }
+    }
+
+    fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
+        self.0.read().base.property_is_enumerable(name)
+    }
+
+    fn construct(
+        &self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        _args: &[Value<'gc>],
+    ) -> Result<Object<'gc>, Error> {
+        let this: Object<'gc> = Object::DictionaryObject(*self);
+        Ok(Self::empty_object(
+            activation.context.gc_context,
+            Some(this),
+        ))
+    }
+
+    fn derive(
+        &self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        class: GcCell<'gc, Class<'gc>>,
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+    ) -> Result<Object<'gc>, Error> {
+        let this: Object<'gc> = Object::DictionaryObject(*self);
+        Self::derive(this, activation.context.gc_context, class, scope)
+    }
+
+    fn value_of(&self, _mc: MutationContext<'gc, '_>) -> Result<Value<'gc>, Error> {
+        Ok(Value::Object(Object::from(*self)))
+    }
+
+    fn as_dictionary_object(&self) -> Option<DictionaryObject<'gc>> {
+        Some(*self)
+    }
+}
@@ -155,6 +155,9 @@ impl<'gc> FunctionObject<'gc> {
             class_proto.set_interfaces(activation.context.gc_context, interfaces);
         }
 
+        link_protected_overrides(activation.context.gc_context, class, class_proto);
+        link_interface_methods(activation.context.gc_context, class, class_proto);
+
         let fn_proto = activation.avm2().prototypes().function;
         let class_constr_proto = activation.avm2().prototypes().class;
 
@@ -348,3 +351,138 @@ impl<'gc> TObject<'gc> for FunctionObject<'gc> {
         .into())
     }
 }
+
+/// Move a class's overrides of protected traits into the protected namespace
+/// of the superclass that originally declared them.
+///
+/// Each class has its own protected namespace, and overrides are declared in
+/// it, but code in the superclass still refers to the trait by the name it was
+/// declared with. Renaming the overrides makes both refer to the same
+/// property; lookups through the subclass's namespace are handled when
+/// resolving multinames.
+fn link_protected_overrides<'gc>(
+    mc: MutationContext<'gc, '_>,
+    class: GcCell<'gc, Class<'gc>>,
+    class_proto: Object<'gc>,
+) {
+    let protected_ns = match class.read().protected_namespace() {
+        Some(protected_ns) => protected_ns.clone(),
+        None => return,
+    };
+
+    let overrides: Vec<QName<'gc>> = class
+        .read()
+        .instance_traits()
+        .iter()
+        .filter(|instance_trait| {
+            instance_trait.is_override() && *instance_trait.name().namespace() == protected_ns
+        })
+        .map(|instance_trait| instance_trait.name().clone())
+        .collect();
+
+    for name in overrides {
+        let mut super_proto = class_proto.proto();
+        while let Some(p) = super_proto {
+            if let Some(super_class) = p.as_class() {
+                let super_class = super_class.read();
+                if let Some(super_ns) = super_class.protected_namespace() {
+                    let super_name = QName::new(super_ns.clone(), name.local_name());
+                    if super_class.has_instance_trait(&super_name) {
+                        class.write(mc).rename_instance_traits(&name, &super_name);
+                        break;
+                    }
+                }
+            }
+            super_proto = p.proto();
+        }
+    }
+}
+
+/// Make the methods a class implements for its interfaces available under
+/// the interfaces' names.
+///
+/// Interfaces declare their methods in their own namespace, and calls through
+/// an interface-typed reference use that namespace, but classes implement
+/// them as public methods. Each implementation gets an alias in the interface
+/// namespace, unless a superclass already provides one that it doesn't
+/// override.
+fn link_interface_methods<'gc>(
+    mc: MutationContext<'gc, '_>,
+    class: GcCell<'gc, Class<'gc>>,
+    class_proto: Object<'gc>,
+) {
+    if class.read().is_interface() {
+        return;
+    }
+
+    let mut interfaces = Vec::new();
+    let mut proto = Some(class_proto);
+    while let Some(p) = proto {
+        for interface in p.interfaces() {
+            collect_interfaces(interface, &mut interfaces);
+        }
+        proto = p.proto();
+    }
+
+    for interface in interfaces {
+        let interface_class = match interface.as_class() {
+            Some(interface_class) => interface_class,
+            None => continue,
+        };
+
+        for interface_trait in interface_class.read().instance_traits() {
+            let name = interface_trait.name();
+            if name.namespace().is_public() || class.read().has_instance_trait(name) {
+                continue;
+            }
+
+            let public_name = QName::new(Namespace::public(), name.local_name());
+            let mut implementation = public_traits(class, &public_name);
+            if implementation.is_empty() {
+                let mut super_proto = class_proto.proto();
+                while let Some(p) = super_proto {
+                    if let Some(super_class) = p.as_class() {
+                        if super_class.read().has_instance_trait(name) {
+                            break;
+                        }
+
+                        implementation = public_traits(super_class, &public_name);
+                        if !implementation.is_empty() {
+                            break;
+                        }
+                    }
+                    super_proto = p.proto();
+                }
+            }
+
+            for implementation_trait in implementation {
+                class
+                    .write(mc)
+                    .define_instance_trait(implementation_trait.with_name(name.clone()));
+            }
+        }
+    }
+}
+
+/// Add an interface and all of the interfaces it extends to a list.
+fn collect_interfaces<'gc>(interface: Object<'gc>, interfaces: &mut Vec<Object<'gc>>) {
+    if interfaces.iter().any(|i| Object::ptr_eq(*i, interface)) {
+        return;
+    }
+
+    interfaces.push(interface);
+    for super_interface in interface.interfaces() {
+        collect_interfaces(super_interface, interfaces);
+    }
+}
+
+/// Get the instance traits of a class with a given name.
+fn public_traits<'gc>(class: GcCell<'gc, Class<'gc>>, name: &QName<'gc>) -> Vec<Trait<'gc>> {
+    class
+        .read()
+        .instance_traits()
+        .iter()
+        .filter(|instance_trait| instance_trait.name() == name)
+        .cloned()
+        .collect()
+}
@@ -12,7 +12,7 @@ use crate::avm2::value::Value;
 use crate::avm2::Error;
 use crate::{impl_avm2_custom_object, impl_avm2_custom_object_properties};
 use gc_arena::{Collect, GcCell, MutationContext};
-use std::cell::Ref;
+use std::cell::{Ref, RefMut};
 
 /// An Object which represents a boxed namespace name.
 #[derive(Collect, Debug, Clone, Copy)]
@@ -84,6 +84,10 @@ impl<'gc> TObject<'gc> for NamespaceObject<'gc> {
         Some(Ref::map(self.0.read(), |s| &s.namespace))
     }
 
+    fn as_namespace_mut(&self, mc: MutationContext<'gc, '_>) -> Option<RefMut<Namespace<'gc>>> {
+        Some(RefMut::map(self.0.write(mc), |s| &mut s.namespace))
+    }
+
     fn construct(
         &self,
         activation: &mut Activation<'_, 'gc, '_>,
@@ -231,8 +231,11 @@ impl<'gc> TObject<'gc> for ScriptObject<'gc> {
         self.0.write(mc).set_proto(proto)
     }
 
-    fn get_enumerant_name(&self, index: u32) -> Option<QName<'gc>> {
-        self.0.read().get_enumerant_name(index)
+    fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>> {
+        self.0
+            .read()
+            .get_enumerant_name(index)
+            .map(|name| name.local_name().into())
     }
 
     fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
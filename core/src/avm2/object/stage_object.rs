@@ -232,8 +232,12 @@ impl<'gc> TObject<'gc> for StageObject<'gc> {
         self.0.write(mc).base.set_proto(proto)
     }
 
-    fn get_enumerant_name(&self, index: u32) -> Option<QName<'gc>> {
-        self.0.read().base.get_enumerant_name(index)
+    fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>> {
+        self.0
+            .read()
+            .base
+            .get_enumerant_name(index)
+            .map(|name| name.local_name().into())
     }
 
     fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
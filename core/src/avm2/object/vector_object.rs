@@ -0,0 +1,320 @@
+//! Vector-structured objects
+
+use crate::avm2::activation::Activation;
+use crate::avm2::class::Class;
+use crate::avm2::names::{Namespace, QName};
+use crate::avm2::object::script_object::{ScriptObjectClass, ScriptObjectData};
+use crate::avm2::object::{Object, ObjectPtr, TObject};
+use crate::avm2::scope::Scope;
+use crate::avm2::string::AvmString;
+use crate::avm2::traits::Trait;
+use crate::avm2::value::Value;
+use crate::avm2::vector::{VectorStorage, VectorType};
+use crate::avm2::Error;
+use crate::impl_avm2_custom_object;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::cell::{Ref, RefMut};
+
+/// An Object which stores typed numerical properties in a vector.
+#[derive(Collect, Debug, Clone, Copy)]
+#[collect(no_drop)]
+pub struct VectorObject<'gc>(GcCell<'gc, VectorObjectData<'gc>>);
+
+#[derive(Collect, Debug, Clone)]
+#[collect(no_drop)]
+pub struct VectorObjectData<'gc> {
+    /// Base script object
+    base: ScriptObjectData<'gc>,
+
+    /// Vector-structured properties
+    vector: VectorStorage<'gc>,
+}
+
+impl<'gc> VectorObject<'gc> {
+    /// Construct a prototype for one of the specializations of `Vector`.
+    ///
+    /// Every vector constructed from this prototype will hold values of the
+    /// given type.
+    pub fn derive(
+        base_proto: Object<'gc>,
+        mc: MutationContext<'gc, '_>,
+        class: GcCell<'gc, Class<'gc>>,
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+        value_type: VectorType,
+    ) -> Result<Object<'gc>, Error> {
+        let base = ScriptObjectData::base_new(
+            Some(base_proto),
+            ScriptObjectClass::InstancePrototype(class, scope),
+        );
+
+        Ok(VectorObject(GcCell::allocate(
+            mc,
+            VectorObjectData {
+                base,
+                vector: VectorStorage::new(0, false, value_type),
+            },
+        ))
+        .into())
+    }
+
+    /// Wrap an existing vector in an object.
+    pub fn from_vector(
+        vector: VectorStorage<'gc>,
+        base_proto: Object<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> Object<'gc> {
+        let base = ScriptObjectData::base_new(Some(base_proto), ScriptObjectClass::NoClass);
+
+        VectorObject(GcCell::allocate(mc, VectorObjectData { base, vector })).into()
+    }
+
+    /// Coerce a value to the type stored in this vector and store it at the
+    /// given index.
+    ///
+    /// The coercion may run user code, so the vector must not be borrowed
+    /// until it is done.
+    fn set_index(
+        self,
+        index: usize,
+        value: Value<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        let value_type = self.0.read().vector.value_type();
+        let value = value_type.coerce(value, activation)?;
+
+        self.0
+            .write(activation.context.gc_context)
+            .vector
+            .set(index, value)
+    }
+}
+
+impl<'gc> TObject<'gc> for VectorObject<'gc> {
+    impl_avm2_custom_object!(base);
+
+    fn get_property_local(
+        self,
+        receiver: Object<'gc>,
+        name: &QName<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let read = self.0.read();
+
+        if name.namespace().is_public() {
+            if let Ok(index) = name.local_name().parse::<usize>() {
+                return read.vector.get(index);
+            }
+        }
+
+        let rv = read.base.get_property_local(receiver, name, activation)?;
+
+        drop(read);
+
+        rv.resolve(activation)
+    }
+
+    fn set_property_local(
+        self,
+        receiver: Object<'gc>,
+        name: &QName<'gc>,
+        value: Value<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        if name.namespace().is_public() {
+            if let Ok(index) = name.local_name().parse::<usize>() {
+                return self.set_index(index, value, activation);
+            }
+        }
+
+        let mut write = self.0.write(activation.context.gc_context);
+
+        let rv = write
+            .base
+            .set_property_local(receiver, name, value, activation)?;
+
+        drop(write);
+
+        rv.resolve(activation)?;
+
+        Ok(())
+    }
+
+    fn init_property_local(
+        self,
+        receiver: Object<'gc>,
+        name: &QName<'gc>,
+        value: Value<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<(), Error> {
+        if name.namespace().is_public() {
+            if let Ok(index) = name.local_name().parse::<usize>() {
+                return self.set_index(index, value, activation);
+            }
+        }
+
+        let mut write = self.0.write(activation.context.gc_context);
+
+        let rv = write
+            .base
+            .init_property_local(receiver, name, value, activation)?;
+
+        drop(write);
+
+        rv.resolve(activation)?;
+
+        Ok(())
+    }
+
+    fn is_property_overwritable(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        name: &QName<'gc>,
+    ) -> bool {
+        self.0.write(gc_context).base.is_property_overwritable(name)
+    }
+
+    fn delete_property(&self, gc_context: MutationContext<'gc, '_>, name: &QName<'gc>) -> bool {
+        // Vectors are dense, so their indices cannot be deleted.
+        if name.namespace().is_public() && name.local_name().parse::<usize>().is_ok() {
+            return false;
+        }
+
+        self.0.write(gc_context).base.delete_property(name)
+    }
+
+    fn has_own_property(self, name: &QName<'gc>) -> Result<bool, Error> {
+        if name.namespace().is_public() {
+            if let Ok(index) = name.local_name().parse::<usize>() {
+                return Ok(index < self.0.read().vector.length());
+            }
+        }
+
+        self.0.read().base.has_own_property(name)
+    }
+
+    fn resolve_any(self, local_name: AvmString<'gc>) -> Result<Option<Namespace<'gc>>, Error> {
+        if let Ok(index) = local_name.parse::<usize>() {
+            if index < self.0.read().vector.length() {
+                return Ok(Some(Namespace::public()));
+            }
+        }
+
+        self.0.read().base.resolve_any(local_name)
+    }
+
+    fn resolve_any_trait(
+        self,
+        local_name: AvmString<'gc>,
+    ) -> Result<Option<Namespace<'gc>>, Error> {
+        self.0.read().base.resolve_any_trait(local_name)
+    }
+
+    /// Vectors enumerate their elements in index order, followed by their
+    /// dynamic properties.
+    fn get_enumerant_name(&self, index: u32) -> Option<Value<'gc>> {
+        let read = self.0.read();
+        let length = read.vector.length() as u32;
+        let true_index = index.checked_sub(1)?;
+
+        if true_index < length {
+            return Some(true_index.into());
+        }
+
+        read.base
+            .get_enumerant_name(index - length)
+            .map(|name| name.local_name().into())
+    }
+
+    fn get_enumerant_value(
+        &self,
+        index: u32,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        let read = self.0.read();
+        let length = read.vector.length() as u32;
+        let true_index = match index.checked_sub(1) {
+            Some(true_index) => true_index,
+            None => return Ok(Value::Undefined),
+        };
+
+        if true_index < length {
+            return read.vector.get(true_index as usize);
+        }
+
+        let name = read.base.get_enumerant_name(index - length);
+        drop(read);
+
+        match name {
+            Some(name) => {
+                let mut object: Object<'gc> = (*self).into();
+                object.get_property(object, &name, activation)
+            }
+            None => Ok(Value::Undefined),
+        }
+    }
+
+    fn property_is_enumerable(&self, name: &QName<'gc>) -> bool {
+        if name.namespace().is_public() {
+            if let Ok(index) = name.local_name().parse::<usize>() {
+                return index < self.0.read().vector.length();
+            }
+        }
+
+        self.0.read().base.property_is_enumerable(name)
+    }
+
+    fn to_string(&self, _mc: MutationContext<'gc, '_>) -> Result<Value<'gc>, Error> {
+        Ok(Value::Object(Object::from(*self)))
+    }
+
+    fn value_of(&self, _mc: MutationContext<'gc, '_>) -> Result<Value<'gc>, Error> {
+        Ok(Value::Object(Object::from(*self)))
+    }
+
+    fn as_vector_storage(&self) -> Option<Ref<VectorStorage<'gc>>> {
+        Some(Ref::map(self.0.read(), |vod| &vod.vector))
+    }
+
+    fn as_vector_storage_mut(
+        &self,
+        mc: MutationContext<'gc, '_>,
+    ) -> Option<RefMut<VectorStorage<'gc>>> {
+        Some(RefMut::map(self.0.write(mc), |vod| &mut vod.vector))
+    }
+
+    fn construct(
+        &self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        _args: &[Value<'gc>],
+    ) -> Result<Object<'gc>, Error> {
+        let this: Object<'gc> = Object::VectorObject(*self);
+        let base = ScriptObjectData::base_new(Some(this), ScriptObjectClass::NoClass);
+        let vector = self.0.read().vector.empty_like();
+
+        Ok(VectorObject(GcCell::allocate(
+            activation.context.gc_context,
+            VectorObjectData { base, vector },
+        ))
+        .into())
+    }
+
+    fn derive(
+        &self,
+        activation: &mut Activation<'_, 'gc, '_>,
+        class: GcCell<'gc, Class<'gc>>,
+        scope: Option<GcCell<'gc, Scope<'gc>>>,
+    ) -> Result<Object<'gc>, Error> {
+        let this: Object<'gc> = Object::VectorObject(*self);
+        let base = ScriptObjectData::base_new(
+            Some(this),
+            ScriptObjectClass::InstancePrototype(class, scope),
+        );
+        let vector = self.0.read().vector.empty_like();
+
+        Ok(VectorObject(GcCell::allocate(
+            activation.context.gc_context,
+            VectorObjectData { base, vector },
+        ))
+        .into())
+    }
+}
@@ -297,6 +297,13 @@ impl<'gc> Trait<'gc> {
         self
     }
 
+    /// Convenience chaining method that renames a trait.
+    pub fn with_name(mut self, name: QName<'gc>) -> Self {
+        self.name = name;
+
+        self
+    }
+
     /// Set the slot or dispatch ID of this trait.
     pub fn set_slot_id(&mut self, id: u32) {
         match &mut self.kind {
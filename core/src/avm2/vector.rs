@@ -0,0 +1,213 @@
+//! Vector support types
+
+use crate::avm2::activation::Activation;
+use crate::avm2::value::Value;
+use crate::avm2::Error;
+use gc_arena::Collect;
+
+/// The type of value stored in a vector.
+///
+/// Flash has dedicated specializations of `Vector` for each of the numeric
+/// types; every other element type shares the `Vector$object`
+/// specialization.
+#[derive(Clone, Copy, Collect, Debug, PartialEq, Eq)]
+#[collect(require_static)]
+pub enum VectorType {
+    Int,
+    Uint,
+    Number,
+    Object,
+}
+
+impl VectorType {
+    /// The value that new vector slots are filled with.
+    pub fn default_value<'gc>(self) -> Value<'gc> {
+        match self {
+            Self::Int => 0i32.into(),
+            Self::Uint => 0u32.into(),
+            Self::Number => 0.0.into(),
+            Self::Object => Value::Null,
+        }
+    }
+
+    /// Coerce a value so that it may be stored in a vector of this type.
+    pub fn coerce<'gc>(
+        self,
+        value: Value<'gc>,
+        activation: &mut Activation<'_, 'gc, '_>,
+    ) -> Result<Value<'gc>, Error> {
+        Ok(match self {
+            Self::Int => value.coerce_to_i32(activation)?.into(),
+            Self::Uint => value.coerce_to_u32(activation)?.into(),
+            Self::Number => value.coerce_to_number(activation)?.into(),
+            Self::Object => match value {
+                Value::Undefined => Value::Null,
+                value => value,
+            },
+        })
+    }
+}
+
+/// The vector storage portion of a vector object.
+///
+/// Unlike arrays, vectors are dense: every index below the vector's length
+/// holds a value of the vector's type. Vectors may also be fixed-length, in
+/// which case any operation that would change their length fails.
+#[derive(Clone, Collect, Debug)]
+#[collect(no_drop)]
+pub struct VectorStorage<'gc> {
+    storage: Vec<Value<'gc>>,
+
+    /// Whether or not the length of the vector may change.
+    is_fixed: bool,
+
+    /// The type of value stored in this vector.
+    value_type: VectorType,
+}
+
+impl<'gc> VectorStorage<'gc> {
+    /// Construct new vector storage of a given length.
+    ///
+    /// All slots of the new vector hold the default value of its type.
+    pub fn new(length: usize, is_fixed: bool, value_type: VectorType) -> Self {
+        Self {
+            storage: vec![value_type.default_value(); length],
+            is_fixed,
+            value_type,
+        }
+    }
+
+    /// Construct new, empty vector storage holding the same type of value as
+    /// this one.
+    pub fn empty_like(&self) -> Self {
+        Self::new(0, false, self.value_type)
+    }
+
+    /// The type of value stored in this vector.
+    pub fn value_type(&self) -> VectorType {
+        self.value_type
+    }
+
+    /// Check if the length of this vector is fixed.
+    pub fn is_fixed(&self) -> bool {
+        self.is_fixed
+    }
+
+    /// Set whether or not the length of this vector is fixed.
+    pub fn set_is_fixed(&mut self, is_fixed: bool) {
+        self.is_fixed = is_fixed;
+    }
+
+    /// Get the length of the vector.
+    pub fn length(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Fail if the length of this vector may not change.
+    fn check_fixed(&self) -> Result<(), Error> {
+        if self.is_fixed {
+            return Err("RangeError: Cannot change the length of a fixed Vector".into());
+        }
+
+        Ok(())
+    }
+
+    /// Set the length of the vector.
+    ///
+    /// New slots hold the default value of the vector's type.
+    pub fn set_length(&mut self, length: usize) -> Result<(), Error> {
+        self.check_fixed()?;
+        self.storage.resize(length, self.value_type.default_value());
+
+        Ok(())
+    }
+
+    /// Retrieve a value from vector storage by index.
+    ///
+    /// Reading past the end of a vector is an error.
+    pub fn get(&self, index: usize) -> Result<Value<'gc>, Error> {
+        self.storage.get(index).cloned().ok_or_else(|| {
+            format!(
+                "RangeError: Index {} is out of range {}",
+                index,
+                self.storage.len()
+            )
+            .into()
+        })
+    }
+
+    /// Set a vector storage slot to a particular value.
+    ///
+    /// The value must already be coerced to the vector's type. Writing to the
+    /// index just past the end of a vector grows it by one, unless it is fixed;
+    /// writing any further is an error.
+    pub fn set(&mut self, index: usize, value: Value<'gc>) -> Result<(), Error> {
+        if index == self.storage.len() {
+            return self.push(value);
+        }
+
+        if let Some(slot) = self.storage.get_mut(index) {
+            *slot = value;
+
+            Ok(())
+        } else {
+            Err(format!(
+                "RangeError: Index {} is out of range {}",
+                index,
+                self.storage.len()
+            )
+            .into())
+        }
+    }
+
+    /// Push a single value onto the end of this vector.
+    pub fn push(&mut self, value: Value<'gc>) -> Result<(), Error> {
+        self.check_fixed()?;
+        self.storage.push(value);
+
+        Ok(())
+    }
+
+    /// Pop a value from the back of the vector.
+    ///
+    /// Popping from an empty vector yields the default value of its type.
+    pub fn pop(&mut self) -> Result<Value<'gc>, Error> {
+        self.check_fixed()?;
+
+        Ok(self
+            .storage
+            .pop()
+            .unwrap_or_else(|| self.value_type.default_value()))
+    }
+
+    /// Shift a value from the front of the vector.
+    ///
+    /// Shifting an empty vector yields the default value of its type.
+    pub fn shift(&mut self) -> Result<Value<'gc>, Error> {
+        self.check_fixed()?;
+
+        if self.storage.is_empty() {
+            Ok(self.value_type.default_value())
+        } else {
+            Ok(self.storage.remove(0))
+        }
+    }
+
+    /// Unshift a single value onto the start of this vector.
+    pub fn unshift(&mut self, value: Value<'gc>) -> Result<(), Error> {
+        self.check_fixed()?;
+        self.storage.insert(0, value);
+
+        Ok(())
+    }
+
+    /// Reverse the order of the values in this vector.
+    pub fn reverse(&mut self) {
+        self.storage.reverse()
+    }
+
+    /// Iterate over vector values.
+    pub fn iter<'a>(&'a self) -> impl DoubleEndedIterator<Item = Value<'gc>> + 'a {
+        self.storage.iter().cloned()
+    }
+}
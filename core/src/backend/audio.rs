@@ -1,15 +1,16 @@
 use crate::{
     avm1::SoundObject,
     display_object::{
-        self, DisplayObject, MovieClip, SoundTransform as DisplayObjectSoundTransform,
-        TDisplayObject,
+        self, DisplayObject, SoundTransform as DisplayObjectSoundTransform, TDisplayObject,
     },
+    tag_utils::SwfSlice,
 };
 use downcast_rs::Downcast;
 use gc_arena::Collect;
 use generational_arena::{Arena, Index};
 
 pub mod decoders;
+pub mod mixer;
 pub mod swf {
     pub use swf::{
         read, AudioCompression, CharacterId, Sound, SoundEnvelope, SoundEnvelopePoint, SoundEvent,
@@ -28,6 +29,18 @@ pub trait AudioBackend: Downcast {
     fn pause(&mut self);
     fn register_sound(&mut self, swf_sound: &swf::Sound) -> Result<SoundHandle, Error>;
 
+    /// Register a sound defined by a tag in `movie_data`.
+    ///
+    /// Backends can keep a reference to the movie's data instead of copying
+    /// the sound. By default, this is the same as `register_sound`.
+    fn register_sound_from_movie(
+        &mut self,
+        swf_sound: &swf::Sound,
+        _movie_data: &SwfSlice,
+    ) -> Result<SoundHandle, Error> {
+        self.register_sound(swf_sound)
+    }
+
     /// Used by the web backend to pre-decode sound streams.
     /// Returns the sound handle to be used to add data to the stream.
     /// Other backends return `None`.
@@ -64,6 +77,8 @@ pub trait AudioBackend: Downcast {
 
     /// Starts playing a "stream" sound, which is an audio stream that is distributed
     /// among the frames of a Flash MovieClip.
+    /// `clip_data` is the tag data of the MovieClip, and the stream starts playing from
+    /// the beginning of `clip_frame`.
     /// On the web backend, `stream_handle` should be the handle for the preloaded stream.
     /// Other backends can pass `None`.
     fn start_stream(
@@ -74,6 +89,14 @@ pub trait AudioBackend: Downcast {
         handle: &swf::SoundStreamHead,
     ) -> Result<SoundInstanceHandle, Error>;
 
+    /// Seeks a playing "stream" sound to the beginning of the given frame of its
+    /// MovieClip, keeping the same sound instance.
+    /// Returns `false` if the stream could not be seeked, such as when the frame has
+    /// no stream data; the stream should then be stopped.
+    fn seek_stream(&mut self, _instance: SoundInstanceHandle, _clip_frame: u16) -> bool {
+        false
+    }
+
     /// Stops a playing sound instance.
     /// No-op if the sound is not playing.
     fn stop_sound(&mut self, sound: SoundInstanceHandle);
@@ -98,6 +121,13 @@ pub trait AudioBackend: Downcast {
     }
     fn tick(&mut self) {}
 
+    /// Inform the audio backend that a frame of the movie is about to run,
+    /// `dt` milliseconds of movie time after the previous one.
+    ///
+    /// Backends that can start sounds partway through their output buffers
+    /// use this to start the sounds of each frame exactly a frame apart.
+    fn advance_frame_clock(&mut self, _dt: f64) {}
+
     /// Inform the audio backend of the current stage frame rate.
     ///
     /// This is only necessary if your particular audio backend needs to know
@@ -174,6 +204,9 @@ pub struct AudioManager<'gc> {
     /// The global sound transform applied to all sounds.
     global_sound_transform: DisplayObjectSoundTransform,
 
+    /// The volume set by the user, applied on top of every sound transform.
+    volume: f32,
+
     /// Whether a sound transform has been changed.
     transforms_dirty: bool,
 }
@@ -186,6 +219,7 @@ impl<'gc> AudioManager<'gc> {
         Self {
             sounds: Vec::with_capacity(Self::MAX_SOUNDS),
             global_sound_transform: Default::default(),
+            volume: 1.0,
             transforms_dirty: false,
         }
     }
@@ -303,7 +337,7 @@ impl<'gc> AudioManager<'gc> {
         &mut self,
         audio: &mut dyn AudioBackend,
         stream_handle: Option<SoundHandle>,
-        movie_clip: MovieClip<'gc>,
+        display_object: Option<DisplayObject<'gc>>,
         clip_frame: u16,
         data: crate::tag_utils::SwfSlice,
         stream_info: &swf::SoundStreamHead,
@@ -315,7 +349,7 @@ impl<'gc> AudioManager<'gc> {
             let instance = SoundInstance {
                 sound: None,
                 instance: handle,
-                display_object: Some(movie_clip.into()),
+                display_object,
                 avm1_object: None,
             };
             audio.set_sound_transform(handle, self.transform_for_sound(&instance));
@@ -326,6 +360,18 @@ impl<'gc> AudioManager<'gc> {
         }
     }
 
+    /// Seeks a playing stream sound to the beginning of the given frame.
+    /// Returns `false` if the stream could not be seeked.
+    pub fn seek_stream(
+        &mut self,
+        audio: &mut dyn AudioBackend,
+        instance: SoundInstanceHandle,
+        clip_frame: u16,
+    ) -> bool {
+        self.sounds.iter().any(|other| other.instance == instance)
+            && audio.seek_stream(instance, clip_frame)
+    }
+
     pub fn global_sound_transform(&self) -> &DisplayObjectSoundTransform {
         &self.global_sound_transform
     }
@@ -335,6 +381,12 @@ impl<'gc> AudioManager<'gc> {
         self.transforms_dirty = true;
     }
 
+    /// Set the volume chosen by the user, from 0.0 to 1.0.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        self.transforms_dirty = true;
+    }
+
     pub fn set_sound_transforms_dirty(&mut self) {
         self.transforms_dirty = true;
     }
@@ -347,7 +399,12 @@ impl<'gc> AudioManager<'gc> {
             parent = display_object.parent();
         }
         transform.concat(&self.global_sound_transform);
-        SoundTransform::from_display_object_transform(&transform)
+        let mut transform = SoundTransform::from_display_object_transform(&transform);
+        transform.left_to_left *= self.volume;
+        transform.left_to_right *= self.volume;
+        transform.right_to_left *= self.volume;
+        transform.right_to_right *= self.volume;
+        transform
     }
 
     /// Update the sound transforms for all sounds.
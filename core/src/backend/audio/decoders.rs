@@ -200,6 +200,86 @@ pub fn make_stream_decoder(
     Ok(decoder)
 }
 
+/// Where the stream data of each frame of a MovieClip begins.
+///
+/// The table is built in a single pass over the clip's tags, so that the stream can then
+/// be started on any frame without scanning the clip again.
+#[derive(Debug, Default)]
+pub struct StreamFrameTable {
+    /// The offset of each frame's tags into the clip's tag data, along with the number of
+    /// samples to skip at the start of the frame's stream block. Indexed by frame number,
+    /// starting from frame 1.
+    frames: Vec<(usize, usize)>,
+}
+
+impl StreamFrameTable {
+    /// Finds the frames of a MovieClip's stream. `swf_data` should be the MovieClip's tag
+    /// data.
+    pub fn new(format: &SoundFormat, swf_data: &SwfSlice) -> Self {
+        let data = swf_data.data();
+        let mut reader = swf::read::Reader::new(data, swf_data.version());
+        let mut frames = vec![];
+        let mut frame_start = 0;
+        let mut seek_samples = None;
+        while let Ok((tag_code, tag_len)) = reader.read_tag_code_and_length() {
+            if tag_len > reader.get_ref().len() {
+                break;
+            }
+            let tag_data = &reader.get_ref()[..tag_len];
+            *reader.get_mut() = &reader.get_ref()[tag_len..];
+
+            match TagCode::from_u16(tag_code) {
+                Some(TagCode::ShowFrame) => {
+                    frames.push((frame_start, seek_samples.take().unwrap_or(0)));
+                    frame_start = data.len() - reader.get_ref().len();
+                }
+                Some(TagCode::SoundStreamBlock) if seek_samples.is_none() => {
+                    // MP3 stream blocks begin with the sample count and the seek samples.
+                    // SWF19 p.188
+                    seek_samples = Some(
+                        if format.compression == AudioCompression::Mp3 && tag_len >= 4 {
+                            i16::from_le_bytes([tag_data[2], tag_data[3]]).max(0) as usize
+                        } else {
+                            0
+                        },
+                    );
+                }
+                Some(TagCode::End) => break,
+                _ => (),
+            }
+        }
+
+        Self { frames }
+    }
+
+    /// Makes a `StreamDecoder` for the stream, starting at the beginning of `clip_frame`.
+    ///
+    /// The audio of an MP3 stream block may start before its frame does, so the samples
+    /// that the block says to skip are dropped to keep the stream in sync with the
+    /// timeline. If the frame has no `SoundStreamBlock`, the stream starts with the next
+    /// block after it. Returns an error if the clip has no such frame.
+    pub fn make_decoder(
+        &self,
+        format: &SoundFormat,
+        swf_data: &SwfSlice,
+        clip_frame: u16,
+    ) -> Result<Box<dyn Decoder + Send>, Error> {
+        let (frame_start, seek_samples) = clip_frame
+            .checked_sub(1)
+            .and_then(|index| self.frames.get(usize::from(index)))
+            .copied()
+            .ok_or_else(|| format!("No frame {} in stream", clip_frame))?;
+        let frame_data = swf_data
+            .to_start_and_end(frame_start, swf_data.data().len())
+            .ok_or("Invalid slice generated when seeking sound stream")?;
+        let mut decoder = make_stream_decoder(format, frame_data)?;
+        for _ in 0..seek_samples {
+            decoder.next();
+        }
+        Ok(decoder)
+    }
+}
+
 /// Adds seeking ability to decoders where the underline stream is `std::io::Seek`.
 pub trait SeekableDecoder: Decoder {
     /// Resets the decoder to the beginning of the stream.
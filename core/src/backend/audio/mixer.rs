@@ -0,0 +1,1260 @@
+//! Software audio mixer.
+//!
+//! The mixer decodes, resamples and mixes all playing sounds into a buffer of
+//! output samples, applying the in and out points, loop counts and envelopes
+//! of event sounds. Audio backends that use it only have to provide an output
+//! sink that periodically asks the mixer for more samples.
+
+#[cfg(feature = "minimp3")]
+use super::decoders::Mp3Decoder;
+use super::decoders::{
+    self, AdpcmDecoder, Decoder, NellymoserDecoder, PcmDecoder, SeekableDecoder, StreamFrameTable,
+};
+use super::swf::{self, AudioCompression};
+use super::{SoundHandle, SoundInstanceHandle, SoundTransform};
+use crate::tag_utils::{SwfSlice, WeakSwfSlice};
+use generational_arena::Arena;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+type Error = Box<dyn std::error::Error>;
+
+type Signal = Box<dyn Send + dasp::signal::Signal<Frame = [i16; 2]>>;
+
+/// Sample positions in `SoundInfo` and sound envelopes are always in 44.1KHz.
+const SWF_SAMPLE_RATE: u32 = 44100;
+
+/// Mixes playing sounds into an output buffer.
+///
+/// Sounds are registered and started by the audio backend, while the output
+/// sink uses the mixer (or an `AudioMixerProxy` if it runs on another thread)
+/// to fill its buffers.
+pub struct AudioMixer {
+    /// The sounds that have been registered with the mixer.
+    sounds: Arena<Sound>,
+
+    /// The sounds that are currently playing.
+    ///
+    /// This is shared with `AudioMixerProxy`s, so that sounds can be mixed on
+    /// an audio thread.
+    sound_instances: Arc<Mutex<Arena<SoundInstance>>>,
+
+    /// The number of channels of the output, with the left and right channels
+    /// of a stereo sound being the first two.
+    num_output_channels: u8,
+
+    /// The sample rate of the output.
+    output_sample_rate: u32,
+
+    /// How far the output has been mixed, shared with `AudioMixerProxy`s.
+    output_clock: Arc<OutputClock>,
+
+    /// The output sample frame at which the current frame of the movie
+    /// started, or `None` before the first frame.
+    ///
+    /// This advances by exactly one movie frame each frame, so that event
+    /// sounds started on consecutive frames play exactly a frame apart, instead
+    /// of at the start of whichever output buffer is mixed next.
+    frame_clock: Option<f64>,
+}
+
+/// How far the output of a mixer has been mixed.
+#[derive(Default)]
+struct OutputClock {
+    /// The number of output sample frames mixed so far.
+    position: AtomicU64,
+
+    /// The number of sample frames in the last buffer that was mixed.
+    buffer_length: AtomicU64,
+}
+
+/// A handle to an `AudioMixer` that can be sent to an audio thread to mix the
+/// playing sounds.
+#[derive(Clone)]
+pub struct AudioMixerProxy {
+    sound_instances: Arc<Mutex<Arena<SoundInstance>>>,
+    output_clock: Arc<OutputClock>,
+    num_output_channels: u8,
+    output_sample_rate: u32,
+}
+
+/// Contains the data and metadata for a sound in an SWF file.
+/// A `Sound` is defined by the `DefineSound` SWF tags.
+struct Sound {
+    format: swf::SoundFormat,
+    data: StoredSoundData,
+
+    /// Number of samples in this audio.
+    /// This does not include the skip_sample_frames.
+    num_sample_frames: u32,
+
+    /// Number of samples to skip encoder delay.
+    skip_sample_frames: u16,
+}
+
+/// An actively playing instance of a sound.
+/// This sound can be either an event sound (`StartSound`) or
+/// a stream sound (`SoundStreamBlock`).
+struct SoundInstance {
+    /// The audio stream. Call `next()` to yield sample frames.
+    signal: Signal,
+
+    /// Flag indicating whether this sound is still playing.
+    /// If this flag is false, the sound will be cleaned up after the
+    /// current buffer is mixed.
+    active: bool,
+
+    /// The number of output sample frames that have been mixed.
+    position: u64,
+
+    /// The output sample frame at which this sound begins playing.
+    start_position: u64,
+
+    /// The clip data that a stream sound plays from, used to seek the stream.
+    /// `None` if this is an event sound.
+    stream_source: Option<StreamSource>,
+
+    /// The volume transform for this sound instance.
+    left_transform: [f32; 2],
+
+    right_transform: [f32; 2],
+}
+
+/// The source of a stream sound.
+#[derive(Clone)]
+struct StreamSource {
+    format: swf::SoundFormat,
+
+    /// The tag data of the movie clip that contains the stream.
+    clip_data: SwfSlice,
+
+    /// Where each frame of the stream begins in `clip_data`.
+    frames: Arc<StreamFrameTable>,
+}
+
+impl AudioMixer {
+    pub fn new(num_output_channels: u8, output_sample_rate: u32) -> Self {
+        Self {
+            sounds: Arena::new(),
+            sound_instances: Arc::new(Mutex::new(Arena::new())),
+            num_output_channels,
+            output_sample_rate,
+            output_clock: Arc::new(OutputClock::default()),
+            frame_clock: None,
+        }
+    }
+
+    /// Returns a proxy that can be used to mix the playing sounds from
+    /// another thread.
+    pub fn proxy(&self) -> AudioMixerProxy {
+        AudioMixerProxy {
+            sound_instances: Arc::clone(&self.sound_instances),
+            output_clock: Arc::clone(&self.output_clock),
+            num_output_channels: self.num_output_channels,
+            output_sample_rate: self.output_sample_rate,
+        }
+    }
+
+    /// Fill `output_buffer` with the next interleaved samples of the playing
+    /// sounds.
+    pub fn mix<'a, T>(&self, output_buffer: &mut [T])
+    where
+        T: 'a + dasp::Sample,
+        T::Signed: dasp::sample::conv::FromSample<i16>,
+    {
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        mix_audio(
+            &mut sound_instances,
+            &self.output_clock,
+            self.num_output_channels,
+            output_buffer,
+        )
+    }
+
+    pub fn num_output_channels(&self) -> u8 {
+        self.num_output_channels
+    }
+
+    pub fn output_sample_rate(&self) -> u32 {
+        self.output_sample_rate
+    }
+
+    /// Advance the frame clock by a frame of the movie, `dt` milliseconds long.
+    ///
+    /// The clock is kept within an output buffer and a frame of what has been
+    /// mixed. If it drifts further from the output, such as after the movie
+    /// lagged or the output stalled, it jumps to the current output position.
+    pub fn advance_frame_clock(&mut self, dt: f64) {
+        let position = self.output_clock.position.load(Ordering::Relaxed) as f64;
+        let buffer_length = self.output_clock.buffer_length.load(Ordering::Relaxed) as f64;
+        let step = dt * f64::from(self.output_sample_rate) / 1000.0;
+        self.frame_clock = match self.frame_clock.map(|clock| clock + step) {
+            Some(clock)
+                if clock + buffer_length >= position
+                    && clock <= position + buffer_length + step =>
+            {
+                Some(clock)
+            }
+            _ => Some(position),
+        };
+    }
+
+    pub fn register_sound(&mut self, swf_sound: &swf::Sound) -> Result<SoundHandle, Error> {
+        Ok(self.sounds.insert(Sound::from_swf_sound(swf_sound, None)?))
+    }
+
+    /// Register a sound whose data is part of a movie.
+    ///
+    /// The sound refers to the movie's data instead of copying it.
+    pub fn register_sound_from_movie(
+        &mut self,
+        swf_sound: &swf::Sound,
+        movie_data: &SwfSlice,
+    ) -> Result<SoundHandle, Error> {
+        Ok(self
+            .sounds
+            .insert(Sound::from_swf_sound(swf_sound, Some(movie_data))?))
+    }
+
+    /// Replace the data of a registered sound.
+    ///
+    /// Sounds that are already playing continue to play the old data.
+    pub fn replace_sound(
+        &mut self,
+        handle: SoundHandle,
+        swf_sound: &swf::Sound,
+    ) -> Result<(), Error> {
+        let sound = self
+            .sounds
+            .get_mut(handle)
+            .ok_or("replace_sound: Unregistered sound")?;
+        *sound = Sound::from_swf_sound(swf_sound, None)?;
+        Ok(())
+    }
+
+    pub fn start_sound(
+        &mut self,
+        sound_handle: SoundHandle,
+        settings: &swf::SoundInfo,
+    ) -> Result<SoundInstanceHandle, Error> {
+        let signal = self.make_signal_from_sound(sound_handle, settings)?;
+        Ok(self.start_signal(signal, None))
+    }
+
+    /// Restart a playing sound instance with a different sound or settings,
+    /// keeping its handle and transform.
+    pub fn restart_sound(
+        &mut self,
+        instance: SoundInstanceHandle,
+        sound_handle: SoundHandle,
+        settings: &swf::SoundInfo,
+    ) -> Result<(), Error> {
+        let signal = self.make_signal_from_sound(sound_handle, settings)?;
+        let start_position = self.scheduled_start_position();
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        let instance = sound_instances
+            .get_mut(instance)
+            .ok_or("restart_sound: Sound is not playing")?;
+        instance.signal = signal;
+        instance.position = 0;
+        instance.start_position = start_position;
+        instance.stream_source = None;
+        Ok(())
+    }
+
+    pub fn start_stream(
+        &mut self,
+        _stream_handle: Option<SoundHandle>,
+        clip_frame: u16,
+        clip_data: SwfSlice,
+        stream_info: &swf::SoundStreamHead,
+    ) -> Result<SoundInstanceHandle, Error> {
+        // The audio data for stream sounds is distributed among the frames of a
+        // movie clip. The stream tag reader will parse through the SWF and
+        // feed the decoder audio data on the fly.
+        let format = stream_info.stream_format.clone();
+        let frames = Arc::new(StreamFrameTable::new(&format, &clip_data));
+        let stream_source = StreamSource {
+            format,
+            clip_data,
+            frames,
+        };
+        let signal = self.make_signal_from_stream(&stream_source, clip_frame)?;
+
+        Ok(self.start_signal(signal, Some(stream_source)))
+    }
+
+    pub fn seek_stream(&mut self, instance: SoundInstanceHandle, clip_frame: u16) -> bool {
+        let stream_source = {
+            let sound_instances = self.sound_instances.lock().unwrap();
+            match sound_instances
+                .get(instance)
+                .and_then(|instance| instance.stream_source.clone())
+            {
+                Some(source) => source,
+                None => return false,
+            }
+        };
+
+        // Decode outside of the lock, so that the audio thread isn't kept waiting.
+        let signal = match self.make_signal_from_stream(&stream_source, clip_frame) {
+            Ok(signal) => signal,
+            Err(_) => return false,
+        };
+
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        if let Some(instance) = sound_instances.get_mut(instance) {
+            instance.signal = signal;
+            instance.position = 0;
+            instance.active = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn stop_sound(&mut self, sound: SoundInstanceHandle) {
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        sound_instances.remove(sound);
+    }
+
+    pub fn stop_all_sounds(&mut self) {
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        // This is a workaround for a bug in generational-arena:
+        // Arena::clear does not properly bump the generational index, allowing for stale references
+        // to continue to work (this caused #1315). Arena::remove will force a generation bump.
+        // See https://github.com/fitzgen/generational-arena/issues/30
+        if let Some((i, _)) = sound_instances.iter().next() {
+            sound_instances.remove(i);
+        }
+        sound_instances.clear();
+    }
+
+    pub fn get_sound_position(&self, instance: SoundInstanceHandle) -> Option<u32> {
+        let sound_instances = self.sound_instances.lock().unwrap();
+        sound_instances.get(instance).map(|instance| {
+            let ms = instance.position * 1000 / u64::from(self.output_sample_rate);
+            ms as u32
+        })
+    }
+
+    pub fn get_sound_duration(&self, sound: SoundHandle) -> Option<u32> {
+        if let Some(sound) = self.sounds.get(sound) {
+            // AS duration does not subtract `skip_sample_frames`.
+            let num_sample_frames: f64 = sound.num_sample_frames.into();
+            let sample_rate: f64 = sound.format.sample_rate.into();
+            let ms = (num_sample_frames * 1000.0 / sample_rate).round();
+            Some(ms as u32)
+        } else {
+            None
+        }
+    }
+
+    pub fn set_sound_transform(
+        &mut self,
+        instance: SoundInstanceHandle,
+        transform: SoundTransform,
+    ) {
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        if let Some(instance) = sound_instances.get_mut(instance) {
+            instance.left_transform = [transform.left_to_left, transform.right_to_left];
+            instance.right_transform = [transform.left_to_right, transform.right_to_right];
+        }
+    }
+
+    /// The output sample frame at which event sounds started now begin
+    /// playing: the start of the current frame, unless that has already been
+    /// mixed.
+    fn scheduled_start_position(&self) -> u64 {
+        let position = self.output_clock.position.load(Ordering::Relaxed);
+        self.frame_clock
+            .map_or(position, |clock| (clock as u64).max(position))
+    }
+
+    /// Add a signal to the playing sounds.
+    fn start_signal(
+        &mut self,
+        signal: Signal,
+        stream_source: Option<StreamSource>,
+    ) -> SoundInstanceHandle {
+        // Stream sounds are kept in sync with the timeline as it plays, so
+        // they start with the next sample that is mixed.
+        let start_position = if stream_source.is_some() {
+            0
+        } else {
+            self.scheduled_start_position()
+        };
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        sound_instances.insert(SoundInstance {
+            signal,
+            active: true,
+            position: 0,
+            start_position,
+            stream_source,
+            left_transform: [1.0, 0.0],
+            right_transform: [0.0, 1.0],
+        })
+    }
+
+    /// Creates a signal that decodes and resamples a registered sound.
+    fn make_signal_from_sound(
+        &self,
+        sound_handle: SoundHandle,
+        settings: &swf::SoundInfo,
+    ) -> Result<Signal, Error> {
+        let sound = self
+            .sounds
+            .get(sound_handle)
+            .ok_or("start_sound: Unregistered sound")?;
+        let data = sound
+            .data
+            .upgrade()
+            .ok_or("start_sound: Sound's movie has been unloaded")?;
+        let data = Cursor::new(data);
+
+        if sound.skip_sample_frames == 0
+            && settings.in_sample.is_none()
+            && settings.out_sample.is_none()
+            && settings.num_loops <= 1
+            && settings.envelope.is_none()
+        {
+            // For simple event sounds, just use the same signal as streams.
+            self.make_signal_from_simple_event_sound(&sound.format, data)
+        } else {
+            // For event sounds with envelopes/other properties, wrap it in `EventSoundSignal`.
+            self.make_signal_from_event_sound(sound, settings, data)
+        }
+    }
+
+    /// Instantiate a seeabkle decoder for the compression that the sound data uses.
+    fn make_seekable_decoder(
+        format: &swf::SoundFormat,
+        data: Cursor<SoundData>,
+    ) -> Result<Box<dyn Send + SeekableDecoder>, Error> {
+        let decoder: Box<dyn Send + SeekableDecoder> = match format.compression {
+            AudioCompression::Uncompressed | AudioCompression::UncompressedUnknownEndian => {
+                Box::new(PcmDecoder::new(
+                    data,
+                    format.is_stereo,
+                    format.sample_rate,
+                    format.is_16_bit,
+                ))
+            }
+            AudioCompression::Adpcm => Box::new(AdpcmDecoder::new(
+                data,
+                format.is_stereo,
+                format.sample_rate,
+            )),
+            #[cfg(feature = "minimp3")]
+            AudioCompression::Mp3 => Box::new(Mp3Decoder::new(
+                if format.is_stereo { 2 } else { 1 },
+                format.sample_rate.into(),
+                data,
+            )),
+            AudioCompression::Nellymoser => {
+                Box::new(NellymoserDecoder::new(data, format.sample_rate.into()))
+            }
+            _ => {
+                let msg = format!(
+                    "start_sound: Unhandled audio compression {:?}",
+                    format.compression
+                );
+                log::error!("{}", msg);
+                return Err(msg.into());
+            }
+        };
+        Ok(decoder)
+    }
+
+    /// Resamples a stream.
+    /// TODO: Allow interpolator to be user-configurable?
+    fn make_resampler<S: Send + dasp::signal::Signal<Frame = [i16; 2]>>(
+        &self,
+        format: &swf::SoundFormat,
+        mut signal: S,
+    ) -> dasp::signal::interpolate::Converter<
+        S,
+        impl dasp::interpolate::Interpolator<Frame = [i16; 2]>,
+    > {
+        let left = signal.next();
+        let right = signal.next();
+        let interpolator = dasp::interpolate::linear::Linear::new(left, right);
+        dasp::signal::interpolate::Converter::from_hz_to_hz(
+            signal,
+            interpolator,
+            format.sample_rate.into(),
+            self.output_sample_rate.into(),
+        )
+    }
+
+    /// Creates a `dasp::signal::Signal` that decodes and resamples the audio stream
+    /// to the output format.
+    fn make_signal_from_event_sound(
+        &self,
+        sound: &Sound,
+        settings: &swf::SoundInfo,
+        data: Cursor<SoundData>,
+    ) -> Result<Signal, Error> {
+        // Instantiate a decoder for the compression that the sound data uses.
+        let decoder = Self::make_seekable_decoder(&sound.format, data)?;
+
+        // Wrap the decoder in the event sound signal (controls looping/envelope)
+        let signal = EventSoundSignal::new_with_settings(
+            decoder,
+            settings,
+            sound.num_sample_frames,
+            sound.skip_sample_frames,
+        );
+        // Convert the `Decoder` to a `Signal`, and resample it the the output
+        // sample rate.
+        let signal = self.make_resampler(&sound.format, signal);
+        if let Some(envelope) = &settings.envelope {
+            use dasp::Signal;
+            let envelope_signal = EnvelopeSignal::new(&envelope[..], self.output_sample_rate);
+            Ok(Box::new(signal.mul_amp(envelope_signal)))
+        } else {
+            Ok(Box::new(signal))
+        }
+    }
+
+    /// Creates a `dasp::signal::Signal` that decodes and resamples a "stream" sound.
+    fn make_signal_from_stream(
+        &self,
+        source: &StreamSource,
+        clip_frame: u16,
+    ) -> Result<Signal, Error> {
+        // Instantiate a decoder for the compression that the sound data uses.
+        let clip_stream_decoder =
+            source
+                .frames
+                .make_decoder(&source.format, &source.clip_data, clip_frame)?;
+
+        // Convert the `Decoder` to a `Signal`, and resample it the the output
+        // sample rate.
+        let signal = dasp::signal::from_iter(clip_stream_decoder);
+        Ok(Box::new(self.make_resampler(&source.format, signal)))
+    }
+
+    /// Creates a `dasp::signal::Signal` that decodes and resamples the audio stream
+    /// to the output format.
+    fn make_signal_from_simple_event_sound(
+        &self,
+        format: &swf::SoundFormat,
+        data_stream: Cursor<SoundData>,
+    ) -> Result<Signal, Error> {
+        // Instantiate a decoder for the compression that the sound data uses.
+        let decoder = decoders::make_decoder(format, data_stream)?;
+
+        // Convert the `Decoder` to a `Signal`, and resample it the the output
+        // sample rate.
+        let signal = dasp::signal::from_iter(decoder);
+        Ok(Box::new(self.make_resampler(format, signal)))
+    }
+}
+
+impl AudioMixerProxy {
+    /// Fill `output_buffer` with the next interleaved samples of the playing
+    /// sounds.
+    pub fn mix<'a, T>(&self, output_buffer: &mut [T])
+    where
+        T: 'a + dasp::Sample,
+        T::Signed: dasp::sample::conv::FromSample<i16>,
+    {
+        let mut sound_instances = self.sound_instances.lock().unwrap();
+        mix_audio(
+            &mut sound_instances,
+            &self.output_clock,
+            self.num_output_channels,
+            output_buffer,
+        )
+    }
+
+    pub fn num_output_channels(&self) -> u8 {
+        self.num_output_channels
+    }
+
+    pub fn output_sample_rate(&self) -> u32 {
+        self.output_sample_rate
+    }
+}
+
+impl Sound {
+    /// Build a sound from its tag.
+    ///
+    /// If the tag is part of `movie_data`, the sound refers to its data in the
+    /// movie, without keeping the movie alive; otherwise the data is copied.
+    fn from_swf_sound(
+        swf_sound: &swf::Sound,
+        movie_data: Option<&SwfSlice>,
+    ) -> Result<Self, Error> {
+        // Slice off latency seek for MP3 data.
+        let (skip_sample_frames, data) = if swf_sound.format.compression == AudioCompression::Mp3 {
+            if swf_sound.data.len() < 2 {
+                return Err("register_sound: MP3 sound is missing its latency seek".into());
+            }
+            let skip_sample_frames =
+                u16::from(swf_sound.data[0]) | (u16::from(swf_sound.data[1]) << 8);
+            (skip_sample_frames, &swf_sound.data[2..])
+        } else {
+            (0, swf_sound.data)
+        };
+
+        Ok(Self {
+            format: swf_sound.format.clone(),
+            data: movie_data
+                .and_then(|movie_data| movie_data.to_unbounded_subslice(data))
+                .map(|data| StoredSoundData::Movie(data.downgrade()))
+                .unwrap_or_else(|| StoredSoundData::Owned(Arc::from(data))),
+            num_sample_frames: swf_sound.num_samples,
+            skip_sample_frames,
+        })
+    }
+}
+
+/// Refill the output buffer by stepping through all active sounds
+/// and mixing in their output.
+fn mix_audio<'a, T>(
+    sound_instances: &mut Arena<SoundInstance>,
+    output_clock: &OutputClock,
+    num_output_channels: u8,
+    output_buffer: &mut [T],
+) where
+    T: 'a + dasp::Sample,
+    T::Signed: dasp::sample::conv::FromSample<i16>,
+{
+    use dasp::{
+        frame::{Frame, Stereo},
+        Sample,
+    };
+
+    let start_position = output_clock.position.load(Ordering::Relaxed);
+    let mut output_position = start_position;
+
+    // For each sample, mix the samples from all active sound instances.
+    for buf_frame in output_buffer.chunks_exact_mut(num_output_channels.into()) {
+        let mut output_frame = Stereo::<T::Signed>::EQUILIBRIUM;
+        for (_, sound) in sound_instances.iter_mut() {
+            if sound.start_position > output_position {
+                // This sound starts later in the buffer, or in a later one.
+                continue;
+            }
+            if sound.active && !sound.signal.is_exhausted() {
+                let sound_frame = sound.signal.next();
+                let [left_0, left_1] = sound_frame.mul_amp(sound.left_transform);
+                let [right_0, right_1] = sound_frame.mul_amp(sound.right_transform);
+                let sound_frame: Stereo<T::Signed> = [
+                    Sample::add_amp(left_0, left_1).to_sample(),
+                    Sample::add_amp(right_0, right_1).to_sample(),
+                ];
+                output_frame = output_frame.add_amp(sound_frame);
+                sound.position += 1;
+            } else {
+                sound.active = false;
+            }
+        }
+
+        // Any channels past the first two are left silent.
+        let output_samples = output_frame
+            .iter()
+            .copied()
+            .chain(std::iter::repeat(<T::Signed as Sample>::EQUILIBRIUM));
+        for (buf_sample, output_sample) in buf_frame.iter_mut().zip(output_samples) {
+            *buf_sample = output_sample.to_sample();
+        }
+        output_position += 1;
+    }
+
+    output_clock
+        .position
+        .store(output_position, Ordering::Relaxed);
+    output_clock
+        .buffer_length
+        .store(output_position - start_position, Ordering::Relaxed);
+
+    // Remove all dead sounds.
+    sound_instances.retain(|_, sound| sound.active);
+}
+
+/// Implements the `AudioBackend` methods that manage sounds by forwarding them
+/// to an `AudioMixer` field of the backend.
+///
+/// Backends that use this only have to implement `play`, `pause`, and
+/// whatever is needed to feed the output of the mixer to their sink.
+#[macro_export]
+macro_rules! impl_audio_mixer_backend {
+    ($mixer:ident) => {
+        fn register_sound(
+            &mut self,
+            swf_sound: &$crate::backend::audio::swf::Sound,
+        ) -> Result<$crate::backend::audio::SoundHandle, Box<dyn std::error::Error>> {
+            self.$mixer.register_sound(swf_sound)
+        }
+
+        fn register_sound_from_movie(
+            &mut self,
+            swf_sound: &$crate::backend::audio::swf::Sound,
+            movie_data: &$crate::tag_utils::SwfSlice,
+        ) -> Result<$crate::backend::audio::SoundHandle, Box<dyn std::error::Error>> {
+            self.$mixer.register_sound_from_movie(swf_sound, movie_data)
+        }
+
+        fn start_sound(
+            &mut self,
+            sound_handle: $crate::backend::audio::SoundHandle,
+            settings: &$crate::backend::audio::swf::SoundInfo,
+        ) -> Result<$crate::backend::audio::SoundInstanceHandle, Box<dyn std::error::Error>> {
+            self.$mixer.start_sound(sound_handle, settings)
+        }
+
+        fn start_stream(
+            &mut self,
+            stream_handle: Option<$crate::backend::audio::SoundHandle>,
+            clip_frame: u16,
+            clip_data: $crate::tag_utils::SwfSlice,
+            stream_info: &$crate::backend::audio::swf::SoundStreamHead,
+        ) -> Result<$crate::backend::audio::SoundInstanceHandle, Box<dyn std::error::Error>> {
+            self.$mixer
+                .start_stream(stream_handle, clip_frame, clip_data, stream_info)
+        }
+
+        fn seek_stream(
+            &mut self,
+            instance: $crate::backend::audio::SoundInstanceHandle,
+            clip_frame: u16,
+        ) -> bool {
+            self.$mixer.seek_stream(instance, clip_frame)
+        }
+
+        fn stop_sound(&mut self, sound: $crate::backend::audio::SoundInstanceHandle) {
+            self.$mixer.stop_sound(sound)
+        }
+
+        fn stop_all_sounds(&mut self) {
+            self.$mixer.stop_all_sounds()
+        }
+
+        fn get_sound_position(
+            &self,
+            instance: $crate::backend::audio::SoundInstanceHandle,
+        ) -> Option<u32> {
+            self.$mixer.get_sound_position(instance)
+        }
+
+        fn get_sound_duration(&self, sound: $crate::backend::audio::SoundHandle) -> Option<u32> {
+            self.$mixer.get_sound_duration(sound)
+        }
+
+        fn set_sound_transform(
+            &mut self,
+            instance: $crate::backend::audio::SoundInstanceHandle,
+            transform: $crate::backend::audio::SoundTransform,
+        ) {
+            self.$mixer.set_sound_transform(instance, transform)
+        }
+
+        fn advance_frame_clock(&mut self, dt: f64) {
+            self.$mixer.advance_frame_clock(dt)
+        }
+    };
+}
+
+/// The encoded data of a registered sound.
+enum StoredSoundData {
+    /// Data from the tag that defined the sound, within its movie.
+    ///
+    /// Registered sounds live as long as the backend, so they mustn't keep
+    /// their movie alive. Playing instances of the sound do, however.
+    Movie(WeakSwfSlice),
+
+    /// Data that isn't part of a movie, such as decoded MP3 data.
+    Owned(Arc<[u8]>),
+}
+
+impl StoredSoundData {
+    /// The data to play the sound from, unless its movie has been unloaded.
+    fn upgrade(&self) -> Option<SoundData> {
+        match self {
+            StoredSoundData::Movie(slice) => slice.upgrade().map(SoundData::Movie),
+            StoredSoundData::Owned(data) => Some(SoundData::Owned(data.clone())),
+        }
+    }
+}
+
+/// The encoded data of a sound, which is cheap to clone for each playing
+/// instance of the sound.
+#[derive(Clone)]
+enum SoundData {
+    /// Data from the tag that defined the sound, within its movie.
+    Movie(SwfSlice),
+
+    /// Data that isn't part of a movie, such as decoded MP3 data.
+    Owned(Arc<[u8]>),
+}
+
+impl AsRef<[u8]> for SoundData {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            SoundData::Movie(slice) => slice.as_ref(),
+            SoundData::Owned(data) => data,
+        }
+    }
+}
+
+impl Default for SoundData {
+    fn default() -> Self {
+        SoundData::Owned(Arc::new([]))
+    }
+}
+
+/// Convert a sample position in 44.1KHz, as used by `SoundInfo`, to a sample
+/// frame of a sound with the given sample rate.
+fn swf_sample_to_sample_frame(sample: u32, sample_rate: u16) -> u32 {
+    (u64::from(sample) * u64::from(sample_rate) / u64::from(SWF_SAMPLE_RATE)) as u32
+}
+
+/// A signal for event sound instances using sound settings (looping, start/end point, envelope).
+struct EventSoundSignal {
+    decoder: Box<dyn SeekableDecoder + Send>,
+    num_loops: u16,
+    start_sample_frame: u32,
+    end_sample_frame: Option<u32>,
+    cur_sample_frame: u32,
+    is_exhausted: bool,
+}
+
+impl EventSoundSignal {
+    fn new_with_settings(
+        decoder: Box<dyn SeekableDecoder + Send>,
+        settings: &swf::SoundInfo,
+        num_sample_frames: u32,
+        skip_sample_frames: u16,
+    ) -> Self {
+        let skip_sample_frames = u32::from(skip_sample_frames);
+        let sample_rate = decoder.sample_rate();
+        let start_sample_frame = settings
+            .in_sample
+            .map(|n| swf_sample_to_sample_frame(n, sample_rate))
+            .unwrap_or(0)
+            + skip_sample_frames;
+        let end_sample_frame = settings
+            .out_sample
+            .map(|n| swf_sample_to_sample_frame(n, sample_rate))
+            .unwrap_or(num_sample_frames)
+            + skip_sample_frames;
+
+        let mut signal = Self {
+            decoder,
+            num_loops: settings.num_loops,
+            start_sample_frame,
+            end_sample_frame: Some(end_sample_frame),
+            cur_sample_frame: start_sample_frame,
+            is_exhausted: false,
+        };
+        signal.next_loop();
+        signal
+    }
+
+    /// Resets the decoder to the start point of the loop.
+    fn next_loop(&mut self) {
+        if self.num_loops > 0 {
+            self.num_loops -= 1;
+            self.decoder.seek_to_sample_frame(self.start_sample_frame);
+            self.cur_sample_frame = self.start_sample_frame;
+        } else {
+            self.is_exhausted = true;
+        }
+    }
+}
+
+impl dasp::signal::Signal for EventSoundSignal {
+    type Frame = [i16; 2];
+
+    fn next(&mut self) -> Self::Frame {
+        // Loop the sound if necessary, and get the next frame.
+        if !self.is_exhausted {
+            if let Some(frame) = self.decoder.next() {
+                self.cur_sample_frame += 1;
+                if let Some(end) = self.end_sample_frame {
+                    if self.cur_sample_frame > end {
+                        self.next_loop();
+                    }
+                }
+                frame
+            } else if self.cur_sample_frame > self.start_sample_frame {
+                self.next_loop();
+                self.next()
+            } else {
+                // The sound has no data past its start point, so looping won't
+                // produce any more frames.
+                self.is_exhausted = true;
+                [0, 0]
+            }
+        } else {
+            [0, 0]
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.is_exhausted
+    }
+}
+
+/// A signal that represents the sound envelope for an event sound.
+/// The sound signal gets multiplied by the envelope for volume/panning effects.
+struct EnvelopeSignal {
+    /// Iterator through the envelope points specified in the SWWF file.
+    envelope: std::vec::IntoIter<swf::SoundEnvelopePoint>,
+
+    /// The starting envelope point.
+    prev_point: swf::SoundEnvelopePoint,
+
+    /// The ending envelope point.
+    next_point: swf::SoundEnvelopePoint,
+
+    /// The current sample index.
+    cur_sample: u32,
+}
+
+impl EnvelopeSignal {
+    fn new(envelope: &[swf::SoundEnvelopePoint], output_sample_rate: u32) -> Self {
+        // Scale the envelope points from 44.1KHz to the output rate.
+        let scale = f64::from(output_sample_rate) / f64::from(SWF_SAMPLE_RATE);
+        let mut envelope = envelope
+            .iter()
+            .map(|pt| swf::SoundEnvelopePoint {
+                sample: (f64::from(pt.sample) * scale) as u32,
+                ..*pt
+            })
+            .collect::<swf::SoundEnvelope>()
+            .into_iter();
+        let first_point = envelope.next().unwrap_or(swf::SoundEnvelopePoint {
+            sample: 0,
+            left_volume: 1.0,
+            right_volume: 1.0,
+        });
+        Self {
+            // The initial volume is the first point's volume.
+            prev_point: swf::SoundEnvelopePoint {
+                sample: 0,
+                left_volume: first_point.left_volume,
+                right_volume: first_point.right_volume,
+            },
+            next_point: first_point,
+            cur_sample: 0,
+            envelope,
+        }
+    }
+}
+
+impl dasp::signal::Signal for EnvelopeSignal {
+    type Frame = [f32; 2];
+
+    fn next(&mut self) -> Self::Frame {
+        // Calculate interpolated volume.
+        let out = if self.prev_point.sample < self.next_point.sample {
+            let a = f64::from(self.cur_sample - self.prev_point.sample);
+            let b = f64::from(self.next_point.sample - self.prev_point.sample);
+            let lerp = a / b;
+            let interpolator = dasp::interpolate::linear::Linear::new(
+                [self.prev_point.left_volume, self.prev_point.right_volume],
+                [self.next_point.left_volume, self.next_point.right_volume],
+            );
+            use dasp::interpolate::Interpolator;
+            interpolator.interpolate(lerp)
+        } else {
+            [self.next_point.left_volume, self.next_point.right_volume]
+        };
+
+        // Update envelope endpoints.
+        self.cur_sample = self.cur_sample.saturating_add(1);
+        while self.cur_sample > self.next_point.sample {
+            self.prev_point = self.next_point.clone();
+            self.next_point = self
+                .envelope
+                .next()
+                .clone()
+                .unwrap_or(swf::SoundEnvelopePoint {
+                    sample: u32::MAX,
+                    left_volume: self.prev_point.left_volume,
+                    right_volume: self.prev_point.right_volume,
+                });
+
+            if self.prev_point.sample > self.next_point.sample {
+                self.next_point.sample = self.prev_point.sample;
+                log::error!("Invalid sound envelope; sample indices are out of order");
+            }
+        }
+
+        out
+    }
+
+    fn is_exhausted(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Register a sound of 8-bit mono PCM at 44.1KHz, where each sample frame
+    /// holds its own index.
+    fn register_ramp(mixer: &mut AudioMixer, num_sample_frames: u8) -> SoundHandle {
+        let data: Vec<u8> = (0..num_sample_frames).collect();
+        mixer
+            .register_sound(&swf::Sound {
+                id: 1,
+                format: swf::SoundFormat {
+                    compression: AudioCompression::Uncompressed,
+                    sample_rate: 44100,
+                    is_stereo: false,
+                    is_16_bit: false,
+                },
+                num_samples: num_sample_frames.into(),
+                data: &data,
+            })
+            .unwrap()
+    }
+
+    fn sound_info(
+        in_sample: Option<u32>,
+        out_sample: Option<u32>,
+        num_loops: u16,
+    ) -> swf::SoundInfo {
+        swf::SoundInfo {
+            event: swf::SoundEvent::Event,
+            in_sample,
+            out_sample,
+            num_loops,
+            envelope: None,
+        }
+    }
+
+    /// Mix the whole of a sound, and return the left channel of each sample frame.
+    fn mix_to_end(mixer: &AudioMixer, instance: SoundInstanceHandle) -> Vec<i16> {
+        let mut samples = Vec::new();
+        while mixer.get_sound_position(instance).is_some() {
+            let mut buffer = [0i16; 2];
+            mixer.mix(&mut buffer);
+            samples.push(buffer[0]);
+        }
+        samples
+    }
+
+    #[test]
+    fn mixes_loops_between_in_and_out_points() {
+        let mut mixer = AudioMixer::new(2, 44100);
+        let sound = register_ramp(&mut mixer, 8);
+        let instance = mixer
+            .start_sound(sound, &sound_info(Some(2), Some(4), 3))
+            .unwrap();
+        let samples = mix_to_end(&mixer, instance);
+
+        let ramp = |i: i16| (i - 127) * 128;
+        let expected: Vec<i16> = [2, 3, 4, 2, 3, 4].iter().map(|i| ramp(*i)).collect();
+        assert_eq!(&samples[..expected.len()], &expected[..]);
+    }
+
+    #[test]
+    fn silences_extra_output_channels() {
+        let mut mixer = AudioMixer::new(4, 44100);
+        let sound = register_ramp(&mut mixer, 8);
+        mixer
+            .start_sound(sound, &sound_info(None, None, 1))
+            .unwrap();
+
+        let mut buffer = [1i16; 8];
+        mixer.mix(&mut buffer);
+        assert_eq!(&buffer[2..4], &[0, 0]);
+        assert_eq!(&buffer[6..8], &[0, 0]);
+    }
+
+    #[test]
+    fn sounds_from_a_movie_do_not_keep_it_alive() {
+        let mut mixer = AudioMixer::new(2, 44100);
+        let source = crate::tag_utils::SwfMovie::empty(8);
+        let movie = Arc::new(source.from_movie_and_subdata((0..8).collect(), &source));
+        let sound = mixer
+            .register_sound_from_movie(
+                &swf::Sound {
+                    id: 1,
+                    format: swf::SoundFormat {
+                        compression: AudioCompression::Uncompressed,
+                        sample_rate: 44100,
+                        is_stereo: false,
+                        is_16_bit: false,
+                    },
+                    num_samples: 8,
+                    data: movie.data(),
+                },
+                &SwfSlice::from(movie.clone()),
+            )
+            .unwrap();
+
+        // Playing instances keep the data alive until they finish...
+        let instance = mixer
+            .start_sound(sound, &sound_info(None, None, 1))
+            .unwrap();
+        let weak_movie = Arc::downgrade(&movie);
+        drop(movie);
+        assert!(weak_movie.upgrade().is_some());
+        mix_to_end(&mixer, instance);
+
+        // ...but the registered sound doesn't.
+        assert!(weak_movie.upgrade().is_none());
+        assert!(mixer
+            .start_sound(sound, &sound_info(None, None, 1))
+            .is_err());
+    }
+
+    /// Mix one sample frame at a time up to `end`, and return the output
+    /// positions at which the output goes from silent to playing.
+    fn sound_starts(mixer: &AudioMixer, start: u64, end: u64) -> Vec<u64> {
+        let mut starts = vec![];
+        let mut was_silent = true;
+        for position in start..end {
+            let mut buffer = [0i16; 2];
+            mixer.mix(&mut buffer);
+            if was_silent && buffer[0] != 0 {
+                starts.push(position);
+            }
+            was_silent = buffer[0] == 0;
+        }
+        starts
+    }
+
+    #[test]
+    fn starts_sounds_a_frame_apart() {
+        let mut mixer = AudioMixer::new(2, 44100);
+        let sound = register_ramp(&mut mixer, 8);
+
+        // Mix in buffers that don't line up with the frames of the movie.
+        let mut buffer = [0i16; 400];
+        mixer.mix(&mut buffer);
+
+        // 20ms is 882 sample frames. The first frame starts right after the
+        // output mixed so far, without waiting for another buffer.
+        mixer.advance_frame_clock(20.0);
+        mixer
+            .start_sound(sound, &sound_info(None, None, 1))
+            .unwrap();
+        mixer.advance_frame_clock(20.0);
+        mixer
+            .start_sound(sound, &sound_info(None, None, 1))
+            .unwrap();
+
+        assert_eq!(sound_starts(&mixer, 200, 1200), vec![200, 1082]);
+    }
+
+    #[test]
+    fn starts_late_event_sounds_immediately() {
+        let mut mixer = AudioMixer::new(2, 44100);
+        let sound = register_ramp(&mut mixer, 8);
+        mixer.advance_frame_clock(20.0);
+
+        // The output has already been mixed past the start of the frame.
+        let mut buffer = [0i16; 400];
+        mixer.mix(&mut buffer);
+        mixer
+            .start_sound(sound, &sound_info(None, None, 1))
+            .unwrap();
+
+        assert_eq!(sound_starts(&mixer, 200, 300), vec![200]);
+    }
+
+    #[test]
+    fn starts_streams_immediately() {
+        let mut mixer = AudioMixer::new(2, 44100);
+        let mut buffer = [0i16; 400];
+        mixer.mix(&mut buffer);
+
+        // The frame clock runs ahead of the output, but streams ignore it.
+        mixer.advance_frame_clock(20.0);
+        mixer.advance_frame_clock(1.0);
+        mixer
+            .start_stream(None, 1, stream_clip(1, &[]), &stream_head())
+            .unwrap();
+
+        assert_eq!(sound_starts(&mixer, 200, 300), vec![200]);
+    }
+
+    /// Build the tag data of a clip with a stream of 8-bit mono PCM at 44.1KHz,
+    /// where each sample of a frame's stream block holds the frame number.
+    /// The frames in `silent_frames` have no stream block.
+    fn stream_clip(num_frames: u8, silent_frames: &[u8]) -> SwfSlice {
+        const SAMPLES_PER_FRAME: u16 = 16;
+        let mut data = vec![];
+        for frame in 1..=num_frames {
+            if silent_frames.contains(&frame) {
+                data.extend_from_slice(&((::swf::TagCode::ShowFrame as u16) << 6).to_le_bytes());
+                continue;
+            }
+            let block_header = ((::swf::TagCode::SoundStreamBlock as u16) << 6) | SAMPLES_PER_FRAME;
+            data.extend_from_slice(&block_header.to_le_bytes());
+            data.extend(std::iter::repeat(frame).take(SAMPLES_PER_FRAME.into()));
+            data.extend_from_slice(&((::swf::TagCode::ShowFrame as u16) << 6).to_le_bytes());
+        }
+        data.extend_from_slice(&[0, 0]);
+
+        let movie = crate::tag_utils::SwfMovie::empty(8);
+        SwfSlice::from(Arc::new(movie.from_movie_and_subdata(data, &movie)))
+    }
+
+    fn stream_head() -> swf::SoundStreamHead {
+        let format = swf::SoundFormat {
+            compression: AudioCompression::Uncompressed,
+            sample_rate: 44100,
+            is_stereo: false,
+            is_16_bit: false,
+        };
+        swf::SoundStreamHead {
+            stream_format: format.clone(),
+            playback_format: format,
+            num_samples_per_block: 16,
+            latency_seek: 0,
+        }
+    }
+
+    #[test]
+    fn seeks_stream_to_frame() {
+        let mut mixer = AudioMixer::new(2, 44100);
+        let instance = mixer
+            .start_stream(None, 2, stream_clip(4, &[]), &stream_head())
+            .unwrap();
+
+        let ramp = |i: i16| (i - 127) * 128;
+        let mut buffer = [0i16; 2];
+        mixer.mix(&mut buffer);
+        assert_eq!(buffer[0], ramp(2));
+
+        assert!(mixer.seek_stream(instance, 4));
+        mixer.mix(&mut buffer);
+        assert_eq!(buffer[0], ramp(4));
+
+        assert!(mixer.seek_stream(instance, 1));
+        mixer.mix(&mut buffer);
+        assert_eq!(buffer[0], ramp(1));
+
+        assert!(!mixer.seek_stream(instance, 5));
+    }
+
+    #[test]
+    fn starts_stream_on_frame_without_block() {
+        let mut mixer = AudioMixer::new(2, 44100);
+        let instance = mixer
+            .start_stream(None, 2, stream_clip(4, &[2]), &stream_head())
+            .unwrap();
+
+        // The stream picks up with the block of the next frame.
+        let ramp = |i: i16| (i - 127) * 128;
+        let mut buffer = [0i16; 2];
+        mixer.mix(&mut buffer);
+        assert_eq!(buffer[0], ramp(3));
+
+        assert!(mixer.seek_stream(instance, 2));
+        mixer.mix(&mut buffer);
+        assert_eq!(buffer[0], ramp(3));
+    }
+}
@@ -1,9 +1,264 @@
-use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, Offset,
+    TimeZone, Timelike, Utc, Weekday,
+};
+use std::fmt;
 
 pub trait LocaleBackend {
     fn get_current_date_time(&self) -> DateTime<Utc>;
 
-    fn get_timezone(&self) -> FixedOffset;
+    /// Get the time zone that local times are calculated in.
+    fn get_timezone(&self) -> LocalTimeZone;
+}
+
+/// The time zone used by the local time methods of `Date`.
+///
+/// Flash Player doesn't consult a time zone database. Instead, it applies the
+/// daylight saving time rules of the current year to every date, however far
+/// in the past or future it is. A time zone is therefore only a standard
+/// offset from UTC, along with an optional yearly daylight saving time rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalTimeZone {
+    standard_offset: FixedOffset,
+    dst: Option<DstRule>,
+}
+
+impl LocalTimeZone {
+    /// A time zone that never observes daylight saving time.
+    pub fn fixed(offset: FixedOffset) -> Self {
+        Self {
+            standard_offset: offset,
+            dst: None,
+        }
+    }
+
+    /// A time zone that observes daylight saving time every year.
+    pub fn with_dst(standard_offset: FixedOffset, dst: DstRule) -> Self {
+        Self {
+            standard_offset,
+            dst: Some(dst),
+        }
+    }
+
+    /// Derive a time zone from the offsets of the host's time zone over the
+    /// current year.
+    ///
+    /// `offset_at` returns the host's offset from UTC at a given time. If the
+    /// host doesn't start and end daylight saving time exactly once this year,
+    /// the current offset is used for every date.
+    pub fn from_host(now: DateTime<Utc>, offset_at: impl Fn(DateTime<Utc>) -> FixedOffset) -> Self {
+        let fallback = Self::fixed(offset_at(now));
+
+        let mut time = Utc.ymd(now.year(), 1, 1).and_hms(0, 0, 0);
+        let mut offset = offset_at(time);
+        let initial_offset = offset;
+        let mut transitions = vec![];
+        while time.year() == now.year() {
+            let next_time = time + Duration::days(1);
+            let next_offset = offset_at(next_time);
+            if next_offset != offset {
+                // Narrow the change down to the second.
+                let (mut before, mut after) = (time, next_time);
+                while after.signed_duration_since(before) > Duration::seconds(1) {
+                    let middle = before + after.signed_duration_since(before) / 2;
+                    if offset_at(middle) == offset {
+                        before = middle;
+                    } else {
+                        after = middle;
+                    }
+                }
+                transitions.push((after, next_offset));
+            }
+            time = next_time;
+            offset = next_offset;
+        }
+
+        let (first, second) = match transitions[..] {
+            [first, second] if second.1 == initial_offset => (first, second),
+            _ => return fallback,
+        };
+
+        let first_is_start = first.1.local_minus_utc() > second.1.local_minus_utc();
+        let (start, end, standard_offset, dst_offset) = if first_is_start {
+            (first.0, second.0, second.1, first.1)
+        } else {
+            (second.0, first.0, first.1, second.1)
+        };
+
+        let standard_time =
+            |time: DateTime<Utc>| time.with_timezone(&standard_offset).naive_local();
+        Self::with_dst(
+            standard_offset,
+            DstRule {
+                start: DstTransition::from_standard_time(standard_time(start)),
+                end: DstTransition::from_standard_time(standard_time(end)),
+                offset: Duration::seconds(
+                    (dst_offset.local_minus_utc() - standard_offset.local_minus_utc()).into(),
+                ),
+            },
+        )
+    }
+
+    /// The offset of standard time from UTC.
+    pub fn standard_offset(&self) -> FixedOffset {
+        self.standard_offset
+    }
+
+    /// The offset of local time from UTC at a given UTC time.
+    pub fn offset_at(&self, utc: &NaiveDateTime) -> FixedOffset {
+        let dst = match self.dst {
+            Some(dst) => dst,
+            None => return self.standard_offset,
+        };
+
+        let standard_seconds = self.standard_offset.local_minus_utc();
+        let is_dst = utc
+            .checked_add_signed(Duration::seconds(standard_seconds.into()))
+            .map_or(false, |standard_time| dst.contains(standard_time));
+        if is_dst {
+            FixedOffset::east_opt(standard_seconds + dst.offset.num_seconds() as i32)
+                .unwrap_or(self.standard_offset)
+        } else {
+            self.standard_offset
+        }
+    }
+}
+
+/// The yearly period in which daylight saving time is observed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DstRule {
+    /// When daylight saving time starts, in standard time.
+    pub start: DstTransition,
+
+    /// When daylight saving time ends, in standard time.
+    pub end: DstTransition,
+
+    /// How far clocks are moved forward during daylight saving time.
+    pub offset: Duration,
+}
+
+impl DstRule {
+    /// Whether a standard time is within daylight saving time.
+    fn contains(&self, standard_time: NaiveDateTime) -> bool {
+        let year = standard_time.year();
+        let (start, end) = match (self.start.in_year(year), self.end.in_year(year)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return false,
+        };
+
+        if start < end {
+            standard_time >= start && standard_time < end
+        } else {
+            // Daylight saving time spans the new year in the southern hemisphere.
+            standard_time >= start || standard_time < end
+        }
+    }
+}
+
+/// A yearly point in time, such as "the last Sunday of March at 01:00".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DstTransition {
+    /// The month, from 1 to 12.
+    pub month: u32,
+
+    /// The week of the month, from 1 to 5, where 5 is always the last week.
+    pub week: u8,
+
+    pub weekday: Weekday,
+
+    /// The time of day, in seconds since midnight.
+    pub time: u32,
+}
+
+impl DstTransition {
+    fn from_standard_time(time: NaiveDateTime) -> Self {
+        let days_in_month = match time.month() {
+            12 => NaiveDate::from_ymd(time.year() + 1, 1, 1),
+            month => NaiveDate::from_ymd(time.year(), month + 1, 1),
+        }
+        .signed_duration_since(NaiveDate::from_ymd(time.year(), time.month(), 1))
+        .num_days() as u32;
+
+        let week = if time.day() + 7 > days_in_month {
+            5
+        } else {
+            (time.day0() / 7 + 1) as u8
+        };
+
+        Self {
+            month: time.month(),
+            week,
+            weekday: time.weekday(),
+            time: time.num_seconds_from_midnight(),
+        }
+    }
+
+    /// The standard time of this transition in a given year.
+    fn in_year(&self, year: i32) -> Option<NaiveDateTime> {
+        let date = NaiveDate::from_weekday_of_month_opt(year, self.month, self.weekday, self.week)
+            .or_else(|| {
+                if self.week == 5 {
+                    NaiveDate::from_weekday_of_month_opt(year, self.month, self.weekday, 4)
+                } else {
+                    None
+                }
+            })?;
+        date.and_hms(0, 0, 0)
+            .checked_add_signed(Duration::seconds(self.time.into()))
+    }
+}
+
+/// The offset of a `LocalTimeZone` from UTC at some point in time.
+#[derive(Clone, Copy, Debug)]
+pub struct LocalOffset {
+    timezone: LocalTimeZone,
+    offset: FixedOffset,
+}
+
+impl Offset for LocalOffset {
+    fn fix(&self) -> FixedOffset {
+        self.offset
+    }
+}
+
+impl fmt::Display for LocalOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.offset, f)
+    }
+}
+
+impl TimeZone for LocalTimeZone {
+    type Offset = LocalOffset;
+
+    fn from_offset(offset: &LocalOffset) -> Self {
+        offset.timezone
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<LocalOffset> {
+        self.offset_from_local_datetime(&local.and_hms(0, 0, 0))
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<LocalOffset> {
+        // Like Flash Player, daylight saving time is looked up as if the local
+        // time was in standard time. Every local time is valid and unambiguous,
+        // even those skipped or repeated when the clocks change.
+        let standard_seconds = self.standard_offset.local_minus_utc();
+        match local.checked_sub_signed(Duration::seconds(standard_seconds.into())) {
+            Some(utc) => LocalResult::Single(self.offset_from_utc_datetime(&utc)),
+            None => LocalResult::None,
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> LocalOffset {
+        self.offset_from_utc_datetime(&utc.and_hms(0, 0, 0))
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> LocalOffset {
+        LocalOffset {
+            timezone: *self,
+            offset: self.offset_at(utc),
+        }
+    }
 }
 
 /// Locale backend that mostly does nothing.
@@ -11,21 +266,32 @@ pub trait LocaleBackend {
 /// For tests, this backend will emulate being in Nepal with a local time of 2001-02-03 at 04:05:06.
 /// Nepal has a timezone offset of +5:45, and has never used DST.
 /// This makes it an ideal candidate for fixed tests.
-pub struct NullLocaleBackend {}
+pub struct NullLocaleBackend {
+    timezone: LocalTimeZone,
+}
 
 impl NullLocaleBackend {
     pub fn new() -> Self {
-        Self {}
+        Self::with_timezone(LocalTimeZone::fixed(FixedOffset::east(20700)))
+    }
+
+    /// Emulate being in another time zone, still at a local time of
+    /// 2001-02-03 at 04:05:06.
+    pub fn with_timezone(timezone: LocalTimeZone) -> Self {
+        Self { timezone }
     }
 }
 
 impl LocaleBackend for NullLocaleBackend {
     fn get_current_date_time(&self) -> DateTime<Utc> {
-        self.get_timezone().ymd(2001, 2, 3).and_hms(4, 5, 6).into()
+        self.get_timezone()
+            .ymd(2001, 2, 3)
+            .and_hms(4, 5, 6)
+            .with_timezone(&Utc)
     }
 
-    fn get_timezone(&self) -> FixedOffset {
-        FixedOffset::east(20700)
+    fn get_timezone(&self) -> LocalTimeZone {
+        self.timezone
     }
 }
 
@@ -34,3 +300,118 @@ impl Default for NullLocaleBackend {
         NullLocaleBackend::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Eastern Time, with the daylight saving time rules in use since 2007.
+    fn eastern_time() -> LocalTimeZone {
+        LocalTimeZone::with_dst(
+            FixedOffset::west(5 * 3600),
+            DstRule {
+                start: DstTransition {
+                    month: 3,
+                    week: 2,
+                    weekday: Weekday::Sun,
+                    time: 2 * 3600,
+                },
+                end: DstTransition {
+                    month: 11,
+                    week: 1,
+                    weekday: Weekday::Sun,
+                    time: 3600,
+                },
+                offset: Duration::hours(1),
+            },
+        )
+    }
+
+    fn offset_hours(timezone: &LocalTimeZone, utc: DateTime<Utc>) -> f64 {
+        timezone.offset_at(&utc.naive_utc()).local_minus_utc() as f64 / 3600.0
+    }
+
+    #[test]
+    fn applies_current_dst_rules_to_every_year() {
+        let timezone = eastern_time();
+        assert_eq!(
+            offset_hours(&timezone, Utc.ymd(2021, 1, 15).and_hms(12, 0, 0)),
+            -5.0
+        );
+        assert_eq!(
+            offset_hours(&timezone, Utc.ymd(2021, 7, 15).and_hms(12, 0, 0)),
+            -4.0
+        );
+
+        // In 1985, daylight saving time started in April, but Flash Player
+        // uses this year's rules.
+        assert_eq!(
+            offset_hours(&timezone, Utc.ymd(1985, 3, 20).and_hms(12, 0, 0)),
+            -4.0
+        );
+    }
+
+    #[test]
+    fn converts_transition_times() {
+        let timezone = eastern_time();
+
+        // DST starts on 2021-03-14 at 02:00 EST, 07:00 UTC.
+        assert_eq!(
+            offset_hours(&timezone, Utc.ymd(2021, 3, 14).and_hms(6, 59, 59)),
+            -5.0
+        );
+        assert_eq!(
+            offset_hours(&timezone, Utc.ymd(2021, 3, 14).and_hms(7, 0, 0)),
+            -4.0
+        );
+
+        // Local times skipped by the change are looked up in standard time.
+        let skipped = timezone.ymd(2021, 3, 14).and_hms(2, 30, 0);
+        assert_eq!(
+            skipped.with_timezone(&Utc),
+            Utc.ymd(2021, 3, 14).and_hms(6, 30, 0)
+        );
+    }
+
+    #[test]
+    fn handles_southern_hemisphere_dst() {
+        let timezone = LocalTimeZone::with_dst(
+            FixedOffset::east(10 * 3600),
+            DstRule {
+                start: DstTransition {
+                    month: 10,
+                    week: 1,
+                    weekday: Weekday::Sun,
+                    time: 2 * 3600,
+                },
+                end: DstTransition {
+                    month: 4,
+                    week: 1,
+                    weekday: Weekday::Sun,
+                    time: 2 * 3600,
+                },
+                offset: Duration::hours(1),
+            },
+        );
+        assert_eq!(
+            offset_hours(&timezone, Utc.ymd(2021, 1, 15).and_hms(12, 0, 0)),
+            11.0
+        );
+        assert_eq!(
+            offset_hours(&timezone, Utc.ymd(2021, 7, 15).and_hms(12, 0, 0)),
+            10.0
+        );
+    }
+
+    #[test]
+    fn derives_dst_rules_from_host() {
+        let host = eastern_time();
+        let now = Utc.ymd(2021, 6, 1).and_hms(0, 0, 0);
+        let timezone = LocalTimeZone::from_host(now, |time| host.offset_at(&time.naive_utc()));
+        assert_eq!(timezone, host);
+
+        let fixed = FixedOffset::east(20700);
+        let timezone = LocalTimeZone::from_host(now, |_| fixed);
+        assert_eq!(timezone, LocalTimeZone::fixed(fixed));
+    }
+}
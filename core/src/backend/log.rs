@@ -1,3 +1,7 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Where the output of `trace()` goes.
 pub trait LogBackend {
     fn avm_trace(&self, message: &str);
 }
@@ -22,3 +26,50 @@ impl Default for NullLogBackend {
         NullLogBackend::new()
     }
 }
+
+/// Logging backend that prints traces to standard output, one per line.
+#[derive(Default)]
+pub struct StdoutLogBackend {}
+
+impl StdoutLogBackend {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl LogBackend for StdoutLogBackend {
+    fn avm_trace(&self, message: &str) {
+        println!("{}", message);
+    }
+}
+
+/// Logging backend that keeps every trace in memory.
+///
+/// Clones share the same buffer, so a clone can be kept to read the traces
+/// of a player that owns the original.
+#[derive(Clone, Default)]
+pub struct MemoryLogBackend {
+    messages: Rc<RefCell<Vec<String>>>,
+}
+
+impl MemoryLogBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The messages traced so far, one per line.
+    pub fn output(&self) -> String {
+        self.messages.borrow().join("\n")
+    }
+
+    /// Remove and return the messages traced so far.
+    pub fn take_messages(&self) -> Vec<String> {
+        std::mem::take(&mut *self.messages.borrow_mut())
+    }
+}
+
+impl LogBackend for MemoryLogBackend {
+    fn avm_trace(&self, message: &str) {
+        self.messages.borrow_mut().push(message.to_string());
+    }
+}
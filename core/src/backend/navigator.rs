@@ -1,5 +1,7 @@
 //! Browser-related platform functions
 
+pub mod rewrite;
+
 use crate::loader::Error;
 use indexmap::IndexMap;
 use std::borrow::Cow;
@@ -9,7 +11,9 @@ use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::ptr::null;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 use std::time::Duration;
 use swf::avm1::types::SendVarsMethod;
@@ -107,6 +111,10 @@ pub struct RequestOptions {
     ///
     /// The body consists of data and a mime type.
     body: Option<(Vec<u8>, String)>,
+
+    /// Where to report the download progress of the response, if anyone is
+    /// interested in it.
+    progress: Option<Arc<FetchProgress>>,
 }
 
 impl RequestOptions {
@@ -115,6 +123,7 @@ impl RequestOptions {
         Self {
             method: NavigationMethod::Get,
             body: None,
+            progress: None,
         }
     }
 
@@ -123,9 +132,17 @@ impl RequestOptions {
         Self {
             method: NavigationMethod::Post,
             body,
+            progress: None,
         }
     }
 
+    /// Request that the download progress of the response be reported to the
+    /// given `FetchProgress`.
+    pub fn with_progress(mut self, progress: Arc<FetchProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
     /// Retrieve the navigation method for this request.
     pub fn method(&self) -> NavigationMethod {
         self.method
@@ -135,6 +152,83 @@ impl RequestOptions {
     pub fn body(&self) -> &Option<(Vec<u8>, String)> {
         &self.body
     }
+
+    /// Retrieve where the download progress of this request should be
+    /// reported, if anywhere.
+    pub fn progress(&self) -> Option<&Arc<FetchProgress>> {
+        self.progress.as_ref()
+    }
+}
+
+/// The download progress of a fetch.
+///
+/// Navigator backends update this as the response body arrives, and the
+/// player reads it to report loading progress to content (e.g. in
+/// `MovieClip.getBytesLoaded`). It may be updated from any thread.
+#[derive(Debug, Default)]
+pub struct FetchProgress {
+    bytes_loaded: AtomicUsize,
+    bytes_total: AtomicUsize,
+
+    /// The parts of the response body that have arrived but haven't been
+    /// taken yet, if the body is read as it arrives.
+    received: Option<Mutex<Vec<u8>>>,
+}
+
+impl FetchProgress {
+    /// Progress for a fetch whose response body is read as it arrives, with
+    /// `take_received`, rather than once the fetch completes.
+    pub fn streaming() -> Self {
+        Self {
+            received: Some(Mutex::new(Vec::new())),
+            ..Default::default()
+        }
+    }
+
+    /// The number of bytes of the response body received so far.
+    pub fn bytes_loaded(&self) -> usize {
+        self.bytes_loaded.load(Ordering::Relaxed)
+    }
+
+    /// The expected size of the response body, or zero if it is not yet
+    /// known.
+    pub fn bytes_total(&self) -> usize {
+        self.bytes_total.load(Ordering::Relaxed)
+    }
+
+    pub fn set_bytes_loaded(&self, bytes_loaded: usize) {
+        self.bytes_loaded.store(bytes_loaded, Ordering::Relaxed);
+    }
+
+    pub fn set_bytes_total(&self, bytes_total: usize) {
+        self.bytes_total.store(bytes_total, Ordering::Relaxed);
+    }
+
+    /// Record that the next part of the response body has arrived.
+    ///
+    /// Backends must report the whole body this way, in order, even if it
+    /// arrives all at once.
+    pub fn receive(&self, data: &[u8]) {
+        self.bytes_loaded.fetch_add(data.len(), Ordering::Relaxed);
+        if let Some(received) = &self.received {
+            received.lock().unwrap().extend_from_slice(data);
+        }
+    }
+
+    /// Take the parts of the response body that have arrived since the last
+    /// call. This is always empty unless the progress is `streaming`.
+    pub fn take_received(&self) -> Vec<u8> {
+        match &self.received {
+            Some(received) => std::mem::take(&mut *received.lock().unwrap()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Mark the fetch as completely downloaded, with the given total size.
+    pub fn complete(&self, length: usize) {
+        self.set_bytes_total(length);
+        self.set_bytes_loaded(length);
+    }
 }
 
 /// Type alias for pinned, boxed, and owned futures that output a falliable
@@ -173,6 +267,14 @@ pub trait NavigatorBackend {
     );
 
     /// Fetch data at a given URL and return it some time in the future.
+    ///
+    /// If the request options ask for it, the download progress of the
+    /// response should be reported as it arrives, by passing each part of the
+    /// body to `FetchProgress::receive`. Backends that cannot observe the
+    /// download should at least report the whole body once it completes.
+    ///
+    /// The request must not be sent until the returned future is first
+    /// polled, as it may be dropped if the request turns out to be forbidden.
     fn fetch(&self, url: &str, request_options: RequestOptions) -> OwnedFuture<Vec<u8>, Error>;
 
     /// Get the amount of time since the SWF was launched.
@@ -359,11 +461,18 @@ impl NavigatorBackend for NullNavigatorBackend {
     ) {
     }
 
-    fn fetch(&self, url: &str, _opts: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
+    fn fetch(&self, url: &str, opts: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
         let mut path = self.relative_base_path.clone();
         path.push(url);
 
-        Box::pin(async move { fs::read(path).map_err(Error::NetworkError) })
+        Box::pin(async move {
+            let data = fs::read(path).map_err(Error::NetworkError)?;
+            if let Some(progress) = opts.progress() {
+                progress.receive(&data);
+                progress.complete(data.len());
+            }
+            Ok(data)
+        })
     }
 
     fn time_since_launch(&mut self) -> Duration {
@@ -0,0 +1,269 @@
+//! URL rewriting for movies that reference servers which no longer exist.
+//!
+//! Rules are written one per line, as a keyword followed by its arguments:
+//!
+//! * `host <from> <to>` - Replace the host `from` with `to`. A host of the
+//!   form `*.example.com` also matches every subdomain of `example.com`.
+//! * `https <host>` - Upgrade `http` URLs on `host` to `https`. A host of `*`
+//!   upgrades every URL.
+//! * `map <prefix> <target>` - Replace the URL prefix `prefix` with `target`.
+//!   On desktop, `target` may be a local directory instead of a URL.
+//!
+//! Empty lines and lines starting with `#` are ignored.
+
+use std::fmt;
+use std::str::FromStr;
+use url::Url;
+
+/// A single URL rewrite rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UrlRewriteRule {
+    /// Replace a host with another.
+    Host { from: HostPattern, to: String },
+
+    /// Upgrade `http` URLs on a host to `https`.
+    UpgradeScheme { host: HostPattern },
+
+    /// Replace the start of a URL with another URL.
+    Prefix { from: String, to: Url },
+}
+
+impl UrlRewriteRule {
+    /// Apply this rule to a URL, returning the rewritten URL if it matched.
+    pub fn apply(&self, url: &Url) -> Option<Url> {
+        match self {
+            UrlRewriteRule::Host { from, to } => {
+                if !from.matches(url) {
+                    return None;
+                }
+
+                let mut rewritten = url.clone();
+                rewritten.set_host(Some(to)).ok()?;
+                Some(rewritten)
+            }
+            UrlRewriteRule::UpgradeScheme { host } => {
+                if url.scheme() != "http" || !host.matches(url) {
+                    return None;
+                }
+
+                let mut rewritten = url.clone();
+                rewritten.set_scheme("https").ok()?;
+                Some(rewritten)
+            }
+            UrlRewriteRule::Prefix { from, to } => {
+                let rest = url.as_str().strip_prefix(from.as_str())?;
+                Url::parse(&format!("{}{}", to, rest)).ok()
+            }
+        }
+    }
+}
+
+impl FromStr for UrlRewriteRule {
+    type Err = UrlRewriteError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let args: Vec<&str> = rule.split_whitespace().collect();
+        let error = || UrlRewriteError(rule.to_string());
+
+        match args.as_slice() {
+            ["host", from, to] => Ok(UrlRewriteRule::Host {
+                from: HostPattern::new(from),
+                to: to.to_string(),
+            }),
+            ["https", host] => Ok(UrlRewriteRule::UpgradeScheme {
+                host: HostPattern::new(host),
+            }),
+            ["map", from, to] => {
+                let to = parse_target(to).ok_or_else(error)?;
+                Ok(UrlRewriteRule::Prefix {
+                    from: from.to_string(),
+                    to,
+                })
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Parse the target of a `map` rule, which is either a URL or a local
+/// directory.
+fn parse_target(target: &str) -> Option<Url> {
+    // A Windows drive letter parses as a URL scheme, so only accept URLs with
+    // a host or a scheme longer than a single letter.
+    match Url::parse(target) {
+        Ok(url) if url.scheme().len() > 1 => Some(url),
+        _ => directory_url(target),
+    }
+}
+
+#[cfg(any(unix, windows, target_os = "redox"))]
+fn directory_url(path: &str) -> Option<Url> {
+    let path = std::env::current_dir().ok()?.join(path);
+    Url::from_directory_path(path).ok()
+}
+
+#[cfg(not(any(unix, windows, target_os = "redox")))]
+fn directory_url(_path: &str) -> Option<Url> {
+    None
+}
+
+/// A host to match, optionally including all of its subdomains.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HostPattern {
+    /// Matches every host.
+    Any,
+
+    /// Matches exactly this host.
+    Exact(String),
+
+    /// Matches this host and every subdomain of it.
+    Subdomains(String),
+}
+
+impl HostPattern {
+    pub fn new(pattern: &str) -> Self {
+        let pattern = pattern.to_ascii_lowercase();
+        if pattern == "*" {
+            HostPattern::Any
+        } else if let Some(domain) = pattern.strip_prefix("*.") {
+            HostPattern::Subdomains(domain.to_string())
+        } else {
+            HostPattern::Exact(pattern)
+        }
+    }
+
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host,
+            None => return false,
+        };
+
+        match self {
+            HostPattern::Any => true,
+            HostPattern::Exact(pattern) => host == pattern,
+            HostPattern::Subdomains(domain) => {
+                host == domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .map_or(false, |subdomain| subdomain.ends_with('.'))
+            }
+        }
+    }
+}
+
+/// A rule that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UrlRewriteError(String);
+
+impl fmt::Display for UrlRewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid URL rewrite rule: {}", self.0)
+    }
+}
+
+impl std::error::Error for UrlRewriteError {}
+
+/// A list of URL rewrite rules, applied in order to every URL a movie tries
+/// to fetch or navigate to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UrlRewriter {
+    rules: Vec<UrlRewriteRule>,
+}
+
+impl UrlRewriter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Parse a list of rules, one per line.
+    pub fn parse(rules: &str) -> Result<Self, UrlRewriteError> {
+        let mut rewriter = Self::new();
+        for line in rules.lines() {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                rewriter.add_rule(line.parse()?);
+            }
+        }
+        Ok(rewriter)
+    }
+
+    pub fn add_rule(&mut self, rule: UrlRewriteRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rewrite a URL with every matching rule, in order.
+    pub fn rewrite(&self, url: Url) -> Url {
+        let mut rewritten = url.clone();
+        for rule in &self.rules {
+            if let Some(new_url) = rule.apply(&rewritten) {
+                rewritten = new_url;
+            }
+        }
+
+        if rewritten != url {
+            log::info!("Rewrote URL {} to {}", url, rewritten);
+        }
+        rewritten
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(rules: &str, url: &str) -> String {
+        let rewriter = UrlRewriter::parse(rules).unwrap();
+        rewriter.rewrite(Url::parse(url).unwrap()).into()
+    }
+
+    #[test]
+    fn rewrites_hosts() {
+        let rules = "host *.dead.example archive.example";
+        assert_eq!(
+            rewrite(rules, "http://www.dead.example/a.swf?b=c"),
+            "http://archive.example/a.swf?b=c"
+        );
+        assert_eq!(
+            rewrite(rules, "http://dead.example/a.swf"),
+            "http://archive.example/a.swf"
+        );
+        assert_eq!(
+            rewrite(rules, "http://notdead.example/a.swf"),
+            "http://notdead.example/a.swf"
+        );
+    }
+
+    #[test]
+    fn upgrades_schemes() {
+        let rules = "https secure.example";
+        assert_eq!(
+            rewrite(rules, "http://secure.example/"),
+            "https://secure.example/"
+        );
+        assert_eq!(
+            rewrite(rules, "http://other.example/"),
+            "http://other.example/"
+        );
+    }
+
+    #[test]
+    fn maps_prefixes_in_order() {
+        let rules = "# Archived copy\n\
+                     host old.example new.example\n\
+                     map http://new.example/games/ https://mirror.example/g/\n";
+        assert_eq!(
+            rewrite(rules, "http://old.example/games/level1.swf"),
+            "https://mirror.example/g/level1.swf"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert!(UrlRewriter::parse("host only.example").is_err());
+        assert!(UrlRewriter::parse("rewrite a b").is_err());
+    }
+}
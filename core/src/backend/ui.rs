@@ -1,3 +1,4 @@
+use crate::backend::render::Bitmap;
 use crate::events::KeyCode;
 use downcast_rs::Downcast;
 
@@ -20,9 +21,33 @@ pub trait UiBackend: Downcast {
 
     fn is_fullscreen(&self) -> bool;
 
+    /// Called when the root timeline reaches a frame labelled as a named
+    /// anchor, so that the host may record it in its navigation history.
+    fn set_frame_anchor(&mut self, anchor: &str);
+
+    /// Called when the root movie reaches a milestone in its lifecycle, so
+    /// that the host may react (e.g. by hiding a loading animation) at the
+    /// same moment that Flash Player would.
+    ///
+    /// Each event is reported at most once per root movie.
+    fn movie_lifecycle_event(&mut self, event: MovieLifecycleEvent);
+
     /// Displays a warning about unsupported content in Ruffle.
     /// The user can still click an "OK" or "run anyway" message to dismiss the warning.
     fn display_unsupported_message(&self);
+
+    /// Asks the user whether to abort a script that has been running for too long, like
+    /// Flash Player's "A script in this movie is causing Flash Player to run slowly" prompt.
+    ///
+    /// This blocks until the user answers. Returns `true` to abort the script, which stops
+    /// all further scripts in the movie, or `false` to keep running it.
+    fn display_slow_script_prompt(&mut self) -> bool;
+
+    /// Prints the pages of a `PrintJob`, once the movie sends it.
+    ///
+    /// Each page is RGBA with premultiplied alpha, at one pixel per point.
+    fn print(&mut self, pages: Vec<Bitmap>);
+
     // Unused, but kept in case we need it later
     fn message(&self, message: &str);
 }
@@ -49,6 +74,27 @@ pub enum MouseCursor {
     Grab,
 }
 
+/// A milestone in the lifecycle of the root movie.
+/// Communicated from the core to the UI backend via `UiBackend::movie_lifecycle_event`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovieLifecycleEvent {
+    /// The header of the root movie has been parsed, and its size, frame rate
+    /// and frame count are known.
+    HeaderParsed,
+
+    /// The root movie has been preloaded and is ready to start playing.
+    PreloadComplete,
+
+    /// The first frame of the root movie has been executed.
+    FirstFrameExecuted,
+
+    /// The first frame of the root movie has been drawn.
+    FirstFrameRendered,
+
+    /// The root timeline has stopped on its last frame.
+    Ended,
+}
+
 /// UiBackend that does nothing.
 pub struct NullUiBackend {}
 
@@ -85,8 +131,18 @@ impl UiBackend for NullUiBackend {
         false
     }
 
+    fn set_frame_anchor(&mut self, _anchor: &str) {}
+
+    fn movie_lifecycle_event(&mut self, _event: MovieLifecycleEvent) {}
+
     fn display_unsupported_message(&self) {}
 
+    fn display_slow_script_prompt(&mut self) -> bool {
+        true
+    }
+
+    fn print(&mut self, _pages: Vec<Bitmap>) {}
+
     fn message(&self, _message: &str) {}
 }
 
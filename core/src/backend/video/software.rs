@@ -5,10 +5,98 @@ use crate::backend::video::{
     EncodedFrame, Error, FrameDependency, VideoBackend, VideoStreamHandle,
 };
 use generational_arena::Arena;
+#[cfg(feature = "h263")]
+use h263_rs::parser::{decode_picture, H263Reader};
+#[cfg(feature = "h263")]
+use h263_rs::{DecoderOption, H263State, PictureTypeCode};
+#[cfg(feature = "h263")]
+use h263_rs_yuv::bt601::yuv420_to_rgba;
 use swf::{VideoCodec, VideoDeblocking};
 
 /// A single preloaded video stream.
-pub enum VideoStream {}
+pub struct VideoStream {
+    decoder: VideoDecoder,
+
+    /// The bitmap that decoded frames are drawn to.
+    ///
+    /// This is reused for every frame of the stream, unless the frame size
+    /// changes.
+    bitmap: Option<BitmapInfo>,
+}
+
+/// The codec-specific decoder state of a video stream.
+///
+/// Each codec is behind a feature, so that it can be left out of builds that
+/// can't fetch it.
+enum VideoDecoder {
+    #[cfg(feature = "h263")]
+    H263(H263State),
+}
+
+#[cfg_attr(
+    not(feature = "h263"),
+    allow(unused_variables, clippy::match_single_binding)
+)]
+impl VideoDecoder {
+    /// Create the decoder for a stream of the given codec.
+    fn new(codec: VideoCodec) -> Result<Self, Error> {
+        match codec {
+            #[cfg(feature = "h263")]
+            VideoCodec::H263 => Ok(VideoDecoder::H263(H263State::new(
+                DecoderOption::SORENSON_SPARK_BITSTREAM,
+            ))),
+            _ => Err(format!("Unsupported video codec type {:?}", codec).into()),
+        }
+    }
+
+    /// Find out which frames a frame of the stream needs to be decoded.
+    fn preload_frame(&self, encoded_frame: EncodedFrame<'_>) -> Result<FrameDependency, Error> {
+        match *self {
+            #[cfg(feature = "h263")]
+            VideoDecoder::H263(_) => {
+                let mut reader = H263Reader::from_source(encoded_frame.data());
+                let picture =
+                    decode_picture(&mut reader, DecoderOption::SORENSON_SPARK_BITSTREAM, None)?
+                        .ok_or("Picture in video stream is not a picture")?;
+
+                match picture.picture_type {
+                    PictureTypeCode::IFrame => Ok(FrameDependency::None),
+                    PictureTypeCode::PFrame => Ok(FrameDependency::Past),
+                    PictureTypeCode::DisposablePFrame => Ok(FrameDependency::Past),
+                    _ => Err("Invalid picture type code".into()),
+                }
+            }
+        }
+    }
+
+    /// Decode the next frame of the stream, returning its size and its RGBA
+    /// pixels.
+    fn decode_frame(
+        &mut self,
+        encoded_frame: EncodedFrame<'_>,
+    ) -> Result<(u16, u16, Vec<u8>), Error> {
+        match *self {
+            #[cfg(feature = "h263")]
+            VideoDecoder::H263(ref mut state) => {
+                let mut reader = H263Reader::from_source(encoded_frame.data());
+                state.decode_next_picture(&mut reader)?;
+
+                let picture = state
+                    .get_last_picture()
+                    .ok_or("Decoding a picture should let us grab that picture")?;
+                let (width, height) = picture
+                    .format()
+                    .into_width_and_height()
+                    .ok_or("H.263 picture has no size")?;
+                let chroma_width = picture.chroma_samples_per_row();
+                let (y, b, r) = picture.as_yuv();
+                let rgba = yuv420_to_rgba(y, b, r, width.into(), chroma_width);
+
+                Ok((width, height, rgba))
+            }
+        }
+    }
+}
 
 /// Software video backend that proxies to CPU-only codec implementations that
 /// ship with Ruffle.
@@ -38,33 +126,53 @@ impl VideoBackend for SoftwareVideoBackend {
         codec: VideoCodec,
         _filter: VideoDeblocking,
     ) -> Result<VideoStreamHandle, Error> {
-        Err(format!("Unsupported video codec type {:?}", codec).into())
+        let decoder = VideoDecoder::new(codec)?;
+
+        Ok(self.streams.insert(VideoStream {
+            decoder,
+            bitmap: None,
+        }))
     }
 
     fn preload_video_stream_frame(
         &mut self,
         stream: VideoStreamHandle,
-        _encoded_frame: EncodedFrame<'_>,
+        encoded_frame: EncodedFrame<'_>,
     ) -> Result<FrameDependency, Error> {
-        let _stream = self
+        let stream = self
             .streams
             .get_mut(stream)
             .ok_or("Unregistered video stream")?;
 
-        unreachable!()
+        stream.decoder.preload_frame(encoded_frame)
     }
 
     fn decode_video_stream_frame(
         &mut self,
         stream: VideoStreamHandle,
-        _encoded_frame: EncodedFrame<'_>,
-        _renderer: &mut dyn RenderBackend,
+        encoded_frame: EncodedFrame<'_>,
+        renderer: &mut dyn RenderBackend,
     ) -> Result<BitmapInfo, Error> {
-        let _stream = self
+        let stream = self
             .streams
             .get_mut(stream)
             .ok_or("Unregistered video stream")?;
 
-        unreachable!()
+        let (width, height, rgba) = stream.decoder.decode_frame(encoded_frame)?;
+
+        let bitmap = match stream.bitmap {
+            Some(bitmap) if bitmap.width == width && bitmap.height == height => {
+                renderer.update_texture(bitmap.handle, width.into(), height.into(), rgba)?;
+                bitmap
+            }
+            _ => BitmapInfo {
+                handle: renderer.register_bitmap_raw(width.into(), height.into(), rgba)?,
+                width,
+                height,
+            },
+        };
+        stream.bitmap = Some(bitmap);
+
+        Ok(bitmap)
     }
 }
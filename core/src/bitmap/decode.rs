@@ -0,0 +1,563 @@
+//! Decoding of the bitmaps in SWF files, and of images loaded at runtime.
+//!
+//! Every bitmap is decoded here, rather than by the render backends, so that
+//! each backend receives the same pixels. Decoded bitmaps are in sRGB, with
+//! premultiplied alpha: a pixel's color channels never exceed its alpha.
+
+use crate::backend::render::{Bitmap, BitmapFormat, JpegTagFormat};
+use crate::Color;
+use std::io::Read;
+
+type Error = Box<dyn std::error::Error>;
+
+/// How much two pixels on either side of a block edge may differ for the
+/// deblocking filter to smooth them. Larger steps are taken to be edges of
+/// the image itself, which should stay sharp.
+const DEBLOCKING_THRESHOLD: i32 = 48;
+
+/// The deblocking strength of 1.0, in 8.8 fixed-point.
+const MAX_DEBLOCKING: u16 = 0x100;
+
+/// Determines the format of the image data in `data` from a DefineBitsJPEG2/3 tag.
+pub fn determine_jpeg_tag_format(data: &[u8]) -> JpegTagFormat {
+    match data {
+        [0xff, 0xd8, ..] => JpegTagFormat::Jpeg,
+        [0xff, 0xd9, 0xff, 0xd8, ..] => JpegTagFormat::Jpeg, // erroneous header in SWF
+        [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, ..] => JpegTagFormat::Png,
+        [0x47, 0x49, 0x46, 0x38, 0x39, 0x61, ..] => JpegTagFormat::Gif,
+        [0x47, 0x49, 0x46, 0x38, 0x37, 0x61, ..] => JpegTagFormat::Gif,
+        _ => JpegTagFormat::Unknown,
+    }
+}
+
+/// Decodes bitmap data from a DefineBitsJPEG2/3 tag.
+/// The data is returned with pre-multiplied alpha.
+pub fn decode_define_bits_jpeg(data: &[u8], alpha_data: Option<&[u8]>) -> Result<Bitmap, Error> {
+    decode_define_bits_jpeg_4(data, alpha_data, 0)
+}
+
+/// Decodes bitmap data from a DefineBitsJPEG4 tag, smoothing the edges of its
+/// JPEG blocks by the tag's deblocking parameter, an 8.8 fixed-point number.
+/// The data is returned with pre-multiplied alpha.
+pub fn decode_define_bits_jpeg_4(
+    data: &[u8],
+    alpha_data: Option<&[u8]>,
+    deblocking: u16,
+) -> Result<Bitmap, Error> {
+    let format = determine_jpeg_tag_format(data);
+    if format != JpegTagFormat::Jpeg && alpha_data.is_some() {
+        // Only DefineBitsJPEG3 with true JPEG data should have separate alpha data.
+        log::warn!("DefineBitsJPEG contains non-JPEG data with alpha; probably incorrect")
+    }
+    match format {
+        JpegTagFormat::Jpeg => decode_jpeg(data, alpha_data, deblocking),
+        JpegTagFormat::Png => decode_png(data),
+        JpegTagFormat::Gif => decode_gif(data),
+        JpegTagFormat::Unknown => Err("Unknown bitmap data format".into()),
+    }
+}
+
+/// Reads the width and height of a JPEG, PNG or GIF image from its header,
+/// without decoding it.
+pub fn image_dimensions(data: &[u8]) -> Result<(u32, u32), Error> {
+    match determine_jpeg_tag_format(data) {
+        JpegTagFormat::Jpeg => {
+            let jpeg_data = remove_invalid_jpeg_data(data);
+            let mut decoder = jpeg_decoder::Decoder::new(&jpeg_data[..]);
+            decoder.read_info()?;
+            let metadata = decoder.info().ok_or("Unable to get image info")?;
+            Ok((metadata.width.into(), metadata.height.into()))
+        }
+        JpegTagFormat::Png => {
+            let (info, _) = png::Decoder::new(data).read_info()?;
+            Ok((info.width, info.height))
+        }
+        JpegTagFormat::Gif => {
+            let reader = gif::DecodeOptions::new().read_info(data)?;
+            Ok((reader.width().into(), reader.height().into()))
+        }
+        JpegTagFormat::Unknown => Err("Unknown bitmap data format".into()),
+    }
+}
+
+pub fn glue_swf_jpeg_to_tables(jpeg_tables: &[u8], jpeg_data: &[u8]) -> Vec<u8> {
+    let mut full_jpeg = Vec::with_capacity(jpeg_tables.len() + jpeg_data.len() - 4);
+    full_jpeg.extend_from_slice(&jpeg_tables[..jpeg_tables.len() - 2]);
+    full_jpeg.extend_from_slice(&jpeg_data[2..]);
+    full_jpeg
+}
+
+/// Glues the JPEG encoding tables from a JPEGTables SWF tag to the JPEG data
+/// in a DefineBits tag, producing complete JPEG data suitable for a decoder.
+pub fn glue_tables_to_jpeg<'a>(
+    jpeg_data: &'a [u8],
+    jpeg_tables: Option<&'a [u8]>,
+) -> std::borrow::Cow<'a, [u8]> {
+    if let Some(jpeg_tables) = jpeg_tables {
+        if jpeg_tables.len() >= 2 {
+            let mut full_jpeg = Vec::with_capacity(jpeg_tables.len() + jpeg_data.len());
+            full_jpeg.extend_from_slice(&jpeg_tables[..jpeg_tables.len() - 2]);
+            if jpeg_data.len() >= 2 {
+                full_jpeg.extend_from_slice(&jpeg_data[2..]);
+            }
+
+            return std::borrow::Cow::from(full_jpeg);
+        }
+    }
+
+    // No JPEG tables or not enough data; return JPEG data as is
+    std::borrow::Cow::Borrowed(jpeg_data)
+}
+
+/// Removes potential invalid JPEG data from SWF DefineBitsJPEG tags.
+///
+/// SWF19 p.138:
+/// "Before version 8 of the SWF file format, SWF files could contain an erroneous header of 0xFF, 0xD9, 0xFF, 0xD8 before the JPEG SOI marker."
+/// These bytes need to be removed for the JPEG to decode properly.
+pub fn remove_invalid_jpeg_data(mut data: &[u8]) -> std::borrow::Cow<'_, [u8]> {
+    // TODO: Might be better to return an Box<Iterator<Item=u8>> instead of a Cow here,
+    // where the spliced iter is a data[..n].chain(data[n+4..])?
+    if data.get(0..4) == Some(&[0xFF, 0xD9, 0xFF, 0xD8]) {
+        data = &data[4..];
+    }
+    if let Some(pos) = data.windows(4).position(|w| w == [0xFF, 0xD9, 0xFF, 0xD8]) {
+        let mut out_data = Vec::with_capacity(data.len() - 4);
+        out_data.extend_from_slice(&data[..pos]);
+        out_data.extend_from_slice(&data[pos + 4..]);
+        std::borrow::Cow::from(out_data)
+    } else {
+        std::borrow::Cow::Borrowed(data)
+    }
+}
+
+/// Decodes a JPEG with optional alpha data, and an optional deblocking
+/// strength in 8.8 fixed-point.
+/// The decoded bitmap will have pre-multiplied alpha.
+pub fn decode_jpeg(
+    jpeg_data: &[u8],
+    alpha_data: Option<&[u8]>,
+    deblocking: u16,
+) -> Result<Bitmap, Error> {
+    let jpeg_data = remove_invalid_jpeg_data(jpeg_data);
+
+    let mut decoder = jpeg_decoder::Decoder::new(&jpeg_data[..]);
+    decoder.read_info()?;
+    let metadata = decoder.info().ok_or("Unable to get image info")?;
+    let decoded_data = decoder.decode()?;
+
+    let mut decoded_data = match metadata.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => decoded_data,
+        jpeg_decoder::PixelFormat::CMYK32 => cmyk_to_rgb(&decoded_data),
+        jpeg_decoder::PixelFormat::L8 => {
+            let mut rgb = Vec::with_capacity(decoded_data.len() * 3);
+            for elem in decoded_data {
+                rgb.push(elem);
+                rgb.push(elem);
+                rgb.push(elem);
+            }
+            rgb
+        }
+    };
+
+    let width = usize::from(metadata.width);
+    let height = usize::from(metadata.height);
+    deblock(&mut decoded_data, width, height, deblocking);
+
+    // Decompress the alpha data (DEFLATE compression).
+    if let Some(alpha_data) = alpha_data {
+        let alpha_data = decompress_zlib(alpha_data)?;
+
+        if alpha_data.len() == width * height {
+            let mut rgba = Vec::with_capacity(alpha_data.len() * 4);
+            for (rgb, &alpha) in decoded_data.chunks_exact(3).zip(&alpha_data) {
+                // The JPEG holds straight colors, which have to be premultiplied
+                // to match the rest of the bitmaps.
+                rgba.push(premultiply(rgb[0], alpha));
+                rgba.push(premultiply(rgb[1], alpha));
+                rgba.push(premultiply(rgb[2], alpha));
+                rgba.push(alpha);
+            }
+            return Ok(Bitmap {
+                width: metadata.width.into(),
+                height: metadata.height.into(),
+                data: BitmapFormat::Rgba(rgba),
+            });
+        } else {
+            // Size isn't correct; fallback to RGB?
+            log::error!("Size mismatch in DefineBitsJPEG3 alpha data");
+        }
+    }
+
+    // No alpha.
+    Ok(Bitmap {
+        width: metadata.width.into(),
+        height: metadata.height.into(),
+        data: BitmapFormat::Rgb(decoded_data),
+    })
+}
+
+/// Converts the CMYK pixels of a JPEG, such as those saved by Photoshop, to RGB.
+fn cmyk_to_rgb(cmyk: &[u8]) -> Vec<u8> {
+    let mut rgb = Vec::with_capacity(cmyk.len() / 4 * 3);
+    for pixel in cmyk.chunks_exact(4) {
+        let white = 255 - u16::from(pixel[3]);
+        for &ink in &pixel[..3] {
+            rgb.push(((255 - u16::from(ink)) * white / 255) as u8);
+        }
+    }
+    rgb
+}
+
+/// Smooths the edges between the 8x8 blocks that a JPEG is compressed in,
+/// which become visible at low quality, as Flash Player does for
+/// DefineBitsJPEG4 tags.
+///
+/// `strength` is an 8.8 fixed-point number: 0 disables the filter, and 1.0
+/// halves the step between the pixels on either side of an edge.
+fn deblock(rgb: &mut [u8], width: usize, height: usize, strength: u16) {
+    let strength = i32::from(strength.min(MAX_DEBLOCKING));
+    if strength == 0 {
+        return;
+    }
+
+    // Vertical edges, between the blocks of a row.
+    for x in (8..width).step_by(8) {
+        for y in 0..height {
+            let row = y * width;
+            let next = if x + 1 < width { x + 1 } else { x };
+            let pixels = [row + x - 2, row + x - 1, row + x, row + next];
+            filter_block_edge(rgb, pixels, strength);
+        }
+    }
+
+    // Horizontal edges, between rows of blocks.
+    for y in (8..height).step_by(8) {
+        let next = if y + 1 < height { y + 1 } else { y };
+        for x in 0..width {
+            let pixels = [
+                (y - 2) * width + x,
+                (y - 1) * width + x,
+                y * width + x,
+                next * width + x,
+            ];
+            filter_block_edge(rgb, pixels, strength);
+        }
+    }
+}
+
+/// Smooths the step between two pixels on either side of a block edge,
+/// spreading part of it to the next pixel on each side.
+///
+/// `pixels` are the indices of the last two pixels of one block and the first
+/// two of the next. The last index may repeat the third at the image's edge.
+fn filter_block_edge(rgb: &mut [u8], pixels: [usize; 4], strength: i32) {
+    let [p1, p0, q0, q1] = pixels;
+    for channel in 0..3 {
+        let step = i32::from(rgb[q0 * 3 + channel]) - i32::from(rgb[p0 * 3 + channel]);
+        if step.abs() >= DEBLOCKING_THRESHOLD {
+            continue;
+        }
+
+        let delta = step * strength / (4 * i32::from(MAX_DEBLOCKING));
+        let changes = [(p1, delta / 2), (p0, delta), (q0, -delta), (q1, -delta / 2)];
+        for &(pixel, delta) in &changes[..if q1 == q0 { 3 } else { 4 }] {
+            let value = &mut rgb[pixel * 3 + channel];
+            *value = (i32::from(*value) + delta).clamp(0, 255) as u8;
+        }
+    }
+}
+
+fn rgb5_component(compressed: u16, shift: u16) -> u8 {
+    let component = compressed >> shift & 0x1F;
+    ((component * 255 + 15) / 31) as u8
+}
+
+/// Decodes the bitmap data in DefineBitsLossless tag into RGBA.
+/// DefineBitsLossless is Zlib encoded pixel data (similar to PNG), possibly
+/// palletized.
+///
+/// DefineBitsLossless2 colors are already premultiplied, but some encoders
+/// write colors brighter than their alpha, which would glow when blended.
+/// These are clamped to the alpha.
+pub fn decode_define_bits_lossless(swf_tag: &swf::DefineBitsLossless) -> Result<Bitmap, Error> {
+    // Decompress the image data (DEFLATE compression).
+    let mut decoded_data = decompress_zlib(swf_tag.data)?;
+
+    // Swizzle/de-palettize the bitmap.
+    let out_data = match (swf_tag.version, swf_tag.format) {
+        (1, swf::BitmapFormat::Rgb15) => {
+            let padded_width = (swf_tag.width + 0b1) & !0b1;
+            let mut out_data: Vec<u8> =
+                Vec::with_capacity(swf_tag.width as usize * swf_tag.height as usize * 4);
+            let mut i = 0;
+            for _ in 0..swf_tag.height {
+                for _ in 0..swf_tag.width {
+                    let compressed = ((decoded_data[i] as u16) << 8) | decoded_data[i + 1] as u16;
+                    out_data.push(rgb5_component(compressed, 10));
+                    out_data.push(rgb5_component(compressed, 5));
+                    out_data.push(rgb5_component(compressed, 0));
+                    out_data.push(0xff);
+                    i += 2;
+                }
+                i += (padded_width - swf_tag.width) as usize * 2;
+            }
+            out_data
+        }
+        (1, swf::BitmapFormat::Rgb32) => {
+            let mut i = 0;
+            while i < decoded_data.len() {
+                decoded_data[i] = decoded_data[i + 1];
+                decoded_data[i + 1] = decoded_data[i + 2];
+                decoded_data[i + 2] = decoded_data[i + 3];
+                decoded_data[i + 3] = 0xff;
+                i += 4;
+            }
+            decoded_data
+        }
+        (2, swf::BitmapFormat::Rgb32) => {
+            let mut i = 0;
+            while i < decoded_data.len() {
+                let alpha = decoded_data[i];
+                decoded_data[i] = decoded_data[i + 1].min(alpha);
+                decoded_data[i + 1] = decoded_data[i + 2].min(alpha);
+                decoded_data[i + 2] = decoded_data[i + 3].min(alpha);
+                decoded_data[i + 3] = alpha;
+                i += 4;
+            }
+            decoded_data
+        }
+        (1, swf::BitmapFormat::ColorMap8) => {
+            let mut i = 0;
+            let padded_width = (swf_tag.width + 0b11) & !0b11;
+
+            let mut palette = Vec::with_capacity(swf_tag.num_colors as usize + 1);
+            for _ in 0..=swf_tag.num_colors {
+                palette.push(Color {
+                    r: decoded_data[i],
+                    g: decoded_data[i + 1],
+                    b: decoded_data[i + 2],
+                    a: 255,
+                });
+                i += 3;
+            }
+            let mut out_data = vec![];
+            for _ in 0..swf_tag.height {
+                for _ in 0..swf_tag.width {
+                    let entry = decoded_data[i] as usize;
+                    if entry < palette.len() {
+                        let color = &palette[entry];
+                        out_data.push(color.r);
+                        out_data.push(color.g);
+                        out_data.push(color.b);
+                        out_data.push(color.a);
+                    } else {
+                        out_data.push(0);
+                        out_data.push(0);
+                        out_data.push(0);
+                        out_data.push(255);
+                    }
+                    i += 1;
+                }
+                i += (padded_width - swf_tag.width) as usize;
+            }
+            out_data
+        }
+        (2, swf::BitmapFormat::ColorMap8) => {
+            let mut i = 0;
+            let padded_width = (swf_tag.width + 0b11) & !0b11;
+
+            let mut palette = Vec::with_capacity(swf_tag.num_colors as usize + 1);
+            for _ in 0..=swf_tag.num_colors {
+                let a = decoded_data[i + 3];
+                palette.push(Color {
+                    r: decoded_data[i].min(a),
+                    g: decoded_data[i + 1].min(a),
+                    b: decoded_data[i + 2].min(a),
+                    a,
+                });
+                i += 4;
+            }
+            let mut out_data = vec![];
+            for _ in 0..swf_tag.height {
+                for _ in 0..swf_tag.width {
+                    let entry = decoded_data[i] as usize;
+                    if entry < palette.len() {
+                        let color = &palette[entry];
+                        out_data.push(color.r);
+                        out_data.push(color.g);
+                        out_data.push(color.b);
+                        out_data.push(color.a);
+                    } else {
+                        out_data.push(0);
+                        out_data.push(0);
+                        out_data.push(0);
+                        out_data.push(0);
+                    }
+                    i += 1;
+                }
+                i += (padded_width - swf_tag.width) as usize;
+            }
+            out_data
+        }
+        _ => {
+            return Err(format!(
+                "Unexpected DefineBitsLossless{} format: {:?} ",
+                swf_tag.version, swf_tag.format,
+            )
+            .into());
+        }
+    };
+
+    Ok(Bitmap {
+        width: swf_tag.width.into(),
+        height: swf_tag.height.into(),
+        data: BitmapFormat::Rgba(out_data),
+    })
+}
+
+/// Decodes PNG data from a DefineBitsJPEG2/3 tag, or a loaded image, into RGB
+/// or premultiplied RGBA.
+pub fn decode_png(data: &[u8]) -> Result<Bitmap, Error> {
+    use png::{ColorType, Transformations};
+
+    let mut decoder = png::Decoder::new(data);
+    // EXPAND expands palettized and low bit depth types to 8 bits per channel,
+    // and STRIP_16 reduces 16 bit depth ones.
+    decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+    let (info, mut reader) = decoder.read_info()?;
+
+    let mut data = vec![0; info.buffer_size()];
+    reader.next_frame(&mut data)?;
+
+    let data = match info.color_type {
+        ColorType::RGB => BitmapFormat::Rgb(data),
+        ColorType::RGBA => {
+            premultiply_alpha_rgba(&mut data);
+            BitmapFormat::Rgba(data)
+        }
+        ColorType::Grayscale => {
+            let mut rgb = Vec::with_capacity(data.len() * 3);
+            for l in data {
+                rgb.extend_from_slice(&[l, l, l]);
+            }
+            BitmapFormat::Rgb(rgb)
+        }
+        ColorType::GrayscaleAlpha => {
+            let mut rgba = Vec::with_capacity(data.len() * 2);
+            for pixel in data.chunks_exact(2) {
+                let l = premultiply(pixel[0], pixel[1]);
+                rgba.extend_from_slice(&[l, l, l, pixel[1]]);
+            }
+            BitmapFormat::Rgba(rgba)
+        }
+        ColorType::Indexed => return Err("Unexpanded palettized PNG".into()),
+    };
+
+    Ok(Bitmap {
+        width: info.width,
+        height: info.height,
+        data,
+    })
+}
+
+/// Decodes the first frame of GIF data from a DefineBitsJPEG2/3 tag, or a
+/// loaded image, into premultiplied RGBA.
+pub fn decode_gif(data: &[u8]) -> Result<Bitmap, Error> {
+    let mut decode_options = gif::DecodeOptions::new();
+    decode_options.set_color_output(gif::ColorOutput::RGBA);
+    let mut reader = decode_options.read_info(data)?;
+    let frame = reader.read_next_frame()?.ok_or("No frames in GIF")?;
+
+    // Transparent pixels keep the color of their palette entry.
+    let mut data = frame.buffer.to_vec();
+    premultiply_alpha_rgba(&mut data);
+
+    Ok(Bitmap {
+        width: frame.width.into(),
+        height: frame.height.into(),
+        data: BitmapFormat::Rgba(data),
+    })
+}
+
+/// Multiplies a color channel by an alpha value, rounding to the nearest
+/// value.
+fn premultiply(channel: u8, alpha: u8) -> u8 {
+    ((u16::from(channel) * u16::from(alpha) + 127) / 255) as u8
+}
+
+/// Converts standard RGBA to RGBA with premultiplied alpha, as images in SWFs
+/// are stored.
+pub fn premultiply_alpha_rgba(rgba: &mut [u8]) {
+    rgba.chunks_exact_mut(4).for_each(|rgba| {
+        let a = rgba[3];
+        rgba[0] = premultiply(rgba[0], a);
+        rgba[1] = premultiply(rgba[1], a);
+        rgba[2] = premultiply(rgba[2], a);
+    })
+}
+
+/// Images in SWFs are stored with premultiplied alpha.
+/// Converts RGBA premultiplied alpha to standard RBGA.
+pub fn unmultiply_alpha_rgba(rgba: &mut [u8]) {
+    rgba.chunks_exact_mut(4).for_each(|rgba| {
+        if rgba[3] > 0 {
+            let a = f32::from(rgba[3]) / 255.0;
+            rgba[0] = f32::min(f32::from(rgba[0]) / a, 255.0) as u8;
+            rgba[1] = f32::min(f32::from(rgba[1]) / a, 255.0) as u8;
+            rgba[2] = f32::min(f32::from(rgba[2]) / a, 255.0) as u8;
+        }
+    })
+}
+
+/// Decodes zlib-compressed data.
+fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut out_data = Vec::new();
+    let mut decoder = flate2::bufread::ZlibDecoder::new(data);
+    decoder.read_to_end(&mut out_data)?;
+    out_data.shrink_to_fit();
+    Ok(out_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a black grayscale PNG.
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&vec![0; (width * height) as usize])
+            .unwrap();
+        drop(writer);
+        data
+    }
+
+    #[test]
+    fn image_formats_are_sniffed_from_their_signatures() {
+        let format = determine_jpeg_tag_format;
+        assert_eq!(format(&[0xff, 0xd8, 0xff, 0xe0]), JpegTagFormat::Jpeg);
+        assert_eq!(format(&[0xff, 0xd9, 0xff, 0xd8, 0xff]), JpegTagFormat::Jpeg);
+        assert_eq!(format(&png(1, 1)), JpegTagFormat::Png);
+        assert_eq!(format(b"GIF89a\x01\x00"), JpegTagFormat::Gif);
+        assert_eq!(format(b"GIF87a\x01\x00"), JpegTagFormat::Gif);
+
+        // SWFs, truncated signatures and other images aren't bitmaps.
+        assert_eq!(format(b"FWS\x0a"), JpegTagFormat::Unknown);
+        assert_eq!(format(b"CWS\x0a"), JpegTagFormat::Unknown);
+        assert_eq!(format(&[0xff]), JpegTagFormat::Unknown);
+        assert_eq!(format(b"GIF8"), JpegTagFormat::Unknown);
+        assert_eq!(format(b"BM"), JpegTagFormat::Unknown);
+        assert_eq!(format(&[]), JpegTagFormat::Unknown);
+    }
+
+    #[test]
+    fn image_dimensions_are_read_from_the_header() {
+        assert_eq!(image_dimensions(&png(3, 2)).unwrap(), (3, 2));
+        assert_eq!(image_dimensions(&png(70000, 1)).unwrap(), (70000, 1));
+        assert!(image_dimensions(b"FWS\x0a").is_err());
+    }
+}
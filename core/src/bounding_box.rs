@@ -105,7 +105,7 @@ impl BoundingBox {
     pub fn set_x(&mut self, x: Twips) {
         let width = self.width();
         self.x_min = x;
-        self.x_max = x + width;
+        self.x_max = x.saturating_add(width);
 
         if self.y_max >= self.y_min {
             self.valid = true;
@@ -117,7 +117,7 @@ impl BoundingBox {
     pub fn set_y(&mut self, y: Twips) {
         let height = self.height();
         self.y_min = y;
-        self.y_max = y + height;
+        self.y_max = y.saturating_add(height);
 
         if self.x_max >= self.x_min {
             self.valid = true;
@@ -127,7 +127,7 @@ impl BoundingBox {
     /// Determine the width of the bounding box.
     pub fn width(&self) -> Twips {
         if self.valid {
-            self.x_max - self.x_min
+            self.x_max.saturating_sub(self.x_min)
         } else {
             Default::default()
         }
@@ -135,7 +135,7 @@ impl BoundingBox {
 
     /// Adjust the width of the bounding box.
     pub fn set_width(&mut self, width: Twips) {
-        self.x_max = self.x_min + width;
+        self.x_max = self.x_min.saturating_add(width);
 
         self.valid = self.x_max >= self.x_min && self.y_max >= self.y_min;
     }
@@ -143,7 +143,7 @@ impl BoundingBox {
     /// Determine the height of the bounding box.
     pub fn height(&self) -> Twips {
         if self.valid {
-            self.y_max - self.y_min
+            self.y_max.saturating_sub(self.y_min)
         } else {
             Default::default()
         }
@@ -151,7 +151,7 @@ impl BoundingBox {
 
     /// Adjust the height of the bounding box.
     pub fn set_height(&mut self, height: Twips) {
-        self.y_max = self.y_min + height;
+        self.y_max = self.y_min.saturating_add(height);
 
         self.valid = self.x_max >= self.x_min && self.y_max >= self.y_min;
     }
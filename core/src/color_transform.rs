@@ -62,6 +62,22 @@ impl ColorTransform {
         ]
     }
 
+    /// Applies this color transform to a color.
+    ///
+    /// Each channel is multiplied first, then offset by the additive term,
+    /// and only then clamped to `[0, 255]`. Clamping the intermediate product
+    /// instead would let a large multiplier saturate a channel that the
+    /// additive term is supposed to pull back down. All render backends must
+    /// follow this order so that transformed colors look the same everywhere.
+    pub fn transform_color(&self, color: &swf::Color) -> swf::Color {
+        swf::Color {
+            r: transform_channel(color.r, self.r_mult, self.r_add),
+            g: transform_channel(color.g, self.g_mult, self.g_add),
+            b: transform_channel(color.b, self.b_mult, self.b_add),
+            a: transform_channel(color.a, self.a_mult, self.a_add),
+        }
+    }
+
     /// Sets the multiplicate component of this color transform.
     pub fn set_mult_color(&mut self, color: &swf::Color) {
         self.r_mult = Fixed8::from_f32(f32::from(color.r) / 255.0);
@@ -71,6 +87,13 @@ impl ColorTransform {
     }
 }
 
+/// Applies the multiplicative and additive terms of a color transform to a
+/// single color channel, clamping the result.
+fn transform_channel(value: u8, mult: Fixed8, add: i16) -> u8 {
+    let value = (i32::from(value) * i32::from(mult.get())) >> 8;
+    (value + i32::from(add)).max(0).min(255) as u8
+}
+
 impl std::default::Default for ColorTransform {
     fn default() -> ColorTransform {
         ColorTransform {
@@ -89,30 +112,125 @@ impl std::default::Default for ColorTransform {
 impl std::ops::Mul for ColorTransform {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self {
+        let (r_mult, r_add) = concat_channel((self.r_mult, self.r_add), (rhs.r_mult, rhs.r_add));
+        let (g_mult, g_add) = concat_channel((self.g_mult, self.g_add), (rhs.g_mult, rhs.g_add));
+        let (b_mult, b_add) = concat_channel((self.b_mult, self.b_add), (rhs.b_mult, rhs.b_add));
+        let (a_mult, a_add) = concat_channel((self.a_mult, self.a_add), (rhs.a_mult, rhs.a_add));
         ColorTransform {
-            r_mult: self.r_mult.wrapping_mul(rhs.r_mult),
-            g_mult: self.g_mult.wrapping_mul(rhs.g_mult),
-            b_mult: self.b_mult.wrapping_mul(rhs.b_mult),
-            a_mult: self.a_mult.wrapping_mul(rhs.a_mult),
-
-            r_add: self
-                .r_add
-                .wrapping_add(self.r_mult.wrapping_mul_int(rhs.r_add)),
-            g_add: self
-                .g_add
-                .wrapping_add(self.g_mult.wrapping_mul_int(rhs.g_add)),
-            b_add: self
-                .b_add
-                .wrapping_add(self.b_mult.wrapping_mul_int(rhs.b_add)),
-            a_add: self
-                .a_add
-                .wrapping_add(self.a_mult.wrapping_mul_int(rhs.a_add)),
+            r_mult,
+            g_mult,
+            b_mult,
+            a_mult,
+            r_add,
+            g_add,
+            b_add,
+            a_add,
         }
     }
 }
 
+/// Concatenates the terms of a single channel of two color transforms, so
+/// that the result applies `rhs` and then `lhs`.
+///
+/// The results keep the 8.8 fixed-point precision of the multiplier, and
+/// terms that overflow are clamped rather than wrapping around, which would
+/// turn a bright channel dark.
+fn concat_channel(
+    (lhs_mult, lhs_add): (Fixed8, i16),
+    (rhs_mult, rhs_add): (Fixed8, i16),
+) -> (Fixed8, i16) {
+    let clamp = |value: i32| value.max(i16::MIN.into()).min(i16::MAX.into()) as i16;
+    let mult = (i32::from(lhs_mult.get()) * i32::from(rhs_mult.get())) >> 8;
+    let add = i32::from(lhs_add) + ((i32::from(lhs_mult.get()) * i32::from(rhs_add)) >> 8);
+    (Fixed8::from_bits(clamp(mult)), clamp(add))
+}
+
 impl std::ops::MulAssign for ColorTransform {
     fn mul_assign(&mut self, rhs: Self) {
         *self = *self * rhs;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::Color;
+
+    fn cxform(mult: [f32; 4], add: [i16; 4]) -> ColorTransform {
+        ColorTransform {
+            r_mult: Fixed8::from_f32(mult[0]),
+            g_mult: Fixed8::from_f32(mult[1]),
+            b_mult: Fixed8::from_f32(mult[2]),
+            a_mult: Fixed8::from_f32(mult[3]),
+            r_add: add[0],
+            g_add: add[1],
+            b_add: add[2],
+            a_add: add[3],
+        }
+    }
+
+    fn rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color { r, g, b, a }
+    }
+
+    #[test]
+    fn identity_leaves_color_untouched() {
+        let color = rgba(12, 34, 56, 78);
+        assert_eq!(ColorTransform::default().transform_color(&color), color);
+    }
+
+    #[test]
+    fn additive_overflow_clamps() {
+        let cxform = cxform([1.0; 4], [255, 1000, -1000, 255]);
+        assert_eq!(
+            cxform.transform_color(&rgba(128, 128, 128, 128)),
+            rgba(255, 255, 0, 255)
+        );
+    }
+
+    #[test]
+    fn clamps_after_adding() {
+        // Multiplying overshoots 255, but the additive term brings the
+        // channel back into range before it is clamped.
+        let cxform = cxform([2.0, 2.0, -1.0, 4.0], [-200, -300, 300, -900]);
+        assert_eq!(
+            cxform.transform_color(&rgba(200, 200, 100, 255)),
+            rgba(200, 100, 200, 120)
+        );
+    }
+
+    #[test]
+    fn concatenation_clamps_instead_of_wrapping() {
+        let parent = cxform([16.0, 1.0, 1.0, 1.0], [0, 30000, -30000, 0]);
+        let child = cxform([16.0, 0.5, 1.0, 1.0], [0, 10000, -10000, 10]);
+        let combined = parent * child;
+        assert_eq!(combined.r_mult, Fixed8::MAX);
+        assert_eq!(combined.g_mult, Fixed8::from_f32(0.5));
+        assert_eq!((combined.g_add, combined.b_add), (i16::MAX, i16::MIN));
+        assert_eq!(combined.a_add, 10);
+    }
+
+    #[test]
+    fn alpha_keeps_fixed8_precision() {
+        // Flash Player reads `_alpha = 33` back as 32.8125, as the multiplier
+        // is truncated to 84 / 256.
+        let cxform = ColorTransform {
+            a_mult: Fixed8::from_f64(33.0 / 100.0),
+            ..Default::default()
+        };
+        assert_eq!(cxform.a_mult.to_f64() * 100.0, 32.8125);
+
+        // Concatenating with the identity keeps that precision exactly.
+        assert_eq!((ColorTransform::default() * cxform).a_mult, cxform.a_mult);
+        assert_eq!((cxform * ColorTransform::default()).a_mult, cxform.a_mult);
+    }
+
+    #[test]
+    fn alpha_clamps_to_opaque_and_transparent() {
+        let opaque = cxform([1.0, 1.0, 1.0, 0.5], [0, 0, 0, 200]);
+        assert_eq!(opaque.transform_color(&rgba(0, 0, 0, 255)).a, 255);
+
+        let transparent = cxform([1.0, 1.0, 1.0, 0.5], [0, 0, 0, -200]);
+        assert_eq!(transparent.transform_color(&rgba(0, 0, 0, 255)).a, 0);
+    }
+}
@@ -1,6 +1,8 @@
+use chrono::{DateTime, TimeZone, Utc};
 use gc_arena::Collect;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// Controls whether the content is letterboxed or pillarboxed when the
 /// player's aspect ratio does not match the movie's aspect ratio.
@@ -30,3 +32,144 @@ impl Default for Letterbox {
         Letterbox::Fullscreen
     }
 }
+
+/// Controls what the root timeline does once it plays past its last frame.
+///
+/// This only affects the root movie; clips inside of it always loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LoopPolicy {
+    /// The root timeline wraps around to its first frame, as in Flash Player.
+    Loop,
+
+    /// The root timeline stops and holds on its last frame, as with the
+    /// `loop=false` embed parameter. The movie can still be restarted by
+    /// script or from the context menu.
+    Once,
+}
+
+impl Default for LoopPolicy {
+    fn default() -> Self {
+        LoopPolicy::Loop
+    }
+}
+
+/// Settings that make a movie play the same way on every run, for automated
+/// tests and for replaying recorded input.
+///
+/// `getTimer` and `new Date()` read a virtual clock that advances by exactly
+/// one frame's worth of time per frame, no matter how long the frame actually
+/// took, and `Math.random` and `random` are seeded with a fixed value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeterministicMode {
+    /// The seed of the random number generator.
+    pub random_seed: u64,
+
+    /// The date and time that the virtual clock starts at.
+    pub start_date: DateTime<Utc>,
+}
+
+impl Default for DeterministicMode {
+    fn default() -> Self {
+        Self {
+            random_seed: 0,
+            start_date: Utc.ymd(2001, 1, 1).and_hms(0, 0, 0),
+        }
+    }
+}
+
+/// Controls which requests movies may make to domains other than their own.
+///
+/// Every frontend defaults to `AllowAll`, so that movies keep loading what they
+/// could load before policy files were supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CrossDomainMode {
+    /// Movies may load data and movies from any domain.
+    ///
+    /// This lets archived movies load data from mirrors that don't serve the
+    /// policy files of the original servers.
+    #[cfg_attr(feature = "serde", serde(rename = "allowAll"))]
+    AllowAll,
+
+    /// Movies may only load data from another domain if its
+    /// `crossdomain.xml` policy file allows it, as in Flash Player. Movies
+    /// themselves can be loaded from any domain.
+    #[cfg_attr(feature = "serde", serde(rename = "respectPolicy"))]
+    RespectPolicy,
+
+    /// Movies may not load data or movies from any other domain.
+    #[cfg_attr(feature = "serde", serde(rename = "denyCross"))]
+    DenyCross,
+}
+
+impl Default for CrossDomainMode {
+    fn default() -> Self {
+        CrossDomainMode::AllowAll
+    }
+}
+
+impl FromStr for CrossDomainMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "allowall" => Ok(CrossDomainMode::AllowAll),
+            "respectpolicy" => Ok(CrossDomainMode::RespectPolicy),
+            "denycross" => Ok(CrossDomainMode::DenyCross),
+            _ => Err(format!("Unknown cross-domain mode: {}", s)),
+        }
+    }
+}
+
+/// The rendering quality of the stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StageQuality {
+    #[cfg_attr(feature = "serde", serde(rename = "low"))]
+    Low,
+
+    #[cfg_attr(feature = "serde", serde(rename = "medium"))]
+    Medium,
+
+    #[cfg_attr(feature = "serde", serde(rename = "high"))]
+    High,
+
+    #[cfg_attr(feature = "serde", serde(rename = "best"))]
+    Best,
+}
+
+impl StageQuality {
+    /// The name of this quality, as returned by ActionScript.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StageQuality::Low => "LOW",
+            StageQuality::Medium => "MEDIUM",
+            StageQuality::High => "HIGH",
+            StageQuality::Best => "BEST",
+        }
+    }
+}
+
+impl Default for StageQuality {
+    fn default() -> Self {
+        StageQuality::High
+    }
+}
+
+impl FromStr for StageQuality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(StageQuality::Low),
+            "medium" => Ok(StageQuality::Medium),
+            "high" => Ok(StageQuality::High),
+            "best" => Ok(StageQuality::Best),
+            _ => Err(format!("Unknown stage quality: {}", s)),
+        }
+    }
+}
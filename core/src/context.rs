@@ -5,26 +5,33 @@ use crate::avm1::{Avm1, Object as Avm1Object, Timers, Value as Avm1Value};
 use crate::avm2::{Avm2, Object as Avm2Object, Value as Avm2Value};
 use crate::backend::{
     audio::{AudioBackend, AudioManager, SoundHandle, SoundInstanceHandle},
-    locale::LocaleBackend,
+    locale::{LocalTimeZone, LocaleBackend},
     log::LogBackend,
-    navigator::NavigatorBackend,
+    navigator::{NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions},
     render::RenderBackend,
     storage::StorageBackend,
     ui::UiBackend,
     video::VideoBackend,
 };
 use crate::context_menu::ContextMenuState;
-use crate::display_object::{EditText, MovieClip, SoundTransform, Stage};
+use crate::debug_overlay::DebugOverlay;
+use crate::display_object::{EditText, SoundTransform, Stage, Video};
 use crate::external::ExternalInterface;
 use crate::focus_tracker::FocusTracker;
 use crate::library::Library;
-use crate::loader::LoadManager;
+use crate::loader::{Error as LoaderError, LoadManager};
+use crate::local_connection::LocalConnections;
 use crate::player::Player;
 use crate::prelude::*;
+use crate::security::Security;
+use crate::site_settings::SiteSettings;
+use crate::streams::StreamManager;
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::transform::TransformStack;
+use chrono::{DateTime, FixedOffset, Utc};
 use core::fmt;
 use gc_arena::{Collect, MutationContext};
+use indexmap::IndexMap;
 use instant::Instant;
 use rand::rngs::SmallRng;
 use std::collections::{HashMap, VecDeque};
@@ -116,6 +123,9 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// The system properties
     pub system: &'a mut SystemProperties,
 
+    /// The security state, used to check which network requests are allowed.
+    pub security: &'a mut Security,
+
     /// The current instance ID. Used to generate default `instanceN` names.
     pub instance_counter: &'a mut i32,
 
@@ -125,9 +135,18 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// Text fields with unbound variable bindings.
     pub unbound_text_fields: &'a mut Vec<EditText<'gc>>,
 
+    /// Videos with frames waiting to be decoded before the next render.
+    pub queued_videos: &'a mut Vec<Video<'gc>>,
+
     /// Timed callbacks created with `setInterval`/`setTimeout`.
     pub timers: &'a mut Timers<'gc>,
 
+    /// Streams that are being played with `NetStream`.
+    pub stream_manager: &'a mut StreamManager<'gc>,
+
+    /// The connection names that `LocalConnection`s are listening on.
+    pub local_connections: &'a mut LocalConnections<'gc>,
+
     pub current_context_menu: &'a mut Option<ContextMenuState<'gc>>,
 
     /// The AVM1 global state.
@@ -157,6 +176,12 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
 
     /// The current stage frame rate.
     pub frame_rate: &'a mut f64,
+
+    /// The settings of the site the root movie was loaded from.
+    pub site_settings: &'a SiteSettings,
+
+    /// The clock that movies read the time from in deterministic mode.
+    pub virtual_clock: &'a Option<VirtualClock>,
 }
 
 /// Convenience methods for controlling audio.
@@ -215,7 +240,7 @@ impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
     pub fn start_stream(
         &mut self,
         stream_handle: Option<SoundHandle>,
-        movie_clip: MovieClip<'gc>,
+        display_object: Option<DisplayObject<'gc>>,
         frame: u16,
         data: crate::tag_utils::SwfSlice,
         stream_info: &swf::SoundStreamHead,
@@ -223,18 +248,69 @@ impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
         self.audio_manager.start_stream(
             self.audio,
             stream_handle,
-            movie_clip,
+            display_object,
             frame,
             data,
             stream_info,
         )
     }
 
+    pub fn seek_stream(&mut self, instance: SoundInstanceHandle, frame: u16) -> bool {
+        self.audio_manager.seek_stream(self.audio, instance, frame)
+    }
+
     pub fn set_sound_transforms_dirty(&mut self) {
         self.audio_manager.set_sound_transforms_dirty()
     }
 }
 
+/// Convenience methods for network requests.
+impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
+    /// Fetch data on behalf of the movie that `requester` belongs to.
+    ///
+    /// Requests to other domains are subject to their cross-domain policy,
+    /// and every request is subject to the movie's sandbox.
+    pub fn fetch_data(
+        &mut self,
+        requester: DisplayObject<'gc>,
+        url: &str,
+        options: RequestOptions,
+    ) -> OwnedFuture<Vec<u8>, LoaderError> {
+        let movie = requester.movie();
+        self.security
+            .fetch(self.navigator, movie.as_deref(), url, options)
+    }
+
+    /// Fetch a movie on behalf of the movie that `requester` belongs to.
+    ///
+    /// The request is subject to the requesting movie's sandbox.
+    pub fn fetch_movie(
+        &mut self,
+        requester: DisplayObject<'gc>,
+        url: &str,
+        options: RequestOptions,
+    ) -> OwnedFuture<Vec<u8>, LoaderError> {
+        let movie = requester.movie();
+        self.security
+            .fetch_movie(self.navigator, movie.as_deref(), url, options)
+    }
+
+    /// Navigate to a URL on behalf of the movie that `requester` belongs to,
+    /// unless the movie's sandbox doesn't allow it.
+    pub fn navigate_to_url(
+        &mut self,
+        requester: DisplayObject<'gc>,
+        url: String,
+        window: Option<String>,
+        vars_method: Option<(NavigationMethod, IndexMap<String, String>)>,
+    ) {
+        let movie = requester.movie();
+        if self.security.allows_access(movie.as_deref(), &url) {
+            self.navigator.navigate_to_url(url, window, vars_method);
+        }
+    }
+}
+
 impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
     /// Transform a borrowed update context into an owned update context with
     /// a shorter internal lifetime.
@@ -271,10 +347,14 @@ impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
             player: self.player.clone(),
             load_manager: self.load_manager,
             system: self.system,
+            security: self.security,
             instance_counter: self.instance_counter,
             shared_objects: self.shared_objects,
             unbound_text_fields: self.unbound_text_fields,
+            queued_videos: self.queued_videos,
             timers: self.timers,
+            stream_manager: self.stream_manager,
+            local_connections: self.local_connections,
             current_context_menu: self.current_context_menu,
             avm1: self.avm1,
             avm2: self.avm2,
@@ -285,8 +365,71 @@ impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
             times_get_time_called: self.times_get_time_called,
             time_offset: self.time_offset,
             frame_rate: self.frame_rate,
+            site_settings: self.site_settings,
+            virtual_clock: self.virtual_clock,
+        }
+    }
+}
+
+impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
+    /// The time since the movie was launched, as returned by `getTimer`.
+    pub fn time_since_launch(&mut self) -> Duration {
+        match self.virtual_clock {
+            Some(clock) => clock.elapsed(),
+            None => self.navigator.time_since_launch(),
+        }
+    }
+
+    /// The current date and time, as returned by `new Date()`.
+    pub fn current_date_time(&self) -> DateTime<Utc> {
+        match self.virtual_clock {
+            Some(clock) => clock.now(),
+            None => self.locale.get_current_date_time(),
+        }
+    }
+
+    /// The time zone of the local time methods of `Date`. This is UTC in
+    /// deterministic mode, so that movies show the same dates everywhere.
+    pub fn timezone(&self) -> LocalTimeZone {
+        match self.virtual_clock {
+            Some(_) => LocalTimeZone::fixed(FixedOffset::east(0)),
+            None => self.locale.get_timezone(),
+        }
+    }
+}
+
+/// A clock that only advances as frames run, rather than with real time.
+///
+/// See `DeterministicMode`.
+#[derive(Debug, Clone, Copy)]
+pub struct VirtualClock {
+    start_date: DateTime<Utc>,
+    elapsed: Duration,
+}
+
+impl VirtualClock {
+    pub fn new(start_date: DateTime<Utc>) -> Self {
+        Self {
+            start_date,
+            elapsed: Duration::from_secs(0),
         }
     }
+
+    /// The time that passed since the clock was started.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The date and time that the clock reads.
+    pub fn now(&self) -> DateTime<Utc> {
+        let elapsed =
+            chrono::Duration::from_std(self.elapsed).unwrap_or_else(|_| chrono::Duration::zero());
+        self.start_date + elapsed
+    }
+
+    pub fn advance(&mut self, dt: Duration) {
+        self.elapsed += dt;
+    }
 }
 
 /// A queued ActionScript call.
@@ -301,6 +444,10 @@ pub struct QueuedActions<'gc> {
 
     /// Whether this is an unload action, which can still run if the clip is removed.
     pub is_unload: bool,
+
+    /// Whether this action has already been counted as deferred to a later
+    /// frame.
+    is_deferred: bool,
 }
 
 /// Action and gotos need to be queued up to execute at the end of the frame.
@@ -309,11 +456,27 @@ pub struct QueuedActions<'gc> {
 pub struct ActionQueue<'gc> {
     /// Each priority is kept in a separate bucket.
     action_queue: Vec<VecDeque<QueuedActions<'gc>>>,
+
+    /// The number of queued actions that can run in a single frame, or
+    /// `None` for no limit.
+    ///
+    /// Once the budget is spent, any further actions stay queued until the
+    /// next frame, so that content whose actions keep queueing more actions
+    /// can't hang the player.
+    max_actions_per_frame: Option<usize>,
+
+    /// The number of queued actions that have run since the frame started.
+    actions_this_frame: usize,
+
+    /// The number of actions that have been deferred to a later frame
+    /// because the budget of their frame was spent.
+    deferred_actions: usize,
 }
 
 impl<'gc> ActionQueue<'gc> {
     const DEFAULT_CAPACITY: usize = 32;
     const NUM_PRIORITIES: usize = 3;
+    const DEFAULT_MAX_ACTIONS_PER_FRAME: usize = 100_000;
 
     /// Crates a new `ActionQueue` with an empty queue.
     pub fn new() -> Self {
@@ -321,7 +484,61 @@ impl<'gc> ActionQueue<'gc> {
         for _ in 0..Self::NUM_PRIORITIES {
             action_queue.push(VecDeque::with_capacity(Self::DEFAULT_CAPACITY))
         }
-        Self { action_queue }
+        Self {
+            action_queue,
+            max_actions_per_frame: Some(Self::DEFAULT_MAX_ACTIONS_PER_FRAME),
+            actions_this_frame: 0,
+            deferred_actions: 0,
+        }
+    }
+
+    pub fn max_actions_per_frame(&self) -> Option<usize> {
+        self.max_actions_per_frame
+    }
+
+    pub fn set_max_actions_per_frame(&mut self, max_actions_per_frame: Option<usize>) {
+        self.max_actions_per_frame = max_actions_per_frame;
+    }
+
+    /// The number of actions that have been deferred to a later frame
+    /// because their frame's budget was spent.
+    pub fn deferred_actions(&self) -> usize {
+        self.deferred_actions
+    }
+
+    /// The number of actions waiting to run.
+    pub fn len(&self) -> usize {
+        self.action_queue.iter().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.action_queue.iter().all(VecDeque::is_empty)
+    }
+
+    /// Start a new frame, giving the queue a fresh budget.
+    pub fn start_frame(&mut self) {
+        self.actions_this_frame = 0;
+    }
+
+    /// Mark the actions that are still queued as deferred to a later frame.
+    ///
+    /// Actions that stay deferred for several frames are only counted once.
+    fn defer_queued_actions(&mut self) {
+        let mut newly_deferred = 0;
+        for action in self.action_queue.iter_mut().flatten() {
+            if !action.is_deferred {
+                action.is_deferred = true;
+                newly_deferred += 1;
+            }
+        }
+
+        if newly_deferred > 0 {
+            log::warn!(
+                "{} queued actions ran over the budget of their frame and were deferred",
+                newly_deferred
+            );
+            self.deferred_actions += newly_deferred;
+        }
     }
 
     /// Queues ActionScript to run for the given movie clip.
@@ -338,6 +555,7 @@ impl<'gc> ActionQueue<'gc> {
             clip,
             action_type,
             is_unload,
+            is_deferred: false,
         };
         debug_assert!(priority < Self::NUM_PRIORITIES);
         if let Some(queue) = self.action_queue.get_mut(priority) {
@@ -346,10 +564,21 @@ impl<'gc> ActionQueue<'gc> {
     }
 
     /// Sorts and drains the actions from the queue.
+    ///
+    /// Returns `None` once the frame's budget is spent, even if actions are
+    /// still queued.
     pub fn pop_action(&mut self) -> Option<QueuedActions<'gc>> {
+        if let Some(max_actions_per_frame) = self.max_actions_per_frame {
+            if self.actions_this_frame >= max_actions_per_frame {
+                self.defer_queued_actions();
+                return None;
+            }
+        }
+
         for queue in self.action_queue.iter_mut().rev() {
             let action = queue.pop_front();
             if action.is_some() {
+                self.actions_this_frame += 1;
                 return action;
             }
         }
@@ -381,12 +610,56 @@ pub struct RenderContext<'a, 'gc> {
     /// The current player's stage (including all loaded levels)
     pub stage: Stage<'gc>,
 
+    /// The focus tracker, which highlights objects focused with the keyboard.
+    pub focus_tracker: FocusTracker<'gc>,
+
     /// The stack of clip depths, used in masking.
     pub clip_depth_stack: Vec<Depth>,
 
     /// Whether to allow pushing a new mask. A masker-inside-a-masker does not work in Flash, instead
     /// causing the inner mask to be included as part of the outer mask. Maskee-inside-a-maskee works as one expects.
     pub allow_mask: bool,
+
+    /// The debug overlay that rendered display objects are recorded in, if it is enabled.
+    pub debug_overlay: Option<DebugOverlay>,
+
+    /// How far the player is into the next frame, from 0 to 1, if moving
+    /// objects are drawn interpolated between frames.
+    pub frame_interpolation: Option<f64>,
+
+    /// The part of the stage being redrawn, if the rest of the previous frame
+    /// is kept.
+    pub dirty_rect: Option<BoundingBox>,
+
+    /// Whether objects outside of the stage can be skipped. This is off when
+    /// objects are drawn somewhere other than where they are on the stage,
+    /// such as when they're printed.
+    pub cull: bool,
+
+    /// Whether anything drawn since this was last reset has content that
+    /// changes without marking cached bitmaps as dirty, such as script
+    /// drawings, video or objects moving between frames. Cached bitmaps that
+    /// show such content are redrawn every time.
+    pub drew_untracked_content: bool,
+}
+
+impl<'a, 'gc> RenderContext<'a, 'gc> {
+    /// Whether an object with the given world bounds can be skipped, as it
+    /// is off-screen or outside of the area being redrawn.
+    pub fn is_culled(&self, world_bounds: &BoundingBox) -> bool {
+        if !self.cull {
+            return false;
+        }
+
+        if !world_bounds.intersects(&self.stage.view_bounds()) {
+            return true;
+        }
+
+        match &self.dirty_rect {
+            Some(dirty_rect) => !world_bounds.intersects(dirty_rect),
+            None => false,
+        }
+    }
 }
 
 /// The type of action being run.
@@ -412,6 +685,14 @@ pub enum ActionType<'gc> {
         args: Vec<Avm1Value<'gc>>,
     },
 
+    /// A method whose name is only known at runtime, e.g. one called through
+    /// a `LocalConnection`.
+    DynamicMethod {
+        object: Avm1Object<'gc>,
+        name: String,
+        args: Vec<Avm1Value<'gc>>,
+    },
+
     /// A system listener method,
     NotifyListeners {
         listener: &'static str,
@@ -462,6 +743,12 @@ impl fmt::Debug for ActionType<'_> {
                 .field("name", name)
                 .field("args", args)
                 .finish(),
+            ActionType::DynamicMethod { object, name, args } => f
+                .debug_struct("ActionType::DynamicMethod")
+                .field("object", object)
+                .field("name", name)
+                .field("args", args)
+                .finish(),
             ActionType::NotifyListeners {
                 listener,
                 method,
@@ -485,3 +772,56 @@ impl fmt::Debug for ActionType<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+
+    #[test]
+    fn deferred_actions_are_counted_once() {
+        with_avm(8, |activation, _root| {
+            let clip = activation.context.stage.root_clip();
+            let movie = Arc::new(SwfMovie::empty(8));
+            let mut queue = ActionQueue::new();
+            queue.set_max_actions_per_frame(Some(1));
+            for _ in 0..3 {
+                queue.queue_actions(
+                    clip,
+                    ActionType::Normal {
+                        bytecode: SwfSlice::empty(movie.clone()),
+                    },
+                    false,
+                );
+            }
+
+            assert!(queue.pop_action().is_some());
+            assert!(queue.pop_action().is_none());
+            assert!(queue.pop_action().is_none());
+            assert_eq!(queue.deferred_actions(), 2);
+
+            // The action that is deferred again isn't counted twice.
+            queue.start_frame();
+            assert!(queue.pop_action().is_some());
+            assert!(queue.pop_action().is_none());
+            assert_eq!(queue.deferred_actions(), 2);
+
+            // Actions queued in between frames aren't deferred until they
+            // miss a frame's budget.
+            queue.start_frame();
+            queue.queue_actions(
+                clip,
+                ActionType::Normal {
+                    bytecode: SwfSlice::empty(movie),
+                },
+                false,
+            );
+            assert_eq!(queue.deferred_actions(), 2);
+            assert!(queue.pop_action().is_some());
+            assert!(queue.pop_action().is_none());
+            assert_eq!(queue.deferred_actions(), 3);
+
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,261 @@
+//! Debug overlay showing the bounds and depths of display objects.
+//!
+//! When enabled, every display object that is rendered gets an outline of its
+//! bounding box drawn on top of the stage, color keyed by the type of the
+//! object, and labeled with its depth. Masks are outlined with the extent of
+//! their mask and labeled with the range of depths that they mask.
+//!
+//! If any videos are rendered, the number of frames they dropped and the
+//! number they showed are drawn in the top left corner of the stage, as
+//! `dropped/shown`.
+
+use crate::backend::render::RenderBackend;
+use crate::display_object::VideoFrameStats;
+use crate::prelude::*;
+
+/// The size of a pixel of a label's digits, in pixels.
+const LABEL_SCALE: f32 = 2.0;
+
+/// The width and height of a label's digits, in font pixels.
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+
+/// A minimal bitmap font for depth labels. Each glyph is five rows of three
+/// pixels, with the leftmost pixel in the highest bit.
+const GLYPHS: [(char, [u8; GLYPH_HEIGHT]); 13] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+];
+
+/// The bounds and depths of the display objects rendered in a frame.
+///
+/// Display objects are recorded as they are rendered, and the overlay is
+/// drawn once the whole stage has been rendered.
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    items: Vec<OverlayItem>,
+
+    /// The frame statistics of the rendered videos, if there are any.
+    video_stats: Option<VideoFrameStats>,
+}
+
+#[derive(Debug)]
+struct OverlayItem {
+    /// The bounds of the object, in viewport space.
+    bounds: BoundingBox,
+
+    color: Color,
+
+    label: String,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record a display object that is about to be rendered.
+    ///
+    /// `matrix` is the transform from the object's space to viewport space.
+    pub fn add(&mut self, object: DisplayObject<'_>, matrix: &Matrix) {
+        if let Some(video) = object.as_video() {
+            *self.video_stats.get_or_insert_with(Default::default) += video.frame_stats();
+        }
+
+        let bounds = object.bounds_with_transform(matrix);
+        if !bounds.valid {
+            return;
+        }
+
+        let clip_depth = object.clip_depth();
+        let (color, label) = if clip_depth > 0 {
+            (
+                Color::from_rgb(0xff00ff, 255),
+                format!("{}:{}", object.depth(), clip_depth),
+            )
+        } else {
+            (object_color(object), object.depth().to_string())
+        };
+
+        self.items.push(OverlayItem {
+            bounds,
+            color,
+            label,
+        });
+    }
+
+    /// Draw the outlines and labels of every recorded display object.
+    pub fn draw(&self, renderer: &mut dyn RenderBackend) {
+        for item in &self.items {
+            let bounds = &item.bounds;
+            let width = (bounds.x_max - bounds.x_min).to_pixels() as f32;
+            let height = (bounds.y_max - bounds.y_min).to_pixels() as f32;
+            let x_max = bounds.x_max - Twips::from_pixels(1.0);
+            let y_max = bounds.y_max - Twips::from_pixels(1.0);
+
+            let color = &item.color;
+            draw_box(renderer, color, bounds.x_min, bounds.y_min, width, 1.0);
+            draw_box(renderer, color, bounds.x_min, y_max, width, 1.0);
+            draw_box(renderer, color, bounds.x_min, bounds.y_min, 1.0, height);
+            draw_box(renderer, color, x_max, bounds.y_min, 1.0, height);
+
+            draw_label(renderer, color, bounds.x_min, bounds.y_min, &item.label);
+        }
+
+        if let Some(stats) = self.video_stats {
+            let label = format!("{}/{}", stats.dropped, stats.presented);
+            let color = Color::from_rgb(0x00c0c0, 255);
+            draw_label(renderer, &color, Twips::zero(), Twips::zero(), &label);
+        }
+    }
+}
+
+/// The color that objects of each type are outlined with.
+fn object_color(object: DisplayObject<'_>) -> Color {
+    let rgb = match object {
+        DisplayObject::MovieClip(_) => 0xff0000,
+        DisplayObject::Button(_) => 0xffa000,
+        DisplayObject::EditText(_) | DisplayObject::Text(_) => 0x0080ff,
+        DisplayObject::Graphic(_) | DisplayObject::MorphShape(_) => 0x00c000,
+        DisplayObject::Bitmap(_) | DisplayObject::Video(_) => 0x00c0c0,
+        _ => 0x808080,
+    };
+
+    Color::from_rgb(rgb, 255)
+}
+
+/// Fill a rectangle in viewport space, sized in pixels.
+fn draw_box(
+    renderer: &mut dyn RenderBackend,
+    color: &Color,
+    x: Twips,
+    y: Twips,
+    width: f32,
+    height: f32,
+) {
+    renderer.draw_rect(color.clone(), &Matrix::create_box(width, height, 0.0, x, y));
+}
+
+/// Draw a label on a dark background, with its top left corner at `x`, `y`.
+fn draw_label(renderer: &mut dyn RenderBackend, color: &Color, x: Twips, y: Twips, label: &str) {
+    let num_chars = label.chars().count();
+    if num_chars == 0 {
+        return;
+    }
+
+    let background = Color::from_rgb(0x000000, 160);
+    let width = (num_chars * (GLYPH_WIDTH + 1) + 1) as f32 * LABEL_SCALE;
+    let height = (GLYPH_HEIGHT + 2) as f32 * LABEL_SCALE;
+    draw_box(renderer, &background, x, y, width, height);
+
+    let pixel = Twips::from_pixels(LABEL_SCALE.into());
+    for (index, c) in label.chars().enumerate() {
+        let rows = match GLYPHS.iter().find(|(glyph, _)| *glyph == c) {
+            Some((_, rows)) => rows,
+            None => continue,
+        };
+
+        let glyph_x = x + pixel * (index * (GLYPH_WIDTH + 1) + 1) as i32;
+        for (row_index, row) in rows.iter().enumerate() {
+            let dot_y = y + pixel * (row_index + 1) as i32;
+            for column in 0..GLYPH_WIDTH {
+                if row & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    let dot_x = glyph_x + pixel * column as i32;
+                    draw_box(renderer, color, dot_x, dot_y, LABEL_SCALE, LABEL_SCALE);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::display_object::EditText;
+
+    #[test]
+    fn objects_are_labeled_with_their_depths() {
+        with_avm(8, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let context = &mut activation.context;
+            let movie = context.swf.clone();
+            let text_field = EditText::from_swf_tag(
+                context,
+                movie,
+                swf::EditText {
+                    id: 1,
+                    bounds: swf::Rectangle {
+                        x_min: Twips::zero(),
+                        x_max: Twips::from_pixels(10.0),
+                        y_min: Twips::zero(),
+                        y_max: Twips::from_pixels(20.0),
+                    },
+                    font_id: None,
+                    font_class_name: None,
+                    height: None,
+                    color: None,
+                    max_length: None,
+                    layout: None,
+                    variable_name: "".into(),
+                    initial_text: None,
+                    is_word_wrap: false,
+                    is_multiline: false,
+                    is_password: false,
+                    is_read_only: true,
+                    is_auto_size: false,
+                    is_selectable: true,
+                    has_border: false,
+                    was_static: false,
+                    is_html: false,
+                    is_device_font: false,
+                },
+            );
+            let text_field: DisplayObject<'_> = text_field.into();
+            text_field.set_depth(context.gc_context, 3);
+
+            let mut overlay = DebugOverlay::new();
+            let matrix = Matrix {
+                tx: Twips::from_pixels(5.0),
+                ..Default::default()
+            };
+            overlay.add(text_field, &matrix);
+
+            // Masks are labeled with the depths they mask instead.
+            text_field.set_clip_depth(context.gc_context, 7);
+            overlay.add(text_field, &Matrix::identity());
+
+            let items: Vec<_> = overlay
+                .items
+                .iter()
+                .map(|item| (item.bounds.x_min, item.color.clone(), item.label.as_str()))
+                .collect();
+            assert_eq!(
+                items,
+                vec![
+                    (Twips::from_pixels(5.0), Color::from_rgb(0x0080ff, 255), "3"),
+                    (Twips::zero(), Color::from_rgb(0xff00ff, 255), "3:7"),
+                ]
+            );
+            assert!(overlay.video_stats.is_none());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn every_label_character_has_a_glyph() {
+        for c in "0123456789-:/".chars() {
+            assert!(GLYPHS.iter().any(|(glyph, _)| *glyph == c), "{}", c);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Tracking of the parts of the stage that change between frames.
+//!
+//! Before a frame is drawn, the display list is compared with what was drawn
+//! the last time. Only the area covered by objects that were added, removed,
+//! moved or otherwise changed has to be redrawn, which lets render backends
+//! that support it keep the rest of the previous frame. Mostly static content,
+//! such as menus and paused games, then costs very little to draw.
+
+use crate::color_transform::ColorTransform;
+use crate::config::StageQuality;
+use crate::display_object::{DisplayObject, Stage, TDisplayObject, TDisplayObjectContainer};
+use crate::focus_tracker::FocusTracker;
+use crate::prelude::*;
+use fnv::FnvHashMap;
+use swf::BlendMode;
+
+/// The extra space redrawn around changed objects to cover anti-aliasing, in
+/// twips.
+const DIRTY_RECT_PADDING: i32 = 20;
+
+/// The part of the stage that has to be redrawn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DirtyRegion {
+    /// Nothing changed since the last frame.
+    Clean,
+
+    /// Only the given area of the stage changed, in viewport twips.
+    Partial(BoundingBox),
+
+    /// The whole stage has to be redrawn.
+    Full,
+}
+
+/// How a display object was drawn in the last frame.
+#[derive(Clone, Debug, PartialEq)]
+struct DrawnState {
+    id: CharacterId,
+    parent: Option<u64>,
+    depth: Depth,
+    clip_depth: Depth,
+    masker: Option<u64>,
+
+    /// The bounds of the object's own content on the stage, without children.
+    bounds: BoundingBox,
+    matrix: Matrix,
+    color_transform: ColorTransform,
+    blend_mode: BlendMode,
+    opaque_background: Option<Color>,
+
+    /// Whether the object is drawn through its own or an ancestor's filters,
+    /// which can spread it beyond its bounds.
+    is_filtered: bool,
+
+    content_revision: Option<u64>,
+}
+
+/// A display object drawn in the last frame.
+#[derive(Debug)]
+struct DrawnObject {
+    state: DrawnState,
+
+    /// The object's filters, which are only copied when they change.
+    filters: Vec<swf::Filter>,
+
+    /// The update that the object was last seen in.
+    generation: u64,
+}
+
+/// The state of the stage itself, any change to which redraws everything.
+#[derive(Clone, Debug, PartialEq)]
+struct StageState {
+    background_color: Option<Color>,
+    quality: StageQuality,
+    view_bounds: BoundingBox,
+    view_matrix: Matrix,
+}
+
+/// Compares each frame with the last one to find what has to be redrawn.
+///
+/// Each update visits the visible display list once, as drawing it does, and
+/// only copies the state of the objects that changed.
+#[derive(Debug, Default)]
+pub struct DirtyRegionTracker {
+    /// The objects drawn in the last frame, keyed by their instance keys.
+    objects: FnvHashMap<u64, DrawnObject>,
+    generation: u64,
+    stage: Option<StageState>,
+    highlight: Option<BoundingBox>,
+}
+
+impl DirtyRegionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget what was last drawn, so that the next frame is drawn in full.
+    pub fn invalidate(&mut self) {
+        self.objects.clear();
+        self.stage = None;
+        self.highlight = None;
+    }
+
+    /// Find the part of the stage that changed since the last call, and
+    /// remember the current state of the stage for the next one.
+    pub fn update<'gc>(
+        &mut self,
+        stage: Stage<'gc>,
+        focus_tracker: FocusTracker<'gc>,
+    ) -> DirtyRegion {
+        let stage_state = StageState {
+            background_color: stage.background_color(),
+            quality: stage.quality(),
+            view_bounds: stage.view_bounds(),
+            view_matrix: *stage.matrix(),
+        };
+        let highlight = focus_tracker
+            .get()
+            .filter(|_| focus_tracker.is_highlighted())
+            .map(|focus| focus.world_bounds());
+
+        self.generation += 1;
+        let mut diff = Diff {
+            objects: &mut self.objects,
+            generation: self.generation,
+            dirty: BoundingBox::default(),
+            is_full: false,
+        };
+        diff.visit(
+            stage.into(),
+            &Matrix::default(),
+            &ColorTransform::default(),
+            false,
+        );
+
+        // Anything that wasn't visited has been removed or hidden.
+        let generation = diff.generation;
+        let (dirty, is_full) = (&mut diff.dirty, &mut diff.is_full);
+        diff.objects.retain(|_, object| {
+            if object.generation == generation {
+                return true;
+            }
+            *is_full |= object.state.is_filtered;
+            dirty.union(&object.state.bounds);
+            false
+        });
+        if highlight != self.highlight {
+            for bounds in self.highlight.iter().chain(highlight.iter()) {
+                diff.dirty.union(bounds);
+            }
+        }
+
+        let is_stage_changed = self.stage.as_ref() != Some(&stage_state);
+        self.stage = Some(stage_state);
+        self.highlight = highlight;
+
+        if is_stage_changed || diff.is_full {
+            DirtyRegion::Full
+        } else if diff.dirty.valid {
+            let padding = Twips::new(DIRTY_RECT_PADDING);
+            let mut dirty = diff.dirty;
+            dirty.x_min -= padding;
+            dirty.y_min -= padding;
+            dirty.x_max += padding;
+            dirty.y_max += padding;
+            DirtyRegion::Partial(dirty)
+        } else {
+            DirtyRegion::Clean
+        }
+    }
+}
+
+/// The comparison of the display list with the last frame.
+struct Diff<'a> {
+    /// The objects drawn in the last frame, which are updated as they are
+    /// visited.
+    objects: &'a mut FnvHashMap<u64, DrawnObject>,
+
+    /// The generation of the current update.
+    generation: u64,
+
+    /// The area covered by changes so far.
+    dirty: BoundingBox,
+
+    /// Whether a change was found that can't be bounded.
+    is_full: bool,
+}
+
+impl<'a> Diff<'a> {
+    fn visit<'gc>(
+        &mut self,
+        object: DisplayObject<'gc>,
+        parent_matrix: &Matrix,
+        parent_color_transform: &ColorTransform,
+        parent_is_filtered: bool,
+    ) {
+        if !object.visible() || object.removed() {
+            return;
+        }
+
+        let matrix = *parent_matrix * *object.matrix();
+        let color_transform = *parent_color_transform * *object.color_transform();
+        let filters = object.filters();
+        let is_filtered = parent_is_filtered || !filters.is_empty();
+        let state = DrawnState {
+            id: object.id(),
+            parent: object.parent().map(|parent| parent.instance_key()),
+            depth: object.depth(),
+            clip_depth: object.clip_depth(),
+            masker: object.masker().map(|masker| masker.instance_key()),
+            bounds: object.self_bounds().transform(&matrix),
+            matrix,
+            color_transform,
+            blend_mode: object.blend_mode(),
+            opaque_background: object.opaque_background(),
+            is_filtered,
+            content_revision: object.content_revision(),
+        };
+
+        let generation = self.generation;
+        match self.objects.get_mut(&object.instance_key()) {
+            Some(previous)
+                if previous.state == state
+                    && previous.filters[..] == filters[..]
+                    && state.content_revision.is_some() =>
+            {
+                previous.generation = generation;
+            }
+            Some(previous) => {
+                self.is_full |= previous.state.is_filtered || state.is_filtered;
+                self.dirty.union(&previous.state.bounds);
+                self.dirty.union(&state.bounds);
+                if previous.filters[..] != filters[..] {
+                    previous.filters = filters.to_vec();
+                }
+                previous.state = state;
+                previous.generation = generation;
+            }
+            None => {
+                self.is_full |= state.is_filtered;
+                self.dirty.union(&state.bounds);
+                self.objects.insert(
+                    object.instance_key(),
+                    DrawnObject {
+                        state,
+                        filters: filters.to_vec(),
+                        generation,
+                    },
+                );
+            }
+        }
+        drop(filters);
+
+        if let Some(container) = object.as_container() {
+            for child in container.iter_render_list() {
+                self.visit(child, &matrix, &color_transform, is_filtered);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::display_object::{Graphic, Lists};
+
+    /// A shape that is 10 pixels square.
+    fn square() -> swf::Shape {
+        let bounds = swf::Rectangle {
+            x_min: Twips::zero(),
+            x_max: Twips::from_pixels(10.0),
+            y_min: Twips::zero(),
+            y_max: Twips::from_pixels(10.0),
+        };
+        swf::Shape {
+            version: 1,
+            id: 1,
+            shape_bounds: bounds.clone(),
+            edge_bounds: bounds,
+            has_fill_winding_rule: false,
+            has_non_scaling_strokes: false,
+            has_scaling_strokes: false,
+            styles: swf::ShapeStyles {
+                fill_styles: Vec::new(),
+                line_styles: Vec::new(),
+            },
+            shape: Vec::new(),
+        }
+    }
+
+    /// The region redrawn for changes in the given area, in pixels.
+    fn partial(x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> DirtyRegion {
+        let padding = Twips::new(DIRTY_RECT_PADDING);
+        DirtyRegion::Partial(BoundingBox {
+            x_min: Twips::from_pixels(x_min) - padding,
+            y_min: Twips::from_pixels(y_min) - padding,
+            x_max: Twips::from_pixels(x_max) + padding,
+            y_max: Twips::from_pixels(y_max) + padding,
+            valid: true,
+        })
+    }
+
+    #[test]
+    fn redraws_changed_objects() {
+        with_avm(8, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let context = &mut activation.context;
+            let (stage, focus_tracker) = (context.stage, context.focus_tracker);
+            let mut tracker = DirtyRegionTracker::new();
+            assert_eq!(tracker.update(stage, focus_tracker), DirtyRegion::Full);
+            assert_eq!(tracker.update(stage, focus_tracker), DirtyRegion::Clean);
+
+            let movie = context.swf.clone();
+            let shape: DisplayObject<'_> = Graphic::from_swf_tag(context, square(), movie).into();
+            let mut root = stage.root_clip().as_container().unwrap();
+            root.replace_at_depth(context, shape, 1);
+            assert_eq!(
+                tracker.update(stage, focus_tracker),
+                partial(0.0, 0.0, 10.0, 10.0)
+            );
+
+            // Both where the shape was and where it is now are redrawn.
+            shape.set_x(context.gc_context, 50.0);
+            assert_eq!(
+                tracker.update(stage, focus_tracker),
+                partial(0.0, 0.0, 60.0, 10.0)
+            );
+            assert_eq!(tracker.update(stage, focus_tracker), DirtyRegion::Clean);
+
+            root.remove_child(context, shape, Lists::all());
+            assert_eq!(
+                tracker.update(stage, focus_tracker),
+                partial(50.0, 0.0, 60.0, 10.0)
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn copies_are_tracked_separately() {
+        with_avm(8, |activation, _root| -> Result<(), crate::avm1::Error> {
+            let context = &mut activation.context;
+            let (stage, focus_tracker) = (context.stage, context.focus_tracker);
+            let mut tracker = DirtyRegionTracker::new();
+
+            let movie = context.swf.clone();
+            let shape: DisplayObject<'_> = Graphic::from_swf_tag(context, square(), movie).into();
+            let copy = shape.instantiate(context.gc_context);
+            assert_ne!(shape.instance_key(), copy.instance_key());
+
+            let mut root = stage.root_clip().as_container().unwrap();
+            root.replace_at_depth(context, shape, 1);
+            tracker.update(stage, focus_tracker);
+
+            // A copy in the same place as the original is a new object, even
+            // though it is drawn the same way.
+            root.remove_child(context, shape, Lists::all());
+            root.replace_at_depth(context, copy, 1);
+            assert_eq!(
+                tracker.update(stage, focus_tracker),
+                partial(0.0, 0.0, 10.0, 10.0)
+            );
+            Ok(())
+        })
+    }
+}
@@ -4,8 +4,10 @@ use crate::avm1::{
 use crate::avm2::{
     Avm2, Event as Avm2Event, Object as Avm2Object, TObject as Avm2TObject, Value as Avm2Value,
 };
+use crate::backend::render::BitmapHandle;
 use crate::context::{RenderContext, UpdateContext};
 use crate::drawing::Drawing;
+use crate::matrix;
 use crate::player::NEWEST_PLAYER_VERSION;
 use crate::prelude::*;
 use crate::tag_utils::SwfMovie;
@@ -15,10 +17,11 @@ use crate::vminterface::{AvmType, Instantiator};
 use bitflags::bitflags;
 use gc_arena::{Collect, MutationContext};
 use ruffle_macros::enum_trait_object;
-use std::cell::{Ref, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use swf::Fixed8;
+use swf::{BlendMode, Fixed8};
 
 mod bitmap;
 mod button;
@@ -31,21 +34,21 @@ mod stage;
 mod text;
 mod video;
 
-use crate::avm1::activation::Activation;
+use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::backend::ui::MouseCursor;
 pub use crate::display_object::container::{
     DisplayObjectContainer, Lists, TDisplayObjectContainer,
 };
-use crate::events::{ClipEvent, ClipEventResult};
+use crate::events::{ClipEvent, ClipEventMask, ClipEventResult};
 pub use bitmap::Bitmap;
 pub use button::Button;
 pub use edit_text::{AutoSizeMode, EditText, TextSelection};
 pub use graphic::Graphic;
 pub use morph_shape::{MorphShape, MorphShapeStatic};
 pub use movie_clip::{MovieClip, Scene};
-pub use stage::{Stage, StageAlign, StageScaleMode};
+pub use stage::{Stage, StageAlign, StageOrientation, StageScaleMode};
 pub use text::Text;
-pub use video::Video;
+pub use video::{Video, VideoFrameStats};
 
 #[derive(Clone, Debug, Collect)]
 #[collect(no_drop)]
@@ -69,12 +72,6 @@ pub struct DisplayObjectBase<'gc> {
     scale_y: Percent,
     skew: f64,
 
-    /// The previous sibling of this display object in order of execution.
-    prev_sibling: Option<DisplayObject<'gc>>,
-
-    /// The next sibling of this display object in order of execution.
-    next_sibling: Option<DisplayObject<'gc>>,
-
     /// The sound transform of sounds playing via this display object.
     sound_transform: SoundTransform,
 
@@ -86,6 +83,55 @@ pub struct DisplayObjectBase<'gc> {
 
     /// Bit flags for various display object properites.
     flags: DisplayObjectFlags,
+
+    /// The surface filters applied to this display object when rendered.
+    #[collect(require_static)]
+    filters: Vec<swf::Filter>,
+
+    /// How this display object is blended with the content beneath it.
+    #[collect(require_static)]
+    blend_mode: BlendMode,
+
+    /// The color filling the bounds of this display object behind its
+    /// content, if any.
+    #[collect(require_static)]
+    opaque_background: Option<Color>,
+
+    /// The position of this display object before a script first moved it
+    /// during the current frame.
+    #[collect(require_static)]
+    moved_from: Option<(Twips, Twips)>,
+
+    /// The position that this display object is drawn moving away from
+    /// between frames, when frame interpolation is enabled.
+    #[collect(require_static)]
+    interpolate_from: Option<(Twips, Twips)>,
+
+    /// Whether this display object was placed, or had its whole matrix
+    /// replaced, during the current frame. It's then drawn where it ends up
+    /// instead of moving there.
+    skip_interpolation: bool,
+
+    /// The object this display object was last dragged over with `startDrag`.
+    drop_target: Option<DisplayObject<'gc>>,
+
+    /// The bitmap that this display object was last drawn into, if it is
+    /// cached as a bitmap.
+    #[collect(require_static)]
+    bitmap_cache: BitmapCacheSlot,
+
+    /// Whether the content or children of this display object have changed
+    /// since it was last drawn into its cached bitmap.
+    #[collect(require_static)]
+    bitmap_cache_dirty: Cell<bool>,
+
+    /// Identifies this display object for as long as the player runs.
+    #[collect(require_static)]
+    instance_key: InstanceKey,
+
+    /// Whether this display object is highlighted when focused with the
+    /// keyboard, or `None` to follow the stage's setting.
+    focus_rect: Option<bool>,
 }
 
 impl<'gc> Default for DisplayObjectBase<'gc> {
@@ -101,12 +147,21 @@ impl<'gc> Default for DisplayObjectBase<'gc> {
             scale_x: Percent::from_unit(1.0),
             scale_y: Percent::from_unit(1.0),
             skew: 0.0,
-            prev_sibling: None,
-            next_sibling: None,
             masker: None,
             maskee: None,
             sound_transform: Default::default(),
             flags: DisplayObjectFlags::VISIBLE,
+            filters: Vec::new(),
+            blend_mode: BlendMode::Normal,
+            opaque_background: None,
+            moved_from: None,
+            interpolate_from: None,
+            skip_interpolation: false,
+            drop_target: None,
+            bitmap_cache: Default::default(),
+            bitmap_cache_dirty: Cell::new(true),
+            instance_key: Default::default(),
+            focus_rect: None,
         }
     }
 }
@@ -137,6 +192,7 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_place_frame(&mut self, frame: u16) {
         self.place_frame = frame;
+        self.skip_interpolation = true;
     }
 
     fn transform(&self) -> &Transform {
@@ -152,8 +208,9 @@ impl<'gc> DisplayObjectBase<'gc> {
     }
 
     fn set_matrix(&mut self, matrix: &Matrix) {
-        self.transform.matrix = *matrix;
+        self.transform.matrix = matrix::round(matrix);
         self.flags -= DisplayObjectFlags::SCALE_ROTATION_CACHED;
+        self.skip_interpolation = true;
     }
 
     fn color_transform(&self) -> &ColorTransform {
@@ -174,7 +231,8 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_x(&mut self, value: f64) {
         self.set_transformed_by_script(true);
-        self.transform.matrix.tx = Twips::from_pixels(value)
+        self.record_move();
+        self.transform.matrix.tx = position_to_twips(value)
     }
 
     fn y(&self) -> f64 {
@@ -183,7 +241,61 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_y(&mut self, value: f64) {
         self.set_transformed_by_script(true);
-        self.transform.matrix.ty = Twips::from_pixels(value)
+        self.record_move();
+        self.transform.matrix.ty = position_to_twips(value)
+    }
+
+    /// Remember where this object was before a script moved it this frame.
+    fn record_move(&mut self) {
+        if self.moved_from.is_none() {
+            self.moved_from = Some((self.transform.matrix.tx, self.transform.matrix.ty));
+        }
+    }
+
+    fn interpolate_from(&self) -> Option<(Twips, Twips)> {
+        self.interpolate_from
+    }
+
+    fn start_interpolation(&mut self) -> bool {
+        let (tx, ty) = (self.transform.matrix.tx, self.transform.matrix.ty);
+        let moved_from = self.moved_from.take();
+        let skip_interpolation = std::mem::take(&mut self.skip_interpolation);
+        self.interpolate_from = moved_from.filter(|&(from_x, from_y)| {
+            // Objects that jump further than this are drawn where they land,
+            // rather than smeared across the jump.
+            let distance =
+                (tx.to_pixels() - from_x.to_pixels()).hypot(ty.to_pixels() - from_y.to_pixels());
+            !skip_interpolation && distance > 0.0 && distance <= MAX_INTERPOLATED_DISTANCE
+        });
+        self.interpolate_from.is_some()
+    }
+
+    fn drop_target(&self) -> Option<DisplayObject<'gc>> {
+        self.drop_target
+    }
+
+    fn take_bitmap_cache(&self) -> Option<BitmapCache> {
+        self.bitmap_cache.0.borrow_mut().take()
+    }
+
+    fn set_bitmap_cache(&self, cache: Option<BitmapCache>) {
+        *self.bitmap_cache.0.borrow_mut() = cache;
+    }
+
+    fn is_bitmap_cache_dirty(&self) -> bool {
+        self.bitmap_cache_dirty.get()
+    }
+
+    fn set_bitmap_cache_dirty(&self, dirty: bool) {
+        self.bitmap_cache_dirty.set(dirty);
+    }
+
+    fn instance_key(&self) -> u64 {
+        self.instance_key.0
+    }
+
+    fn set_drop_target(&mut self, drop_target: Option<DisplayObject<'gc>>) {
+        self.drop_target = drop_target;
     }
 
     /// Caches the scale and rotation factors for this display object, if necessary.
@@ -228,17 +340,16 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_scale(&mut self, scale_x: f32, scale_y: f32, rotation: f32) {
         self.cache_scale_rotation();
-        let mut matrix = &mut self.transform.matrix;
         let rotation = rotation.to_radians();
-        let cos_x = f32::cos(rotation);
-        let sin_x = f32::sin(rotation);
         self.scale_x = Percent::from_unit(scale_x.into());
         self.scale_y = Percent::from_unit(scale_y.into());
         self.rotation = Degrees::from_radians(rotation.into());
-        matrix.a = (scale_x * cos_x) as f32;
-        matrix.b = (scale_x * sin_x) as f32;
-        matrix.c = (scale_y * -sin_x) as f32;
-        matrix.d = (scale_y * cos_x) as f32;
+        let (a, b, c, d) = matrix::compose(scale_x.into(), scale_y.into(), rotation.into(), 0.0);
+        let mut matrix = &mut self.transform.matrix;
+        matrix.a = a;
+        matrix.b = b;
+        matrix.c = c;
+        matrix.d = d;
     }
 
     fn rotation(&mut self) -> Degrees {
@@ -250,15 +361,17 @@ impl<'gc> DisplayObjectBase<'gc> {
         self.set_transformed_by_script(true);
         self.cache_scale_rotation();
         self.rotation = degrees;
-        let cos_x = f64::cos(degrees.into_radians());
-        let sin_x = f64::sin(degrees.into_radians());
-        let cos_y = f64::cos(degrees.into_radians() + self.skew);
-        let sin_y = f64::sin(degrees.into_radians() + self.skew);
+        let (a, b, c, d) = matrix::compose(
+            self.scale_x.into_unit(),
+            self.scale_y.into_unit(),
+            degrees.into_radians(),
+            self.skew,
+        );
         let mut matrix = &mut self.transform.matrix;
-        matrix.a = (self.scale_x.into_unit() * cos_x) as f32;
-        matrix.b = (self.scale_x.into_unit() * sin_x) as f32;
-        matrix.c = (self.scale_y.into_unit() * -sin_y) as f32;
-        matrix.d = (self.scale_y.into_unit() * cos_y) as f32;
+        matrix.a = a;
+        matrix.b = b;
+        matrix.c = c;
+        matrix.d = d;
     }
 
     fn scale_x(&mut self) -> Percent {
@@ -270,11 +383,15 @@ impl<'gc> DisplayObjectBase<'gc> {
         self.set_transformed_by_script(true);
         self.cache_scale_rotation();
         self.scale_x = value;
-        let cos = f64::cos(self.rotation.into_radians());
-        let sin = f64::sin(self.rotation.into_radians());
+        let (a, b, _, _) = matrix::compose(
+            value.into_unit(),
+            self.scale_y.into_unit(),
+            self.rotation.into_radians(),
+            self.skew,
+        );
         let mut matrix = &mut self.transform.matrix;
-        matrix.a = (cos * value.into_unit()) as f32;
-        matrix.b = (sin * value.into_unit()) as f32;
+        matrix.a = a;
+        matrix.b = b;
     }
 
     fn scale_y(&mut self) -> Percent {
@@ -286,11 +403,15 @@ impl<'gc> DisplayObjectBase<'gc> {
         self.set_transformed_by_script(true);
         self.cache_scale_rotation();
         self.scale_y = value;
-        let cos = f64::cos(self.rotation.into_radians() + self.skew);
-        let sin = f64::sin(self.rotation.into_radians() + self.skew);
+        let (_, _, c, d) = matrix::compose(
+            self.scale_x.into_unit(),
+            value.into_unit(),
+            self.rotation.into_radians(),
+            self.skew,
+        );
         let mut matrix = &mut self.transform.matrix;
-        matrix.c = (-sin * value.into_unit()) as f32;
-        matrix.d = (cos * value.into_unit()) as f32;
+        matrix.c = c;
+        matrix.d = d;
     }
 
     fn name(&self) -> &str {
@@ -326,22 +447,6 @@ impl<'gc> DisplayObjectBase<'gc> {
         self.parent = parent;
     }
 
-    fn prev_sibling(&self) -> Option<DisplayObject<'gc>> {
-        self.prev_sibling
-    }
-
-    fn set_prev_sibling(&mut self, node: Option<DisplayObject<'gc>>) {
-        self.prev_sibling = node;
-    }
-
-    fn next_sibling(&self) -> Option<DisplayObject<'gc>> {
-        self.next_sibling
-    }
-
-    fn set_next_sibling(&mut self, node: Option<DisplayObject<'gc>>) {
-        self.next_sibling = node;
-    }
-
     fn removed(&self) -> bool {
         self.flags.contains(DisplayObjectFlags::REMOVED)
     }
@@ -366,6 +471,50 @@ impl<'gc> DisplayObjectBase<'gc> {
         self.flags.set(DisplayObjectFlags::VISIBLE, value);
     }
 
+    fn filters(&self) -> &[swf::Filter] {
+        &self.filters
+    }
+
+    fn set_filters(&mut self, filters: Vec<swf::Filter>) {
+        self.filters = filters;
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, value: BlendMode) {
+        self.blend_mode = value;
+    }
+
+    fn is_bitmap_cached(&self) -> bool {
+        self.flags.contains(DisplayObjectFlags::CACHE_AS_BITMAP) || !self.filters.is_empty()
+    }
+
+    fn cache_as_bitmap_flag(&self) -> bool {
+        self.flags.contains(DisplayObjectFlags::CACHE_AS_BITMAP)
+    }
+
+    fn set_bitmap_cached(&mut self, value: bool) {
+        self.flags.set(DisplayObjectFlags::CACHE_AS_BITMAP, value);
+    }
+
+    fn opaque_background(&self) -> Option<Color> {
+        self.opaque_background.clone()
+    }
+
+    fn set_opaque_background(&mut self, value: Option<Color>) {
+        self.opaque_background = value;
+    }
+
+    fn focus_rect(&self) -> Option<bool> {
+        self.focus_rect
+    }
+
+    fn set_focus_rect(&mut self, value: Option<bool>) {
+        self.focus_rect = value;
+    }
+
     fn lock_root(&self) -> bool {
         self.flags.contains(DisplayObjectFlags::LOCK_ROOT)
     }
@@ -427,17 +576,95 @@ impl<'gc> DisplayObjectBase<'gc> {
     }
 }
 
+/// Convert a position set by a script into twips, like Flash does.
+///
+/// Positions that are out of the range of twips end up at `i32::MIN` twips,
+/// and NaN ends up at 0, in the same way as positions transformed by a matrix.
+fn position_to_twips(pixels: f64) -> Twips {
+    let twips = pixels * Twips::TWIPS_PER_PIXEL;
+    if twips.is_nan() {
+        Twips::zero()
+    } else if twips >= f64::from(i32::MIN) && twips < -f64::from(i32::MIN) {
+        Twips::new(twips as i32)
+    } else {
+        Twips::new(i32::MIN)
+    }
+}
+
+/// Make an object and its ancestors work out which clip events they are
+/// interested in again, after a change that could affect it, such as adding a
+/// child or an event handler.
+pub fn invalidate_clip_event_interest<'gc>(
+    object: DisplayObject<'gc>,
+    gc_context: MutationContext<'gc, '_>,
+) {
+    let mut node = Some(object);
+    while let Some(object) = node {
+        if let Some(clip) = object.as_movie_clip() {
+            // The ancestors of an invalidated clip have already been
+            // invalidated themselves.
+            if !clip.invalidate_clip_event_interest(gc_context) {
+                break;
+            }
+        }
+        node = object.parent();
+    }
+}
+
+/// Give the AVM1 object of a new instance of an exported symbol the class
+/// registered for the symbol with `Object.registerClass`, and run the class's
+/// constructor on it straight away, as `attachMovie` does.
+///
+/// A constructor without a `prototype` leaves the object as it is.
+pub fn construct_registered_avm1_class<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    display_object: DisplayObject<'gc>,
+    object: Avm1Object<'gc>,
+    constructor: Avm1Object<'gc>,
+) {
+    let version = context.swf.version();
+    let globals = context.avm1.global_object_cell();
+    let mut activation = Activation::from_nothing(
+        context.reborrow(),
+        ActivationIdentifier::root("[Construct]"),
+        version,
+        globals,
+        display_object,
+    );
+    if let Ok(prototype) = constructor.get("prototype", &mut activation) {
+        object.set_proto(activation.context.gc_context, prototype);
+        let _ = constructor.construct_on_existing(&mut activation, object, &[]);
+    }
+}
+
 pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_, 'gc>) {
     if this.maskee().is_some() {
         return;
     }
-    context.transform_stack.push(&*this.transform());
+    if this.content_revision().is_none() {
+        context.drew_untracked_content = true;
+    }
+    match (context.frame_interpolation, this.interpolate_from()) {
+        (Some(progress), Some((from_x, from_y))) => {
+            // Interpolated positions change without invalidating anything.
+            context.drew_untracked_content = true;
+            let mut transform = this.transform().clone();
+            let lerp = |from: Twips, to: Twips| {
+                let from = f64::from(from.get());
+                Twips::new((from + (f64::from(to.get()) - from) * progress).round() as i32)
+            };
+            transform.matrix.tx = lerp(from_x, transform.matrix.tx);
+            transform.matrix.ty = lerp(from_y, transform.matrix.ty);
+            context.transform_stack.push(&transform);
+        }
+        _ => context.transform_stack.push(&*this.transform()),
+    }
 
     let mask = this.masker();
     let mut mask_transform = crate::transform::Transform::default();
     if let Some(m) = mask {
-        mask_transform.matrix = this.global_to_local_matrix();
-        mask_transform.matrix *= m.local_to_global_matrix();
+        mask_transform.matrix =
+            matrix::multiply(&this.global_to_local_matrix(), &m.local_to_global_matrix());
         context.renderer.push_mask();
         context.allow_mask = false;
         context.transform_stack.push(&mask_transform);
@@ -446,7 +673,28 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         context.allow_mask = true;
         context.renderer.activate_mask();
     }
-    this.render_self(context);
+    let blend_mode = this.blend_mode();
+    if blend_mode != BlendMode::Normal {
+        context.renderer.push_blend_mode(blend_mode);
+    }
+    if let Some(color) = this.opaque_background() {
+        // The background covers the bounds of the object and its children.
+        let bounds = this.bounds();
+        let rect = Matrix::create_box(
+            bounds.width().to_pixels() as f32,
+            bounds.height().to_pixels() as f32,
+            0.0,
+            bounds.x_min,
+            bounds.y_min,
+        );
+        context
+            .renderer
+            .draw_rect(color, &(context.transform_stack.transform().matrix * rect));
+    }
+    render_filtered(this, context);
+    if blend_mode != BlendMode::Normal {
+        context.renderer.pop_blend_mode();
+    }
     if let Some(m) = mask {
         context.renderer.deactivate_mask();
         context.allow_mask = false;
@@ -460,6 +708,232 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
     context.transform_stack.pop();
 }
 
+/// Renders a display object through its surface filters, if it has any and
+/// the render backend is able to apply them. Otherwise, the object is drawn
+/// unfiltered.
+fn render_filtered<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_, 'gc>) {
+    let filters = this.filters().to_vec();
+    if filters.is_empty() || !context.renderer.push_filter_layer() {
+        render_cached(this, context);
+        return;
+    }
+
+    render_cached(this, context);
+
+    // Filter sizes are specified in stage pixels, but scale with the object.
+    let matrix = context.transform_stack.transform().matrix;
+    let mut bounds = this.bounds_with_transform(&matrix);
+    let (pad_x, pad_y) = crate::filters::filter_padding(&filters);
+    let (scale_x, scale_y) = (
+        f64::from(matrix.a).hypot(f64::from(matrix.b)),
+        f64::from(matrix.c).hypot(f64::from(matrix.d)),
+    );
+    let pad_x = Twips::new((f64::from(pad_x.get()) * scale_x).ceil() as i32);
+    let pad_y = Twips::new((f64::from(pad_y.get()) * scale_y).ceil() as i32);
+    bounds.x_min -= pad_x;
+    bounds.x_max += pad_x;
+    bounds.y_min -= pad_y;
+    bounds.y_max += pad_y;
+
+    context.renderer.pop_filter_layer(&filters, &bounds);
+}
+
+/// The furthest that a display object moved by scripts is drawn moving between
+/// frames, in pixels. Objects that move further have been teleported.
+const MAX_INTERPOLATED_DISTANCE: f64 = 200.0;
+
+/// The largest width or height of the bitmap that a display object is cached
+/// as, in pixels. Larger objects are drawn directly, as in Flash Player 8.
+const MAX_BITMAP_CACHE_SIZE: f64 = 2880.0;
+
+/// The bitmap that a display object cached as a bitmap was last drawn into.
+#[derive(Debug)]
+pub struct BitmapCache {
+    handle: BitmapHandle,
+    width: u32,
+    height: u32,
+
+    /// The matrix that the object was drawn into the bitmap with.
+    matrix: Matrix,
+
+    /// Whether the bitmap shows content that changes without marking it as
+    /// dirty, such as script drawings or video, and so has to be redrawn
+    /// every time.
+    untracked: bool,
+}
+
+/// Holds a display object's `BitmapCache`. Copies of a display object start
+/// out uncached, as each of them needs a bitmap of its own.
+#[derive(Debug, Default)]
+struct BitmapCacheSlot(RefCell<Option<BitmapCache>>);
+
+impl Clone for BitmapCacheSlot {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// A number that identifies a display object. Unlike the object's address,
+/// it is never reused once the object has been collected. Copies of a display
+/// object get keys of their own.
+#[derive(Debug)]
+struct InstanceKey(u64);
+
+impl Default for InstanceKey {
+    fn default() -> Self {
+        static NEXT_KEY: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_KEY.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Clone for InstanceKey {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// Marks the bitmaps that `object` and its ancestors are cached as for
+/// redrawing, as the content or children of `object` have changed.
+pub fn invalidate_cached_bitmaps<'gc>(object: DisplayObject<'gc>) {
+    object.set_bitmap_cache_dirty(true);
+    invalidate_parent_cached_bitmaps(object);
+}
+
+/// Marks the bitmaps that the ancestors of `object` are cached as for
+/// redrawing, as the way `object` is drawn into them has changed.
+///
+/// The bitmap of `object` itself is kept, as it doesn't include the object's
+/// own transform, filters or blend mode.
+pub fn invalidate_parent_cached_bitmaps<'gc>(object: DisplayObject<'gc>) {
+    // Masks are drawn along with the objects they mask.
+    let maskee_parent = object.maskee().and_then(|maskee| maskee.parent());
+    for parent in [object.parent(), maskee_parent].iter().flatten() {
+        let mut node = Some(*parent);
+        while let Some(object) = node {
+            object.set_bitmap_cache_dirty(true);
+            node = object.parent();
+        }
+    }
+}
+
+/// Renders a display object through the bitmap that it is cached as, if it is
+/// cached as a bitmap and the render backend can draw into bitmaps.
+///
+/// The bitmap is redrawn whenever the object is marked as dirty, and is
+/// otherwise reused. It is aligned to the pixels of the stage, so that
+/// moving the object by whole pixels doesn't redraw it.
+fn render_cached<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_, 'gc>) {
+    let cache = this.take_bitmap_cache();
+    if !this.is_bitmap_cached() || !context.renderer.supports_offscreen_targets() {
+        if let Some(cache) = cache {
+            context.renderer.unregister_bitmap(cache.handle);
+        }
+        this.render_self(context);
+        return;
+    }
+
+    // Masks only need their shapes, not a bitmap.
+    if !context.allow_mask {
+        this.set_bitmap_cache(cache);
+        this.render_self(context);
+        return;
+    }
+
+    let transform = context.transform_stack.transform().clone();
+    let bounds = this.bounds_with_transform(&transform.matrix);
+    let x = bounds.x_min.to_pixels().floor();
+    let y = bounds.y_min.to_pixels().floor();
+    let width = bounds.x_max.to_pixels().ceil() - x;
+    let height = bounds.y_max.to_pixels().ceil() - y;
+    if !bounds.valid || width < 1.0 || height < 1.0 {
+        this.set_bitmap_cache(cache);
+        return;
+    }
+    if width > MAX_BITMAP_CACHE_SIZE || height > MAX_BITMAP_CACHE_SIZE {
+        if let Some(cache) = cache {
+            context.renderer.unregister_bitmap(cache.handle);
+        }
+        this.render_self(context);
+        return;
+    }
+
+    let (width, height) = (width as u32, height as u32);
+    let (x, y) = (Twips::from_pixels(x), Twips::from_pixels(y));
+    let offscreen_transform = Transform {
+        matrix: Matrix {
+            tx: transform.matrix.tx - x,
+            ty: transform.matrix.ty - y,
+            ..transform.matrix
+        },
+        color_transform: Default::default(),
+    };
+
+    let mut cache = match cache {
+        Some(cache) if cache.width == width && cache.height == height => cache,
+        cache => {
+            let rgba = vec![0; width as usize * height as usize * 4];
+            let handle = match cache {
+                Some(cache) => context
+                    .renderer
+                    .update_texture(cache.handle, width, height, rgba),
+                None => context.renderer.register_bitmap_raw(width, height, rgba),
+            };
+            match handle {
+                Ok(handle) => {
+                    this.set_bitmap_cache_dirty(true);
+                    BitmapCache {
+                        handle,
+                        width,
+                        height,
+                        matrix: offscreen_transform.matrix,
+                        untracked: false,
+                    }
+                }
+                Err(e) => {
+                    log::error!("Couldn't create the bitmap of a cached object: {}", e);
+                    this.render_self(context);
+                    return;
+                }
+            }
+        }
+    };
+
+    if cache.untracked || this.is_bitmap_cache_dirty() || cache.matrix != offscreen_transform.matrix
+    {
+        if !context.renderer.push_offscreen_target(cache.handle) {
+            this.set_bitmap_cache(Some(cache));
+            this.render_self(context);
+            return;
+        }
+
+        // Everything is drawn, even off-stage, as the bitmap may be reused
+        // after the object moves.
+        let cull = std::mem::replace(&mut context.cull, false);
+        let debug_overlay = context.debug_overlay.take();
+        let drew_untracked_content = std::mem::replace(&mut context.drew_untracked_content, false);
+        cache.matrix = offscreen_transform.matrix;
+        context.transform_stack.push_absolute(offscreen_transform);
+        this.render_self(context);
+        context.transform_stack.pop();
+        cache.untracked = context.drew_untracked_content;
+        context.drew_untracked_content |= drew_untracked_content;
+        context.debug_overlay = debug_overlay;
+        context.cull = cull;
+        context.renderer.pop_offscreen_target();
+        this.set_bitmap_cache_dirty(false);
+    }
+
+    context.renderer.render_bitmap(
+        cache.handle,
+        &Transform {
+            matrix: Matrix::translate(x, y),
+            color_transform: transform.color_transform,
+        },
+        false,
+    );
+    this.set_bitmap_cache(Some(cache));
+}
+
 #[enum_trait_object(
     #[derive(Clone, Collect, Debug, Copy)]
     #[collect(no_drop)]
@@ -491,6 +965,16 @@ pub trait TDisplayObject<'gc>:
     /// Composite DisplayObjects that only contain children should return `&Default::default()`
     fn self_bounds(&self) -> BoundingBox;
 
+    /// A value that changes whenever this object's own content would be drawn
+    /// differently, not counting its transform or its children.
+    ///
+    /// This is used to find the parts of the stage that have to be redrawn.
+    /// Objects whose content can change without being tracked return `None`,
+    /// and are redrawn every frame.
+    fn content_revision(&self) -> Option<u64> {
+        Some(0)
+    }
+
     /// The untransformed bounding box of this object including children.
     fn bounds(&self) -> BoundingBox {
         self.bounds_with_transform(&Matrix::default())
@@ -515,7 +999,7 @@ pub trait TDisplayObject<'gc>:
 
         if let Some(ctr) = self.as_container() {
             for child in ctr.iter_execution_list() {
-                let matrix = *matrix * *child.matrix();
+                let matrix = matrix::multiply(matrix, &child.matrix());
                 bounds.union(&child.bounds_with_transform(&matrix));
             }
         }
@@ -543,7 +1027,7 @@ pub trait TDisplayObject<'gc>:
         let mut node = self.parent();
         let mut matrix = *self.matrix();
         while let Some(display_object) = node {
-            matrix = *display_object.matrix() * matrix;
+            matrix = matrix::multiply(&display_object.matrix(), &matrix);
             node = display_object.parent();
         }
         matrix
@@ -551,19 +1035,17 @@ pub trait TDisplayObject<'gc>:
 
     /// Returns the matrix for transforming from global stage to this object's local space.
     fn global_to_local_matrix(&self) -> Matrix {
-        let mut matrix = self.local_to_global_matrix();
-        matrix.invert();
-        matrix
+        matrix::invert(&self.local_to_global_matrix())
     }
 
     /// Converts a local position to a global stage position
     fn local_to_global(&self, local: (Twips, Twips)) -> (Twips, Twips) {
-        self.local_to_global_matrix() * local
+        matrix::transform_point(&self.local_to_global_matrix(), local)
     }
 
     /// Converts a local position on the stage to a local position on this display object
     fn global_to_local(&self, global: (Twips, Twips)) -> (Twips, Twips) {
-        self.global_to_local_matrix() * global
+        matrix::transform_point(&self.global_to_local_matrix(), global)
     }
 
     /// The `x` position in pixels of this display object in local space.
@@ -624,8 +1106,8 @@ pub trait TDisplayObject<'gc>:
     /// This does odd things on rotated clips to match the behavior of Flash.
     fn set_width(&self, gc_context: MutationContext<'gc, '_>, value: f64) {
         let object_bounds = self.bounds();
-        let object_width = (object_bounds.x_max - object_bounds.x_min).to_pixels();
-        let object_height = (object_bounds.y_max - object_bounds.y_min).to_pixels();
+        let object_width = (object_bounds.x_max.saturating_sub(object_bounds.x_min)).to_pixels();
+        let object_height = (object_bounds.y_max.saturating_sub(object_bounds.y_min)).to_pixels();
         let aspect_ratio = object_height / object_width;
 
         let (target_scale_x, target_scale_y) = if object_width != 0.0 {
@@ -672,8 +1154,8 @@ pub trait TDisplayObject<'gc>:
     /// This does odd things on rotated clips to match the behavior of Flash.
     fn set_height(&self, gc_context: MutationContext<'gc, '_>, value: f64) {
         let object_bounds = self.bounds();
-        let object_width = (object_bounds.x_max - object_bounds.x_min).to_pixels();
-        let object_height = (object_bounds.y_max - object_bounds.y_min).to_pixels();
+        let object_width = (object_bounds.x_max.saturating_sub(object_bounds.x_min)).to_pixels();
+        let object_height = (object_bounds.y_max.saturating_sub(object_bounds.y_min)).to_pixels();
         let aspect_ratio = object_width / object_height;
 
         let (target_scale_x, target_scale_y) = if object_height != 0.0 {
@@ -783,18 +1265,6 @@ pub trait TDisplayObject<'gc>:
         self.avm2_parent().filter(|p| p.as_stage().is_none())
     }
 
-    fn prev_sibling(&self) -> Option<DisplayObject<'gc>>;
-    fn set_prev_sibling(
-        &self,
-        gc_context: MutationContext<'gc, '_>,
-        node: Option<DisplayObject<'gc>>,
-    );
-    fn next_sibling(&self) -> Option<DisplayObject<'gc>>;
-    fn set_next_sibling(
-        &self,
-        gc_context: MutationContext<'gc, '_>,
-        node: Option<DisplayObject<'gc>>,
-    );
     fn masker(&self) -> Option<DisplayObject<'gc>>;
     fn set_masker(
         &self,
@@ -823,6 +1293,50 @@ pub trait TDisplayObject<'gc>:
     /// Returned by the `_visible`/`visible` ActionScript properties.
     fn set_visible(&self, gc_context: MutationContext<'gc, '_>, value: bool);
 
+    /// The surface filters applied to this display object, in the order they are applied.
+    /// Set by the `PlaceObject3` tag.
+    fn filters(&self) -> Ref<[swf::Filter]>;
+
+    /// Sets the surface filters applied to this display object.
+    fn set_filters(&self, gc_context: MutationContext<'gc, '_>, filters: Vec<swf::Filter>);
+
+    /// The blend mode used when compositing this display object onto the
+    /// content beneath it. Set by the `PlaceObject3` tag.
+    fn blend_mode(&self) -> BlendMode;
+
+    /// Sets the blend mode used when compositing this display object.
+    fn set_blend_mode(&self, gc_context: MutationContext<'gc, '_>, value: BlendMode);
+
+    /// Whether this display object is cached as a bitmap when rendered.
+    /// This is always the case for objects with filters.
+    /// Returned by the `cacheAsBitmap` ActionScript property.
+    fn is_bitmap_cached(&self) -> bool;
+
+    /// Whether this display object was set to be cached as a bitmap, by
+    /// `cacheAsBitmap` or `PlaceObject3`, regardless of its filters.
+    fn cache_as_bitmap_flag(&self) -> bool;
+
+    /// Sets whether this display object is cached as a bitmap when rendered.
+    /// Set by the `cacheAsBitmap` ActionScript property and `PlaceObject3`.
+    fn set_bitmap_cached(&self, gc_context: MutationContext<'gc, '_>, value: bool);
+
+    /// The color filling the bounds of this display object behind its
+    /// content, if any. Returned by the `opaqueBackground` ActionScript
+    /// property.
+    fn opaque_background(&self) -> Option<Color>;
+
+    /// Sets the color filling the bounds of this display object.
+    fn set_opaque_background(&self, gc_context: MutationContext<'gc, '_>, value: Option<Color>);
+
+    /// Whether this display object is highlighted when it's focused with the
+    /// keyboard, or `None` if it follows the stage's setting. Returned by the
+    /// `_focusrect` ActionScript property.
+    fn focus_rect(&self) -> Option<bool>;
+
+    /// Sets whether this display object is highlighted when it's focused
+    /// with the keyboard.
+    fn set_focus_rect(&self, gc_context: MutationContext<'gc, '_>, value: Option<bool>);
+
     /// The sound transform for sounds played inside this display object.
     fn sound_transform(&self) -> Ref<SoundTransform>;
 
@@ -849,6 +1363,47 @@ pub trait TDisplayObject<'gc>:
     /// When this flag is set, changes from SWF `PlaceObject` tags are ignored.
     fn set_transformed_by_script(&self, gc_context: MutationContext<'gc, '_>, value: bool);
 
+    /// The position that this display object is drawn moving away from
+    /// between frames, when frame interpolation is enabled.
+    fn interpolate_from(&self) -> Option<(Twips, Twips)>;
+
+    /// Start drawing this display object moving from wherever it was before
+    /// scripts moved it during the frame that just ran.
+    ///
+    /// Returns `true` if the object moved.
+    fn start_interpolation(&self, gc_context: MutationContext<'gc, '_>) -> bool;
+
+    /// Takes the bitmap that this display object was last cached as, if any.
+    ///
+    /// This is done while rendering, without a `MutationContext`, so the
+    /// cache can be updated without touching the rest of the object.
+    fn take_bitmap_cache(&self) -> Option<BitmapCache>;
+
+    /// Stores the bitmap that this display object was cached as.
+    fn set_bitmap_cache(&self, cache: Option<BitmapCache>);
+
+    /// Whether the content or children of this display object have changed
+    /// since it was last drawn into its cached bitmap.
+    /// See `invalidate_cached_bitmaps`.
+    fn is_bitmap_cache_dirty(&self) -> bool;
+
+    fn set_bitmap_cache_dirty(&self, dirty: bool);
+
+    /// A number that identifies this display object, and no other, for as
+    /// long as the player runs.
+    fn instance_key(&self) -> u64;
+
+    /// The object under the mouse when this display object was last dragged
+    /// with `startDrag`, as reported by `_droptarget`.
+    fn drop_target(&self) -> Option<DisplayObject<'gc>>;
+
+    /// Sets the object under the mouse while this display object is dragged.
+    fn set_drop_target(
+        &self,
+        gc_context: MutationContext<'gc, '_>,
+        drop_target: Option<DisplayObject<'gc>>,
+    );
+
     /// Called whenever the focus tracker has deemed this display object worthy, or no longer worthy,
     /// of being the currently focused object.
     /// This should only be called by the focus manager. To change a focus, go through that.
@@ -890,6 +1445,13 @@ pub trait TDisplayObject<'gc>:
         ClipEventResult::NotHandled
     }
 
+    /// The propagating clip events that this object or any of its children
+    /// can handle. Objects that can't handle an event don't have to be given
+    /// it at all.
+    fn clip_event_interest(&self, _context: &mut UpdateContext<'_, 'gc, '_>) -> ClipEventMask {
+        ClipEventMask::empty()
+    }
+
     /// Emit an `enterFrame` event on this DisplayObject and any children it
     /// may have.
     fn enter_frame(&self, context: &mut UpdateContext<'_, 'gc, '_>) {
@@ -963,6 +1525,22 @@ pub trait TDisplayObject<'gc>:
         }
     }
 
+    /// Emit a `render` broadcast event.
+    fn render_event(&self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let mut render_evt = Avm2Event::new("render");
+        render_evt.set_bubbles(false);
+        render_evt.set_cancelable(false);
+
+        let dobject_proto = context.avm2.prototypes().display_object;
+
+        if let Err(e) = Avm2::broadcast_event(context, render_evt, dobject_proto) {
+            log::error!(
+                "Encountered AVM2 error when broadcasting render event: {}",
+                e
+            );
+        }
+    }
+
     fn render_self(&self, _context: &mut RenderContext<'_, 'gc>) {}
 
     fn render(&self, context: &mut RenderContext<'_, 'gc>) {
@@ -990,6 +1568,10 @@ pub trait TDisplayObject<'gc>:
             }
         }
 
+        if let Some(cache) = self.take_bitmap_cache() {
+            context.renderer.unregister_bitmap(cache.handle);
+        }
+
         self.set_removed(context.gc_context, true);
     }
 
@@ -1040,6 +1622,18 @@ pub trait TDisplayObject<'gc>:
             if let Some(clip_depth) = place_object.clip_depth {
                 self.set_clip_depth(context.gc_context, clip_depth.into());
             }
+            if let Some(filters) = &place_object.filters {
+                self.set_filters(context.gc_context, filters.clone());
+            }
+            if let Some(blend_mode) = place_object.blend_mode {
+                self.set_blend_mode(context.gc_context, blend_mode);
+            }
+            if let Some(is_bitmap_cached) = place_object.is_bitmap_cached {
+                self.set_bitmap_cached(context.gc_context, is_bitmap_cached);
+            }
+            if let Some(background_color) = &place_object.background_color {
+                self.set_opaque_background(context.gc_context, Some(background_color.clone()));
+            }
             if let Some(ratio) = place_object.ratio {
                 if let Some(mut morph_shape) = self.as_morph_shape() {
                     morph_shape.set_ratio(context.gc_context, ratio);
@@ -1083,6 +1677,10 @@ pub trait TDisplayObject<'gc>:
         self.set_color_transform(gc_context, &*other.color_transform());
         self.set_clip_depth(gc_context, other.clip_depth());
         self.set_name(gc_context, &*other.name());
+        self.set_filters(gc_context, other.filters().to_vec());
+        self.set_blend_mode(gc_context, other.blend_mode());
+        self.set_bitmap_cached(gc_context, other.cache_as_bitmap_flag());
+        self.set_opaque_background(gc_context, other.opaque_background());
         if let (Some(mut me), Some(other)) = (self.as_morph_shape(), other.as_morph_shape()) {
             me.set_ratio(gc_context, other.ratio());
         }
@@ -1180,7 +1778,7 @@ pub trait TDisplayObject<'gc>:
     }
 
     /// The cursor to use when this object is the hovered element under a mouse
-    fn mouse_cursor(&self) -> MouseCursor {
+    fn mouse_cursor(&self, _context: &mut UpdateContext<'_, 'gc, '_>) -> MouseCursor {
         MouseCursor::Hand
     }
 
@@ -1323,6 +1921,7 @@ macro_rules! impl_display_object_sansbounds {
             &self,
             context: gc_arena::MutationContext<'gc, '_>,
         ) -> std::cell::RefMut<swf::Matrix> {
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
             std::cell::RefMut::map(self.0.write(context), |o| o.$field.matrix_mut())
         }
         fn color_transform(&self) -> std::cell::Ref<crate::color_transform::ColorTransform> {
@@ -1332,6 +1931,7 @@ macro_rules! impl_display_object_sansbounds {
             &self,
             context: gc_arena::MutationContext<'gc, '_>,
         ) -> std::cell::RefMut<crate::color_transform::ColorTransform> {
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
             std::cell::RefMut::map(self.0.write(context), |o| o.$field.color_transform_mut())
         }
         fn set_color_transform(
@@ -1342,31 +1942,36 @@ macro_rules! impl_display_object_sansbounds {
             self.0
                 .write(context)
                 .$field
-                .set_color_transform(color_transform)
+                .set_color_transform(color_transform);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn rotation(&self, gc_context: gc_arena::MutationContext<'gc, '_>) -> Degrees {
             self.0.write(gc_context).$field.rotation()
         }
         fn set_rotation(&self, gc_context: gc_arena::MutationContext<'gc, '_>, degrees: Degrees) {
-            self.0.write(gc_context).$field.set_rotation(degrees)
+            self.0.write(gc_context).$field.set_rotation(degrees);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn scale_x(&self, gc_context: gc_arena::MutationContext<'gc, '_>) -> Percent {
             self.0.write(gc_context).$field.scale_x()
         }
         fn set_scale_x(&self, gc_context: gc_arena::MutationContext<'gc, '_>, value: Percent) {
-            self.0.write(gc_context).$field.set_scale_x(value)
+            self.0.write(gc_context).$field.set_scale_x(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn scale_y(&self, gc_context: gc_arena::MutationContext<'gc, '_>) -> Percent {
             self.0.write(gc_context).$field.scale_y()
         }
         fn set_scale_y(&self, gc_context: gc_arena::MutationContext<'gc, '_>, value: Percent) {
-            self.0.write(gc_context).$field.set_scale_y(value)
+            self.0.write(gc_context).$field.set_scale_y(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn alpha(&self) -> f64 {
             self.0.read().$field.alpha()
         }
         fn set_alpha(&self, gc_context: gc_arena::MutationContext<'gc, '_>, value: f64) {
-            self.0.write(gc_context).$field.set_alpha(value)
+            self.0.write(gc_context).$field.set_alpha(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn name(&self) -> std::cell::Ref<str> {
             std::cell::Ref::map(self.0.read(), |o| o.$field.name())
@@ -1382,7 +1987,8 @@ macro_rules! impl_display_object_sansbounds {
             context: gc_arena::MutationContext<'gc, '_>,
             depth: crate::prelude::Depth,
         ) {
-            self.0.write(context).$field.set_clip_depth(depth)
+            self.0.write(context).$field.set_clip_depth(depth);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn avm2_parent(&self) -> Option<crate::display_object::DisplayObject<'gc>> {
             self.0.read().$field.avm2_parent()
@@ -1394,26 +2000,6 @@ macro_rules! impl_display_object_sansbounds {
         ) {
             self.0.write(context).$field.set_parent(parent)
         }
-        fn prev_sibling(&self) -> Option<DisplayObject<'gc>> {
-            self.0.read().$field.prev_sibling()
-        }
-        fn set_prev_sibling(
-            &self,
-            context: gc_arena::MutationContext<'gc, '_>,
-            node: Option<DisplayObject<'gc>>,
-        ) {
-            self.0.write(context).$field.set_prev_sibling(node);
-        }
-        fn next_sibling(&self) -> Option<DisplayObject<'gc>> {
-            self.0.read().$field.next_sibling()
-        }
-        fn set_next_sibling(
-            &self,
-            context: gc_arena::MutationContext<'gc, '_>,
-            node: Option<DisplayObject<'gc>>,
-        ) {
-            self.0.write(context).$field.set_next_sibling(node);
-        }
         fn masker(&self) -> Option<DisplayObject<'gc>> {
             self.0.read().$field.masker()
         }
@@ -1429,6 +2015,7 @@ macro_rules! impl_display_object_sansbounds {
                 }
             }
             self.0.write(context).$field.set_masker(node);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn maskee(&self) -> Option<DisplayObject<'gc>> {
             self.0.read().$field.maskee()
@@ -1445,6 +2032,7 @@ macro_rules! impl_display_object_sansbounds {
                 }
             }
             self.0.write(context).$field.set_maskee(node);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn removed(&self) -> bool {
             self.0.read().$field.removed()
@@ -1471,6 +2059,55 @@ macro_rules! impl_display_object_sansbounds {
         }
         fn set_visible(&self, context: gc_arena::MutationContext<'gc, '_>, value: bool) {
             self.0.write(context).$field.set_visible(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
+        }
+        fn filters(&self) -> std::cell::Ref<[swf::Filter]> {
+            std::cell::Ref::map(self.0.read(), |r| r.$field.filters())
+        }
+        fn set_filters(
+            &self,
+            context: gc_arena::MutationContext<'gc, '_>,
+            filters: Vec<swf::Filter>,
+        ) {
+            self.0.write(context).$field.set_filters(filters);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
+        }
+        fn blend_mode(&self) -> swf::BlendMode {
+            self.0.read().$field.blend_mode()
+        }
+        fn set_blend_mode(
+            &self,
+            context: gc_arena::MutationContext<'gc, '_>,
+            value: swf::BlendMode,
+        ) {
+            self.0.write(context).$field.set_blend_mode(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
+        }
+        fn is_bitmap_cached(&self) -> bool {
+            self.0.read().$field.is_bitmap_cached()
+        }
+        fn cache_as_bitmap_flag(&self) -> bool {
+            self.0.read().$field.cache_as_bitmap_flag()
+        }
+        fn set_bitmap_cached(&self, context: gc_arena::MutationContext<'gc, '_>, value: bool) {
+            self.0.write(context).$field.set_bitmap_cached(value);
+        }
+        fn opaque_background(&self) -> Option<swf::Color> {
+            self.0.read().$field.opaque_background()
+        }
+        fn set_opaque_background(
+            &self,
+            context: gc_arena::MutationContext<'gc, '_>,
+            value: Option<swf::Color>,
+        ) {
+            self.0.write(context).$field.set_opaque_background(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
+        }
+        fn focus_rect(&self) -> Option<bool> {
+            self.0.read().$field.focus_rect()
+        }
+        fn set_focus_rect(&self, context: gc_arena::MutationContext<'gc, '_>, value: Option<bool>) {
+            self.0.write(context).$field.set_focus_rect(value);
         }
         fn lock_root(&self) -> bool {
             self.0.read().$field.lock_root()
@@ -1491,6 +2128,37 @@ macro_rules! impl_display_object_sansbounds {
                 .$field
                 .set_transformed_by_script(value)
         }
+        fn interpolate_from(&self) -> Option<(swf::Twips, swf::Twips)> {
+            self.0.read().$field.interpolate_from()
+        }
+        fn start_interpolation(&self, context: gc_arena::MutationContext<'gc, '_>) -> bool {
+            self.0.write(context).$field.start_interpolation()
+        }
+        fn take_bitmap_cache(&self) -> Option<crate::display_object::BitmapCache> {
+            self.0.read().$field.take_bitmap_cache()
+        }
+        fn set_bitmap_cache(&self, cache: Option<crate::display_object::BitmapCache>) {
+            self.0.read().$field.set_bitmap_cache(cache)
+        }
+        fn is_bitmap_cache_dirty(&self) -> bool {
+            self.0.read().$field.is_bitmap_cache_dirty()
+        }
+        fn set_bitmap_cache_dirty(&self, dirty: bool) {
+            self.0.read().$field.set_bitmap_cache_dirty(dirty)
+        }
+        fn instance_key(&self) -> u64 {
+            self.0.read().$field.instance_key()
+        }
+        fn drop_target(&self) -> Option<DisplayObject<'gc>> {
+            self.0.read().$field.drop_target()
+        }
+        fn set_drop_target(
+            &self,
+            context: gc_arena::MutationContext<'gc, '_>,
+            drop_target: Option<DisplayObject<'gc>>,
+        ) {
+            self.0.write(context).$field.set_drop_target(drop_target)
+        }
         fn placed_by_script(&self) -> bool {
             self.0.read().$field.placed_by_script()
         }
@@ -1536,16 +2204,19 @@ macro_rules! impl_display_object {
             self.0.read().$field.x()
         }
         fn set_x(&self, gc_context: gc_arena::MutationContext<'gc, '_>, value: f64) {
-            self.0.write(gc_context).$field.set_x(value)
+            self.0.write(gc_context).$field.set_x(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn y(&self) -> f64 {
             self.0.read().$field.y()
         }
         fn set_y(&self, gc_context: gc_arena::MutationContext<'gc, '_>, value: f64) {
-            self.0.write(gc_context).$field.set_y(value)
+            self.0.write(gc_context).$field.set_y(value);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
         fn set_matrix(&self, context: gc_arena::MutationContext<'gc, '_>, matrix: &swf::Matrix) {
-            self.0.write(context).$field.set_matrix(matrix)
+            self.0.write(context).$field.set_matrix(matrix);
+            crate::display_object::invalidate_parent_cached_bitmaps((*self).into());
         }
     };
 }
@@ -1554,6 +2225,21 @@ impl<'gc> DisplayObject<'gc> {
     pub fn ptr_eq(a: DisplayObject<'gc>, b: DisplayObject<'gc>) -> bool {
         a.as_ptr() == b.as_ptr()
     }
+
+    /// The name of the concrete type of this display object, for debugging.
+    pub fn type_name(self) -> &'static str {
+        match self {
+            DisplayObject::Stage(_) => "Stage",
+            DisplayObject::Bitmap(_) => "Bitmap",
+            DisplayObject::Button(_) => "Button",
+            DisplayObject::EditText(_) => "EditText",
+            DisplayObject::Graphic(_) => "Graphic",
+            DisplayObject::MorphShape(_) => "MorphShape",
+            DisplayObject::MovieClip(_) => "MovieClip",
+            DisplayObject::Text(_) => "Text",
+            DisplayObject::Video(_) => "Video",
+        }
+    }
 }
 
 bitflags! {
@@ -1586,6 +2272,9 @@ bitflags! {
         /// Whether this object has `_lockroot` set to true, in which case
         /// it becomes the _root of itself and of any children
         const LOCK_ROOT                = 1 << 6;
+
+        /// Whether this object has `cacheAsBitmap` set to true.
+        const CACHE_AS_BITMAP          = 1 << 7;
     }
 }
 
@@ -66,7 +66,6 @@ impl<'gc> Bitmap<'gc> {
         Self::new_with_bitmap_data(context, id, bitmap_handle, width, height, None, true)
     }
 
-    #[allow(dead_code)]
     pub fn bitmap_handle(self) -> BitmapHandle {
         self.0.read().static_data.bitmap_handle
     }
@@ -113,8 +112,17 @@ impl<'gc> TDisplayObject<'gc> for Bitmap<'gc> {
         }
     }
 
+    fn content_revision(&self) -> Option<u64> {
+        // Bitmap data can be drawn into by scripts.
+        if self.0.read().bitmap_data.is_some() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
     fn render_self(&self, context: &mut RenderContext) {
-        if !self.world_bounds().intersects(&context.stage.view_bounds()) {
+        if context.is_culled(&self.world_bounds()) {
             // Off-screen; culled
             return;
         }
@@ -4,8 +4,8 @@ use crate::context::{ActionType, RenderContext, UpdateContext};
 use crate::display_object::container::{
     dispatch_added_event, dispatch_removed_event, ChildContainer,
 };
-use crate::display_object::{DisplayObjectBase, TDisplayObject};
-use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult};
+use crate::display_object::{construct_registered_avm1_class, DisplayObjectBase, TDisplayObject};
+use crate::events::{ButtonKeyCode, ClipEvent, ClipEventMask, ClipEventResult};
 use crate::prelude::*;
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::types::{Degrees, Percent};
@@ -97,6 +97,27 @@ impl<'gc> Button<'gc> {
         ))
     }
 
+    /// Fetch the AVM1 constructor associated with this button's symbol by
+    /// `Object.registerClass`.
+    ///
+    /// Registered classes only apply to buttons from SWF 9 and later movies.
+    fn registered_avm1_constructor(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Option<Object<'gc>> {
+        let static_data = self.0.read().static_data;
+        let static_data = static_data.read();
+        if static_data.swf.version() < 9 {
+            return None;
+        }
+        let library = context
+            .library
+            .library_for_movie_mut(static_data.swf.clone());
+        let symbol_name = library.export_name(static_data.id)?;
+        let ctor = library.avm1_registered_constructor(symbol_name)?;
+        Some(Object::FunctionObject(ctor))
+    }
+
     pub fn set_sounds(self, gc_context: MutationContext<'gc, '_>, sounds: swf::ButtonSounds) {
         let button = self.0.write(gc_context);
         let mut static_data = button.static_data.write(gc_context);
@@ -150,6 +171,7 @@ impl<'gc> Button<'gc> {
         // Children that exist in both states should persist and not be recreated.
         // TODO: This behavior probably differs in AVM2 (I suspect they always get recreated).
         let mut children = Vec::new();
+        let mut transforms = Vec::new();
 
         for record in &write.static_data.read().records {
             if record.states.contains(swf_state) {
@@ -180,15 +202,18 @@ impl<'gc> Button<'gc> {
                 };
 
                 // Set transform of child (and modify previous child if it already existed)
-                child.set_matrix(context.gc_context, &record.matrix);
-                child.set_color_transform(
-                    context.gc_context,
-                    &record.color_transform.clone().into(),
-                );
+                transforms.push((child, record.matrix, record.color_transform.clone()));
             }
         }
         drop(write);
 
+        // Children mark their parent's cached bitmap as dirty when they move,
+        // so this waits until the button is no longer borrowed.
+        for (child, matrix, color_transform) in transforms {
+            child.set_matrix(context.gc_context, &matrix);
+            child.set_color_transform(context.gc_context, &color_transform.into());
+        }
+
         // Kill children that no longer exist in this state.
         for depth in removed_depths {
             if let Some(child) = self.child_by_depth(depth) {
@@ -253,16 +278,23 @@ impl<'gc> TDisplayObject<'gc> for Button<'gc> {
     ) {
         self.set_default_instance_name(context);
 
-        let mut mc = self.0.write(context.gc_context);
-        if mc.object.is_none() {
+        if self.0.read().object.is_none() {
             let object = StageObject::for_display_object(
                 context.gc_context,
                 display_object,
                 Some(context.avm1.prototypes().button),
             );
-            mc.object = Some(object.into());
+            self.0.write(context.gc_context).object = Some(object.into());
 
-            drop(mc);
+            // Registered classes are applied before the button's first frame.
+            if let Some(constructor) = self.registered_avm1_constructor(context) {
+                construct_registered_avm1_class(
+                    context,
+                    display_object,
+                    object.into(),
+                    constructor,
+                );
+            }
 
             if run_frame {
                 self.run_frame(context);
@@ -379,7 +411,7 @@ impl<'gc> TDisplayObject<'gc> for Button<'gc> {
         None
     }
 
-    fn mouse_cursor(&self) -> MouseCursor {
+    fn mouse_cursor(&self, _context: &mut UpdateContext<'_, 'gc, '_>) -> MouseCursor {
         if self.use_hand_cursor() {
             MouseCursor::Hand
         } else {
@@ -407,6 +439,11 @@ impl<'gc> TDisplayObject<'gc> for Button<'gc> {
         !self.is_empty()
     }
 
+    fn clip_event_interest(&self, _context: &mut UpdateContext<'_, 'gc, '_>) -> ClipEventMask {
+        // Buttons track the mouse and key presses themselves.
+        ClipEventMask::all()
+    }
+
     /// Executes and propagates the given clip event.
     /// Events execute inside-out; the deepest child will react first, followed by its parent, and
     /// so forth.
@@ -437,55 +474,60 @@ impl<'gc> TDisplayObject<'gc> for Button<'gc> {
 
         // Translate the clip event to a button event, based on how the button state changes.
         let cur_state = write.state;
-        let new_state = match event {
-            ClipEvent::RollOut => ButtonState::Up,
-            ClipEvent::RollOver => ButtonState::Over,
-            ClipEvent::Press => ButtonState::Down,
-            ClipEvent::Release => ButtonState::Over,
+        let static_data = write.static_data;
+        let static_data = static_data.read();
+        let (new_state, condition, sound) = match event {
+            ClipEvent::DragOut => (
+                ButtonState::Over,
+                Some(swf::ButtonActionCondition::OVER_DOWN_TO_OUT_DOWN),
+                None,
+            ),
+            ClipEvent::DragOver => (
+                ButtonState::Down,
+                Some(swf::ButtonActionCondition::OUT_DOWN_TO_OVER_DOWN),
+                None,
+            ),
+            ClipEvent::Press => (
+                ButtonState::Down,
+                Some(swf::ButtonActionCondition::OVER_UP_TO_OVER_DOWN),
+                static_data.over_to_down_sound.as_ref(),
+            ),
+            ClipEvent::Release => (
+                ButtonState::Over,
+                Some(swf::ButtonActionCondition::OVER_DOWN_TO_OVER_UP),
+                static_data.down_to_over_sound.as_ref(),
+            ),
+            ClipEvent::ReleaseOutside => (
+                ButtonState::Up,
+                Some(swf::ButtonActionCondition::OUT_DOWN_TO_IDLE),
+                static_data.over_to_up_sound.as_ref(),
+            ),
+            ClipEvent::RollOut => (
+                ButtonState::Up,
+                Some(swf::ButtonActionCondition::OVER_UP_TO_IDLE),
+                static_data.over_to_up_sound.as_ref(),
+            ),
+            ClipEvent::RollOver => (
+                ButtonState::Over,
+                Some(swf::ButtonActionCondition::IDLE_TO_OVER_UP),
+                static_data.up_to_over_sound.as_ref(),
+            ),
             ClipEvent::KeyPress { key_code } => {
                 handled = write.run_actions(
                     context,
                     swf::ButtonActionCondition::KEY_PRESS,
                     Some(key_code),
                 );
-                cur_state
+                (cur_state, None, None)
             }
             _ => return ClipEventResult::NotHandled,
         };
 
-        match (cur_state, new_state) {
-            (ButtonState::Up, ButtonState::Over) => {
-                write.run_actions(context, swf::ButtonActionCondition::IDLE_TO_OVER_UP, None);
-                write.play_sound(context, write.static_data.read().up_to_over_sound.as_ref());
-            }
-            (ButtonState::Over, ButtonState::Up) => {
-                write.run_actions(context, swf::ButtonActionCondition::OVER_UP_TO_IDLE, None);
-                write.play_sound(context, write.static_data.read().over_to_up_sound.as_ref());
-            }
-            (ButtonState::Over, ButtonState::Down) => {
-                write.run_actions(
-                    context,
-                    swf::ButtonActionCondition::OVER_UP_TO_OVER_DOWN,
-                    None,
-                );
-                write.play_sound(
-                    context,
-                    write.static_data.read().over_to_down_sound.as_ref(),
-                );
-            }
-            (ButtonState::Down, ButtonState::Over) => {
-                write.run_actions(
-                    context,
-                    swf::ButtonActionCondition::OVER_DOWN_TO_OVER_UP,
-                    None,
-                );
-                write.play_sound(
-                    context,
-                    write.static_data.read().down_to_over_sound.as_ref(),
-                );
-            }
-            _ => (),
-        };
+        if let Some(condition) = condition.filter(|_| new_state != cur_state) {
+            write.run_actions(context, condition, None);
+            write.play_sound(context, sound);
+        }
+        drop(static_data);
 
         // Queue ActionScript-defined event handlers after the SWF defined ones.
         // (e.g., clip.onRelease = foo).
@@ -530,6 +572,9 @@ impl<'gc> TDisplayObject<'gc> for Button<'gc> {
         } else if let Some(node) = self.masker() {
             node.set_maskee(context.gc_context, None, true);
         }
+        if let Some(cache) = self.take_bitmap_cache() {
+            context.renderer.unregister_bitmap(cache.handle);
+        }
         self.set_removed(context.gc_context, true);
     }
 }
@@ -156,6 +156,16 @@ bitflags! {
         MovieClip(MovieClip<'gc>),
     }
 )]
+impl<'gc> From<DisplayObjectContainer<'gc>> for DisplayObject<'gc> {
+    fn from(container: DisplayObjectContainer<'gc>) -> Self {
+        match container {
+            DisplayObjectContainer::Stage(stage) => stage.into(),
+            DisplayObjectContainer::Button(button) => button.into(),
+            DisplayObjectContainer::MovieClip(movie_clip) => movie_clip.into(),
+        }
+    }
+}
+
 pub trait TDisplayObjectContainer<'gc>:
     'gc + Clone + Copy + Collect + Debug + Into<DisplayObjectContainer<'gc>>
 {
@@ -181,8 +191,18 @@ pub trait TDisplayObjectContainer<'gc>:
     /// this mechanism.
     fn child_by_name(self, name: &str, case_sensitive: bool) -> Option<DisplayObject<'gc>>;
 
-    /// Yield the head of the execution list.
-    fn first_executed_child(self) -> Option<DisplayObject<'gc>>;
+    /// Returns a copy of the execution list, in the order that children
+    /// should be executed.
+    fn execution_list(self) -> Vec<DisplayObject<'gc>>;
+
+    /// Returns the child at the given position of the execution list.
+    ///
+    /// Children are kept on the list in the order they were added, and are
+    /// executed back to front.
+    fn child_by_exec_index(self, index: usize) -> Option<DisplayObject<'gc>>;
+
+    /// Returns the position of a child on the execution list, if it's on it.
+    fn exec_index_of(self, child: DisplayObject<'gc>) -> Option<usize>;
 
     /// Returns the number of children on the render list.
     fn num_children(self) -> usize;
@@ -291,15 +311,15 @@ pub trait TDisplayObjectContainer<'gc>:
     ///
     /// This yields an iterator that does *not* lock the parent and can be
     /// safely held in situations where display objects need to be unlocked.
-    /// This means that unexpected but legal and defined items may be yielded
-    /// due to intended or unintended list manipulation by the caller.
+    /// Children added to the container during iteration will not be yielded,
+    /// and children removed from the execution list (or unloaded) before
+    /// being reached will be skipped. If the container itself is unloaded
+    /// during iteration, no further children are yielded.
     ///
     /// The iterator's concrete type is stated here due to Rust language
     /// limitations.
     fn iter_execution_list(self) -> ExecIter<'gc> {
-        ExecIter {
-            cur_child: self.first_executed_child(),
-        }
+        ExecIter::from_container(self.into())
     }
 
     /// Iterates over the children of this display object in render order. This
@@ -337,6 +357,13 @@ pub trait TDisplayObjectContainer<'gc>:
         for child in self.iter_render_list() {
             let depth = child.depth();
 
+            if let Some(debug_overlay) = &mut context.debug_overlay {
+                if child.visible() || child.clip_depth() > 0 {
+                    let matrix = context.transform_stack.transform().matrix * *child.matrix();
+                    debug_overlay.add(child, &matrix);
+                }
+            }
+
             // Check if we need to pop off a mask.
             // This must be a while loop because multiple masks can be popped
             // at the same dpeth.
@@ -391,8 +418,16 @@ macro_rules! impl_display_object_container {
             self.0.read().$field.get_name(name, case_sensitive)
         }
 
-        fn first_executed_child(self) -> Option<DisplayObject<'gc>> {
-            self.0.read().$field.first_executed_child()
+        fn execution_list(self) -> Vec<DisplayObject<'gc>> {
+            self.0.read().$field.iter_execution_list().collect()
+        }
+
+        fn child_by_exec_index(self, index: usize) -> Option<DisplayObject<'gc>> {
+            self.0.read().$field.child_by_exec_index(index)
+        }
+
+        fn exec_index_of(self, child: DisplayObject<'gc>) -> Option<usize> {
+            self.0.read().$field.exec_index_of(child)
         }
 
         fn num_children(self) -> usize {
@@ -416,59 +451,39 @@ macro_rules! impl_display_object_container {
             let mut write = self.0.write(context.gc_context);
 
             let prev_child = write.$field.insert_child_into_depth_list(depth, child);
-            let removed_child = if let Some(prev_child) = prev_child {
-                let position = write
-                    .$field
-                    .iter_render_list()
-                    .position(|x| DisplayObject::ptr_eq(x, prev_child))
-                    .unwrap();
-
-                if !prev_child.placed_by_script() {
+            let prev_position = prev_child.and_then(|prev| write.$field.render_position(prev));
+            let removed_child = match (prev_child, prev_position) {
+                (Some(prev_child), Some(position)) if !prev_child.placed_by_script() => {
                     write.$field.replace_id(position, child);
 
                     Some(prev_child)
-                } else {
+                }
+                (Some(_), Some(position)) => {
                     write.$field.insert_id(position + 1, child);
 
                     None
                 }
-            } else {
-                let above = write
-                    .$field
-                    .iter_depth_range((Bound::Excluded(depth), Bound::Unbounded))
-                    .next();
-
-                if let Some((_, above_child)) = above {
-                    let position = write
-                        .$field
-                        .iter_render_list()
-                        .position(|x| DisplayObject::ptr_eq(x, above_child))
-                        .unwrap();
-                    write.$field.insert_id(position, child);
-
-                    None
-                } else {
-                    write.$field.push_id(child);
+                _ => {
+                    write.$field.insert_into_render_list_by_depth(depth, child);
 
                     None
                 }
             };
 
             if let Some(removed_child) = removed_child {
-                write
-                    .$field
-                    .remove_child_from_exec_list(context, removed_child);
+                write.$field.remove_child_from_exec_list(removed_child);
             }
 
-            write
-                .$field
-                .add_child_to_exec_list(context.gc_context, child);
+            write.$field.add_child_to_exec_list(child);
 
             drop(write);
 
             child.set_parent(context.gc_context, Some(self.into()));
             child.set_place_frame(context.gc_context, 0);
             child.set_depth(context.gc_context, depth);
+            child.set_removed(context.gc_context, false);
+            crate::display_object::invalidate_clip_event_interest(self.into(), context.gc_context);
+            crate::display_object::invalidate_cached_bitmaps(self.into());
 
             if let Some(removed_child) = removed_child {
                 removed_child.unload(context);
@@ -498,6 +513,7 @@ macro_rules! impl_display_object_container {
                 child,
                 depth,
             );
+            crate::display_object::invalidate_cached_bitmaps((*self).into());
         }
 
         fn insert_at_index(
@@ -526,10 +542,19 @@ macro_rules! impl_display_object_container {
             child.set_place_frame(context.gc_context, 0);
             child.set_parent(context.gc_context, Some((*self).into()));
 
+            // A child that was previously removed from the display list comes
+            // back to life once it is placed again.
+            child.set_removed(context.gc_context, false);
+
             self.0
                 .write(context.gc_context)
                 .$field
-                .insert_at_id(context, child, index);
+                .insert_at_id(child, index);
+            crate::display_object::invalidate_clip_event_interest(
+                (*self).into(),
+                context.gc_context,
+            );
+            crate::display_object::invalidate_cached_bitmaps((*self).into());
 
             if parent_changed {
                 dispatch_added_event(
@@ -551,6 +576,7 @@ macro_rules! impl_display_object_container {
                 .write(context.gc_context)
                 .$field
                 .swap_at_id(index1, index2);
+            crate::display_object::invalidate_cached_bitmaps((*self).into());
         }
 
         fn remove_child(
@@ -574,11 +600,16 @@ macro_rules! impl_display_object_container {
             let removed_from_render_list = from_lists.contains(Lists::RENDER)
                 && write.$field.remove_child_from_render_list(child);
             let removed_from_execution_list = from_lists.contains(Lists::EXECUTION)
-                && write.$field.remove_child_from_exec_list(context, child);
+                && write.$field.remove_child_from_exec_list(child);
 
             drop(write);
+            crate::display_object::invalidate_cached_bitmaps((*self).into());
 
             if removed_from_execution_list {
+                crate::display_object::invalidate_clip_event_interest(
+                    (*self).into(),
+                    context.gc_context,
+                );
                 child.unload(context);
 
                 //TODO: This is an awful, *awful* hack to deal with the fact
@@ -616,7 +647,7 @@ macro_rules! impl_display_object_container {
             for removed in removed_list {
                 write.$field.remove_child_from_render_list(removed);
                 write.$field.remove_child_from_depth_list(removed);
-                write.$field.remove_child_from_exec_list(context, removed);
+                write.$field.remove_child_from_exec_list(removed);
 
                 drop(write);
 
@@ -628,6 +659,13 @@ macro_rules! impl_display_object_container {
 
                 write = self.0.write(context.gc_context);
             }
+
+            drop(write);
+            crate::display_object::invalidate_clip_event_interest(
+                (*self).into(),
+                context.gc_context,
+            );
+            crate::display_object::invalidate_cached_bitmaps((*self).into());
         }
 
         fn clear(&mut self, context: &mut UpdateContext<'_, 'gc, '_>) {
@@ -638,10 +676,12 @@ macro_rules! impl_display_object_container {
                 dispatch_removed_event(removed, context);
             }
 
-            self.0
-                .write(context.gc_context)
-                .$field
-                .clear(context.gc_context)
+            self.0.write(context.gc_context).$field.clear();
+            crate::display_object::invalidate_clip_event_interest(
+                (*self).into(),
+                context.gc_context,
+            );
+            crate::display_object::invalidate_cached_bitmaps((*self).into());
         }
 
         fn is_empty(self) -> bool {
@@ -652,10 +692,15 @@ macro_rules! impl_display_object_container {
 
 /// A structure that stores child display objects.
 ///
-/// Child display objects are stored in two lists: a render list and a depth
-/// list. The latter references display objects by their chosen depth; while
-/// the render list represents the order in which those children should be
-/// rendered. Not all children have a position on this depth.
+/// Child display objects are stored in three lists: a render list, a depth
+/// list, and an execution list. The depth list references display objects by
+/// their chosen depth; the render list represents the order in which those
+/// children should be rendered; and the execution list represents the order
+/// in which their scripts run. Not all children have a position on the depth
+/// list.
+///
+/// All three orders are stored explicitly so that they can never disagree
+/// about which children the container holds.
 #[derive(Clone, Debug, Collect)]
 #[collect(no_drop)]
 pub struct ChildContainer<'gc> {
@@ -684,9 +729,10 @@ pub struct ChildContainer<'gc> {
 
     /// The execution-order list for display objects' AVM1 scripts.
     ///
-    /// This list is an intrusive linked list baked into all display objects.
-    /// Thus, this merely references the first item in the list.
-    exec_list: Option<DisplayObject<'gc>>,
+    /// Children are stored in the order they were added to the container.
+    /// Flash Player executes the most recently added child first, so this
+    /// list is executed back-to-front.
+    exec_list: Vec<DisplayObject<'gc>>,
 }
 
 impl<'gc> Default for ChildContainer<'gc> {
@@ -700,29 +746,15 @@ impl<'gc> ChildContainer<'gc> {
         ChildContainer {
             render_list: Vec::new(),
             depth_list: BTreeMap::new(),
-            exec_list: None,
+            exec_list: Vec::new(),
         }
     }
 
-    /// Get the head of the execution list.
-    pub fn first_executed_child(&self) -> Option<DisplayObject<'gc>> {
-        self.exec_list
-    }
-
     /// Adds a child to the front of the execution list.
     ///
     /// This does not affect the render or depth lists.
-    pub fn add_child_to_exec_list(
-        &mut self,
-        gc_context: MutationContext<'gc, '_>,
-        child: DisplayObject<'gc>,
-    ) {
-        if let Some(head) = self.exec_list {
-            head.set_prev_sibling(gc_context, Some(child));
-            child.set_next_sibling(gc_context, Some(head));
-        }
-
-        self.exec_list = Some(child);
+    pub fn add_child_to_exec_list(&mut self, child: DisplayObject<'gc>) {
+        self.exec_list.push(child);
     }
 
     /// Removes a child from the execution list.
@@ -734,35 +766,30 @@ impl<'gc> ChildContainer<'gc> {
     /// child. You must unload the child yourself in a clean stack frame, as
     /// display objects are permitted to run code when unloading. We also don't
     /// unset the parent either as that's expected to happen after unloading.
-    pub fn remove_child_from_exec_list(
-        &mut self,
-        context: &mut UpdateContext<'_, 'gc, '_>,
-        child: DisplayObject<'gc>,
-    ) -> bool {
-        // Remove from children linked list.
-        let prev = child.prev_sibling();
-        let next = child.next_sibling();
-        let present_on_execution_list = prev.is_some()
-            || next.is_some()
-            || (self.exec_list.is_some() && DisplayObject::ptr_eq(self.exec_list.unwrap(), child));
-
-        if let Some(prev) = prev {
-            prev.set_next_sibling(context.gc_context, next);
-        }
-        if let Some(next) = next {
-            next.set_prev_sibling(context.gc_context, prev);
+    pub fn remove_child_from_exec_list(&mut self, child: DisplayObject<'gc>) -> bool {
+        let exec_list_position = self
+            .exec_list
+            .iter()
+            .position(|x| DisplayObject::ptr_eq(*x, child));
+        if let Some(position) = exec_list_position {
+            self.exec_list.remove(position);
+            true
+        } else {
+            false
         }
+    }
 
-        child.set_prev_sibling(context.gc_context, None);
-        child.set_next_sibling(context.gc_context, None);
-
-        if let Some(head) = self.exec_list {
-            if DisplayObject::ptr_eq(head, child) {
-                self.exec_list = next;
-            }
-        }
+    /// Get the child at a position of the execution list, which is in the
+    /// order children were added.
+    pub fn child_by_exec_index(&self, index: usize) -> Option<DisplayObject<'gc>> {
+        self.exec_list.get(index).copied()
+    }
 
-        present_on_execution_list
+    /// Find the position of a child on the execution list.
+    pub fn exec_index_of(&self, child: DisplayObject<'gc>) -> Option<usize> {
+        self.exec_list
+            .iter()
+            .position(|x| DisplayObject::ptr_eq(*x, child))
     }
 
     /// Add a child to the depth list.
@@ -796,11 +823,7 @@ impl<'gc> ChildContainer<'gc> {
     /// This returns `true` if the child was successfully removed, and `false`
     /// if no list alterations were made.
     pub fn remove_child_from_render_list(&mut self, child: DisplayObject<'gc>) -> bool {
-        let render_list_position = self
-            .render_list
-            .iter()
-            .position(|x| DisplayObject::ptr_eq(*x, child));
-        if let Some(position) = render_list_position {
+        if let Some(position) = self.render_position(child) {
             self.render_list.remove(position);
             true
         } else {
@@ -808,6 +831,33 @@ impl<'gc> ChildContainer<'gc> {
         }
     }
 
+    /// Returns the position of a child on the render list, if it is present.
+    pub fn render_position(&self, child: DisplayObject<'gc>) -> Option<usize> {
+        self.render_list
+            .iter()
+            .position(|x| DisplayObject::ptr_eq(*x, child))
+    }
+
+    /// Insert a child into the render list at the position implied by a
+    /// depth, which is directly behind the first child on the depth list
+    /// above that depth.
+    ///
+    /// Children above the depth that are not on the render list are ignored.
+    /// If no such child exists, the child is placed at the end of the render
+    /// list.
+    pub fn insert_into_render_list_by_depth(&mut self, depth: Depth, child: DisplayObject<'gc>) {
+        let position = self
+            .depth_list
+            .range((Bound::Excluded(depth), Bound::Unbounded))
+            .find_map(|(_, above_child)| self.render_position(*above_child));
+
+        if let Some(position) = position {
+            self.render_list.insert(position, child);
+        } else {
+            self.render_list.push(child);
+        }
+    }
+
     /// Returns the highest depth on the render list, or `None` if no children
     /// have a depth less than the provided value.
     pub fn highest_depth(&self, less_than: Depth) -> Option<Depth> {
@@ -896,21 +946,10 @@ impl<'gc> ChildContainer<'gc> {
     /// position will be shifted back by one, which must be taken into account
     /// when calculating future insertion IDs.
     ///
-    /// `parent` should be the display object that owns this container.
-    ///
     /// All children at or after the given ID will be shifted down in the
     /// render list. The child will *not* be put onto the depth list.
-    pub fn insert_at_id(
-        &mut self,
-        context: &mut UpdateContext<'_, 'gc, '_>,
-        child: DisplayObject<'gc>,
-        id: usize,
-    ) {
-        if let Some(old_id) = self
-            .render_list
-            .iter()
-            .position(|x| DisplayObject::ptr_eq(*x, child))
-        {
+    pub fn insert_at_id(&mut self, child: DisplayObject<'gc>, id: usize) {
+        if let Some(old_id) = self.render_position(child) {
             match old_id.cmp(&id) {
                 Ordering::Less if id < self.render_list.len() => {
                     self.render_list[old_id..=id].rotate_left(1)
@@ -924,7 +963,7 @@ impl<'gc> ChildContainer<'gc> {
             }
         } else {
             self.render_list.insert(id, child);
-            self.add_child_to_exec_list(context.gc_context, child);
+            self.add_child_to_exec_list(child);
         }
     }
 
@@ -941,8 +980,7 @@ impl<'gc> ChildContainer<'gc> {
     /// to the current depth of the given child. Their relative positions in
     /// the render list will also be swapped. If the target depth is empty, the
     /// same steps occur, but the child will merely be removed and reinserted
-    /// within the render list at a position after the closest previous child
-    /// in the depth list.
+    /// within the render list in the same way as a newly placed child.
     ///
     /// `parent` should be the display object that owns this container.
     pub fn swap_at_depth(
@@ -961,55 +999,23 @@ impl<'gc> ChildContainer<'gc> {
             prev_child.set_transformed_by_script(gc_context, true);
             self.depth_list.insert(prev_depth, prev_child);
 
-            let prev_position = self
-                .render_list
-                .iter()
-                .position(|x| DisplayObject::ptr_eq(*x, prev_child))
-                .unwrap();
-            let next_position = self
-                .render_list
-                .iter()
-                .position(|x| DisplayObject::ptr_eq(*x, child))
-                .unwrap();
-            self.render_list.swap(prev_position, next_position);
+            if let (Some(prev_position), Some(next_position)) = (
+                self.render_position(prev_child),
+                self.render_position(child),
+            ) {
+                self.render_list.swap(prev_position, next_position);
+            }
         } else {
             self.depth_list.remove(&prev_depth);
-
-            let old_position = self
-                .render_list
-                .iter()
-                .position(|x| DisplayObject::ptr_eq(*x, child))
-                .unwrap();
-            self.render_list.remove(old_position);
-
-            if let Some((_, below_child)) = self.depth_list.range(..depth).rev().next() {
-                let new_position = self
-                    .render_list
-                    .iter()
-                    .position(|x| DisplayObject::ptr_eq(*x, *below_child))
-                    .unwrap();
-                self.render_list.insert(new_position + 1, child);
-            } else {
-                self.render_list.insert(0, child);
-            }
+            self.remove_child_from_render_list(child);
+            self.insert_into_render_list_by_depth(depth, child);
         }
     }
 
     /// Remove all children from the container's execution, render, and depth
     /// lists.
-    pub fn clear(&mut self, gc_context: MutationContext<'gc, '_>) {
-        let mut head = self.exec_list;
-
-        while let Some(child) = head {
-            let next_head = child.next_sibling();
-
-            child.set_next_sibling(gc_context, None);
-            child.set_prev_sibling(gc_context, None);
-
-            head = next_head;
-        }
-
-        self.exec_list = None;
+    pub fn clear(&mut self) {
+        self.exec_list.clear();
         self.render_list.clear();
         self.depth_list.clear();
     }
@@ -1037,22 +1043,70 @@ impl<'gc> ChildContainer<'gc> {
     pub fn iter_render_list<'a>(&'a self) -> impl 'a + Iterator<Item = DisplayObject<'gc>> {
         self.render_list.iter().copied()
     }
+
+    /// Yield children in the order their scripts are executed.
+    pub fn iter_execution_list<'a>(&'a self) -> impl 'a + Iterator<Item = DisplayObject<'gc>> {
+        self.exec_list.iter().rev().copied()
+    }
 }
 
 pub struct ExecIter<'gc> {
-    cur_child: Option<DisplayObject<'gc>>,
+    src: DisplayObjectContainer<'gc>,
+
+    /// The children on the execution list when iteration started, in the
+    /// order they are executed.
+    children: std::vec::IntoIter<DisplayObject<'gc>>,
+
+    /// The position on the execution list just past the next child, which
+    /// is walked back to front.
+    ///
+    /// Children added during iteration go on the end of the list, so this
+    /// only goes out of date when scripts remove or move children that come
+    /// before it, in which case the child is looked up again.
+    cursor: usize,
+}
+
+impl<'gc> ExecIter<'gc> {
+    fn from_container(src: DisplayObjectContainer<'gc>) -> Self {
+        let children = src.execution_list();
+        Self {
+            src,
+            cursor: children.len(),
+            children: children.into_iter(),
+        }
+    }
 }
 
 impl<'gc> Iterator for ExecIter<'gc> {
     type Item = DisplayObject<'gc>;
     fn next(&mut self) -> Option<Self::Item> {
-        let cur = self.cur_child;
+        let src = self.src;
+
+        // Scripts run by earlier children may have unloaded the container.
+        if DisplayObject::from(src).removed() {
+            return None;
+        }
+
+        for child in &mut self.children {
+            let expected = self
+                .cursor
+                .checked_sub(1)
+                .and_then(|index| src.child_by_exec_index(index));
+            let index = match expected {
+                Some(expected) if DisplayObject::ptr_eq(expected, child) => Some(self.cursor - 1),
+                _ => src.exec_index_of(child),
+            };
 
-        self.cur_child = self
-            .cur_child
-            .and_then(|display_cell| display_cell.next_sibling());
+            // Children that are no longer on the list are skipped.
+            if let Some(index) = index {
+                self.cursor = index;
+                if !child.removed() {
+                    return Some(child);
+                }
+            }
+        }
 
-        cur
+        None
     }
 }
 
@@ -11,11 +11,13 @@ use crate::avm2::{
 };
 use crate::backend::ui::MouseCursor;
 use crate::context::{RenderContext, UpdateContext};
-use crate::display_object::{DisplayObjectBase, TDisplayObject};
+use crate::display_object::{
+    construct_registered_avm1_class, invalidate_cached_bitmaps, DisplayObjectBase, TDisplayObject,
+};
 use crate::drawing::Drawing;
-use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult, KeyCode};
+use crate::events::{ButtonKeyCode, ClipEvent, ClipEventMask, ClipEventResult, KeyCode};
 use crate::font::{Glyph, TextRenderSettings};
-use crate::html::{BoxBounds, FormatSpans, LayoutBox, LayoutContent, TextFormat};
+use crate::html::{BoxBounds, FormatSpans, LayoutBox, LayoutContent, LineMetrics, TextFormat};
 use crate::prelude::*;
 use crate::shape_utils::DrawCommand;
 use crate::string_utils;
@@ -129,6 +131,15 @@ pub struct EditTextData<'gc> {
     /// The intrinsic bounds of the laid-out text.
     intrinsic_bounds: BoxBounds<Twips>,
 
+    /// The vertical metrics of each line of the laid-out text.
+    line_metrics: Vec<LineMetrics>,
+
+    /// The topmost visible line of text, starting at 1.
+    scroll: usize,
+
+    /// How far the text has been scrolled horizontally, in pixels.
+    hscroll: f64,
+
     /// The current intrinsic bounds of the text field.
     bounds: BoundingBox,
 
@@ -147,6 +158,10 @@ pub struct EditTextData<'gc> {
     /// The selected portion of the text, or None if the text is not selected.
     selection: Option<TextSelection>,
 
+    /// The link that the mouse was pressed on, which is followed if the
+    /// mouse is released over it.
+    pressed_link: Option<TextLink>,
+
     /// Whether or not this EditText has the current keyboard focus
     has_focus: bool,
 
@@ -154,6 +169,52 @@ pub struct EditTextData<'gc> {
     render_settings: TextRenderSettings,
 }
 
+impl<'gc> EditTextData<'gc> {
+    /// The width and height of the area that text is drawn into.
+    fn visible_bounds(&self) -> (Twips, Twips) {
+        let padding = Twips::from_pixels(EditText::INTERNAL_PADDING * 2.0);
+        (
+            self.bounds.width() - padding,
+            self.bounds.height() - padding,
+        )
+    }
+
+    fn maxscroll(&self) -> usize {
+        let visible_height = self.visible_bounds().1;
+        let text_bottom = match self.line_metrics.last() {
+            Some(line) => line.bottom(),
+            None => return 1,
+        };
+
+        // Find the first line that can be scrolled to without leaving empty
+        // space below the last line.
+        self.line_metrics
+            .iter()
+            .position(|line| text_bottom - line.offset() <= visible_height)
+            .unwrap_or(self.line_metrics.len() - 1)
+            + 1
+    }
+
+    fn maxhscroll(&self) -> f64 {
+        let visible_width = self.visible_bounds().0;
+        (self.intrinsic_bounds.extent_x() - visible_width)
+            .to_pixels()
+            .floor()
+            .max(0.0)
+    }
+
+    /// How far the text is currently offset by scrolling.
+    fn scroll_offset(&self) -> (Twips, Twips) {
+        let scroll_y = self
+            .line_metrics
+            .get(self.scroll - 1)
+            .map(LineMetrics::offset)
+            .unwrap_or_default();
+
+        (Twips::from_pixels(self.hscroll), scroll_y)
+    }
+}
+
 impl<'gc> EditText<'gc> {
     /// Creates a new `EditText` from an SWF `DefineEditText` tag.
     pub fn from_swf_tag(
@@ -196,7 +257,7 @@ impl<'gc> EditText<'gc> {
 
         let bounds: BoundingBox = swf_tag.bounds.clone().into();
 
-        let (layout, intrinsic_bounds) = LayoutBox::lower_from_text_spans(
+        let (layout, intrinsic_bounds, line_metrics) = LayoutBox::lower_from_text_spans(
             &text_spans,
             context,
             swf_movie.clone(),
@@ -273,12 +334,16 @@ impl<'gc> EditText<'gc> {
                 object: None,
                 layout,
                 intrinsic_bounds,
+                line_metrics,
+                scroll: 1,
+                hscroll: 0.0,
                 bounds,
                 autosize: AutoSizeMode::None,
                 variable: variable.map(|s| s.to_string_lossy(encoding)),
                 bound_stage_object: None,
                 firing_variable_binding: false,
                 selection: None,
+                pressed_link: None,
                 has_focus: false,
                 render_settings: Default::default(),
             },
@@ -730,6 +795,10 @@ impl<'gc> EditText<'gc> {
                 y: Twips::zero(),
             });
         }
+        drop(write);
+
+        // This is called whenever the text field moves or resizes.
+        invalidate_cached_bitmaps(self.into());
     }
 
     /// Internal padding between the bounds of the EditText and the text.
@@ -757,7 +826,7 @@ impl<'gc> EditText<'gc> {
             edit_text.text_spans.clear_displayed_text();
         }
 
-        let (new_layout, intrinsic_bounds) = LayoutBox::lower_from_text_spans(
+        let (new_layout, intrinsic_bounds, line_metrics) = LayoutBox::lower_from_text_spans(
             &edit_text.text_spans,
             context,
             movie,
@@ -768,6 +837,7 @@ impl<'gc> EditText<'gc> {
 
         edit_text.layout = new_layout;
         edit_text.intrinsic_bounds = intrinsic_bounds;
+        edit_text.line_metrics = line_metrics;
 
         match autosize {
             AutoSizeMode::None => {}
@@ -808,6 +878,69 @@ impl<'gc> EditText<'gc> {
                 self.redraw_border(context.gc_context);
             }
         }
+
+        // The text may have gotten shorter, so keep the scroll position in range.
+        let mut edit_text = self.0.write(context.gc_context);
+        edit_text.scroll = edit_text.scroll.min(edit_text.maxscroll());
+        edit_text.hscroll = edit_text.hscroll.min(edit_text.maxhscroll());
+    }
+
+    /// The topmost visible line of text, starting at 1.
+    pub fn scroll(self) -> usize {
+        self.0.read().scroll
+    }
+
+    /// Scroll the text so that the given line is the topmost visible line.
+    ///
+    /// The line is clamped so that the field never scrolls past `maxscroll`.
+    pub fn set_scroll(self, scroll: usize, gc_context: MutationContext<'gc, '_>) {
+        let mut edit_text = self.0.write(gc_context);
+        edit_text.scroll = scroll.max(1).min(edit_text.maxscroll());
+    }
+
+    /// The largest value that `scroll` can take, which is the topmost line
+    /// when the last line of text is at the bottom of the field.
+    pub fn maxscroll(self) -> usize {
+        self.0.read().maxscroll()
+    }
+
+    /// The bottommost line of text that is fully visible, starting at 1.
+    pub fn bottom_scroll(self) -> usize {
+        let edit_text = self.0.read();
+        let visible_height = edit_text.visible_bounds().1;
+        let top = edit_text
+            .line_metrics
+            .get(edit_text.scroll - 1)
+            .map(LineMetrics::offset)
+            .unwrap_or_default();
+        let visible_lines = edit_text
+            .line_metrics
+            .iter()
+            .skip(edit_text.scroll - 1)
+            .take_while(|line| line.bottom() - top <= visible_height)
+            .count();
+
+        edit_text.scroll + visible_lines.max(1) - 1
+    }
+
+    /// How far the text has been scrolled horizontally, in pixels.
+    pub fn hscroll(self) -> f64 {
+        self.0.read().hscroll
+    }
+
+    pub fn set_hscroll(self, hscroll: f64, gc_context: MutationContext<'gc, '_>) {
+        let mut edit_text = self.0.write(gc_context);
+        let maxhscroll = edit_text.maxhscroll();
+        edit_text.hscroll = if hscroll.is_nan() {
+            0.0
+        } else {
+            hscroll.floor().max(0.0).min(maxhscroll)
+        };
+    }
+
+    /// The largest value that `hscroll` can take, in pixels.
+    pub fn maxhscroll(self) -> f64 {
+        self.0.read().maxhscroll()
     }
 
     /// Measure the width and height of the `EditText`'s current text load.
@@ -1099,9 +1232,10 @@ impl<'gc> EditText<'gc> {
     pub fn screen_position_to_index(self, position: (Twips, Twips)) -> Option<usize> {
         let text = self.0.read();
         let position = self.global_to_local(position);
+        let (scroll_x, scroll_y) = text.scroll_offset();
         let position = (
-            position.0 + Twips::from_pixels(Self::INTERNAL_PADDING),
-            position.1 + Twips::from_pixels(Self::INTERNAL_PADDING),
+            position.0 + Twips::from_pixels(Self::INTERNAL_PADDING) + scroll_x,
+            position.1 + Twips::from_pixels(Self::INTERNAL_PADDING) + scroll_y,
         );
 
         for layout_box in text.layout.iter() {
@@ -1143,6 +1277,91 @@ impl<'gc> EditText<'gc> {
         None
     }
 
+    /// The link at a position on the screen, if any.
+    pub fn link_at(self, position: (Twips, Twips)) -> Option<TextLink> {
+        let text = self.0.read();
+        let position = self.global_to_local(position);
+        let (scroll_x, scroll_y) = text.scroll_offset();
+        let position = (
+            position.0 + Twips::from_pixels(Self::INTERNAL_PADDING) + scroll_x,
+            position.1 + Twips::from_pixels(Self::INTERNAL_PADDING) + scroll_y,
+        );
+
+        text.layout.iter().find_map(|layout_box| {
+            let bounds = layout_box.bounds();
+            if position.0 < bounds.offset_x()
+                || position.0 > bounds.extent_x()
+                || position.1 < bounds.offset_y()
+                || position.1 > bounds.extent_y()
+            {
+                return None;
+            }
+
+            match layout_box.content() {
+                LayoutContent::Text { text_format, .. } => {
+                    let url = text_format.url.as_deref().filter(|url| !url.is_empty())?;
+                    Some(TextLink {
+                        url: url.to_string(),
+                        target: text_format.target.clone().unwrap_or_default(),
+                    })
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Follow a link that was clicked.
+    ///
+    /// An `asfunction:` link calls the ActionScript function named in it,
+    /// which is looked up from the text field's parent and passed everything
+    /// after the first comma as a string. Any other link is opened like
+    /// `getURL`.
+    fn follow_link(self, context: &mut UpdateContext<'_, 'gc, '_>, link: &TextLink) {
+        let asfunction = link
+            .url
+            .get(..11)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("asfunction:"))
+            .map(|_| &link.url[11..]);
+        let asfunction = match asfunction {
+            Some(asfunction) => asfunction,
+            None => {
+                let target = Some(link.target.clone()).filter(|target| !target.is_empty());
+                context.navigate_to_url(self.into(), link.url.clone(), target, None);
+                return;
+            }
+        };
+
+        let mut parts = asfunction.splitn(2, ',');
+        let function_path = parts.next().unwrap_or_default().trim();
+        let argument = parts.next().unwrap_or_default();
+        let parent = self.parent().unwrap_or_else(|| self.into());
+        let this = match parent.object() {
+            Avm1Value::Object(this) => this,
+            _ => return,
+        };
+
+        Avm1::run_with_stack_frame_for_display_object(
+            parent,
+            self.swf_version(),
+            context,
+            |activation| {
+                let argument = AvmString::new(activation.context.gc_context, argument).into();
+                let result = activation.get_variable(function_path).and_then(|function| {
+                    function.call_with_default_this(
+                        this,
+                        function_path,
+                        activation,
+                        None,
+                        &[argument],
+                    )
+                });
+                if let Err(e) = result {
+                    log::warn!("Couldn't call asfunction {}: {}", function_path, e);
+                }
+            },
+        );
+    }
+
     pub fn text_input(self, character: char, context: &mut UpdateContext<'_, 'gc, '_>) {
         if !self.0.read().is_editable {
             return;
@@ -1251,6 +1470,27 @@ impl<'gc> EditText<'gc> {
         }
     }
 
+    /// Fetch the AVM1 constructor associated with this text field's symbol by
+    /// `Object.registerClass`.
+    ///
+    /// Registered classes only apply to text fields from SWF 9 and later
+    /// movies.
+    fn registered_avm1_constructor(
+        &self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> Option<Avm1Object<'gc>> {
+        let static_data = self.0.read().static_data;
+        if static_data.swf.version() < 9 {
+            return None;
+        }
+        let library = context
+            .library
+            .library_for_movie_mut(static_data.swf.clone());
+        let symbol_name = library.export_name(static_data.text.id)?;
+        let ctor = library.avm1_registered_constructor(symbol_name)?;
+        Some(Avm1Object::FunctionObject(ctor))
+    }
+
     /// Construct the text field's AVM1 representation.
     fn construct_as_avm1_object(
         &self,
@@ -1258,18 +1498,20 @@ impl<'gc> EditText<'gc> {
         display_object: DisplayObject<'gc>,
         run_frame: bool,
     ) {
-        let mut text = self.0.write(context.gc_context);
-        if text.object.is_none() {
+        if self.0.read().object.is_none() {
             let object: Avm1Object<'gc> = Avm1StageObject::for_display_object(
                 context.gc_context,
                 display_object,
                 Some(context.avm1.prototypes().text_field),
             )
             .into();
+            self.0.write(context.gc_context).object = Some(object.into());
 
-            text.object = Some(object.into());
+            // Registered classes are applied as soon as the text field exists.
+            if let Some(constructor) = self.registered_avm1_constructor(context) {
+                construct_registered_avm1_class(context, display_object, object, constructor);
+            }
         }
-        drop(text);
 
         Avm1::run_with_stack_frame_for_display_object(
             (*self).into(),
@@ -1473,8 +1715,14 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
         self.redraw_border(gc_context);
     }
 
+    fn content_revision(&self) -> Option<u64> {
+        // Text, scrolling and the caret all change the way a text field is
+        // drawn, so it is always redrawn.
+        None
+    }
+
     fn render_self(&self, context: &mut RenderContext<'_, 'gc>) {
-        if !self.world_bounds().intersects(&context.stage.view_bounds()) {
+        if context.is_culled(&self.world_bounds()) {
             // Off-screen; culled
             return;
         }
@@ -1509,10 +1757,11 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
 
         // TODO: Where does this come from? How is this different than INTERNAL_PADDING? Does this apply to y as well?
         // If this is actually right, offset the border in `redraw_border` instead of doing an extra push.
+        let (scroll_x, scroll_y) = edit_text.scroll_offset();
         context.transform_stack.push(&Transform {
             matrix: Matrix {
-                tx: Twips::from_pixels(Self::INTERNAL_PADDING),
-                ty: Twips::from_pixels(Self::INTERNAL_PADDING),
+                tx: Twips::from_pixels(Self::INTERNAL_PADDING) - scroll_x,
+                ty: Twips::from_pixels(Self::INTERNAL_PADDING) - scroll_y,
                 ..Default::default()
             },
             ..Default::default()
@@ -1603,9 +1852,9 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
         self_node: DisplayObject<'gc>,
         point: (Twips, Twips),
     ) -> Option<DisplayObject<'gc>> {
-        // The button is hovered if the mouse is over any child nodes.
+        // Links can be clicked even if the text can't be selected.
         if self.visible()
-            && self.is_selectable()
+            && (self.is_selectable() || self.link_at(point).is_some())
             && self.hit_test_shape(
                 context,
                 point,
@@ -1621,8 +1870,14 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
         }
     }
 
-    fn mouse_cursor(&self) -> MouseCursor {
-        MouseCursor::IBeam
+    fn mouse_cursor(&self, context: &mut UpdateContext<'_, 'gc, '_>) -> MouseCursor {
+        if self.link_at(*context.mouse_position).is_some() {
+            MouseCursor::Hand
+        } else if self.is_selectable() {
+            MouseCursor::IBeam
+        } else {
+            MouseCursor::Arrow
+        }
     }
 
     fn on_focus_changed(&self, gc_context: MutationContext<'gc, '_>, focused: bool) {
@@ -1638,6 +1893,11 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
         true
     }
 
+    fn clip_event_interest(&self, _context: &mut UpdateContext<'_, 'gc, '_>) -> ClipEventMask {
+        // Text fields receive typed characters while they have focus.
+        ClipEventMask::KEY_PRESS
+    }
+
     fn handle_clip_event(
         &self,
         context: &mut UpdateContext<'_, 'gc, '_>,
@@ -1645,6 +1905,12 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
     ) -> ClipEventResult {
         match event {
             ClipEvent::Press => {
+                let link = self.link_at(*context.mouse_position);
+                self.0.write(context.gc_context).pressed_link = link;
+                if !self.is_selectable() {
+                    return ClipEventResult::Handled;
+                }
+
                 let tracker = context.focus_tracker;
                 tracker.set(Some((*self).into()), context);
                 if let Some(position) = self
@@ -1658,6 +1924,25 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
                 }
                 ClipEventResult::Handled
             }
+            ClipEvent::Release => {
+                let pressed_link = self.0.write(context.gc_context).pressed_link.take();
+                let is_selecting = self
+                    .0
+                    .read()
+                    .selection
+                    .map_or(false, |selection| !selection.is_caret());
+                match pressed_link {
+                    // Dragging out a selection from a link doesn't follow it.
+                    Some(link)
+                        if !is_selecting
+                            && self.link_at(*context.mouse_position).as_ref() == Some(&link) =>
+                    {
+                        self.follow_link(context, &link);
+                        ClipEventResult::Handled
+                    }
+                    _ => ClipEventResult::NotHandled,
+                }
+            }
             ClipEvent::KeyPress { key_code } => {
                 let mut edit_text = self.0.write(context.gc_context);
                 let selection = edit_text.selection;
@@ -1737,6 +2022,17 @@ struct EditTextStaticData {
     is_device_font: bool,
 }
 
+/// A link in the text of a text field.
+#[derive(Clone, Debug, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+pub struct TextLink {
+    /// The URL that the link opens, or an `asfunction:` call.
+    pub url: String,
+
+    /// The window that the URL is opened in, or empty for the current one.
+    pub target: String,
+}
+
 #[derive(Copy, Clone, Debug, Collect)]
 #[collect(require_static)]
 pub struct TextSelection {
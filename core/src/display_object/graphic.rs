@@ -60,6 +60,38 @@ impl<'gc> Graphic<'gc> {
         ))
     }
 
+    /// Replace the shape of this graphic with a new `Shape` tag.
+    ///
+    /// The new shape is registered with the same render handle as the old
+    /// one, so every existing instance of this graphic is drawn with it.
+    /// Only instances created afterwards pick up its new bounds, though.
+    pub fn replace_swf_tag(self, context: &mut UpdateContext<'_, 'gc, '_>, swf_shape: swf::Shape) {
+        let mut write = self.0.write(context.gc_context);
+        let movie = write.static_data.movie.clone();
+        let library = movie
+            .clone()
+            .and_then(|movie| context.library.library_for_movie(movie));
+        let render_handle = if let Some(handle) = write.static_data.render_handle {
+            context
+                .renderer
+                .replace_shape((&swf_shape).into(), library, handle);
+            handle
+        } else {
+            context
+                .renderer
+                .register_shape((&swf_shape).into(), library)
+        };
+
+        let static_data = GraphicStatic {
+            id: swf_shape.id,
+            bounds: swf_shape.shape_bounds.clone().into(),
+            render_handle: Some(render_handle),
+            shape: swf_shape,
+            movie,
+        };
+        write.static_data = gc_arena::Gc::allocate(context.gc_context, static_data);
+    }
+
     /// Construct an empty `Graphic`.
     pub fn new_with_avm2(
         context: &mut UpdateContext<'_, 'gc, '_>,
@@ -150,8 +182,17 @@ impl<'gc> TDisplayObject<'gc> for Graphic<'gc> {
         // Noop
     }
 
+    fn content_revision(&self) -> Option<u64> {
+        // Drawings can be changed by scripts at any time.
+        if self.0.read().drawing.is_some() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
     fn render_self(&self, context: &mut RenderContext) {
-        if !self.world_bounds().intersects(&context.stage.view_bounds()) {
+        if context.is_culled(&self.world_bounds()) {
             // Off-screen; culled
             return;
         }
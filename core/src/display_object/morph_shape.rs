@@ -1,10 +1,12 @@
-use crate::backend::render::ShapeHandle;
+use crate::backend::render::{MovieLibrary, RenderBackend, ShapeHandle};
 use crate::context::{RenderContext, UpdateContext};
-use crate::display_object::{DisplayObjectBase, TDisplayObject};
+use crate::display_object::{invalidate_cached_bitmaps, DisplayObjectBase, TDisplayObject};
 use crate::prelude::*;
+use crate::shape_utils::morph_shape_at_ratio;
 use crate::tag_utils::SwfMovie;
-use crate::types::{Degrees, Percent};
 use gc_arena::{Collect, Gc, GcCell, MutationContext};
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::sync::Arc;
 use swf::Twips;
 
@@ -41,6 +43,7 @@ impl<'gc> MorphShape<'gc> {
 
     pub fn set_ratio(&mut self, gc_context: MutationContext<'gc, '_>, ratio: u16) {
         self.0.write(gc_context).ratio = ratio;
+        invalidate_cached_bitmaps((*self).into());
     }
 }
 
@@ -59,23 +62,33 @@ impl<'gc> TDisplayObject<'gc> for MorphShape<'gc> {
         // Noop
     }
 
+    fn content_revision(&self) -> Option<u64> {
+        Some(self.ratio().into())
+    }
+
     fn render_self(&self, context: &mut RenderContext) {
-        if let Some(frame) = self.0.read().static_data.frames.get(&self.ratio()) {
-            context
-                .renderer
-                .render_shape(frame.shape_handle, context.transform_stack.transform());
-        } else {
-            log::warn!("Missing ratio for morph shape");
-        }
+        let static_data = self.0.read().static_data;
+        let library = context
+            .library
+            .library_for_movie(Arc::clone(&static_data.movie));
+        let shape_handle = static_data.shape_handle(
+            self.ratio(),
+            context.stage.render_generation(),
+            context.renderer,
+            library,
+        );
+        context
+            .renderer
+            .render_shape(shape_handle, context.transform_stack.transform());
     }
 
     fn self_bounds(&self) -> BoundingBox {
-        // TODO: Use the bounds of the current ratio.
-        if let Some(frame) = self.0.read().static_data.frames.get(&self.ratio()) {
-            frame.bounds.clone()
-        } else {
-            BoundingBox::default()
-        }
+        let static_data = self.0.read().static_data;
+        let mut frames = static_data.frames.borrow_mut();
+        frames
+            .frame(&static_data.start, &static_data.end, self.ratio())
+            .bounds
+            .clone()
     }
 
     fn hit_test_shape(
@@ -85,431 +98,213 @@ impl<'gc> TDisplayObject<'gc> for MorphShape<'gc> {
         _options: HitTestOptions,
     ) -> bool {
         if self.world_bounds().contains(point) {
-            if let Some(frame) = self.0.read().static_data.frames.get(&self.ratio()) {
-                let local_matrix = self.global_to_local_matrix();
-                let point = local_matrix * point;
-                return crate::shape_utils::shape_hit_test(&frame.shape, point, &local_matrix);
-            } else {
-                log::warn!("Missing ratio for morph shape");
-            }
+            let static_data = self.0.read().static_data;
+            let mut frames = static_data.frames.borrow_mut();
+            let frame = frames.frame(&static_data.start, &static_data.end, self.ratio());
+            let local_matrix = self.global_to_local_matrix();
+            let point = local_matrix * point;
+            return crate::shape_utils::shape_hit_test(&frame.shape, point, &local_matrix);
         }
 
         false
     }
 }
 
-/// A precalculated intermediate frame for a morph shape.
+/// The most interpolated frames that are kept for each morph shape, unless
+/// more are drawn by a single render.
+const MAX_CACHED_FRAMES: usize = 8;
+
+/// An intermediate frame of a morph shape, interpolated at some ratio.
 struct Frame {
-    shape_handle: ShapeHandle,
+    ratio: u16,
     shape: swf::Shape,
     bounds: BoundingBox,
-}
 
-/// Static data shared between all instances of a morph shape.
-#[allow(dead_code)]
-#[derive(Collect)]
-#[collect(require_static)]
-pub struct MorphShapeStatic {
-    id: CharacterId,
-    start: swf::MorphShape,
-    end: swf::MorphShape,
-    frames: fnv::FnvHashMap<u16, Frame>,
-    movie: Arc<SwfMovie>,
+    /// The shape registered with the renderer, once the frame has been drawn.
+    shape_handle: Option<ShapeHandle>,
+
+    /// The render that last drew this frame.
+    drawn_in: Option<u64>,
 }
 
-impl MorphShapeStatic {
-    pub fn from_swf_tag(
-        context: &mut UpdateContext<'_, '_, '_>,
-        swf_tag: &swf::DefineMorphShape,
-        movie: Arc<SwfMovie>,
-    ) -> Self {
-        let mut morph_shape = Self {
-            id: swf_tag.id,
-            start: swf_tag.start.clone(),
-            end: swf_tag.end.clone(),
-            frames: fnv::FnvHashMap::default(),
-            movie,
-        };
-        // Pre-register the start and end states.
-        morph_shape.register_ratio(context, 0);
-        morph_shape.register_ratio(context, 65535);
-        morph_shape
-    }
+/// The most recently used frames of a morph shape.
+#[derive(Default)]
+struct FrameCache {
+    /// The cached frames, from the most to the least recently used.
+    frames: VecDeque<Frame>,
 
-    pub fn register_ratio(&mut self, context: &mut UpdateContext<'_, '_, '_>, ratio: u16) {
-        if self.frames.contains_key(&ratio) {
-            // Already registered.
-            return;
-        }
+    /// The shapes of evicted frames, which are replaced by the next frames
+    /// to be drawn rather than registering new shapes with the renderer.
+    free_handles: Vec<ShapeHandle>,
 
-        let library = context.library.library_for_movie(Arc::clone(&self.movie));
+    /// The latest render that drew any of the frames.
+    current_render: u64,
+}
 
-        // Interpolate MorphShapes into a Shape.
-        use swf::{FillStyle, LineStyle, ShapeRecord, ShapeStyles};
-        // Start shape is ratio 65535, end shape is ratio 0.
-        let b = f32::from(ratio) / 65535.0;
-        let a = 1.0 - b;
-        let fill_styles: Vec<FillStyle> = self
-            .start
-            .fill_styles
-            .iter()
-            .zip(self.end.fill_styles.iter())
-            .map(|(start, end)| lerp_fill(start, end, a, b))
-            .collect();
-        let line_styles: Vec<LineStyle> = self
-            .start
-            .line_styles
-            .iter()
-            .zip(self.end.line_styles.iter())
-            .map(|(start, end)| LineStyle {
-                width: lerp_twips(start.width, end.width, a, b),
-                color: lerp_color(&start.color, &end.color, a, b),
-                start_cap: start.start_cap,
-                end_cap: start.end_cap,
-                join_style: start.join_style,
-                fill_style: None,
-                allow_scale_x: start.allow_scale_x,
-                allow_scale_y: start.allow_scale_y,
-                is_pixel_hinted: start.is_pixel_hinted,
-                allow_close: start.allow_close,
-            })
-            .collect();
-
-        let mut shape = Vec::with_capacity(self.start.shape.len());
-        let mut start_iter = self.start.shape.iter();
-        let mut end_iter = self.end.shape.iter();
-        let mut start = start_iter.next();
-        let mut end = end_iter.next();
-        let mut start_x = Twips::zero();
-        let mut start_y = Twips::zero();
-        let mut end_x = Twips::zero();
-        let mut end_y = Twips::zero();
-        // TODO: Feels like this could be cleaned up a bit.
-        // We step through both the start records and end records, interpolating edges pairwise.
-        // Fill style/line style changes should only appear in the start records.
-        // However, StyleChangeRecord move_to can appear it both start and end records,
-        // and not necessarily in matching pairs; therefore, we have to keep track of the pen position
-        // in case one side is missing a move_to; it will implicitly use the last pen position.
-        while let (Some(s), Some(e)) = (start, end) {
-            match (s, e) {
-                (ShapeRecord::StyleChange(start_change), ShapeRecord::StyleChange(end_change)) => {
-                    let mut style_change = start_change.clone();
-                    if start_change.move_to != end_change.move_to {
-                        if let Some((s_x, s_y)) = start_change.move_to {
-                            start_x = s_x;
-                            start_y = s_y;
-                        }
-                        if let Some((e_x, e_y)) = end_change.move_to {
-                            end_x = e_x;
-                            end_y = e_y;
-                        }
-                        style_change.move_to = Some((
-                            lerp_twips(start_x, end_x, a, b),
-                            lerp_twips(start_y, end_y, a, b),
-                        ));
-                    }
-                    shape.push(ShapeRecord::StyleChange(style_change));
-                    start = start_iter.next();
-                    end = end_iter.next();
-                }
-                (ShapeRecord::StyleChange(start_change), _) => {
-                    let mut style_change = start_change.clone();
-                    if let Some((s_x, s_y)) = start_change.move_to {
-                        start_x = s_x;
-                        start_y = s_y;
-                        style_change.move_to = Some((
-                            lerp_twips(start_x, end_x, a, b),
-                            lerp_twips(start_y, end_y, a, b),
-                        ));
-                    }
-                    shape.push(ShapeRecord::StyleChange(style_change));
-                    Self::update_pos(&mut start_x, &mut start_y, s);
-                    start = start_iter.next();
-                }
-                (_, ShapeRecord::StyleChange(end_change)) => {
-                    let mut style_change = end_change.clone();
-                    if let Some((e_x, e_y)) = end_change.move_to {
-                        end_x = e_x;
-                        end_y = e_y;
-                        style_change.move_to = Some((
-                            lerp_twips(start_x, end_x, a, b),
-                            lerp_twips(start_y, end_y, a, b),
-                        ));
+impl FrameCache {
+    /// Returns the frame at the given ratio, interpolating it if it isn't
+    /// cached.
+    fn frame(&mut self, start: &swf::MorphShape, end: &swf::MorphShape, ratio: u16) -> &mut Frame {
+        if let Some(index) = self.frames.iter().position(|frame| frame.ratio == ratio) {
+            if index > 0 {
+                let frame = self.frames.remove(index).unwrap();
+                self.frames.push_front(frame);
+            }
+        } else {
+            // Frames drawn by the current render are kept, as the renderer
+            // may still draw their shapes. The cache grows instead when more
+            // instances than it holds are drawn at different ratios.
+            let current_render = Some(self.current_render);
+            while self.frames.len() >= MAX_CACHED_FRAMES {
+                match self
+                    .frames
+                    .iter()
+                    .rposition(|frame| frame.drawn_in != current_render)
+                {
+                    Some(index) => {
+                        let evicted = self.frames.remove(index).unwrap();
+                        self.free_handles.extend(evicted.shape_handle);
                     }
-                    shape.push(ShapeRecord::StyleChange(style_change));
-                    Self::update_pos(&mut end_x, &mut end_y, s);
-                    end = end_iter.next();
-                    continue;
-                }
-                _ => {
-                    shape.push(lerp_edges(s, e, a, b));
-                    Self::update_pos(&mut start_x, &mut start_y, s);
-                    Self::update_pos(&mut end_x, &mut end_y, e);
-                    start = start_iter.next();
-                    end = end_iter.next();
+                    None => break,
                 }
             }
+            let shape = morph_shape_at_ratio(start, end, ratio);
+            let bounds = shape.shape_bounds.clone().into();
+            self.frames.push_front(Frame {
+                ratio,
+                shape,
+                bounds,
+                shape_handle: None,
+                drawn_in: None,
+            });
         }
-
-        let styles = ShapeStyles {
-            fill_styles,
-            line_styles,
-        };
-
-        let bounds = crate::shape_utils::calculate_shape_bounds(&shape[..]);
-        let shape = swf::Shape {
-            version: 4,
-            id: 0,
-            shape_bounds: bounds.clone(),
-            edge_bounds: bounds.clone(),
-            has_fill_winding_rule: false,
-            has_non_scaling_strokes: false,
-            has_scaling_strokes: true,
-            styles,
-            shape,
-        };
-
-        let frame = Frame {
-            shape_handle: context.renderer.register_shape((&shape).into(), library),
-            shape,
-            bounds: bounds.into(),
-        };
-        self.frames.insert(ratio, frame);
+        &mut self.frames[0]
     }
 
-    fn update_pos(x: &mut Twips, y: &mut Twips, record: &swf::ShapeRecord) {
-        use swf::ShapeRecord;
-        match record {
-            ShapeRecord::StraightEdge { delta_x, delta_y } => {
-                *x += *delta_x;
-                *y += *delta_y;
-            }
-            ShapeRecord::CurvedEdge {
-                control_delta_x,
-                control_delta_y,
-                anchor_delta_x,
-                anchor_delta_y,
-            } => {
-                *x += *control_delta_x + *anchor_delta_x;
-                *y += *control_delta_y + *anchor_delta_y;
-            }
-            ShapeRecord::StyleChange(ref style_change) => {
-                if let Some((move_x, move_y)) = style_change.move_to {
-                    *x = move_x;
-                    *y = move_y;
-                }
-            }
+    /// Returns the renderer's shape for the frame at the given ratio, to be
+    /// drawn by the given render. Frames that haven't been drawn recently are
+    /// passed to `register`, along with a free shape that it may replace.
+    fn shape_handle(
+        &mut self,
+        start: &swf::MorphShape,
+        end: &swf::MorphShape,
+        ratio: u16,
+        render: u64,
+        register: impl FnOnce(&swf::Shape, Option<ShapeHandle>) -> ShapeHandle,
+    ) -> ShapeHandle {
+        self.current_render = render;
+        self.frame(start, end, ratio);
+        let frame = &mut self.frames[0];
+        frame.drawn_in = Some(render);
+        if let Some(shape_handle) = frame.shape_handle {
+            return shape_handle;
         }
-    }
-}
 
-// Interpolation functions
-// These interpolate between two SWF shape structures.
-// a + b should = 1.0
-
-fn lerp_color(start: &Color, end: &Color, a: f32, b: f32) -> Color {
-    // f32 -> u8 cast is defined to saturate for out of bounds values,
-    // so we don't have to worry about clamping.
-    Color {
-        r: (a * f32::from(start.r) + b * f32::from(end.r)) as u8,
-        g: (a * f32::from(start.g) + b * f32::from(end.g)) as u8,
-        b: (a * f32::from(start.b) + b * f32::from(end.b)) as u8,
-        a: (a * f32::from(start.a) + b * f32::from(end.a)) as u8,
+        let shape_handle = register(&frame.shape, self.free_handles.pop());
+        frame.shape_handle = Some(shape_handle);
+        shape_handle
     }
 }
 
-fn lerp_twips(start: Twips, end: Twips, a: f32, b: f32) -> Twips {
-    Twips::new((start.get() as f32 * a + end.get() as f32 * b) as i32)
+/// Static data shared between all instances of a morph shape.
+#[derive(Collect)]
+#[collect(require_static)]
+pub struct MorphShapeStatic {
+    id: CharacterId,
+    start: swf::MorphShape,
+    end: swf::MorphShape,
+    frames: RefCell<FrameCache>,
+    movie: Arc<SwfMovie>,
 }
 
-fn lerp_fill(start: &swf::FillStyle, end: &swf::FillStyle, a: f32, b: f32) -> swf::FillStyle {
-    use swf::FillStyle;
-    match (start, end) {
-        // Color-to-color
-        (FillStyle::Color(start), FillStyle::Color(end)) => {
-            FillStyle::Color(lerp_color(start, end, a, b))
-        }
-
-        // Bitmap-to-bitmap
-        // ID should be the same.
-        (
-            FillStyle::Bitmap {
-                id: start_id,
-                matrix: start,
-                is_smoothed,
-                is_repeating,
-            },
-            FillStyle::Bitmap { matrix: end, .. },
-        ) => FillStyle::Bitmap {
-            id: *start_id,
-            matrix: lerp_matrix(start, end, a, b),
-            is_smoothed: *is_smoothed,
-            is_repeating: *is_repeating,
-        },
-
-        // Linear-to-linear
-        (FillStyle::LinearGradient(start), FillStyle::LinearGradient(end)) => {
-            FillStyle::LinearGradient(lerp_gradient(start, end, a, b))
-        }
-
-        // Radial-to-radial
-        (FillStyle::RadialGradient(start), FillStyle::RadialGradient(end)) => {
-            FillStyle::RadialGradient(lerp_gradient(start, end, a, b))
-        }
-
-        // Focal gradients also interpolate focal point.
-        (
-            FillStyle::FocalGradient {
-                gradient: start,
-                focal_point: start_focal,
-            },
-            FillStyle::FocalGradient {
-                gradient: end,
-                focal_point: end_focal,
-            },
-        ) => FillStyle::FocalGradient {
-            gradient: lerp_gradient(start, end, a, b),
-            focal_point: a * start_focal + b * end_focal,
-        },
-
-        // All other combinations should not occur, because SWF stores the start/end fill as the same type, always.
-        // If you happened to make, say, a solid color-to-radial gradient tween in the IDE, this would get baked down into
-        // a radial-to-radial gradient on export.
-        _ => {
-            log::warn!(
-                "Unexpected morph shape fill style combination: {:#?}, {:#?}",
-                start,
-                end
-            );
-            start.clone()
+impl MorphShapeStatic {
+    pub fn from_swf_tag(swf_tag: &swf::DefineMorphShape, movie: Arc<SwfMovie>) -> Self {
+        Self {
+            id: swf_tag.id,
+            start: swf_tag.start.clone(),
+            end: swf_tag.end.clone(),
+            frames: RefCell::new(FrameCache::default()),
+            movie,
         }
     }
-}
 
-fn lerp_edges(
-    start: &swf::ShapeRecord,
-    end: &swf::ShapeRecord,
-    a: f32,
-    b: f32,
-) -> swf::ShapeRecord {
-    use swf::ShapeRecord;
-    match (start, end) {
-        (
-            &ShapeRecord::StraightEdge {
-                delta_x: start_dx,
-                delta_y: start_dy,
-            },
-            &ShapeRecord::StraightEdge {
-                delta_x: end_dx,
-                delta_y: end_dy,
-            },
-        ) => ShapeRecord::StraightEdge {
-            delta_x: lerp_twips(start_dx, end_dx, a, b),
-            delta_y: lerp_twips(start_dy, end_dy, a, b),
-        },
-
-        (
-            &ShapeRecord::CurvedEdge {
-                control_delta_x: start_cdx,
-                control_delta_y: start_cdy,
-                anchor_delta_x: start_adx,
-                anchor_delta_y: start_ady,
-            },
-            &ShapeRecord::CurvedEdge {
-                control_delta_x: end_cdx,
-                control_delta_y: end_cdy,
-                anchor_delta_x: end_adx,
-                anchor_delta_y: end_ady,
-            },
-        ) => ShapeRecord::CurvedEdge {
-            control_delta_x: lerp_twips(start_cdx, end_cdx, a, b),
-            control_delta_y: lerp_twips(start_cdy, end_cdy, a, b),
-            anchor_delta_x: lerp_twips(start_adx, end_adx, a, b),
-            anchor_delta_y: lerp_twips(start_ady, end_ady, a, b),
-        },
-
-        (
-            &ShapeRecord::StraightEdge {
-                delta_x: start_dx,
-                delta_y: start_dy,
-            },
-            &ShapeRecord::CurvedEdge {
-                control_delta_x: end_cdx,
-                control_delta_y: end_cdy,
-                anchor_delta_x: end_adx,
-                anchor_delta_y: end_ady,
-            },
-        ) => {
-            let start_cdx = start_dx / 2;
-            let start_cdy = start_dy / 2;
-            let start_adx = start_cdx;
-            let start_ady = start_cdy;
-            ShapeRecord::CurvedEdge {
-                control_delta_x: lerp_twips(start_cdx, end_cdx, a, b),
-                control_delta_y: lerp_twips(start_cdy, end_cdy, a, b),
-                anchor_delta_x: lerp_twips(start_adx, end_adx, a, b),
-                anchor_delta_y: lerp_twips(start_ady, end_ady, a, b),
-            }
-        }
-
-        (
-            &ShapeRecord::CurvedEdge {
-                control_delta_x: start_cdx,
-                control_delta_y: start_cdy,
-                anchor_delta_x: start_adx,
-                anchor_delta_y: start_ady,
-            },
-            &ShapeRecord::StraightEdge {
-                delta_x: end_dx,
-                delta_y: end_dy,
+    /// Returns the renderer's shape for the given ratio, registering it if
+    /// it hasn't been drawn recently.
+    fn shape_handle(
+        &self,
+        ratio: u16,
+        render: u64,
+        renderer: &mut dyn RenderBackend,
+        library: Option<&MovieLibrary<'_>>,
+    ) -> ShapeHandle {
+        self.frames.borrow_mut().shape_handle(
+            &self.start,
+            &self.end,
+            ratio,
+            render,
+            |shape, free_handle| {
+                let shape = shape.into();
+                if let Some(shape_handle) = free_handle {
+                    renderer.replace_shape(shape, library, shape_handle);
+                    shape_handle
+                } else {
+                    renderer.register_shape(shape, library)
+                }
             },
-        ) => {
-            let end_cdx = end_dx / 2;
-            let end_cdy = end_dy / 2;
-            let end_adx = end_cdx;
-            let end_ady = end_cdy;
-            ShapeRecord::CurvedEdge {
-                control_delta_x: lerp_twips(start_cdx, end_cdx, a, b),
-                control_delta_y: lerp_twips(start_cdy, end_cdy, a, b),
-                anchor_delta_x: lerp_twips(start_adx, end_adx, a, b),
-                anchor_delta_y: lerp_twips(start_ady, end_ady, a, b),
-            }
-        }
-        _ => unreachable!("{:?} {:?}", start, end),
+        )
     }
 }
 
-fn lerp_matrix(start: &swf::Matrix, end: &swf::Matrix, a: f32, b: f32) -> swf::Matrix {
-    // TODO: Lerping a matrix element-wise is geometrically wrong,
-    // but I doubt Flash is decomposing the matrix into scale-rotate-translate?
-    swf::Matrix {
-        a: start.a * a + end.a * b,
-        b: start.b * a + end.b * b,
-        c: start.c * a + end.c * b,
-        d: start.d * a + end.d * b,
-        tx: lerp_twips(start.tx, end.tx, a, b),
-        ty: lerp_twips(start.ty, end.ty, a, b),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws a frame at each ratio in one render, and returns the shapes
+    /// that were drawn. New shapes are numbered by counting `next` up.
+    fn draw(cache: &mut FrameCache, ratios: &[u16], render: u64, next: &mut usize) -> Vec<usize> {
+        let empty = swf::MorphShape {
+            shape_bounds: Default::default(),
+            edge_bounds: Default::default(),
+            fill_styles: vec![],
+            line_styles: vec![],
+            shape: vec![],
+        };
+        ratios
+            .iter()
+            .map(|&ratio| {
+                let handle = cache.shape_handle(&empty, &empty, ratio, render, |_, free| {
+                    free.unwrap_or_else(|| {
+                        *next += 1;
+                        ShapeHandle(*next - 1)
+                    })
+                });
+                handle.0
+            })
+            .collect()
     }
-}
 
-fn lerp_gradient(start: &swf::Gradient, end: &swf::Gradient, a: f32, b: f32) -> swf::Gradient {
-    use swf::{Gradient, GradientRecord};
-    // Morph gradients are guaranteed to have the same number of records in the start/end gradient.
-    debug_assert!(start.records.len() == end.records.len());
-    let records: Vec<GradientRecord> = start
-        .records
-        .iter()
-        .zip(end.records.iter())
-        .map(|(start, end)| swf::GradientRecord {
-            ratio: (f32::from(start.ratio) * a + f32::from(end.ratio) * b) as u8,
-            color: lerp_color(&start.color, &end.color, a, b),
-        })
-        .collect();
-
-    Gradient {
-        matrix: lerp_matrix(&start.matrix, &end.matrix, a, b),
-        spread: start.spread,
-        interpolation: start.interpolation,
-        records,
+    #[test]
+    fn instances_drawn_in_one_render_stay_cached() {
+        let mut cache = FrameCache::default();
+        let mut registered = 0;
+        let ratios: Vec<u16> = (0..12).map(|i| i * 1000).collect();
+
+        // Every ratio gets its own shape, and keeps it in the next render.
+        let first = draw(&mut cache, &ratios, 1, &mut registered);
+        assert_eq!(first, (0..12).collect::<Vec<_>>());
+        assert_eq!(draw(&mut cache, &ratios, 2, &mut registered), first);
+        assert_eq!(registered, 12);
+
+        // New ratios reuse the shapes of ones that weren't drawn by this
+        // render, but never a shape that this render already drew with.
+        let mut third = draw(&mut cache, &[0, 1, 2, 3, 4, 5, 6, 7, 8], 3, &mut registered);
+        third.sort_unstable();
+        third.dedup();
+        assert_eq!(third.len(), 9);
+        assert_eq!(registered, 12);
+
+        // Every frame of earlier renders was evicted to make room, leaving
+        // only the ones this render drew.
+        assert_eq!(cache.frames.len(), 9);
     }
 }
@@ -8,31 +8,38 @@ use crate::avm2::{
     StageObject as Avm2StageObject, TObject as Avm2TObject, Value as Avm2Value,
 };
 use crate::backend::audio::{PreloadStreamHandle, SoundHandle, SoundInstanceHandle};
+use crate::backend::render::BitmapInfo;
 use crate::backend::ui::MouseCursor;
+use crate::bitmap::decode::{
+    decode_define_bits_jpeg, decode_define_bits_jpeg_4, decode_define_bits_lossless,
+    glue_tables_to_jpeg,
+};
 use bitflags::bitflags;
 
 use crate::avm1::activation::{Activation as Avm1Activation, ActivationIdentifier};
 use crate::character::Character;
+use crate::config::LoopPolicy;
 use crate::context::{ActionType, RenderContext, UpdateContext};
 use crate::display_object::container::{
     dispatch_added_event_only, dispatch_added_to_stage_event_only, dispatch_removed_event,
     ChildContainer, TDisplayObjectContainer,
 };
 use crate::display_object::{
-    Bitmap, Button, DisplayObjectBase, EditText, Graphic, MorphShapeStatic, TDisplayObject, Text,
-    Video,
+    invalidate_clip_event_interest, Bitmap, Button, DisplayObjectBase, EditText, Graphic,
+    MorphShape, MorphShapeStatic, TDisplayObject, Text, Video,
 };
 use crate::drawing::Drawing;
-use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult};
+use crate::events::{ButtonKeyCode, ClipEvent, ClipEventMask, ClipEventResult};
 use crate::font::Font;
 use crate::prelude::*;
 use crate::tag_utils::{self, DecodeResult, SwfMovie, SwfSlice, SwfStream};
+use crate::transform::Transform;
 use crate::types::{Degrees, Percent};
 use crate::vminterface::{AvmObject, AvmType, Instantiator};
-use gc_arena::{Collect, Gc, GcCell, MutationContext};
+use gc_arena::{Collect, GcCell, MutationContext};
 use smallvec::SmallVec;
-use std::cell::{Ref, RefCell, RefMut};
-use std::collections::HashMap;
+use std::cell::{Ref, RefMut};
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::sync::Arc;
 use swf::extensions::ReadSwfExt;
@@ -66,7 +73,13 @@ pub struct MovieClip<'gc>(GcCell<'gc, MovieClipData<'gc>>);
 #[collect(no_drop)]
 pub struct MovieClipData<'gc> {
     base: DisplayObjectBase<'gc>,
-    static_data: Gc<'gc, MovieClipStatic>,
+
+    /// Data built from the clip's tags, which is shared between instances and
+    /// never changes once the clip has been preloaded.
+    ///
+    /// This holds no GC references, so it is kept outside of the GC arena.
+    #[collect(require_static)]
+    static_data: Arc<MovieClipStatic>,
     tag_stream_pos: u64,
     current_frame: FrameNumber,
     #[collect(require_static)]
@@ -85,6 +98,21 @@ pub struct MovieClipData<'gc> {
     use_hand_cursor: bool,
     last_queued_script_frame: Option<FrameNumber>,
     queued_script_frame: Option<FrameNumber>,
+
+    /// The 9-slice scaling grid of this clip, in local coordinates.
+    ///
+    /// This is an invalid bounding box if the clip is not 9-slice scaled.
+    scaling_grid: BoundingBox,
+
+    /// The propagating clip events that this clip or its children can handle,
+    /// or `None` if they have to be worked out again.
+    #[collect(require_static)]
+    clip_event_interest: Option<ClipEventMask>,
+
+    /// The propagating clip events that scripts have assigned handler
+    /// methods for on this clip.
+    #[collect(require_static)]
+    script_clip_events: ClipEventMask,
 }
 
 impl<'gc> MovieClip<'gc> {
@@ -94,7 +122,7 @@ impl<'gc> MovieClip<'gc> {
             gc_context,
             MovieClipData {
                 base: Default::default(),
-                static_data: Gc::allocate(gc_context, MovieClipStatic::empty(swf)),
+                static_data: Arc::new(MovieClipStatic::empty(swf)),
                 tag_stream_pos: 0,
                 current_frame: 0,
                 audio_stream: None,
@@ -112,6 +140,9 @@ impl<'gc> MovieClip<'gc> {
                 use_hand_cursor: true,
                 last_queued_script_frame: None,
                 queued_script_frame: None,
+                scaling_grid: BoundingBox::default(),
+                clip_event_interest: None,
+                script_clip_events: ClipEventMask::empty(),
             },
         ))
     }
@@ -126,7 +157,7 @@ impl<'gc> MovieClip<'gc> {
             gc_context,
             MovieClipData {
                 base: Default::default(),
-                static_data: Gc::allocate(gc_context, MovieClipStatic::empty(swf)),
+                static_data: Arc::new(MovieClipStatic::empty(swf)),
                 tag_stream_pos: 0,
                 current_frame: 0,
                 audio_stream: None,
@@ -144,6 +175,9 @@ impl<'gc> MovieClip<'gc> {
                 use_hand_cursor: true,
                 last_queued_script_frame: None,
                 queued_script_frame: None,
+                scaling_grid: BoundingBox::default(),
+                clip_event_interest: None,
+                script_clip_events: ClipEventMask::empty(),
             },
         ))
     }
@@ -158,10 +192,7 @@ impl<'gc> MovieClip<'gc> {
             gc_context,
             MovieClipData {
                 base: Default::default(),
-                static_data: Gc::allocate(
-                    gc_context,
-                    MovieClipStatic::with_data(id, swf, num_frames),
-                ),
+                static_data: Arc::new(MovieClipStatic::with_data(id, swf, num_frames)),
                 tag_stream_pos: 0,
                 current_frame: 0,
                 audio_stream: None,
@@ -179,6 +210,9 @@ impl<'gc> MovieClip<'gc> {
                 use_hand_cursor: true,
                 last_queued_script_frame: None,
                 queued_script_frame: None,
+                scaling_grid: BoundingBox::default(),
+                clip_event_interest: None,
+                script_clip_events: ClipEventMask::empty(),
             },
         ))
     }
@@ -190,10 +224,7 @@ impl<'gc> MovieClip<'gc> {
             gc_context,
             MovieClipData {
                 base: Default::default(),
-                static_data: Gc::allocate(
-                    gc_context,
-                    MovieClipStatic::with_data(0, movie.into(), num_frames),
-                ),
+                static_data: Arc::new(MovieClipStatic::with_data(0, movie.into(), num_frames)),
                 tag_stream_pos: 0,
                 current_frame: 0,
                 audio_stream: None,
@@ -211,6 +242,9 @@ impl<'gc> MovieClip<'gc> {
                 use_hand_cursor: true,
                 last_queued_script_frame: None,
                 queued_script_frame: None,
+                scaling_grid: BoundingBox::default(),
+                clip_event_interest: None,
+                script_clip_events: ClipEventMask::empty(),
             },
         ))
     }
@@ -225,24 +259,31 @@ impl<'gc> MovieClip<'gc> {
         gc_context: MutationContext<'gc, '_>,
         movie: Option<Arc<SwfMovie>>,
     ) {
-        self.0
-            .write(gc_context)
-            .replace_with_movie(gc_context, movie)
+        self.0.write(gc_context).replace_with_movie(movie)
     }
 
-    pub fn preload(
-        self,
-        context: &mut UpdateContext<'_, 'gc, '_>,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
-    ) {
+    pub fn preload(self, context: &mut UpdateContext<'_, 'gc, '_>) {
         use swf::TagCode;
-        // TODO: Re-creating static data because preload step occurs after construction.
-        // Should be able to hoist this up somewhere, or use MaybeUninit.
-        let mut static_data = (&*self.0.read().static_data).clone();
-        let data = self.0.read().static_data.swf.clone();
+        // Instances share the static data of their preloaded symbol, and
+        // their tags must only be read once.
+        if self.0.read().static_data.is_preloaded() {
+            return;
+        }
+
+        // The static data is built up while reading the tags, and replaces
+        // the placeholder that the clip was constructed with.
+        let mut static_data = {
+            let read = self.0.read();
+            MovieClipStatic::with_data(
+                read.static_data.id,
+                read.static_data.swf.clone(),
+                read.static_data.total_frames,
+            )
+        };
+        let data = static_data.swf.clone();
         let mut reader = data.read_from(0);
         let mut cur_frame = 1;
-        let mut ids = fnv::FnvHashMap::default();
+        static_data.frames.push(FrameInfo::default());
         let mut preload_stream_handle = None;
         let tag_callback = |reader: &mut SwfStream<'_>, tag_code, tag_len| match tag_code {
             TagCode::FileAttributes => {
@@ -266,6 +307,10 @@ impl<'gc> MovieClip<'gc> {
                 .0
                 .write(context.gc_context)
                 .csm_text_settings(context, reader),
+            TagCode::DefineScalingGrid => self
+                .0
+                .write(context.gc_context)
+                .define_scaling_grid(context, reader),
             TagCode::DefineBits => self
                 .0
                 .write(context.gc_context)
@@ -326,18 +371,14 @@ impl<'gc> MovieClip<'gc> {
                 .0
                 .write(context.gc_context)
                 .define_font_4(context, reader),
-            TagCode::DefineMorphShape => self.0.write(context.gc_context).define_morph_shape(
-                context,
-                reader,
-                morph_shapes,
-                1,
-            ),
-            TagCode::DefineMorphShape2 => self.0.write(context.gc_context).define_morph_shape(
-                context,
-                reader,
-                morph_shapes,
-                2,
-            ),
+            TagCode::DefineMorphShape => self
+                .0
+                .write(context.gc_context)
+                .define_morph_shape(context, reader, 1),
+            TagCode::DefineMorphShape2 => self
+                .0
+                .write(context.gc_context)
+                .define_morph_shape(context, reader, 2),
             TagCode::DefineShape => self
                 .0
                 .write(context.gc_context)
@@ -362,12 +403,10 @@ impl<'gc> MovieClip<'gc> {
                 .0
                 .write(context.gc_context)
                 .define_video_stream(context, reader),
-            TagCode::DefineSprite => self.0.write(context.gc_context).define_sprite(
-                context,
-                reader,
-                tag_len,
-                morph_shapes,
-            ),
+            TagCode::DefineSprite => self
+                .0
+                .write(context.gc_context)
+                .define_sprite(context, reader, tag_len),
             TagCode::DefineText => self
                 .0
                 .write(context.gc_context)
@@ -397,51 +436,21 @@ impl<'gc> MovieClip<'gc> {
                 .0
                 .write(context.gc_context)
                 .jpeg_tables(context, reader, tag_len),
-            TagCode::PlaceObject => self.0.write(context.gc_context).preload_place_object(
-                context,
-                reader,
-                tag_len,
-                &mut ids,
-                morph_shapes,
-                1,
-            ),
-            TagCode::PlaceObject2 => self.0.write(context.gc_context).preload_place_object(
-                context,
-                reader,
-                tag_len,
-                &mut ids,
-                morph_shapes,
-                2,
-            ),
-            TagCode::PlaceObject3 => self.0.write(context.gc_context).preload_place_object(
-                context,
-                reader,
-                tag_len,
-                &mut ids,
-                morph_shapes,
-                3,
-            ),
-            TagCode::PlaceObject4 => self.0.write(context.gc_context).preload_place_object(
+            TagCode::PlaceObject
+            | TagCode::PlaceObject2
+            | TagCode::PlaceObject3
+            | TagCode::PlaceObject4
+            | TagCode::RemoveObject
+            | TagCode::RemoveObject2 => {
+                static_data.mark_display_tags();
+                Ok(())
+            }
+            TagCode::ShowFrame => self.0.write(context.gc_context).preload_show_frame(
                 context,
                 reader,
-                tag_len,
-                &mut ids,
-                morph_shapes,
-                4,
+                &mut cur_frame,
+                &mut static_data,
             ),
-            TagCode::RemoveObject => self
-                .0
-                .write(context.gc_context)
-                .preload_remove_object(context, reader, &mut ids, 1),
-            TagCode::RemoveObject2 => self
-                .0
-                .write(context.gc_context)
-                .preload_remove_object(context, reader, &mut ids, 2),
-            TagCode::ShowFrame => {
-                self.0
-                    .write(context.gc_context)
-                    .preload_show_frame(context, reader, &mut cur_frame)
-            }
             TagCode::ScriptLimits => self
                 .0
                 .write(context.gc_context)
@@ -486,8 +495,7 @@ impl<'gc> MovieClip<'gc> {
             }
         }
 
-        self.0.write(context.gc_context).static_data =
-            Gc::allocate(context.gc_context, static_data);
+        self.0.write(context.gc_context).static_data = Arc::new(static_data);
     }
 
     #[inline]
@@ -676,10 +684,10 @@ impl<'gc> MovieClip<'gc> {
         }
 
         for FrameLabelData { frame_num, label } in sfl_data.frame_labels {
-            static_data.frame_labels.insert(
-                label.to_string_lossy(reader.encoding()),
-                frame_num as u16 + 1,
-            );
+            let label = label.to_string_lossy(reader.encoding());
+            if !static_data.add_frame_label(label, frame_num as u16 + 1) {
+                log::warn!("Movie clip {}: Duplicated frame label", static_data.id);
+            }
         }
 
         Ok(())
@@ -750,6 +758,27 @@ impl<'gc> MovieClip<'gc> {
         }
     }
 
+    /// Move the playhead so that this clip stays in step with the timeline
+    /// that placed it.
+    ///
+    /// Animated graphic symbols are exported as ordinary sprites, and their
+    /// timelines are kept in sync with their parent's by giving each
+    /// placement a ratio: the number of frames into the sprite that should be
+    /// showing. `frame_offset` is that many frames. It wraps around the end of
+    /// the clip, as graphic symbols loop by default.
+    ///
+    /// This is an implicit goto, so frame scripts are not rerun. Clips that
+    /// haven't run their first frame yet are left alone if they would start
+    /// on it anyway.
+    pub fn sync_timeline(self, context: &mut UpdateContext<'_, 'gc, '_>, frame_offset: u32) {
+        let total_frames = u32::from(self.total_frames().max(1));
+        let frame = (frame_offset % total_frames) as FrameNumber + 1;
+
+        if frame != self.current_frame().max(1) {
+            self.run_goto(self.into(), context, frame, true);
+        }
+    }
+
     pub fn current_frame(self) -> FrameNumber {
         self.0.read().current_frame
     }
@@ -884,41 +913,27 @@ impl<'gc> MovieClip<'gc> {
     }
 
     /// Yield the current frame label as a tuple of string and frame number.
+    ///
+    /// If the most recent labelled frame has several labels, the first one
+    /// defined is used.
     pub fn current_label(self) -> Option<(String, FrameNumber)> {
         let read = self.0.read();
-        let current_frame = read.current_frame();
-        let mut best: Option<(&str, FrameNumber)> = None;
-
-        for (label, frame) in read.static_data.frame_labels.iter() {
-            if *frame > current_frame {
-                continue;
-            }
-
-            if best.map(|v| *frame >= v.1).unwrap_or(true) {
-                best = Some((label, *frame));
-            }
-        }
-
-        best.map(|(s, fnum)| (s.to_string(), fnum))
+        read.static_data
+            .frame_labels
+            .range(..=read.current_frame())
+            .next_back()
+            .and_then(|(frame, labels)| Some((labels.first()?.clone(), *frame)))
     }
 
-    /// Yield a list of labels and frame-numbers in the current scene.
-    ///
-    /// Labels are returned sorted by frame number.
-    pub fn labels_in_range(self, from: FrameNumber, to: FrameNumber) -> Vec<(String, FrameNumber)> {
-        let read = self.0.read();
-
-        let mut values: Vec<(String, FrameNumber)> = read
+    /// The labels on a frame, in the order they were defined.
+    pub fn labels_on_frame(self, frame: FrameNumber) -> Vec<String> {
+        self.0
+            .read()
             .static_data
             .frame_labels
-            .iter()
-            .filter(|(_label, frame)| **frame >= from && **frame < to)
-            .map(|(label, frame)| (label.clone(), *frame))
-            .collect();
-
-        values.sort_unstable_by(|(_, framea), (_, frameb)| framea.cmp(frameb));
-
-        values
+            .get(&frame)
+            .cloned()
+            .unwrap_or_default()
     }
 
     pub fn total_frames(self) -> FrameNumber {
@@ -939,10 +954,56 @@ impl<'gc> MovieClip<'gc> {
         write.avm2_constructor = prototype;
     }
 
+    /// Returns the frame number of the given frame label.
+    ///
+    /// The same label may be used in several scenes, in which case the one in
+    /// the current scene wins.
     pub fn frame_label_to_number(self, frame_label: &str) -> Option<FrameNumber> {
-        // Frame labels are case insensitive.
-        let label = frame_label.to_ascii_lowercase();
-        self.0.read().static_data.frame_labels.get(&label).copied()
+        let read = self.0.read();
+        let static_data = &read.static_data;
+        let current_scene = static_data.scene_start(read.current_frame());
+        static_data
+            .frame_label_in_scene(frame_label, current_scene)
+            .or_else(|| {
+                let frame_label = static_data.normalize_label(frame_label);
+                static_data
+                    .frame_label_index
+                    .iter()
+                    .filter(|((_, label), _)| *label == frame_label)
+                    .map(|(_, frame)| *frame)
+                    .min()
+            })
+    }
+
+    /// Returns the frame number of a frame label within the given scene.
+    pub fn frame_label_to_number_in_scene(
+        self,
+        frame_label: &str,
+        scene_label: &str,
+    ) -> Option<FrameNumber> {
+        let read = self.0.read();
+        let scene = read.static_data.scene_labels.get(scene_label)?;
+        read.static_data
+            .frame_label_in_scene(frame_label, scene.start)
+    }
+
+    /// Returns the named anchor placed on the given frame, if any.
+    pub fn frame_anchor(self, frame: FrameNumber) -> Option<String> {
+        self.0.read().static_data.frame_anchors.get(&frame).cloned()
+    }
+
+    /// Returns the frame number of the given named anchor.
+    ///
+    /// Like frame labels, anchors are matched case-insensitively.
+    pub fn anchor_to_frame_number(self, anchor: &str) -> Option<FrameNumber> {
+        self.0
+            .read()
+            .static_data
+            .frame_anchors
+            .iter()
+            .filter(|(_, label)| label.eq_ignore_ascii_case(anchor))
+            .map(|(frame, _)| *frame)
+            .min()
     }
 
     pub fn scene_label_to_number(self, scene_label: &str) -> Option<FrameNumber> {
@@ -956,38 +1017,6 @@ impl<'gc> MovieClip<'gc> {
             .copied()
     }
 
-    pub fn frame_exists_within_scene(self, frame_label: &str, scene_label: &str) -> bool {
-        let scene = self.scene_label_to_number(scene_label);
-        let frame = self.frame_label_to_number(frame_label);
-
-        if scene.is_none() || frame.is_none() {
-            return false;
-        }
-
-        let scene = scene.unwrap();
-        let frame = frame.unwrap();
-
-        if scene <= frame {
-            let mut end = self.total_frames();
-            for (
-                _label,
-                Scene {
-                    start: new_scene_start,
-                    ..
-                },
-            ) in self.0.read().static_data.scene_labels.iter()
-            {
-                if *new_scene_start < end && *new_scene_start > scene {
-                    end = *new_scene_start;
-                }
-            }
-
-            frame < end
-        } else {
-            false
-        }
-    }
-
     /// Gets the clip events for this movieclip.
     pub fn clip_actions(&self) -> Ref<[ClipAction]> {
         Ref::map(self.0.read(), |mc| mc.clip_actions())
@@ -1000,6 +1029,32 @@ impl<'gc> MovieClip<'gc> {
         let mut mc = self.0.write(gc_context);
         mc.has_button_clip_event = actions.iter().any(|a| a.event.is_button_event());
         mc.set_clip_actions(actions);
+        drop(mc);
+        invalidate_clip_event_interest(self.into(), gc_context);
+    }
+
+    /// Note that a script assigned a handler method for the given events,
+    /// such as `onMouseMove`, to this clip.
+    pub fn add_script_clip_events(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        events: ClipEventMask,
+    ) {
+        if !self.0.read().script_clip_events.contains(events) {
+            self.0.write(gc_context).script_clip_events |= events;
+            invalidate_clip_event_interest(self.into(), gc_context);
+        }
+    }
+
+    /// Forget which clip events this clip and its children are interested
+    /// in. Returns `false` if they had already been forgotten.
+    pub fn invalidate_clip_event_interest(self, gc_context: MutationContext<'gc, '_>) -> bool {
+        if self.0.read().clip_event_interest.is_some() {
+            self.0.write(gc_context).clip_event_interest = None;
+            true
+        } else {
+            false
+        }
     }
 
     /// Returns an iterator of AVM1 `DoAction` blocks on the given frame number.
@@ -1014,10 +1069,14 @@ impl<'gc> MovieClip<'gc> {
         let mut actions: SmallVec<[SwfSlice; 2]> = SmallVec::new();
 
         // Iterate through this clip's tags, counting frames until we reach the target frame.
+        // If the clip has been preloaded, we can start right at the target frame.
         if frame > 0 && frame <= self.total_frames() {
-            let mut cur_frame = 1;
             let clip = self.0.read();
-            let mut reader = clip.static_data.swf.read_from(0);
+            let (mut cur_frame, start) = match clip.static_data.frame_info(frame) {
+                Some(info) => (frame, info.tag_stream_pos),
+                None => (1, 0),
+            };
+            let mut reader = clip.static_data.swf.read_from(start);
             while cur_frame <= frame && !reader.get_ref().is_empty() {
                 let tag_callback = |reader: &mut Reader<'_>, tag_code, tag_len| {
                     match tag_code {
@@ -1063,6 +1122,17 @@ impl<'gc> MovieClip<'gc> {
         }
     }
 
+    /// Whether this clip stops on its last frame instead of looping, which
+    /// only the root clip does, and only if the player is set to play once.
+    fn holds_last_frame(
+        self,
+        self_display_object: DisplayObject<'gc>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+    ) -> bool {
+        context.stage.loop_policy() == LoopPolicy::Once
+            && DisplayObject::ptr_eq(self_display_object, context.stage.root_clip())
+    }
+
     fn run_frame_internal(
         self,
         self_display_object: DisplayObject<'gc>,
@@ -1071,6 +1141,10 @@ impl<'gc> MovieClip<'gc> {
     ) {
         match self.determine_next_frame() {
             NextFrame::Next => self.0.write(context.gc_context).current_frame += 1,
+            NextFrame::First if self.holds_last_frame(self_display_object, context) => {
+                context.stage.hold_last_frame(context.gc_context);
+                return self.stop(context);
+            }
             NextFrame::First => return self.run_goto(self_display_object, context, 1, true),
             NextFrame::Same => self.stop(context),
         }
@@ -1221,11 +1295,15 @@ impl<'gc> MovieClip<'gc> {
         //    the goto frame, so we should instead aggregate the deltas into a final list
         //    of commands, and THEN modify the children as necessary.
 
-        // This map will maintain a map of depth -> placement commands.
+        // This list maintains the placement commands for each depth, in the order
+        // that the timeline first placed them. It must stay in that order, as
+        // the order of child construction and queued actions depends on it.
         // TODO: Move this to UpdateContext to avoid allocations.
         let mut goto_commands: Vec<GotoPlaceObject<'_>> = vec![];
 
-        self.0.write(context.gc_context).stop_audio_stream(context);
+        // A playing stream sound is kept, and seeked to the destination frame
+        // once we get there, so that it stays in sync with the timeline.
+        let prev_audio_stream = self.0.read().audio_stream;
 
         let is_rewind = if frame < self.current_frame() {
             // Because we can only step forward, we have to start at frame 1
@@ -1267,8 +1345,8 @@ impl<'gc> MovieClip<'gc> {
         let mc = self.0.read();
         let tag_stream_start = mc.static_data.swf.as_ref().as_ptr() as u64;
         let mut frame_pos = mc.tag_stream_pos;
-        let data = mc.static_data.swf.clone();
-        let mut index = 0;
+        let static_data = mc.static_data.clone();
+        let data = static_data.swf.clone();
 
         // Sanity; let's make sure we don't seek way too far.
         // TODO: This should be self.frames_loaded() when we implement that.
@@ -1284,31 +1362,34 @@ impl<'gc> MovieClip<'gc> {
             self.0.write(context.gc_context).current_frame += 1;
             frame_pos = reader.get_ref().as_ptr() as u64 - tag_stream_start;
 
+            // Frames that don't change the display list have nothing to
+            // aggregate, so we can jump straight past them.
+            if let Some(next_frame_pos) = static_data.skip_frame(self.current_frame()) {
+                reader = data.read_from(next_frame_pos);
+                continue;
+            }
+
             use swf::TagCode;
             let tag_callback = |reader: &mut SwfStream<'gc>, tag_code, tag_len| match tag_code {
                 TagCode::PlaceObject => {
-                    index += 1;
                     let mut mc = self.0.write(context.gc_context);
 
-                    mc.goto_place_object(reader, tag_len, 1, &mut goto_commands, is_rewind, index)
+                    mc.goto_place_object(reader, tag_len, 1, &mut goto_commands, is_rewind)
                 }
                 TagCode::PlaceObject2 => {
-                    index += 1;
                     let mut mc = self.0.write(context.gc_context);
 
-                    mc.goto_place_object(reader, tag_len, 2, &mut goto_commands, is_rewind, index)
+                    mc.goto_place_object(reader, tag_len, 2, &mut goto_commands, is_rewind)
                 }
                 TagCode::PlaceObject3 => {
-                    index += 1;
                     let mut mc = self.0.write(context.gc_context);
 
-                    mc.goto_place_object(reader, tag_len, 3, &mut goto_commands, is_rewind, index)
+                    mc.goto_place_object(reader, tag_len, 3, &mut goto_commands, is_rewind)
                 }
                 TagCode::PlaceObject4 => {
-                    index += 1;
                     let mut mc = self.0.write(context.gc_context);
 
-                    mc.goto_place_object(reader, tag_len, 4, &mut goto_commands, is_rewind, index)
+                    mc.goto_place_object(reader, tag_len, 4, &mut goto_commands, is_rewind)
                 }
                 TagCode::RemoveObject => {
                     self.goto_remove_object(reader, 1, context, &mut goto_commands, is_rewind)
@@ -1327,7 +1408,7 @@ impl<'gc> MovieClip<'gc> {
                                 context: &mut UpdateContext<'_, 'gc, '_>,
                                 params: &GotoPlaceObject<'_>| {
             let child_entry = clip.child_by_depth(params.depth());
-            match child_entry {
+            let child = match child_entry {
                 // Apply final delta to display parameters.
                 // For rewinds, if an object was created before the final frame,
                 // it will exist on the final frame as well. Re-use this object
@@ -1337,32 +1418,43 @@ impl<'gc> MovieClip<'gc> {
                 // modify the previous child.
                 Some(prev_child) if params.id() == 0 || is_rewind => {
                     prev_child.apply_place_object(context, self.movie(), &params.place_object);
+                    Some(prev_child)
                 }
                 _ => {
-                    if let Some(child) = clip.instantiate_child(
+                    let child = clip.instantiate_child(
                         self_display_object,
                         context,
                         params.id(),
                         params.depth(),
                         &params.place_object,
                         params.modifies_original_item(),
-                    ) {
+                    );
+                    if let Some(child) = child {
                         // Set the place frame to the frame where the object *would* have been placed.
                         child.set_place_frame(context.gc_context, params.frame);
                     }
+                    child
+                }
+            };
+
+            // Synchronized clips have to be wherever they would have been had
+            // both timelines played up to this frame.
+            if let (Some(child), Some(ratio_frame), Some(ratio)) =
+                (child, params.ratio_frame, params.place_object.ratio)
+            {
+                if let Some(child_clip) = child.as_movie_clip() {
+                    let frames_since_ratio = clamped_frame.saturating_sub(ratio_frame);
+                    child_clip
+                        .sync_timeline(context, u32::from(ratio) + u32::from(frames_since_ratio));
                 }
             }
         };
 
         // We have to be sure that queued actions are generated in the same order
-        // as if the playhead had reached this frame normally.
-
-        // First, sort the goto commands in the order of execution.
-        // (Maybe it'd be better to keeps this list sorted as we create it?
-        // Currently `swap_remove` calls futz with the order; but we could use `remove`).
-        goto_commands.sort_by_key(|params| params.index);
+        // as if the playhead had reached this frame normally. The goto commands
+        // are already in timeline order.
 
-        // Then, run frames for children that were created before this frame.
+        // First, run frames for children that were created before this frame.
         goto_commands
             .iter()
             .filter(|params| params.frame < frame)
@@ -1382,6 +1474,16 @@ impl<'gc> MovieClip<'gc> {
             self.run_frame_internal(self_display_object, context, false);
         } else {
             self.0.write(context.gc_context).current_frame = clamped_frame;
+            self.0.write(context.gc_context).stop_audio_stream(context);
+        }
+
+        // Running the final frame stops the stream if the frame has no stream
+        // data; otherwise, the stream picks up from the start of this frame.
+        let audio_stream = self.0.read().audio_stream;
+        if let (Some(prev_audio_stream), Some(audio_stream)) = (prev_audio_stream, audio_stream) {
+            if prev_audio_stream == audio_stream && !context.seek_stream(audio_stream, frame) {
+                self.0.write(context.gc_context).stop_audio_stream(context);
+            }
         }
 
         // Finally, run frames for children that are placed on this frame.
@@ -1423,6 +1525,8 @@ impl<'gc> MovieClip<'gc> {
                     self.into(),
                 );
 
+                // A constructor without a usable `prototype` leaves the clip
+                // as a plain `MovieClip` below.
                 if let Ok(prototype) = constructor
                     .get("prototype", &mut activation)
                     .map(|v| v.coerce_to_object(&mut activation))
@@ -1445,9 +1549,8 @@ impl<'gc> MovieClip<'gc> {
                         self.run_frame(&mut activation.context);
                     }
                     let _ = constructor.construct_on_existing(&mut activation, object, &[]);
+                    return;
                 }
-
-                return;
             }
 
             let object: Avm1Object<'gc> = StageObject::for_display_object(
@@ -1608,23 +1711,56 @@ impl<'gc> MovieClip<'gc> {
         }
     }
 
+    /// Set the frame script that runs on a given frame, replacing any script
+    /// already registered for it. `None` removes the frame's script.
     pub fn register_frame_script(
         self,
         frame_id: FrameNumber,
-        callable: Avm2Object<'gc>,
+        callable: Option<Avm2Object<'gc>>,
         context: &mut UpdateContext<'_, 'gc, '_>,
     ) {
         let mut write = self.0.write(context.gc_context);
 
-        write
+        write.frame_scripts.retain(|fs| fs.frame_id != frame_id);
+        if let Some(callable) = callable {
+            write
+                .frame_scripts
+                .push(Avm2FrameScript { frame_id, callable });
+        }
+    }
+
+    /// The frame script that runs on a given frame, if any.
+    pub fn frame_script(self, frame_id: FrameNumber) -> Option<Avm2Object<'gc>> {
+        self.0
+            .read()
             .frame_scripts
-            .push(Avm2FrameScript { frame_id, callable });
+            .iter()
+            .find(|fs| fs.frame_id == frame_id)
+            .map(|fs| fs.callable)
     }
 
     pub fn set_focusable(self, focusable: bool, context: &mut UpdateContext<'_, 'gc, '_>) {
         self.0.write(context.gc_context).is_focusable = focusable;
     }
 
+    /// Whether this movie clip acts as a button, because it has a mouse
+    /// handler, either via on(..) or via a property such as `mc.onRelease`.
+    pub fn is_button_mode(self, context: &mut UpdateContext<'_, 'gc, '_>) -> bool {
+        if self.0.read().has_button_clip_event {
+            return true;
+        }
+
+        let mut activation = Avm1Activation::from_stub(
+            context.reborrow(),
+            ActivationIdentifier::root("[Mouse Pick]"),
+        );
+        let object = self.object().coerce_to_object(&mut activation);
+
+        ClipEvent::BUTTON_EVENT_METHODS
+            .iter()
+            .any(|handler| object.has_property(&mut activation, handler))
+    }
+
     /// Handle a RemoveObject tag when running a goto action.
     #[inline]
     fn goto_remove_object<'a>(
@@ -1642,7 +1778,7 @@ impl<'gc> MovieClip<'gc> {
         }?;
         let depth = Depth::from(remove_object.depth);
         if let Some(i) = goto_commands.iter().position(|o| o.depth() == depth) {
-            goto_commands.swap_remove(i);
+            goto_commands.remove(i);
         }
         if !is_rewind {
             // For fast-forwards, if this tag were to remove an object
@@ -1686,6 +1822,147 @@ impl<'gc> MovieClip<'gc> {
     pub fn tag_stream_len(&self) -> usize {
         self.0.read().tag_stream_len()
     }
+
+    /// The 9-slice scaling grid of this clip.
+    ///
+    /// The returned bounding box is invalid if the clip is not 9-slice scaled.
+    pub fn scaling_grid(self) -> BoundingBox {
+        self.0.read().scaling_grid.clone()
+    }
+
+    pub fn set_scaling_grid(self, gc_context: MutationContext<'gc, '_>, grid: BoundingBox) {
+        self.0.write(gc_context).scaling_grid = grid;
+    }
+
+    /// Renders this clip's drawing and children.
+    fn render_contents(self, context: &mut RenderContext<'_, 'gc>) {
+        let movie = self.movie();
+
+        self.0.read().drawing.render(context, movie);
+        self.render_children(context);
+    }
+
+    /// Renders this clip with 9-slice scaling.
+    ///
+    /// Only the clip's own drawing is scaled with the grid. Its bounds are cut
+    /// into a 3x3 grid of cells by the scaling grid, and each cell is drawn
+    /// separately, masked to its own area. Once the clip's own scale is
+    /// applied, the corner cells keep their original size, the edge cells only
+    /// stretch along the edge, and the center cell fills the remaining space.
+    /// Children are then rendered once, scaled normally.
+    ///
+    /// Returns `false` without rendering anything if the clip cannot be
+    /// 9-slice scaled.
+    fn render_nine_slice(self, context: &mut RenderContext<'_, 'gc>, grid: &BoundingBox) -> bool {
+        // Each cell needs its own mask, which can't be drawn inside of
+        // another mask.
+        if !context.allow_mask {
+            return false;
+        }
+
+        let bounds = self.self_bounds();
+        let matrix = *self.matrix();
+        let scale_x = f64::from(matrix.a).hypot(f64::from(matrix.b));
+        let scale_y = f64::from(matrix.c).hypot(f64::from(matrix.d));
+        if !bounds.valid || scale_x <= 0.0 || scale_y <= 0.0 {
+            return false;
+        }
+
+        let columns =
+            nine_slice_segments(bounds.x_min, grid.x_min, grid.x_max, bounds.x_max, scale_x);
+        let rows = nine_slice_segments(bounds.y_min, grid.y_min, grid.y_max, bounds.y_max, scale_y);
+        let (columns, rows) = match (columns, rows) {
+            (Some(columns), Some(rows)) => (columns, rows),
+            _ => return false,
+        };
+
+        for &(src_x, dst_x) in columns.iter() {
+            for &(src_y, dst_y) in rows.iter() {
+                let (src_width, dst_width) = (src_x.1 - src_x.0, dst_x.1 - dst_x.0);
+                let (src_height, dst_height) = (src_y.1 - src_y.0, dst_y.1 - dst_y.0);
+                if src_width <= 0.0 || dst_width <= 0.0 || src_height <= 0.0 || dst_height <= 0.0 {
+                    continue;
+                }
+
+                let scale_x = dst_width / src_width;
+                let scale_y = dst_height / src_height;
+                let cell_matrix = Matrix {
+                    a: scale_x as f32,
+                    d: scale_y as f32,
+                    tx: Twips::new((dst_x.0 - src_x.0 * scale_x).round() as i32),
+                    ty: Twips::new((dst_y.0 - src_y.0 * scale_y).round() as i32),
+                    ..Default::default()
+                };
+                let mask = context.transform_stack.transform().matrix
+                    * Matrix::create_box(
+                        (dst_width / 20.0) as f32,
+                        (dst_height / 20.0) as f32,
+                        0.0,
+                        Twips::new(dst_x.0.round() as i32),
+                        Twips::new(dst_y.0.round() as i32),
+                    );
+
+                context.renderer.push_mask();
+                context.renderer.draw_rect(Color::from_rgb(0, 0xff), &mask);
+                context.renderer.activate_mask();
+
+                context.transform_stack.push(&Transform {
+                    matrix: cell_matrix,
+                    ..Default::default()
+                });
+                self.0.read().drawing.render(context, self.movie());
+                context.transform_stack.pop();
+
+                context.renderer.deactivate_mask();
+                context.renderer.draw_rect(Color::from_rgb(0, 0xff), &mask);
+                context.renderer.pop_mask();
+            }
+        }
+
+        self.render_children(context);
+        true
+    }
+}
+
+/// Splits one axis of a 9-slice scaled clip into three segments, returning the
+/// source and destination range of each segment in twips.
+///
+/// The outer segments are sized so that they will be unscaled once the clip's
+/// `scale` is applied, and the inner segment stretches to fill the remaining
+/// space. If the clip is too small to fit both outer segments, they shrink
+/// proportionally instead. Returns `None` if the grid lies outside of the
+/// clip's bounds.
+#[allow(clippy::type_complexity)]
+fn nine_slice_segments(
+    min: Twips,
+    grid_min: Twips,
+    grid_max: Twips,
+    max: Twips,
+    scale: f64,
+) -> Option<[((f64, f64), (f64, f64)); 3]> {
+    if min > grid_min || grid_min >= grid_max || grid_max > max {
+        return None;
+    }
+
+    let (min, grid_min, grid_max, max) = (
+        f64::from(min.get()),
+        f64::from(grid_min.get()),
+        f64::from(grid_max.get()),
+        f64::from(max.get()),
+    );
+    let mut start = (grid_min - min) / scale;
+    let mut end = (max - grid_max) / scale;
+    if start + end > max - min {
+        let shrink = (max - min) / (start + end);
+        start *= shrink;
+        end *= shrink;
+    }
+
+    Some([
+        ((min, grid_min), (min, min + start)),
+        ((grid_min, grid_max), (min + start, max - end)),
+        ((grid_max, max), (max - end, max)),
+    ])
 }
 
 impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
@@ -1761,6 +2038,11 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
             child.run_frame(context);
         }
 
+        // A child's script may have removed this clip.
+        if self.removed() {
+            return;
+        }
+
         // Run my load/enterFrame clip event.
         let mut mc = self.0.write(context.gc_context);
         let is_load_frame = !mc.initialized();
@@ -1830,11 +2112,23 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         }
     }
 
+    fn content_revision(&self) -> Option<u64> {
+        // Clips only draw anything themselves if scripts have drawn into them,
+        // which they can do at any time.
+        if self.self_bounds().valid {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
     fn render_self(&self, context: &mut RenderContext<'_, 'gc>) {
-        let movie = self.movie();
+        let scaling_grid = self.scaling_grid();
+        if scaling_grid.valid && self.render_nine_slice(context, &scaling_grid) {
+            return;
+        }
 
-        self.0.read().drawing.render(context, movie);
-        self.render_children(context);
+        self.render_contents(context);
     }
 
     fn self_bounds(&self) -> BoundingBox {
@@ -1925,23 +2219,7 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
             if self.world_bounds().contains(point) {
                 // This movieclip operates in "button mode" if it has a mouse handler,
                 // either via on(..) or via property mc.onRelease, etc.
-                let is_button_mode = {
-                    if self.0.read().has_button_clip_event {
-                        true
-                    } else {
-                        let mut activation = Avm1Activation::from_stub(
-                            context.reborrow(),
-                            ActivationIdentifier::root("[Mouse Pick]"),
-                        );
-                        let object = self.object().coerce_to_object(&mut activation);
-
-                        ClipEvent::BUTTON_EVENT_METHODS
-                            .iter()
-                            .any(|handler| object.has_property(&mut activation, handler))
-                    }
-                };
-
-                if is_button_mode
+                if self.is_button_mode(context)
                     && self.hit_test_shape(
                         context,
                         point,
@@ -1993,7 +2271,7 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         None
     }
 
-    fn mouse_cursor(&self) -> MouseCursor {
+    fn mouse_cursor(&self, _context: &mut UpdateContext<'_, 'gc, '_>) -> MouseCursor {
         if self.use_hand_cursor() {
             MouseCursor::Hand
         } else {
@@ -2018,6 +2296,14 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         }
 
         if event.propagates() {
+            // Skip clips that nothing inside of can handle the event, unless
+            // every clip inherits a handler for it.
+            if !context.avm1.prototype_clip_events().contains(event.mask())
+                && !self.clip_event_interest(context).contains(event.mask())
+            {
+                return ClipEventResult::NotHandled;
+            }
+
             for child in self.iter_execution_list() {
                 if child.handle_clip_event(context, event) == ClipEventResult::Handled {
                     return ClipEventResult::Handled;
@@ -2028,6 +2314,19 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
         self.0.read().run_clip_event((*self).into(), context, event)
     }
 
+    fn clip_event_interest(&self, context: &mut UpdateContext<'_, 'gc, '_>) -> ClipEventMask {
+        if let Some(interest) = self.0.read().clip_event_interest {
+            return interest;
+        }
+
+        let mut interest = self.0.read().own_clip_event_interest(context);
+        for child in self.iter_execution_list() {
+            interest |= child.clip_event_interest(context);
+        }
+        self.0.write(context.gc_context).clip_event_interest = Some(interest);
+        interest
+    }
+
     fn as_movie_clip(&self) -> Option<MovieClip<'gc>> {
         Some(*self)
     }
@@ -2142,20 +2441,13 @@ impl<'gc> MovieClipData<'gc> {
     ///
     /// If no movie is provided, then the movie clip will be replaced with an
     /// empty movie of the same SWF version.
-    pub fn replace_with_movie(
-        &mut self,
-        gc_context: MutationContext<'gc, '_>,
-        movie: Option<Arc<SwfMovie>>,
-    ) {
+    pub fn replace_with_movie(&mut self, movie: Option<Arc<SwfMovie>>) {
         let is_swf = movie.is_some();
         let movie = movie.unwrap_or_else(|| Arc::new(SwfMovie::empty(self.movie().version())));
         let total_frames = movie.header().num_frames;
 
         self.base.reset_for_movie_load();
-        self.static_data = Gc::allocate(
-            gc_context,
-            MovieClipStatic::with_data(0, movie.into(), total_frames),
-        );
+        self.static_data = Arc::new(MovieClipStatic::with_data(0, movie.into(), total_frames));
         self.tag_stream_pos = 0;
         self.flags = MovieClipFlags::PLAYING;
         if is_swf {
@@ -2219,7 +2511,6 @@ impl<'gc> MovieClipData<'gc> {
         version: u8,
         goto_commands: &mut Vec<GotoPlaceObject<'a>>,
         is_rewind: bool,
-        index: usize,
     ) -> DecodeResult {
         let place_object = if version == 1 {
             reader.read_place_object(tag_len)
@@ -2229,8 +2520,7 @@ impl<'gc> MovieClipData<'gc> {
 
         // We merge the deltas from this PlaceObject with the previous command.
         let depth = Depth::from(place_object.depth);
-        let mut goto_place =
-            GotoPlaceObject::new(self.current_frame(), place_object, is_rewind, index);
+        let mut goto_place = GotoPlaceObject::new(self.current_frame(), place_object, is_rewind);
         if let Some(i) = goto_commands.iter().position(|o| o.depth() == depth) {
             goto_commands[i].merge(&mut goto_place);
         } else {
@@ -2320,6 +2610,35 @@ impl<'gc> MovieClipData<'gc> {
         }
     }
 
+    /// The propagating clip events that this clip itself can handle.
+    fn own_clip_event_interest(&self, context: &mut UpdateContext<'_, 'gc, '_>) -> ClipEventMask {
+        let mut interest = self
+            .clip_actions
+            .iter()
+            .fold(ClipEventMask::empty(), |interest, action| {
+                interest | action.event.mask()
+            });
+
+        // Handler methods can be assigned by scripts, or come from a class
+        // registered for the clip.
+        if let Some(AvmObject::Avm1(object)) = self.object {
+            if context.swf.version() >= 6 {
+                interest |= self.script_clip_events;
+                let is_plain_movie_clip = match object.proto() {
+                    Avm1Value::Object(proto) => {
+                        Avm1Object::ptr_eq(proto, context.avm1.prototypes().movie_clip)
+                    }
+                    _ => false,
+                };
+                if !is_plain_movie_clip {
+                    interest |= ClipEventMask::METHODS;
+                }
+            }
+        }
+
+        interest
+    }
+
     pub fn clip_actions(&self) -> &[ClipAction] {
         &self.clip_actions
     }
@@ -2352,11 +2671,9 @@ impl<'gc> MovieClipData<'gc> {
         &self,
         context: &mut UpdateContext<'_, 'gc, '_>,
     ) -> Option<Avm1Object<'gc>> {
-        let symbol_name = self.static_data.exported_name.borrow();
-        let symbol_name = symbol_name.as_ref()?;
         let library = context.library.library_for_movie_mut(self.movie());
-        let registry = library.avm1_constructor_registry()?;
-        let ctor = registry.get(symbol_name)?;
+        let symbol_name = library.export_name(self.static_data.id)?;
+        let ctor = library.avm1_registered_constructor(symbol_name)?;
         Some(Avm1Object::FunctionObject(ctor))
     }
 
@@ -2367,111 +2684,76 @@ impl<'gc> MovieClipData<'gc> {
 
 // Preloading of definition tags
 impl<'gc, 'a> MovieClipData<'gc> {
-    #[inline]
-    fn define_bits_lossless(
+    /// Register a bitmap that the renderer has decoded as a character of this
+    /// movie.
+    fn register_bitmap(
         &mut self,
         context: &mut UpdateContext<'_, 'gc, '_>,
-        reader: &mut SwfStream<'a>,
-        version: u8,
-    ) -> DecodeResult {
-        let define_bits_lossless = reader.read_define_bits_lossless(version)?;
-        let bitmap_info = context
-            .renderer
-            .register_bitmap_png(&define_bits_lossless)?;
-        let bitmap = crate::display_object::Bitmap::new(
+        id: CharacterId,
+        bitmap_info: BitmapInfo,
+    ) {
+        let bitmap = Bitmap::new(
             context,
-            define_bits_lossless.id,
+            id,
             bitmap_info.handle,
             bitmap_info.width,
             bitmap_info.height,
         );
+        context
+            .library
+            .register_bitmap_owner(&self.movie(), bitmap_info.handle);
         context
             .library
             .library_for_movie_mut(self.movie())
-            .register_character(define_bits_lossless.id, Character::Bitmap(bitmap));
-        Ok(())
+            .register_character(id, Character::Bitmap(bitmap));
     }
 
     #[inline]
-    fn define_morph_shape(
+    fn define_bits_lossless(
         &mut self,
         context: &mut UpdateContext<'_, 'gc, '_>,
         reader: &mut SwfStream<'a>,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
         version: u8,
     ) -> DecodeResult {
-        // Certain backends may have to preload morph shape frames, so defer registering until the end.
-        let swf_shape = reader.read_define_morph_shape(version)?;
-        let morph_shape = MorphShapeStatic::from_swf_tag(context, &swf_shape, self.movie());
-        morph_shapes.insert(swf_shape.id, morph_shape);
+        let define_bits_lossless = reader.read_define_bits_lossless(version)?;
+        let bitmap = decode_define_bits_lossless(&define_bits_lossless)?;
+        let bitmap_info = context.renderer.register_bitmap(bitmap)?;
+        self.register_bitmap(context, define_bits_lossless.id, bitmap_info);
         Ok(())
     }
 
     #[inline]
-    fn define_shape(
+    fn define_morph_shape(
         &mut self,
         context: &mut UpdateContext<'_, 'gc, '_>,
         reader: &mut SwfStream<'a>,
         version: u8,
     ) -> DecodeResult {
-        let movie = self.movie();
-        let swf_shape = reader.read_define_shape(version)?;
-        let id = swf_shape.id;
-        let graphic = Graphic::from_swf_tag(context, swf_shape, movie);
+        let swf_shape = reader.read_define_morph_shape(version)?;
+        let static_data = MorphShapeStatic::from_swf_tag(&swf_shape, self.movie());
+        let morph_shape = MorphShape::new(context.gc_context, static_data);
         context
             .library
             .library_for_movie_mut(self.movie())
-            .register_character(id, Character::Graphic(graphic));
+            .register_character(swf_shape.id, Character::MorphShape(morph_shape));
         Ok(())
     }
 
     #[inline]
-    fn preload_place_object(
+    fn define_shape(
         &mut self,
         context: &mut UpdateContext<'_, 'gc, '_>,
         reader: &mut SwfStream<'a>,
-        tag_len: usize,
-        ids: &mut fnv::FnvHashMap<Depth, CharacterId>,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
         version: u8,
     ) -> DecodeResult {
-        use swf::PlaceObjectAction;
-        let place_object = if version == 1 {
-            reader.read_place_object(tag_len)
-        } else {
-            reader.read_place_object_2_or_3(version)
-        }?;
-        match place_object.action {
-            PlaceObjectAction::Place(id) => {
-                if let Some(morph_shape) = morph_shapes.get_mut(&id) {
-                    ids.insert(place_object.depth.into(), id);
-                    if let Some(ratio) = place_object.ratio {
-                        morph_shape.register_ratio(context, ratio);
-                    }
-                }
-            }
-            PlaceObjectAction::Modify => {
-                if let Some(&id) = ids.get(&place_object.depth.into()) {
-                    if let Some(morph_shape) = morph_shapes.get_mut(&id) {
-                        ids.insert(place_object.depth.into(), id);
-                        if let Some(ratio) = place_object.ratio {
-                            morph_shape.register_ratio(context, ratio);
-                        }
-                    }
-                }
-            }
-            PlaceObjectAction::Replace(id) => {
-                if let Some(morph_shape) = morph_shapes.get_mut(&id) {
-                    ids.insert(place_object.depth.into(), id);
-                    if let Some(ratio) = place_object.ratio {
-                        morph_shape.register_ratio(context, ratio);
-                    }
-                } else {
-                    ids.remove(&place_object.depth.into());
-                }
-            }
-        };
-
+        let movie = self.movie();
+        let swf_shape = reader.read_define_shape(version)?;
+        let id = swf_shape.id;
+        let graphic = Graphic::from_swf_tag(context, swf_shape, movie);
+        context
+            .library
+            .library_for_movie_mut(self.movie())
+            .register_character(id, Character::Graphic(graphic));
         Ok(())
     }
 
@@ -2539,6 +2821,35 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    #[inline]
+    fn define_scaling_grid(
+        &mut self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        reader: &mut SwfStream<'a>,
+    ) -> DecodeResult {
+        let id = reader.read_u16()?;
+        let splitter_rect = reader.read_rectangle()?;
+        let library = context.library.library_for_movie_mut(self.movie());
+        match library.character_by_id(id) {
+            Some(Character::MovieClip(movie_clip)) => {
+                movie_clip.set_scaling_grid(context.gc_context, splitter_rect.into());
+            }
+            Some(_) => {
+                log::warn!(
+                    "Tried to apply DefineScalingGrid to non-sprite character ID {}",
+                    id
+                );
+            }
+            None => {
+                log::warn!(
+                    "Tried to apply DefineScalingGrid to unregistered character ID {}",
+                    id
+                );
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     fn preload_video_frame(
         &mut self,
@@ -2572,29 +2883,15 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<'a>,
         tag_len: usize,
     ) -> DecodeResult {
-        use std::io::Read;
         let id = reader.read_u16()?;
-        let data_len = tag_len - 2;
-        let mut jpeg_data = Vec::with_capacity(data_len);
-        reader.get_mut().read_to_end(&mut jpeg_data)?;
-        let bitmap_info = context.renderer.register_bitmap_jpeg(
-            &jpeg_data,
-            context
-                .library
-                .library_for_movie_mut(self.movie())
-                .jpeg_tables(),
-        )?;
-        let bitmap = crate::display_object::Bitmap::new(
-            context,
-            id,
-            bitmap_info.handle,
-            bitmap_info.width,
-            bitmap_info.height,
-        );
-        context
+        let jpeg_data = reader.read_slice(tag_len - 2)?;
+        let jpeg_tables = context
             .library
             .library_for_movie_mut(self.movie())
-            .register_character(id, Character::Bitmap(bitmap));
+            .jpeg_tables();
+        let jpeg_data = glue_tables_to_jpeg(jpeg_data, jpeg_tables.as_ref().map(SwfSlice::data));
+        let bitmap_info = context.renderer.register_bitmap_jpeg(&jpeg_data)?;
+        self.register_bitmap(context, id, bitmap_info);
         Ok(())
     }
 
@@ -2605,23 +2902,10 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<'a>,
         tag_len: usize,
     ) -> DecodeResult {
-        use std::io::Read;
         let id = reader.read_u16()?;
-        let data_len = tag_len - 2;
-        let mut jpeg_data = Vec::with_capacity(data_len);
-        reader.get_mut().read_to_end(&mut jpeg_data)?;
-        let bitmap_info = context.renderer.register_bitmap_jpeg_2(&jpeg_data)?;
-        let bitmap = crate::display_object::Bitmap::new(
-            context,
-            id,
-            bitmap_info.handle,
-            bitmap_info.width,
-            bitmap_info.height,
-        );
-        context
-            .library
-            .library_for_movie_mut(self.movie())
-            .register_character(id, Character::Bitmap(bitmap));
+        let jpeg_data = reader.read_slice(tag_len - 2)?;
+        let bitmap_info = context.renderer.register_bitmap_jpeg(jpeg_data)?;
+        self.register_bitmap(context, id, bitmap_info);
         Ok(())
     }
 
@@ -2632,36 +2916,16 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<'a>,
         tag_len: usize,
     ) -> DecodeResult {
-        use std::io::Read;
         let id = reader.read_u16()?;
         let jpeg_len = reader.read_u32()? as usize;
         let alpha_len = tag_len
             .checked_sub(jpeg_len + 6)
             .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "Invalid jpeg length"))?;
-        let mut jpeg_data = Vec::with_capacity(jpeg_len);
-        let mut alpha_data = Vec::with_capacity(alpha_len);
-        reader
-            .get_mut()
-            .take(jpeg_len as u64)
-            .read_to_end(&mut jpeg_data)?;
-        reader
-            .get_mut()
-            .take(alpha_len as u64)
-            .read_to_end(&mut alpha_data)?;
-        let bitmap_info = context
-            .renderer
-            .register_bitmap_jpeg_3(&jpeg_data, &alpha_data)?;
-        let bitmap = Bitmap::new(
-            context,
-            id,
-            bitmap_info.handle,
-            bitmap_info.width,
-            bitmap_info.height,
-        );
-        context
-            .library
-            .library_for_movie_mut(self.movie())
-            .register_character(id, Character::Bitmap(bitmap));
+        let jpeg_data = reader.read_slice(jpeg_len)?;
+        let alpha_data = reader.read_slice(alpha_len)?;
+        let bitmap = decode_define_bits_jpeg(jpeg_data, Some(alpha_data))?;
+        let bitmap_info = context.renderer.register_bitmap(bitmap)?;
+        self.register_bitmap(context, id, bitmap_info);
         Ok(())
     }
 
@@ -2672,37 +2936,17 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<'a>,
         tag_len: usize,
     ) -> DecodeResult {
-        use std::io::Read;
         let id = reader.read_u16()?;
         let jpeg_len = reader.read_u32()? as usize;
-        let _deblocking = reader.read_u16()?;
+        let deblocking = reader.read_u16()?;
         let alpha_len = tag_len
-            .checked_sub(jpeg_len + 6)
+            .checked_sub(jpeg_len + 8)
             .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "Invalid jpeg length"))?;
-        let mut jpeg_data = Vec::with_capacity(jpeg_len);
-        let mut alpha_data = Vec::with_capacity(alpha_len);
-        reader
-            .get_mut()
-            .take(jpeg_len as u64)
-            .read_to_end(&mut jpeg_data)?;
-        reader
-            .get_mut()
-            .take(alpha_len as u64)
-            .read_to_end(&mut alpha_data)?;
-        let bitmap_info = context
-            .renderer
-            .register_bitmap_jpeg_3(&jpeg_data, &alpha_data)?;
-        let bitmap = Bitmap::new(
-            context,
-            id,
-            bitmap_info.handle,
-            bitmap_info.width,
-            bitmap_info.height,
-        );
-        context
-            .library
-            .library_for_movie_mut(self.movie())
-            .register_character(id, Character::Bitmap(bitmap));
+        let jpeg_data = reader.read_slice(jpeg_len)?;
+        let alpha_data = reader.read_slice(alpha_len)?;
+        let bitmap = decode_define_bits_jpeg_4(jpeg_data, Some(alpha_data), deblocking)?;
+        let bitmap_info = context.renderer.register_bitmap(bitmap)?;
+        self.register_bitmap(context, id, bitmap_info);
         Ok(())
     }
 
@@ -2926,7 +3170,10 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<'a>,
     ) -> DecodeResult {
         let sound = reader.read_define_sound()?;
-        if let Ok(handle) = context.audio.register_sound(&sound) {
+        let registered = context
+            .audio
+            .register_sound_from_movie(&sound, &self.static_data.swf);
+        if let Ok(handle) = registered {
             context
                 .library
                 .library_for_movie_mut(self.movie())
@@ -2966,7 +3213,6 @@ impl<'gc, 'a> MovieClipData<'gc> {
         context: &mut UpdateContext<'_, 'gc, '_>,
         reader: &mut SwfStream<'a>,
         tag_len: usize,
-        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
     ) -> DecodeResult {
         let id = reader.read_character_id()?;
         let num_frames = reader.read_u16()?;
@@ -2985,7 +3231,7 @@ impl<'gc, 'a> MovieClipData<'gc> {
             num_frames,
         );
 
-        movie_clip.preload(context, morph_shapes);
+        movie_clip.preload(context);
 
         context
             .library
@@ -3030,16 +3276,10 @@ impl<'gc, 'a> MovieClipData<'gc> {
         let exports = reader.read_export_assets()?;
         for export in exports {
             let name = export.name.to_str_lossy(reader.encoding());
-            let character = context
+            context
                 .library
                 .library_for_movie_mut(self.movie())
                 .register_export(export.id, &name);
-
-            // TODO: do other types of Character need to know their exported name?
-            if let Some(Character::MovieClip(movie_clip)) = character {
-                *movie_clip.0.read().static_data.exported_name.borrow_mut() =
-                    Some(name.to_string());
-            }
         }
         Ok(())
     }
@@ -3054,15 +3294,16 @@ impl<'gc, 'a> MovieClipData<'gc> {
         static_data: &mut MovieClipStatic,
     ) -> DecodeResult {
         let frame_label = reader.read_frame_label(tag_len)?;
-        // Frame labels are case insensitive (ASCII).
-        let label = frame_label
-            .label
-            .to_str_lossy(reader.encoding())
-            .to_ascii_lowercase();
-        if let std::collections::hash_map::Entry::Vacant(v) = static_data.frame_labels.entry(label)
-        {
-            v.insert(cur_frame);
-        } else {
+        let original_label = frame_label.label.to_str_lossy(reader.encoding());
+        if frame_label.is_anchor {
+            // Anchors are exposed to the browser, so keep the author's casing.
+            static_data
+                .frame_anchors
+                .entry(cur_frame)
+                .or_insert_with(|| original_label.to_string());
+        }
+
+        if !static_data.add_frame_label(original_label.to_string(), cur_frame) {
             log::warn!("Movie clip {}: Duplicated frame label", self.id());
         }
         Ok(())
@@ -3075,31 +3316,18 @@ impl<'gc, 'a> MovieClipData<'gc> {
         reader: &mut SwfStream<'a>,
         tag_len: usize,
     ) -> DecodeResult {
-        use std::io::Read;
-        // TODO(Herschel): Can we use a slice instead of copying?
-        let mut jpeg_data = Vec::with_capacity(tag_len);
-        reader.get_mut().read_to_end(&mut jpeg_data)?;
+        let jpeg_data = reader.read_slice(tag_len)?;
+        let jpeg_tables = self
+            .static_data
+            .swf
+            .to_unbounded_subslice(jpeg_data)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::Other, "JPEGTables outside of movie")
+            })?;
         context
             .library
             .library_for_movie_mut(self.movie())
-            .set_jpeg_tables(jpeg_data);
-        Ok(())
-    }
-
-    #[inline]
-    fn preload_remove_object(
-        &mut self,
-        _context: &mut UpdateContext<'_, 'gc, '_>,
-        reader: &mut SwfStream<'a>,
-        ids: &mut fnv::FnvHashMap<Depth, CharacterId>,
-        version: u8,
-    ) -> DecodeResult {
-        let remove_object = if version == 1 {
-            reader.read_remove_object_1()
-        } else {
-            reader.read_remove_object_2()
-        }?;
-        ids.remove(&remove_object.depth.into());
+            .set_jpeg_tables(jpeg_tables);
         Ok(())
     }
 
@@ -3107,10 +3335,19 @@ impl<'gc, 'a> MovieClipData<'gc> {
     fn preload_show_frame(
         &mut self,
         _context: &mut UpdateContext<'_, 'gc, '_>,
-        _reader: &mut SwfStream<'a>,
+        reader: &mut SwfStream<'a>,
         cur_frame: &mut FrameNumber,
+        static_data: &mut MovieClipStatic,
     ) -> DecodeResult {
         *cur_frame += 1;
+
+        // The next frame starts right after this tag.
+        let tag_stream_start = static_data.swf.as_ref().as_ptr() as u64;
+        let tag_end = reader.get_ref().as_ptr() as u64 + reader.get_ref().len() as u64;
+        static_data.frames.push(FrameInfo {
+            tag_stream_pos: tag_end - tag_stream_start,
+            has_display_tags: false,
+        });
         Ok(())
     }
 }
@@ -3178,6 +3415,13 @@ impl<'gc, 'a> MovieClip<'gc> {
                     &place_object,
                     matches!(place_object.action, PlaceObjectAction::Replace(_)),
                 ) {
+                    // Graphic symbols may start partway into their timeline.
+                    // Afterwards they play along with us, until a goto.
+                    if let (Some(ratio), Some(child_clip)) =
+                        (place_object.ratio, child.as_movie_clip())
+                    {
+                        child_clip.sync_timeline(context, ratio.into());
+                    }
                     child
                 } else {
                     return Ok(());
@@ -3249,21 +3493,14 @@ impl<'gc, 'a> MovieClip<'gc> {
         if mc.playing() {
             if let (Some(stream_info), None) = (&mc.static_data.audio_stream_info, mc.audio_stream)
             {
-                let slice = mc
-                    .static_data
-                    .swf
-                    .to_start_and_end(mc.tag_stream_pos as usize, mc.tag_stream_len())
-                    .ok_or_else(|| {
-                        std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "Invalid slice generated when constructing sound stream block",
-                        )
-                    })?;
+                // The current frame has already been advanced to the frame whose tags
+                // are running, so this block belongs to it. Frames are counted from 1,
+                // as they are when the stream is preloaded.
                 let audio_stream = context.start_stream(
                     mc.static_data.audio_stream_handle,
-                    self,
-                    mc.current_frame() + 1,
-                    slice,
+                    Some(self.into()),
+                    mc.current_frame(),
+                    mc.static_data.swf.clone(),
                     &stream_info,
                 );
                 drop(mc);
@@ -3319,7 +3556,7 @@ impl<'gc, 'a> MovieClip<'gc> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Scene {
     pub name: String,
     pub start: FrameNumber,
@@ -3336,21 +3573,51 @@ impl Default for Scene {
     }
 }
 
+/// How the frame labels of a movie are matched by goto and label lookups.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LabelMatching {
+    /// Whether labels that only differ in case are different labels.
+    case_sensitive: bool,
+}
+
+impl LabelMatching {
+    /// The first SWF version whose AVM1 frame labels are case-sensitive, if
+    /// any. Unlike identifiers, AVM1 labels ignore case in every version.
+    const AVM1_CASE_SENSITIVE_VERSION: Option<u8> = None;
+
+    /// The rules Flash Player applies to the labels of a movie: AVM2 movies
+    /// match labels exactly, while AVM1 movies ignore ASCII case.
+    fn for_movie(movie: &SwfMovie) -> Self {
+        let case_sensitive = movie.is_action_script_3()
+            || Self::AVM1_CASE_SENSITIVE_VERSION
+                .map_or(false, |version| movie.version() >= version);
+        Self { case_sensitive }
+    }
+}
+
 /// Static data shared between all instances of a movie clip.
 #[allow(dead_code)]
-#[derive(Clone, Collect)]
-#[collect(require_static)]
+#[derive(Debug)]
 struct MovieClipStatic {
     id: CharacterId,
     swf: SwfSlice,
-    frame_labels: HashMap<String, FrameNumber>,
+    /// The labels on each frame in the author's casing, in the order they
+    /// were defined. A label may be reused in different scenes.
+    frame_labels: BTreeMap<FrameNumber, Vec<String>>,
+    /// The frame of each label, keyed by the start of the scene it is in and
+    /// the label as normalized by `normalize_label`.
+    frame_label_index: HashMap<(FrameNumber, String), FrameNumber>,
+    /// How the frame labels of this clip's movie are matched.
+    label_matching: LabelMatching,
+    /// Frame labels flagged as named anchors, keyed by frame number.
+    frame_anchors: HashMap<FrameNumber, String>,
     scene_labels: HashMap<String, Scene>,
     audio_stream_info: Option<swf::SoundStreamHead>,
     audio_stream_handle: Option<SoundHandle>,
     total_frames: FrameNumber,
-    /// The last known symbol name under which this movie clip was exported.
-    /// Used for looking up constructors registered with `Object.registerClass`.
-    exported_name: RefCell<Option<String>>,
+    /// Where each frame starts in the tag stream, indexed from the first
+    /// frame. This is only filled in once the clip is preloaded.
+    frames: Vec<FrameInfo>,
 }
 
 impl MovieClipStatic {
@@ -3359,17 +3626,110 @@ impl MovieClipStatic {
     }
 
     fn with_data(id: CharacterId, swf: SwfSlice, total_frames: FrameNumber) -> Self {
+        let label_matching = LabelMatching::for_movie(&swf.movie);
         Self {
             id,
             swf,
             total_frames,
-            frame_labels: HashMap::new(),
+            frame_labels: BTreeMap::new(),
+            label_matching,
+            frame_label_index: HashMap::new(),
+            frame_anchors: HashMap::new(),
             scene_labels: HashMap::new(),
             audio_stream_info: None,
             audio_stream_handle: None,
-            exported_name: RefCell::new(None),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Whether the clip's tags have been read.
+    fn is_preloaded(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    /// Returns the scene that the given frame belongs to, if the clip has
+    /// any scenes.
+    fn scene_containing(&self, frame: FrameNumber) -> Option<&Scene> {
+        self.scene_labels
+            .values()
+            .filter(|scene| scene.start <= frame)
+            .max_by_key(|scene| scene.start)
+    }
+
+    /// The start of the scene that the given frame belongs to, or 0 if the
+    /// clip has no scenes.
+    fn scene_start(&self, frame: FrameNumber) -> FrameNumber {
+        self.scene_containing(frame).map_or(0, |scene| scene.start)
+    }
+
+    /// Normalize a frame label, so that labels that are the same label
+    /// compare equal.
+    fn normalize_label(&self, label: &str) -> String {
+        if self.label_matching.case_sensitive {
+            label.to_string()
+        } else {
+            label.to_ascii_lowercase()
+        }
+    }
+
+    /// The frame of a label in the scene starting at `scene_start`.
+    fn frame_label_in_scene(&self, label: &str, scene_start: FrameNumber) -> Option<FrameNumber> {
+        self.frame_label_index
+            .get(&(scene_start, self.normalize_label(label)))
+            .copied()
+    }
+
+    /// Add a frame label, ignoring repeats of a label already on that frame.
+    ///
+    /// Returns `false` if the label is already used elsewhere in the same
+    /// scene, in which case the earlier frame keeps it.
+    fn add_frame_label(&mut self, label: String, frame: FrameNumber) -> bool {
+        let key = (self.scene_start(frame), self.normalize_label(&label));
+        match self.frame_label_index.get(&key) {
+            Some(existing_frame) => *existing_frame == frame,
+            None => {
+                self.frame_label_index.insert(key, frame);
+                self.frame_labels.entry(frame).or_default().push(label);
+                true
+            }
+        }
+    }
+
+    /// Get the preloaded information about a frame, if there is any.
+    fn frame_info(&self, frame: FrameNumber) -> Option<&FrameInfo> {
+        self.frames.get(usize::from(frame).checked_sub(1)?)
+    }
+
+    /// Find where the frame after the given one starts, if the given frame
+    /// has no tags that change the display list and can be skipped by gotos.
+    fn skip_frame(&self, frame: FrameNumber) -> Option<u64> {
+        let info = self.frame_info(frame)?;
+        if info.has_display_tags {
+            return None;
         }
+        self.frame_info(frame.checked_add(1)?)
+            .map(|next| next.tag_stream_pos)
     }
+
+    /// Note that the frame being preloaded places or removes objects.
+    fn mark_display_tags(&mut self) {
+        if let Some(info) = self.frames.last_mut() {
+            info.has_display_tags = true;
+        }
+    }
+}
+
+/// Where a frame is in a movie clip's tag stream.
+///
+/// This is found when the clip is preloaded, so that gotos and frame lookups
+/// can jump straight to a frame instead of reading every tag before it.
+#[derive(Debug, Default)]
+struct FrameInfo {
+    /// The position of the frame's first tag, from the start of the tag stream.
+    tag_stream_pos: u64,
+
+    /// Whether the frame places or removes any objects.
+    has_display_tags: bool,
 }
 
 /// Stores the placement settings for display objects during a
@@ -3380,17 +3740,16 @@ struct GotoPlaceObject<'a> {
     frame: FrameNumber,
     /// The display properties of the object.
     place_object: swf::PlaceObject<'a>,
-    /// Increasing index of this place command, for sorting.
-    index: usize,
+    /// The frame on which the timeline last gave this object a ratio, if any.
+    ///
+    /// This is tracked separately from the ratio itself, as rewinds fill in a
+    /// default ratio that must not be used to synchronize movie clips.
+    ratio_frame: Option<FrameNumber>,
 }
 
 impl<'a> GotoPlaceObject<'a> {
-    fn new(
-        frame: FrameNumber,
-        mut place_object: swf::PlaceObject<'a>,
-        is_rewind: bool,
-        index: usize,
-    ) -> Self {
+    fn new(frame: FrameNumber, mut place_object: swf::PlaceObject<'a>, is_rewind: bool) -> Self {
+        let ratio_frame = place_object.ratio.map(|_| frame);
         if is_rewind {
             if let swf::PlaceObjectAction::Place(_) = place_object.action {
                 if place_object.matrix.is_none() {
@@ -3411,13 +3770,19 @@ impl<'a> GotoPlaceObject<'a> {
                 if place_object.class_name.is_none() {
                     place_object.class_name = Some(Default::default());
                 }
+                if place_object.filters.is_none() {
+                    place_object.filters = Some(Default::default());
+                }
+                if place_object.blend_mode.is_none() {
+                    place_object.blend_mode = Some(swf::BlendMode::Normal);
+                }
             }
         }
 
         Self {
             frame,
             place_object,
-            index,
+            ratio_frame,
         }
     }
 
@@ -3449,10 +3814,14 @@ impl<'a> GotoPlaceObject<'a> {
         match (cur_place.action, next_place.action) {
             (cur, PlaceObjectAction::Modify) => {
                 cur_place.action = cur;
+                if next.ratio_frame.is_some() {
+                    self.ratio_frame = next.ratio_frame;
+                }
             }
             (_, new) => {
                 cur_place.action = new;
                 self.frame = next.frame;
+                self.ratio_frame = next.ratio_frame;
             }
         };
         if next_place.matrix.is_some() {
@@ -3476,6 +3845,12 @@ impl<'a> GotoPlaceObject<'a> {
         if next_place.background_color.is_some() {
             cur_place.background_color = next_place.background_color.take();
         }
+        if next_place.filters.is_some() {
+            cur_place.filters = next_place.filters.take();
+        }
+        if next_place.blend_mode.is_some() {
+            cur_place.blend_mode = next_place.blend_mode.take();
+        }
         // TODO: Other stuff.
     }
 }
@@ -3583,3 +3958,75 @@ pub struct Avm2FrameScript<'gc> {
     /// The AVM2 callable object to invoke when the frame script runs.
     pub callable: Avm2Object<'gc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nine_slice_corners_keep_their_size() {
+        let px = Twips::from_pixels;
+        let segments = |scale| nine_slice_segments(px(0.0), px(20.0), px(80.0), px(100.0), scale);
+
+        // Doubling the clip halves the corners, so they're drawn unscaled.
+        assert_eq!(
+            segments(2.0),
+            Some([
+                ((0.0, 400.0), (0.0, 200.0)),
+                ((400.0, 1600.0), (200.0, 1800.0)),
+                ((1600.0, 2000.0), (1800.0, 2000.0)),
+            ])
+        );
+
+        // Corners that don't fit shrink to share the clip, and the center
+        // disappears.
+        assert_eq!(
+            segments(0.125),
+            Some([
+                ((0.0, 400.0), (0.0, 1000.0)),
+                ((400.0, 1600.0), (1000.0, 1000.0)),
+                ((1600.0, 2000.0), (1000.0, 2000.0)),
+            ])
+        );
+    }
+
+    #[test]
+    fn nine_slice_grids_must_be_inside_the_clip() {
+        let px = Twips::from_pixels;
+        assert_eq!(
+            nine_slice_segments(px(0.0), px(-1.0), px(80.0), px(100.0), 1.0),
+            None
+        );
+        assert_eq!(
+            nine_slice_segments(px(0.0), px(20.0), px(101.0), px(100.0), 1.0),
+            None
+        );
+        assert_eq!(
+            nine_slice_segments(px(0.0), px(50.0), px(50.0), px(100.0), 1.0),
+            None
+        );
+    }
+
+    #[test]
+    fn frames_without_display_tags_are_skipped() {
+        let movie = Arc::new(SwfMovie::empty(10));
+        let mut static_data = MovieClipStatic::empty(SwfSlice::empty(movie));
+        for (tag_stream_pos, has_display_tags) in &[(0, true), (10, false), (20, false)] {
+            static_data.frames.push(FrameInfo {
+                tag_stream_pos: *tag_stream_pos,
+                has_display_tags: *has_display_tags,
+            });
+        }
+
+        assert_eq!(
+            static_data.frame_info(2).map(|info| info.tag_stream_pos),
+            Some(10)
+        );
+        assert_eq!(static_data.skip_frame(1), None);
+        assert_eq!(static_data.skip_frame(2), Some(20));
+
+        // The last frame has nothing after it to skip to.
+        assert_eq!(static_data.skip_frame(3), None);
+        assert!(static_data.frame_info(0).is_none());
+    }
+}
@@ -2,12 +2,12 @@
 
 use crate::avm1::Object as Avm1Object;
 use crate::avm2::{
-    Activation as Avm2Activation, Event as Avm2Event, Namespace as Avm2Namespace,
+    Activation as Avm2Activation, AvmString, Event as Avm2Event, Namespace as Avm2Namespace,
     Object as Avm2Object, QName as Avm2QName, ScriptObject as Avm2ScriptObject,
     StageObject as Avm2StageObject, Value as Avm2Value,
 };
 use crate::backend::ui::UiBackend;
-use crate::config::Letterbox;
+use crate::config::{Letterbox, LoopPolicy, StageQuality};
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::container::{
     ChildContainer, DisplayObjectContainer, TDisplayObjectContainer,
@@ -65,6 +65,16 @@ pub struct StageData<'gc> {
     /// The alignment of the stage.
     align: StageAlign,
 
+    /// The rendering quality of the stage.
+    quality: StageQuality,
+
+    /// The orientation of the device's screen.
+    orientation: StageOrientation,
+
+    /// Whether objects focused with the keyboard are highlighted, unless
+    /// they override it with their own `_focusrect` property.
+    focus_rect: bool,
+
     /// The dimensions of the stage's containing viewport.
     #[collect(require_static)]
     viewport_size: (u32, u32),
@@ -79,6 +89,25 @@ pub struct StageData<'gc> {
     /// Whether to show default context menu items
     show_menu: bool,
 
+    /// Whether `Stage.invalidate` has been called since the last `render`
+    /// event was broadcast.
+    invalidated: bool,
+
+    /// What the root timeline does once it plays past its last frame.
+    loop_policy: LoopPolicy,
+
+    /// Whether the root timeline has been held on its last frame by the
+    /// loop policy since this was last checked.
+    held_last_frame: bool,
+
+    /// Counts the renders of the stage or of its objects, so that caches of
+    /// renderer resources can tell which of them the current render used.
+    render_generation: u64,
+
+    /// Whether the movie asked a `Stage3D` for a 3D context, which Ruffle
+    /// can't provide.
+    stage3d_requested: bool,
+
     /// The AVM2 view of this stage object.
     avm2_object: Avm2Object<'gc>,
 }
@@ -96,10 +125,18 @@ impl<'gc> Stage<'gc> {
                 stage_size: (width, height),
                 scale_mode: Default::default(),
                 align: Default::default(),
+                quality: Default::default(),
+                orientation: Default::default(),
+                focus_rect: true,
                 viewport_size: (width, height),
                 viewport_scale_factor: 1.0,
                 view_bounds: Default::default(),
                 show_menu: true,
+                invalidated: false,
+                loop_policy: LoopPolicy::Loop,
+                held_last_frame: false,
+                render_generation: 0,
+                stage3d_requested: false,
                 avm2_object: Avm2ScriptObject::bare_object(gc_context),
             },
         ))
@@ -174,6 +211,52 @@ impl<'gc> Stage<'gc> {
         self.build_matrices(context);
     }
 
+    /// Get the rendering quality of the stage.
+    pub fn quality(self) -> StageQuality {
+        self.0.read().quality
+    }
+
+    /// Set the rendering quality of the stage, which the render backend may
+    /// use to adjust its anti-aliasing.
+    pub fn set_quality(self, context: &mut UpdateContext<'_, 'gc, '_>, quality: StageQuality) {
+        self.0.write(context.gc_context).quality = quality;
+        context.renderer.set_quality(quality);
+    }
+
+    /// Whether objects focused with the keyboard are highlighted, unless
+    /// they override it. This is the global `_focusrect` property.
+    pub fn focus_rect(self) -> bool {
+        self.0.read().focus_rect
+    }
+
+    pub fn set_focus_rect(self, gc_context: MutationContext<'gc, '_>, focus_rect: bool) {
+        self.0.write(gc_context).focus_rect = focus_rect;
+    }
+
+    pub fn orientation(self) -> StageOrientation {
+        self.0.read().orientation
+    }
+
+    /// Set the orientation of the device's screen, relative to its default
+    /// orientation, and send AVM2 content an `orientationChange` event.
+    ///
+    /// The orientation doesn't affect how the stage is laid out. Rotating a
+    /// device resizes the viewport as well, which the frontend reports with
+    /// `set_viewport_size`.
+    pub fn set_orientation(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        orientation: StageOrientation,
+    ) {
+        let before = std::mem::replace(
+            &mut self.0.write(context.gc_context).orientation,
+            orientation,
+        );
+        if before != orientation {
+            self.fire_orientation_change_event(context, before, orientation);
+        }
+    }
+
     /// Get the current viewport size, in device pixels.
     pub fn viewport_size(self) -> (u32, u32) {
         self.0.read().viewport_size
@@ -217,6 +300,60 @@ impl<'gc> Stage<'gc> {
         write.show_menu = show_menu;
     }
 
+    /// Request a `render` event before the next frame is drawn.
+    pub fn invalidate(self, gc_context: MutationContext<'gc, '_>) {
+        self.0.write(gc_context).invalidated = true;
+    }
+
+    /// Check whether a `render` event was requested, clearing the request.
+    pub fn take_invalidated(self, gc_context: MutationContext<'gc, '_>) -> bool {
+        std::mem::take(&mut self.0.write(gc_context).invalidated)
+    }
+
+    pub fn loop_policy(self) -> LoopPolicy {
+        self.0.read().loop_policy
+    }
+
+    pub fn set_loop_policy(self, gc_context: MutationContext<'gc, '_>, loop_policy: LoopPolicy) {
+        self.0.write(gc_context).loop_policy = loop_policy;
+    }
+
+    /// Record that the root timeline reached its last frame and was stopped
+    /// there by `LoopPolicy::Once`.
+    pub fn hold_last_frame(self, gc_context: MutationContext<'gc, '_>) {
+        self.0.write(gc_context).held_last_frame = true;
+    }
+
+    /// Check whether the root timeline was held on its last frame, clearing
+    /// the flag.
+    pub fn take_held_last_frame(self, gc_context: MutationContext<'gc, '_>) -> bool {
+        std::mem::take(&mut self.0.write(gc_context).held_last_frame)
+    }
+
+    /// Whether the movie asked a `Stage3D` for a 3D context.
+    pub fn stage3d_requested(self) -> bool {
+        self.0.read().stage3d_requested
+    }
+
+    /// Record that the movie asked a `Stage3D` for a 3D context.
+    pub fn set_stage3d_requested(self, gc_context: MutationContext<'gc, '_>) {
+        self.0.write(gc_context).stage3d_requested = true;
+    }
+
+    /// Start a new render of the stage, or of some of its objects.
+    ///
+    /// Renderers may draw shapes only at the end of a render, so a shape that
+    /// was drawn during the current render must not be replaced.
+    pub fn begin_render(self, gc_context: MutationContext<'gc, '_>) {
+        let mut write = self.0.write(gc_context);
+        write.render_generation = write.render_generation.wrapping_add(1);
+    }
+
+    /// The number of the current render, as started by `begin_render`.
+    pub fn render_generation(self) -> u64 {
+        self.0.read().render_generation
+    }
+
     /// Determine if we should letterbox the stage content.
     fn should_letterbox(self, ui: &mut dyn UiBackend) -> bool {
         // Only enable letterbox is the default `ShowAll` scale mode.
@@ -429,6 +566,37 @@ impl<'gc> Stage<'gc> {
             .expect("Stage must always have a root movie")
     }
 
+    /// Unload the movie in a level, as with `unloadMovieNum`.
+    ///
+    /// Other levels are removed from the stage entirely. Unloading `_level0`
+    /// unloads every level, but leaves an empty movie clip in `_level0`.
+    pub fn unload_level(self, context: &mut UpdateContext<'_, 'gc, '_>, level_id: Depth) {
+        if level_id == 0 {
+            self.unload_other_levels(context);
+            if let Some(mut root) = self.root_clip().as_movie_clip() {
+                root.unload(context);
+                root.replace_with_movie(context.gc_context, None);
+            }
+        } else if let Some(level) = self.child_by_depth(level_id) {
+            let mut stage = self;
+            stage.remove_child(context, level, Lists::all());
+        }
+    }
+
+    /// Remove every level except `_level0`, as when a new movie is loaded
+    /// into `_level0`.
+    pub fn unload_other_levels(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let levels: Vec<_> = self
+            .iter_depth_list()
+            .filter(|(depth, _)| *depth != 0)
+            .map(|(_, level)| level)
+            .collect();
+        let mut stage = self;
+        for level in levels {
+            stage.remove_child(context, level, Lists::all());
+        }
+    }
+
     /// Fires `Stage.onResize` in AVM1 or `Event.RESIZE` in AVM2.
     fn fire_resize_event(self, context: &mut UpdateContext<'_, 'gc, '_>) {
         // This event fires immediately when scaleMode is changed;
@@ -452,6 +620,54 @@ impl<'gc> Stage<'gc> {
             }
         }
     }
+
+    /// Fires `StageOrientationEvent.ORIENTATION_CHANGE` in AVM2.
+    ///
+    /// AVM1 has no way to observe the orientation of the device.
+    fn fire_orientation_change_event(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        before: StageOrientation,
+        after: StageOrientation,
+    ) {
+        if let Avm2Value::Object(stage) = self.object2() {
+            let result = Self::orientation_change_event(context, before, after)
+                .and_then(|event| crate::avm2::Avm2::dispatch_event_object(context, event, stage));
+            if let Err(e) = result {
+                log::error!("Encountered AVM2 error when dispatching event: {}", e);
+            }
+        }
+    }
+
+    /// Construct a `StageOrientationEvent` for a change of orientation.
+    fn orientation_change_event(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        before: StageOrientation,
+        after: StageOrientation,
+    ) -> Result<Avm2Object<'gc>, crate::avm2::Error> {
+        use crate::avm2::TObject;
+        let mut activation = Avm2Activation::from_nothing(context.reborrow());
+        let mc = activation.context.gc_context;
+        let args: [Avm2Value<'gc>; 5] = [
+            "orientationChange".into(),
+            false.into(),
+            false.into(),
+            AvmString::new(mc, before.to_string()).into(),
+            AvmString::new(mc, after.to_string()).into(),
+        ];
+        let mut proto = activation.context.avm2.prototypes().stageorientationevent;
+        let event = proto.construct(&mut activation, &args)?;
+        let constructor = proto
+            .get_property(
+                proto,
+                &Avm2QName::new(Avm2Namespace::public(), "constructor"),
+                &mut activation,
+            )?
+            .coerce_to_object(&mut activation)?;
+        constructor.call(Some(event), &args, &mut activation, Some(proto))?;
+
+        Ok(event)
+    }
 }
 
 impl<'gc> TDisplayObject<'gc> for Stage<'gc> {
@@ -526,6 +742,13 @@ impl<'gc> TDisplayObject<'gc> for Stage<'gc> {
             self.draw_letterbox(context);
         }
 
+        let focus_tracker = context.focus_tracker;
+        focus_tracker.render_highlight(context);
+
+        if let Some(debug_overlay) = context.debug_overlay.take() {
+            debug_overlay.draw(context.renderer);
+        }
+
         context.renderer.end_frame();
     }
 
@@ -607,6 +830,112 @@ impl FromStr for StageScaleMode {
     }
 }
 
+/// The orientation of the device's screen, relative to its default
+/// orientation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+pub enum StageOrientation {
+    /// The screen is in its default orientation, e.g. portrait on most phones.
+    Default,
+
+    /// The device is turned to the left, a quarter turn counterclockwise.
+    RotatedLeft,
+
+    /// The device is turned to the right, a quarter turn clockwise.
+    RotatedRight,
+
+    /// The device is upside down.
+    UpsideDown,
+
+    /// The orientation can't be determined.
+    Unknown,
+}
+
+impl StageOrientation {
+    /// The orientation of a screen rotated counterclockwise by the given
+    /// angle in degrees, such as the `screen.orientation.angle` of a browser.
+    pub fn from_angle(degrees: i32) -> Self {
+        match degrees.rem_euclid(360) {
+            0 => StageOrientation::Default,
+            90 => StageOrientation::RotatedLeft,
+            180 => StageOrientation::UpsideDown,
+            270 => StageOrientation::RotatedRight,
+            _ => StageOrientation::Unknown,
+        }
+    }
+
+    /// The angle in degrees that a screen in this orientation is rotated
+    /// counterclockwise by, or `None` if it isn't known.
+    pub fn angle(self) -> Option<i32> {
+        match self {
+            StageOrientation::Default => Some(0),
+            StageOrientation::RotatedLeft => Some(90),
+            StageOrientation::UpsideDown => Some(180),
+            StageOrientation::RotatedRight => Some(270),
+            StageOrientation::Unknown => None,
+        }
+    }
+
+    /// Find where a point of a viewport of the given size ends up in the
+    /// viewport once the screen turns from this orientation to another.
+    ///
+    /// The point stays put on the screen, while the viewport's axes turn with
+    /// it. If either orientation is unknown, the point is left as it is.
+    pub fn rotate_point(
+        self,
+        to: StageOrientation,
+        (width, height): (f64, f64),
+        (x, y): (f64, f64),
+    ) -> (f64, f64) {
+        let turn = match (self.angle(), to.angle()) {
+            (Some(from), Some(to)) => (to - from).rem_euclid(360),
+            _ => 0,
+        };
+        match turn {
+            90 => (y, width - x),
+            180 => (width - x, height - y),
+            270 => (height - y, x),
+            _ => (x, y),
+        }
+    }
+}
+
+impl Default for StageOrientation {
+    fn default() -> StageOrientation {
+        StageOrientation::Default
+    }
+}
+
+impl Display for StageOrientation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Match string values returned by AS.
+        let s = match *self {
+            StageOrientation::Default => "default",
+            StageOrientation::RotatedLeft => "rotatedLeft",
+            StageOrientation::RotatedRight => "rotatedRight",
+            StageOrientation::UpsideDown => "upsideDown",
+            StageOrientation::Unknown => "unknown",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for StageOrientation {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let orientation = match s {
+            "default" => StageOrientation::Default,
+            "rotatedLeft" => StageOrientation::RotatedLeft,
+            "rotatedRight" => StageOrientation::RotatedRight,
+            "upsideDown" => StageOrientation::UpsideDown,
+            "unknown" => StageOrientation::Unknown,
+            _ => return Err(ParseEnumError),
+        };
+        Ok(orientation)
+    }
+}
+
 bitflags! {
     /// The alignment of the stage.
     /// This controls the position of the movie after scaling to fill the viewport.
@@ -650,3 +979,73 @@ impl FromStr for StageAlign {
         Ok(align)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orientation_from_angle() {
+        assert_eq!(StageOrientation::from_angle(0), StageOrientation::Default);
+        assert_eq!(
+            StageOrientation::from_angle(90),
+            StageOrientation::RotatedLeft
+        );
+        assert_eq!(
+            StageOrientation::from_angle(180),
+            StageOrientation::UpsideDown
+        );
+        assert_eq!(
+            StageOrientation::from_angle(270),
+            StageOrientation::RotatedRight
+        );
+
+        // Angles wrap around, and ones that aren't a quarter turn are unknown.
+        assert_eq!(
+            StageOrientation::from_angle(-90),
+            StageOrientation::RotatedRight
+        );
+        assert_eq!(
+            StageOrientation::from_angle(450),
+            StageOrientation::RotatedLeft
+        );
+        assert_eq!(StageOrientation::from_angle(45), StageOrientation::Unknown);
+
+        for orientation in &[
+            StageOrientation::Default,
+            StageOrientation::RotatedLeft,
+            StageOrientation::RotatedRight,
+            StageOrientation::UpsideDown,
+        ] {
+            let angle = orientation.angle().unwrap();
+            assert_eq!(StageOrientation::from_angle(angle), *orientation);
+        }
+        assert_eq!(StageOrientation::Unknown.angle(), None);
+    }
+
+    #[test]
+    fn rotated_points_stay_put_on_the_screen() {
+        let portrait = (100.0, 200.0);
+        let point = (10.0, 20.0);
+
+        // Turning a portrait screen left moves its right edge to the top.
+        let left =
+            StageOrientation::Default.rotate_point(StageOrientation::RotatedLeft, portrait, point);
+        assert_eq!(left, (20.0, 90.0));
+
+        // Turning it back again puts the point where it was.
+        let landscape = (200.0, 100.0);
+        let back =
+            StageOrientation::RotatedLeft.rotate_point(StageOrientation::Default, landscape, left);
+        assert_eq!(back, point);
+
+        assert_eq!(
+            StageOrientation::Default.rotate_point(StageOrientation::UpsideDown, portrait, point),
+            (90.0, 180.0)
+        );
+        assert_eq!(
+            StageOrientation::Default.rotate_point(StageOrientation::Unknown, portrait, point),
+            point
+        );
+    }
+}
@@ -9,13 +9,15 @@ use crate::collect::CollectWrapper;
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::{DisplayObjectBase, TDisplayObject};
 use crate::prelude::*;
+use crate::streams::NetStream;
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::types::{Degrees, Percent};
 use crate::vminterface::{AvmObject, AvmType, Instantiator};
 use gc_arena::{Collect, GcCell, MutationContext};
 use std::borrow::{Borrow, BorrowMut};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use swf::{CharacterId, DefineVideoStream, VideoFrame};
 
 /// A Video display object is a high-level interface to a video player.
@@ -42,6 +44,18 @@ pub struct VideoData<'gc> {
     /// The last decoded frame in the video stream.
     decoded_frame: Option<(u32, CollectWrapper<BitmapInfo>)>,
 
+    /// The frames the timeline has asked for that haven't been shown yet.
+    ///
+    /// Frames are decoded right before the video is drawn, rather than as
+    /// the timeline reaches them. See `present_frame` for which ones get
+    /// shown.
+    #[collect(require_static)]
+    frame_queue: VecDeque<QueuedFrame>,
+
+    /// How many frames this video player has shown and dropped.
+    #[collect(require_static)]
+    frame_stats: VideoFrameStats,
+
     /// AVM representation of this video player.
     object: Option<AvmObject<'gc>>,
 
@@ -52,6 +66,50 @@ pub struct VideoData<'gc> {
     /// the prior keyframe. The first frame in the stream will always be
     /// treated as a keyframe regardless of it being flagged as one.
     keyframes: BTreeSet<u32>,
+
+    /// The `NetStream` attached to this video player, if any.
+    ///
+    /// Frames from an attached stream are displayed in place of the frames
+    /// of the video player's own source.
+    net_stream: Option<NetStream<'gc>>,
+}
+
+/// A frame that the timeline asked a video player to show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct QueuedFrame {
+    frame_id: u32,
+
+    /// The time on the movie clock at which the frame is due to be shown.
+    due: Duration,
+}
+
+/// Pick the frame of a queue to show at the time `now` on the movie clock.
+///
+/// This is the last frame that is already due. The frames queued before it
+/// were due even earlier, so showing them now would only make the video fall
+/// further behind; they are removed from the queue and counted as dropped.
+/// Frames that aren't due yet are left in the queue.
+fn next_due_frame(queue: &mut VecDeque<QueuedFrame>, now: Duration) -> Option<(u32, u32)> {
+    let num_due = queue.iter().take_while(|frame| frame.due <= now).count();
+    let mut due_frames = queue.drain(..num_due);
+    let frame = due_frames.next_back()?;
+
+    Some((frame.frame_id, due_frames.len() as u32))
+}
+
+/// The number of frames of a video that were shown, or dropped because the
+/// player fell behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VideoFrameStats {
+    pub presented: u32,
+    pub dropped: u32,
+}
+
+impl std::ops::AddAssign for VideoFrameStats {
+    fn add_assign(&mut self, other: Self) {
+        self.presented += other.presented;
+        self.dropped += other.dropped;
+    }
 }
 
 /// An optionally-instantiated video stream.
@@ -110,12 +168,25 @@ impl<'gc> Video<'gc> {
                 source,
                 stream: VideoStream::Uninstantiated(0),
                 decoded_frame: None,
+                frame_queue: VecDeque::new(),
+                frame_stats: Default::default(),
                 object: None,
                 keyframes: BTreeSet::new(),
+                net_stream: None,
             },
         ))
     }
 
+    /// Attach a `NetStream` to this video player, or detach the current one
+    /// if `None` is given.
+    pub fn attach_net_stream(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        net_stream: Option<NetStream<'gc>>,
+    ) {
+        self.0.write(gc_context).net_stream = net_stream;
+    }
+
     /// Preload frame data from an SWF.
     ///
     /// This function yields an error if this video player is not playing an
@@ -148,25 +219,24 @@ impl<'gc> Video<'gc> {
         }
     }
 
+    /// How many frames this video player has shown and dropped.
+    pub fn frame_stats(self) -> VideoFrameStats {
+        self.0.read().frame_stats
+    }
+
     /// Seek to a particular frame in the video stream.
     ///
-    /// This function ensures that the given `frame_id` is valid by first
-    /// wrapping it to the underlying video stream's boundaries, and then
-    /// snapping it to the last independently seekable frame. Then, all frames
-    /// from that keyframe up to the (wrapped) requested frame are decoded in
-    /// order. This matches Flash Player behavior.
+    /// The frame is wrapped to the underlying video stream's boundaries and
+    /// queued, to be decoded right before the video is drawn.
     pub fn seek(self, context: &mut UpdateContext<'_, 'gc, '_>, mut frame_id: u32) {
-        let read = self.0.read();
-        if let VideoStream::Uninstantiated(_) = &read.stream {
-            drop(read);
-
-            let mut write = self.0.write(context.gc_context);
+        let due = context.time_since_launch();
+        let mut write = self.0.write(context.gc_context);
+        if let VideoStream::Uninstantiated(_) = &write.stream {
             write.stream = VideoStream::Uninstantiated(frame_id);
-
             return;
         };
 
-        let num_frames = match &*read.source.read() {
+        let num_frames = match &*write.source.read() {
             VideoSource::Swf { streamdef, .. } => Some(streamdef.num_frames),
         };
 
@@ -174,6 +244,49 @@ impl<'gc> Video<'gc> {
             frame_id %= num_frames as u32;
         }
 
+        if write.frame_queue.is_empty() {
+            context.queued_videos.push(self);
+        }
+        write.frame_queue.push_back(QueuedFrame { frame_id, due });
+    }
+
+    /// Decode the frame that is due to be shown, if there is a new one.
+    ///
+    /// This is done right before the video is drawn. Only the last frame that
+    /// is due on the movie clock is shown; the ones queued before it are
+    /// dropped, and only decoded if the codec needs them to decode the new
+    /// frame.
+    pub fn present_frame(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let now = context.time_since_launch();
+        let mut write = self.0.write(context.gc_context);
+        let next_frame = next_due_frame(&mut write.frame_queue, now);
+        if !write.frame_queue.is_empty() {
+            context.queued_videos.push(self);
+        }
+
+        let (frame_id, dropped) = match next_frame {
+            Some(next_frame) => next_frame,
+            None => return,
+        };
+        write.frame_stats.dropped += dropped;
+
+        let last_frame = write.decoded_frame.as_ref().map(|(lf, _)| *lf);
+        if last_frame == Some(frame_id) {
+            return;
+        }
+        write.frame_stats.presented += 1;
+        drop(write);
+
+        self.decode_to(context, frame_id);
+    }
+
+    /// Decode a particular frame in the video stream.
+    ///
+    /// The frame is snapped to the last independently seekable frame, and
+    /// all frames from that keyframe up to the requested frame are decoded in
+    /// order. This matches Flash Player behavior.
+    fn decode_to(self, context: &mut UpdateContext<'_, 'gc, '_>, frame_id: u32) {
+        let read = self.0.read();
         let last_frame = read.decoded_frame.as_ref().map(|(lf, _)| *lf);
 
         if last_frame == Some(frame_id) {
@@ -221,8 +334,7 @@ impl<'gc> Video<'gc> {
     /// Decode a single frame of video.
     ///
     /// This function makes no attempt to ensure that the proposed seek is
-    /// valid, hence the fact that it's not `pub`. To do a seek that accounts
-    /// for keyframes, see `Video.seek`.
+    /// valid. To do a seek that accounts for keyframes, see `Video.decode_to`.
     fn seek_internal(self, context: &mut UpdateContext<'_, 'gc, '_>, frame_id: u32) {
         let read = self.0.read();
         let source = read.source;
@@ -405,28 +517,42 @@ impl<'gc> TDisplayObject<'gc> for Video<'gc> {
         bounding_box
     }
 
+    fn content_revision(&self) -> Option<u64> {
+        None
+    }
+
     fn render(&self, context: &mut RenderContext) {
-        if !self.world_bounds().intersects(&context.stage.view_bounds()) {
+        if context.is_culled(&self.world_bounds()) {
             // Off-screen; culled
             return;
         }
 
         context.transform_stack.push(&*self.transform());
 
-        if let Some((_frame_id, ref bitmap)) = self.0.read().decoded_frame {
+        let read = self.0.read();
+        let bitmap = match read.net_stream {
+            Some(net_stream) => net_stream.video_frame(),
+            None => read
+                .decoded_frame
+                .as_ref()
+                .map(|(_frame_id, bitmap)| bitmap.0),
+        };
+        drop(read);
+
+        if let Some(bitmap) = bitmap {
             let mut transform = context.transform_stack.transform().clone();
             let bounds = self.self_bounds();
 
             // The actual decoded frames might be different in size than the declared
             // bounds of the VideoStream tag, so a final scale adjustment has to be done.
             transform.matrix *= Matrix::scale(
-                bounds.width().to_pixels() as f32 / bitmap.0.width as f32,
-                bounds.height().to_pixels() as f32 / bitmap.0.height as f32,
+                bounds.width().to_pixels() as f32 / bitmap.width as f32,
+                bounds.height().to_pixels() as f32 / bitmap.height as f32,
             );
 
             context
                 .renderer
-                .render_bitmap(bitmap.0.handle, &transform, false);
+                .render_bitmap(bitmap.handle, &transform, false);
         } else {
             log::warn!("Video has no decoded frame to render.");
         }
@@ -438,3 +564,80 @@ impl<'gc> TDisplayObject<'gc> for Video<'gc> {
         self.0.write(mc).object = Some(to.into());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use swf::{VideoCodec, VideoDeblocking};
+
+    fn queued(frames: &[(u32, u64)]) -> VecDeque<QueuedFrame> {
+        frames
+            .iter()
+            .map(|&(frame_id, due)| QueuedFrame {
+                frame_id,
+                due: Duration::from_millis(due),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_the_last_due_frame_is_shown() {
+        let mut queue = queued(&[(0, 0), (1, 10), (2, 20), (3, 30)]);
+
+        assert_eq!(
+            next_due_frame(&mut queue, Duration::from_millis(25)),
+            Some((2, 2))
+        );
+        assert_eq!(queue, queued(&[(3, 30)]));
+
+        // Frames that aren't due yet are kept for later.
+        assert_eq!(next_due_frame(&mut queue, Duration::from_millis(5)), None);
+        assert_eq!(queue, queued(&[(3, 30)]));
+    }
+
+    #[test]
+    fn videos_are_queued_until_presented() {
+        with_avm(8, |activation, _root| {
+            let context = &mut activation.context;
+            let streamdef = DefineVideoStream {
+                id: 1,
+                num_frames: 10,
+                width: 16,
+                height: 16,
+                is_smoothed: false,
+                deblocking: VideoDeblocking::UseVideoPacketValue,
+                codec: VideoCodec::H263,
+            };
+            let video = Video::from_swf_tag(context.swf.clone(), streamdef, context.gc_context);
+            video.post_instantiation(context, video.into(), None, Instantiator::Movie, false);
+
+            // Instantiating the video queues its first frame.
+            assert_eq!(context.queued_videos.len(), 1);
+            for video in std::mem::take(context.queued_videos) {
+                video.present_frame(context);
+            }
+            assert!(context.queued_videos.is_empty());
+
+            // A video is only queued once, however many frames it falls
+            // behind by.
+            for frame_id in 1..=3 {
+                video.seek(context, frame_id);
+            }
+            assert_eq!(context.queued_videos.len(), 1);
+            for video in std::mem::take(context.queued_videos) {
+                video.present_frame(context);
+            }
+            assert!(context.queued_videos.is_empty());
+            assert_eq!(
+                video.frame_stats(),
+                VideoFrameStats {
+                    presented: 2,
+                    dropped: 2,
+                }
+            );
+
+            Ok(())
+        });
+    }
+}
@@ -63,3 +63,55 @@ pub fn f64_to_wrapping_u32(n: f64) -> u32 {
 pub fn f64_to_wrapping_i32(n: f64) -> i32 {
     f64_to_wrapping_u32(n) as i32
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unreadable_literal)]
+    fn wrapping_i32() {
+        assert_eq!(f64_to_wrapping_i32(0.0), 0);
+        assert_eq!(f64_to_wrapping_i32(-0.0), 0);
+        assert_eq!(f64_to_wrapping_i32(f64::NAN), 0);
+        assert_eq!(f64_to_wrapping_i32(f64::INFINITY), 0);
+        assert_eq!(f64_to_wrapping_i32(f64::NEG_INFINITY), 0);
+        assert_eq!(f64_to_wrapping_i32(1.9), 1);
+        assert_eq!(f64_to_wrapping_i32(-1.9), -1);
+        assert_eq!(f64_to_wrapping_i32(2147483647.0), i32::MAX);
+        assert_eq!(f64_to_wrapping_i32(2147483648.0), i32::MIN);
+        assert_eq!(f64_to_wrapping_i32(-2147483649.0), i32::MAX);
+        assert_eq!(f64_to_wrapping_i32(4294967296.0), 0);
+        assert_eq!(f64_to_wrapping_i32(4294967295.0), -1);
+        assert_eq!(f64_to_wrapping_i32(1e20), 1661992960);
+    }
+
+    #[test]
+    #[allow(clippy::unreadable_literal)]
+    fn wrapping_u32() {
+        assert_eq!(f64_to_wrapping_u32(-0.0), 0);
+        assert_eq!(f64_to_wrapping_u32(f64::NAN), 0);
+        assert_eq!(f64_to_wrapping_u32(f64::INFINITY), 0);
+        assert_eq!(f64_to_wrapping_u32(-1.0), u32::MAX);
+        assert_eq!(f64_to_wrapping_u32(-1.5), u32::MAX);
+        assert_eq!(f64_to_wrapping_u32(2147483648.0), 0x80000000);
+        assert_eq!(f64_to_wrapping_u32(4294967296.0), 0);
+        assert_eq!(f64_to_wrapping_u32(4294967297.5), 1);
+    }
+
+    #[test]
+    fn wrapping_16() {
+        assert_eq!(f64_to_wrapping_u16(-1.0), u16::MAX);
+        assert_eq!(f64_to_wrapping_u16(65536.0), 0);
+        assert_eq!(f64_to_wrapping_i16(32768.0), i16::MIN);
+        assert_eq!(f64_to_wrapping_i16(f64::NAN), 0);
+    }
+
+    #[test]
+    fn to_string() {
+        assert_eq!(f64_to_string(-0.0), "0");
+        assert_eq!(f64_to_string(f64::NAN), "NaN");
+        assert_eq!(f64_to_string(f64::NEG_INFINITY), "-Infinity");
+        assert_eq!(f64_to_string(1.5), "1.5");
+    }
+}
@@ -1,6 +1,8 @@
+use bitflags::bitflags;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use std::convert::TryFrom;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PlayerEvent {
     KeyDown { key_code: KeyCode },
     KeyUp { key_code: KeyCode },
@@ -77,6 +79,20 @@ impl ClipEvent {
         "onRollOver",
     ];
 
+    /// The bit of this event in a `ClipEventMask`, which is empty if the event
+    /// doesn't propagate.
+    pub fn mask(self) -> ClipEventMask {
+        match self {
+            Self::MouseUp => ClipEventMask::MOUSE_UP,
+            Self::MouseDown => ClipEventMask::MOUSE_DOWN,
+            Self::MouseMove => ClipEventMask::MOUSE_MOVE,
+            Self::KeyUp => ClipEventMask::KEY_UP,
+            Self::KeyDown => ClipEventMask::KEY_DOWN,
+            Self::KeyPress { .. } => ClipEventMask::KEY_PRESS,
+            _ => ClipEventMask::empty(),
+        }
+    }
+
     /// Indicates that the event should be propagated down to children.
     pub fn propagates(self) -> bool {
         matches!(
@@ -136,6 +152,50 @@ impl ClipEvent {
     }
 }
 
+bitflags! {
+    /// A set of the clip events that propagate to every clip on the stage.
+    ///
+    /// Clips remember which of these events they or their children can handle,
+    /// so that the rest don't have to be passed on to every child.
+    pub struct ClipEventMask: u8 {
+        const MOUSE_UP = 1 << 0;
+        const MOUSE_DOWN = 1 << 1;
+        const MOUSE_MOVE = 1 << 2;
+        const KEY_UP = 1 << 3;
+        const KEY_DOWN = 1 << 4;
+        const KEY_PRESS = 1 << 5;
+
+        /// The events that can be handled by methods, such as `onMouseMove`.
+        const METHODS = Self::MOUSE_UP.bits
+            | Self::MOUSE_DOWN.bits
+            | Self::MOUSE_MOVE.bits
+            | Self::KEY_UP.bits
+            | Self::KEY_DOWN.bits;
+    }
+}
+
+impl ClipEventMask {
+    /// The event handled by a method of the given name, if it propagates.
+    ///
+    /// Names are compared without regard to case, as in SWF6 and earlier.
+    pub fn from_method_name(name: &str) -> Self {
+        [
+            ClipEvent::MouseUp,
+            ClipEvent::MouseDown,
+            ClipEvent::MouseMove,
+            ClipEvent::KeyUp,
+            ClipEvent::KeyDown,
+        ]
+        .iter()
+        .filter(|event| {
+            event
+                .method_name()
+                .map_or(false, |method| method.eq_ignore_ascii_case(name))
+        })
+        .fold(Self::empty(), |mask, event| mask | event.mask())
+    }
+}
+
 /// Flash virtual keycode.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
 #[repr(u8)]
@@ -224,6 +284,10 @@ pub enum KeyCode {
     F10 = 121,
     F11 = 122,
     F12 = 123,
+    F13 = 124,
+    F14 = 125,
+    F15 = 126,
+    NumLock = 144,
     ScrollLock = 145,
     Semicolon = 186,
     Equals = 187,
@@ -238,6 +302,21 @@ pub enum KeyCode {
     Apostrophe = 222,
 }
 
+impl KeyCode {
+    /// The key code of the key that types the given letter, in either case.
+    ///
+    /// Flash Player reports letter keys by the letter they type on the user's
+    /// keyboard layout rather than by their position, so that the key labelled
+    /// "A" on an AZERTY keyboard has the same code as on a QWERTY one.
+    pub fn from_letter(letter: char) -> Option<Self> {
+        if letter.is_ascii_alphabetic() {
+            Self::try_from(letter.to_ascii_uppercase() as u8).ok()
+        } else {
+            None
+        }
+    }
+}
+
 /// Key codes for SWF4 keyPress button handlers. These are annoyingly different than
 /// `Key.isDown` key codes.
 /// TODO: After 18, these are mostly ASCII... should we just use u8? How are different
@@ -358,6 +437,20 @@ pub enum ButtonKeyCode {
     Tilde = 126,
 }
 
+impl ButtonKeyCode {
+    /// The `keyPress` code of a typed character.
+    ///
+    /// Only printable ASCII characters have one, as SWF files store the key of
+    /// a `keyPress` handler in 7 bits.
+    pub fn from_char(c: char) -> Option<Self> {
+        if (' '..='~').contains(&c) {
+            Self::try_from(c as u8).ok()
+        } else {
+            None
+        }
+    }
+}
+
 pub fn key_code_to_button_key_code(key_code: KeyCode) -> Option<ButtonKeyCode> {
     let out = match key_code {
         KeyCode::Left => ButtonKeyCode::Left,
@@ -378,3 +471,67 @@ pub fn key_code_to_button_key_code(key_code: KeyCode) -> Option<ButtonKeyCode> {
     };
     Some(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clip_event_masks_cover_the_propagating_events() {
+        let events = [
+            ClipEvent::Construct,
+            ClipEvent::Data,
+            ClipEvent::EnterFrame,
+            ClipEvent::KeyUp,
+            ClipEvent::KeyDown,
+            ClipEvent::KeyPress {
+                key_code: ButtonKeyCode::Left,
+            },
+            ClipEvent::MouseUp,
+            ClipEvent::MouseDown,
+            ClipEvent::MouseMove,
+            ClipEvent::Press,
+            ClipEvent::RollOver,
+        ];
+        for event in events.iter() {
+            assert_eq!(event.propagates(), !event.mask().is_empty(), "{:?}", event);
+        }
+
+        assert_eq!(
+            ClipEventMask::from_method_name("onMouseMove"),
+            ClipEventMask::MOUSE_MOVE
+        );
+        assert_eq!(
+            ClipEventMask::from_method_name("ONKEYDOWN"),
+            ClipEventMask::KEY_DOWN
+        );
+        assert_eq!(
+            ClipEventMask::from_method_name("onPress"),
+            ClipEventMask::empty()
+        );
+        assert_eq!(
+            ClipEventMask::from_method_name("onEnterFrame"),
+            ClipEventMask::empty()
+        );
+    }
+
+    #[test]
+    fn letters_map_to_their_key_codes() {
+        assert_eq!(KeyCode::from_letter('a'), Some(KeyCode::A));
+        assert_eq!(KeyCode::from_letter('Q'), Some(KeyCode::Q));
+        assert_eq!(KeyCode::from_letter('z'), Some(KeyCode::Z));
+        assert_eq!(KeyCode::from_letter('1'), None);
+        assert_eq!(KeyCode::from_letter('é'), None);
+        assert_eq!(KeyCode::from_letter(';'), None);
+    }
+
+    #[test]
+    fn printable_characters_map_to_key_press_codes() {
+        assert_eq!(ButtonKeyCode::from_char(' '), Some(ButtonKeyCode::Space));
+        assert_eq!(ButtonKeyCode::from_char('~'), Some(ButtonKeyCode::Tilde));
+        assert_eq!(ButtonKeyCode::from_char('A').map(u8::from), Some(b'A'));
+        assert_eq!(ButtonKeyCode::from_char('\u{7f}'), None);
+        assert_eq!(ButtonKeyCode::from_char('\n'), None);
+        assert_eq!(ButtonKeyCode::from_char('é'), None);
+    }
+}
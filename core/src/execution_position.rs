@@ -0,0 +1,154 @@
+//! Tracking of the tags and scripts a movie is running, for crash reports.
+//!
+//! The player records where it is as it runs tags and scripts, so that a
+//! panic hook or a logger can say which part of a movie was running at the
+//! time. Positions are kept per thread, and point at the start of the tag or
+//! instruction that was last started.
+
+use crate::tag_utils::SwfMovie;
+use std::cell::Cell;
+use std::thread::LocalKey;
+use swf::TagCode;
+
+thread_local! {
+    /// The code of the tag being run, and where its header is in memory.
+    static CURRENT_TAG: Cell<Option<(TagCode, *const u8)>> = Cell::new(None);
+
+    /// Where the AVM1 action being run is in memory.
+    static CURRENT_ACTION: Cell<Option<*const u8>> = Cell::new(None);
+
+    /// The index of the AVM2 method being run, and the offset of the
+    /// instruction being run into its body.
+    static CURRENT_OP: Cell<Option<(u32, usize)>> = Cell::new(None);
+}
+
+/// The part of a movie that a thread is running.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExecutionPosition {
+    /// The tag being run, and the offset of its header into the movie's
+    /// decompressed data.
+    pub tag: Option<(TagCode, usize)>,
+
+    /// The offset of the AVM1 action being run into the movie's decompressed
+    /// data.
+    pub avm1_action: Option<usize>,
+
+    /// The index of the AVM2 method being run in its ABC file, and the
+    /// offset of the instruction being run into the method's body.
+    pub avm2_op: Option<(u32, usize)>,
+}
+
+impl ExecutionPosition {
+    /// Find out which part of a movie this thread is running.
+    ///
+    /// Tags and actions that belong to other movies, such as ones loaded by
+    /// this movie, aren't reported.
+    pub fn current(movie: &SwfMovie) -> Self {
+        let data = movie.data().as_ptr_range();
+        let offset = |ptr: *const u8| {
+            if data.contains(&ptr) {
+                Some(ptr as usize - data.start as usize)
+            } else {
+                None
+            }
+        };
+
+        Self {
+            tag: CURRENT_TAG
+                .with(Cell::get)
+                .and_then(|(code, ptr)| Some((code, offset(ptr)?))),
+            avm1_action: CURRENT_ACTION.with(Cell::get).and_then(offset),
+            avm2_op: CURRENT_OP.with(Cell::get),
+        }
+    }
+}
+
+/// Puts a position back to what it was before a tag list or script started
+/// running, when dropped.
+#[must_use]
+pub(crate) struct PositionGuard<T: 'static + Copy> {
+    position: &'static LocalKey<Cell<Option<T>>>,
+    previous: Option<T>,
+}
+
+impl<T: 'static + Copy> PositionGuard<T> {
+    fn new(position: &'static LocalKey<Cell<Option<T>>>) -> Self {
+        Self {
+            position,
+            previous: position.with(Cell::get),
+        }
+    }
+}
+
+impl<T: 'static + Copy> Drop for PositionGuard<T> {
+    fn drop(&mut self) {
+        let previous = self.previous;
+        self.position.with(|position| position.set(previous));
+    }
+}
+
+/// Start running a list of tags.
+pub(crate) fn enter_tags() -> PositionGuard<(TagCode, *const u8)> {
+    PositionGuard::new(&CURRENT_TAG)
+}
+
+/// Record that the tag whose header starts at `data` is being run.
+pub(crate) fn set_tag(code: TagCode, data: &[u8]) {
+    CURRENT_TAG.with(|tag| tag.set(Some((code, data.as_ptr()))));
+}
+
+/// Start running a list of AVM1 actions.
+pub(crate) fn enter_avm1_actions() -> PositionGuard<*const u8> {
+    PositionGuard::new(&CURRENT_ACTION)
+}
+
+/// Record that the AVM1 action starting at `data` is being run.
+pub(crate) fn set_avm1_action(data: &[u8]) {
+    CURRENT_ACTION.with(|action| action.set(Some(data.as_ptr())));
+}
+
+/// Start running an AVM2 method.
+pub(crate) fn enter_avm2_method() -> PositionGuard<(u32, usize)> {
+    PositionGuard::new(&CURRENT_OP)
+}
+
+/// Record that the instruction at `offset` into an AVM2 method's body is
+/// being run.
+pub(crate) fn set_avm2_op(method: u32, offset: usize) {
+    CURRENT_OP.with(|op| op.set(Some((method, offset))));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_offsets_into_the_movie() {
+        let empty = SwfMovie::empty(10);
+        let movie = empty.from_movie_and_subdata(vec![0; 16], &empty);
+        let other = empty.from_movie_and_subdata(vec![0; 16], &empty);
+
+        let _tags = enter_tags();
+        set_tag(TagCode::DoAction, &movie.data()[4..]);
+        {
+            let _actions = enter_avm1_actions();
+            set_avm1_action(&movie.data()[10..]);
+            assert_eq!(
+                ExecutionPosition::current(&movie),
+                ExecutionPosition {
+                    tag: Some((TagCode::DoAction, 4)),
+                    avm1_action: Some(10),
+                    avm2_op: None,
+                }
+            );
+        }
+
+        // Finishing a script forgets where it was, and other movies' tags
+        // aren't reported.
+        set_tag(TagCode::ShowFrame, &other.data()[2..]);
+        assert_eq!(
+            ExecutionPosition::current(&movie),
+            ExecutionPosition::default()
+        );
+    }
+}
@@ -6,10 +6,19 @@ use crate::avm1::Value as Avm1Value;
 use crate::avm1::{
     AvmString as Avm1String, Object as Avm1Object, ScriptObject as Avm1ScriptObject,
 };
+use crate::avm2::{
+    Activation as Avm2Activation, ArrayObject as Avm2ArrayObject, ArrayStorage as Avm2ArrayStorage,
+    AvmString as Avm2String, Error as Avm2Error, Namespace as Avm2Namespace, Object as Avm2Object,
+    QName as Avm2QName, ScriptObject as Avm2ScriptObject, TObject as _, Value as Avm2Value,
+};
 use crate::context::UpdateContext;
 use gc_arena::Collect;
 use std::collections::BTreeMap;
 
+/// How deeply objects and lists may be nested when they are passed to or from
+/// ActionScript 3. Anything nested deeper is passed as null.
+const MAX_AVM2_NESTING: usize = 256;
+
 /// An intermediate format of representing shared data between ActionScript and elsewhere.
 /// Regardless of the capabilities of both sides, all data will be translated to this potentially
 /// lossy format. Any recursion or additional metadata in ActionScript will not be translated.
@@ -183,6 +192,127 @@ impl Value {
             }
         }
     }
+
+    pub fn from_avm2<'gc>(
+        activation: &mut Avm2Activation<'_, 'gc, '_>,
+        value: Avm2Value<'gc>,
+    ) -> Result<Value, Avm2Error> {
+        Self::from_avm2_nested(activation, value, &mut Vec::new())
+    }
+
+    /// Convert an AVM2 value found inside of `parents`. An object that
+    /// contains itself is passed as null where it recurs.
+    fn from_avm2_nested<'gc>(
+        activation: &mut Avm2Activation<'_, 'gc, '_>,
+        value: Avm2Value<'gc>,
+        parents: &mut Vec<Avm2Object<'gc>>,
+    ) -> Result<Value, Avm2Error> {
+        Ok(match value {
+            Avm2Value::Undefined | Avm2Value::Null => Value::Null,
+            Avm2Value::Bool(value) => Value::Bool(value),
+            Avm2Value::Number(value) => Value::Number(value),
+            Avm2Value::Unsigned(value) => Value::Number(value.into()),
+            Avm2Value::Integer(value) => Value::Number(value.into()),
+            Avm2Value::String(value) => Value::String(value.to_string()),
+            Avm2Value::Object(object) => {
+                if parents.len() >= MAX_AVM2_NESTING
+                    || parents
+                        .iter()
+                        .any(|parent| Avm2Object::ptr_eq(*parent, object))
+                {
+                    return Ok(Value::Null);
+                }
+
+                parents.push(object);
+                let value = Self::from_avm2_object(activation, object, parents);
+                parents.pop();
+                value?
+            }
+        })
+    }
+
+    fn from_avm2_object<'gc>(
+        activation: &mut Avm2Activation<'_, 'gc, '_>,
+        object: Avm2Object<'gc>,
+        parents: &mut Vec<Avm2Object<'gc>>,
+    ) -> Result<Value, Avm2Error> {
+        let array = object
+            .as_array_storage()
+            .map(|array| array.iter().collect::<Vec<_>>());
+        if let Some(array) = array {
+            let mut values = Vec::with_capacity(array.len());
+            for value in array {
+                let value = value.unwrap_or(Avm2Value::Undefined);
+                values.push(Value::from_avm2_nested(activation, value, parents)?);
+            }
+            Ok(Value::List(values))
+        } else {
+            let mut values = BTreeMap::new();
+            let mut index = 0;
+            while let Some(next) = object.get_next_enumerant(index) {
+                index = next;
+                let key = match object.get_enumerant_name(index) {
+                    Some(key) => key.coerce_to_string(activation)?.to_string(),
+                    None => continue,
+                };
+                let value = object.get_enumerant_value(index, activation)?;
+                values.insert(key, Value::from_avm2_nested(activation, value, parents)?);
+            }
+            Ok(Value::Object(values))
+        }
+    }
+
+    pub fn into_avm2<'gc>(
+        self,
+        activation: &mut Avm2Activation<'_, 'gc, '_>,
+    ) -> Result<Avm2Value<'gc>, Avm2Error> {
+        self.into_avm2_nested(activation, 0)
+    }
+
+    /// Convert this value into AVM2, when it's found inside of `depth` other
+    /// objects or lists.
+    fn into_avm2_nested<'gc>(
+        self,
+        activation: &mut Avm2Activation<'_, 'gc, '_>,
+        depth: usize,
+    ) -> Result<Avm2Value<'gc>, Avm2Error> {
+        Ok(match self {
+            Value::Null => Avm2Value::Null,
+            Value::Bool(value) => Avm2Value::Bool(value),
+            Value::Number(value) => Avm2Value::Number(value),
+            Value::String(value) => {
+                Avm2Value::String(Avm2String::new(activation.context.gc_context, value))
+            }
+            Value::Object(_) | Value::List(_) if depth >= MAX_AVM2_NESTING => Avm2Value::Null,
+            Value::Object(values) => {
+                let object_proto = activation.avm2().prototypes().object;
+                let mut object =
+                    Avm2ScriptObject::object(activation.context.gc_context, object_proto);
+                for (key, value) in values {
+                    let name = Avm2QName::new(
+                        Avm2Namespace::public(),
+                        Avm2String::new(activation.context.gc_context, key),
+                    );
+                    let value = value.into_avm2_nested(activation, depth + 1)?;
+                    object.set_property(object, &name, value, activation)?;
+                }
+                object.into()
+            }
+            Value::List(values) => {
+                let mut storage = Vec::with_capacity(values.len());
+                for value in values {
+                    storage.push(Some(value.into_avm2_nested(activation, depth + 1)?));
+                }
+                let array_proto = activation.avm2().prototypes().array;
+                Avm2ArrayObject::from_array(
+                    Avm2ArrayStorage::from_storage(storage),
+                    array_proto,
+                    activation.context.gc_context,
+                )
+                .into()
+            }
+        })
+    }
 }
 
 #[derive(Collect, Clone)]
@@ -192,6 +322,9 @@ pub enum Callback<'gc> {
         this: Avm1Value<'gc>,
         method: Avm1Object<'gc>,
     },
+    Avm2 {
+        method: Avm2Object<'gc>,
+    },
 }
 
 impl<'gc> Callback<'gc> {
@@ -227,6 +360,22 @@ impl<'gc> Callback<'gc> {
                     Value::Null
                 }
             }
+            Callback::Avm2 { method } => {
+                let mut activation = Avm2Activation::from_nothing(context.reborrow());
+                let result = args
+                    .into_iter()
+                    .map(|v| v.into_avm2(&mut activation))
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|args| method.call(None, &args, &mut activation, None))
+                    .and_then(|value| Value::from_avm2(&mut activation, value));
+                match result {
+                    Ok(result) => result,
+                    Err(e) => {
+                        log::error!("Error in ExternalInterface callback {}: {}", name, e);
+                        Value::Null
+                    }
+                }
+            }
         }
     }
 }
@@ -302,3 +451,99 @@ impl<'gc> ExternalInterface<'gc> {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::avm2::{Avm2, TObject};
+
+    /// Run a test in an AVM2 activation with the player globals loaded.
+    fn with_avm2<F>(test: F)
+    where
+        F: for<'gc> FnOnce(&mut Avm2Activation<'_, 'gc, '_>),
+    {
+        with_avm(
+            9,
+            |avm1_activation, _root| -> Result<(), crate::avm1::Error> {
+                Avm2::load_player_globals(&mut avm1_activation.context).unwrap();
+                let mut activation =
+                    Avm2Activation::from_nothing(avm1_activation.context.reborrow());
+                test(&mut activation);
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn avm2_values_round_trip() {
+        with_avm2(|activation| {
+            let mut values = BTreeMap::new();
+            values.insert(
+                "list".to_string(),
+                Value::List(vec![1.5.into(), "two".into(), Value::Null, true.into()]),
+            );
+            values.insert("name".to_string(), "ruffle".into());
+            let value = Value::Object(values);
+
+            let avm2_value = value.clone().into_avm2(activation).unwrap();
+            assert_eq!(Value::from_avm2(activation, avm2_value).unwrap(), value);
+            assert_eq!(
+                Value::from_avm2(activation, Avm2Value::Integer(-3)).unwrap(),
+                Value::Number(-3.0)
+            );
+        });
+    }
+
+    #[test]
+    fn avm2_objects_that_contain_themselves_are_cut_off() {
+        with_avm2(|activation| {
+            let object_proto = activation.avm2().prototypes().object;
+            let mut object = Avm2ScriptObject::object(activation.context.gc_context, object_proto);
+            let name = Avm2QName::new(Avm2Namespace::public(), "self");
+            object
+                .set_property(object, &name, object.into(), activation)
+                .unwrap();
+
+            let array_proto = activation.avm2().prototypes().array;
+            let array: Avm2Object<'_> = Avm2ArrayObject::from_array(
+                Avm2ArrayStorage::from_storage(vec![Some(object.into())]),
+                array_proto,
+                activation.context.gc_context,
+            );
+            array
+                .as_array_storage_mut(activation.context.gc_context)
+                .unwrap()
+                .push(array.into());
+
+            let mut values = BTreeMap::new();
+            values.insert("self".to_string(), Value::Null);
+            let object = Value::Object(values);
+            assert_eq!(
+                Value::from_avm2(activation, array.into()).unwrap(),
+                Value::List(vec![object, Value::Null])
+            );
+        });
+    }
+
+    #[test]
+    fn deeply_nested_values_are_cut_off() {
+        fn depth(value: &Value) -> usize {
+            match value {
+                Value::List(values) => 1 + values.iter().map(depth).max().unwrap_or(0),
+                _ => 0,
+            }
+        }
+
+        with_avm2(|activation| {
+            let mut value = Value::Number(1.0);
+            for _ in 0..MAX_AVM2_NESTING * 2 {
+                value = Value::List(vec![value]);
+            }
+
+            let avm2_value = value.into_avm2(activation).unwrap();
+            let value = Value::from_avm2(activation, avm2_value).unwrap();
+            assert_eq!(depth(&value), MAX_AVM2_NESTING);
+        });
+    }
+}
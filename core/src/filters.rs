@@ -0,0 +1,636 @@
+//! Software implementations of display object filters.
+//!
+//! Render backends that cannot apply filters on the GPU may use these as a
+//! CPU fallback: the filtered object is drawn into an offscreen surface, read
+//! back into a `Bitmap`, processed with `apply_filters`, and then composited
+//! back onto the stage. This is slow, but it is correct everywhere.
+//!
+//! `ConvolutionFilter` isn't implemented yet, and leaves the bitmap untouched.
+
+use crate::backend::render::{Bitmap, BitmapFormat};
+use swf::{Color, Filter, GradientBevelFilter, GradientGlowFilter, GradientRecord, Twips};
+
+/// Applies a list of filters, in order, to a bitmap.
+///
+/// The bitmap data is expected to be RGBA with premultiplied alpha. RGB
+/// bitmaps are left untouched, as they have no alpha for glows and shadows to
+/// work with.
+pub fn apply_filters(bitmap: &mut Bitmap, filters: &[Filter]) {
+    let (width, height) = (bitmap.width as usize, bitmap.height as usize);
+    let rgba = match &mut bitmap.data {
+        BitmapFormat::Rgba(rgba) => rgba,
+        BitmapFormat::Rgb(_) => return,
+    };
+
+    if width == 0 || height == 0 || rgba.len() < width * height * 4 {
+        return;
+    }
+
+    for filter in filters {
+        match filter {
+            Filter::BlurFilter(blur) => {
+                box_blur(
+                    rgba,
+                    width,
+                    height,
+                    4,
+                    blur.blur_x,
+                    blur.blur_y,
+                    blur.num_passes,
+                );
+            }
+            Filter::GlowFilter(glow) => {
+                let shadow = ShadowParams {
+                    color: glow.color.clone(),
+                    blur_x: glow.blur_x,
+                    blur_y: glow.blur_y,
+                    strength: glow.strength,
+                    offset: (0, 0),
+                    is_inner: glow.is_inner,
+                    is_knockout: glow.is_knockout,
+                    num_passes: glow.num_passes,
+                };
+                apply_shadow(rgba, width, height, &shadow);
+            }
+            Filter::DropShadowFilter(drop_shadow) => {
+                let shadow = ShadowParams {
+                    color: drop_shadow.color.clone(),
+                    blur_x: drop_shadow.blur_x,
+                    blur_y: drop_shadow.blur_y,
+                    strength: drop_shadow.strength,
+                    offset: (
+                        (drop_shadow.angle.cos() * drop_shadow.distance).round() as isize,
+                        (drop_shadow.angle.sin() * drop_shadow.distance).round() as isize,
+                    ),
+                    is_inner: drop_shadow.is_inner,
+                    is_knockout: drop_shadow.is_knockout,
+                    num_passes: drop_shadow.num_passes,
+                };
+                apply_shadow(rgba, width, height, &shadow);
+            }
+            Filter::ColorMatrixFilter(color_matrix) => {
+                apply_color_matrix(rgba, &color_matrix.matrix);
+            }
+            Filter::GradientGlowFilter(glow) => {
+                apply_gradient_glow(rgba, width, height, glow);
+            }
+            Filter::GradientBevelFilter(bevel) => {
+                apply_gradient_bevel(rgba, width, height, bevel);
+            }
+            Filter::BevelFilter(bevel) => {
+                // A bevel is a gradient bevel that fades from the highlight
+                // to the shadow color through transparency.
+                let transparent = Color::from_rgb(0, 0);
+                let bevel = GradientBevelFilter {
+                    colors: vec![
+                        GradientRecord {
+                            ratio: 0,
+                            color: bevel.highlight_color.clone(),
+                        },
+                        GradientRecord {
+                            ratio: 128,
+                            color: transparent,
+                        },
+                        GradientRecord {
+                            ratio: 255,
+                            color: bevel.shadow_color.clone(),
+                        },
+                    ],
+                    blur_x: bevel.blur_x,
+                    blur_y: bevel.blur_y,
+                    angle: bevel.angle,
+                    distance: bevel.distance,
+                    strength: bevel.strength,
+                    is_inner: bevel.is_inner,
+                    is_knockout: bevel.is_knockout,
+                    is_on_top: bevel.is_on_top,
+                    num_passes: bevel.num_passes,
+                };
+                apply_gradient_bevel(rgba, width, height, &bevel);
+            }
+            Filter::ConvolutionFilter(_) => {}
+        }
+    }
+}
+
+/// Returns how far the given filters may extend the drawn area of an object
+/// beyond its bounds on each side, as a horizontal and vertical distance.
+pub fn filter_padding(filters: &[Filter]) -> (Twips, Twips) {
+    let mut padding = (0.0, 0.0);
+    for filter in filters {
+        let (blur_x, blur_y, passes, distance) = match filter {
+            Filter::BlurFilter(f) => (f.blur_x, f.blur_y, f.num_passes, 0.0),
+            Filter::GlowFilter(f) => (f.blur_x, f.blur_y, f.num_passes, 0.0),
+            Filter::DropShadowFilter(f) => (f.blur_x, f.blur_y, f.num_passes, f.distance),
+            Filter::BevelFilter(f) => (f.blur_x, f.blur_y, f.num_passes, f.distance),
+            Filter::GradientGlowFilter(f) => (f.blur_x, f.blur_y, f.num_passes, f.distance),
+            Filter::GradientBevelFilter(f) => (f.blur_x, f.blur_y, f.num_passes, f.distance),
+            Filter::ColorMatrixFilter(_) | Filter::ConvolutionFilter(_) => continue,
+        };
+        let passes = f64::from(passes.max(1));
+        padding.0 += (blur_x / 2.0).ceil() * passes + distance.abs();
+        padding.1 += (blur_y / 2.0).ceil() * passes + distance.abs();
+    }
+
+    (Twips::from_pixels(padding.0), Twips::from_pixels(padding.1))
+}
+
+/// The shared parameters of the glow and drop shadow filters.
+struct ShadowParams {
+    color: Color,
+    blur_x: f64,
+    blur_y: f64,
+    strength: f32,
+    offset: (isize, isize),
+    is_inner: bool,
+    is_knockout: bool,
+    num_passes: u8,
+}
+
+/// Draws a tinted, blurred copy of the bitmap's alpha channel either behind
+/// (outer) or inside (inner) the bitmap.
+fn apply_shadow(rgba: &mut [u8], width: usize, height: usize, params: &ShadowParams) {
+    let (dx, dy) = params.offset;
+
+    // Build the shadow's coverage from the (offset) source alpha. Inner
+    // shadows are cast by the area *outside* the object.
+    let mut coverage = vec![0u8; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let (sx, sy) = (x as isize - dx, y as isize - dy);
+            let alpha = if sx >= 0 && sy >= 0 && (sx as usize) < width && (sy as usize) < height {
+                rgba[(sy as usize * width + sx as usize) * 4 + 3]
+            } else {
+                0
+            };
+            coverage[y * width + x] = if params.is_inner { 255 - alpha } else { alpha };
+        }
+    }
+
+    box_blur(
+        &mut coverage,
+        width,
+        height,
+        1,
+        params.blur_x,
+        params.blur_y,
+        params.num_passes,
+    );
+
+    let color = [
+        f32::from(params.color.r),
+        f32::from(params.color.g),
+        f32::from(params.color.b),
+    ];
+    let color_alpha = f32::from(params.color.a) / 255.0;
+
+    for (pixel, coverage) in rgba.chunks_exact_mut(4).zip(coverage.iter()) {
+        let src_alpha = f32::from(pixel[3]) / 255.0;
+        let mut shadow_alpha =
+            (f32::from(*coverage) / 255.0 * params.strength).min(1.0) * color_alpha;
+
+        if params.is_inner {
+            // Inner shadows only appear where the object itself is drawn.
+            shadow_alpha *= src_alpha;
+        }
+
+        for (channel, color) in pixel[0..3].iter_mut().zip(color.iter()) {
+            let src = f32::from(*channel);
+            let shadow = color * shadow_alpha;
+            let out = match (params.is_inner, params.is_knockout) {
+                // Source atop: the shadow is painted over the object.
+                (true, false) => shadow + src * (1.0 - shadow_alpha),
+                (true, true) => shadow,
+                // Destination over: the shadow is painted behind the object.
+                (false, false) => src + shadow * (1.0 - src_alpha),
+                (false, true) => shadow * (1.0 - src_alpha),
+            };
+            *channel = out.round().max(0.0).min(255.0) as u8;
+        }
+
+        let out_alpha = match (params.is_inner, params.is_knockout) {
+            (true, false) => src_alpha,
+            (true, true) => shadow_alpha,
+            (false, false) => src_alpha + shadow_alpha * (1.0 - src_alpha),
+            (false, true) => shadow_alpha * (1.0 - src_alpha),
+        };
+        pixel[3] = (out_alpha * 255.0).round().max(0.0).min(255.0) as u8;
+    }
+}
+
+/// Transforms the unmultiplied color of every pixel by a 4x5 color matrix.
+///
+/// Each row of the matrix computes one of the red, green, blue and alpha
+/// channels from the four channels of the pixel, plus an offset.
+fn apply_color_matrix(rgba: &mut [u8], matrix: &[f64; 20]) {
+    for pixel in rgba.chunks_exact_mut(4) {
+        let alpha = f64::from(pixel[3]);
+        let mut color = [0.0; 4];
+        for (value, channel) in color.iter_mut().zip(pixel.iter()) {
+            *value = if alpha > 0.0 {
+                f64::from(*channel) * 255.0 / alpha
+            } else {
+                0.0
+            };
+        }
+        color[3] = alpha;
+
+        let mut out = [0.0; 4];
+        for (row, out) in matrix.chunks_exact(5).zip(out.iter_mut()) {
+            let value = row[0] * color[0]
+                + row[1] * color[1]
+                + row[2] * color[2]
+                + row[3] * color[3]
+                + row[4];
+            *out = value.max(0.0).min(255.0);
+        }
+
+        let out_alpha = out[3] / 255.0;
+        for (channel, value) in pixel[0..3].iter_mut().zip(out.iter()) {
+            *channel = (value * out_alpha).round() as u8;
+        }
+        pixel[3] = out[3].round() as u8;
+    }
+}
+
+/// Draws a glow colored by a gradient, where the blurred alpha of the object
+/// picks the color of each pixel from the gradient.
+fn apply_gradient_glow(rgba: &mut [u8], width: usize, height: usize, glow: &GradientGlowFilter) {
+    let gradient = gradient_table(&glow.colors);
+    let offset = filter_offset(glow.angle, glow.distance);
+    let coverage = blurred_alpha(
+        rgba,
+        width,
+        height,
+        glow.blur_x,
+        glow.blur_y,
+        glow.num_passes,
+    );
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut alpha = f32::from(sample(&coverage, width, height, x, y, offset));
+            if glow.is_inner {
+                alpha = 255.0 - alpha;
+            }
+            let position = (alpha * glow.strength).round().max(0.0).min(255.0) as usize;
+            let pixel = &mut rgba[(y * width + x) * 4..(y * width + x + 1) * 4];
+            composite_effect(
+                pixel,
+                gradient[position],
+                glow.is_inner,
+                glow.is_knockout,
+                glow.is_on_top,
+            );
+        }
+    }
+}
+
+/// Draws a bevel colored by a gradient. The highlight side of the object's
+/// edges picks its colors from the start of the gradient, and the shadow
+/// side from the end.
+fn apply_gradient_bevel(rgba: &mut [u8], width: usize, height: usize, bevel: &GradientBevelFilter) {
+    let gradient = gradient_table(&bevel.colors);
+    let (dx, dy) = filter_offset(bevel.angle, bevel.distance);
+    let coverage = blurred_alpha(
+        rgba,
+        width,
+        height,
+        bevel.blur_x,
+        bevel.blur_y,
+        bevel.num_passes,
+    );
+
+    for y in 0..height {
+        for x in 0..width {
+            let shadow = f32::from(sample(&coverage, width, height, x, y, (dx, dy)));
+            let highlight = f32::from(sample(&coverage, width, height, x, y, (-dx, -dy)));
+            let position = (127.5 + (shadow - highlight) / 2.0 * bevel.strength)
+                .round()
+                .max(0.0)
+                .min(255.0) as usize;
+            let pixel = &mut rgba[(y * width + x) * 4..(y * width + x + 1) * 4];
+            composite_effect(
+                pixel,
+                gradient[position],
+                bevel.is_inner,
+                bevel.is_knockout,
+                bevel.is_on_top,
+            );
+        }
+    }
+}
+
+/// Returns the offset, in whole pixels, of a filter's effect from the object.
+fn filter_offset(angle: f64, distance: f64) -> (isize, isize) {
+    (
+        (angle.cos() * distance).round() as isize,
+        (angle.sin() * distance).round() as isize,
+    )
+}
+
+/// Returns the blurred alpha channel of an RGBA image.
+fn blurred_alpha(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    blur_x: f64,
+    blur_y: f64,
+    num_passes: u8,
+) -> Vec<u8> {
+    let mut alpha: Vec<u8> = rgba.chunks_exact(4).map(|pixel| pixel[3]).collect();
+    box_blur(&mut alpha, width, height, 1, blur_x, blur_y, num_passes);
+    alpha
+}
+
+/// Samples a single channel image as if it were moved by the given offset.
+/// Samples outside of the image are transparent.
+fn sample(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+    offset: (isize, isize),
+) -> u8 {
+    let (sx, sy) = (x as isize - offset.0, y as isize - offset.1);
+    if sx >= 0 && sy >= 0 && (sx as usize) < width && (sy as usize) < height {
+        data[sy as usize * width + sx as usize]
+    } else {
+        0
+    }
+}
+
+/// Builds a table of the 256 colors of a gradient, as premultiplied RGBA.
+fn gradient_table(records: &[GradientRecord]) -> Vec<[f32; 4]> {
+    let premultiplied = |color: &Color| {
+        let alpha = f32::from(color.a) / 255.0;
+        [
+            f32::from(color.r) * alpha,
+            f32::from(color.g) * alpha,
+            f32::from(color.b) * alpha,
+            f32::from(color.a),
+        ]
+    };
+
+    let mut table = Vec::with_capacity(256);
+    for position in 0..=255u8 {
+        let next = records.iter().position(|record| record.ratio >= position);
+        let color = match next {
+            None => records.last().map(|record| premultiplied(&record.color)),
+            Some(0) => records.first().map(|record| premultiplied(&record.color)),
+            Some(index) => {
+                let (start, end) = (&records[index - 1], &records[index]);
+                let range = f32::from(end.ratio - start.ratio);
+                let t = f32::from(position - start.ratio) / range;
+                let (start, end) = (premultiplied(&start.color), premultiplied(&end.color));
+                let mut color = [0.0; 4];
+                for ((color, start), end) in color.iter_mut().zip(start.iter()).zip(end.iter()) {
+                    *color = start + (end - start) * t;
+                }
+                Some(color)
+            }
+        };
+        table.push(color.unwrap_or([0.0; 4]));
+    }
+    table
+}
+
+/// Combines a premultiplied color drawn by a filter with a pixel of the
+/// object.
+///
+/// Inner effects are drawn over the object and only where it is drawn, outer
+/// effects behind it, and effects on top over all of it. Knockout effects
+/// replace the object instead.
+fn composite_effect(
+    pixel: &mut [u8],
+    effect: [f32; 4],
+    is_inner: bool,
+    is_knockout: bool,
+    is_on_top: bool,
+) {
+    let src_alpha = f32::from(pixel[3]) / 255.0;
+    let effect_alpha = effect[3] / 255.0;
+    for (channel, effect) in pixel.iter_mut().zip(effect.iter()) {
+        let src = f32::from(*channel);
+        let out = match (is_on_top, is_inner, is_knockout) {
+            (true, _, false) => effect + src * (1.0 - effect_alpha),
+            (true, _, true) => *effect,
+            // Source atop: the effect is painted over the object.
+            (false, true, false) => effect * src_alpha + src * (1.0 - effect_alpha),
+            (false, true, true) => effect * src_alpha,
+            // Destination over: the effect is painted behind the object.
+            (false, false, false) => src + effect * (1.0 - src_alpha),
+            (false, false, true) => effect * (1.0 - src_alpha),
+        };
+        *channel = out.round().max(0.0).min(255.0) as u8;
+    }
+}
+
+/// Blurs an image with interleaved channels using repeated box blurs, which
+/// is how the Flash Player implements blur quality.
+fn box_blur(
+    data: &mut [u8],
+    width: usize,
+    height: usize,
+    channels: usize,
+    blur_x: f64,
+    blur_y: f64,
+    num_passes: u8,
+) {
+    // The blur amount is the width of the box, centered on each pixel.
+    let radius_x = (blur_x / 2.0).floor().max(0.0) as usize;
+    let radius_y = (blur_y / 2.0).floor().max(0.0) as usize;
+    if radius_x == 0 && radius_y == 0 {
+        return;
+    }
+
+    let mut line = Vec::with_capacity(width.max(height));
+    for _ in 0..num_passes.max(1) {
+        if radius_x > 0 {
+            for y in 0..height {
+                for c in 0..channels {
+                    line.clear();
+                    line.extend((0..width).map(|x| data[(y * width + x) * channels + c]));
+                    for (x, value) in blur_line(&line, radius_x).enumerate() {
+                        data[(y * width + x) * channels + c] = value;
+                    }
+                }
+            }
+        }
+
+        if radius_y > 0 {
+            for x in 0..width {
+                for c in 0..channels {
+                    line.clear();
+                    line.extend((0..height).map(|y| data[(y * width + x) * channels + c]));
+                    for (y, value) in blur_line(&line, radius_y).enumerate() {
+                        data[(y * width + x) * channels + c] = value;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs a moving-average box filter over a single row or column of values.
+/// Samples outside of the line are treated as transparent.
+fn blur_line(line: &[u8], radius: usize) -> impl Iterator<Item = u8> + '_ {
+    let window = (radius * 2 + 1) as u32;
+    let mut sum: u32 = line.iter().take(radius).map(|v| u32::from(*v)).sum();
+    (0..line.len()).map(move |i| {
+        if let Some(entering) = line.get(i + radius) {
+            sum += u32::from(*entering);
+        }
+        let value = ((sum + window / 2) / window) as u8;
+        if i >= radius {
+            sum -= u32::from(line[i - radius]);
+        }
+        value
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::{BevelFilter, BlurFilter, ColorMatrixFilter, GlowFilter};
+
+    fn single_pixel(width: u32, height: u32) -> Bitmap {
+        let mut rgba = vec![0; (width * height * 4) as usize];
+        let center = ((height / 2) * width + width / 2) as usize * 4;
+        rgba[center..center + 4].copy_from_slice(&[255, 255, 255, 255]);
+        Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(rgba),
+        }
+    }
+
+    fn pixels(bitmap: &Bitmap) -> &[u8] {
+        match &bitmap.data {
+            BitmapFormat::Rgba(rgba) => rgba,
+            BitmapFormat::Rgb(rgb) => rgb,
+        }
+    }
+
+    #[test]
+    fn blur_spreads_and_preserves_total() {
+        let mut bitmap = single_pixel(9, 9);
+        apply_filters(
+            &mut bitmap,
+            &[Filter::BlurFilter(Box::new(BlurFilter {
+                blur_x: 2.0,
+                blur_y: 2.0,
+                num_passes: 1,
+            }))],
+        );
+
+        let alphas: Vec<u8> = pixels(&bitmap).chunks(4).map(|p| p[3]).collect();
+        let total: u32 = alphas.iter().map(|a| u32::from(*a)).sum();
+        assert!(alphas[3 * 9 + 3] > 0);
+        assert!(alphas[4 * 9 + 4] < 255);
+        assert!((total as i32 - 255).abs() <= 9);
+    }
+
+    #[test]
+    fn knockout_glow_removes_source() {
+        let mut bitmap = single_pixel(9, 9);
+        apply_filters(
+            &mut bitmap,
+            &[Filter::GlowFilter(Box::new(GlowFilter {
+                color: Color::from_rgb(0xff0000, 255),
+                blur_x: 4.0,
+                blur_y: 4.0,
+                strength: 1.0,
+                is_inner: false,
+                is_knockout: true,
+                num_passes: 1,
+            }))],
+        );
+
+        let rgba = pixels(&bitmap);
+        // The object itself has been knocked out...
+        assert_eq!(rgba[(4 * 9 + 4) * 4 + 3], 0);
+        // ...leaving only the red glow around it.
+        let neighbor = &rgba[(4 * 9 + 5) * 4..(4 * 9 + 6) * 4];
+        assert!(neighbor[3] > 0);
+        assert_eq!(neighbor[1], 0);
+        assert_eq!(neighbor[0], neighbor[3]);
+    }
+
+    #[test]
+    fn color_matrix_swaps_channels() {
+        let mut bitmap = Bitmap {
+            width: 2,
+            height: 1,
+            data: BitmapFormat::Rgba(vec![255, 0, 0, 255, 0, 0, 128, 128]),
+        };
+        #[rustfmt::skip]
+        let matrix = [
+            0.0, 0.0, 1.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0, 0.0,
+            1.0, 0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ];
+        apply_filters(
+            &mut bitmap,
+            &[Filter::ColorMatrixFilter(Box::new(ColorMatrixFilter {
+                matrix,
+            }))],
+        );
+        assert_eq!(pixels(&bitmap), [0, 0, 255, 255, 128, 0, 0, 128]);
+    }
+
+    #[test]
+    fn bevel_lights_edges_facing_the_light() {
+        // An opaque red square in the middle of the bitmap.
+        let mut rgba = vec![0; 9 * 9 * 4];
+        for y in 2..7 {
+            for x in 2..7 {
+                rgba[(y * 9 + x) * 4..(y * 9 + x + 1) * 4].copy_from_slice(&[255, 0, 0, 255]);
+            }
+        }
+        let mut bitmap = Bitmap {
+            width: 9,
+            height: 9,
+            data: BitmapFormat::Rgba(rgba),
+        };
+        apply_filters(
+            &mut bitmap,
+            &[Filter::BevelFilter(Box::new(BevelFilter {
+                shadow_color: Color::from_rgb(0x000000, 255),
+                highlight_color: Color::from_rgb(0xffffff, 255),
+                blur_x: 0.0,
+                blur_y: 0.0,
+                angle: std::f64::consts::FRAC_PI_4,
+                distance: 1.0,
+                strength: 1.0,
+                is_inner: true,
+                is_knockout: false,
+                is_on_top: false,
+                num_passes: 1,
+            }))],
+        );
+
+        let rgba = pixels(&bitmap);
+        let pixel = |x: usize, y: usize| &rgba[(y * 9 + x) * 4..(y * 9 + x + 1) * 4];
+        assert_eq!(pixel(2, 2), [255, 255, 255, 255]);
+        assert_eq!(pixel(6, 6), [0, 0, 0, 255]);
+        assert_eq!(pixel(4, 4), [255, 0, 0, 255]);
+        // An inner bevel isn't drawn outside of the object.
+        assert_eq!(pixel(1, 1), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn padding_covers_blur_and_distance() {
+        let filters = [Filter::BlurFilter(Box::new(BlurFilter {
+            blur_x: 8.0,
+            blur_y: 4.0,
+            num_passes: 2,
+        }))];
+        assert_eq!(
+            filter_padding(&filters),
+            (Twips::from_pixels(8.0), Twips::from_pixels(4.0))
+        );
+    }
+}
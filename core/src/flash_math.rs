@@ -0,0 +1,204 @@
+//! Math functions with the same edge-case behavior as Flash Player.
+//!
+//! Flash's `Math` follows ECMA-262 rather than the IEEE 754 recommendations
+//! that Rust's `f64` methods (and the C library beneath them) implement. The
+//! two mostly agree, but they differ on a handful of special inputs, and
+//! content that relies on those (physics code in particular) breaks if we get
+//! them wrong. Each function here documents where it deviates from its `f64`
+//! counterpart.
+
+/// Rounds a number to the nearest integer, with halves rounded towards
+/// positive infinity.
+///
+/// This differs from `f64::round`, which rounds halves away from zero:
+/// `round(-2.5)` is `-2`, not `-3`.
+///
+/// Numbers in the range `[-0.5, 0)` round to negative zero, and numbers too
+/// large to have a fractional part are returned unchanged. Naively computing
+/// `floor(n + 0.5)` gets both of these wrong.
+pub fn round(n: f64) -> f64 {
+    // Every `f64` of at least this magnitude is an integer.
+    const MIN_INTEGRAL: f64 = 4503599627370496.0; // 2^52
+
+    if !n.is_finite() || n.abs() >= MIN_INTEGRAL {
+        n
+    } else if (-0.5..0.0).contains(&n) {
+        -0.0
+    } else if n == 0.0 {
+        // Preserve the sign of zero.
+        n
+    } else {
+        let floor = n.floor();
+        if n - floor >= 0.5 {
+            floor + 1.0
+        } else {
+            floor
+        }
+    }
+}
+
+/// Raises `base` to the power of `exponent`.
+///
+/// This differs from `f64::powf` in two ways:
+///
+///  * A `NaN` exponent always results in `NaN`, even if the base is `1`.
+///  * A base of `1` or `-1` raised to an infinite power results in `NaN`,
+///    rather than `1`.
+///
+/// Negative bases with non-integral exponents result in `NaN`, and any base
+/// (including `NaN`) raised to a zero exponent results in `1`, just like
+/// `f64::powf`.
+pub fn pow(base: f64, exponent: f64) -> f64 {
+    if exponent.is_nan() || (base.abs() == 1.0 && exponent.is_infinite()) {
+        f64::NAN
+    } else {
+        base.powf(exponent)
+    }
+}
+
+/// Computes the angle, in radians, from the positive X axis to the point
+/// `(x, y)`.
+///
+/// This is identical to `f64::atan2`, including its treatment of signed zeros
+/// and infinities (e.g. `atan2(0, -0)` is `PI`, and `atan2(-0, -0)` is `-PI`),
+/// which Flash matches exactly. It lives here so that all of the `Math`
+/// globals share a single definition of their edge cases.
+pub fn atan2(y: f64, x: f64) -> f64 {
+    y.atan2(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    /// Compare two numbers, treating `NaN`s as equal and distinguishing signed
+    /// zeros.
+    fn assert_same(actual: f64, expected: f64) {
+        if expected.is_nan() {
+            assert!(actual.is_nan(), "expected NaN, got {}", actual);
+        } else {
+            assert_eq!(actual, expected);
+            assert_eq!(
+                actual.is_sign_negative(),
+                expected.is_sign_negative(),
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn round_halves_towards_positive_infinity() {
+        assert_same(round(0.5), 1.0);
+        assert_same(round(1.5), 2.0);
+        assert_same(round(2.5), 3.0);
+        assert_same(round(-1.5), -1.0);
+        assert_same(round(-2.5), -2.0);
+        assert_same(round(-23.5), -23.0);
+        assert_same(round(-23.7), -24.0);
+        assert_same(round(23.2), 23.0);
+    }
+
+    #[test]
+    fn round_signed_zeros() {
+        assert_same(round(0.0), 0.0);
+        assert_same(round(-0.0), -0.0);
+        assert_same(round(0.4), 0.0);
+        assert_same(round(-0.4), -0.0);
+        assert_same(round(-0.5), -0.0);
+        assert_same(round(-0.51), -1.0);
+        assert_same(round(f64::MIN_POSITIVE), 0.0);
+        assert_same(round(-f64::MIN_POSITIVE), -0.0);
+    }
+
+    #[test]
+    fn round_non_finite_and_large() {
+        assert_same(round(f64::NAN), f64::NAN);
+        assert_same(round(f64::INFINITY), f64::INFINITY);
+        assert_same(round(f64::NEG_INFINITY), f64::NEG_INFINITY);
+        assert_same(round(f64::MAX), f64::MAX);
+        assert_same(round(f64::MIN), f64::MIN);
+        assert_same(round(4503599627370497.0), 4503599627370497.0);
+        assert_same(round(-4503599627370497.0), -4503599627370497.0);
+        // The largest double below 0.5; adding 0.5 to it would round up to 1.
+        assert_same(round(0.49999999999999994), 0.0);
+    }
+
+    #[test]
+    fn pow_nan_exponent() {
+        assert_same(pow(1.0, f64::NAN), f64::NAN);
+        assert_same(pow(0.0, f64::NAN), f64::NAN);
+        assert_same(pow(f64::NAN, f64::NAN), f64::NAN);
+    }
+
+    #[test]
+    fn pow_zero_exponent() {
+        assert_same(pow(f64::NAN, 0.0), 1.0);
+        assert_same(pow(f64::NAN, -0.0), 1.0);
+        assert_same(pow(f64::INFINITY, 0.0), 1.0);
+        assert_same(pow(-5.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn pow_unit_base_infinite_exponent() {
+        assert_same(pow(1.0, f64::INFINITY), f64::NAN);
+        assert_same(pow(1.0, f64::NEG_INFINITY), f64::NAN);
+        assert_same(pow(-1.0, f64::INFINITY), f64::NAN);
+        assert_same(pow(-1.0, f64::NEG_INFINITY), f64::NAN);
+        assert_same(pow(1.0, 1e300), 1.0);
+    }
+
+    #[test]
+    fn pow_negative_bases() {
+        assert_same(pow(-2.0, 2.0), 4.0);
+        assert_same(pow(-2.0, 3.0), -8.0);
+        assert_same(pow(-2.0, -1.0), -0.5);
+        assert_same(pow(-2.0, 0.5), f64::NAN);
+        assert_same(pow(-8.0, 1.0 / 3.0), f64::NAN);
+        assert_same(pow(-0.0, 3.0), -0.0);
+        assert_same(pow(-0.0, 2.0), 0.0);
+        assert_same(pow(-0.0, -3.0), f64::NEG_INFINITY);
+        assert_same(pow(-0.0, -2.0), f64::INFINITY);
+        assert_same(pow(f64::NEG_INFINITY, 3.0), f64::NEG_INFINITY);
+        assert_same(pow(f64::NEG_INFINITY, -3.0), -0.0);
+    }
+
+    #[test]
+    fn pow_infinite_exponents() {
+        assert_same(pow(2.0, f64::INFINITY), f64::INFINITY);
+        assert_same(pow(0.5, f64::INFINITY), 0.0);
+        assert_same(pow(2.0, f64::NEG_INFINITY), 0.0);
+        assert_same(pow(0.5, f64::NEG_INFINITY), f64::INFINITY);
+        assert_same(pow(-2.0, f64::INFINITY), f64::INFINITY);
+    }
+
+    #[test]
+    fn atan2_signed_zeros() {
+        assert_same(atan2(0.0, 0.0), 0.0);
+        assert_same(atan2(-0.0, 0.0), -0.0);
+        assert_same(atan2(0.0, -0.0), PI);
+        assert_same(atan2(-0.0, -0.0), -PI);
+        assert_same(atan2(0.0, -1.0), PI);
+        assert_same(atan2(-0.0, -1.0), -PI);
+        assert_same(atan2(0.0, 1.0), 0.0);
+        assert_same(atan2(-0.0, 1.0), -0.0);
+        assert_same(atan2(1.0, 0.0), FRAC_PI_2);
+        assert_same(atan2(-1.0, -0.0), -FRAC_PI_2);
+    }
+
+    #[test]
+    fn atan2_infinities() {
+        assert_same(atan2(f64::INFINITY, f64::INFINITY), FRAC_PI_4);
+        assert_same(atan2(f64::NEG_INFINITY, f64::INFINITY), -FRAC_PI_4);
+        assert!((atan2(f64::INFINITY, f64::NEG_INFINITY) - 3.0 * FRAC_PI_4).abs() < 1e-15);
+        assert!((atan2(f64::NEG_INFINITY, f64::NEG_INFINITY) + 3.0 * FRAC_PI_4).abs() < 1e-15);
+        assert_same(atan2(1.0, f64::INFINITY), 0.0);
+        assert_same(atan2(1.0, f64::NEG_INFINITY), PI);
+        assert_same(atan2(-1.0, f64::NEG_INFINITY), -PI);
+        assert_same(atan2(f64::INFINITY, 1.0), FRAC_PI_2);
+        assert_same(atan2(f64::NAN, 1.0), f64::NAN);
+        assert_same(atan2(1.0, f64::NAN), f64::NAN);
+    }
+}
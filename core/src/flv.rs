@@ -0,0 +1,547 @@
+//! Streaming FLV demuxer
+//!
+//! FLV is the container format played by `NetStream`. An `FlvReader` is fed
+//! the bytes of a file as they arrive, and yields each tag of the file once
+//! all of its data is available.
+
+use swf::{SoundFormat, VideoCodec};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("Not an FLV file")]
+    InvalidSignature,
+
+    #[error("Unsupported FLV version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Invalid FLV header")]
+    InvalidHeader,
+
+    #[error("Invalid audio tag")]
+    InvalidAudioTag,
+
+    #[error("Invalid video tag")]
+    InvalidVideoTag,
+
+    #[error("Invalid script data")]
+    InvalidScriptData,
+}
+
+/// The size of the header of every FLV tag.
+const TAG_HEADER_LENGTH: usize = 11;
+
+/// The size of the back-pointer that follows every FLV tag.
+const PREVIOUS_TAG_SIZE_LENGTH: usize = 4;
+
+/// How deeply objects and arrays in script data may be nested, so that a
+/// malicious file can't overflow the stack.
+const MAX_SCRIPT_DATA_DEPTH: usize = 64;
+
+/// The header at the start of an FLV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlvHeader {
+    pub version: u8,
+    pub has_audio: bool,
+    pub has_video: bool,
+}
+
+/// A single tag of an FLV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlvTag {
+    /// The time at which this tag is to be played, in milliseconds.
+    pub timestamp: u32,
+
+    pub data: FlvTagData,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlvTagData {
+    Audio(AudioData),
+    Video(VideoData),
+    Script(ScriptData),
+
+    /// A tag of a type we don't know about, or an encrypted tag.
+    Unknown(u8),
+}
+
+/// A chunk of an FLV file's audio stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioData {
+    /// The format of the audio, or `None` if it is encoded in a format that
+    /// can't be embedded in a SWF (such as AAC).
+    pub format: Option<SoundFormat>,
+
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VideoFrameType {
+    Keyframe,
+    Interframe,
+    DisposableInterframe,
+    GeneratedKeyframe,
+    Command,
+}
+
+/// A frame of an FLV file's video stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoData {
+    pub frame_type: VideoFrameType,
+
+    /// The codec of the frame, or `None` if it is encoded with a codec that
+    /// can't be embedded in a SWF (such as H.264).
+    pub codec: Option<VideoCodec>,
+
+    /// The bitstream of the frame, in the same form as it would be stored in
+    /// a SWF `VideoFrame` tag.
+    pub data: Vec<u8>,
+}
+
+/// A named script data value, such as `onMetaData`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptData {
+    pub name: String,
+    pub value: ScriptDataValue,
+}
+
+/// An AMF0 value stored in an FLV script data tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptDataValue {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    Object(Vec<(String, ScriptDataValue)>),
+    Null,
+    Undefined,
+    EcmaArray(Vec<(String, ScriptDataValue)>),
+    StrictArray(Vec<ScriptDataValue>),
+
+    /// A date, in milliseconds since the Unix epoch.
+    Date(f64),
+}
+
+/// Reads the tags of an FLV file as its data arrives.
+#[derive(Debug, Default)]
+pub struct FlvReader {
+    /// The data that has arrived, but hasn't been read yet.
+    buffer: Vec<u8>,
+
+    /// The position of the next unread byte in `buffer`.
+    pos: usize,
+
+    header: Option<FlvHeader>,
+}
+
+impl FlvReader {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add newly arrived data to the end of the stream.
+    pub fn append(&mut self, data: &[u8]) {
+        self.buffer.drain(..self.pos);
+        self.pos = 0;
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// The header of the file, if enough data has arrived to read it.
+    pub fn header(&self) -> Option<&FlvHeader> {
+        self.header.as_ref()
+    }
+
+    /// Read the next tag of the file.
+    ///
+    /// Returns `Ok(None)` if the next tag hasn't fully arrived yet; it can be
+    /// read once more data has been appended.
+    pub fn next_tag(&mut self) -> Result<Option<FlvTag>, Error> {
+        if self.header.is_none() && !self.read_header()? {
+            return Ok(None);
+        }
+
+        let data = &self.buffer[self.pos..];
+        if data.len() < TAG_HEADER_LENGTH {
+            return Ok(None);
+        }
+
+        let tag_type = data[0];
+        let data_len = read_u24(&data[1..4]) as usize;
+        let timestamp = read_u24(&data[4..7]) | (u32::from(data[7]) << 24);
+        let tag_len = TAG_HEADER_LENGTH + data_len;
+        if data.len() < tag_len + PREVIOUS_TAG_SIZE_LENGTH {
+            return Ok(None);
+        }
+
+        // A tag with an invalid body is skipped, so that the rest of the file
+        // can still be read.
+        let body = &data[TAG_HEADER_LENGTH..tag_len];
+        let data = match tag_type {
+            8 => read_audio_data(body).map(FlvTagData::Audio),
+            9 => read_video_data(body).map(FlvTagData::Video),
+            18 => read_script_data(body).map(FlvTagData::Script),
+            _ => Ok(FlvTagData::Unknown(tag_type)),
+        };
+        self.pos += tag_len + PREVIOUS_TAG_SIZE_LENGTH;
+        let data = data?;
+
+        Ok(Some(FlvTag { timestamp, data }))
+    }
+
+    /// Read the file header, returning `false` if it hasn't fully arrived.
+    fn read_header(&mut self) -> Result<bool, Error> {
+        let data = &self.buffer[self.pos..];
+        if data.len() < 3 {
+            return Ok(false);
+        } else if &data[..3] != b"FLV" {
+            return Err(Error::InvalidSignature);
+        } else if data.len() < 9 {
+            return Ok(false);
+        }
+
+        let version = data[3];
+        if version != 1 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let flags = data[4];
+        let data_offset = u32::from_be_bytes([data[5], data[6], data[7], data[8]]) as usize;
+        if data_offset < 9 {
+            return Err(Error::InvalidHeader);
+        } else if data.len() < data_offset + PREVIOUS_TAG_SIZE_LENGTH {
+            return Ok(false);
+        }
+
+        self.header = Some(FlvHeader {
+            version,
+            has_audio: flags & 0b100 != 0,
+            has_video: flags & 0b1 != 0,
+        });
+        self.pos += data_offset + PREVIOUS_TAG_SIZE_LENGTH;
+
+        Ok(true)
+    }
+}
+
+fn read_u24(data: &[u8]) -> u32 {
+    u32::from_be_bytes([0, data[0], data[1], data[2]])
+}
+
+fn read_audio_data(body: &[u8]) -> Result<AudioData, Error> {
+    let flags = *body.get(0).ok_or(Error::InvalidAudioTag)?;
+
+    // The audio header byte is laid out just like a SWF sound format, except
+    // that FLV also allows codecs that SWFs can't embed.
+    let format = swf::read::Reader::new(&body[..1], 0)
+        .read_sound_format()
+        .ok();
+
+    // AAC has an extra packet type byte.
+    let data_start = if flags >> 4 == 10 { 2 } else { 1 };
+    let data = body.get(data_start..).ok_or(Error::InvalidAudioTag)?;
+
+    Ok(AudioData {
+        format,
+        data: data.to_vec(),
+    })
+}
+
+fn read_video_data(body: &[u8]) -> Result<VideoData, Error> {
+    let flags = *body.get(0).ok_or(Error::InvalidVideoTag)?;
+    let frame_type = match flags >> 4 {
+        1 => VideoFrameType::Keyframe,
+        2 => VideoFrameType::Interframe,
+        3 => VideoFrameType::DisposableInterframe,
+        4 => VideoFrameType::GeneratedKeyframe,
+        5 => VideoFrameType::Command,
+        _ => return Err(Error::InvalidVideoTag),
+    };
+    let (codec, data_start) = match flags & 0xF {
+        2 => (Some(VideoCodec::H263), 1),
+        3 => (Some(VideoCodec::ScreenVideo), 1),
+        // VP6 frames start with a byte of size adjustments that SWFs don't
+        // have.
+        4 => (Some(VideoCodec::Vp6), 2),
+        5 => (Some(VideoCodec::Vp6WithAlpha), 2),
+        6 => (Some(VideoCodec::ScreenVideoV2), 1),
+        _ => (None, 1),
+    };
+    let data = body.get(data_start..).ok_or(Error::InvalidVideoTag)?;
+
+    Ok(VideoData {
+        frame_type,
+        codec,
+        data: data.to_vec(),
+    })
+}
+
+fn read_script_data(body: &[u8]) -> Result<ScriptData, Error> {
+    let mut reader = ScriptDataReader {
+        data: body,
+        depth: 0,
+    };
+    let name = match reader.read_value()? {
+        ScriptDataValue::String(name) => name,
+        _ => return Err(Error::InvalidScriptData),
+    };
+    let value = reader.read_value()?;
+
+    Ok(ScriptData { name, value })
+}
+
+/// Reads AMF0 values out of a script data tag.
+struct ScriptDataReader<'a> {
+    data: &'a [u8],
+
+    /// How many objects and arrays the value being read is nested in.
+    depth: usize,
+}
+
+impl<'a> ScriptDataReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < len {
+            return Err(Error::InvalidScriptData);
+        }
+
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_f64(&mut self) -> Result<f64, Error> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(f64::from_be_bytes(bytes))
+    }
+
+    fn read_string(&mut self, len: usize) -> Result<String, Error> {
+        Ok(String::from_utf8_lossy(self.read_bytes(len)?).into_owned())
+    }
+
+    fn read_value(&mut self) -> Result<ScriptDataValue, Error> {
+        if self.depth >= MAX_SCRIPT_DATA_DEPTH {
+            return Err(Error::InvalidScriptData);
+        }
+
+        self.depth += 1;
+        let value = self.read_value_contents();
+        self.depth -= 1;
+        value
+    }
+
+    fn read_value_contents(&mut self) -> Result<ScriptDataValue, Error> {
+        Ok(match self.read_u8()? {
+            0 => ScriptDataValue::Number(self.read_f64()?),
+            1 => ScriptDataValue::Boolean(self.read_u8()? != 0),
+            2 => {
+                let len = self.read_u16()?.into();
+                ScriptDataValue::String(self.read_string(len)?)
+            }
+            3 => ScriptDataValue::Object(self.read_properties()?),
+            5 => ScriptDataValue::Null,
+            6 => ScriptDataValue::Undefined,
+            // References to other objects aren't supported.
+            7 => {
+                self.read_u16()?;
+                ScriptDataValue::Undefined
+            }
+            8 => {
+                // The length is only a hint; the array is terminated like an
+                // object.
+                self.read_u32()?;
+                ScriptDataValue::EcmaArray(self.read_properties()?)
+            }
+            10 => {
+                let len = self.read_u32()?;
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    values.push(self.read_value()?);
+                }
+                ScriptDataValue::StrictArray(values)
+            }
+            11 => {
+                let time = self.read_f64()?;
+                // The time zone offset is always ignored.
+                self.read_u16()?;
+                ScriptDataValue::Date(time)
+            }
+            12 => {
+                let len = self.read_u32()? as usize;
+                ScriptDataValue::String(self.read_string(len)?)
+            }
+            _ => return Err(Error::InvalidScriptData),
+        })
+    }
+
+    /// Read the properties of an object, up to the object end marker.
+    fn read_properties(&mut self) -> Result<Vec<(String, ScriptDataValue)>, Error> {
+        let mut properties = Vec::new();
+
+        loop {
+            let len = self.read_u16()?.into();
+            let name = self.read_string(len)?;
+            if name.is_empty() && self.data.get(0) == Some(&9) {
+                self.read_u8()?;
+                return Ok(properties);
+            }
+
+            properties.push((name, self.read_value()?));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::AudioCompression;
+
+    fn header() -> Vec<u8> {
+        vec![b'F', b'L', b'V', 1, 0b101, 0, 0, 0, 9, 0, 0, 0, 0]
+    }
+
+    fn tag(tag_type: u8, timestamp: u32, body: &[u8]) -> Vec<u8> {
+        let len = body.len() as u32;
+        let mut tag = vec![tag_type];
+        tag.extend_from_slice(&len.to_be_bytes()[1..]);
+        tag.extend_from_slice(&timestamp.to_be_bytes()[1..]);
+        tag.push((timestamp >> 24) as u8);
+        tag.extend_from_slice(&[0, 0, 0]);
+        tag.extend_from_slice(body);
+        tag.extend_from_slice(&(len + 11).to_be_bytes());
+        tag
+    }
+
+    fn amf_string(s: &str) -> Vec<u8> {
+        let mut data = vec![2];
+        data.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        data.extend_from_slice(s.as_bytes());
+        data
+    }
+
+    #[test]
+    fn read_tags_as_they_arrive() {
+        let mut file = header();
+        file.extend(tag(9, 0, &[0x12, 1, 2, 3]));
+        file.extend(tag(8, 40, &[0x2E, 4, 5]));
+
+        let mut reader = FlvReader::new();
+        for byte in &file[..file.len() - 1] {
+            reader.append(&[*byte]);
+            if let Some(tag) = reader.next_tag().unwrap() {
+                assert_eq!(tag.timestamp, 0);
+                assert_eq!(
+                    tag.data,
+                    FlvTagData::Video(VideoData {
+                        frame_type: VideoFrameType::Keyframe,
+                        codec: Some(VideoCodec::H263),
+                        data: vec![1, 2, 3],
+                    })
+                );
+            }
+        }
+        assert_eq!(
+            reader.header(),
+            Some(&FlvHeader {
+                version: 1,
+                has_audio: true,
+                has_video: true,
+            })
+        );
+
+        assert_eq!(reader.next_tag(), Ok(None));
+        reader.append(&file[file.len() - 1..]);
+        let tag = reader.next_tag().unwrap().unwrap();
+        assert_eq!(tag.timestamp, 40);
+        match tag.data {
+            FlvTagData::Audio(AudioData {
+                format: Some(format),
+                data,
+            }) => {
+                assert_eq!(format.compression, AudioCompression::Mp3);
+                assert_eq!(format.sample_rate, 44100);
+                assert!(format.is_16_bit);
+                assert!(!format.is_stereo);
+                assert_eq!(data, vec![4, 5]);
+            }
+            data => panic!("Expected audio data, got {:?}", data),
+        }
+        assert_eq!(reader.next_tag(), Ok(None));
+    }
+
+    #[test]
+    fn read_metadata() {
+        let mut body = amf_string("onMetaData");
+        body.extend_from_slice(&[8, 0, 0, 0, 2]);
+        body.extend_from_slice(&[0, 8]);
+        body.extend_from_slice(b"duration");
+        body.push(0);
+        body.extend_from_slice(&2.5f64.to_be_bytes());
+        body.extend_from_slice(&[0, 5]);
+        body.extend_from_slice(b"codec");
+        body.extend(amf_string("h263"));
+        body.extend_from_slice(&[0, 0, 9]);
+
+        let mut file = header();
+        file.extend(tag(18, 0, &body));
+
+        let mut reader = FlvReader::new();
+        reader.append(&file);
+        let tag = reader.next_tag().unwrap().unwrap();
+        assert_eq!(
+            tag.data,
+            FlvTagData::Script(ScriptData {
+                name: "onMetaData".to_string(),
+                value: ScriptDataValue::EcmaArray(vec![
+                    ("duration".to_string(), ScriptDataValue::Number(2.5)),
+                    (
+                        "codec".to_string(),
+                        ScriptDataValue::String("h263".to_string())
+                    ),
+                ]),
+            })
+        );
+    }
+
+    #[test]
+    fn reject_deeply_nested_script_data() {
+        let mut body = amf_string("onMetaData");
+        for _ in 0..MAX_SCRIPT_DATA_DEPTH {
+            body.extend_from_slice(&[10, 0, 0, 0, 1]);
+        }
+        body.push(5);
+
+        let mut file = header();
+        file.extend(tag(18, 0, &body));
+        file.extend(tag(9, 40, &[0x12, 1]));
+
+        // The invalid tag is skipped, and the next one can still be read.
+        let mut reader = FlvReader::new();
+        reader.append(&file);
+        assert_eq!(reader.next_tag(), Err(Error::InvalidScriptData));
+        assert_eq!(reader.next_tag().unwrap().unwrap().timestamp, 40);
+    }
+
+    #[test]
+    fn reject_invalid_files() {
+        let mut reader = FlvReader::new();
+        reader.append(b"FWS\x08");
+        assert_eq!(reader.next_tag(), Err(Error::InvalidSignature));
+
+        let mut reader = FlvReader::new();
+        reader.append(&[b'F', b'L', b'V', 2, 0, 0, 0, 0, 9, 0, 0, 0, 0]);
+        assert_eq!(reader.next_tag(), Err(Error::UnsupportedVersion(2)));
+    }
+}
@@ -1,19 +1,55 @@
-use crate::avm1::{Avm1, Value};
-use crate::context::UpdateContext;
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::{Avm1, TObject, Value};
+use crate::context::{RenderContext, UpdateContext};
+use crate::display_object::Stage;
 pub use crate::display_object::{DisplayObject, TDisplayObject, TDisplayObjectContainer};
+use crate::events::ClipEvent;
+use crate::prelude::*;
 use gc_arena::{Collect, GcCell, MutationContext};
 
+/// The color of the highlight drawn around an object focused by the keyboard.
+const HIGHLIGHT_COLOR: Color = Color {
+    r: 255,
+    g: 255,
+    b: 0,
+    a: 255,
+};
+
+/// The width of the highlight, in pixels.
+const HIGHLIGHT_WIDTH: f32 = 2.0;
+
 #[derive(Clone, Copy, Collect, Debug)]
 #[collect(no_drop)]
-pub struct FocusTracker<'gc>(GcCell<'gc, Option<DisplayObject<'gc>>>);
+pub struct FocusTracker<'gc>(GcCell<'gc, FocusTrackerData<'gc>>);
+
+#[derive(Clone, Collect, Debug, Default)]
+#[collect(no_drop)]
+struct FocusTrackerData<'gc> {
+    /// The object that currently has keyboard focus.
+    focus: Option<DisplayObject<'gc>>,
+
+    /// Whether the focus was moved with the keyboard, in which case the
+    /// focused object is highlighted and can be activated with Enter or Space.
+    highlight: bool,
+}
 
 impl<'gc> FocusTracker<'gc> {
     pub fn new(gc_context: MutationContext<'gc, '_>) -> Self {
-        Self(GcCell::allocate(gc_context, None))
+        Self(GcCell::allocate(gc_context, Default::default()))
     }
 
     pub fn get(&self) -> Option<DisplayObject<'gc>> {
-        *self.0.read()
+        self.0.read().focus
+    }
+
+    /// Whether the focused object is highlighted.
+    pub fn is_highlighted(&self) -> bool {
+        self.0.read().highlight && self.get().is_some()
+    }
+
+    /// Stop highlighting the focused object, such as when the mouse is used.
+    pub fn hide_highlight(&self, gc_context: MutationContext<'gc, '_>) {
+        self.0.write(gc_context).highlight = false;
     }
 
     pub fn set(
@@ -21,7 +57,11 @@ impl<'gc> FocusTracker<'gc> {
         focused_element: Option<DisplayObject<'gc>>,
         context: &mut UpdateContext<'_, 'gc, '_>,
     ) {
-        let old = std::mem::replace(&mut *self.0.write(context.gc_context), focused_element);
+        let old = {
+            let mut write = self.0.write(context.gc_context);
+            write.highlight = false;
+            std::mem::replace(&mut write.focus, focused_element)
+        };
 
         if old.is_none() && focused_element.is_none() {
             // We didn't have anything, we still don't, no change.
@@ -43,6 +83,31 @@ impl<'gc> FocusTracker<'gc> {
 
         log::info!("Focus is now on {:?}", focused_element);
 
+        // The objects themselves hear of the change first, each being told
+        // about the other.
+        let old_object = old.map(|v| v.object()).unwrap_or(Value::Null);
+        let new_object = focused_element.map(|v| v.object()).unwrap_or(Value::Null);
+        if let (Some(old), Value::Object(object)) = (old, old_object) {
+            Avm1::run_stack_frame_for_method(
+                old,
+                object,
+                old.swf_version(),
+                context,
+                "onKillFocus",
+                &[new_object],
+            );
+        }
+        if let (Some(new), Value::Object(object)) = (focused_element, new_object) {
+            Avm1::run_stack_frame_for_method(
+                new,
+                object,
+                new.swf_version(),
+                context,
+                "onSetFocus",
+                &[old_object],
+            );
+        }
+
         let level0 = context.stage.root_clip();
         Avm1::notify_system_listeners(
             level0,
@@ -50,10 +115,227 @@ impl<'gc> FocusTracker<'gc> {
             context,
             "Selection",
             "onSetFocus",
-            &[
-                old.map(|v| v.object()).unwrap_or(Value::Null),
-                focused_element.map(|v| v.object()).unwrap_or(Value::Null),
-            ],
+            &[old_object, new_object],
         );
     }
+
+    /// Move the focus to the next object in the tab order, or the previous
+    /// one if `reverse` is set, as when the user presses Tab or Shift+Tab.
+    pub fn cycle(&self, context: &mut UpdateContext<'_, 'gc, '_>, reverse: bool) {
+        let tab_order = tab_order(context);
+        if tab_order.is_empty() {
+            return;
+        }
+
+        let old = self.get();
+        let position = old.and_then(|old| {
+            tab_order
+                .iter()
+                .position(|object| object.as_ptr() == old.as_ptr())
+        });
+        let next = match (position, reverse) {
+            (Some(0), true) | (None, true) => tab_order.len() - 1,
+            (Some(position), true) => position - 1,
+            (Some(position), false) => (position + 1) % tab_order.len(),
+            (None, false) => 0,
+        };
+        let new = tab_order[next];
+
+        if let Some(old) = old.filter(|old| is_button_like(*old, context)) {
+            old.handle_clip_event(context, ClipEvent::RollOut);
+        }
+        self.set(Some(new), context);
+        if is_button_like(new, context) {
+            new.handle_clip_event(context, ClipEvent::RollOver);
+        }
+        self.0.write(context.gc_context).highlight = true;
+    }
+
+    /// Activate the highlighted object, as when the user presses Enter or
+    /// Space. Only buttons and movie clips acting as buttons are activated,
+    /// and only if their focus rectangle is enabled.
+    pub fn activate(&self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        if !self.is_highlighted() {
+            return;
+        }
+
+        let stage = context.stage;
+        if let Some(focus) = self
+            .get()
+            .filter(|focus| has_focus_rect(*focus, stage) && is_button_like(*focus, context))
+        {
+            focus.handle_clip_event(context, ClipEvent::Press);
+            focus.handle_clip_event(context, ClipEvent::Release);
+        }
+    }
+
+    /// Draw the highlight around the focused object, if it was focused with
+    /// the keyboard.
+    pub fn render_highlight(&self, context: &mut RenderContext<'_, 'gc>) {
+        if !self.is_highlighted() {
+            return;
+        }
+
+        let focus = match self.get() {
+            Some(focus) if !focus.removed() && has_focus_rect(focus, context.stage) => focus,
+            _ => return,
+        };
+        // World bounds include the stage's view matrix, so they are already in
+        // viewport space.
+        let bounds = focus.world_bounds();
+        if !bounds.valid {
+            return;
+        }
+
+        let width = (bounds.x_max - bounds.x_min).to_pixels() as f32;
+        let height = (bounds.y_max - bounds.y_min).to_pixels() as f32;
+        let thickness = Twips::from_pixels(HIGHLIGHT_WIDTH.into());
+        let edges = [
+            (bounds.x_min, bounds.y_min, width, HIGHLIGHT_WIDTH),
+            (
+                bounds.x_min,
+                bounds.y_max - thickness,
+                width,
+                HIGHLIGHT_WIDTH,
+            ),
+            (bounds.x_min, bounds.y_min, HIGHLIGHT_WIDTH, height),
+            (
+                bounds.x_max - thickness,
+                bounds.y_min,
+                HIGHLIGHT_WIDTH,
+                height,
+            ),
+        ];
+        for (x, y, width, height) in edges.iter() {
+            context.renderer.draw_rect(
+                HIGHLIGHT_COLOR.clone(),
+                &Matrix::create_box(*width, *height, 0.0, *x, *y),
+            );
+        }
+    }
+}
+
+/// Whether an object is highlighted when it's focused with the keyboard.
+///
+/// This is set by the `_focusrect` property of the object, or of the stage if
+/// the object doesn't override it. Objects without a focus rectangle can
+/// still be focused with Tab, but ignore Enter and Space.
+fn has_focus_rect<'gc>(object: DisplayObject<'gc>, stage: Stage<'gc>) -> bool {
+    object.focus_rect().unwrap_or_else(|| stage.focus_rect())
+}
+
+/// Whether an object reacts to being activated like a button.
+fn is_button_like<'gc>(
+    object: DisplayObject<'gc>,
+    context: &mut UpdateContext<'_, 'gc, '_>,
+) -> bool {
+    match object {
+        DisplayObject::Button(_) => true,
+        DisplayObject::MovieClip(clip) => clip.is_button_mode(context),
+        _ => false,
+    }
+}
+
+/// The tab-related properties of an object that may be focused.
+struct TabStop<'gc> {
+    object: DisplayObject<'gc>,
+    tab_index: Option<i32>,
+}
+
+/// Build the order in which Tab moves the focus between objects.
+///
+/// Buttons, movie clips acting as buttons and input text fields can be
+/// focused, unless their `tabEnabled` property is `false`; any other movie
+/// clip can be focused if its `tabEnabled` property is `true`. The children of
+/// a movie clip are skipped if its `tabChildren` property is `false`.
+///
+/// If any of these objects has a `tabIndex` property, only the objects with
+/// one are focused, in the order of their indices. Otherwise, objects are
+/// focused from top to bottom and then left to right.
+fn tab_order<'gc>(context: &mut UpdateContext<'_, 'gc, '_>) -> Vec<DisplayObject<'gc>> {
+    let levels: Vec<_> = context.stage.iter_depth_list().collect();
+    let mut activation = Activation::from_stub(
+        context.reborrow(),
+        ActivationIdentifier::root("[Tab Order]"),
+    );
+    let mut tab_stops = Vec::new();
+    for (_depth, level) in levels {
+        collect_tab_stops(level, &mut activation, &mut tab_stops);
+    }
+
+    if tab_stops
+        .iter()
+        .any(|tab_stop| tab_stop.tab_index.is_some())
+    {
+        tab_stops.retain(|tab_stop| tab_stop.tab_index.is_some());
+        tab_stops.sort_by_key(|tab_stop| tab_stop.tab_index);
+    } else {
+        tab_stops.sort_by_key(|tab_stop| {
+            let bounds = tab_stop.object.world_bounds();
+            (bounds.y_min, bounds.x_min)
+        });
+    }
+
+    tab_stops
+        .into_iter()
+        .map(|tab_stop| tab_stop.object)
+        .collect()
+}
+
+/// Collect the visible objects in the tab order, in depth order.
+fn collect_tab_stops<'gc>(
+    object: DisplayObject<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    tab_stops: &mut Vec<TabStop<'gc>>,
+) {
+    if !object.visible() || object.removed() {
+        return;
+    }
+
+    let default_enabled = match object {
+        DisplayObject::Button(_) => true,
+        DisplayObject::MovieClip(clip) => clip.is_button_mode(&mut activation.context),
+        DisplayObject::EditText(text) => text.is_editable(),
+        _ => false,
+    };
+    let tab_enabled = match tab_property(object, "tabEnabled", activation) {
+        Some(tab_enabled) => tab_enabled.as_bool(activation.swf_version()),
+        None => default_enabled,
+    };
+    if tab_enabled {
+        let tab_index = tab_property(object, "tabIndex", activation)
+            .and_then(|tab_index| tab_index.coerce_to_f64(activation).ok())
+            .filter(|tab_index| tab_index.is_finite() && *tab_index >= 0.0)
+            .map(|tab_index| tab_index as i32);
+        tab_stops.push(TabStop { object, tab_index });
+    }
+
+    if let DisplayObject::MovieClip(clip) = object {
+        let tab_children = tab_property(object, "tabChildren", activation)
+            .map_or(true, |tab_children| {
+                tab_children.as_bool(activation.swf_version())
+            });
+        if tab_children {
+            for child in clip.iter_render_list() {
+                collect_tab_stops(child, activation, tab_stops);
+            }
+        }
+    }
+}
+
+/// Read a tab-related property of an object, if it has been set.
+fn tab_property<'gc>(
+    object: DisplayObject<'gc>,
+    name: &str,
+    activation: &mut Activation<'_, 'gc, '_>,
+) -> Option<Value<'gc>> {
+    let object = match object.object() {
+        Value::Object(object) => object,
+        _ => return None,
+    };
+
+    match object.get(name, activation) {
+        Ok(Value::Undefined) | Ok(Value::Null) | Err(_) => None,
+        Ok(value) => Some(value),
+    }
 }
@@ -1,4 +1,5 @@
 use crate::backend::render::{RenderBackend, ShapeHandle};
+use crate::html::bidi::{self, Direction};
 use crate::html::TextSpan;
 use crate::prelude::*;
 use crate::transform::Transform;
@@ -30,6 +31,12 @@ pub struct EvalParameters {
     /// pairs of letters, separate from the ordinary width between glyphs. This
     /// parameter allows enabling or disabling that feature.
     kerning: bool,
+
+    /// The direction to draw the text in.
+    ///
+    /// This is resolved when text is laid out. Until then, it is detected
+    /// from the text being evaluated.
+    direction: Option<Direction>,
 }
 
 impl EvalParameters {
@@ -40,6 +47,7 @@ impl EvalParameters {
             height,
             letter_spacing,
             kerning,
+            direction: None,
         }
     }
 
@@ -50,6 +58,7 @@ impl EvalParameters {
             height: Twips::from_pixels(span.size),
             letter_spacing: Twips::from_pixels(span.letter_spacing),
             kerning: span.kerning,
+            direction: None,
         }
     }
 
@@ -57,6 +66,12 @@ impl EvalParameters {
     pub fn height(&self) -> Twips {
         self.height
     }
+
+    /// Evaluate text in the given direction, rather than detecting it.
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Collect, Copy)]
@@ -226,7 +241,7 @@ impl<'gc> Font<'gc> {
         text: &str,
         mut transform: Transform,
         params: EvalParameters,
-        mut glyph_func: FGlyph,
+        glyph_func: FGlyph,
     ) where
         FGlyph: FnMut(usize, &Transform, &Glyph, Twips, Twips),
     {
@@ -235,7 +250,31 @@ impl<'gc> Font<'gc> {
 
         transform.matrix.a = scale;
         transform.matrix.d = scale;
-        let mut char_indices = text.char_indices().peekable();
+
+        // Right-to-left text has to be shaped and put into visual order first.
+        // Positions still refer to the original text.
+        if params.direction == Some(Direction::RightToLeft) || bidi::has_rtl(text) {
+            let chars = bidi::visual_chars(text, params.direction, |c| {
+                self.get_glyph_for_char(c).is_some()
+            });
+            self.evaluate_chars(chars.into_iter(), transform, scale, params, glyph_func);
+        } else {
+            self.evaluate_chars(text.char_indices(), transform, scale, params, glyph_func);
+        }
+    }
+
+    /// Lay out characters, given in visual order, one after the other.
+    fn evaluate_chars<FGlyph>(
+        &self,
+        chars: impl Iterator<Item = (usize, char)>,
+        mut transform: Transform,
+        scale: f32,
+        params: EvalParameters,
+        mut glyph_func: FGlyph,
+    ) where
+        FGlyph: FnMut(usize, &Transform, &Glyph, Twips, Twips),
+    {
+        let mut char_indices = chars.peekable();
         let has_kerning_info = self.has_kerning_info();
         let mut x = Twips::zero();
         while let Some((pos, c)) = char_indices.next() {
@@ -0,0 +1,282 @@
+//! Hot reloading of movie assets.
+//!
+//! While developing content it's useful to see new artwork without restarting
+//! the movie. `reload_assets` compares a freshly loaded copy of a movie with
+//! the one that is playing; if they only differ in shapes, bitmaps and sounds,
+//! the changed definitions are swapped into the running movie's library in
+//! place, and the timeline and script state are left alone.
+
+use crate::backend::render::{Bitmap, BitmapFormat, BitmapHandle};
+use crate::bitmap::decode::{
+    decode_define_bits_jpeg, decode_define_bits_jpeg_4, decode_define_bits_lossless,
+    glue_tables_to_jpeg,
+};
+use crate::character::Character;
+use crate::context::UpdateContext;
+use crate::display_object::Graphic;
+use crate::prelude::*;
+use crate::tag_utils::{SwfMovie, SwfSlice};
+use std::sync::Arc;
+use swf::TagCode;
+
+/// The kinds of definition tags that can be replaced in a running movie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetKind {
+    Shape,
+    Bitmap,
+    Sound,
+}
+
+impl AssetKind {
+    fn from_tag_code(tag_code: TagCode) -> Option<Self> {
+        match tag_code {
+            TagCode::DefineShape
+            | TagCode::DefineShape2
+            | TagCode::DefineShape3
+            | TagCode::DefineShape4 => Some(Self::Shape),
+            TagCode::DefineBits
+            | TagCode::DefineBitsJpeg2
+            | TagCode::DefineBitsJpeg3
+            | TagCode::DefineBitsJpeg4
+            | TagCode::DefineBitsLossless
+            | TagCode::DefineBitsLossless2 => Some(Self::Bitmap),
+            TagCode::DefineSound => Some(Self::Sound),
+            _ => None,
+        }
+    }
+}
+
+/// A replaceable definition tag.
+struct AssetTag<'a> {
+    tag_code: TagCode,
+    kind: AssetKind,
+
+    /// The body of the tag, starting with its character ID.
+    data: &'a [u8],
+}
+
+/// The top-level tags of a movie, split into replaceable definitions and
+/// everything else.
+struct MovieTags<'a> {
+    assets: fnv::FnvHashMap<CharacterId, AssetTag<'a>>,
+    other: Vec<(u16, &'a [u8])>,
+}
+
+impl<'a> MovieTags<'a> {
+    /// Split up the tags of a movie, returning `None` if it is malformed.
+    fn split(movie: &'a SwfMovie) -> Option<Self> {
+        let mut reader = swf::read::Reader::new(movie.data(), movie.version());
+        let mut assets = fnv::FnvHashMap::default();
+        let mut other = Vec::new();
+
+        while !reader.get_ref().is_empty() {
+            let (tag_code, tag_len) = reader.read_tag_code_and_length().ok()?;
+            let data = reader.get_ref();
+            if tag_len > data.len() {
+                return None;
+            }
+
+            let (data, rest) = data.split_at(tag_len);
+            *reader.get_mut() = rest;
+
+            let tag = TagCode::from_u16(tag_code)
+                .and_then(|tag_code| Some((tag_code, AssetKind::from_tag_code(tag_code)?)));
+            match (tag, data) {
+                (Some((tag_code, kind)), [id_lo, id_hi, ..]) => {
+                    let id = u16::from_le_bytes([*id_lo, *id_hi]);
+                    let asset = AssetTag {
+                        tag_code,
+                        kind,
+                        data,
+                    };
+                    if assets.insert(id, asset).is_some() {
+                        return None;
+                    }
+                }
+                _ => other.push((tag_code, data)),
+            }
+
+            if tag_code == TagCode::End as u16 {
+                break;
+            }
+        }
+
+        Some(Self { assets, other })
+    }
+}
+
+/// A changed definition, decoded and ready to be swapped in.
+enum Replacement<'gc, 'a> {
+    Shape(Graphic<'gc>, swf::Shape),
+    Bitmap(BitmapHandle, u32, u32, Vec<u8>),
+    Sound(swf::Sound<'a>),
+}
+
+/// Swaps the changed shapes, bitmaps and sounds of `new_movie` into the
+/// library of the running `movie`.
+///
+/// Instances of a shape that are already on the stage are redrawn with its
+/// new outline, but keep their old bounds until they are placed again. Sounds
+/// that are already playing are not affected.
+///
+/// Returns `false`, without changing anything, if the movies differ in any
+/// other way (including a bitmap changing size); the movie has to be restarted
+/// to pick up such changes.
+pub fn reload_assets<'gc>(
+    context: &mut UpdateContext<'_, 'gc, '_>,
+    movie: Arc<SwfMovie>,
+    new_movie: &SwfMovie,
+) -> bool {
+    let (header, new_header) = (movie.header(), new_movie.header());
+    if header.version != new_header.version
+        || header.frame_rate != new_header.frame_rate
+        || header.num_frames != new_header.num_frames
+        || header.stage_size != new_header.stage_size
+    {
+        return false;
+    }
+
+    let (tags, new_tags) = match (MovieTags::split(&movie), MovieTags::split(new_movie)) {
+        (Some(tags), Some(new_tags)) => (tags, new_tags),
+        _ => return false,
+    };
+
+    if tags.other != new_tags.other || tags.assets.len() != new_tags.assets.len() {
+        return false;
+    }
+
+    let library = match context.library.library_for_movie(movie.clone()) {
+        Some(library) => library,
+        None => return false,
+    };
+
+    // Decode everything up front, so that we don't end up with a half-reloaded
+    // movie if something goes wrong.
+    let mut replacements = Vec::new();
+    for (id, new_asset) in new_tags.assets.iter() {
+        let asset = match tags.assets.get(id) {
+            Some(asset) => asset,
+            None => return false,
+        };
+
+        if asset.kind != new_asset.kind {
+            return false;
+        } else if asset.data == new_asset.data {
+            continue;
+        }
+
+        let mut reader = swf::read::Reader::new(new_asset.data, new_movie.version());
+        let replacement = match (new_asset.kind, library.character_by_id(*id)) {
+            (AssetKind::Shape, Some(Character::Graphic(graphic))) => {
+                let version = match new_asset.tag_code {
+                    TagCode::DefineShape => 1,
+                    TagCode::DefineShape2 => 2,
+                    TagCode::DefineShape3 => 3,
+                    _ => 4,
+                };
+                match reader.read_define_shape(version) {
+                    Ok(shape) => Replacement::Shape(*graphic, shape),
+                    Err(_) => return false,
+                }
+            }
+            (AssetKind::Bitmap, Some(Character::Bitmap(bitmap))) => {
+                let jpeg_tables = library.jpeg_tables();
+                let jpeg_tables = jpeg_tables.as_ref().map(SwfSlice::data);
+                let decoded = decode_bitmap(new_asset, jpeg_tables, &mut reader);
+                match decoded {
+                    Some(decoded)
+                        if decoded.width == u32::from(bitmap.width())
+                            && decoded.height == u32::from(bitmap.height()) =>
+                    {
+                        let rgba = match decoded.data {
+                            BitmapFormat::Rgba(rgba) => rgba,
+                            BitmapFormat::Rgb(rgb) => {
+                                let mut rgba = Vec::with_capacity(rgb.len() / 3 * 4);
+                                for rgb in rgb.chunks_exact(3) {
+                                    rgba.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+                                }
+                                rgba
+                            }
+                        };
+                        Replacement::Bitmap(
+                            bitmap.bitmap_handle(),
+                            decoded.width,
+                            decoded.height,
+                            rgba,
+                        )
+                    }
+                    _ => return false,
+                }
+            }
+            (AssetKind::Sound, Some(Character::Sound(_))) => match reader.read_define_sound() {
+                Ok(sound) => Replacement::Sound(sound),
+                Err(_) => return false,
+            },
+            _ => return false,
+        };
+
+        replacements.push(replacement);
+    }
+
+    for replacement in replacements {
+        match replacement {
+            Replacement::Shape(graphic, shape) => graphic.replace_swf_tag(context, shape),
+            Replacement::Bitmap(handle, width, height, rgba) => {
+                if let Err(e) = context.renderer.update_texture(handle, width, height, rgba) {
+                    log::error!("Unable to reload bitmap: {}", e);
+                }
+            }
+            Replacement::Sound(sound) => match context.audio.register_sound(&sound) {
+                Ok(handle) => context
+                    .library
+                    .library_for_movie_mut(movie.clone())
+                    .replace_sound(sound.id, handle),
+                Err(e) => log::error!("Unable to reload sound ID {}: {}", sound.id, e),
+            },
+        }
+    }
+
+    true
+}
+
+/// Decode the bitmap of a `DefineBits` family tag.
+fn decode_bitmap(
+    asset: &AssetTag<'_>,
+    jpeg_tables: Option<&[u8]>,
+    reader: &mut swf::read::Reader<'_>,
+) -> Option<Bitmap> {
+    let data = asset.data.get(2..)?;
+    let bitmap = match asset.tag_code {
+        TagCode::DefineBits => {
+            decode_define_bits_jpeg(&glue_tables_to_jpeg(data, jpeg_tables), None)
+        }
+        TagCode::DefineBitsJpeg2 => decode_define_bits_jpeg(data, None),
+        TagCode::DefineBitsJpeg3 | TagCode::DefineBitsJpeg4 => {
+            let _id = reader.read_u16().ok()?;
+            let jpeg_len = reader.read_u32().ok()? as usize;
+
+            // DefineBitsJpeg4 has a deblocking filter parameter before the JPEG.
+            let (jpeg_start, deblocking) = if asset.tag_code == TagCode::DefineBitsJpeg4 {
+                (6, reader.read_u16().ok()?)
+            } else {
+                (4, 0)
+            };
+            let jpeg_end = jpeg_start.checked_add(jpeg_len)?;
+            let jpeg_data = data.get(jpeg_start..jpeg_end)?;
+            let alpha_data = data.get(jpeg_end..)?;
+            decode_define_bits_jpeg_4(jpeg_data, Some(alpha_data), deblocking)
+        }
+        TagCode::DefineBitsLossless | TagCode::DefineBitsLossless2 => {
+            let version = if asset.tag_code == TagCode::DefineBitsLossless {
+                1
+            } else {
+                2
+            };
+            let define_bits_lossless = reader.read_define_bits_lossless(version).ok()?;
+            decode_define_bits_lossless(&define_bits_lossless)
+        }
+        _ => return None,
+    };
+
+    bitmap.ok()
+}
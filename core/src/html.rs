@@ -1,5 +1,6 @@
 //! HTML related utilities
 
+pub mod bidi;
 mod dimensions;
 mod iterators;
 mod layout;
@@ -8,7 +9,7 @@ mod text_format;
 pub use dimensions::BoxBounds;
 pub use dimensions::Position;
 pub use dimensions::Size;
-pub use layout::{LayoutBox, LayoutContent};
+pub use layout::{LayoutBox, LayoutContent, LineMetrics};
 pub use text_format::{FormatSpans, TextFormat, TextSpan};
 
 #[cfg(test)]
@@ -0,0 +1,263 @@
+//! Right-to-left text support.
+//!
+//! Flash stores text in logical order and, for Arabic, as unjoined base
+//! letters. Before text can be drawn, each line has to be put into visual
+//! order with the Unicode bidirectional algorithm, and Arabic letters have to
+//! be replaced with the presentation forms that join to their neighbours.
+//! Embedded fonts only contain the glyphs that the author used, so a
+//! presentation form is only used if the font has a glyph for it.
+
+use std::ops::Range;
+use unicode_bidi::{bidi_class, BidiClass, BidiInfo, Level};
+
+/// The direction of a run of text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Whether a string contains any right-to-left characters, and so has to go
+/// through `visual_chars` before being drawn.
+pub fn has_rtl(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(
+            bidi_class(c),
+            BidiClass::R | BidiClass::AL | BidiClass::RLE | BidiClass::RLO | BidiClass::RLI
+        )
+    })
+}
+
+/// Detect the direction of a paragraph from its first strongly directional
+/// character. Paragraphs without any are left-to-right.
+pub fn paragraph_direction(text: &str) -> Direction {
+    match BidiInfo::new(text, None).paragraphs.first() {
+        Some(paragraph) if paragraph.level.is_rtl() => Direction::RightToLeft,
+        _ => Direction::LeftToRight,
+    }
+}
+
+/// Split a line of text into runs that are each drawn in a single direction.
+///
+/// The runs are returned in the order they appear on the line from left to
+/// right, as byte ranges of `text`. `direction` is the direction of the
+/// paragraph the line is in; if it's `None`, it is detected from the line.
+pub fn visual_runs(text: &str, direction: Option<Direction>) -> Vec<(Range<usize>, Direction)> {
+    let level = direction.map(|direction| match direction {
+        Direction::LeftToRight => Level::ltr(),
+        Direction::RightToLeft => Level::rtl(),
+    });
+    let info = BidiInfo::new(text, level);
+
+    let mut visual_runs = Vec::new();
+    for paragraph in &info.paragraphs {
+        let (levels, runs) = info.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            let direction = if levels[run.start].is_rtl() {
+                Direction::RightToLeft
+            } else {
+                Direction::LeftToRight
+            };
+            visual_runs.push((run, direction));
+        }
+    }
+
+    visual_runs
+}
+
+/// Shape and reorder a line of text for display.
+///
+/// This returns the characters to draw from left to right, each with the byte
+/// position in `text` of the character it was made from. `direction` is the
+/// direction of the paragraph the line is in, as in `visual_runs`, and
+/// `has_glyph` tells whether the font can draw a given character.
+pub fn visual_chars(
+    text: &str,
+    direction: Option<Direction>,
+    has_glyph: impl Fn(char) -> bool,
+) -> Vec<(usize, char)> {
+    let shaped = shape_arabic(text, has_glyph);
+
+    let mut chars = Vec::with_capacity(shaped.len());
+    for (run, direction) in visual_runs(text, direction) {
+        let first = shaped
+            .binary_search_by_key(&run.start, |(pos, _)| *pos)
+            .unwrap_or_else(|first| first);
+        let run_chars = shaped[first..]
+            .iter()
+            .take_while(|(pos, _)| *pos < run.end)
+            .copied();
+
+        // Brackets are mirrored in right-to-left runs.
+        match direction {
+            Direction::LeftToRight => chars.extend(run_chars),
+            Direction::RightToLeft => {
+                chars.extend(run_chars.rev().map(|(pos, c)| (pos, mirror(c))));
+            }
+        }
+    }
+
+    chars
+}
+
+/// The glyph for a character drawn right-to-left.
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '\u{AB}' => '\u{BB}',
+        '\u{BB}' => '\u{AB}',
+        c => c,
+    }
+}
+
+/// How an Arabic character joins to its neighbours.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Joining {
+    /// Joins on both sides.
+    Dual,
+
+    /// Only joins to the character before it.
+    Right,
+
+    /// Joins on both sides without changing shape, such as the tatweel.
+    Causing,
+
+    /// Doesn't affect joining, such as vowel marks.
+    Transparent,
+}
+
+/// The joining type of a character, and its isolated presentation form, if
+/// it has any. The final, initial and medial forms follow the isolated form.
+fn arabic_joining(c: char) -> Option<(Joining, Option<u32>)> {
+    let dual = |isolated| Some((Joining::Dual, Some(isolated)));
+    let right = |isolated| Some((Joining::Right, Some(isolated)));
+    match c {
+        '\u{0622}' => right(0xFE81),
+        '\u{0623}' => right(0xFE83),
+        '\u{0624}' => right(0xFE85),
+        '\u{0625}' => right(0xFE87),
+        '\u{0626}' => dual(0xFE89),
+        '\u{0627}' => right(0xFE8D),
+        '\u{0628}' => dual(0xFE8F),
+        '\u{0629}' => right(0xFE93),
+        '\u{062A}' => dual(0xFE95),
+        '\u{062B}' => dual(0xFE99),
+        '\u{062C}' => dual(0xFE9D),
+        '\u{062D}' => dual(0xFEA1),
+        '\u{062E}' => dual(0xFEA5),
+        '\u{062F}' => right(0xFEA9),
+        '\u{0630}' => right(0xFEAB),
+        '\u{0631}' => right(0xFEAD),
+        '\u{0632}' => right(0xFEAF),
+        '\u{0633}' => dual(0xFEB1),
+        '\u{0634}' => dual(0xFEB5),
+        '\u{0635}' => dual(0xFEB9),
+        '\u{0636}' => dual(0xFEBD),
+        '\u{0637}' => dual(0xFEC1),
+        '\u{0638}' => dual(0xFEC5),
+        '\u{0639}' => dual(0xFEC9),
+        '\u{063A}' => dual(0xFECD),
+        '\u{0640}' | '\u{200D}' => Some((Joining::Causing, None)),
+        '\u{0641}' => dual(0xFED1),
+        '\u{0642}' => dual(0xFED5),
+        '\u{0643}' => dual(0xFED9),
+        '\u{0644}' => dual(0xFEDD),
+        '\u{0645}' => dual(0xFEE1),
+        '\u{0646}' => dual(0xFEE5),
+        '\u{0647}' => dual(0xFEE9),
+        '\u{0648}' => right(0xFEED),
+        '\u{0649}' => right(0xFEEF),
+        '\u{064A}' => dual(0xFEF1),
+        c if bidi_class(c) == BidiClass::NSM => Some((Joining::Transparent, None)),
+        _ => None,
+    }
+}
+
+/// The isolated form of the ligature of lam with the given alef, if there is
+/// one. The final form follows it.
+fn lam_alef_ligature(alef: char) -> Option<u32> {
+    match alef {
+        '\u{0622}' => Some(0xFEF5),
+        '\u{0623}' => Some(0xFEF7),
+        '\u{0625}' => Some(0xFEF9),
+        '\u{0627}' => Some(0xFEFB),
+        _ => None,
+    }
+}
+
+/// Replace Arabic letters with the presentation forms that join them to their
+/// neighbours, keeping the characters in logical order.
+fn shape_arabic(text: &str, has_glyph: impl Fn(char) -> bool) -> Vec<(usize, char)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let joining: Vec<Option<Joining>> = chars
+        .iter()
+        .map(|(_, c)| arabic_joining(*c).map(|(joining, _)| joining))
+        .collect();
+
+    // Find the closest character on either side that isn't transparent.
+    let neighbour = |range: &mut dyn Iterator<Item = usize>| {
+        range
+            .find(|j| joining[*j] != Some(Joining::Transparent))
+            .and_then(|j| joining[j])
+    };
+
+    let mut shaped = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        i += 1;
+
+        let (joining_type, isolated) = match arabic_joining(c) {
+            Some((joining_type, Some(isolated))) => (joining_type, isolated),
+            _ => {
+                shaped.push((pos, c));
+                continue;
+            }
+        };
+
+        let joins_previous = matches!(
+            neighbour(&mut (0..i - 1).rev()),
+            Some(Joining::Dual) | Some(Joining::Causing)
+        );
+
+        // Lam followed by alef is drawn as a single ligature.
+        if c == '\u{0644}' {
+            if let Some(&(_, alef)) = chars.get(i) {
+                let ligature = lam_alef_ligature(alef)
+                    .map(|isolated| isolated + u32::from(joins_previous))
+                    .and_then(std::char::from_u32)
+                    .filter(|ligature| has_glyph(*ligature));
+                if let Some(ligature) = ligature {
+                    shaped.push((pos, ligature));
+                    i += 1;
+                    continue;
+                }
+            }
+        }
+
+        let joins_next = joining_type == Joining::Dual
+            && matches!(
+                neighbour(&mut (i..chars.len())),
+                Some(Joining::Dual) | Some(Joining::Right) | Some(Joining::Causing)
+            );
+        let form = match (joins_previous, joins_next) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        };
+        let shaped_char = std::char::from_u32(isolated + form)
+            .filter(|shaped_char| has_glyph(*shaped_char))
+            .unwrap_or(c);
+        shaped.push((pos, shaped_char));
+    }
+
+    shaped
+}
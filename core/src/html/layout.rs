@@ -4,6 +4,7 @@ use crate::collect::CollectWrapper;
 use crate::context::UpdateContext;
 use crate::drawing::Drawing;
 use crate::font::{EvalParameters, Font};
+use crate::html::bidi::{self, Direction};
 use crate::html::dimensions::{BoxBounds, Position, Size};
 use crate::html::text_format::{FormatSpans, TextFormat, TextSpan};
 use crate::shape_utils::DrawCommand;
@@ -11,6 +12,7 @@ use crate::string_utils;
 use crate::tag_utils::SwfMovie;
 use gc_arena::{Collect, GcCell, MutationContext};
 use std::cmp::{max, min};
+use std::ops::Range;
 use std::sync::Arc;
 use swf::Twips;
 
@@ -88,6 +90,12 @@ pub struct LayoutContext<'a, 'gc> {
 
     /// The total width of the text field being laid out.
     max_bounds: Twips,
+
+    /// The vertical metrics of each line that has been laid out so far.
+    lines: Vec<LineMetrics>,
+
+    /// The start of the paragraph that was last laid out, and its direction.
+    paragraph_direction: Option<(usize, Direction)>,
 }
 
 impl<'a, 'gc> LayoutContext<'a, 'gc> {
@@ -105,6 +113,8 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
             current_line: 0,
             current_line_span: Default::default(),
             max_bounds,
+            lines: Vec::new(),
+            paragraph_direction: None,
         }
     }
 
@@ -138,6 +148,82 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         }
     }
 
+    /// Determine the alignment of the current line of text within a paragraph
+    /// running in the given direction.
+    ///
+    /// Right-to-left paragraphs swap left and right alignment.
+    fn directional_alignment(&self, direction: Direction) -> swf::TextAlign {
+        match (self.effective_alignment(), direction) {
+            (align, _) if self.current_line_span.bullet => align,
+            (swf::TextAlign::Left, Direction::RightToLeft) => swf::TextAlign::Right,
+            (swf::TextAlign::Right, Direction::RightToLeft) => swf::TextAlign::Left,
+            (align, _) => align,
+        }
+    }
+
+    /// Determine the direction of the paragraph containing the text at `pos`.
+    fn paragraph_direction(&mut self, pos: usize) -> Direction {
+        let is_break = |c: char| c == '\n' || c == '\r';
+        let start = self.text[..pos].rfind(is_break).map_or(0, |i| i + 1);
+
+        match self.paragraph_direction {
+            Some((cached_start, direction)) if cached_start == start => direction,
+            _ => {
+                let end = self.text[start..]
+                    .find(is_break)
+                    .map_or(self.text.len(), |i| start + i);
+                let direction = bidi::paragraph_direction(&self.text[start..end]);
+
+                self.paragraph_direction = Some((start, direction));
+                direction
+            }
+        }
+    }
+
+    /// Put the text of the current line into visual order.
+    ///
+    /// Text boxes are split wherever the direction of the text changes, so
+    /// that each box is drawn in a single direction, and then placed from
+    /// left to right in the order that the Unicode bidirectional algorithm
+    /// puts them in. Lines of left-to-right text are left alone; tab stops
+    /// are not kept on lines that have to be reordered.
+    fn reorder_line(&mut self, direction: Direction) {
+        let line = self.boxes.get(self.current_line..).unwrap_or_default();
+        let (line_start, line_end, mut x) = match (line.first(), line.last()) {
+            (Some(first), Some(last)) => match (first.text_range(), last.text_range()) {
+                (Some((start, _)), Some((_, end))) => (start, end, first.bounds.offset_x()),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        let line_text = &self.text[line_start..line_end];
+        if direction == Direction::LeftToRight && !bidi::has_rtl(line_text) {
+            return;
+        }
+
+        let line: Vec<LayoutBox<'gc>> = self.boxes.drain(self.current_line..).collect();
+        for (run, run_direction) in bidi::visual_runs(line_text, Some(direction)) {
+            let run = line_start + run.start..line_start + run.end;
+            let mut pieces: Vec<LayoutBox<'gc>> = line
+                .iter()
+                .filter_map(|linebox| linebox.slice_text(run.clone(), run_direction, self.text))
+                .collect();
+            if run_direction == Direction::RightToLeft {
+                pieces.reverse();
+            }
+
+            for mut piece in pieces {
+                let (mut origin, size) = piece.bounds.into_position_and_size();
+                origin.set_x(x);
+                piece.bounds = BoxBounds::from_position_and_size(origin, size);
+                x += size.width();
+
+                self.boxes.push(piece);
+            }
+        }
+    }
+
     /// Construct an underline drawing for the current line of text and add it
     /// to the line.
     fn append_underlines(&mut self) {
@@ -227,6 +313,16 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
             return;
         }
 
+        let direction = match self
+            .boxes
+            .get(self.current_line)
+            .and_then(|b| b.text_range())
+        {
+            Some((start, _)) => self.paragraph_direction(start),
+            None => Direction::LeftToRight,
+        };
+        self.reorder_line(direction);
+
         let mut line_bounds = None;
         let mut box_count: i32 = 0;
         for linebox in self.boxes.get_mut(self.current_line..).unwrap() {
@@ -259,8 +355,9 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
 
         let misalignment =
             self.max_bounds - left_adjustment - right_adjustment - line_bounds.width();
+        let alignment = self.directional_alignment(direction);
         let align_adjustment = max(
-            match self.effective_alignment() {
+            match alignment {
                 swf::TextAlign::Left | swf::TextAlign::Justify => Default::default(),
                 swf::TextAlign::Center => (misalignment) / 2,
                 swf::TextAlign::Right => misalignment,
@@ -268,7 +365,7 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
             Twips::from_pixels(0.0),
         );
         let interim_adjustment = max(
-            if !final_line_of_para && self.effective_alignment() == swf::TextAlign::Justify {
+            if !final_line_of_para && alignment == swf::TextAlign::Justify {
                 misalignment / max(box_count.saturating_sub(1), 1)
             } else {
                 Twips::from_pixels(0.0)
@@ -307,6 +404,11 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
 
         self.append_underlines();
 
+        self.lines.push(LineMetrics {
+            offset: self.cursor.y(),
+            height: self.max_font_size + self.line_leading_adjustment(),
+        });
+
         line_bounds +=
             Position::from((left_adjustment + align_adjustment, Twips::from_pixels(0.0)));
         line_bounds += Size::from((Twips::from_pixels(0.0), font_leading_adjustment));
@@ -522,16 +624,18 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
         (width, offset + self.cursor.x())
     }
 
-    /// Destroy the layout context, returning the newly constructed layout list.
+    /// Destroy the layout context, returning the newly constructed layout
+    /// list, the bounds of the laid-out text, and the metrics of each line.
     fn end_layout(
         mut self,
         context: &mut UpdateContext<'_, 'gc, '_>,
-    ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>) {
+    ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>, Vec<LineMetrics>) {
         self.fixup_line(context, !self.has_line_break, true);
 
         (
             self.boxes,
             self.exterior_bounds.unwrap_or_else(Default::default),
+            self.lines,
         )
     }
 
@@ -540,6 +644,36 @@ impl<'a, 'gc> LayoutContext<'a, 'gc> {
     }
 }
 
+/// The vertical extent of a single line of laid-out text.
+///
+/// Lines are what `EditText` scrolls by, so these are retained after layout
+/// has finished.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+pub struct LineMetrics {
+    /// The distance from the top of the laid-out text to the top of the line.
+    offset: Twips,
+
+    /// The height of the line, including leading.
+    height: Twips,
+}
+
+impl LineMetrics {
+    pub fn offset(&self) -> Twips {
+        self.offset
+    }
+
+    pub fn height(&self) -> Twips {
+        self.height
+    }
+
+    /// The distance from the top of the laid-out text to the bottom of the
+    /// line.
+    pub fn bottom(&self) -> Twips {
+        self.offset + self.height
+    }
+}
+
 /// A `LayoutBox` represents a single content box within a fully laid-out
 /// `EditText`.
 ///
@@ -656,7 +790,8 @@ impl<'gc> LayoutBox<'gc> {
     /// Construct a new layout hierarchy from text spans.
     ///
     /// The returned bounds will include both the text bounds itself, as well
-    /// as left and right margins on any of the lines.
+    /// as left and right margins on any of the lines. The metrics of every
+    /// laid-out line are returned alongside them.
     pub fn lower_from_text_spans(
         fs: &FormatSpans,
         context: &mut UpdateContext<'_, 'gc, '_>,
@@ -664,7 +799,7 @@ impl<'gc> LayoutBox<'gc> {
         bounds: Twips,
         is_word_wrap: bool,
         is_device_font: bool,
-    ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>) {
+    ) -> (Vec<LayoutBox<'gc>>, BoxBounds<Twips>, Vec<LineMetrics>) {
         let mut layout_context = LayoutContext::new(movie, bounds, fs.displayed_text());
 
         for (span_start, _end, span_text, span) in fs.iter_spans() {
@@ -767,6 +902,56 @@ impl<'gc> LayoutBox<'gc> {
         self.bounds
     }
 
+    /// Returns the start and end positions of the text this box contains, if
+    /// it is a text box.
+    fn text_range(&self) -> Option<(usize, usize)> {
+        match &self.content {
+            LayoutContent::Text { start, end, .. } => Some((*start, *end)),
+            _ => None,
+        }
+    }
+
+    /// Construct a text box for the part of this box's text that lies within
+    /// `range`, drawn in the given direction.
+    ///
+    /// The new box is measured and placed at the start of this box. If this
+    /// isn't a text box, or none of its text is within `range`, this returns
+    /// `None`.
+    fn slice_text(&self, range: Range<usize>, direction: Direction, text: &str) -> Option<Self> {
+        match &self.content {
+            LayoutContent::Text {
+                start,
+                end,
+                text_format,
+                font,
+                params,
+                color,
+            } => {
+                let start = max(*start, range.start);
+                let end = min(*end, range.end);
+                if start >= end {
+                    return None;
+                }
+
+                let params = params.with_direction(direction);
+                let size = Size::from(font.measure(text.get(start..end)?, params, false));
+
+                Some(Self {
+                    bounds: BoxBounds::from_position_and_size(self.bounds.origin(), size),
+                    content: LayoutContent::Text {
+                        start,
+                        end,
+                        text_format: text_format.clone(),
+                        font: *font,
+                        params,
+                        color: color.clone(),
+                    },
+                })
+            }
+            _ => None,
+        }
+    }
+
     pub fn content(&self) -> &LayoutContent<'gc> {
         &self.content
     }
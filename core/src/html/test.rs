@@ -1,8 +1,12 @@
 //! Tests for HTML module
 
+use crate::avm1::test_utils::with_avm;
+use crate::html::bidi::{self, Direction};
 use crate::html::dimensions::{BoxBounds, Position, Size};
+use crate::html::layout::{LayoutBox, LayoutContent};
 use crate::html::text_format::{FormatSpans, TextFormat, TextSpan};
-use swf::{Rectangle, Twips};
+use crate::player::{Player, DEVICE_FONT_TAG};
+use swf::{Rectangle, TextAlign, Twips};
 
 #[test]
 fn position_add() {
@@ -863,3 +867,158 @@ fn formatspans_replace_text_degenerate() {
     assert_eq!((0, 1), fs.get_span_boundaries(0, 5));
     assert_eq!((1, 2), fs.get_span_boundaries(5, 9));
 }
+
+/// The characters of a string after reordering, without their positions.
+fn visual_string(text: &str) -> String {
+    bidi::visual_chars(text, None, |_| false)
+        .into_iter()
+        .map(|(_, c)| c)
+        .collect()
+}
+
+#[test]
+fn bidi_paragraph_direction() {
+    assert_eq!(bidi::paragraph_direction("123 abc"), Direction::LeftToRight);
+    assert_eq!(
+        bidi::paragraph_direction("123 \u{5e9}\u{5dc}\u{5d5}\u{5dd} abc"),
+        Direction::RightToLeft
+    );
+    assert_eq!(bidi::paragraph_direction("..."), Direction::LeftToRight);
+}
+
+#[test]
+fn bidi_byte_order_mark_is_neutral() {
+    assert!(!bidi::has_rtl("\u{feff}abc"));
+    assert_eq!(
+        bidi::paragraph_direction("\u{feff}abc"),
+        Direction::LeftToRight
+    );
+    assert_eq!(visual_string("\u{feff}ab"), "\u{feff}ab");
+}
+
+#[test]
+fn bidi_visual_runs() {
+    // Alef and bet in a left-to-right line form a single right-to-left run.
+    assert_eq!(
+        bidi::visual_runs("ab \u{5d0}\u{5d1} cd", None),
+        vec![
+            (0..3, Direction::LeftToRight),
+            (3..7, Direction::RightToLeft),
+            (7..10, Direction::LeftToRight),
+        ]
+    );
+
+    // Left-to-right text in a right-to-left paragraph stays left-to-right.
+    assert_eq!(
+        bidi::visual_runs("ab", Some(Direction::RightToLeft)),
+        vec![(0..2, Direction::LeftToRight)]
+    );
+}
+
+#[test]
+fn bidi_reorder_hebrew() {
+    // Hebrew letters alef, bet, gimel.
+    assert_eq!(
+        visual_string("\u{5d0}\u{5d1}\u{5d2}"),
+        "\u{5d2}\u{5d1}\u{5d0}"
+    );
+    assert_eq!(
+        visual_string("ab \u{5d0}\u{5d1} cd"),
+        "ab \u{5d1}\u{5d0} cd"
+    );
+}
+
+#[test]
+fn bidi_reorder_keeps_numbers_and_positions() {
+    let chars = bidi::visual_chars("\u{5d0} 12 \u{5d1}", None, |_| false);
+    let expected = vec![
+        (6, '\u{5d1}'),
+        (5, ' '),
+        (3, '1'),
+        (4, '2'),
+        (2, ' '),
+        (0, '\u{5d0}'),
+    ];
+    assert_eq!(chars[..], expected[..]);
+}
+
+#[test]
+fn bidi_mirrors_brackets() {
+    assert_eq!(visual_string("\u{5d0}(\u{5d1})"), "(\u{5d1})\u{5d0}");
+}
+
+#[test]
+fn bidi_arabic_shaping() {
+    // Beh, alef, beh: the first beh joins the alef, which doesn't join the
+    // last beh.
+    let shaped: String = bidi::visual_chars("\u{628}\u{627}\u{628}", None, |_| true)
+        .into_iter()
+        .map(|(_, c)| c)
+        .collect();
+    assert_eq!(shaped, "\u{fe8f}\u{fe8e}\u{fe91}");
+
+    // Lam-alef forms a ligature.
+    let shaped = bidi::visual_chars("\u{644}\u{627}", None, |_| true);
+    assert_eq!(shaped, vec![(0, '\u{fefb}')]);
+
+    // Without glyphs for the presentation forms, the letters are unchanged.
+    assert_eq!(visual_string("\u{628}\u{627}"), "\u{627}\u{628}");
+}
+
+/// Lay out a line of text in the device font, returning where each text box
+/// starts in the text and the position of its left edge.
+fn layout_line(text: &str, align: TextAlign) -> Vec<(usize, Twips)> {
+    let mut boxes = Vec::new();
+
+    with_avm(8, |activation, _root| {
+        let context = &mut activation.context;
+        let device_font =
+            Player::load_device_font(context.gc_context, DEVICE_FONT_TAG, context.renderer)
+                .unwrap();
+        context.library.set_device_font(Some(device_font));
+
+        let mut span = TextSpan::with_length_and_format(text.len(), TextFormat::default());
+        span.align = align;
+        let fs = FormatSpans::from_str_and_spans(text, &[span]);
+        let movie = context.swf.clone();
+        let (layout, _, _) = LayoutBox::lower_from_text_spans(
+            &fs,
+            context,
+            movie,
+            Twips::from_pixels(200.0),
+            false,
+            true,
+        );
+
+        for layout_box in layout {
+            if let LayoutContent::Text { start, .. } = layout_box.content() {
+                boxes.push((*start, layout_box.bounds().offset_x()));
+            }
+        }
+
+        Ok(())
+    });
+
+    boxes
+}
+
+#[test]
+fn bidi_layout_reorders_whole_lines() {
+    // Alef, "ab", bet, "cd": the paragraph is right-to-left, so the runs of
+    // the line are drawn from right to left, each in its own direction.
+    let boxes = layout_line("\u{5d0} ab \u{5d1} cd", TextAlign::Left);
+    let starts: Vec<usize> = boxes.iter().map(|(start, _)| *start).collect();
+    assert_eq!(starts, vec![9, 5, 3, 0]);
+    assert!(boxes.windows(2).all(|pair| pair[0].1 < pair[1].1));
+}
+
+#[test]
+fn bidi_layout_aligns_to_paragraph_direction() {
+    // Left and right alignment are swapped in right-to-left paragraphs.
+    assert_eq!(layout_line("ab", TextAlign::Left)[0].1, Twips::zero());
+    assert!(layout_line("\u{5d0}ab", TextAlign::Left)[0].1 > Twips::zero());
+    assert_eq!(
+        layout_line("\u{5d0}ab", TextAlign::Right)[0].1,
+        Twips::zero()
+    );
+}
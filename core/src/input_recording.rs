@@ -0,0 +1,368 @@
+//! Recording the input given to a movie, and replaying it later.
+//!
+//! A recording lists every `PlayerEvent` handled by the player, along with
+//! the number of frames that had run when it arrived. Together with the
+//! `DeterministicMode` the movie ran in, this is enough to play the movie
+//! again exactly as it was played the first time, whether to reproduce a
+//! bug report or to test interactive content.
+//!
+//! Mouse positions are recorded in stage pixels, rather than in pixels of
+//! the viewport, so that a recording can be replayed in a window of any size.
+//!
+//! Recordings are saved as JSON:
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "randomSeed": "0",
+//!     "startDate": 978307200000,
+//!     "events": [
+//!         { "frame": 12, "type": "mouseDown", "x": 100, "y": 50 },
+//!         { "frame": 14, "type": "keyDown", "keyCode": 65 }
+//!     ]
+//! }
+//! ```
+
+use crate::config::DeterministicMode;
+use crate::events::{KeyCode, MouseWheelDelta, PlayerEvent};
+use chrono::{TimeZone, Utc};
+use json::JsonValue;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// The version of the recording format written by `InputRecording::to_json`.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("Unsupported recording version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Invalid recording header")]
+    InvalidHeader,
+
+    #[error("Invalid event #{0}")]
+    InvalidEvent(usize),
+}
+
+/// An input event, and the frame it arrived on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    /// The number of frames that had run when the event arrived.
+    pub frame: u32,
+
+    /// The event, with mouse positions in stage pixels.
+    pub event: PlayerEvent,
+}
+
+/// The input given to a movie while it played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputRecording {
+    /// The settings the movie ran with, which must be used to replay it.
+    pub mode: DeterministicMode,
+
+    /// Every event, in the order they arrived.
+    pub events: Vec<RecordedEvent>,
+}
+
+impl InputRecording {
+    pub fn new(mode: DeterministicMode) -> Self {
+        Self {
+            mode,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let events = self
+            .events
+            .iter()
+            .map(|recorded| {
+                let mut event = event_to_json(&recorded.event);
+                event["frame"] = recorded.frame.into();
+                event
+            })
+            .collect();
+
+        let mut recording = JsonValue::new_object();
+        recording["version"] = FORMAT_VERSION.into();
+        // Seeds don't always fit in a JSON number.
+        recording["randomSeed"] = self.mode.random_seed.to_string().into();
+        recording["startDate"] = self.mode.start_date.timestamp_millis().into();
+        recording["events"] = JsonValue::Array(events);
+        recording.pretty(4)
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, Error> {
+        let recording = json::parse(input).map_err(|e| Error::InvalidJson(e.to_string()))?;
+
+        let version = recording["version"].as_u32().ok_or(Error::InvalidHeader)?;
+        if version != FORMAT_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let random_seed = recording["randomSeed"]
+            .as_str()
+            .and_then(|seed| seed.parse().ok())
+            .ok_or(Error::InvalidHeader)?;
+        let start_date = recording["startDate"]
+            .as_i64()
+            .and_then(|date| Utc.timestamp_millis_opt(date).single())
+            .ok_or(Error::InvalidHeader)?;
+
+        let mut events = Vec::new();
+        for (i, event) in recording["events"].members().enumerate() {
+            let frame = event["frame"].as_u32().ok_or(Error::InvalidEvent(i))?;
+            let event = event_from_json(event).ok_or(Error::InvalidEvent(i))?;
+            events.push(RecordedEvent { frame, event });
+        }
+
+        Ok(Self {
+            mode: DeterministicMode {
+                random_seed,
+                start_date,
+            },
+            events,
+        })
+    }
+}
+
+fn event_to_json(event: &PlayerEvent) -> JsonValue {
+    let mut json = JsonValue::new_object();
+    let event_type = match *event {
+        PlayerEvent::KeyDown { key_code } => {
+            json["keyCode"] = u8::from(key_code).into();
+            "keyDown"
+        }
+        PlayerEvent::KeyUp { key_code } => {
+            json["keyCode"] = u8::from(key_code).into();
+            "keyUp"
+        }
+        PlayerEvent::MouseMove { x, y } => {
+            json["x"] = x.into();
+            json["y"] = y.into();
+            "mouseMove"
+        }
+        PlayerEvent::MouseUp { x, y } => {
+            json["x"] = x.into();
+            json["y"] = y.into();
+            "mouseUp"
+        }
+        PlayerEvent::MouseDown { x, y } => {
+            json["x"] = x.into();
+            json["y"] = y.into();
+            "mouseDown"
+        }
+        PlayerEvent::MouseLeft => "mouseLeft",
+        PlayerEvent::MouseWheel { delta } => {
+            match delta {
+                MouseWheelDelta::Lines(lines) => json["lines"] = lines.into(),
+                MouseWheelDelta::Pixels(pixels) => json["pixels"] = pixels.into(),
+            }
+            "mouseWheel"
+        }
+        PlayerEvent::TextInput { codepoint } => {
+            json["codepoint"] = codepoint.to_string().into();
+            "textInput"
+        }
+    };
+    json["type"] = event_type.into();
+    json
+}
+
+fn event_from_json(event: &JsonValue) -> Option<PlayerEvent> {
+    let key_code = || {
+        event["keyCode"]
+            .as_u8()
+            .and_then(|key_code| KeyCode::try_from(key_code).ok())
+    };
+    let position = || Some((event["x"].as_f64()?, event["y"].as_f64()?));
+
+    Some(match event["type"].as_str()? {
+        "keyDown" => PlayerEvent::KeyDown {
+            key_code: key_code()?,
+        },
+        "keyUp" => PlayerEvent::KeyUp {
+            key_code: key_code()?,
+        },
+        "mouseMove" => {
+            let (x, y) = position()?;
+            PlayerEvent::MouseMove { x, y }
+        }
+        "mouseUp" => {
+            let (x, y) = position()?;
+            PlayerEvent::MouseUp { x, y }
+        }
+        "mouseDown" => {
+            let (x, y) = position()?;
+            PlayerEvent::MouseDown { x, y }
+        }
+        "mouseLeft" => PlayerEvent::MouseLeft,
+        "mouseWheel" => {
+            let delta = if let Some(lines) = event["lines"].as_f64() {
+                MouseWheelDelta::Lines(lines)
+            } else {
+                MouseWheelDelta::Pixels(event["pixels"].as_f64()?)
+            };
+            PlayerEvent::MouseWheel { delta }
+        }
+        "textInput" => {
+            let mut chars = event["codepoint"].as_str()?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(codepoint), None) => PlayerEvent::TextInput { codepoint },
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Records the events handled by a player.
+pub struct InputRecorder {
+    recording: InputRecording,
+    frame: u32,
+}
+
+impl InputRecorder {
+    pub fn new(mode: DeterministicMode) -> Self {
+        Self {
+            recording: InputRecording::new(mode),
+            frame: 0,
+        }
+    }
+
+    pub fn record(&mut self, event: PlayerEvent) {
+        self.recording.events.push(RecordedEvent {
+            frame: self.frame,
+            event,
+        });
+    }
+
+    /// Called after every frame.
+    pub fn next_frame(&mut self) {
+        self.frame = self.frame.saturating_add(1);
+    }
+
+    pub fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// Hands the events of a recording back to a player, on the same frames
+/// they were recorded on.
+pub struct InputReplay {
+    events: VecDeque<RecordedEvent>,
+    frame: u32,
+}
+
+impl InputReplay {
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            events: recording.events.into(),
+            frame: 0,
+        }
+    }
+
+    /// Take the events that arrived before the next frame.
+    pub fn take_events(&mut self) -> Vec<PlayerEvent> {
+        let mut events = Vec::new();
+        while let Some(recorded) = self.events.front() {
+            if recorded.frame > self.frame {
+                break;
+            }
+            events.extend(self.events.pop_front().map(|recorded| recorded.event));
+        }
+        events
+    }
+
+    /// Called after every frame.
+    pub fn next_frame(&mut self) {
+        self.frame = self.frame.saturating_add(1);
+    }
+
+    /// Whether every event was replayed.
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> InputRecording {
+        let mut recorder = InputRecorder::new(DeterministicMode {
+            random_seed: u64::MAX,
+            start_date: Utc.ymd(2005, 6, 7).and_hms_milli(8, 9, 10, 11),
+        });
+        recorder.record(PlayerEvent::MouseMove { x: 10.5, y: -3.0 });
+        recorder.next_frame();
+        recorder.next_frame();
+        recorder.record(PlayerEvent::MouseDown { x: 10.5, y: -3.0 });
+        recorder.record(PlayerEvent::KeyDown {
+            key_code: KeyCode::A,
+        });
+        recorder.record(PlayerEvent::TextInput { codepoint: 'ü' });
+        recorder.next_frame();
+        recorder.record(PlayerEvent::MouseWheel {
+            delta: MouseWheelDelta::Lines(-3.0),
+        });
+        recorder.record(PlayerEvent::MouseWheel {
+            delta: MouseWheelDelta::Pixels(120.0),
+        });
+        recorder.record(PlayerEvent::KeyUp {
+            key_code: KeyCode::A,
+        });
+        recorder.record(PlayerEvent::MouseUp { x: 0.0, y: 0.0 });
+        recorder.record(PlayerEvent::MouseLeft);
+        recorder.finish()
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let recording = recording();
+        assert_eq!(
+            InputRecording::from_json(&recording.to_json()),
+            Ok(recording)
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert_eq!(
+            InputRecording::from_json(r#"{"version": 2}"#),
+            Err(Error::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            InputRecording::from_json(r#"{"version": 1, "startDate": 0}"#),
+            Err(Error::InvalidHeader)
+        );
+        assert_eq!(
+            InputRecording::from_json(
+                r#"{"version": 1, "randomSeed": "1", "startDate": 0,
+                    "events": [{"frame": 0, "type": "keyDown", "keyCode": 1}]}"#
+            ),
+            Err(Error::InvalidEvent(0))
+        );
+    }
+
+    #[test]
+    fn replay_on_recorded_frames() {
+        let mut replay = InputReplay::new(recording());
+        assert_eq!(
+            replay.take_events(),
+            vec![PlayerEvent::MouseMove { x: 10.5, y: -3.0 }]
+        );
+        replay.next_frame();
+        assert_eq!(replay.take_events(), vec![]);
+        replay.next_frame();
+        assert_eq!(replay.take_events().len(), 3);
+        replay.next_frame();
+        assert_eq!(replay.take_events().len(), 5);
+        assert!(replay.is_finished());
+    }
+}
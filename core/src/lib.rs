@@ -28,19 +28,33 @@ mod collect;
 pub mod color_transform;
 pub mod context;
 pub mod context_menu;
+mod debug_overlay;
+mod dirty_region;
 mod drawing;
 mod ecma_conversions;
 pub mod events;
+pub mod execution_position;
+pub mod filters;
+mod flash_math;
+mod flv;
 pub mod focus_tracker;
 mod font;
+mod hot_reload;
 mod html;
+pub mod input_recording;
 mod library;
 pub mod loader;
+pub mod local_connection;
+pub mod matrix;
 mod player;
 mod prelude;
+pub mod security;
 pub mod shape_utils;
+pub mod site_settings;
+pub mod streams;
 pub mod string_utils;
 pub mod tag_utils;
+pub mod test_utils;
 mod transform;
 mod types;
 mod vminterface;
@@ -52,6 +66,7 @@ pub mod external;
 
 pub use chrono;
 pub use context_menu::ContextMenuItem;
+pub use display_object::StageOrientation;
 pub use events::PlayerEvent;
 pub use indexmap;
 pub use player::Player;
@@ -2,13 +2,15 @@ use crate::avm1::function::FunctionObject;
 use crate::avm1::property_map::PropertyMap as Avm1PropertyMap;
 use crate::avm2::{Domain as Avm2Domain, Object as Avm2Object};
 use crate::backend::audio::SoundHandle;
+use crate::backend::render::{BitmapHandle, RenderBackend};
 use crate::character::Character;
 use crate::display_object::{Bitmap, TDisplayObject};
 use crate::font::{Font, FontDescriptor};
 use crate::prelude::*;
-use crate::tag_utils::{SwfMovie, SwfSlice};
+use crate::tag_utils::{SwfMovie, SwfSlice, WeakSwfSlice};
 use crate::vminterface::AvmType;
 use gc_arena::{Collect, Gc, GcCell, MutationContext};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::{Arc, Weak};
 use swf::{CharacterId, TagCode};
@@ -129,13 +131,30 @@ impl<'gc> Avm2ConstructorRegistry<'gc> {
     }
 }
 
+/// The JPEG tables of a movie, after `remove_invalid_jpeg_data`.
+#[derive(Collect)]
+#[collect(require_static)]
+enum JpegTables {
+    /// The tables as they are in the movie.
+    ///
+    /// Libraries are kept for as long as their movie is, so this mustn't keep
+    /// the movie alive itself.
+    Movie(WeakSwfSlice),
+
+    /// A cleaned up copy of the tables, in a movie of its own.
+    Cleaned(SwfSlice),
+}
+
 /// Symbol library for a single given SWF.
 #[derive(Collect)]
 #[collect(no_drop)]
 pub struct MovieLibrary<'gc> {
     characters: HashMap<CharacterId, Character<'gc>>,
     export_characters: Avm1PropertyMap<Character<'gc>>,
-    jpeg_tables: Option<Vec<u8>>,
+
+    /// The last name that each exported character was exported under.
+    export_names: HashMap<CharacterId, String>,
+    jpeg_tables: Option<JpegTables>,
     fonts: HashMap<FontDescriptor, Font<'gc>>,
     avm_type: AvmType,
     avm2_domain: Option<Avm2Domain<'gc>>,
@@ -150,6 +169,7 @@ impl<'gc> MovieLibrary<'gc> {
         MovieLibrary {
             characters: HashMap::new(),
             export_characters: Avm1PropertyMap::new(),
+            export_names: HashMap::new(),
             jpeg_tables: None,
             fonts: HashMap::new(),
             avm_type,
@@ -171,6 +191,25 @@ impl<'gc> MovieLibrary<'gc> {
         }
     }
 
+    /// Replaces a sound that has already been registered, including under any
+    /// export names it has.
+    ///
+    /// Movies can't redefine characters; this is only used to hot-reload
+    /// assets.
+    pub fn replace_sound(&mut self, id: CharacterId, handle: SoundHandle) {
+        if let Some(Character::Sound(old_handle)) = self.characters.get(&id).cloned() {
+            for (_, character) in self.export_characters.iter_mut() {
+                if let Character::Sound(export_handle) = character {
+                    if *export_handle == old_handle {
+                        *export_handle = handle;
+                    }
+                }
+            }
+
+            self.characters.insert(id, Character::Sound(handle));
+        }
+    }
+
     /// Registers an export name for a given character ID.
     /// This character will then be instantiable from AVM1.
     pub fn register_export(
@@ -181,6 +220,7 @@ impl<'gc> MovieLibrary<'gc> {
         if let Some(character) = self.characters.get(&id) {
             self.export_characters
                 .insert(export_name, character.clone(), false);
+            self.export_names.insert(id, export_name.to_string());
             Some(character)
         } else {
             log::warn!(
@@ -192,6 +232,12 @@ impl<'gc> MovieLibrary<'gc> {
         }
     }
 
+    /// The name a character was last exported under, which is used to find
+    /// the class registered for it with `Object.registerClass`.
+    pub fn export_name(&self, id: CharacterId) -> Option<&str> {
+        self.export_names.get(&id).map(String::as_str)
+    }
+
     pub fn contains_character(&self, id: CharacterId) -> bool {
         self.characters.contains_key(&id)
     }
@@ -208,6 +254,12 @@ impl<'gc> MovieLibrary<'gc> {
         self.avm1_constructor_registry
     }
 
+    /// Returns the constructor that `Object.registerClass` has associated
+    /// with the given export name, if any.
+    pub fn avm1_registered_constructor(&self, export_name: &str) -> Option<FunctionObject<'gc>> {
+        self.avm1_constructor_registry?.get(export_name)
+    }
+
     /// Instantiates the library item with the given character ID into a display object.
     /// The object must then be post-instantiated before being used.
     pub fn instantiate_by_id(
@@ -297,7 +349,7 @@ impl<'gc> MovieLibrary<'gc> {
         }
     }
 
-    pub fn set_jpeg_tables(&mut self, data: Vec<u8>) {
+    pub fn set_jpeg_tables(&mut self, data: SwfSlice) {
         if self.jpeg_tables.is_some() {
             // SWF spec says there should only be one JPEGTables tag.
             // TODO: What is the behavior when there are multiples?
@@ -306,15 +358,28 @@ impl<'gc> MovieLibrary<'gc> {
         }
         // Some SWFs have a JPEGTables tag with 0 length; ignore these.
         // (Does this happen when there is only a single DefineBits tag?)
-        self.jpeg_tables = if data.is_empty() {
-            None
-        } else {
-            Some(crate::backend::render::remove_invalid_jpeg_data(&data[..]).to_vec())
+        if data.as_ref().is_empty() {
+            return;
         }
+        self.jpeg_tables = match crate::bitmap::decode::remove_invalid_jpeg_data(data.as_ref()) {
+            Cow::Borrowed(tables) => data
+                .to_subslice(tables)
+                .map(|tables| JpegTables::Movie(tables.downgrade())),
+            Cow::Owned(tables) => Some(JpegTables::Cleaned(SwfSlice::from(Arc::new(
+                data.movie.from_movie_and_subdata(tables, &data.movie),
+            )))),
+        };
     }
 
-    pub fn jpeg_tables(&self) -> Option<&[u8]> {
-        self.jpeg_tables.as_ref().map(|data| &data[..])
+    /// The JPEG tables shared by `DefineBits` tags in this movie.
+    ///
+    /// The tables are kept in the movie's data, unless they had to be cleaned
+    /// up.
+    pub fn jpeg_tables(&self) -> Option<SwfSlice> {
+        match self.jpeg_tables.as_ref()? {
+            JpegTables::Movie(tables) => tables.upgrade(),
+            JpegTables::Cleaned(tables) => Some(tables.clone()),
+        }
     }
 
     /// Check if the current movie's VM type is compatible with running code on
@@ -376,6 +441,10 @@ pub struct Library<'gc> {
     /// A list of the symbols associated with specific AVM2 constructor
     /// prototypes.
     avm2_constructor_registry: Avm2ConstructorRegistry<'gc>,
+
+    /// The bitmaps that each movie has registered with the renderer, so that
+    /// they can be freed once the movie is unloaded.
+    bitmap_owners: Vec<(Weak<SwfMovie>, BitmapHandle)>,
 }
 
 unsafe impl<'gc> gc_arena::Collect for Library<'gc> {
@@ -405,13 +474,69 @@ impl<'gc> Library<'gc> {
                 Avm1ConstructorRegistry::new(true, gc_context),
             ),
             avm2_constructor_registry: Default::default(),
+            bitmap_owners: Vec::new(),
         }
     }
 
+    /// Record that a bitmap has been registered with the renderer on behalf
+    /// of a movie.
+    pub fn register_bitmap_owner(&mut self, movie: &Arc<SwfMovie>, handle: BitmapHandle) {
+        self.bitmap_owners.push((Arc::downgrade(movie), handle));
+    }
+
+    /// Free the bitmaps of every movie that has been unloaded since the last
+    /// call.
+    pub fn release_unloaded_bitmaps(&mut self, renderer: &mut dyn RenderBackend) {
+        self.bitmap_owners.retain(|(movie, handle)| {
+            if movie.strong_count() > 0 {
+                return true;
+            }
+
+            renderer.unregister_bitmap(*handle);
+            false
+        });
+    }
+
     pub fn library_for_movie(&self, movie: Arc<SwfMovie>) -> Option<&MovieLibrary<'gc>> {
         self.movie_libraries.get(&movie)
     }
 
+    /// The library that an export name used by a display object resolves to,
+    /// as in `attachMovie` or `Sound.attachSound`.
+    ///
+    /// The library of the object's own movie is searched first, followed by
+    /// the libraries of the movies it was loaded into, walking up the display
+    /// list, and finally the library of `level0`. This lets movies loaded with
+    /// `loadMovie` use the exports of the movie that loaded them.
+    pub fn library_for_export(
+        &self,
+        object: DisplayObject<'gc>,
+        level0: DisplayObject<'gc>,
+        export_name: &str,
+    ) -> Option<&MovieLibrary<'gc>> {
+        let mut last_movie: Option<Arc<SwfMovie>> = None;
+        let scope = std::iter::successors(Some(object), |object| object.parent())
+            .chain(std::iter::once(level0));
+        for object in scope {
+            let movie = match object.movie() {
+                Some(movie) => movie,
+                None => continue,
+            };
+            // Consecutive objects usually belong to the same movie.
+            if matches!(&last_movie, Some(last_movie) if Arc::ptr_eq(last_movie, &movie)) {
+                continue;
+            }
+
+            if let Some(library) = self.library_for_movie(movie.clone()) {
+                if library.character_by_export_name(export_name).is_some() {
+                    return Some(library);
+                }
+            }
+            last_movie = Some(movie);
+        }
+        None
+    }
+
     pub fn library_for_movie_mut(&mut self, movie: Arc<SwfMovie>) -> &mut MovieLibrary<'gc> {
         if !self.movie_libraries.contains_key(&movie) {
             let slice = SwfSlice::from(movie.clone());
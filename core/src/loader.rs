@@ -3,11 +3,17 @@
 use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::avm1::{Avm1, AvmString, Object, TObject, Value};
 use crate::avm2::Domain as Avm2Domain;
-use crate::backend::navigator::OwnedFuture;
-use crate::context::{ActionQueue, ActionType};
-use crate::display_object::{DisplayObject, MorphShape, TDisplayObject};
+use crate::backend::navigator::{FetchProgress, OwnedFuture, RequestOptions};
+use crate::backend::render::{BitmapInfo, JpegTagFormat};
+use crate::bitmap::decode::{determine_jpeg_tag_format, image_dimensions};
+use crate::context::{ActionQueue, ActionType, UpdateContext};
+use crate::display_object::{
+    Bitmap, DisplayObject, MovieClip, TDisplayObject, TDisplayObjectContainer,
+};
+use crate::events::ClipEvent;
 use crate::player::{Player, NEWEST_PLAYER_VERSION};
-use crate::tag_utils::SwfMovie;
+use crate::streams::NetStream;
+use crate::tag_utils::{MovieInfo, SwfMovie};
 use crate::vminterface::Instantiator;
 use crate::xml::XmlNode;
 use encoding_rs::UTF_8;
@@ -40,12 +46,18 @@ pub enum Error {
     #[error("Non-XML loader spawned as XML loader")]
     NotXmlLoader,
 
+    #[error("Non-NetStream loader spawned as NetStream loader")]
+    NotNetStreamLoader,
+
     #[error("Could not fetch movie {0}")]
     FetchError(String),
 
     #[error("Invalid SWF")]
     InvalidSwf(#[from] crate::tag_utils::Error),
 
+    #[error("Invalid image: {0}")]
+    InvalidImage(String),
+
     #[error("Invalid XML encoding")]
     InvalidXmlEncoding(#[from] FromUtf8Error),
 
@@ -127,7 +139,7 @@ impl<'gc> LoadManager<'gc> {
         fetch: OwnedFuture<Vec<u8>, Error>,
         url: String,
         parameters: Vec<(String, String)>,
-        on_metadata: Box<dyn FnOnce(&swf::Header)>,
+        on_metadata: Box<dyn FnOnce(&MovieInfo)>,
     ) -> OwnedFuture<(), Error> {
         let loader = Loader::RootMovie { self_handle: None };
         let handle = self.add_loader(loader);
@@ -138,30 +150,103 @@ impl<'gc> LoadManager<'gc> {
         loader.root_movie_loader(player, fetch, url, parameters, on_metadata)
     }
 
-    /// Kick off a movie clip load.
+    /// Kick off a movie clip load on behalf of the movie that `requester`
+    /// belongs to.
     ///
     /// Returns the loader's async process, which you will need to spawn.
     pub fn load_movie_into_clip(
-        &mut self,
-        player: Weak<Mutex<Player>>,
+        context: &mut UpdateContext<'_, 'gc, '_>,
         target_clip: DisplayObject<'gc>,
-        fetch: OwnedFuture<Vec<u8>, Error>,
-        url: String,
+        requester: DisplayObject<'gc>,
+        url: &str,
+        options: RequestOptions,
         loader_url: Option<String>,
         target_broadcaster: Option<Object<'gc>>,
     ) -> OwnedFuture<(), Error> {
+        let progress = Arc::new(FetchProgress::default());
+        let fetch = context.fetch_movie(requester, url, options.with_progress(progress.clone()));
         let loader = Loader::Movie {
             self_handle: None,
             target_clip,
             target_broadcaster,
             loader_status: LoaderStatus::Pending,
+            progress,
+            reported_bytes_loaded: 0,
         };
-        let handle = self.add_loader(loader);
+        let handle = context.load_manager.add_loader(loader);
 
-        let loader = self.get_loader_mut(handle).unwrap();
+        let player = context.player.clone().unwrap();
+        let loader = context.load_manager.get_loader_mut(handle).unwrap();
         loader.introduce_loader_handle(handle);
 
-        loader.movie_loader(player, fetch, url, loader_url)
+        loader.movie_loader(player, fetch, url.to_string(), loader_url)
+    }
+
+    /// Retrieve the download progress of a movie that is still being loaded
+    /// into a given clip.
+    ///
+    /// Returns `None` if no movie is currently being downloaded into the
+    /// clip.
+    pub fn movie_load_progress(&self, clip: DisplayObject<'gc>) -> Option<&FetchProgress> {
+        self.0.iter().find_map(|(_, loader)| match loader {
+            Loader::Movie {
+                target_clip,
+                loader_status: LoaderStatus::Pending,
+                progress,
+                ..
+            } if DisplayObject::ptr_eq(*target_clip, clip) => Some(progress.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Fire `onClipEvent(data)` on the clips, and `onLoadProgress` for the
+    /// movies, that received more data since the last frame.
+    ///
+    /// The last events, for the whole movie, are fired once the download
+    /// completes.
+    pub fn report_movie_progress(context: &mut UpdateContext<'_, 'gc, '_>) {
+        let mut events = Vec::new();
+        for (_, loader) in context.load_manager.0.iter_mut() {
+            if let Loader::Movie {
+                target_clip,
+                target_broadcaster,
+                loader_status: LoaderStatus::Pending,
+                progress,
+                reported_bytes_loaded,
+                ..
+            } = loader
+            {
+                let bytes_loaded = progress.bytes_loaded();
+                let bytes_total = progress.bytes_total();
+                let is_complete = bytes_total != 0 && bytes_loaded >= bytes_total;
+                if bytes_loaded > *reported_bytes_loaded && !is_complete {
+                    *reported_bytes_loaded = bytes_loaded;
+                    events.push((*target_clip, *target_broadcaster, bytes_loaded, bytes_total));
+                }
+            }
+        }
+
+        for (clip, broadcaster, bytes_loaded, bytes_total) in events {
+            if let Some(mc) = clip.as_movie_clip() {
+                mc.run_clip_event(context, ClipEvent::Data);
+            }
+
+            if let Some(broadcaster) = broadcaster {
+                Avm1::run_stack_frame_for_method(
+                    clip,
+                    broadcaster,
+                    NEWEST_PLAYER_VERSION,
+                    context,
+                    "broadcastMessage",
+                    &[
+                        "onLoadProgress".into(),
+                        Value::Object(broadcaster),
+                        bytes_loaded.into(),
+                        bytes_total.into(),
+                    ],
+                );
+            }
+        }
     }
 
     /// Indicates that a movie clip has initialized (ran its first frame).
@@ -250,6 +335,27 @@ impl<'gc> LoadManager<'gc> {
 
         loader.xml_loader(player, fetch)
     }
+
+    /// Kick off a load of an FLV file into a `NetStream`.
+    ///
+    /// Returns the loader's async process, which you will need to spawn.
+    pub fn load_net_stream(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        target_stream: NetStream<'gc>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let loader = Loader::NetStream {
+            self_handle: None,
+            target_stream,
+        };
+        let handle = self.add_loader(loader);
+
+        let loader = self.get_loader_mut(handle).unwrap();
+        loader.introduce_loader_handle(handle);
+
+        loader.net_stream_loader(player, fetch)
+    }
 }
 
 impl<'gc> Default for LoadManager<'gc> {
@@ -303,6 +409,14 @@ pub enum Loader<'gc> {
         /// or an error has occurred (in which case we don't care about the
         /// loader anymore).
         loader_status: LoaderStatus,
+
+        /// The download progress of the movie.
+        #[collect(require_static)]
+        progress: Arc<FetchProgress>,
+
+        /// The number of bytes loaded that `onLoadProgress` was last fired
+        /// with.
+        reported_bytes_loaded: usize,
     },
 
     /// Loader that is loading form data into an AVM1 object scope.
@@ -342,6 +456,15 @@ pub enum Loader<'gc> {
         /// The target node whose contents will be replaced with the parsed XML.
         target_node: XmlNode<'gc>,
     },
+
+    /// Loader that is loading an FLV file into a `NetStream`.
+    NetStream {
+        /// The handle to refer to this loader instance.
+        self_handle: Option<Handle>,
+
+        /// The stream that will play the loaded file.
+        target_stream: NetStream<'gc>,
+    },
 }
 
 impl<'gc> Loader<'gc> {
@@ -356,6 +479,7 @@ impl<'gc> Loader<'gc> {
             Loader::Form { self_handle, .. } => *self_handle = Some(handle),
             Loader::LoadVars { self_handle, .. } => *self_handle = Some(handle),
             Loader::Xml { self_handle, .. } => *self_handle = Some(handle),
+            Loader::NetStream { self_handle, .. } => *self_handle = Some(handle),
         }
     }
 
@@ -366,7 +490,7 @@ impl<'gc> Loader<'gc> {
         fetch: OwnedFuture<Vec<u8>, Error>,
         mut url: String,
         parameters: Vec<(String, String)>,
-        on_metadata: Box<dyn FnOnce(&swf::Header)>,
+        on_metadata: Box<dyn FnOnce(&MovieInfo)>,
     ) -> OwnedFuture<(), Error> {
         let _handle = match self {
             Loader::RootMovie { self_handle, .. } => {
@@ -397,7 +521,7 @@ impl<'gc> Loader<'gc> {
             });
 
             if let Ok((_length, mut movie)) = data {
-                on_metadata(movie.header());
+                on_metadata(&movie.info());
                 movie.append_parameters(parameters);
                 player.lock().unwrap().set_root_movie(Arc::new(movie));
                 Ok(())
@@ -472,15 +596,49 @@ impl<'gc> Loader<'gc> {
                 })?;
 
             let data = (fetch.await).and_then(|data| {
-                Ok((
-                    data.len(),
-                    SwfMovie::from_data(&data, Some(url.clone()), loader_url.clone())?,
-                ))
+                let length = data.len();
+                if determine_jpeg_tag_format(&data) == JpegTagFormat::Unknown {
+                    let movie = SwfMovie::from_data(&data, Some(url.clone()), loader_url.clone())?;
+                    return Ok((length, movie, None));
+                }
+
+                // Images are loaded into an empty movie with a single bitmap,
+                // whose dimensions must fit in the bitmap's.
+                let (width, height) =
+                    image_dimensions(&data).map_err(|e| Error::InvalidImage(e.to_string()))?;
+                if width > u16::MAX.into() || height > u16::MAX.into() {
+                    return Err(Error::InvalidImage(format!(
+                        "{}x{} is too large",
+                        width, height
+                    )));
+                }
+                let image = player
+                    .lock()
+                    .expect("Could not lock player!!")
+                    .renderer_mut()
+                    .register_bitmap_jpeg(&data)
+                    .map_err(|e| Error::InvalidImage(e.to_string()))?;
+                let movie = SwfMovie::from_loaded_image(
+                    Some(url.clone()),
+                    loader_url.clone(),
+                    length,
+                    image.width.into(),
+                    image.height.into(),
+                );
+                Ok((length, movie, Some(image)))
             });
-            if let Ok((length, movie)) = data {
+            if let Ok((length, movie, image)) = data {
                 let movie = Arc::new(movie);
                 if replacing_root_movie {
-                    player.lock().unwrap().set_root_movie(movie);
+                    let mut player = player.lock().expect("Could not lock player!!");
+                    player.set_root_movie(movie);
+                    if let Some(image) = image {
+                        player.update(|uc| {
+                            if let Some(root) = uc.stage.root_clip().as_movie_clip() {
+                                Self::add_loaded_image(uc, root, image);
+                            }
+                        });
+                    }
                     return Ok(());
                 }
 
@@ -524,23 +682,24 @@ impl<'gc> Loader<'gc> {
                             .as_movie_clip()
                             .expect("Attempted to load movie into not movie clip");
 
+                        // A movie loaded into `_level0` replaces every level.
+                        if DisplayObject::ptr_eq(clip, uc.stage.root_clip()) {
+                            uc.stage.unload_other_levels(uc);
+                        }
+
                         mc.replace_with_movie(uc.gc_context, Some(movie.clone()));
                         mc.post_instantiation(uc, clip, None, Instantiator::Movie, false);
 
-                        let mut morph_shapes = fnv::FnvHashMap::default();
-                        mc.preload(uc, &mut morph_shapes);
-
-                        // Finalize morph shapes.
-                        for (id, static_data) in morph_shapes {
-                            let morph_shape = MorphShape::new(uc.gc_context, static_data);
-                            uc.library
-                                .library_for_movie_mut(movie.clone())
-                                .register_character(
-                                    id,
-                                    crate::character::Character::MorphShape(morph_shape),
-                                );
+                        mc.preload(uc);
+
+                        if let Some(image) = image {
+                            Self::add_loaded_image(uc, mc, image);
                         }
 
+                        // The clip's `onClipEvent(data)` handlers and `onData`
+                        // method run once the movie is loaded.
+                        mc.run_clip_event(uc, ClipEvent::Data);
+
                         if let Some(broadcaster) = broadcaster {
                             Avm1::run_stack_frame_for_method(
                                 clip,
@@ -607,6 +766,16 @@ impl<'gc> Loader<'gc> {
         })
     }
 
+    /// Show an image loaded with `loadMovie` in the clip it was loaded into.
+    fn add_loaded_image<'gc>(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        clip: MovieClip<'gc>,
+        image: BitmapInfo,
+    ) {
+        let bitmap = Bitmap::new(context, 0, image.handle, image.width, image.height);
+        clip.replace_at_depth(context, bitmap.into(), 1);
+    }
+
     pub fn form_loader(
         &mut self,
         player: Weak<Mutex<Player>>,
@@ -625,30 +794,44 @@ impl<'gc> Loader<'gc> {
             let data = fetch.await?;
 
             // Fire the load handler.
-            player.lock().unwrap().update(|uc| {
-                let loader = uc.load_manager.get_loader(handle);
-                let that = match loader {
-                    Some(&Loader::Form { target_object, .. }) => target_object,
-                    None => return Err(Error::Cancelled),
-                    _ => return Err(Error::NotFormLoader),
-                };
+            player
+                .lock()
+                .unwrap()
+                .update(|uc| Self::form_loaded(uc, handle, &data))
+        })
+    }
 
-                let mut activation = Activation::from_stub(
-                    uc.reborrow(),
-                    ActivationIdentifier::root("[Form Loader]"),
-                );
+    /// Set the variables of a finished form load on its target object, and
+    /// send the target a `data` event if it's a clip.
+    pub(crate) fn form_loaded(
+        uc: &mut UpdateContext<'_, 'gc, '_>,
+        handle: Handle,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let loader = uc.load_manager.get_loader(handle);
+        let that = match loader {
+            Some(&Loader::Form { target_object, .. }) => target_object,
+            None => return Err(Error::Cancelled),
+            _ => return Err(Error::NotFormLoader),
+        };
 
-                for (k, v) in form_urlencoded::parse(&data) {
-                    that.set(
-                        &k,
-                        AvmString::new(activation.context.gc_context, v.into_owned()).into(),
-                        &mut activation,
-                    )?;
-                }
+        let mut activation =
+            Activation::from_stub(uc.reborrow(), ActivationIdentifier::root("[Form Loader]"));
 
-                Ok(())
-            })
-        })
+        for (k, v) in form_urlencoded::parse(data) {
+            that.set(
+                &k,
+                AvmString::new(activation.context.gc_context, v.into_owned()).into(),
+                &mut activation,
+            )?;
+        }
+
+        // Clips that variables are loaded into are sent a `data` event.
+        if let Some(clip) = that.as_display_object().and_then(|o| o.as_movie_clip()) {
+            clip.run_clip_event(&mut activation.context, ClipEvent::Data);
+        }
+
+        Ok(())
     }
 
     /// Creates a future for a LoadVars load call.
@@ -852,4 +1035,41 @@ impl<'gc> Loader<'gc> {
             Ok(())
         })
     }
+
+    /// Creates a future for a `NetStream.play` call.
+    pub fn net_stream_loader(
+        &mut self,
+        player: Weak<Mutex<Player>>,
+        fetch: OwnedFuture<Vec<u8>, Error>,
+    ) -> OwnedFuture<(), Error> {
+        let handle = match self {
+            Loader::NetStream { self_handle, .. } => {
+                self_handle.expect("Loader not self-introduced")
+            }
+            _ => return Box::pin(async { Err(Error::NotNetStreamLoader) }),
+        };
+
+        let player = player
+            .upgrade()
+            .expect("Could not upgrade weak reference to player");
+
+        Box::pin(async move {
+            let data = fetch.await;
+
+            player.lock().unwrap().update(|uc| {
+                let stream = match uc.load_manager.get_loader(handle) {
+                    Some(&Loader::NetStream { target_stream, .. }) => target_stream,
+                    None => return Err(Error::Cancelled),
+                    _ => return Err(Error::NotNetStreamLoader),
+                };
+
+                match data {
+                    Ok(data) => stream.finish_loading(uc, &data),
+                    Err(_) => stream.load_failed(uc),
+                }
+
+                Ok(())
+            })
+        })
+    }
 }
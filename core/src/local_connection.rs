@@ -0,0 +1,244 @@
+//! Messages sent between movies with `LocalConnection`.
+//!
+//! A movie listens on a connection name with `LocalConnection.connect`, and
+//! any other movie can then call methods of that `LocalConnection` by name
+//! with `LocalConnection.send`. Movies in the same player are connected
+//! directly. Movies in other players, such as the other Ruffle instances on a
+//! web page, can be reached through a `LocalConnectionBridge` given to the
+//! player by the frontend.
+
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::{AvmString, Object, TObject};
+use crate::context::{ActionType, UpdateContext};
+use crate::external::Value as ExternalValue;
+use crate::site_settings::SiteSettings;
+use gc_arena::Collect;
+use std::collections::HashMap;
+
+/// A method call sent over a `LocalConnection`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalConnectionMessage {
+    /// The fully qualified name of the connection, e.g. `example.com:name`.
+    pub connection_name: String,
+
+    /// The name of the method to call on the receiving `LocalConnection`.
+    pub method_name: String,
+
+    /// The domain of the movie that sent the message.
+    pub sender_domain: String,
+
+    pub args: Vec<ExternalValue>,
+}
+
+/// Connects the `LocalConnection`s of a player to those of other players.
+pub trait LocalConnectionBridge {
+    /// Start receiving the messages that other players send to a connection
+    /// name. Returns `false` if another player is already connected with it.
+    fn connect(&mut self, connection_name: &str) -> bool;
+
+    /// Stop receiving the messages sent to a connection name.
+    fn close(&mut self, connection_name: &str);
+
+    /// Send a message to the player connected with its connection name.
+    /// Returns `false` if no other player is connected with it.
+    ///
+    /// The message must be delivered later, with
+    /// `Player::receive_local_connection_message`, rather than while the
+    /// sending player is running.
+    fn send(&mut self, message: LocalConnectionMessage) -> bool;
+}
+
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Connection<'gc> {
+    /// The `LocalConnection` receiving the messages.
+    object: Object<'gc>,
+
+    /// The domain of the movie that connected.
+    domain: String,
+}
+
+/// The connection names that the movies of a player are listening on.
+#[derive(Collect, Default)]
+#[collect(no_drop)]
+pub struct LocalConnections<'gc> {
+    connections: HashMap<String, Connection<'gc>>,
+
+    #[collect(require_static)]
+    bridge: Option<Box<dyn LocalConnectionBridge>>,
+}
+
+impl<'gc> LocalConnections<'gc> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn set_bridge(&mut self, bridge: Option<Box<dyn LocalConnectionBridge>>) {
+        self.bridge = bridge;
+    }
+
+    /// The domain of a movie, as used in connection names.
+    pub fn domain_for_url(url: Option<&str>) -> String {
+        SiteSettings::site_for_url(url)
+    }
+
+    /// The fully qualified form of a connection name.
+    ///
+    /// Names starting with an underscore are shared by every domain. Other
+    /// names are prefixed with the domain of the movie, unless they already
+    /// name one. Names are case insensitive.
+    pub fn qualify_name(name: &str, domain: &str, allow_domain: bool) -> String {
+        let name = if name.starts_with('_') || (allow_domain && name.contains(':')) {
+            name.to_string()
+        } else {
+            format!("{}:{}", domain, name)
+        };
+        name.to_lowercase()
+    }
+
+    /// Whether the given object is connected with any name.
+    pub fn is_connected(&self, object: Object<'gc>) -> bool {
+        self.connections
+            .values()
+            .any(|connection| Object::ptr_eq(connection.object, object))
+    }
+
+    /// Start listening on a fully qualified connection name. Returns `false`
+    /// if the name is already in use, by this player or another one.
+    pub fn connect(&mut self, name: String, object: Object<'gc>, domain: String) -> bool {
+        if self.connections.contains_key(&name) {
+            return false;
+        }
+
+        if let Some(bridge) = &mut self.bridge {
+            if !bridge.connect(&name) {
+                return false;
+            }
+        }
+
+        self.connections.insert(name, Connection { object, domain });
+        true
+    }
+
+    /// Stop listening on the connection name of an object, if it has one.
+    pub fn close(&mut self, object: Object<'gc>) {
+        let bridge = &mut self.bridge;
+        self.connections.retain(|name, connection| {
+            if Object::ptr_eq(connection.object, object) {
+                if let Some(bridge) = bridge {
+                    bridge.close(name);
+                }
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Send a message to the `LocalConnection` listening on its connection
+    /// name, in this player or another one. Returns `false` if there is none.
+    pub fn send(activation: &mut Activation<'_, 'gc, '_>, message: LocalConnectionMessage) -> bool {
+        let local_connections = &mut activation.context.local_connections;
+        if local_connections
+            .connections
+            .contains_key(&message.connection_name)
+        {
+            Self::receive(activation, message);
+            true
+        } else if let Some(bridge) = &mut local_connections.bridge {
+            bridge.send(message)
+        } else {
+            false
+        }
+    }
+
+    /// Queue a call of the method named by a message on the `LocalConnection`
+    /// it was sent to.
+    ///
+    /// Messages from other domains are only accepted if the receiving object
+    /// has an `allowDomain` method that returns `true` for the domain.
+    pub fn receive(activation: &mut Activation<'_, 'gc, '_>, message: LocalConnectionMessage) {
+        let (object, domain) = match activation
+            .context
+            .local_connections
+            .connections
+            .get(&message.connection_name)
+        {
+            Some(connection) => (connection.object, connection.domain.clone()),
+            None => return,
+        };
+
+        if message.sender_domain != domain {
+            let sender_domain =
+                AvmString::new(activation.context.gc_context, message.sender_domain.clone());
+            let allowed = object
+                .call_method("allowDomain", &[sender_domain.into()], activation)
+                .map(|allowed| allowed.as_bool(activation.swf_version()))
+                .unwrap_or(false);
+            if !allowed {
+                log::warn!(
+                    "LocalConnection: {} doesn't allow messages from {}",
+                    message.connection_name,
+                    message.sender_domain
+                );
+                return;
+            }
+        }
+
+        // Arguments are passed by value, as if they were serialized.
+        let args = message
+            .args
+            .into_iter()
+            .map(|arg| arg.into_avm1(activation))
+            .collect();
+        let clip = activation.context.stage.root_clip();
+        activation.context.action_queue.queue_actions(
+            clip,
+            ActionType::DynamicMethod {
+                object,
+                name: message.method_name,
+                args,
+            },
+            false,
+        );
+    }
+
+    /// Receive a message sent from another player.
+    pub fn receive_external(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        message: LocalConnectionMessage,
+    ) {
+        let swf_version = context.swf.version();
+        let globals = context.avm1.global_object_cell();
+        let base_clip = context.stage.root_clip();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[LocalConnection]"),
+            swf_version,
+            globals,
+            base_clip,
+        );
+        Self::receive(&mut activation, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualify_name() {
+        assert_eq!(
+            LocalConnections::qualify_name("Game", "example.com", false),
+            "example.com:game"
+        );
+        assert_eq!(
+            LocalConnections::qualify_name("_Shared", "example.com", false),
+            "_shared"
+        );
+        assert_eq!(
+            LocalConnections::qualify_name("example.org:game", "example.com", true),
+            "example.org:game"
+        );
+    }
+}
@@ -0,0 +1,380 @@
+//! Matrix math with the same precision as Flash Player.
+//!
+//! Flash keeps the scale, rotation and skew components of a matrix as 16.16
+//! fixed-point numbers, and its translation as a whole number of twips. Any
+//! time a matrix changes, whether it's placed by a `PlaceObject` tag, changed
+//! by `_xscale` or `_rotation`, or concatenated with the matrices of its
+//! parents, the result is rounded back to that precision. Doing the same math
+//! with unrounded floats makes animations slowly drift away from where Flash
+//! would have put them, so every matrix operation of the display list should
+//! go through these functions.
+//!
+//! Points and translations that end up outside of the range of twips are
+//! clamped to it, rather than wrapping around or panicking.
+//! `checked_transform_point` reports them instead.
+//!
+//! This is only for the matrices that the display list stores and that
+//! scripts can read back. The renderer combines matrices with unrounded
+//! floats, as Flash Player does when it draws.
+
+use crate::prelude::*;
+
+/// The number of fractional bits of a matrix component.
+const FRACTIONAL_BITS: i32 = 16;
+
+/// `2^16`, the value of one in a 16.16 fixed-point number.
+const ONE: f64 = (1 << FRACTIONAL_BITS) as f64;
+
+/// Rounds to the nearest integer, with halves rounded to even, as Flash does
+/// when it converts the result of a matrix operation back to fixed-point.
+fn round_ties_even(value: f64) -> f64 {
+    let rounded = value.round();
+    if (rounded - value).abs() == 0.5 {
+        2.0 * (value / 2.0).round()
+    } else {
+        rounded
+    }
+}
+
+/// Converts a number into 16.16 fixed-point.
+///
+/// NaN becomes 0, and numbers out of range are clamped.
+pub fn to_fixed16(value: f64) -> i32 {
+    if value.is_nan() {
+        0
+    } else {
+        let fixed = round_ties_even(value * ONE);
+        fixed.max(i32::MIN.into()).min(i32::MAX.into()) as i32
+    }
+}
+
+/// Converts a 16.16 fixed-point number into a matrix component.
+pub fn from_fixed16(fixed: i32) -> f32 {
+    (f64::from(fixed) / ONE) as f32
+}
+
+/// Rounds a scale, rotation or skew factor to the precision of a matrix
+/// component.
+///
+/// NaN becomes 0, and factors out of range are clamped, so that bounds and hit
+/// tests never have to deal with NaN.
+pub fn round_component(value: f64) -> f32 {
+    from_fixed16(to_fixed16(value))
+}
+
+/// Rounds a distance in twips to a whole number of twips, or returns `None`
+/// if it's NaN or out of range.
+fn checked_round_twips(twips: f64) -> Option<Twips> {
+    let rounded = round_ties_even(twips);
+    if rounded >= i32::MIN.into() && rounded <= i32::MAX.into() {
+        Some(Twips::new(rounded as i32))
+    } else {
+        None
+    }
+}
+
+/// Rounds a distance in twips to a whole number of twips.
+///
+/// NaN becomes 0, and distances out of range are clamped.
+fn round_twips(twips: f64) -> Twips {
+    checked_round_twips(twips).unwrap_or_else(|| {
+        if twips > 0.0 {
+            Twips::new(i32::MAX)
+        } else if twips < 0.0 {
+            Twips::new(i32::MIN)
+        } else {
+            Twips::zero()
+        }
+    })
+}
+
+/// Rounds every component of a matrix to the precision Flash stores it with.
+pub fn round(matrix: &Matrix) -> Matrix {
+    Matrix {
+        a: round_component(matrix.a.into()),
+        b: round_component(matrix.b.into()),
+        c: round_component(matrix.c.into()),
+        d: round_component(matrix.d.into()),
+        ..*matrix
+    }
+}
+
+/// Builds the scale, rotation and skew components of a matrix, as set by the
+/// `_xscale`, `_yscale` and `_rotation` properties.
+///
+/// `rotation` is the angle of the X axis and `skew` the additional angle of
+/// the Y axis, both in radians. Returns `(a, b, c, d)`.
+pub fn compose(scale_x: f64, scale_y: f64, rotation: f64, skew: f64) -> (f32, f32, f32, f32) {
+    let (sin_x, cos_x) = rotation.sin_cos();
+    let (sin_y, cos_y) = (rotation + skew).sin_cos();
+    (
+        round_component(scale_x * cos_x),
+        round_component(scale_x * sin_x),
+        round_component(scale_y * -sin_y),
+        round_component(scale_y * cos_y),
+    )
+}
+
+/// Concatenates two matrices, so that the result transforms by `rhs` and then
+/// by `lhs`.
+pub fn multiply(lhs: &Matrix, rhs: &Matrix) -> Matrix {
+    let (la, lb, lc, ld) = (
+        f64::from(lhs.a),
+        f64::from(lhs.b),
+        f64::from(lhs.c),
+        f64::from(lhs.d),
+    );
+    let (ra, rb, rc, rd) = (
+        f64::from(rhs.a),
+        f64::from(rhs.b),
+        f64::from(rhs.c),
+        f64::from(rhs.d),
+    );
+    let (tx, ty) = transform_point(lhs, (rhs.tx, rhs.ty));
+    Matrix {
+        a: round_component(la * ra + lc * rb),
+        b: round_component(lb * ra + ld * rb),
+        c: round_component(la * rc + lc * rd),
+        d: round_component(lb * rc + ld * rd),
+        tx,
+        ty,
+    }
+}
+
+/// Transforms a point by a matrix, rounding the result to whole twips.
+///
+/// Points that land outside of the range of twips are clamped to it.
+pub fn transform_point(matrix: &Matrix, (x, y): (Twips, Twips)) -> (Twips, Twips) {
+    let (x, y) = (f64::from(x.get()), f64::from(y.get()));
+    let out_x = round_twips(f64::from(matrix.a) * x + f64::from(matrix.c) * y);
+    let out_y = round_twips(f64::from(matrix.b) * x + f64::from(matrix.d) * y);
+    (
+        Twips::new(out_x.get().saturating_add(matrix.tx.get())),
+        Twips::new(out_y.get().saturating_add(matrix.ty.get())),
+    )
+}
+
+/// Transforms a point by a matrix, rounding the result to whole twips, or
+/// returns `None` if it lands outside of the range of twips.
+pub fn checked_transform_point(matrix: &Matrix, (x, y): (Twips, Twips)) -> Option<(Twips, Twips)> {
+    let (x, y) = (f64::from(x.get()), f64::from(y.get()));
+    let out_x = checked_round_twips(f64::from(matrix.a) * x + f64::from(matrix.c) * y)?;
+    let out_y = checked_round_twips(f64::from(matrix.b) * x + f64::from(matrix.d) * y)?;
+    Some((
+        Twips::new(out_x.get().checked_add(matrix.tx.get())?),
+        Twips::new(out_y.get().checked_add(matrix.ty.get())?),
+    ))
+}
+
+/// The inverse of a matrix.
+///
+/// A matrix that can't be inverted, such as one with a scale of 0, has an
+/// inverse of all zeros, so that transforming by it collapses everything onto
+/// a single point rather than producing NaN.
+pub fn invert(matrix: &Matrix) -> Matrix {
+    let (a, b, c, d) = (
+        f64::from(matrix.a),
+        f64::from(matrix.b),
+        f64::from(matrix.c),
+        f64::from(matrix.d),
+    );
+    let det = a * d - b * c;
+    if det == 0.0 || !det.is_finite() {
+        return Matrix {
+            a: 0.0,
+            b: 0.0,
+            c: 0.0,
+            d: 0.0,
+            tx: Twips::zero(),
+            ty: Twips::zero(),
+        };
+    }
+
+    let (tx, ty) = (f64::from(matrix.tx.get()), f64::from(matrix.ty.get()));
+    Matrix {
+        a: round_component(d / det),
+        b: round_component(-b / det),
+        c: round_component(-c / det),
+        d: round_component(a / det),
+        tx: round_twips((c * ty - d * tx) / det),
+        ty: round_twips((b * tx - a * ty) / det),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    /// How many random cases each property is checked with.
+    const CASES: usize = 10000;
+
+    /// The largest error of a component rounded to 16.16.
+    const COMPONENT_EPSILON: f64 = 0.5 / ONE;
+
+    fn random_component(rng: &mut SmallRng) -> f32 {
+        from_fixed16(rng.gen_range(-(8 << FRACTIONAL_BITS)..=(8 << FRACTIONAL_BITS)))
+    }
+
+    fn random_matrix(rng: &mut SmallRng) -> Matrix {
+        Matrix {
+            a: random_component(rng),
+            b: random_component(rng),
+            c: random_component(rng),
+            d: random_component(rng),
+            tx: Twips::new(rng.gen_range(-100_000..=100_000)),
+            ty: Twips::new(rng.gen_range(-100_000..=100_000)),
+        }
+    }
+
+    fn random_point(rng: &mut SmallRng) -> (Twips, Twips) {
+        (
+            Twips::new(rng.gen_range(-100_000..=100_000)),
+            Twips::new(rng.gen_range(-100_000..=100_000)),
+        )
+    }
+
+    #[test]
+    fn flash_player_values() {
+        // `_xscale = 33.333` gives `transform.matrix.a == 0.3333282470703125`.
+        assert_eq!(f64::from(round_component(0.33333)), 0.3333282470703125);
+
+        // `_rotation = 45` gives `a`, `b` and `d` of 0.7071075439453125 and a
+        // `c` of -0.7071075439453125.
+        let (a, b, c, d) = compose(1.0, 1.0, 45f64.to_radians(), 0.0);
+        assert_eq!(f64::from(a), 0.7071075439453125);
+        assert_eq!(f64::from(b), 0.7071075439453125);
+        assert_eq!(f64::from(c), -0.7071075439453125);
+        assert_eq!(f64::from(d), 0.7071075439453125);
+
+        // `_xscale = -50` on top of `_rotation = 90` gives `a == 0` exactly,
+        // rather than the tiny `cos(pi / 2) * -0.5`.
+        let (a, b, _, _) = compose(-0.5, 1.0, 90f64.to_radians(), 0.0);
+        assert_eq!(f64::from(a), 0.0);
+        assert_eq!(f64::from(b), -0.5);
+    }
+
+    #[test]
+    fn components_round_to_fixed16() {
+        assert_eq!(to_fixed16(0.33333), 21845);
+        assert_eq!(to_fixed16(1.0), 65536);
+        assert_eq!(to_fixed16(-1.0), -65536);
+        assert_eq!(to_fixed16(f64::NAN), 0);
+        assert_eq!(to_fixed16(1e10), i32::MAX);
+        assert_eq!(to_fixed16(-1e10), i32::MIN);
+
+        // Halves round to even.
+        assert_eq!(to_fixed16(0.5 / ONE), 0);
+        assert_eq!(to_fixed16(1.5 / ONE), 2);
+        assert_eq!(to_fixed16(-1.5 / ONE), -2);
+    }
+
+    #[test]
+    fn compose_rotation() {
+        let (a, b, c, d) = compose(1.0, 1.0, std::f64::consts::FRAC_PI_4, 0.0);
+        assert_eq!(a, from_fixed16(46341));
+        assert_eq!(b, from_fixed16(46341));
+        assert_eq!(c, from_fixed16(-46341));
+        assert_eq!(d, from_fixed16(46341));
+
+        let (a, b, c, d) = compose(2.0, 0.5, std::f64::consts::PI, 0.0);
+        assert_eq!((a, b, c, d), (-2.0, 0.0, 0.0, -0.5));
+    }
+
+    #[test]
+    fn rounding_is_idempotent() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..CASES {
+            let value = rng.gen_range(-8.0..8.0);
+            let rounded = round_component(value);
+            assert!((f64::from(rounded) - value).abs() <= COMPONENT_EPSILON);
+            assert_eq!(round_component(rounded.into()), rounded);
+        }
+    }
+
+    #[test]
+    fn multiply_by_identity() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        for _ in 0..CASES {
+            let matrix = random_matrix(&mut rng);
+            assert_eq!(multiply(&Matrix::identity(), &matrix), matrix);
+            assert_eq!(multiply(&matrix, &Matrix::identity()), matrix);
+        }
+    }
+
+    #[test]
+    fn multiply_matches_transforming_twice() {
+        let mut rng = SmallRng::seed_from_u64(2);
+        for _ in 0..CASES {
+            let (lhs, rhs) = (random_matrix(&mut rng), random_matrix(&mut rng));
+            let point = random_point(&mut rng);
+            let (x, y) = transform_point(&multiply(&lhs, &rhs), point);
+            let (expected_x, expected_y) = transform_point(&lhs, transform_point(&rhs, point));
+
+            // Each rounding step loses up to half a twip, which the scale of
+            // `lhs` magnifies.
+            let tolerance = 2 + 8 * 2;
+            assert!((x.get() - expected_x.get()).abs() <= tolerance);
+            assert!((y.get() - expected_y.get()).abs() <= tolerance);
+        }
+    }
+
+    #[test]
+    fn invert_round_trips() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        for _ in 0..CASES {
+            let matrix = Matrix {
+                a: rng.gen_range(0.5..4.0),
+                b: rng.gen_range(-0.25..0.25),
+                c: rng.gen_range(-0.25..0.25),
+                d: rng.gen_range(0.5..4.0),
+                ..random_matrix(&mut rng)
+            };
+            let matrix = round(&matrix);
+            let point = random_point(&mut rng);
+            let (x, y) = transform_point(&invert(&matrix), transform_point(&matrix, point));
+
+            // The components of the inverse are off by up to half of the
+            // last fixed-point bit, which adds up over hundreds of thousands
+            // of twips.
+            let tolerance = 16;
+            assert!((x.get() - point.0.get()).abs() <= tolerance);
+            assert!((y.get() - point.1.get()).abs() <= tolerance);
+        }
+    }
+
+    #[test]
+    fn invert_singular() {
+        let inverse = invert(&Matrix::scale(0.0, 1.0));
+        assert_eq!(
+            transform_point(&inverse, (Twips::new(100), Twips::new(100))),
+            (Twips::zero(), Twips::zero())
+        );
+    }
+
+    #[test]
+    fn translation_overflow_is_clamped_or_reported() {
+        let matrix = Matrix::translate(Twips::new(i32::MAX), Twips::new(i32::MIN));
+        let point = (Twips::new(1), Twips::new(-1));
+        assert_eq!(
+            transform_point(&matrix, point),
+            (Twips::new(i32::MAX), Twips::new(i32::MIN))
+        );
+        assert_eq!(checked_transform_point(&matrix, point), None);
+
+        let matrix = Matrix::scale(4.0, 1.0);
+        let point = (Twips::new(i32::MAX / 2), Twips::new(1));
+        assert_eq!(
+            transform_point(&matrix, point),
+            (Twips::new(i32::MAX), Twips::new(1))
+        );
+        assert_eq!(checked_transform_point(&matrix, point), None);
+
+        let point = (Twips::new(100), Twips::new(-100));
+        assert_eq!(
+            checked_transform_point(&matrix, point),
+            Some((Twips::new(400), Twips::new(-100)))
+        );
+    }
+}
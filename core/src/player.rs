@@ -10,32 +10,38 @@ use crate::backend::{
     locale::LocaleBackend,
     log::LogBackend,
     navigator::{NavigatorBackend, RequestOptions},
-    render::RenderBackend,
+    render::{Bitmap, RenderBackend},
     storage::StorageBackend,
-    ui::{MouseCursor, UiBackend},
+    ui::{MouseCursor, MovieLifecycleEvent, UiBackend},
     video::VideoBackend,
 };
-use crate::config::Letterbox;
-use crate::context::{ActionQueue, ActionType, RenderContext, UpdateContext};
+use crate::config::{CrossDomainMode, DeterministicMode, Letterbox, LoopPolicy, StageQuality};
+use crate::context::{ActionQueue, ActionType, RenderContext, UpdateContext, VirtualClock};
 use crate::context_menu::{ContextMenuCallback, ContextMenuItem, ContextMenuState};
-use crate::display_object::{EditText, MorphShape, MovieClip, Stage};
+use crate::debug_overlay::DebugOverlay;
+use crate::dirty_region::{DirtyRegion, DirtyRegionTracker};
+use crate::display_object::{EditText, MovieClip, Stage, StageOrientation, Video as VideoPlayer};
 use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult, KeyCode, PlayerEvent};
 use crate::external::Value as ExternalValue;
 use crate::external::{ExternalInterface, ExternalInterfaceProvider};
 use crate::focus_tracker::FocusTracker;
+use crate::input_recording::{InputRecorder, InputRecording, InputReplay};
 use crate::library::Library;
 use crate::loader::LoadManager;
+use crate::local_connection::{LocalConnectionBridge, LocalConnectionMessage, LocalConnections};
 use crate::prelude::*;
-use crate::tag_utils::SwfMovie;
+use crate::security::{SandboxType, Security};
+use crate::site_settings::SiteSettings;
+use crate::streams::StreamManager;
+use crate::tag_utils::{MovieInfo, SwfMovie};
 use crate::transform::TransformStack;
 use crate::vminterface::{AvmType, Instantiator};
-use gc_arena::{make_arena, ArenaParameters, Collect, GcCell};
+use gc_arena::{make_arena, ArenaParameters, Collect, GcCell, MutationContext};
 use instant::Instant;
 use log::info;
 use rand::{rngs::SmallRng, SeedableRng};
-use std::collections::{HashMap, VecDeque};
-use std::convert::TryFrom;
-use std::ops::DerefMut;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
 
@@ -45,6 +51,31 @@ pub static DEVICE_FONT_TAG: &[u8] = include_bytes!("../assets/noto-sans-definefo
 /// `player_version`.
 pub const NEWEST_PLAYER_VERSION: u8 = 32;
 
+/// The frame rate of a movie before one is loaded, in FPS.
+const DEFAULT_FRAME_RATE: f64 = 12.0;
+
+/// The range of frame rates that Flash Player will run a movie at, in FPS.
+const MIN_FRAME_RATE: f64 = 0.01;
+const MAX_FRAME_RATE: f64 = 1000.0;
+
+/// The most SWF frames that will be run in a single tick to catch up with
+/// the frame rate.
+const MAX_FRAMES_PER_TICK: u32 = 5;
+
+/// The longest time between redraws while objects are drawn moving between
+/// frames, in milliseconds.
+const MAX_INTERPOLATED_REDRAW_TIME: f64 = 1000.0 / 240.0;
+
+/// Clamp a frame rate to the range that Flash Player supports, falling back
+/// to `default` if it isn't a number.
+pub fn clamp_frame_rate(frame_rate: f64, default: f64) -> f64 {
+    if frame_rate.is_nan() {
+        default
+    } else {
+        frame_rate.max(MIN_FRAME_RATE).min(MAX_FRAME_RATE)
+    }
+}
+
 #[derive(Collect)]
 #[collect(no_drop)]
 struct GcRoot<'gc>(GcCell<'gc, GcRootData<'gc>>);
@@ -82,9 +113,18 @@ struct GcRootData<'gc> {
     /// Text fields with unbound variable bindings.
     unbound_text_fields: Vec<EditText<'gc>>,
 
+    /// Videos with frames waiting to be decoded before the next render.
+    queued_videos: Vec<VideoPlayer<'gc>>,
+
     /// Timed callbacks created with `setInterval`/`setTimeout`.
     timers: Timers<'gc>,
 
+    /// Streams that are being played with `NetStream`.
+    stream_manager: StreamManager<'gc>,
+
+    /// The connection names that `LocalConnection`s are listening on.
+    local_connections: LocalConnections<'gc>,
+
     current_context_menu: Option<ContextMenuState<'gc>>,
 
     /// External interface for (for example) JavaScript <-> ActionScript interaction
@@ -113,7 +153,10 @@ impl<'gc> GcRootData<'gc> {
         &mut LoadManager<'gc>,
         &mut HashMap<String, Object<'gc>>,
         &mut Vec<EditText<'gc>>,
+        &mut Vec<VideoPlayer<'gc>>,
         &mut Timers<'gc>,
+        &mut StreamManager<'gc>,
+        &mut LocalConnections<'gc>,
         &mut Option<ContextMenuState<'gc>>,
         &mut ExternalInterface<'gc>,
         &mut AudioManager<'gc>,
@@ -128,7 +171,10 @@ impl<'gc> GcRootData<'gc> {
             &mut self.load_manager,
             &mut self.shared_objects,
             &mut self.unbound_text_fields,
+            &mut self.queued_videos,
             &mut self.timers,
+            &mut self.stream_manager,
+            &mut self.local_connections,
             &mut self.current_context_menu,
             &mut self.external_interface,
             &mut self.audio_manager,
@@ -165,9 +211,23 @@ pub struct Player {
 
     warn_on_unsupported_content: bool,
 
+    /// Whether to draw the debug overlay on top of the stage.
+    show_debug_overlay: bool,
+
+    /// Whether objects moved by scripts are drawn moving smoothly between
+    /// frames, instead of jumping once per frame. This takes precedence over
+    /// the choice saved for the site.
+    frame_interpolation: Option<bool>,
+
+    /// Whether any object is being drawn moving between frames.
+    is_interpolating: bool,
+
     is_playing: bool,
     needs_render: bool,
 
+    /// Finds the parts of the stage that have to be redrawn in each frame.
+    dirty_regions: DirtyRegionTracker,
+
     renderer: Renderer,
     audio: Audio,
     navigator: Navigator,
@@ -192,17 +252,27 @@ pub struct Player {
     frame_accumulator: f64,
     recent_run_frame_timings: VecDeque<f64>,
 
+    /// Whether the last tick missed its frame deadline, even after catching up.
+    /// Its render is skipped to give the next tick more time to catch up.
+    skip_render: bool,
+
     /// Faked time passage for fooling hand-written busy-loop FPS limiters.
     time_offset: u32,
 
     mouse_pos: (Twips, Twips),
     is_mouse_down: bool,
 
+    /// Whether the mouse is over the object it was pressed on, while the
+    /// mouse is down.
+    is_mouse_over_pressed_object: bool,
+
     /// The current mouse cursor icon.
     mouse_cursor: MouseCursor,
 
     system: SystemProperties,
 
+    security: Security,
+
     /// The current instance ID. Used to generate default `instanceN` names.
     instance_counter: i32,
 
@@ -223,6 +293,33 @@ pub struct Player {
     /// The current frame of the main timeline, if available.
     /// The first frame is frame 1.
     current_frame: Option<u16>,
+
+    /// The lifecycle events of the root movie that have already been reported
+    /// to the UI backend.
+    reported_lifecycle_events: Vec<MovieLifecycleEvent>,
+
+    /// The site the root movie was loaded from.
+    site: String,
+
+    /// The settings of `site`.
+    site_settings: SiteSettings,
+
+    /// The quality the stage starts with, as given by the `quality` embed
+    /// parameter. This takes precedence over the quality of the site.
+    initial_quality: Option<StageQuality>,
+
+    /// The settings of deterministic mode, if the player is running in it.
+    deterministic_mode: Option<DeterministicMode>,
+
+    /// The clock that movies read the time from, if running in deterministic
+    /// mode.
+    virtual_clock: Option<VirtualClock>,
+
+    /// Records the input events handled by the player, if enabled.
+    input_recorder: Option<InputRecorder>,
+
+    /// The recorded input events being replayed, if any.
+    input_replay: Option<InputReplay>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -241,7 +338,7 @@ impl Player {
         let fake_movie = Arc::new(SwfMovie::empty(NEWEST_PLAYER_VERSION));
         let movie_width = 550;
         let movie_height = 400;
-        let frame_rate = 12.0;
+        let frame_rate = DEFAULT_FRAME_RATE;
         // Disable script timeout in debug builds by default.
         let max_execution_duration = if cfg!(debug_assertions) { u64::MAX } else { 15 };
 
@@ -252,8 +349,13 @@ impl Player {
 
             warn_on_unsupported_content: true,
 
+            show_debug_overlay: false,
+            frame_interpolation: None,
+            is_interpolating: false,
+
             is_playing: false,
             needs_render: true,
+            dirty_regions: DirtyRegionTracker::new(),
 
             transform_stack: TransformStack::new(),
 
@@ -273,7 +375,10 @@ impl Player {
                         load_manager: LoadManager::new(),
                         shared_objects: HashMap::new(),
                         unbound_text_fields: Vec::new(),
+                        queued_videos: Vec::new(),
                         timers: Timers::new(),
+                        stream_manager: StreamManager::new(),
+                        local_connections: LocalConnections::new(),
                         current_context_menu: None,
                         external_interface: ExternalInterface::new(),
                         focus_tracker: FocusTracker::new(gc_context),
@@ -285,10 +390,12 @@ impl Player {
             frame_rate,
             frame_accumulator: 0.0,
             recent_run_frame_timings: VecDeque::with_capacity(10),
+            skip_render: false,
             time_offset: 0,
 
             mouse_pos: (Twips::zero(), Twips::zero()),
             is_mouse_down: false,
+            is_mouse_over_pressed_object: false,
             mouse_cursor: MouseCursor::Arrow,
 
             renderer,
@@ -300,11 +407,20 @@ impl Player {
             video,
             self_reference: None,
             system: SystemProperties::default(),
+            security: Security::new(),
             instance_counter: 0,
             time_til_next_timer: None,
             storage,
             max_execution_duration: Duration::from_secs(max_execution_duration),
             current_frame: None,
+            reported_lifecycle_events: Vec::new(),
+            site: SiteSettings::site_for_url(None),
+            site_settings: SiteSettings::default(),
+            initial_quality: None,
+            deterministic_mode: None,
+            virtual_clock: None,
+            input_recorder: None,
+            input_replay: None,
         };
 
         player.mutate_with_update_context(|context| {
@@ -346,7 +462,7 @@ impl Player {
         &mut self,
         movie_url: &str,
         parameters: Vec<(String, String)>,
-        on_metadata: Box<dyn FnOnce(&swf::Header)>,
+        on_metadata: Box<dyn FnOnce(&MovieInfo)>,
     ) {
         self.mutate_with_update_context(|context| {
             let fetch = context.navigator.fetch(movie_url, RequestOptions::get());
@@ -375,10 +491,16 @@ impl Player {
             movie.header().stage_size.y_max
         );
 
-        self.frame_rate = movie.header().frame_rate.into();
+        self.frame_rate = clamp_frame_rate(movie.header().frame_rate.into(), DEFAULT_FRAME_RATE);
+        self.site = SiteSettings::site_for_url(movie.url());
+        self.site_settings = SiteSettings::load(self.storage.deref(), &self.site);
+        self.is_interpolating = false;
         self.swf = movie;
         self.instance_counter = 0;
 
+        self.reported_lifecycle_events.clear();
+        self.report_lifecycle_event(MovieLifecycleEvent::HeaderParsed);
+
         self.mutate_with_update_context(|context| {
             context.stage.set_movie_size(
                 context.gc_context,
@@ -412,6 +534,8 @@ impl Player {
 
             root.post_instantiation(context, root, flashvars, Instantiator::Movie, false);
             root.set_default_root_name(context);
+            // A new root movie replaces every level, not just `_level0`.
+            context.stage.unload_other_levels(context);
             context.stage.replace_at_depth(context, root, 0);
 
             // Load and parse the device font.
@@ -445,10 +569,37 @@ impl Player {
             stage.build_matrices(&mut activation.context);
         });
 
+        self.apply_site_settings();
+        let quality = self.initial_quality.unwrap_or(self.site_settings.quality);
+        self.mutate_with_update_context(|context| context.stage.set_quality(context, quality));
         self.preload();
         self.audio.set_frame_rate(self.frame_rate);
     }
 
+    /// Swap the assets of a new copy of the root movie into the running one.
+    ///
+    /// This is meant for development: if the two movies only differ in their
+    /// shapes, bitmaps and sounds, those are replaced in place and the movie
+    /// keeps playing without losing its timeline or script state.
+    ///
+    /// Returns `false` if anything else changed, in which case nothing is
+    /// replaced and the new movie has to be loaded from scratch.
+    pub fn reload_root_movie_assets(&mut self, movie: &SwfMovie) -> bool {
+        let reloaded = self.mutate_with_update_context(|context| {
+            let root_movie = context.swf.clone();
+            crate::hot_reload::reload_assets(context, root_movie, movie)
+        });
+
+        if reloaded {
+            // Replaced bitmaps and shapes aren't noticed by the dirty region
+            // tracker.
+            self.dirty_regions.invalidate();
+            self.needs_render = true;
+        }
+
+        reloaded
+    }
+
     /// Get rough estimate of the max # of times we can update the frame.
     ///
     /// In some cases, we might want to update several times in a row.
@@ -463,10 +614,8 @@ impl Player {
     /// This logic is far from perfect, as it doesn't take into account
     /// that things like rendering also take time. But for now it's good enough.
     fn max_frames_per_tick(&self) -> u32 {
-        const MAX_FRAMES_PER_TICK: u32 = 5;
-
         if self.recent_run_frame_timings.is_empty() {
-            5
+            MAX_FRAMES_PER_TICK
         } else {
             let frame_time = 1000.0 / self.frame_rate;
             let average_run_frame_time = self.recent_run_frame_timings.iter().sum::<f64>()
@@ -491,6 +640,8 @@ impl Player {
             return;
         }
 
+        self.report_lifecycle_event(MovieLifecycleEvent::PreloadComplete);
+
         if self.is_playing() {
             self.frame_accumulator += dt;
             let frame_time = 1000.0 / self.frame_rate;
@@ -500,7 +651,14 @@ impl Player {
 
             while frame < max_frames_per_tick && self.frame_accumulator >= frame_time {
                 let timer = Instant::now();
+                self.audio.advance_frame_clock(frame_time);
                 self.run_frame();
+                // Timers see the same virtual time as `getTimer` in
+                // deterministic mode, so they fire between the same frames on
+                // every run.
+                if self.virtual_clock.is_some() {
+                    self.update_timers(frame_time);
+                }
                 let elapsed = timer.elapsed().as_millis() as f64;
 
                 self.add_frame_timing(elapsed);
@@ -525,14 +683,31 @@ impl Player {
             // so timer callbacks won't get cancelled/delayed.
             self.time_offset = 0;
 
-            // Sanity: If we had too many frames to tick, just reset the accumulator
-            // to prevent running at turbo speed.
-            if self.frame_accumulator >= frame_time {
-                self.frame_accumulator = 0.0;
+            // Sanity: If we had too many frames to tick, drop the frames we couldn't
+            // catch up on to prevent running at turbo speed. The time into the
+            // current frame is kept, so that the frame rate doesn't drift.
+            let missed_deadline = self.frame_accumulator >= frame_time;
+            if missed_deadline {
+                self.frame_accumulator %= frame_time;
             }
 
-            self.update_timers(dt);
+            // Skip rendering the frames that missed their deadline, but never two
+            // ticks in a row, so that slow movies still get drawn.
+            self.skip_render = missed_deadline && !self.skip_render;
+
+            // Moving objects need to be redrawn on every tick, not just on
+            // every frame.
+            if self.is_interpolating {
+                self.needs_render = true;
+            }
+
+            if self.virtual_clock.is_none() {
+                self.update_timers(dt);
+            }
             self.audio.tick();
+        } else {
+            // No frames ran, so there is no late frame to skip drawing.
+            self.skip_render = false;
         }
     }
 
@@ -552,9 +727,14 @@ impl Player {
             dt = dt.min(time_til_next_timer)
         }
 
+        // Wake up often enough to redraw moving objects on fast displays.
+        if self.is_interpolating {
+            dt = dt.min(MAX_INTERPOLATED_REDRAW_TIME);
+        }
+
         dt = dt.max(0.0);
 
-        std::time::Duration::from_micros(dt as u64 * 1000)
+        std::time::Duration::from_micros((dt * 1000.0) as u64)
     }
 
     pub fn is_playing(&self) -> bool {
@@ -563,10 +743,6 @@ impl Player {
 
     pub fn prepare_context_menu(&mut self) -> Vec<ContextMenuItem> {
         self.mutate_with_update_context(|context| {
-            if !context.stage.show_menu() {
-                return vec![];
-            }
-
             let mut activation = Activation::from_stub(
                 context.reborrow(),
                 ActivationIdentifier::root("[ContextMenu]"),
@@ -656,6 +832,23 @@ impl Player {
         );
     }
 
+    /// Navigate the root timeline to the frame with the given named anchor.
+    ///
+    /// This is used by hosts to map their own navigation (such as a browser's
+    /// URL fragment) onto the movie. Returns `false` if no such anchor exists.
+    pub fn goto_anchor(&mut self, anchor: &str) -> bool {
+        self.update(|context| {
+            if let Some(mc) = context.stage.root_clip().as_movie_clip() {
+                if let Some(frame) = mc.anchor_to_frame_number(anchor) {
+                    mc.goto_frame(context, frame, true);
+                    return true;
+                }
+            }
+
+            false
+        })
+    }
+
     fn toggle_play_root_movie<'gc>(context: &mut UpdateContext<'_, 'gc, '_>) {
         if let Some(mc) = context.stage.root_clip().as_movie_clip() {
             if mc.playing() {
@@ -689,10 +882,288 @@ impl Player {
             self.audio.pause();
         }
         self.is_playing = v;
+        // A render skipped for a late frame must not outlast playback, or
+        // nothing would be redrawn until the movie plays again.
+        self.skip_render = false;
+    }
+
+    /// Pause playback of the movie.
+    ///
+    /// While paused, `tick` will not advance the timeline. Frames may still be
+    /// advanced one at a time with `step_frame`.
+    pub fn pause(&mut self) {
+        self.set_is_playing(false);
+    }
+
+    /// Resume playback of the movie after a call to `pause`.
+    pub fn resume(&mut self) {
+        self.set_is_playing(true);
+    }
+
+    /// Run exactly one frame of the movie, regardless of whether the player
+    /// is currently playing.
+    ///
+    /// Timers are advanced by the duration of a single frame rather than by
+    /// wall-clock time, so stepping through content is deterministic.
+    pub fn step_frame(&mut self) {
+        let frame_time = 1000.0 / self.frame_rate;
+        self.run_frame();
+        self.update_timers(frame_time);
+        self.skip_render = false;
+    }
+
+    /// Produce a human-readable dump of the display list, one object per
+    /// line, with children indented beneath their parents.
+    ///
+    /// This is intended for debugger UIs and should be called between frames.
+    pub fn dump_display_list(&mut self) -> String {
+        fn dump_object(object: DisplayObject<'_>, indent: usize, output: &mut String) {
+            for _ in 0..indent {
+                output.push_str("  ");
+            }
+            output.push_str(&format!(
+                "{} \"{}\" (id {}, depth {}) x={} y={}{}\n",
+                object.type_name(),
+                object.name(),
+                object.id(),
+                object.depth(),
+                object.x(),
+                object.y(),
+                if object.visible() { "" } else { " [invisible]" }
+            ));
+
+            if let Some(container) = object.as_container() {
+                for child in container.iter_depth_list().map(|(_depth, child)| child) {
+                    dump_object(child, indent + 1, output);
+                }
+            }
+        }
+
+        self.mutate_with_update_context(|context| {
+            let mut output = String::new();
+            dump_object(context.stage.into(), 0, &mut output);
+            output
+        })
+    }
+
+    /// Check the display list for states that should never happen, and
+    /// describe each problem found, one per entry.
+    ///
+    /// This is intended for tests and fuzzers, and should be called between
+    /// frames. The checks are:
+    ///
+    /// * No child of a clip was placed on a frame after the clip's current
+    ///   frame.
+    /// * No two children of a container share a depth.
+    /// * The transforms and bounds of every object are finite.
+    pub fn check_display_list(&mut self) -> Vec<String> {
+        fn check_object(object: DisplayObject<'_>, errors: &mut Vec<String>) {
+            let matrix = *object.matrix();
+            if ![matrix.a, matrix.b, matrix.c, matrix.d]
+                .iter()
+                .all(|value| value.is_finite())
+            {
+                errors.push(format!("{} has a non-finite matrix", object.path()));
+            }
+
+            let bounds = object.world_bounds();
+            if bounds.valid && (bounds.x_min > bounds.x_max || bounds.y_min > bounds.y_max) {
+                errors.push(format!("{} has inverted bounds", object.path()));
+            }
+
+            let container = match object.as_container() {
+                Some(container) => container,
+                None => return,
+            };
+
+            let clip = object.as_movie_clip();
+            let mut depths = HashSet::new();
+            for child in container.iter_render_list() {
+                if !depths.insert(child.depth()) {
+                    errors.push(format!(
+                        "{} has more than one child at depth {}",
+                        object.path(),
+                        child.depth()
+                    ));
+                }
+
+                if let Some(clip) = clip {
+                    if child.place_frame() > clip.current_frame() {
+                        errors.push(format!(
+                            "{} was placed on frame {}, after the current frame {} of its parent",
+                            child.path(),
+                            child.place_frame(),
+                            clip.current_frame()
+                        ));
+                    }
+                }
+
+                check_object(child, errors);
+            }
+        }
+
+        self.mutate_with_update_context(|context| {
+            let mut errors = Vec::new();
+            check_object(context.stage.into(), &mut errors);
+            errors
+        })
+    }
+
+    /// Jump the root clip to a frame, as `gotoAndStop` or `gotoAndPlay` would.
+    /// `frame` is 1-based.
+    pub fn goto_root_frame(&mut self, frame: u16, stop: bool) {
+        self.update(|context| {
+            if let Some(clip) = context.stage.root_clip().as_movie_clip() {
+                clip.goto_frame(context, frame, stop);
+            }
+        });
+    }
+
+    /// The number of frames of every movie clip on the display list, in the
+    /// depth-first order used by `goto_clip_frame`. The root clip comes first.
+    ///
+    /// This is intended for tests and fuzzers, and should be called between
+    /// frames.
+    pub fn movie_clip_frame_counts(&mut self) -> Vec<u16> {
+        self.mutate_with_update_context(|context| {
+            Self::movie_clips(context.stage.root_clip())
+                .into_iter()
+                .map(|clip| clip.total_frames())
+                .collect()
+        })
+    }
+
+    /// Jump a movie clip to a frame, as `gotoAndStop` or `gotoAndPlay` would.
+    /// `index` is the position of the clip in `movie_clip_frame_counts`, and
+    /// `frame` is 1-based. Nothing happens if there is no such clip.
+    pub fn goto_clip_frame(&mut self, index: usize, frame: u16, stop: bool) {
+        self.update(|context| {
+            if let Some(clip) = Self::movie_clips(context.stage.root_clip()).get(index) {
+                clip.goto_frame(context, frame, stop);
+            }
+        });
+    }
+
+    /// Every movie clip beneath `object`, including itself, depth-first.
+    fn movie_clips(object: DisplayObject<'_>) -> Vec<MovieClip<'_>> {
+        fn collect<'gc>(object: DisplayObject<'gc>, clips: &mut Vec<MovieClip<'gc>>) {
+            if let Some(clip) = object.as_movie_clip() {
+                clips.push(clip);
+            }
+
+            if let Some(container) = object.as_container() {
+                for child in container.iter_render_list() {
+                    collect(child, clips);
+                }
+            }
+        }
+
+        let mut clips = Vec::new();
+        collect(object, &mut clips);
+        clips
+    }
+
+    /// Read a variable of the root movie, as the `GetVariable` method of the
+    /// Flash Player plugin does.
+    ///
+    /// `path` is resolved like the target of a `GetVariable` action, e.g.
+    /// `score`, `_root.player.name` or `/player:name`. The value is converted
+    /// to a string; `None` is returned if the variable is undefined, or if the
+    /// root movie isn't an AVM1 movie.
+    pub fn get_variable(&mut self, path: &str) -> Option<String> {
+        self.update(|context| {
+            let mut activation = Self::root_activation(context, "[Get Variable]")?;
+            let value = match activation.get_variable(path) {
+                Ok(value) => Value::from(value),
+                Err(e) => {
+                    log::warn!("Couldn't get variable {}: {}", path, e);
+                    return None;
+                }
+            };
+
+            match value {
+                Value::Undefined => None,
+                value => value
+                    .coerce_to_string(&mut activation)
+                    .ok()
+                    .map(|value| value.to_string()),
+            }
+        })
+    }
+
+    /// Set a variable of the root movie to a string, as the `SetVariable`
+    /// method of the Flash Player plugin does.
+    ///
+    /// `path` is resolved like the target of a `SetVariable` action. Nothing
+    /// happens if the root movie isn't an AVM1 movie.
+    pub fn set_variable(&mut self, path: &str, value: &str) {
+        self.update(|context| {
+            if let Some(mut activation) = Self::root_activation(context, "[Set Variable]") {
+                let value = AvmString::new(activation.context.gc_context, value.to_string());
+                if let Err(e) = activation.set_variable(path, value.into()) {
+                    log::warn!("Couldn't set variable {}: {}", path, e);
+                }
+            }
+        })
+    }
+
+    /// An activation scoped to the root clip, for scripts run on behalf of
+    /// the frontend. Returns `None` if the root movie isn't an AVM1 movie.
+    fn root_activation<'a, 'gc, 'gc_context>(
+        context: &'a mut UpdateContext<'_, 'gc, 'gc_context>,
+        name: &'static str,
+    ) -> Option<Activation<'a, 'gc, 'gc_context>> {
+        let root_clip = context.stage.root_clip();
+        let movie = root_clip.movie()?;
+        if context.library.library_for_movie_mut(movie).avm_type() != AvmType::Avm1 {
+            return None;
+        }
+
+        let swf_version = context.swf.version();
+        let globals = context.avm1.global_object_cell();
+        Some(Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root(name),
+            swf_version,
+            globals,
+            root_clip,
+        ))
+    }
+
+    /// The number of frames of the root clip.
+    pub fn root_total_frames(&mut self) -> u16 {
+        self.mutate_with_update_context(|context| {
+            context
+                .stage
+                .root_clip()
+                .as_movie_clip()
+                .map(|clip| clip.total_frames())
+                .unwrap_or(1)
+        })
+    }
+
+    /// Produce a human-readable dump of each value currently on the AVM1
+    /// operand stack, bottom-most value first.
+    ///
+    /// The stack is normally empty between frames; values left here usually
+    /// indicate unbalanced bytecode.
+    pub fn dump_avm1_stack(&mut self) -> Vec<String> {
+        self.mutate_with_update_context(|context| {
+            let stack = context.avm1.stack().to_vec();
+            let mut activation = Activation::from_stub(
+                context.reborrow(),
+                ActivationIdentifier::root("[Stack Dumper]"),
+            );
+
+            stack
+                .iter()
+                .map(|value| VariableDumper::dump(value, "  ", &mut activation))
+                .collect()
+        })
     }
 
     pub fn needs_render(&self) -> bool {
-        self.needs_render
+        self.needs_render && !self.skip_render
     }
 
     pub fn background_color(&mut self) -> Option<Color> {
@@ -717,6 +1188,141 @@ impl Player {
         })
     }
 
+    /// Whether the built-in items of the context menu are shown.
+    pub fn show_menu(&mut self) -> bool {
+        self.mutate_with_update_context(|context| context.stage.show_menu())
+    }
+
+    /// Set whether the built-in items of the context menu are shown, as with
+    /// the `menu` embed parameter. Movies may change this with `Stage.showMenu`.
+    pub fn set_show_menu(&mut self, show_menu: bool) {
+        self.mutate_with_update_context(|context| context.stage.set_show_menu(context, show_menu))
+    }
+
+    /// Notes on features that the movie used but Ruffle doesn't support,
+    /// which may explain why it doesn't play as it should. Frontends include
+    /// these in their bug reports.
+    pub fn diagnostics(&mut self) -> Vec<String> {
+        self.mutate_with_update_context(|context| {
+            let mut diagnostics = vec![];
+            if context.stage.stage3d_requested() {
+                diagnostics.push("Stage3D was requested, but is not supported".to_string());
+            }
+            diagnostics
+        })
+    }
+
+    pub fn loop_policy(&mut self) -> LoopPolicy {
+        self.mutate_with_update_context(|context| context.stage.loop_policy())
+    }
+
+    /// Set what the root timeline does once it plays past its last frame.
+    ///
+    /// With `LoopPolicy::Once`, the root clip stops on its last frame and
+    /// `MovieLifecycleEvent::Ended` is reported to the UI backend.
+    pub fn set_loop_policy(&mut self, loop_policy: LoopPolicy) {
+        self.mutate_with_update_context(|context| {
+            context
+                .stage
+                .set_loop_policy(context.gc_context, loop_policy)
+        })
+    }
+
+    /// The settings of the site the root movie was loaded from.
+    pub fn site_settings(&self) -> &SiteSettings {
+        &self.site_settings
+    }
+
+    /// Change the settings of the site the root movie was loaded from, and
+    /// save them for the next movie from the same site.
+    pub fn set_site_settings(&mut self, site_settings: SiteSettings) {
+        if !site_settings.save(self.storage.deref_mut(), &self.site) {
+            log::warn!("Couldn't save the settings of {}", self.site);
+        }
+        // The movie may have changed the quality since it started, which is
+        // kept unless the user picks a different one.
+        let quality = site_settings.quality;
+        let quality_changed = quality != self.site_settings.quality;
+        let interpolation_changed =
+            site_settings.frame_interpolation != self.site_settings.frame_interpolation;
+        self.site_settings = site_settings;
+        self.apply_site_settings();
+        if interpolation_changed {
+            self.stop_interpolation();
+        }
+        if quality_changed {
+            self.mutate_with_update_context(|context| context.stage.set_quality(context, quality));
+        }
+    }
+
+    /// Set the quality the stage starts with, as with the `quality` embed
+    /// parameter, instead of the quality saved for the site.
+    pub fn set_initial_quality(&mut self, quality: Option<StageQuality>) {
+        self.initial_quality = quality;
+    }
+
+    /// Apply the site settings that affect a running movie.
+    fn apply_site_settings(&mut self) {
+        let volume = self.site_settings.volume;
+        self.mutate_with_update_context(|context| context.audio_manager.set_volume(volume));
+    }
+
+    /// Reseed the random number generator used by `Math.random` and
+    /// `random`, so that a movie makes the same choices on every run.
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.rng = SmallRng::seed_from_u64(seed);
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic_mode.is_some()
+    }
+
+    /// Make the movie play the same way on every run, or go back to reading
+    /// the real time. See `DeterministicMode`.
+    pub fn set_deterministic_mode(&mut self, mode: Option<DeterministicMode>) {
+        if let Some(mode) = mode {
+            self.set_random_seed(mode.random_seed);
+        }
+        self.deterministic_mode = mode;
+        self.virtual_clock = mode.map(|mode| VirtualClock::new(mode.start_date));
+    }
+
+    /// Start recording the input events handled by the player.
+    ///
+    /// The recording only replays the same way if the movie ran in
+    /// deterministic mode from its first frame, so this should be called
+    /// after `set_deterministic_mode` and before the root movie is loaded.
+    pub fn start_input_recording(&mut self) {
+        let mode = self.deterministic_mode.unwrap_or_else(|| {
+            log::warn!("Recording input outside of deterministic mode, it may not replay the same");
+            DeterministicMode::default()
+        });
+        self.input_recorder = Some(InputRecorder::new(mode));
+    }
+
+    /// Stop recording input, and return the recording.
+    pub fn stop_input_recording(&mut self) -> Option<InputRecording> {
+        self.input_recorder.take().map(InputRecorder::finish)
+    }
+
+    pub fn is_recording_input(&self) -> bool {
+        self.input_recorder.is_some()
+    }
+
+    /// Replay the input of a recording, in the deterministic mode it was
+    /// recorded in. This should be called before the root movie is loaded.
+    ///
+    /// Events from `handle_event` are ignored until every recorded event was
+    /// replayed.
+    pub fn replay_input(&mut self, recording: InputRecording) {
+        self.set_deterministic_mode(Some(recording.mode));
+        self.input_replay = Some(InputReplay::new(recording));
+    }
+
+    pub fn is_replaying_input(&self) -> bool {
+        self.input_replay.is_some()
+    }
+
     pub fn warn_on_unsupported_content(&self) -> bool {
         self.warn_on_unsupported_content
     }
@@ -725,6 +1331,44 @@ impl Player {
         self.warn_on_unsupported_content = warn_on_unsupported_content
     }
 
+    /// Whether objects that scripts move are drawn moving smoothly between
+    /// frames in the current movie.
+    pub fn frame_interpolation(&self) -> bool {
+        self.frame_interpolation
+            .unwrap_or(self.site_settings.frame_interpolation)
+    }
+
+    /// Draw objects that scripts move with `_x` and `_y` moving smoothly
+    /// between frames. This makes motion less choppy on displays with a
+    /// higher refresh rate than the movie's frame rate, at the cost of
+    /// drawing moving objects up to a frame behind.
+    ///
+    /// `None` uses the choice saved in the settings of the movie's site.
+    pub fn set_frame_interpolation(&mut self, frame_interpolation: Option<bool>) {
+        self.frame_interpolation = frame_interpolation;
+        self.stop_interpolation();
+    }
+
+    /// Draw every object where it is, until the next frame starts moving
+    /// them again.
+    fn stop_interpolation(&mut self) {
+        self.is_interpolating = false;
+        self.dirty_regions.invalidate();
+        self.needs_render = true;
+    }
+
+    pub fn show_debug_overlay(&self) -> bool {
+        self.show_debug_overlay
+    }
+
+    /// Overlay the bounding boxes, mask extents and depths of every rendered
+    /// display object on top of the stage.
+    pub fn set_show_debug_overlay(&mut self, show_debug_overlay: bool) {
+        self.show_debug_overlay = show_debug_overlay;
+        self.dirty_regions.invalidate();
+        self.needs_render = true;
+    }
+
     pub fn movie_width(&mut self) -> u32 {
         self.mutate_with_update_context(|context| context.stage.movie_size().0)
     }
@@ -737,14 +1381,79 @@ impl Player {
         self.mutate_with_update_context(|context| context.stage.viewport_size())
     }
 
+    /// Resize the viewport. The mouse stays at the same point of the viewport,
+    /// which may now be a different point of the stage.
     pub fn set_viewport_dimensions(&mut self, width: u32, height: u32, scale_factor: f64) {
-        self.mutate_with_update_context(|context| {
+        let mouse_pos = self.mouse_pos;
+        self.mouse_pos = self.mutate_with_update_context(|context| {
             let stage = context.stage;
+            let viewport_pos = *stage.matrix() * mouse_pos;
             stage.set_viewport_size(context, width, height, scale_factor);
-        })
+            stage.inverse_view_matrix() * viewport_pos
+        });
+    }
+
+    pub fn viewport_orientation(&self) -> StageOrientation {
+        self.gc_arena
+            .mutate(|_gc_context, gc_root| gc_root.0.read().stage.orientation())
+    }
+
+    /// Set the orientation of the device's screen, as when a phone is
+    /// rotated. This should be called before the viewport is resized to fit
+    /// the new orientation.
+    ///
+    /// The mouse stays at the same point of the screen, so it is moved to
+    /// wherever that point is once the viewport has turned.
+    pub fn set_viewport_orientation(&mut self, orientation: StageOrientation) {
+        let mouse_pos = self.mouse_pos;
+        self.mouse_pos = self.mutate_with_update_context(|context| {
+            let stage = context.stage;
+            let (x, y) = *stage.matrix() * mouse_pos;
+            let (width, height) = stage.viewport_size();
+            let (x, y) = stage.orientation().rotate_point(
+                orientation,
+                (f64::from(width), f64::from(height)),
+                (x.to_pixels(), y.to_pixels()),
+            );
+            stage.set_orientation(context, orientation);
+            stage.inverse_view_matrix() * (Twips::from_pixels(x), Twips::from_pixels(y))
+        });
+        self.needs_render = true;
     }
 
     pub fn handle_event(&mut self, event: PlayerEvent) {
+        // The user can't interfere with a replay.
+        if self.input_replay.is_some() {
+            return;
+        }
+
+        if self.input_recorder.is_some() {
+            let inverse_view_matrix =
+                self.mutate_with_update_context(|context| context.stage.inverse_view_matrix());
+            let stage_event = transform_mouse_event(event.clone(), &inverse_view_matrix);
+            if let Some(recorder) = &mut self.input_recorder {
+                recorder.record(stage_event);
+            }
+        }
+
+        self.dispatch_event(event);
+    }
+
+    /// Feed the recorded events that arrived before the next frame back to
+    /// the movie.
+    fn replay_input_events(&mut self) {
+        let events = match &mut self.input_replay {
+            Some(replay) => replay.take_events(),
+            None => return,
+        };
+
+        let view_matrix = self.mutate_with_update_context(|context| *context.stage.matrix());
+        for event in events {
+            self.dispatch_event(transform_mouse_event(event, &view_matrix));
+        }
+    }
+
+    fn dispatch_event(&mut self, event: PlayerEvent) {
         let mut needs_render = self.needs_render;
         let inverse_view_matrix =
             self.mutate_with_update_context(|context| context.stage.inverse_view_matrix());
@@ -823,12 +1532,8 @@ impl Player {
         // Propagate button events.
         let button_event = match event {
             // ASCII characters convert directly to keyPress button events.
-            PlayerEvent::TextInput { codepoint }
-                if codepoint as u32 >= 32 && codepoint as u32 <= 126 =>
-            {
-                Some(ClipEvent::KeyPress {
-                    key_code: ButtonKeyCode::try_from(codepoint as u8).unwrap(),
-                })
+            PlayerEvent::TextInput { codepoint } => {
+                ButtonKeyCode::from_char(codepoint).map(|key_code| ClipEvent::KeyPress { key_code })
             }
 
             // Special keys have custom values for keyPress.
@@ -864,6 +1569,21 @@ impl Player {
             });
         }
 
+        // Scroll multiline text fields under the mouse.
+        if let PlayerEvent::MouseWheel { delta } = event {
+            self.mutate_with_update_context(|context| {
+                if let Some(text) = context
+                    .mouse_hovered_object
+                    .and_then(|o| o.as_edit_text())
+                    .filter(|text| text.is_multiline())
+                {
+                    let scroll = text.scroll() as f64 - delta.lines().round();
+                    text.set_scroll(scroll.max(1.0) as usize, context.gc_context);
+                    needs_render = true;
+                }
+            });
+        }
+
         // Propagate clip events.
         self.mutate_with_update_context(|context| {
             let (clip_event, listener) = match event {
@@ -894,6 +1614,7 @@ impl Player {
 
             // Fire clip event on all clips.
             if let Some(clip_event) = clip_event {
+                Avm1::update_prototype_clip_events(context, clip_event);
                 let levels: Vec<_> = context.stage.iter_depth_list().collect();
                 for (_depth, level) in levels {
                     level.handle_clip_event(context, clip_event);
@@ -915,6 +1636,7 @@ impl Player {
         });
 
         let mut is_mouse_down = self.is_mouse_down;
+        let is_mouse_over_pressed_object = self.is_mouse_over_pressed_object;
         self.mutate_with_update_context(|context| {
             if let Some(node) = context.mouse_hovered_object {
                 if node.removed() {
@@ -926,6 +1648,7 @@ impl Player {
                 PlayerEvent::MouseDown { .. } => {
                     is_mouse_down = true;
                     needs_render = true;
+                    context.focus_tracker.hide_highlight(context.gc_context);
                     if let Some(node) = context.mouse_hovered_object {
                         node.handle_clip_event(context, ClipEvent::Press);
                     }
@@ -935,16 +1658,54 @@ impl Player {
                     is_mouse_down = false;
                     needs_render = true;
                     if let Some(node) = context.mouse_hovered_object {
-                        node.handle_clip_event(context, ClipEvent::Release);
+                        if is_mouse_over_pressed_object {
+                            node.handle_clip_event(context, ClipEvent::Release);
+                        } else {
+                            // The mouse was dragged off of the object, which
+                            // was already rolled out of.
+                            node.handle_clip_event(context, ClipEvent::ReleaseOutside);
+                            context.mouse_hovered_object = None;
+                        }
                     }
                 }
 
+                // Keyboard navigation between buttons and text fields.
+                PlayerEvent::KeyDown {
+                    key_code: KeyCode::Tab,
+                } => {
+                    needs_render = true;
+                    let reverse = context.ui.is_key_down(KeyCode::Shift);
+                    let tracker = context.focus_tracker;
+                    tracker.cycle(context, reverse);
+                }
+                PlayerEvent::KeyDown {
+                    key_code: KeyCode::Return,
+                }
+                | PlayerEvent::KeyDown {
+                    key_code: KeyCode::Space,
+                } => {
+                    let tracker = context.focus_tracker;
+                    tracker.activate(context);
+                }
+
                 _ => (),
             }
 
             Self::run_actions(context);
         });
+        if is_mouse_down && !self.is_mouse_down {
+            self.is_mouse_over_pressed_object = true;
+        }
         self.is_mouse_down = is_mouse_down;
+
+        // The object under the mouse wasn't picked while the mouse was down,
+        // so pick it now rather than waiting for the mouse to move.
+        if let PlayerEvent::MouseUp { .. } = event {
+            if self.update_roll_over() {
+                needs_render = true;
+            }
+        }
+
         if needs_render {
             self.needs_render = true;
         }
@@ -975,30 +1736,100 @@ impl Player {
                         .set_y(context.gc_context, drag_point.1.to_pixels());
                 }
             }
+
+            if let Some(drag_object) = &context.drag_object {
+                let dragged = drag_object.display_object;
+                let levels: Vec<_> = context.stage.iter_depth_list().collect();
+                let drop_target = levels.iter().rev().find_map(|(_depth, level)| {
+                    Self::find_drop_target(context, *level, dragged, mouse_pos)
+                });
+                dragged.set_drop_target(context.gc_context, drop_target);
+            }
         });
     }
 
+    /// The clip holding the topmost shape under the given point, as reported
+    /// by `_droptarget`, ignoring the object being dragged.
+    fn find_drop_target<'gc>(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: DisplayObject<'gc>,
+        dragged: DisplayObject<'gc>,
+        point: (Twips, Twips),
+    ) -> Option<DisplayObject<'gc>> {
+        // Masks are never drop targets.
+        if DisplayObject::ptr_eq(object, dragged) || !object.visible() || object.maskee().is_some()
+        {
+            return None;
+        }
+
+        if let Some(masker) = object.masker() {
+            if !masker.hit_test_shape(
+                context,
+                point,
+                HitTestOptions {
+                    skip_mask: false,
+                    skip_invisible: true,
+                },
+            ) {
+                return None;
+            }
+        }
+
+        if let Some(container) = object.as_container() {
+            container
+                .iter_render_list()
+                .rev()
+                .filter(|child| child.clip_depth() == 0)
+                .find_map(|child| Self::find_drop_target(context, child, dragged, point))
+        } else if object.hit_test_shape(
+            context,
+            point,
+            HitTestOptions {
+                skip_mask: true,
+                skip_invisible: true,
+            },
+        ) {
+            object.parent()
+        } else {
+            None
+        }
+    }
+
     /// Checks to see if a recent update has caused the current mouse hover
     /// node to change.
     fn update_roll_over(&mut self) -> bool {
-        // TODO: While the mouse is down, maintain the hovered node.
+        let mouse_pos = self.mouse_pos;
+
         if self.is_mouse_down {
-            return false;
+            // While the mouse is down, the object it was pressed on stays
+            // hovered, and is told when the mouse is dragged off of and back
+            // onto it.
+            let was_over = self.is_mouse_over_pressed_object;
+            let is_over = self.mutate_with_update_context(|context| {
+                let pressed = match context.mouse_hovered_object {
+                    Some(node) if !node.removed() => node,
+                    _ => return false,
+                };
+                let is_over = Self::pick_hovered_object(context, mouse_pos)
+                    .map_or(false, |node| DisplayObject::ptr_eq(node, pressed));
+                if is_over != was_over {
+                    let event = if is_over {
+                        ClipEvent::DragOver
+                    } else {
+                        ClipEvent::DragOut
+                    };
+                    pressed.handle_clip_event(context, event);
+                    Self::run_actions(context);
+                }
+                is_over
+            });
+            self.is_mouse_over_pressed_object = is_over;
+            return is_over != was_over;
         }
-        let mouse_pos = self.mouse_pos;
 
-        let mut new_cursor = self.mouse_cursor;
         let hover_changed = self.mutate_with_update_context(|context| {
             // Check hovered object.
-            let mut new_hovered = None;
-            let levels: Vec<_> = context.stage.iter_depth_list().collect();
-            for (_depth, level) in levels.iter().rev() {
-                if new_hovered.is_none() {
-                    new_hovered = level.mouse_pick(context, *level, mouse_pos);
-                } else {
-                    break;
-                }
-            }
+            let new_hovered = Self::pick_hovered_object(context, mouse_pos);
 
             let cur_hovered = context.mouse_hovered_object;
 
@@ -1011,9 +1842,7 @@ impl Player {
                 }
 
                 // RollOver on new node.I still
-                new_cursor = MouseCursor::Arrow;
                 if let Some(node) = new_hovered {
-                    new_cursor = node.mouse_cursor();
                     node.handle_clip_event(context, ClipEvent::RollOver);
                 }
 
@@ -1026,6 +1855,14 @@ impl Player {
             }
         });
 
+        // The cursor can change while moving over the same object, such as
+        // over a link in a text field.
+        let new_cursor =
+            self.mutate_with_update_context(|context| match context.mouse_hovered_object {
+                Some(node) if !node.removed() => node.mouse_cursor(context),
+                _ => MouseCursor::Arrow,
+            });
+
         // Update mouse cursor if it has changed.
         if new_cursor != self.mouse_cursor {
             self.mouse_cursor = new_cursor;
@@ -1035,6 +1872,18 @@ impl Player {
         hover_changed
     }
 
+    /// The topmost object under the given point that handles the mouse.
+    fn pick_hovered_object<'gc>(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        mouse_pos: (Twips, Twips),
+    ) -> Option<DisplayObject<'gc>> {
+        let levels: Vec<_> = context.stage.iter_depth_list().collect();
+        levels
+            .iter()
+            .rev()
+            .find_map(|(_depth, level)| level.mouse_pick(context, *level, mouse_pos))
+    }
+
     /// Preload the first movie in the player.
     ///
     /// This should only be called once. Further movie loads should preload the
@@ -1042,22 +1891,14 @@ impl Player {
     fn preload(&mut self) {
         let mut is_action_script_3 = false;
         self.mutate_with_update_context(|context| {
-            let mut morph_shapes = fnv::FnvHashMap::default();
             let root = context.stage.root_clip();
-            root.as_movie_clip()
-                .unwrap()
-                .preload(context, &mut morph_shapes);
+            root.as_movie_clip().unwrap().preload(context);
 
             let lib = context
                 .library
                 .library_for_movie_mut(root.as_movie_clip().unwrap().movie().unwrap());
 
             is_action_script_3 = lib.avm_type() == AvmType::Avm2;
-            // Finalize morph shapes.
-            for (id, static_data) in morph_shapes {
-                let morph_shape = MorphShape::new(context.gc_context, static_data);
-                lib.register_character(id, crate::character::Character::MorphShape(morph_shape));
-            }
         });
         if is_action_script_3 && self.warn_on_unsupported_content {
             self.ui.display_unsupported_message();
@@ -1065,42 +1906,218 @@ impl Player {
     }
 
     pub fn run_frame(&mut self) {
+        self.replay_input_events();
+
         self.update(|update_context| {
+            update_context.action_queue.start_frame();
+
             // TODO: In what order are levels run?
             let stage = update_context.stage;
 
-            stage.exit_frame(update_context);
-            stage.enter_frame(update_context);
-            stage.construct_frame(update_context);
-            stage.frame_constructed(update_context);
-            stage.run_frame(update_context);
-            stage.run_frame_scripts(update_context);
+            match stage.root_clip().vm_type(update_context) {
+                AvmType::Avm1 => stage.run_frame(update_context),
+                AvmType::Avm2 => Self::run_frame_avm2(stage, update_context),
+            }
 
+            StreamManager::tick(update_context);
+            LoadManager::report_movie_progress(update_context);
             update_context.update_sounds();
+
+            update_context
+                .library
+                .release_unloaded_bitmaps(update_context.renderer);
         });
         self.needs_render = true;
+
+        if let Some(clock) = &mut self.virtual_clock {
+            clock.advance(Duration::from_secs_f64(1.0 / self.frame_rate));
+        }
+
+        if let Some(recorder) = &mut self.input_recorder {
+            recorder.next_frame();
+        }
+
+        if let Some(replay) = &mut self.input_replay {
+            replay.next_frame();
+            if replay.is_finished() {
+                self.input_replay = None;
+            }
+        }
+
+        if self.frame_interpolation() {
+            self.is_interpolating = self.gc_arena.mutate(|gc_context, gc_root| {
+                let stage = gc_root.0.read().stage;
+                start_interpolation(stage.into(), gc_context)
+            });
+        }
+
+        self.report_lifecycle_event(MovieLifecycleEvent::FirstFrameExecuted);
+
+        // Only a root timeline held on its last frame by `LoopPolicy::Once`
+        // has ended; one stopped by a script may still be resumed.
+        let has_ended = self
+            .gc_arena
+            .mutate(|gc_context, gc_root| gc_root.0.read().stage.take_held_last_frame(gc_context));
+        if has_ended {
+            self.report_lifecycle_event(MovieLifecycleEvent::Ended);
+        }
+    }
+
+    /// Run a frame of an AVM2 movie.
+    ///
+    /// AS3 frames run in phases, each of which completes for every object
+    /// before the next one starts:
+    ///
+    /// 1. `enterFrame` is broadcast to all display objects.
+    /// 2. Timelines place their new children, which are then constructed.
+    /// 3. Timelines advance, running the rest of their frame's tags.
+    /// 4. `frameConstructed` is broadcast.
+    /// 5. Frame scripts run.
+    /// 6. `exitFrame` is broadcast.
+    /// 7. If `Stage.invalidate` was called, `render` is broadcast right
+    ///    before the frame is drawn.
+    fn run_frame_avm2<'gc>(stage: Stage<'gc>, context: &mut UpdateContext<'_, 'gc, '_>) {
+        stage.enter_frame(context);
+        stage.construct_frame(context);
+        stage.run_frame(context);
+        stage.frame_constructed(context);
+        stage.run_frame_scripts(context);
+        stage.exit_frame(context);
+
+        if stage.take_invalidated(context.gc_context) {
+            stage.render_event(context);
+        }
     }
 
     pub fn render(&mut self) {
+        self.present_video_frames();
+
+        if !self.renderer.supports_dirty_rects() {
+            self.render_stage(None);
+        } else {
+            match self.find_dirty_region() {
+                // Nothing changed, so the last frame is still up to date.
+                DirtyRegion::Clean => (),
+                DirtyRegion::Partial(dirty_rect) => {
+                    self.renderer.set_dirty_rect(Some(&dirty_rect));
+                    self.render_stage(Some(dirty_rect));
+                }
+                DirtyRegion::Full => {
+                    self.renderer.set_dirty_rect(None);
+                    self.render_stage(None);
+                }
+            }
+        }
+
+        self.needs_render = false;
+
+        if self
+            .reported_lifecycle_events
+            .contains(&MovieLifecycleEvent::FirstFrameExecuted)
+        {
+            self.report_lifecycle_event(MovieLifecycleEvent::FirstFrameRendered);
+        }
+    }
+
+    /// Renders the current state of the stage into an offscreen target and
+    /// returns its pixels, as RGBA with premultiplied alpha.
+    ///
+    /// The screen is left alone. Returns `None` if the render backend can't
+    /// capture frames.
+    pub fn capture_frame(&mut self) -> Option<Bitmap> {
+        if !self.renderer.begin_capture() {
+            return None;
+        }
+
+        self.present_video_frames();
+        self.renderer.set_dirty_rect(None);
+        self.render_stage(None);
+        self.renderer.end_capture()
+    }
+
+    /// Decode the frames that videos are about to show.
+    ///
+    /// Only the videos that had frames queued since the last render are
+    /// visited.
+    fn present_video_frames(&mut self) {
+        self.mutate_with_update_context(|context| {
+            for video in std::mem::take(context.queued_videos) {
+                video.present_frame(context);
+            }
+        });
+    }
+
+    /// Find the part of the stage that has to be redrawn since the last frame.
+    fn find_dirty_region(&mut self) -> DirtyRegion {
+        let dirty_regions = &mut self.dirty_regions;
+        let dirty_region = self.gc_arena.mutate(|_gc_context, gc_root| {
+            let root_data = gc_root.0.read();
+            dirty_regions.update(root_data.stage, root_data.focus_tracker)
+        });
+
+        // The debug overlay and objects drawn between frames aren't tracked.
+        if self.show_debug_overlay || self.frame_interpolation() {
+            DirtyRegion::Full
+        } else {
+            dirty_region
+        }
+    }
+
+    /// Draw the stage, or only the part of it in `dirty_rect` if the rest of
+    /// the last frame is kept.
+    fn render_stage(&mut self, dirty_rect: Option<BoundingBox>) {
+        let frame_interpolation = if self.frame_interpolation() {
+            let frame_time = 1000.0 / self.frame_rate;
+            Some((self.frame_accumulator / frame_time).max(0.0).min(1.0))
+        } else {
+            None
+        };
         let (renderer, ui, transform_stack) =
             (&mut self.renderer, &mut self.ui, &mut self.transform_stack);
+        let show_debug_overlay = self.show_debug_overlay;
 
-        self.gc_arena.mutate(|_gc_context, gc_root| {
+        self.gc_arena.mutate(|gc_context, gc_root| {
             let root_data = gc_root.0.read();
+            root_data.stage.begin_render(gc_context);
             let mut render_context = RenderContext {
                 renderer: renderer.deref_mut(),
                 ui: ui.deref_mut(),
                 library: &root_data.library,
                 transform_stack,
                 stage: root_data.stage,
+                focus_tracker: root_data.focus_tracker,
                 clip_depth_stack: vec![],
                 allow_mask: true,
+                debug_overlay: if show_debug_overlay {
+                    Some(DebugOverlay::new())
+                } else {
+                    None
+                },
+                frame_interpolation,
+                dirty_rect,
+                cull: true,
+                drew_untracked_content: false,
             };
 
             root_data.stage.render(&mut render_context);
         });
+    }
 
-        self.needs_render = false;
+    /// Inform the UI backend that the root movie has reached a milestone in
+    /// its lifecycle.
+    ///
+    /// Each event is only reported once, and nothing is reported until a root
+    /// movie has been set.
+    fn report_lifecycle_event(&mut self, event: MovieLifecycleEvent) {
+        let has_root_movie = event == MovieLifecycleEvent::HeaderParsed
+            || self
+                .reported_lifecycle_events
+                .contains(&MovieLifecycleEvent::HeaderParsed);
+
+        if has_root_movie && !self.reported_lifecycle_events.contains(&event) {
+            self.reported_lifecycle_events.push(event);
+            self.ui.movie_lifecycle_event(event);
+        }
     }
 
     /// The current frame of the main timeline, if available.
@@ -1117,11 +2134,33 @@ impl Player {
         &mut self.audio
     }
 
+    /// Information about the root movie, such as its stage size, frame rate
+    /// and SWF version.
+    ///
+    /// This comes from the header and the first tags of the movie, so it's
+    /// available as soon as the movie is loaded, before its first frame runs.
+    pub fn movie_info(&self) -> MovieInfo {
+        self.swf.info()
+    }
+
     // The frame rate of the current movie in FPS.
     pub fn frame_rate(&self) -> f64 {
         self.frame_rate
     }
 
+    /// Change the frame rate of the current movie, in FPS.
+    ///
+    /// The frame rate is clamped to the range that Flash Player supports.
+    pub fn set_frame_rate(&mut self, frame_rate: f64) {
+        let frame_rate = clamp_frame_rate(frame_rate, self.frame_rate);
+
+        #[allow(clippy::float_cmp)]
+        if frame_rate != self.frame_rate {
+            self.frame_rate = frame_rate;
+            self.audio.set_frame_rate(frame_rate);
+        }
+    }
+
     pub fn renderer(&self) -> &Renderer {
         &self.renderer
     }
@@ -1231,6 +2270,17 @@ impl Player {
                     );
                 }
 
+                ActionType::DynamicMethod { object, name, args } => {
+                    Avm1::run_stack_frame_for_method(
+                        actions.clip,
+                        object,
+                        context.swf.header().version,
+                        context,
+                        &name,
+                        &args,
+                    );
+                }
+
                 // Event handler method call (e.g. onEnterFrame)
                 ActionType::NotifyListeners {
                     listener,
@@ -1283,6 +2333,7 @@ impl Player {
             mouse_position,
             player,
             system_properties,
+            security,
             instance_counter,
             storage,
             locale,
@@ -1293,6 +2344,8 @@ impl Player {
             current_frame,
             time_offset,
             frame_rate,
+            site_settings,
+            virtual_clock,
         ) = (
             self.player_version,
             &self.swf,
@@ -1304,6 +2357,7 @@ impl Player {
             &self.mouse_pos,
             self.self_reference.clone(),
             &mut self.system,
+            &mut self.security,
             &mut self.instance_counter,
             self.storage.deref_mut(),
             self.locale.deref_mut(),
@@ -1314,6 +2368,8 @@ impl Player {
             &mut self.current_frame,
             &mut self.time_offset,
             &mut self.frame_rate,
+            &self.site_settings,
+            &self.virtual_clock,
         );
 
         self.gc_arena.mutate(|gc_context, gc_root| {
@@ -1330,12 +2386,18 @@ impl Player {
                 load_manager,
                 shared_objects,
                 unbound_text_fields,
+                queued_videos,
                 timers,
+                stream_manager,
+                local_connections,
                 current_context_menu,
                 external_interface,
                 audio_manager,
             ) = root_data.update_context_params();
 
+            // Scripts get a new execution budget every update.
+            avm1.reset_execution_budget();
+
             let mut update_context = UpdateContext {
                 player_version,
                 swf,
@@ -1354,6 +2416,7 @@ impl Player {
                 player,
                 load_manager,
                 system: system_properties,
+                security,
                 instance_counter,
                 storage,
                 locale,
@@ -1361,7 +2424,10 @@ impl Player {
                 video,
                 shared_objects,
                 unbound_text_fields,
+                queued_videos,
                 timers,
+                stream_manager,
+                local_connections,
                 current_context_menu,
                 needs_render,
                 avm1,
@@ -1374,6 +2440,8 @@ impl Player {
                 time_offset,
                 audio_manager,
                 frame_rate,
+                site_settings,
+                virtual_clock,
             };
 
             let old_frame_rate = *update_context.frame_rate;
@@ -1388,11 +2456,20 @@ impl Player {
                 update_context.audio.set_frame_rate(new_frame_rate);
             }
 
-            *current_frame = update_context
-                .stage
-                .root_clip()
-                .as_movie_clip()
-                .map(|clip| clip.current_frame());
+            let root_clip = update_context.stage.root_clip().as_movie_clip();
+            let new_frame = root_clip.map(|clip| clip.current_frame());
+
+            // Inform the host whenever the root timeline arrives at a named anchor.
+            if new_frame != *current_frame {
+                if let Some(anchor) = root_clip
+                    .zip(new_frame)
+                    .and_then(|(clip, frame)| clip.frame_anchor(frame))
+                {
+                    update_context.ui.set_frame_anchor(&anchor);
+                }
+            }
+
+            *current_frame = new_frame;
 
             // Hovered object may have been updated; copy it back to the GC root.
             root_data.mouse_hovered_object = update_context.mouse_hovered_object;
@@ -1494,6 +2571,20 @@ impl Player {
         })
     }
 
+    /// Connect the `LocalConnection`s of this player to those of other
+    /// players.
+    pub fn set_local_connection_bridge(&mut self, bridge: Box<dyn LocalConnectionBridge>) {
+        self.mutate_with_update_context(|context| {
+            context.local_connections.set_bridge(Some(bridge))
+        });
+    }
+
+    /// Deliver a message that another player sent to a `LocalConnection` of
+    /// this one.
+    pub fn receive_local_connection_message(&mut self, message: LocalConnectionMessage) {
+        self.update(|context| LocalConnections::receive_external(context, message));
+    }
+
     pub fn log_backend(&self) -> &Log {
         &self.log
     }
@@ -1505,6 +2596,101 @@ impl Player {
     pub fn set_max_execution_duration(&mut self, max_execution_duration: Duration) {
         self.max_execution_duration = max_execution_duration
     }
+
+    /// Which requests movies may make to domains other than their own.
+    pub fn cross_domain_mode(&self) -> CrossDomainMode {
+        self.security.cross_domain_mode()
+    }
+
+    pub fn set_cross_domain_mode(&mut self, cross_domain_mode: CrossDomainMode) {
+        self.security.set_cross_domain_mode(cross_domain_mode)
+    }
+
+    /// The security sandbox that every movie is placed in, if it is
+    /// overridden.
+    pub fn sandbox_type_override(&self) -> Option<SandboxType> {
+        self.security.sandbox_type_override()
+    }
+
+    /// Place every movie in the given security sandbox, instead of the one
+    /// that its URL and file attributes ask for.
+    pub fn set_sandbox_type_override(&mut self, sandbox_type: Option<SandboxType>) {
+        self.security.set_sandbox_type_override(sandbox_type)
+    }
+
+    /// The maximum amount of AVM1 actions that can be executed in a single update before the
+    /// user is asked whether to abort the script, or `None` for no limit.
+    pub fn max_actions_per_update(&mut self) -> Option<u64> {
+        self.mutate_with_update_context(|context| context.avm1.max_actions_per_update())
+    }
+
+    pub fn set_max_actions_per_update(&mut self, max_actions_per_update: Option<u64>) {
+        self.mutate_with_update_context(|context| {
+            context
+                .avm1
+                .set_max_actions_per_update(max_actions_per_update)
+        })
+    }
+
+    /// The maximum amount of queued actions, such as frame scripts and event
+    /// handlers, that can run in a single frame before the rest are deferred
+    /// to the next frame, or `None` for no limit.
+    pub fn max_queued_actions_per_frame(&mut self) -> Option<usize> {
+        self.mutate_with_update_context(|context| context.action_queue.max_actions_per_frame())
+    }
+
+    pub fn set_max_queued_actions_per_frame(&mut self, max_actions_per_frame: Option<usize>) {
+        self.mutate_with_update_context(|context| {
+            context
+                .action_queue
+                .set_max_actions_per_frame(max_actions_per_frame)
+        })
+    }
+
+    /// The number of queued actions that have been deferred to a later frame
+    /// because their frame ran over its budget.
+    pub fn deferred_action_count(&mut self) -> usize {
+        self.mutate_with_update_context(|context| context.action_queue.deferred_actions())
+    }
+}
+
+/// Start interpolating the movement of an object and all of its children.
+///
+/// Returns `true` if any of them moved during the last frame.
+fn start_interpolation<'gc>(
+    object: DisplayObject<'gc>,
+    gc_context: MutationContext<'gc, '_>,
+) -> bool {
+    let mut is_moving = object.start_interpolation(gc_context);
+    if let Some(container) = object.as_container() {
+        for child in container.iter_render_list() {
+            is_moving |= start_interpolation(child, gc_context);
+        }
+    }
+    is_moving
+}
+
+/// Transform the mouse position of an event, in pixels, by a matrix.
+fn transform_mouse_event(event: PlayerEvent, matrix: &Matrix) -> PlayerEvent {
+    let transform = |x, y| {
+        let (x, y) = *matrix * (Twips::from_pixels(x), Twips::from_pixels(y));
+        (x.to_pixels(), y.to_pixels())
+    };
+    match event {
+        PlayerEvent::MouseMove { x, y } => {
+            let (x, y) = transform(x, y);
+            PlayerEvent::MouseMove { x, y }
+        }
+        PlayerEvent::MouseUp { x, y } => {
+            let (x, y) = transform(x, y);
+            PlayerEvent::MouseUp { x, y }
+        }
+        PlayerEvent::MouseDown { x, y } => {
+            let (x, y) = transform(x, y);
+            PlayerEvent::MouseDown { x, y }
+        }
+        event => event,
+    }
 }
 
 #[derive(Collect)]
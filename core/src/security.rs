@@ -0,0 +1,773 @@
+//! Flash Player's security model for network access.
+
+use crate::backend::navigator::{NavigatorBackend, OwnedFuture, RequestOptions};
+use crate::config::CrossDomainMode;
+use crate::loader::Error;
+use crate::tag_utils::SwfMovie;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use url::Url;
+
+/// The sandbox that a movie is placed in, which decides whether it may
+/// access local files or the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SandboxType {
+    /// Movies loaded from the network, which may not access local files.
+    Remote,
+
+    /// Local movies that may access other local files, but not the network.
+    LocalWithFile,
+
+    /// Local movies that may access the network, but not local files.
+    LocalWithNetwork,
+
+    /// Local movies that may access anything.
+    LocalTrusted,
+}
+
+impl SandboxType {
+    /// The sandbox of a movie loaded from `url`.
+    ///
+    /// Local movies are placed in the sandbox asked for by their
+    /// `FileAttributes` tag. Movies made before SWF 8 introduced the sandbox
+    /// keep their unrestricted access.
+    pub fn for_movie(url: Option<&str>, swf_version: u8, use_network_sandbox: bool) -> Self {
+        let is_local = match url.map(Url::parse) {
+            Some(Ok(url)) => url.scheme() == "file",
+            _ => false,
+        };
+
+        if !is_local {
+            SandboxType::Remote
+        } else if swf_version < 8 {
+            SandboxType::LocalTrusted
+        } else if use_network_sandbox {
+            SandboxType::LocalWithNetwork
+        } else {
+            SandboxType::LocalWithFile
+        }
+    }
+
+    /// Whether movies in this sandbox may access `url`.
+    pub fn allows(self, url: &Url) -> bool {
+        match (self, url.scheme()) {
+            (SandboxType::LocalTrusted, _) => true,
+            (SandboxType::LocalWithFile, scheme) => !is_network_scheme(scheme),
+            (SandboxType::Remote, scheme) | (SandboxType::LocalWithNetwork, scheme) => {
+                scheme != "file"
+            }
+        }
+    }
+}
+
+impl fmt::Display for SandboxType {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match self {
+            SandboxType::Remote => "remote",
+            SandboxType::LocalWithFile => "localWithFile",
+            SandboxType::LocalWithNetwork => "localWithNetwork",
+            SandboxType::LocalTrusted => "localTrusted",
+        })
+    }
+}
+
+impl FromStr for SandboxType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "remote" => Ok(SandboxType::Remote),
+            "localwithfile" => Ok(SandboxType::LocalWithFile),
+            "localwithnetwork" => Ok(SandboxType::LocalWithNetwork),
+            "localtrusted" => Ok(SandboxType::LocalTrusted),
+            _ => Err(format!("Unknown sandbox type: {}", s)),
+        }
+    }
+}
+
+/// The security state of a player.
+///
+/// A movie may only load data from another domain if that domain's
+/// `crossdomain.xml` policy file allows it. Policy files are fetched on the
+/// first request to each domain, and cached for the lifetime of the player.
+/// Requests made while a policy file is still being fetched wait for it,
+/// rather than fetching it again.
+pub struct Security {
+    /// Which requests to other domains are allowed.
+    cross_domain_mode: CrossDomainMode,
+
+    /// The policy files fetched or being fetched so far, keyed by URL.
+    policy_files: Arc<Mutex<HashMap<String, PolicyFile>>>,
+
+    /// The sandbox to place every movie in, instead of the one it asks for.
+    sandbox_type_override: Option<SandboxType>,
+}
+
+impl Security {
+    pub fn new() -> Self {
+        Self {
+            cross_domain_mode: Default::default(),
+            policy_files: Default::default(),
+            sandbox_type_override: None,
+        }
+    }
+
+    pub fn cross_domain_mode(&self) -> CrossDomainMode {
+        self.cross_domain_mode
+    }
+
+    pub fn set_cross_domain_mode(&mut self, cross_domain_mode: CrossDomainMode) {
+        self.cross_domain_mode = cross_domain_mode;
+    }
+
+    pub fn sandbox_type_override(&self) -> Option<SandboxType> {
+        self.sandbox_type_override
+    }
+
+    /// Place every movie in the given sandbox, or `None` to use the sandbox
+    /// each movie asks for.
+    pub fn set_sandbox_type_override(&mut self, sandbox_type: Option<SandboxType>) {
+        self.sandbox_type_override = sandbox_type;
+    }
+
+    /// The sandbox that a movie is placed in.
+    pub fn sandbox_type(&self, movie: &SwfMovie) -> SandboxType {
+        self.sandbox_type_override
+            .unwrap_or_else(|| movie.sandbox_type())
+    }
+
+    /// Whether `movie` may fetch or navigate to `url`, according to its
+    /// sandbox.
+    ///
+    /// Relative URLs are resolved against the movie's URL.
+    pub fn allows_access(&self, movie: Option<&SwfMovie>, url: &str) -> bool {
+        let movie = match movie {
+            Some(movie) => movie,
+            None => return true,
+        };
+        let movie_url = movie.url().and_then(|movie_url| Url::parse(movie_url).ok());
+        let url = match (Url::parse(url), movie_url) {
+            (Ok(url), _) => url,
+            (Err(_), Some(movie_url)) => match movie_url.join(url) {
+                Ok(url) => url,
+                Err(_) => return true,
+            },
+            (Err(_), None) => return true,
+        };
+
+        let sandbox_type = self.sandbox_type(movie);
+        if sandbox_type.allows(&url) {
+            true
+        } else {
+            log::warn!(
+                "Access to {} was blocked by the {} sandbox of {}",
+                url,
+                sandbox_type,
+                movie.url().unwrap_or_default()
+            );
+            false
+        }
+    }
+
+    /// Fetch a movie on behalf of `movie`.
+    ///
+    /// Movies don't need a policy file to be loaded from another domain, but
+    /// are still subject to the sandbox of the movie loading them, and can't
+    /// be loaded from other domains at all in `DenyCross` mode.
+    pub fn fetch_movie(
+        &self,
+        navigator: &dyn NavigatorBackend,
+        movie: Option<&SwfMovie>,
+        url: &str,
+        options: RequestOptions,
+    ) -> OwnedFuture<Vec<u8>, Error> {
+        if !self.allows_access(movie, url) {
+            return sandbox_error(url);
+        }
+        if self.cross_domain_mode == CrossDomainMode::DenyCross
+            && cross_domain_request(movie, url).is_some()
+        {
+            return cross_domain_error(url);
+        }
+
+        navigator.fetch(url, options)
+    }
+
+    /// Fetch data on behalf of `movie`.
+    ///
+    /// If the data is on another domain, the request is only sent once that
+    /// domain's policy file has been found to allow the movie's domain.
+    pub fn fetch(
+        &self,
+        navigator: &dyn NavigatorBackend,
+        movie: Option<&SwfMovie>,
+        url: &str,
+        options: RequestOptions,
+    ) -> OwnedFuture<Vec<u8>, Error> {
+        if !self.allows_access(movie, url) {
+            return sandbox_error(url);
+        }
+
+        let (movie_url, parsed_url) = match cross_domain_request(movie, url) {
+            Some(request) => request,
+            None => return navigator.fetch(url, options),
+        };
+        match self.cross_domain_mode {
+            CrossDomainMode::AllowAll => return navigator.fetch(url, options),
+            CrossDomainMode::DenyCross => return cross_domain_error(url),
+            CrossDomainMode::RespectPolicy => {}
+        }
+
+        // Backends don't send a request until its future is polled, so this
+        // can be safely dropped if the policy file doesn't allow it.
+        let fetch = navigator.fetch(url, options);
+        let url = parsed_url;
+
+        let policy_url = match url.join("/crossdomain.xml") {
+            Ok(policy_url) => policy_url.to_string(),
+            Err(_) => return fetch,
+        };
+        let domain = movie_url.host_str().unwrap_or_default().to_string();
+        let is_secure = movie_url.scheme() == "https" || url.scheme() == "http";
+
+        // Only the first request to claim the policy file fetches it, but every
+        // request has a fetch ready in case the one before it is dropped.
+        let policy = policy_file(
+            self.policy_files.clone(),
+            policy_url.clone(),
+            navigator.fetch(&policy_url, RequestOptions::get()),
+        );
+
+        Box::pin(async move {
+            let policy = policy.await;
+
+            if !policy.allows(&domain, is_secure) {
+                log::warn!(
+                    "Request to {} was blocked by the cross-domain policy {}",
+                    url,
+                    policy_url
+                );
+                return Err(Error::FetchError(format!(
+                    "{} is not allowed to access {}",
+                    domain, url
+                )));
+            }
+
+            fetch.await
+        })
+    }
+}
+
+impl Default for Security {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cached policy file.
+enum PolicyFile {
+    /// The policy file is being fetched by one request, and these are the
+    /// requests waiting for it.
+    Pending(Vec<Waker>),
+
+    /// The policy file has been fetched.
+    Loaded(Arc<CrossDomainPolicy>),
+}
+
+/// Wait for the policy file at `policy_url` to be fetched, using
+/// `policy_fetch` to fetch it if no other request is doing so.
+async fn policy_file(
+    policy_files: Arc<Mutex<HashMap<String, PolicyFile>>>,
+    policy_url: String,
+    policy_fetch: OwnedFuture<Vec<u8>, Error>,
+) -> Arc<CrossDomainPolicy> {
+    let claim = ClaimPolicyFile {
+        policy_files: policy_files.clone(),
+        policy_url: policy_url.clone(),
+    };
+    if let Some(policy) = claim.await {
+        return policy;
+    }
+
+    let mut claim = PolicyFileClaim {
+        policy_files,
+        policy_url,
+        is_finished: false,
+    };
+    let policy = Arc::new(match policy_fetch.await {
+        Ok(data) => CrossDomainPolicy::parse(&data),
+        Err(_) => CrossDomainPolicy::default(),
+    });
+    claim.finish(policy.clone());
+    policy
+}
+
+/// A future that resolves to a policy file once another request has fetched
+/// it, or to `None` once this request has claimed the policy file to fetch
+/// it itself.
+struct ClaimPolicyFile {
+    policy_files: Arc<Mutex<HashMap<String, PolicyFile>>>,
+    policy_url: String,
+}
+
+impl Future for ClaimPolicyFile {
+    type Output = Option<Arc<CrossDomainPolicy>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut policy_files = self.policy_files.lock().unwrap();
+        match policy_files.get_mut(&self.policy_url) {
+            Some(PolicyFile::Loaded(policy)) => Poll::Ready(Some(policy.clone())),
+            Some(PolicyFile::Pending(wakers)) => {
+                if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+            None => {
+                policy_files.insert(self.policy_url.clone(), PolicyFile::Pending(vec![]));
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+/// A request's claim on fetching a policy file.
+///
+/// If the request is dropped before the policy file is fetched, the claim is
+/// given up, so that one of the waiting requests can fetch it instead.
+struct PolicyFileClaim {
+    policy_files: Arc<Mutex<HashMap<String, PolicyFile>>>,
+    policy_url: String,
+    is_finished: bool,
+}
+
+impl PolicyFileClaim {
+    /// Cache the fetched policy file, and wake the requests waiting for it.
+    fn finish(&mut self, policy: Arc<CrossDomainPolicy>) {
+        self.is_finished = true;
+        let previous = self
+            .policy_files
+            .lock()
+            .unwrap()
+            .insert(self.policy_url.clone(), PolicyFile::Loaded(policy));
+        if let Some(PolicyFile::Pending(wakers)) = previous {
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+}
+
+impl Drop for PolicyFileClaim {
+    fn drop(&mut self) {
+        if self.is_finished {
+            return;
+        }
+
+        let removed = self.policy_files.lock().unwrap().remove(&self.policy_url);
+        if let Some(PolicyFile::Pending(wakers)) = removed {
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+}
+
+fn is_network_url(url: &Url) -> bool {
+    is_network_scheme(url.scheme())
+}
+
+fn is_network_scheme(scheme: &str) -> bool {
+    matches!(scheme, "http" | "https")
+}
+
+/// The URLs of a movie and a network request it makes to another domain.
+///
+/// Returns `None` if the request is to the movie's own domain, or either of
+/// them isn't on the network.
+fn cross_domain_request(movie: Option<&SwfMovie>, url: &str) -> Option<(Url, Url)> {
+    let movie_url = Url::parse(movie?.url()?).ok()?;
+    let url = Url::parse(url).ok()?;
+    if is_network_url(&movie_url) && is_network_url(&url) && movie_url.origin() != url.origin() {
+        Some((movie_url, url))
+    } else {
+        None
+    }
+}
+
+/// A fetch that fails because requests to other domains are denied.
+fn cross_domain_error(url: &str) -> OwnedFuture<Vec<u8>, Error> {
+    log::warn!("Request to {} was blocked, as it is on another domain", url);
+    let error = Error::FetchError(format!("{} is on another domain", url));
+    Box::pin(async move { Err(error) })
+}
+
+/// A fetch that fails because the sandbox doesn't allow it.
+fn sandbox_error(url: &str) -> OwnedFuture<Vec<u8>, Error> {
+    let error = Error::FetchError(format!("{} is outside of the movie's sandbox", url));
+    Box::pin(async move { Err(error) })
+}
+
+/// A parsed `crossdomain.xml` policy file.
+///
+/// A missing or invalid policy file allows nothing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrossDomainPolicy {
+    allow_access_from: Vec<AllowAccessFrom>,
+}
+
+/// An `<allow-access-from>` entry of a policy file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct AllowAccessFrom {
+    /// The allowed domain, which may start with a `*` wildcard.
+    domain: String,
+
+    /// Whether movies on HTTP may access data on HTTPS.
+    secure: bool,
+}
+
+impl CrossDomainPolicy {
+    pub fn parse(data: &[u8]) -> Self {
+        let mut policy = Self::default();
+        let mut reader = Reader::from_reader(data);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event(&mut buf) {
+                Ok(Event::Start(element)) | Ok(Event::Empty(element)) => {
+                    let attribute = |name: &[u8]| {
+                        element
+                            .attributes()
+                            .filter_map(Result::ok)
+                            .find(|attribute| attribute.key == name)
+                            .map(|attribute| String::from_utf8_lossy(&attribute.value).into_owned())
+                    };
+
+                    match element.name() {
+                        b"site-control" => {
+                            if attribute(b"permitted-cross-domain-policies").as_deref()
+                                == Some("none")
+                            {
+                                return Self::default();
+                            }
+                        }
+                        b"allow-access-from" => {
+                            if let Some(domain) = attribute(b"domain") {
+                                let secure = attribute(b"secure").as_deref() != Some("false");
+                                policy.allow_access_from.push(AllowAccessFrom {
+                                    domain: domain.to_ascii_lowercase(),
+                                    secure,
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(_) => return Self::default(),
+            }
+            buf.clear();
+        }
+
+        policy
+    }
+
+    /// Whether movies on `domain` may access data covered by this policy.
+    ///
+    /// `is_secure` is `false` if the movie is on HTTP and the data is on
+    /// HTTPS, which must be explicitly allowed.
+    pub fn allows(&self, domain: &str, is_secure: bool) -> bool {
+        let domain = domain.to_ascii_lowercase();
+        self.allow_access_from.iter().any(|entry| {
+            let matches = if entry.domain == "*" {
+                true
+            } else if let Some(suffix) = entry.domain.strip_prefix('*') {
+                domain.ends_with(suffix) || Some(domain.as_str()) == suffix.strip_prefix('.')
+            } else {
+                entry.domain == domain
+            };
+            matches && (is_secure || !entry.secure)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::navigator::{NavigationMethod, NullExecutor};
+    use indexmap::IndexMap;
+    use std::borrow::Cow;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    /// A navigator that serves a policy allowing every domain, and records
+    /// the URLs it sends requests to.
+    ///
+    /// Each response takes one extra poll to arrive, so that other requests
+    /// can be made while it is in flight.
+    #[derive(Default)]
+    struct RecordingNavigator {
+        requests: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl NavigatorBackend for RecordingNavigator {
+        fn navigate_to_url(
+            &self,
+            _url: String,
+            _window: Option<String>,
+            _vars_method: Option<(NavigationMethod, IndexMap<String, String>)>,
+        ) {
+        }
+
+        fn fetch(&self, url: &str, _options: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
+            let requests = self.requests.clone();
+            let url = url.to_string();
+            Box::pin(async move {
+                requests.borrow_mut().push(url.clone());
+                YieldOnce(false).await;
+                if url.ends_with("/crossdomain.xml") {
+                    Ok(br#"<cross-domain-policy>
+                        <allow-access-from domain="*" />
+                    </cross-domain-policy>"#
+                        .to_vec())
+                } else {
+                    Ok(url.into_bytes())
+                }
+            })
+        }
+
+        fn time_since_launch(&mut self) -> Duration {
+            Duration::from_millis(0)
+        }
+
+        fn spawn_future(&mut self, _future: OwnedFuture<(), Error>) {}
+
+        fn resolve_relative_url<'a>(&mut self, url: &'a str) -> Cow<'a, str> {
+            url.into()
+        }
+
+        fn pre_process_url(&self, url: Url) -> Url {
+            url
+        }
+    }
+
+    /// A future that is pending the first time it is polled.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn concurrent_requests_share_a_policy_file() {
+        let mut security = Security::new();
+        security.set_cross_domain_mode(CrossDomainMode::RespectPolicy);
+        let navigator = RecordingNavigator::default();
+        let url = "https://games.example.com/game.swf".to_string();
+        let movie = SwfMovie::from_loaded_image(Some(url), None, 0, 1, 1);
+
+        let (mut executor, sender) = NullExecutor::new();
+        for url in &[
+            "https://cdn.example.com/a.txt",
+            "https://cdn.example.com/b.txt",
+        ] {
+            let fetch = security.fetch(&navigator, Some(&movie), url, RequestOptions::get());
+            sender
+                .send(Box::pin(async move {
+                    fetch.await?;
+                    Ok(())
+                }))
+                .unwrap();
+        }
+        executor.block_all().unwrap();
+
+        assert_eq!(
+            *navigator.requests.borrow(),
+            vec![
+                "https://cdn.example.com/crossdomain.xml",
+                "https://cdn.example.com/a.txt",
+                "https://cdn.example.com/b.txt",
+            ]
+        );
+    }
+
+    /// Run a fetch to completion, returning whether it succeeded.
+    fn succeeds(fetch: OwnedFuture<Vec<u8>, Error>) -> bool {
+        let (mut executor, sender) = NullExecutor::new();
+        sender
+            .send(Box::pin(async move {
+                fetch.await?;
+                Ok(())
+            }))
+            .unwrap();
+        executor.block_all().is_ok()
+    }
+
+    #[test]
+    fn cross_domain_modes_gate_requests() {
+        let url = "https://games.example.com/game.swf".to_string();
+        let movie = SwfMovie::from_loaded_image(Some(url), None, 0, 1, 1);
+        let movie = Some(&movie);
+        let own = "https://games.example.com/scores.txt";
+        let other = "https://cdn.example.com/scores.txt";
+        let options = RequestOptions::get;
+
+        // Other domains are allowed by default, without any policy file.
+        let mut security = Security::new();
+        assert_eq!(security.cross_domain_mode(), CrossDomainMode::AllowAll);
+        let navigator = RecordingNavigator::default();
+        assert!(succeeds(security.fetch(
+            &navigator,
+            movie,
+            other,
+            options()
+        )));
+        assert!(succeeds(security.fetch_movie(
+            &navigator,
+            movie,
+            other,
+            options()
+        )));
+        assert_eq!(*navigator.requests.borrow(), vec![other, other]);
+
+        // Movies from other domains don't need a policy file either.
+        security.set_cross_domain_mode(CrossDomainMode::RespectPolicy);
+        let navigator = RecordingNavigator::default();
+        assert!(succeeds(security.fetch_movie(
+            &navigator,
+            movie,
+            other,
+            options()
+        )));
+        assert_eq!(*navigator.requests.borrow(), vec![other]);
+
+        // Nothing is sent to other domains when they are denied, but the
+        // movie's own domain is still allowed.
+        security.set_cross_domain_mode(CrossDomainMode::DenyCross);
+        let navigator = RecordingNavigator::default();
+        assert!(!succeeds(security.fetch(
+            &navigator,
+            movie,
+            other,
+            options()
+        )));
+        assert!(!succeeds(security.fetch_movie(
+            &navigator,
+            movie,
+            other,
+            options()
+        )));
+        assert!(succeeds(security.fetch(&navigator, movie, own, options())));
+        assert!(succeeds(security.fetch_movie(
+            &navigator,
+            movie,
+            own,
+            options()
+        )));
+        assert_eq!(*navigator.requests.borrow(), vec![own, own]);
+    }
+
+    #[test]
+    fn cross_domain_requests_are_between_network_origins() {
+        let url = "https://games.example.com/game.swf".to_string();
+        let movie = SwfMovie::from_loaded_image(Some(url), None, 0, 1, 1);
+        let is_cross_domain = |url| cross_domain_request(Some(&movie), url).is_some();
+        assert!(!is_cross_domain("https://games.example.com/a/b.txt"));
+        assert!(is_cross_domain("https://cdn.example.com/b.txt"));
+        assert!(is_cross_domain("http://games.example.com/b.txt"));
+        assert!(is_cross_domain("https://games.example.com:8080/b.txt"));
+        assert!(!is_cross_domain("file:///games/b.txt"));
+        assert!(!is_cross_domain("b.txt"));
+        assert!(cross_domain_request(None, "https://cdn.example.com/b.txt").is_none());
+
+        let local = SwfMovie::from_loaded_image(Some("file:///game.swf".into()), None, 0, 1, 1);
+        assert!(cross_domain_request(Some(&local), "https://cdn.example.com/b.txt").is_none());
+    }
+
+    #[test]
+    fn parses_allowed_domains() {
+        let policy = CrossDomainPolicy::parse(
+            br#"<?xml version="1.0"?>
+            <cross-domain-policy>
+                <allow-access-from domain="games.example.com" />
+                <allow-access-from domain="*.partner.example" secure="false" />
+            </cross-domain-policy>"#,
+        );
+        assert!(policy.allows("games.example.com", true));
+        assert!(!policy.allows("other.example.com", true));
+        assert!(policy.allows("www.partner.example", true));
+        assert!(policy.allows("partner.example", true));
+        assert!(!policy.allows("notpartner.example", true));
+
+        // Only entries with `secure="false"` let HTTP movies access HTTPS data.
+        assert!(!policy.allows("games.example.com", false));
+        assert!(policy.allows("www.partner.example", false));
+    }
+
+    #[test]
+    fn site_control_none_allows_nothing() {
+        let policy = CrossDomainPolicy::parse(
+            br#"<cross-domain-policy>
+                <site-control permitted-cross-domain-policies="none" />
+                <allow-access-from domain="*" />
+            </cross-domain-policy>"#,
+        );
+        assert!(!policy.allows("example.com", true));
+    }
+
+    #[test]
+    fn sandboxes_follow_file_attributes() {
+        let local = Some("file:///games/game.swf");
+        let remote = Some("https://games.example.com/game.swf");
+        assert_eq!(
+            SandboxType::for_movie(remote, 10, false),
+            SandboxType::Remote
+        );
+        assert_eq!(
+            SandboxType::for_movie(local, 10, false),
+            SandboxType::LocalWithFile
+        );
+        assert_eq!(
+            SandboxType::for_movie(local, 10, true),
+            SandboxType::LocalWithNetwork
+        );
+        assert_eq!(
+            SandboxType::for_movie(local, 7, false),
+            SandboxType::LocalTrusted
+        );
+    }
+
+    #[test]
+    fn sandboxes_restrict_access() {
+        let file = Url::parse("file:///games/scores.txt").unwrap();
+        let network = Url::parse("https://games.example.com/scores.txt").unwrap();
+        assert!(!SandboxType::Remote.allows(&file));
+        assert!(SandboxType::Remote.allows(&network));
+        assert!(SandboxType::LocalWithFile.allows(&file));
+        assert!(!SandboxType::LocalWithFile.allows(&network));
+        assert!(!SandboxType::LocalWithNetwork.allows(&file));
+        assert!(SandboxType::LocalWithNetwork.allows(&network));
+        assert!(SandboxType::LocalTrusted.allows(&file));
+        assert!(SandboxType::LocalTrusted.allows(&network));
+    }
+
+    #[test]
+    fn invalid_policy_allows_nothing() {
+        let policy = CrossDomainPolicy::parse(b"<html><body>Not Found</html>");
+        assert!(!policy.allows("example.com", true));
+    }
+}
@@ -715,6 +715,53 @@ mod tests {
         }];
         assert_eq!(commands, expected);
     }
+
+    /// A morph shape from a square to a wider rectangle.
+    #[test]
+    fn morph_shape_ratios() {
+        fn rectangle(width: f64) -> swf::MorphShape {
+            swf::MorphShape {
+                shape_bounds: Default::default(),
+                edge_bounds: Default::default(),
+                fill_styles: FILL_STYLES.to_vec(),
+                line_styles: vec![],
+                shape: vec![
+                    ShapeRecord::StyleChange(swf::StyleChangeData {
+                        move_to: Some((Twips::from_pixels(0.0), Twips::from_pixels(0.0))),
+                        fill_style_0: None,
+                        fill_style_1: Some(1),
+                        line_style: None,
+                        new_styles: None,
+                    }),
+                    ShapeRecord::StraightEdge {
+                        delta_x: Twips::from_pixels(width),
+                        delta_y: Twips::from_pixels(0.0),
+                    },
+                    ShapeRecord::StraightEdge {
+                        delta_x: Twips::from_pixels(0.0),
+                        delta_y: Twips::from_pixels(100.0),
+                    },
+                    ShapeRecord::StraightEdge {
+                        delta_x: Twips::from_pixels(-width),
+                        delta_y: Twips::from_pixels(0.0),
+                    },
+                    ShapeRecord::StraightEdge {
+                        delta_x: Twips::from_pixels(0.0),
+                        delta_y: Twips::from_pixels(-100.0),
+                    },
+                ],
+            }
+        }
+
+        let start = rectangle(100.0);
+        let end = rectangle(300.0);
+        assert_eq!(morph_shape_at_ratio(&start, &end, 0).shape, start.shape);
+        assert_eq!(morph_shape_at_ratio(&start, &end, 65535).shape, end.shape);
+
+        let middle = morph_shape_at_ratio(&start, &end, 32768);
+        assert_eq!(middle.shape_bounds.x_max, Twips::from_pixels(200.0));
+        assert_eq!(middle.shape_bounds.y_max, Twips::from_pixels(100.0));
+    }
 }
 
 /* SHAPEFLAG HITTEST (point-in-contour)
@@ -1347,3 +1394,366 @@ pub fn swf_glyph_to_shape(glyph: &swf::Glyph) -> swf::Shape {
         shape: glyph.shape_records.clone(),
     }
 }
+
+/// Interpolates the start and end shapes of a DefineMorphShape tag into the
+/// shape shown at the given ratio, from 0 (the start shape) to 65535 (the end
+/// shape).
+pub fn morph_shape_at_ratio(
+    start_shape: &swf::MorphShape,
+    end_shape: &swf::MorphShape,
+    ratio: u16,
+) -> swf::Shape {
+    // Start shape is ratio 65535, end shape is ratio 0.
+    let b = f32::from(ratio) / 65535.0;
+    let a = 1.0 - b;
+    let fill_styles: Vec<FillStyle> = start_shape
+        .fill_styles
+        .iter()
+        .zip(end_shape.fill_styles.iter())
+        .map(|(start, end)| lerp_fill(start, end, a, b))
+        .collect();
+    let line_styles: Vec<LineStyle> = start_shape
+        .line_styles
+        .iter()
+        .zip(end_shape.line_styles.iter())
+        .map(|(start, end)| LineStyle {
+            width: lerp_twips(start.width, end.width, a, b),
+            color: lerp_color(&start.color, &end.color, a, b),
+            start_cap: start.start_cap,
+            end_cap: start.end_cap,
+            join_style: start.join_style,
+            fill_style: None,
+            allow_scale_x: start.allow_scale_x,
+            allow_scale_y: start.allow_scale_y,
+            is_pixel_hinted: start.is_pixel_hinted,
+            allow_close: start.allow_close,
+        })
+        .collect();
+
+    let mut records = Vec::with_capacity(start_shape.shape.len());
+    let mut start_iter = start_shape.shape.iter();
+    let mut end_iter = end_shape.shape.iter();
+    let mut start = start_iter.next();
+    let mut end = end_iter.next();
+    let mut start_x = Twips::zero();
+    let mut start_y = Twips::zero();
+    let mut end_x = Twips::zero();
+    let mut end_y = Twips::zero();
+    // TODO: Feels like this could be cleaned up a bit.
+    // We step through both the start records and end records, interpolating edges pairwise.
+    // Fill style/line style changes should only appear in the start records.
+    // However, StyleChangeRecord move_to can appear it both start and end records,
+    // and not necessarily in matching pairs; therefore, we have to keep track of the pen position
+    // in case one side is missing a move_to; it will implicitly use the last pen position.
+    while let (Some(s), Some(e)) = (start, end) {
+        match (s, e) {
+            (ShapeRecord::StyleChange(start_change), ShapeRecord::StyleChange(end_change)) => {
+                let mut style_change = start_change.clone();
+                if start_change.move_to != end_change.move_to {
+                    if let Some((s_x, s_y)) = start_change.move_to {
+                        start_x = s_x;
+                        start_y = s_y;
+                    }
+                    if let Some((e_x, e_y)) = end_change.move_to {
+                        end_x = e_x;
+                        end_y = e_y;
+                    }
+                    style_change.move_to = Some((
+                        lerp_twips(start_x, end_x, a, b),
+                        lerp_twips(start_y, end_y, a, b),
+                    ));
+                }
+                records.push(ShapeRecord::StyleChange(style_change));
+                start = start_iter.next();
+                end = end_iter.next();
+            }
+            (ShapeRecord::StyleChange(start_change), _) => {
+                let mut style_change = start_change.clone();
+                if let Some((s_x, s_y)) = start_change.move_to {
+                    start_x = s_x;
+                    start_y = s_y;
+                    style_change.move_to = Some((
+                        lerp_twips(start_x, end_x, a, b),
+                        lerp_twips(start_y, end_y, a, b),
+                    ));
+                }
+                records.push(ShapeRecord::StyleChange(style_change));
+                update_pos(&mut start_x, &mut start_y, s);
+                start = start_iter.next();
+            }
+            (_, ShapeRecord::StyleChange(end_change)) => {
+                let mut style_change = end_change.clone();
+                if let Some((e_x, e_y)) = end_change.move_to {
+                    end_x = e_x;
+                    end_y = e_y;
+                    style_change.move_to = Some((
+                        lerp_twips(start_x, end_x, a, b),
+                        lerp_twips(start_y, end_y, a, b),
+                    ));
+                }
+                records.push(ShapeRecord::StyleChange(style_change));
+                update_pos(&mut end_x, &mut end_y, s);
+                end = end_iter.next();
+                continue;
+            }
+            _ => {
+                records.push(lerp_edges(s, e, a, b));
+                update_pos(&mut start_x, &mut start_y, s);
+                update_pos(&mut end_x, &mut end_y, e);
+                start = start_iter.next();
+                end = end_iter.next();
+            }
+        }
+    }
+
+    let styles = swf::ShapeStyles {
+        fill_styles,
+        line_styles,
+    };
+
+    let bounds = calculate_shape_bounds(&records[..]);
+    swf::Shape {
+        version: 4,
+        id: 0,
+        shape_bounds: bounds.clone(),
+        edge_bounds: bounds,
+        has_fill_winding_rule: false,
+        has_non_scaling_strokes: false,
+        has_scaling_strokes: true,
+        styles,
+        shape: records,
+    }
+}
+
+fn update_pos(x: &mut Twips, y: &mut Twips, record: &swf::ShapeRecord) {
+    match record {
+        ShapeRecord::StraightEdge { delta_x, delta_y } => {
+            *x += *delta_x;
+            *y += *delta_y;
+        }
+        ShapeRecord::CurvedEdge {
+            control_delta_x,
+            control_delta_y,
+            anchor_delta_x,
+            anchor_delta_y,
+        } => {
+            *x += *control_delta_x + *anchor_delta_x;
+            *y += *control_delta_y + *anchor_delta_y;
+        }
+        ShapeRecord::StyleChange(ref style_change) => {
+            if let Some((move_x, move_y)) = style_change.move_to {
+                *x = move_x;
+                *y = move_y;
+            }
+        }
+    }
+}
+
+// Interpolation functions
+// These interpolate between two SWF shape structures.
+// a + b should = 1.0
+
+fn lerp_color(start: &swf::Color, end: &swf::Color, a: f32, b: f32) -> swf::Color {
+    // f32 -> u8 cast is defined to saturate for out of bounds values,
+    // so we don't have to worry about clamping.
+    swf::Color {
+        r: (a * f32::from(start.r) + b * f32::from(end.r)) as u8,
+        g: (a * f32::from(start.g) + b * f32::from(end.g)) as u8,
+        b: (a * f32::from(start.b) + b * f32::from(end.b)) as u8,
+        a: (a * f32::from(start.a) + b * f32::from(end.a)) as u8,
+    }
+}
+
+fn lerp_twips(start: Twips, end: Twips, a: f32, b: f32) -> Twips {
+    Twips::new((start.get() as f32 * a + end.get() as f32 * b) as i32)
+}
+
+fn lerp_fill(start: &swf::FillStyle, end: &swf::FillStyle, a: f32, b: f32) -> swf::FillStyle {
+    match (start, end) {
+        // Color-to-color
+        (FillStyle::Color(start), FillStyle::Color(end)) => {
+            FillStyle::Color(lerp_color(start, end, a, b))
+        }
+
+        // Bitmap-to-bitmap
+        // ID should be the same.
+        (
+            FillStyle::Bitmap {
+                id: start_id,
+                matrix: start,
+                is_smoothed,
+                is_repeating,
+            },
+            FillStyle::Bitmap { matrix: end, .. },
+        ) => FillStyle::Bitmap {
+            id: *start_id,
+            matrix: lerp_matrix(start, end, a, b),
+            is_smoothed: *is_smoothed,
+            is_repeating: *is_repeating,
+        },
+
+        // Linear-to-linear
+        (FillStyle::LinearGradient(start), FillStyle::LinearGradient(end)) => {
+            FillStyle::LinearGradient(lerp_gradient(start, end, a, b))
+        }
+
+        // Radial-to-radial
+        (FillStyle::RadialGradient(start), FillStyle::RadialGradient(end)) => {
+            FillStyle::RadialGradient(lerp_gradient(start, end, a, b))
+        }
+
+        // Focal gradients also interpolate focal point.
+        (
+            FillStyle::FocalGradient {
+                gradient: start,
+                focal_point: start_focal,
+            },
+            FillStyle::FocalGradient {
+                gradient: end,
+                focal_point: end_focal,
+            },
+        ) => FillStyle::FocalGradient {
+            gradient: lerp_gradient(start, end, a, b),
+            focal_point: a * start_focal + b * end_focal,
+        },
+
+        // All other combinations should not occur, because SWF stores the start/end fill as the same type, always.
+        // If you happened to make, say, a solid color-to-radial gradient tween in the IDE, this would get baked down into
+        // a radial-to-radial gradient on export.
+        _ => {
+            log::warn!(
+                "Unexpected morph shape fill style combination: {:#?}, {:#?}",
+                start,
+                end
+            );
+            start.clone()
+        }
+    }
+}
+
+fn lerp_edges(
+    start: &swf::ShapeRecord,
+    end: &swf::ShapeRecord,
+    a: f32,
+    b: f32,
+) -> swf::ShapeRecord {
+    match (start, end) {
+        (
+            &ShapeRecord::StraightEdge {
+                delta_x: start_dx,
+                delta_y: start_dy,
+            },
+            &ShapeRecord::StraightEdge {
+                delta_x: end_dx,
+                delta_y: end_dy,
+            },
+        ) => ShapeRecord::StraightEdge {
+            delta_x: lerp_twips(start_dx, end_dx, a, b),
+            delta_y: lerp_twips(start_dy, end_dy, a, b),
+        },
+
+        (
+            &ShapeRecord::CurvedEdge {
+                control_delta_x: start_cdx,
+                control_delta_y: start_cdy,
+                anchor_delta_x: start_adx,
+                anchor_delta_y: start_ady,
+            },
+            &ShapeRecord::CurvedEdge {
+                control_delta_x: end_cdx,
+                control_delta_y: end_cdy,
+                anchor_delta_x: end_adx,
+                anchor_delta_y: end_ady,
+            },
+        ) => ShapeRecord::CurvedEdge {
+            control_delta_x: lerp_twips(start_cdx, end_cdx, a, b),
+            control_delta_y: lerp_twips(start_cdy, end_cdy, a, b),
+            anchor_delta_x: lerp_twips(start_adx, end_adx, a, b),
+            anchor_delta_y: lerp_twips(start_ady, end_ady, a, b),
+        },
+
+        (
+            &ShapeRecord::StraightEdge {
+                delta_x: start_dx,
+                delta_y: start_dy,
+            },
+            &ShapeRecord::CurvedEdge {
+                control_delta_x: end_cdx,
+                control_delta_y: end_cdy,
+                anchor_delta_x: end_adx,
+                anchor_delta_y: end_ady,
+            },
+        ) => {
+            let start_cdx = start_dx / 2;
+            let start_cdy = start_dy / 2;
+            let start_adx = start_cdx;
+            let start_ady = start_cdy;
+            ShapeRecord::CurvedEdge {
+                control_delta_x: lerp_twips(start_cdx, end_cdx, a, b),
+                control_delta_y: lerp_twips(start_cdy, end_cdy, a, b),
+                anchor_delta_x: lerp_twips(start_adx, end_adx, a, b),
+                anchor_delta_y: lerp_twips(start_ady, end_ady, a, b),
+            }
+        }
+
+        (
+            &ShapeRecord::CurvedEdge {
+                control_delta_x: start_cdx,
+                control_delta_y: start_cdy,
+                anchor_delta_x: start_adx,
+                anchor_delta_y: start_ady,
+            },
+            &ShapeRecord::StraightEdge {
+                delta_x: end_dx,
+                delta_y: end_dy,
+            },
+        ) => {
+            let end_cdx = end_dx / 2;
+            let end_cdy = end_dy / 2;
+            let end_adx = end_cdx;
+            let end_ady = end_cdy;
+            ShapeRecord::CurvedEdge {
+                control_delta_x: lerp_twips(start_cdx, end_cdx, a, b),
+                control_delta_y: lerp_twips(start_cdy, end_cdy, a, b),
+                anchor_delta_x: lerp_twips(start_adx, end_adx, a, b),
+                anchor_delta_y: lerp_twips(start_ady, end_ady, a, b),
+            }
+        }
+        _ => unreachable!("{:?} {:?}", start, end),
+    }
+}
+
+fn lerp_matrix(start: &swf::Matrix, end: &swf::Matrix, a: f32, b: f32) -> swf::Matrix {
+    // TODO: Lerping a matrix element-wise is geometrically wrong,
+    // but I doubt Flash is decomposing the matrix into scale-rotate-translate?
+    swf::Matrix {
+        a: start.a * a + end.a * b,
+        b: start.b * a + end.b * b,
+        c: start.c * a + end.c * b,
+        d: start.d * a + end.d * b,
+        tx: lerp_twips(start.tx, end.tx, a, b),
+        ty: lerp_twips(start.ty, end.ty, a, b),
+    }
+}
+
+fn lerp_gradient(start: &swf::Gradient, end: &swf::Gradient, a: f32, b: f32) -> swf::Gradient {
+    use swf::{Gradient, GradientRecord};
+    // Morph gradients are guaranteed to have the same number of records in the start/end gradient.
+    debug_assert!(start.records.len() == end.records.len());
+    let records: Vec<GradientRecord> = start
+        .records
+        .iter()
+        .zip(end.records.iter())
+        .map(|(start, end)| swf::GradientRecord {
+            ratio: (f32::from(start.ratio) * a + f32::from(end.ratio) * b) as u8,
+            color: lerp_color(&start.color, &end.color, a, b),
+        })
+        .collect();
+
+    Gradient {
+        matrix: lerp_matrix(&start.matrix, &end.matrix, a, b),
+        spread: start.spread,
+        interpolation: start.interpolation,
+        records,
+    }
+}
@@ -0,0 +1,274 @@
+//! Player settings that are remembered per site.
+//!
+//! As with the settings manager of Flash Player, the volume, quality and local
+//! storage allowance chosen by the user apply to every movie from the same
+//! site, as does whether moving objects are drawn between frames. They are
+//! kept in the storage backend, next to the site's shared objects.
+
+use crate::backend::storage::StorageBackend;
+use crate::config::StageQuality;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use url::Url;
+
+/// The settings of a single site.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SiteSettings {
+    /// The volume of every sound, from 0.0 to 1.0.
+    pub volume: f32,
+
+    /// The quality the stage starts with.
+    pub quality: StageQuality,
+
+    /// How much data the shared objects of the site may store in total, in
+    /// bytes, or `None` if there is no limit.
+    pub storage_limit: Option<usize>,
+
+    /// Whether objects that scripts move are drawn moving smoothly between
+    /// frames.
+    pub frame_interpolation: bool,
+}
+
+impl Default for SiteSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            quality: StageQuality::default(),
+            storage_limit: None,
+            frame_interpolation: false,
+        }
+    }
+}
+
+impl SiteSettings {
+    /// The name of the site that a movie loaded from `url` belongs to.
+    ///
+    /// Local files all belong to `localhost`, as they do for shared objects.
+    pub fn site_for_url(url: Option<&str>) -> String {
+        url.and_then(|url| Url::parse(url).ok())
+            .filter(|url| url.scheme() != "file")
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| "localhost".to_string())
+    }
+
+    /// The name of the site that owns a shared object, given the full name
+    /// it's stored under.
+    pub fn site_for_shared_object(name: &str) -> &str {
+        name.split('/').next().unwrap_or_default()
+    }
+
+    /// The storage key the settings of a site are kept under.
+    ///
+    /// `#` can't appear in the names of shared objects, so this doesn't clash
+    /// with any of them.
+    fn storage_key(site: &str) -> String {
+        format!("{}/#settings", site)
+    }
+
+    /// Load the settings of a site, or the defaults if it has none.
+    pub fn load(storage: &dyn StorageBackend, site: &str) -> Self {
+        storage
+            .get(&Self::storage_key(site))
+            .map(|data| Self::parse(&String::from_utf8_lossy(&data)))
+            .unwrap_or_default()
+    }
+
+    /// Save these as the settings of a site.
+    pub fn save(&self, storage: &mut dyn StorageBackend, site: &str) -> bool {
+        storage.put(&Self::storage_key(site), self.serialize().as_bytes())
+    }
+
+    /// Whether the shared objects of the site may store the given number of
+    /// bytes in total.
+    pub fn allows_storage(&self, size: usize) -> bool {
+        self.storage_limit.map_or(true, |limit| size <= limit)
+    }
+
+    /// Parse saved settings, one `key=value` pair per line. Unknown or
+    /// invalid entries keep their defaults.
+    fn parse(data: &str) -> Self {
+        let mut settings = Self::default();
+        for (key, value) in data.lines().filter_map(|line| {
+            let mut parts = line.splitn(2, '=');
+            Some((parts.next()?.trim(), parts.next()?.trim()))
+        }) {
+            match key {
+                "volume" => {
+                    if let Ok(volume) = f32::from_str(value) {
+                        settings.volume = volume.max(0.0).min(1.0);
+                    }
+                }
+                "quality" => {
+                    if let Ok(quality) = StageQuality::from_str(value) {
+                        settings.quality = quality;
+                    }
+                }
+                "storage_limit" => {
+                    if value == "unlimited" {
+                        settings.storage_limit = None;
+                    } else if let Ok(limit) = usize::from_str(value) {
+                        settings.storage_limit = Some(limit);
+                    }
+                }
+                "frame_interpolation" => {
+                    if let Ok(frame_interpolation) = bool::from_str(value) {
+                        settings.frame_interpolation = frame_interpolation;
+                    }
+                }
+                _ => (),
+            }
+        }
+        settings
+    }
+
+    fn serialize(&self) -> String {
+        let storage_limit = self
+            .storage_limit
+            .map_or_else(|| "unlimited".to_string(), |limit| limit.to_string());
+        format!(
+            "volume={}\nquality={}\nstorage_limit={}\nframe_interpolation={}\n",
+            self.volume,
+            self.quality.as_str().to_ascii_lowercase(),
+            storage_limit,
+            self.frame_interpolation
+        )
+    }
+}
+
+/// How much data each shared object of a site stores, so that the storage
+/// limit applies to the site as a whole.
+///
+/// Storage backends can't list their keys, so this is kept in the storage
+/// next to the site's settings, and updated whenever a shared object is
+/// written or cleared.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StorageUsage {
+    /// The size in bytes of each shared object, by its full name.
+    sizes: BTreeMap<String, usize>,
+}
+
+impl StorageUsage {
+    fn storage_key(site: &str) -> String {
+        format!("{}/#usage", site)
+    }
+
+    /// Load the storage usage of a site.
+    pub fn load(storage: &dyn StorageBackend, site: &str) -> Self {
+        let data = match storage.get(&Self::storage_key(site)) {
+            Some(data) => data,
+            None => return Self::default(),
+        };
+
+        // Names can contain `=`, but sizes can't.
+        let sizes = String::from_utf8_lossy(&data)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.rsplitn(2, '=');
+                let size = usize::from_str(parts.next()?).ok()?;
+                Some((parts.next()?.to_string(), size))
+            })
+            .collect();
+        Self { sizes }
+    }
+
+    /// Save this as the storage usage of a site.
+    pub fn save(&self, storage: &mut dyn StorageBackend, site: &str) -> bool {
+        let data: String = self
+            .sizes
+            .iter()
+            .map(|(name, size)| format!("{}={}\n", name, size))
+            .collect();
+        storage.put(&Self::storage_key(site), data.as_bytes())
+    }
+
+    /// The total size of the site's shared objects if the named one stored
+    /// `size` bytes instead of its current data.
+    pub fn total_with(&self, name: &str, size: usize) -> usize {
+        self.sizes
+            .iter()
+            .filter(|(other, _)| other.as_str() != name)
+            .map(|(_, size)| *size)
+            .sum::<usize>()
+            + size
+    }
+
+    /// Record the size of a shared object, or that it was removed if the size
+    /// is zero.
+    pub fn set(&mut self, name: &str, size: usize) {
+        if size == 0 {
+            self.sizes.remove(name);
+        } else {
+            self.sizes.insert(name.to_string(), size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::MemoryStorageBackend;
+
+    #[test]
+    fn site_for_url() {
+        assert_eq!(
+            SiteSettings::site_for_url(Some("https://example.com/games/test.swf")),
+            "example.com"
+        );
+        assert_eq!(
+            SiteSettings::site_for_url(Some("file:///C:/games/test.swf")),
+            "localhost"
+        );
+        assert_eq!(SiteSettings::site_for_url(None), "localhost");
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut storage = MemoryStorageBackend::default();
+        let settings = SiteSettings {
+            volume: 0.5,
+            quality: StageQuality::Low,
+            storage_limit: None,
+            frame_interpolation: true,
+        };
+        assert!(settings.save(&mut storage, "example.com"));
+        assert_eq!(SiteSettings::load(&storage, "example.com"), settings);
+        assert_eq!(
+            SiteSettings::load(&storage, "example.org"),
+            SiteSettings::default()
+        );
+    }
+
+    #[test]
+    fn storage_limit() {
+        assert!(SiteSettings::default().allows_storage(usize::MAX));
+
+        let limited = SiteSettings {
+            storage_limit: Some(100),
+            ..Default::default()
+        };
+        assert!(limited.allows_storage(100));
+        assert!(!limited.allows_storage(101));
+    }
+
+    #[test]
+    fn storage_usage_is_per_site() {
+        let mut storage = MemoryStorageBackend::default();
+        let mut usage = StorageUsage::default();
+        usage.set("example.com/game/save", 60);
+        usage.set("example.com/game/=odd=", 30);
+        assert!(usage.save(&mut storage, "example.com"));
+
+        let usage = StorageUsage::load(&storage, "example.com");
+        assert_eq!(usage.total_with("example.com/game/scores", 20), 110);
+        // Rewriting an object replaces its old size.
+        assert_eq!(usage.total_with("example.com/game/save", 20), 50);
+        assert_eq!(
+            StorageUsage::load(&storage, "example.org"),
+            StorageUsage::default()
+        );
+        assert_eq!(
+            SiteSettings::site_for_shared_object("example.com/game/save"),
+            "example.com"
+        );
+    }
+}
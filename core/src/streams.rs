@@ -0,0 +1,790 @@
+//! Playback of externally loaded FLV streams.
+
+use crate::avm1::activation::{Activation as Avm1Activation, ActivationIdentifier};
+use crate::avm1::globals::net_stream::script_data_to_value;
+use crate::avm1::property::Attribute;
+use crate::avm1::{Object as Avm1Object, ScriptObject, TObject, Value as Avm1Value};
+use crate::backend::audio::SoundInstanceHandle;
+use crate::backend::navigator::{FetchProgress, RequestOptions};
+use crate::backend::render::BitmapInfo;
+use crate::backend::video::{EncodedFrame, VideoStreamHandle};
+use crate::context::{ActionType, UpdateContext};
+use crate::display_object::DisplayObject;
+use crate::flv::{FlvReader, FlvTagData, ScriptData, ScriptDataValue, VideoFrameType};
+use crate::tag_utils::SwfSlice;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::sync::Arc;
+use swf::{AudioCompression, SoundStreamHead, TagCode, VideoCodec, VideoDeblocking};
+
+/// How much of a stream has to be downloaded ahead of the playback position
+/// for playback to start, in milliseconds. This is the default
+/// `NetStream.bufferTime`.
+const BUFFER_TIME: f64 = 100.0;
+
+/// Keeps track of the streams that are currently playing.
+#[derive(Collect, Default)]
+#[collect(no_drop)]
+pub struct StreamManager<'gc> {
+    active_streams: Vec<NetStream<'gc>>,
+}
+
+impl<'gc> StreamManager<'gc> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Advance all playing streams by one frame.
+    pub fn tick(context: &mut UpdateContext<'_, 'gc, '_>) {
+        let frame_time = 1000.0 / *context.frame_rate;
+        for stream in context.stream_manager.active_streams.clone() {
+            stream.tick(context, frame_time);
+        }
+    }
+
+    fn activate(&mut self, stream: NetStream<'gc>) {
+        if !self
+            .active_streams
+            .iter()
+            .any(|other| NetStream::ptr_eq(*other, stream))
+        {
+            self.active_streams.push(stream);
+        }
+    }
+
+    fn deactivate(&mut self, stream: NetStream<'gc>) {
+        self.active_streams
+            .retain(|other| !NetStream::ptr_eq(*other, stream));
+    }
+}
+
+/// The playback state of a `NetStream`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+pub enum PlaybackState {
+    /// Nothing has been played yet, or the stream was closed.
+    Idle,
+
+    /// The file being played is being downloaded, and not enough of it has
+    /// arrived to start playing.
+    Loading,
+
+    /// Playback has caught up with the download, and is waiting for more of
+    /// the file to arrive.
+    Buffering,
+
+    /// The file is being played, while the rest of it is downloaded.
+    Playing,
+
+    /// Playback has reached the end of the file.
+    Finished,
+}
+
+/// A stream of FLV data that is being played, as created by `NetStream`.
+///
+/// Audio is mixed into the stage's sound, while video frames are shown by any
+/// `Video` that the stream is attached to.
+#[derive(Clone, Copy, Collect, Debug)]
+#[collect(no_drop)]
+pub struct NetStream<'gc>(GcCell<'gc, NetStreamData<'gc>>);
+
+#[derive(Collect, Debug)]
+#[collect(no_drop)]
+pub struct NetStreamData<'gc> {
+    /// The AVM1 `NetStream` object that receives this stream's events.
+    avm1_object: Option<Avm1Object<'gc>>,
+
+    state: PlaybackState,
+
+    /// Whether playback has been paused with `NetStream.pause`.
+    paused: bool,
+
+    /// The playback position in milliseconds.
+    time: f64,
+
+    /// The duration of the stream in milliseconds.
+    ///
+    /// This is the duration given by the stream's metadata, or the timestamp
+    /// of its last tag if that is later.
+    duration: f64,
+
+    bytes_loaded: usize,
+    bytes_total: usize,
+
+    /// The download of the file, which reports the data as it arrives.
+    #[collect(require_static)]
+    progress: Option<Arc<FetchProgress>>,
+
+    /// Whether the whole file has been downloaded.
+    loaded: bool,
+
+    /// The timestamp of the last tag that has been downloaded, in
+    /// milliseconds. Playback can't go past this until more data arrives.
+    buffered_time: f64,
+
+    #[collect(require_static)]
+    reader: FlvReader,
+
+    #[collect(require_static)]
+    video: VideoTrack,
+
+    #[collect(require_static)]
+    audio: AudioTrack,
+
+    /// Script data tags that are yet to be sent to ActionScript, in order.
+    #[collect(require_static)]
+    pending_script_data: Vec<(u32, ScriptData)>,
+}
+
+/// The demuxed video frames of a stream.
+#[derive(Debug, Default)]
+struct VideoTrack {
+    /// The codec of the stream, as given by its first frame.
+    codec: Option<VideoCodec>,
+
+    /// The decoder that this stream's frames are decoded with.
+    stream: Option<VideoStreamHandle>,
+
+    frames: Vec<StreamVideoFrame>,
+
+    /// The last decoded frame, and its index in `frames`.
+    decoded_frame: Option<(usize, BitmapInfo)>,
+}
+
+#[derive(Debug)]
+struct StreamVideoFrame {
+    timestamp: u32,
+    is_keyframe: bool,
+    data: Vec<u8>,
+}
+
+/// The demuxed audio of a stream.
+#[derive(Debug, Default)]
+struct AudioTrack {
+    /// The format of the stream, as given by its first chunk of audio.
+    stream_info: Option<SoundStreamHead>,
+
+    /// The audio data, stored as a sequence of SWF `SoundStreamBlock` tags so
+    /// that it can be played by the audio backend like any other stream.
+    blocks: Vec<u8>,
+
+    /// The timestamp and position in `blocks` of each chunk of audio.
+    block_offsets: Vec<(u32, usize)>,
+
+    /// The currently playing sound.
+    instance: Option<SoundInstanceHandle>,
+}
+
+impl<'gc> NetStream<'gc> {
+    pub fn new(gc_context: MutationContext<'gc, '_>) -> Self {
+        NetStream(GcCell::allocate(
+            gc_context,
+            NetStreamData {
+                avm1_object: None,
+                state: PlaybackState::Idle,
+                paused: false,
+                time: 0.0,
+                duration: 0.0,
+                bytes_loaded: 0,
+                bytes_total: 0,
+                progress: None,
+                loaded: false,
+                buffered_time: 0.0,
+                reader: FlvReader::new(),
+                video: Default::default(),
+                audio: Default::default(),
+                pending_script_data: Vec::new(),
+            },
+        ))
+    }
+
+    pub fn ptr_eq(a: NetStream<'gc>, b: NetStream<'gc>) -> bool {
+        GcCell::ptr_eq(a.0, b.0)
+    }
+
+    pub fn avm1_object(self) -> Option<Avm1Object<'gc>> {
+        self.0.read().avm1_object
+    }
+
+    pub fn set_avm1_object(self, gc_context: MutationContext<'gc, '_>, object: Avm1Object<'gc>) {
+        self.0.write(gc_context).avm1_object = Some(object);
+    }
+
+    pub fn state(self) -> PlaybackState {
+        self.0.read().state
+    }
+
+    /// The playback position in seconds.
+    pub fn time(self) -> f64 {
+        self.0.read().time / 1000.0
+    }
+
+    pub fn bytes_loaded(self) -> usize {
+        self.0.read().bytes_loaded
+    }
+
+    pub fn bytes_total(self) -> usize {
+        self.0.read().bytes_total
+    }
+
+    /// The last decoded video frame.
+    pub fn video_frame(self) -> Option<BitmapInfo> {
+        self.0.read().video.decoded_frame.map(|(_, bitmap)| bitmap)
+    }
+
+    /// Start downloading and playing the FLV file at `url`.
+    ///
+    /// The file is demuxed as it arrives, and playback starts once enough of
+    /// it has been buffered. The request is made on behalf of `requester`'s
+    /// movie, and so is subject to its sandbox and cross-domain policy.
+    pub fn play(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        requester: DisplayObject<'gc>,
+        url: &str,
+    ) {
+        self.close(context);
+        let progress = Arc::new(FetchProgress::streaming());
+        let mut write = self.0.write(context.gc_context);
+        write.state = PlaybackState::Loading;
+        write.progress = Some(progress.clone());
+        drop(write);
+        context.stream_manager.activate(self);
+
+        let options = RequestOptions::get().with_progress(progress);
+        let fetch = context.fetch_data(requester, url, options);
+        let process =
+            context
+                .load_manager
+                .load_net_stream(context.player.clone().unwrap(), self, fetch);
+        context.navigator.spawn_future(process);
+
+        self.send_status(context, "NetStream.Play.Start", "status");
+    }
+
+    /// Finish downloading the file being played. `data` is the whole file,
+    /// which is only used if none of it was reported as it arrived.
+    pub fn finish_loading(self, context: &mut UpdateContext<'_, 'gc, '_>, data: &[u8]) {
+        let read = self.0.read();
+        if read.state == PlaybackState::Idle || read.loaded {
+            return;
+        }
+        let received = match &read.progress {
+            Some(progress) => progress.take_received(),
+            None => Vec::new(),
+        };
+        let nothing_received = read.bytes_loaded == 0 && received.is_empty();
+        drop(read);
+
+        self.load_data(
+            context,
+            if nothing_received {
+                data
+            } else {
+                &received[..]
+            },
+        );
+
+        let mut write = self.0.write(context.gc_context);
+        write.loaded = true;
+        write.progress = None;
+        write.bytes_total = write.bytes_loaded;
+        write.duration = write.duration.max(write.buffered_time);
+        drop(write);
+
+        self.update_buffer(context);
+    }
+
+    /// Demux the parts of the file that have arrived since the last frame.
+    fn receive_data(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let data = match &self.0.read().progress {
+            Some(progress) => progress.take_received(),
+            None => return,
+        };
+        if !data.is_empty() {
+            self.load_data(context, &data);
+            self.update_buffer(context);
+        }
+    }
+
+    /// Demux newly arrived data of the file being played.
+    fn load_data(self, context: &mut UpdateContext<'_, 'gc, '_>, data: &[u8]) {
+        let mut write = self.0.write(context.gc_context);
+        if write.state == PlaybackState::Idle {
+            return;
+        }
+
+        write.bytes_loaded += data.len();
+        let bytes_total = write.progress.as_ref().map_or(0, |p| p.bytes_total());
+        write.bytes_total = bytes_total.max(write.bytes_loaded);
+        write.reader.append(data);
+
+        loop {
+            let tag = match write.reader.next_tag() {
+                Ok(Some(tag)) => tag,
+                Ok(None) => break,
+                // Invalid tags are skipped, but nothing can be read past an
+                // invalid header.
+                Err(e) => {
+                    log::error!("Error reading FLV stream: {}", e);
+                    if write.reader.header().is_none() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            write.buffered_time = write.buffered_time.max(tag.timestamp.into());
+            write.duration = write.duration.max(write.buffered_time);
+            match tag.data {
+                FlvTagData::Video(video) => {
+                    let codec = match video.codec {
+                        Some(codec) => codec,
+                        None => {
+                            log::warn!("Unsupported video codec in FLV stream");
+                            continue;
+                        }
+                    };
+                    if video.frame_type == VideoFrameType::Command {
+                        continue;
+                    }
+
+                    let track = &mut write.video;
+                    if track.codec.is_none() {
+                        match context.video.register_video_stream(
+                            0,
+                            (0, 0),
+                            codec,
+                            VideoDeblocking::UseVideoPacketValue,
+                        ) {
+                            Ok(stream) => {
+                                track.codec = Some(codec);
+                                track.stream = Some(stream);
+                            }
+                            Err(e) => {
+                                log::error!("Unable to play FLV video: {}", e);
+                                track.codec = Some(codec);
+                            }
+                        }
+                    }
+
+                    let stream = match (track.codec, track.stream) {
+                        (Some(track_codec), Some(stream)) if track_codec == codec => stream,
+                        _ => continue,
+                    };
+                    let frame_id = track.frames.len() as u32;
+                    let dependency = context.video.preload_video_stream_frame(
+                        stream,
+                        EncodedFrame {
+                            codec,
+                            data: &video.data,
+                            frame_id,
+                        },
+                    );
+                    let is_keyframe = match dependency {
+                        Ok(dependency) => dependency.is_keyframe(),
+                        Err(e) => {
+                            log::error!("Got error when pre-loading FLV video frame: {}", e);
+                            video.frame_type == VideoFrameType::Keyframe
+                        }
+                    };
+                    track.frames.push(StreamVideoFrame {
+                        timestamp: tag.timestamp,
+                        is_keyframe: is_keyframe || frame_id == 0,
+                        data: video.data,
+                    });
+                }
+                FlvTagData::Audio(audio) => {
+                    let format = match audio.format {
+                        Some(format) => format,
+                        None => {
+                            log::warn!("Unsupported audio codec in FLV stream");
+                            continue;
+                        }
+                    };
+
+                    let track = &mut write.audio;
+                    let stream_info = track.stream_info.get_or_insert_with(|| SoundStreamHead {
+                        stream_format: format.clone(),
+                        playback_format: format.clone(),
+                        num_samples_per_block: 0,
+                        latency_seek: 0,
+                    });
+                    if stream_info.stream_format != format {
+                        continue;
+                    }
+
+                    // MP3 stream blocks start with a sample count and seek
+                    // offset, which the audio backend skips over.
+                    let mut block = Vec::with_capacity(audio.data.len() + 4);
+                    if format.compression == AudioCompression::Mp3 {
+                        block.extend_from_slice(&[0, 0, 0, 0]);
+                    }
+                    block.extend_from_slice(&audio.data);
+
+                    let offset = track.blocks.len();
+                    let tag_header = ((TagCode::SoundStreamBlock as u16) << 6) | 0x3f;
+                    track.blocks.extend_from_slice(&tag_header.to_le_bytes());
+                    track
+                        .blocks
+                        .extend_from_slice(&(block.len() as u32).to_le_bytes());
+                    track.blocks.extend_from_slice(&block);
+                    track.block_offsets.push((tag.timestamp, offset));
+                }
+                FlvTagData::Script(data) => {
+                    if data.name == "onMetaData" {
+                        if let Some(duration) = metadata_duration(&data.value) {
+                            write.duration = write.duration.max(duration * 1000.0);
+                        }
+                    }
+
+                    write.pending_script_data.push((tag.timestamp, data));
+                }
+                FlvTagData::Unknown(tag_type) => {
+                    log::warn!("Unknown FLV tag type {}", tag_type);
+                }
+            }
+        }
+    }
+
+    /// Start playing once enough of the file has been buffered ahead of the
+    /// playback position, or all of it has been downloaded.
+    fn update_buffer(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let mut write = self.0.write(context.gc_context);
+        let is_buffered = write.loaded || write.buffered_time - write.time >= BUFFER_TIME;
+        if !is_buffered
+            || !matches!(
+                write.state,
+                PlaybackState::Loading | PlaybackState::Buffering
+            )
+        {
+            return;
+        }
+
+        write.state = PlaybackState::Playing;
+        let paused = write.paused;
+        drop(write);
+
+        self.send_status(context, "NetStream.Buffer.Full", "status");
+        self.dispatch_script_data(context);
+        self.update_video(context);
+        if !paused {
+            self.start_audio(context);
+        }
+    }
+
+    /// Give up on downloading the stream. Whatever has arrived of it can
+    /// still be played.
+    pub fn load_failed(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        match self.state() {
+            PlaybackState::Idle => (),
+            PlaybackState::Loading if self.0.read().reader.header().is_none() => {
+                self.close(context);
+                self.send_status(context, "NetStream.Play.StreamNotFound", "error");
+            }
+            _ => self.finish_loading(context, &[]),
+        }
+    }
+
+    /// Pause or resume playback, or toggle it if `paused` is `None`.
+    pub fn pause(self, context: &mut UpdateContext<'_, 'gc, '_>, paused: Option<bool>) {
+        let mut write = self.0.write(context.gc_context);
+        let paused = paused.unwrap_or(!write.paused);
+        if paused == write.paused {
+            return;
+        }
+
+        write.paused = paused;
+        let is_playing = write.state == PlaybackState::Playing;
+        drop(write);
+
+        if !is_playing {
+            return;
+        } else if paused {
+            self.stop_audio(context);
+        } else {
+            self.start_audio(context);
+        }
+    }
+
+    /// Seek to the keyframe closest to `time`, given in seconds.
+    pub fn seek(self, context: &mut UpdateContext<'_, 'gc, '_>, time: f64) {
+        let mut write = self.0.write(context.gc_context);
+        match write.state {
+            PlaybackState::Idle => return,
+            PlaybackState::Loading => {
+                write.time = time.max(0.0) * 1000.0;
+                return;
+            }
+            PlaybackState::Buffering | PlaybackState::Playing | PlaybackState::Finished => {}
+        }
+
+        // Only the part of the file that has arrived can be seeked into.
+        let end = if write.loaded {
+            write.duration
+        } else {
+            write.buffered_time
+        };
+        let time = (time * 1000.0).max(0.0).min(end);
+        let keyframe_time = write
+            .video
+            .frames
+            .iter()
+            .rev()
+            .find(|frame| frame.is_keyframe && f64::from(frame.timestamp) <= time)
+            .map(|frame| f64::from(frame.timestamp));
+        write.time = keyframe_time.unwrap_or(time);
+        write.state = PlaybackState::Playing;
+
+        // Script data before the new position is never sent.
+        let new_time = write.time;
+        write
+            .pending_script_data
+            .retain(|(timestamp, _)| f64::from(*timestamp) >= new_time);
+        let paused = write.paused;
+        drop(write);
+
+        context.stream_manager.activate(self);
+        self.update_video(context);
+        self.stop_audio(context);
+        if !paused {
+            self.start_audio(context);
+        }
+
+        self.send_status(context, "NetStream.Seek.Notify", "status");
+    }
+
+    /// Stop playing the stream, and forget everything about it.
+    pub fn close(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        self.stop_audio(context);
+        context.stream_manager.deactivate(self);
+
+        let mut write = self.0.write(context.gc_context);
+        write.state = PlaybackState::Idle;
+        write.paused = false;
+        write.time = 0.0;
+        write.duration = 0.0;
+        write.bytes_loaded = 0;
+        write.bytes_total = 0;
+        write.progress = None;
+        write.loaded = false;
+        write.buffered_time = 0.0;
+        write.reader = FlvReader::new();
+        write.video = Default::default();
+        write.audio = Default::default();
+        write.pending_script_data.clear();
+    }
+
+    /// Demux any newly arrived data, and advance playback by `frame_time`
+    /// milliseconds.
+    fn tick(self, context: &mut UpdateContext<'_, 'gc, '_>, frame_time: f64) {
+        self.receive_data(context);
+
+        let mut write = self.0.write(context.gc_context);
+        if write.state != PlaybackState::Playing || write.paused {
+            return;
+        }
+
+        write.time += frame_time;
+        let is_finished = write.loaded && write.time >= write.duration;
+        let is_empty = !write.loaded && write.time >= write.buffered_time;
+        if is_finished {
+            write.time = write.duration;
+            write.state = PlaybackState::Finished;
+        } else if is_empty {
+            write.time = write.buffered_time;
+            write.state = PlaybackState::Buffering;
+        }
+        drop(write);
+
+        self.update_video(context);
+        self.dispatch_script_data(context);
+
+        if is_finished {
+            self.stop_audio(context);
+            context.stream_manager.deactivate(self);
+            self.send_status(context, "NetStream.Play.Stop", "status");
+        } else if is_empty {
+            // The audio only went as far as the data that had arrived when it
+            // started, so it's restarted once playback resumes.
+            self.stop_audio(context);
+            self.send_status(context, "NetStream.Buffer.Empty", "status");
+        }
+    }
+
+    /// Decode the video frame for the current playback position.
+    fn update_video(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let read = self.0.read();
+        let time = read.time;
+        let track = &read.video;
+        let (codec, stream) = match (track.codec, track.stream) {
+            (Some(codec), Some(stream)) => (codec, stream),
+            _ => return,
+        };
+        let target = match track
+            .frames
+            .iter()
+            .rposition(|frame| f64::from(frame.timestamp) <= time)
+        {
+            Some(target) => target,
+            None => return,
+        };
+
+        // Frames have to be decoded in order, starting from a keyframe. We
+        // carry on from the last decoded frame unless there's a keyframe
+        // closer to the target.
+        let decoded = track.decoded_frame.map(|(index, _)| index);
+        let start = match decoded {
+            Some(decoded) if decoded == target => return,
+            Some(decoded)
+                if decoded < target
+                    && !track.frames[decoded + 1..=target]
+                        .iter()
+                        .any(|frame| frame.is_keyframe) =>
+            {
+                decoded + 1
+            }
+            _ => track.frames[..=target]
+                .iter()
+                .rposition(|frame| frame.is_keyframe)
+                .unwrap_or(0),
+        };
+
+        let mut decoded_frame = None;
+        for (index, frame) in track.frames.iter().enumerate().take(target + 1).skip(start) {
+            let encoded_frame = EncodedFrame {
+                codec,
+                data: &frame.data,
+                frame_id: index as u32,
+            };
+            match context
+                .video
+                .decode_video_stream_frame(stream, encoded_frame, context.renderer)
+            {
+                Ok(bitmap) => decoded_frame = Some((index, bitmap)),
+                Err(e) => log::error!("Got error when decoding FLV video frame {}: {}", index, e),
+            }
+        }
+        drop(read);
+
+        if decoded_frame.is_some() {
+            self.0.write(context.gc_context).video.decoded_frame = decoded_frame;
+            *context.needs_render = true;
+        }
+    }
+
+    /// Start playing the audio of the stream from the current position.
+    fn start_audio(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        self.stop_audio(context);
+
+        let read = self.0.read();
+        let track = &read.audio;
+        let stream_info = match &track.stream_info {
+            Some(stream_info) => stream_info.clone(),
+            None => return,
+        };
+        let index = track
+            .block_offsets
+            .iter()
+            .rposition(|(timestamp, _)| f64::from(*timestamp) <= read.time)
+            .unwrap_or(0);
+        let offset = match track.block_offsets.get(index) {
+            Some((_, offset)) => *offset,
+            None => return,
+        };
+        let movie = context
+            .swf
+            .from_movie_and_subdata(track.blocks[offset..].to_vec(), context.swf);
+        drop(read);
+
+        // TODO: Streams should only be audible through a clip they have been
+        // attached to with `MovieClip.attachAudio`.
+        let instance =
+            context.start_stream(None, None, 1, SwfSlice::from(Arc::new(movie)), &stream_info);
+        self.0.write(context.gc_context).audio.instance = instance;
+    }
+
+    fn stop_audio(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let instance = self.0.write(context.gc_context).audio.instance.take();
+        if let Some(instance) = instance {
+            context.stop_sound(instance);
+        }
+    }
+
+    /// Send any script data that has been reached by playback to
+    /// ActionScript, by calling the method that it is named after.
+    fn dispatch_script_data(self, context: &mut UpdateContext<'_, 'gc, '_>) {
+        let mut write = self.0.write(context.gc_context);
+        let time = write.time;
+        let reached = write
+            .pending_script_data
+            .iter()
+            .take_while(|(timestamp, _)| f64::from(*timestamp) <= time)
+            .count();
+        let script_data: Vec<_> = write.pending_script_data.drain(..reached).collect();
+        let object = write.avm1_object;
+        drop(write);
+
+        let object = match object {
+            Some(object) if !script_data.is_empty() => object,
+            _ => return,
+        };
+
+        let mut activation = Avm1Activation::from_stub(
+            context.reborrow(),
+            ActivationIdentifier::root("[NetStream]"),
+        );
+        for (_, data) in script_data {
+            let value = script_data_to_value(&mut activation, &data.value);
+            if let Err(e) = object.call_method(&data.name, &[value], &mut activation) {
+                log::error!("Error in NetStream.{}: {}", data.name, e);
+            }
+        }
+    }
+
+    /// Call `onStatus` with an info object describing an event.
+    fn send_status(
+        self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        code: &'static str,
+        level: &'static str,
+    ) {
+        let object = match self.avm1_object() {
+            Some(object) => object,
+            None => return,
+        };
+
+        let info = ScriptObject::object(context.gc_context, Some(context.avm1.prototypes().object));
+        info.define_value(context.gc_context, "code", code.into(), Attribute::empty());
+        info.define_value(
+            context.gc_context,
+            "level",
+            level.into(),
+            Attribute::empty(),
+        );
+
+        context.action_queue.queue_actions(
+            context.stage.root_clip(),
+            ActionType::Method {
+                object,
+                name: "onStatus",
+                args: vec![Avm1Value::Object(info.into())],
+            },
+            false,
+        );
+    }
+}
+
+/// Get the duration, in seconds, given by the `onMetaData` script data.
+fn metadata_duration(value: &ScriptDataValue) -> Option<f64> {
+    let properties = match value {
+        ScriptDataValue::Object(properties) | ScriptDataValue::EcmaArray(properties) => properties,
+        _ => return None,
+    };
+
+    properties.iter().find_map(|(name, value)| match value {
+        ScriptDataValue::Number(duration) if name == "duration" && duration.is_finite() => {
+            Some(*duration)
+        }
+        _ => None,
+    })
+}
@@ -1,8 +1,11 @@
 use crate::backend::navigator::url_from_relative_path;
+use crate::execution_position;
+use crate::player::NEWEST_PLAYER_VERSION;
+use crate::security::SandboxType;
 use gc_arena::Collect;
 use std::path::Path;
-use std::sync::Arc;
-use swf::{Header, TagCode};
+use std::sync::{Arc, Weak};
+use swf::{Color, Header, TagCode, Twips};
 
 pub type Error = Box<dyn std::error::Error>;
 pub type DecodeResult = Result<(), Error>;
@@ -34,6 +37,45 @@ pub struct SwfMovie {
 
     /// The compressed length of the entire datastream
     compressed_length: usize,
+
+    /// The security sandbox this movie asked to be placed in.
+    sandbox_type: SandboxType,
+
+    /// Whether this movie is an ActionScript 3 movie, according to its
+    /// `FileAttributes` tag.
+    is_action_script_3: bool,
+
+    /// The color of the `SetBackgroundColor` tag of the first frame.
+    background_color: Option<Color>,
+}
+
+/// Information about a movie that is known before it starts playing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovieInfo {
+    /// The width of the stage, in pixels.
+    pub width: f64,
+
+    /// The height of the stage, in pixels.
+    pub height: f64,
+
+    /// The frame rate in the header, in frames per second.
+    pub frame_rate: f32,
+
+    /// The number of frames on the root timeline.
+    pub num_frames: u16,
+
+    pub swf_version: u8,
+
+    /// The length of the SWF file, as it was loaded.
+    pub compressed_length: usize,
+
+    /// The length of the SWF file after decompression, as given by its header.
+    pub uncompressed_length: u32,
+
+    pub is_action_script_3: bool,
+
+    /// The background color set by the movie, if any.
+    pub background_color: Option<Color>,
 }
 
 impl SwfMovie {
@@ -54,9 +96,37 @@ impl SwfMovie {
             parameters: Vec::new(),
             encoding: swf::UTF_8,
             compressed_length: 0,
+            sandbox_type: SandboxType::for_movie(None, swf_version, false),
+            is_action_script_3: false,
+            background_color: None,
         }
     }
 
+    /// Construct the movie that an image loaded with `loadMovie` is shown
+    /// in: an empty movie of a single frame, with the size of the image.
+    pub fn from_loaded_image(
+        url: Option<String>,
+        loader_url: Option<String>,
+        compressed_length: usize,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let mut movie = Self::empty(NEWEST_PLAYER_VERSION);
+        movie.header.stage_size = swf::Rectangle {
+            x_min: Twips::zero(),
+            x_max: Twips::from_pixels(width.into()),
+            y_min: Twips::zero(),
+            y_max: Twips::from_pixels(height.into()),
+        };
+        movie.header.num_frames = 1;
+        movie.header.uncompressed_length = compressed_length as u32;
+        movie.compressed_length = compressed_length;
+        movie.sandbox_type = SandboxType::for_movie(url.as_deref(), NEWEST_PLAYER_VERSION, false);
+        movie.url = url;
+        movie.loader_url = loader_url;
+        movie
+    }
+
     /// Construct a movie from an existing movie with any particular data on
     /// it.
     ///
@@ -71,6 +141,9 @@ impl SwfMovie {
             parameters: source.parameters.clone(),
             encoding: source.encoding,
             compressed_length: source.compressed_length,
+            sandbox_type: source.sandbox_type,
+            is_action_script_3: self.is_action_script_3,
+            background_color: self.background_color.clone(),
         }
     }
 
@@ -95,6 +168,16 @@ impl SwfMovie {
         let compressed_length = swf_data.len();
         let swf_buf = swf::read::decompress_swf(swf_data)?;
         let encoding = swf::SwfStr::encoding_for_version(swf_buf.header.version);
+        let file_attributes = read_file_attributes(&swf_buf.data, swf_buf.header.version);
+        let use_network_sandbox = file_attributes
+            .as_ref()
+            .map_or(false, |attributes| attributes.use_network_sandbox);
+        let is_action_script_3 = file_attributes
+            .as_ref()
+            .map_or(false, |attributes| attributes.is_action_script_3);
+        let background_color = read_background_color(&swf_buf.data, swf_buf.header.version);
+        let sandbox_type =
+            SandboxType::for_movie(url.as_deref(), swf_buf.header.version, use_network_sandbox);
         Ok(Self {
             header: swf_buf.header,
             data: swf_buf.data,
@@ -103,6 +186,9 @@ impl SwfMovie {
             parameters: Vec::new(),
             encoding,
             compressed_length,
+            sandbox_type,
+            is_action_script_3,
+            background_color,
         })
     }
 
@@ -119,6 +205,11 @@ impl SwfMovie {
         &self.data
     }
 
+    /// Whether the movie's `FileAttributes` ask for it to run in AVM2.
+    pub fn is_action_script_3(&self) -> bool {
+        self.is_action_script_3
+    }
+
     /// Returns the suggested string encoding for the given SWF version.
     /// For SWF version 6 and higher, this is always UTF-8.
     /// For SWF version 5 and lower, this is locale-dependent,
@@ -156,6 +247,67 @@ impl SwfMovie {
     pub fn compressed_length(&self) -> usize {
         self.compressed_length
     }
+
+    /// Get the security sandbox this movie asked to be placed in.
+    ///
+    /// The player may override this; use `Security::sandbox_type` to get the
+    /// sandbox the movie actually plays in.
+    pub fn sandbox_type(&self) -> SandboxType {
+        self.sandbox_type
+    }
+
+    /// Get the information about this movie that frontends need before it
+    /// starts playing, such as its size.
+    pub fn info(&self) -> MovieInfo {
+        let stage_size = &self.header.stage_size;
+        MovieInfo {
+            width: (stage_size.x_max - stage_size.x_min).to_pixels(),
+            height: (stage_size.y_max - stage_size.y_min).to_pixels(),
+            frame_rate: self.header.frame_rate,
+            num_frames: self.header.num_frames,
+            swf_version: self.header.version,
+            compressed_length: self.compressed_length,
+            uncompressed_length: self.header.uncompressed_length,
+            is_action_script_3: self.is_action_script_3,
+            background_color: self.background_color.clone(),
+        }
+    }
+}
+
+/// Read the `FileAttributes` tag of a movie, which must be its first tag.
+fn read_file_attributes(data: &[u8], swf_version: u8) -> Option<swf::FileAttributes> {
+    let mut reader = swf::read::Reader::new(data, swf_version);
+    match reader.read_tag_code_and_length() {
+        Ok((tag_code, _tag_len))
+            if TagCode::from_u16(tag_code) == Some(TagCode::FileAttributes) =>
+        {
+            reader.read_file_attributes().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Read the color of the `SetBackgroundColor` tag of the first frame of a
+/// movie, if it has one. Only the tag headers are read, up to the first
+/// `ShowFrame`.
+///
+/// The tags are read again, and any errors reported, when the movie plays,
+/// so a movie that ends early or is malformed simply has no color here.
+fn read_background_color(data: &[u8], swf_version: u8) -> Option<Color> {
+    let mut reader = swf::read::Reader::new(data, swf_version);
+    loop {
+        let (tag_code, tag_len) = reader.read_tag_code_and_length().ok()?;
+        let tag_data = reader.get_ref().get(..tag_len)?;
+        match TagCode::from_u16(tag_code) {
+            Some(TagCode::SetBackgroundColor) => {
+                return swf::read::Reader::new(tag_data, swf_version)
+                    .read_rgb()
+                    .ok()
+            }
+            Some(TagCode::ShowFrame) | Some(TagCode::End) => return None,
+            _ => *reader.get_mut() = &reader.get_ref()[tag_len..],
+        }
+    }
 }
 
 /// A shared-ownership reference to some portion of an SWF datastream.
@@ -305,6 +457,39 @@ impl SwfSlice {
     pub fn read_from(&self, from: u64) -> swf::read::Reader<'_> {
         swf::read::Reader::new(&self.data()[from as usize..], self.movie.version())
     }
+
+    /// Create a reference to this slice that doesn't keep the movie alive.
+    pub fn downgrade(&self) -> WeakSwfSlice {
+        WeakSwfSlice {
+            movie: Arc::downgrade(&self.movie),
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+/// A reference to some portion of an SWF datastream that doesn't keep the
+/// movie alive.
+///
+/// This is for data that is kept for as long as the movie is, such as in its
+/// library, which would otherwise never let the movie be freed.
+#[derive(Debug, Clone, Collect)]
+#[collect(require_static)]
+pub struct WeakSwfSlice {
+    movie: Weak<SwfMovie>,
+    start: usize,
+    end: usize,
+}
+
+impl WeakSwfSlice {
+    /// Get the slice back, if the movie is still alive.
+    pub fn upgrade(&self) -> Option<SwfSlice> {
+        Some(SwfSlice {
+            movie: self.movie.upgrade()?,
+            start: self.start,
+            end: self.end,
+        })
+    }
 }
 
 pub fn decode_tags<'a, F>(
@@ -315,7 +500,9 @@ pub fn decode_tags<'a, F>(
 where
     F: for<'b> FnMut(&'b mut SwfStream<'a>, TagCode, usize) -> DecodeResult,
 {
+    let _position = execution_position::enter_tags();
     loop {
+        let tag_start = reader.get_ref();
         let (tag_code, tag_len) = reader.read_tag_code_and_length()?;
         if tag_len > reader.get_ref().len() {
             log::error!("Unexpected EOF when reading tag");
@@ -328,6 +515,7 @@ where
         let end_slice = &reader.get_ref()[tag_len..];
         if let Some(tag) = tag {
             *reader.get_mut() = tag_slice;
+            execution_position::set_tag(tag, tag_start);
             let result = tag_callback(reader, tag, tag_len);
 
             if let Err(e) = result {
@@ -347,3 +535,111 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a movie of the given tags, with a 550x400 stage.
+    fn movie(version: u8, num_frames: u16, tags: Vec<swf::Tag<'_>>) -> SwfMovie {
+        let swf = swf::Swf {
+            header: Header {
+                compression: swf::Compression::None,
+                version,
+                uncompressed_length: 0,
+                stage_size: swf::Rectangle {
+                    x_min: Twips::zero(),
+                    x_max: Twips::from_pixels(550.0),
+                    y_min: Twips::zero(),
+                    y_max: Twips::from_pixels(400.0),
+                },
+                frame_rate: 24.0,
+                num_frames,
+            },
+            tags,
+        };
+        let mut data = Vec::new();
+        swf::write_swf(&swf, &mut data).unwrap();
+        SwfMovie::from_data(&data, None, None).unwrap()
+    }
+
+    fn red() -> Color {
+        Color::from_rgb(0xff0000, 255)
+    }
+
+    #[test]
+    fn movie_info_describes_the_header_and_first_frame() {
+        let movie = movie(
+            10,
+            2,
+            vec![
+                swf::Tag::FileAttributes(swf::FileAttributes {
+                    use_direct_blit: false,
+                    use_gpu: false,
+                    has_metadata: false,
+                    is_action_script_3: true,
+                    use_network_sandbox: false,
+                }),
+                swf::Tag::SetBackgroundColor(red()),
+                swf::Tag::ShowFrame,
+                swf::Tag::ShowFrame,
+            ],
+        );
+        let info = movie.info();
+
+        assert_eq!((info.width, info.height), (550.0, 400.0));
+        assert_eq!(info.frame_rate, 24.0);
+        assert_eq!(info.num_frames, 2);
+        assert_eq!(info.swf_version, 10);
+        assert_eq!(info.compressed_length, movie.compressed_length());
+        assert_eq!(info.uncompressed_length, movie.header().uncompressed_length);
+        assert!(info.is_action_script_3);
+        assert_eq!(info.background_color, Some(red()));
+    }
+
+    #[test]
+    fn background_color_is_only_read_from_the_first_frame() {
+        let color_in_second_frame = movie(
+            8,
+            2,
+            vec![
+                swf::Tag::ShowFrame,
+                swf::Tag::SetBackgroundColor(red()),
+                swf::Tag::ShowFrame,
+            ],
+        );
+        assert_eq!(color_in_second_frame.info().background_color, None);
+        assert!(!color_in_second_frame.info().is_action_script_3);
+
+        // Movies that end without showing a frame have no color either.
+        let no_frames = movie(8, 0, vec![]);
+        assert_eq!(no_frames.info().background_color, None);
+        let truncated = read_background_color(&[0x43, 0x02, 0xff], 8);
+        assert_eq!(truncated, None);
+    }
+
+    #[test]
+    fn loaded_images_have_a_movie_of_their_size() {
+        let movie = SwfMovie::from_loaded_image(
+            Some("https://example.com/image.png".to_string()),
+            Some("https://example.com/movie.swf".to_string()),
+            1234,
+            640,
+            480,
+        );
+
+        assert_eq!((movie.width(), movie.height()), (640, 480));
+        assert_eq!(movie.header().num_frames, 1);
+        assert_eq!(movie.version(), NEWEST_PLAYER_VERSION);
+        assert!(movie.data().is_empty());
+        assert_eq!(movie.compressed_length(), 1234);
+        assert_eq!(movie.url(), Some("https://example.com/image.png"));
+        assert_eq!(movie.loader_url(), Some("https://example.com/movie.swf"));
+        assert_eq!(movie.sandbox_type(), SandboxType::Remote);
+
+        let local =
+            SwfMovie::from_loaded_image(Some("file:///image.png".to_string()), None, 0, 65535, 1);
+        assert_eq!(local.width(), 65535);
+        assert_eq!(local.sandbox_type(), SandboxType::LocalWithFile);
+    }
+}
@@ -0,0 +1,169 @@
+//! Running movies in a headless, deterministic player for tests.
+//!
+//! `TestPlayer` builds a player out of the null backends, so that it draws
+//! nothing, plays no sound and reads files from the local filesystem. The
+//! random number generator is seeded with a fixed value, the clock always reads
+//! the same date and `getTimer` always returns zero, so a movie produces the
+//! same output on every run.
+//!
+//! ```no_run
+//! use ruffle_core::test_utils::TestPlayer;
+//!
+//! let mut player = TestPlayer::from_path("test.swf").unwrap();
+//! player.run_frames(10).unwrap();
+//! println!("{}", player.trace_output());
+//! println!("{}", player.display_list());
+//! ```
+
+use crate::backend::{
+    audio::NullAudioBackend,
+    locale::NullLocaleBackend,
+    log::MemoryLogBackend,
+    navigator::{NullExecutor, NullNavigatorBackend},
+    render::NullRenderer,
+    storage::MemoryStorageBackend,
+    ui::NullUiBackend,
+    video::NullVideoBackend,
+};
+use crate::tag_utils::SwfMovie;
+use crate::Player;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Error = Box<dyn std::error::Error>;
+
+/// The seed of the random number generator of every test player.
+pub const TEST_RANDOM_SEED: u64 = 0;
+
+/// A headless player running a single movie.
+pub struct TestPlayer {
+    player: Arc<Mutex<Player>>,
+    executor: NullExecutor,
+    log: MemoryLogBackend,
+
+    /// The length of a frame of the movie, in milliseconds.
+    frame_time: f64,
+}
+
+impl TestPlayer {
+    /// Load the movie at the given path. Files it loads are looked up relative
+    /// to its directory.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let movie = SwfMovie::from_path(path, None)?;
+        let base_path = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::from_movie(movie, base_path)
+    }
+
+    /// Load a movie, looking up the files it loads relative to `base_path`.
+    pub fn from_movie(movie: SwfMovie, base_path: &Path) -> Result<Self, Error> {
+        let (executor, channel) = NullExecutor::new();
+        let log = MemoryLogBackend::new();
+        let frame_time = 1000.0 / movie.header().frame_rate as f64;
+
+        let player = Player::new(
+            Box::new(NullRenderer),
+            Box::new(NullAudioBackend::new()),
+            Box::new(NullNavigatorBackend::with_base_path(base_path, channel)),
+            Box::new(MemoryStorageBackend::default()),
+            Box::new(NullLocaleBackend::new()),
+            Box::new(NullVideoBackend::new()),
+            Box::new(log.clone()),
+            Box::new(NullUiBackend::new()),
+        )?;
+        {
+            let mut player = player.lock().unwrap();
+            player.set_random_seed(TEST_RANDOM_SEED);
+            player.set_root_movie(Arc::new(movie));
+            player.set_max_execution_duration(Duration::from_secs(300));
+        }
+
+        Ok(Self {
+            player,
+            executor,
+            log,
+            frame_time,
+        })
+    }
+
+    /// The player running the movie, to change its settings or send it
+    /// events between frames.
+    pub fn player(&self) -> Arc<Mutex<Player>> {
+        self.player.clone()
+    }
+
+    /// Run the movie for a number of frames, along with its timers and any
+    /// loads that can make progress.
+    pub fn run_frames(&mut self, num_frames: u32) -> Result<(), Error> {
+        for _ in 0..num_frames {
+            self.player.lock().unwrap().run_frame();
+            self.player.lock().unwrap().update_timers(self.frame_time);
+            self.executor.poll_all()?;
+        }
+
+        Ok(())
+    }
+
+    /// Wait for every pending load to finish.
+    pub fn finish_loads(&mut self) -> Result<(), Error> {
+        self.executor.block_all()?;
+        Ok(())
+    }
+
+    /// The messages traced so far, one per line.
+    pub fn trace_output(&self) -> String {
+        self.log.output()
+    }
+
+    /// Forget the messages traced so far.
+    pub fn clear_trace_output(&self) {
+        self.log.take_messages();
+    }
+
+    /// A description of every object on the display list, one per line.
+    pub fn display_list(&self) -> String {
+        self.player.lock().unwrap().dump_display_list()
+    }
+
+    /// Check the display list for states that should never happen. See
+    /// `Player::check_display_list`.
+    pub fn check_display_list(&self) -> Result<(), Error> {
+        let errors = self.player.lock().unwrap().check_display_list();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n").into())
+        }
+    }
+
+    /// Jump the root clip to a frame, and run any scripts that the jump
+    /// triggers. `frame` is 1-based.
+    pub fn goto_frame(&mut self, frame: u16, stop: bool) -> Result<(), Error> {
+        self.player.lock().unwrap().goto_root_frame(frame, stop);
+        self.executor.poll_all()?;
+        Ok(())
+    }
+
+    /// The number of frames of the movie.
+    pub fn total_frames(&self) -> u16 {
+        self.player.lock().unwrap().root_total_frames()
+    }
+
+    /// The number of frames of every movie clip on the display list. See
+    /// `Player::movie_clip_frame_counts`.
+    pub fn movie_clip_frame_counts(&self) -> Vec<u16> {
+        self.player.lock().unwrap().movie_clip_frame_counts()
+    }
+
+    /// Jump the movie clip at `index` in `movie_clip_frame_counts` to a
+    /// frame, and run any scripts that the jump triggers. `frame` is 1-based.
+    pub fn goto_clip_frame(&mut self, index: usize, frame: u16, stop: bool) -> Result<(), Error> {
+        self.player
+            .lock()
+            .unwrap()
+            .goto_clip_frame(index, frame, stop);
+        self.executor.poll_all()?;
+        Ok(())
+    }
+}
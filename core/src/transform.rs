@@ -54,6 +54,12 @@ impl TransformStack {
         });
     }
 
+    /// Pushes a transform that replaces the current one, rather than being
+    /// combined with it, such as when drawing into an offscreen target.
+    pub fn push_absolute(&mut self, transform: Transform) {
+        self.0.push(transform);
+    }
+
     pub fn pop(&mut self) {
         if self.0.len() <= 1 {
             panic!("Transform stack underflow");
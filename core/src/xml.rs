@@ -1,6 +1,7 @@
 //! Garbage-collectable XML DOM impl
 
 mod document;
+mod entities;
 mod error;
 mod iterators;
 mod namespace;
@@ -0,0 +1,86 @@
+//! XML entity handling, matching Flash Player.
+//!
+//! Flash is lenient when parsing: a `&` that doesn't start a known entity is
+//! kept as-is, instead of failing the whole document. When serializing, it
+//! always escapes the same five characters, in both text and attributes.
+
+use std::borrow::Cow;
+
+/// Replace the entities in parsed XML text or attribute values with the
+/// characters they stand for.
+///
+/// This understands the five predefined XML entities, `&nbsp;`, and decimal
+/// and hexadecimal character references. Anything else, including a lone
+/// `&`, is left unchanged.
+pub fn unescape(input: &str) -> Cow<'_, str> {
+    if !input.contains('&') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .map(|end| &rest[1..=end])
+            .and_then(|entity| Some((entity, decode_entity(entity)?)));
+        if let Some((entity, c)) = entity {
+            output.push(c);
+            rest = &rest[entity.len() + 2..];
+        } else {
+            output.push('&');
+            rest = &rest[1..];
+        }
+    }
+    output.push_str(rest);
+
+    Cow::Owned(output)
+}
+
+/// Decode the name of an entity, without its surrounding `&` and `;`.
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let number = entity.strip_prefix('#')?;
+            let hex = number
+                .strip_prefix('x')
+                .or_else(|| number.strip_prefix('X'));
+            let code = if let Some(hex) = hex {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                number.parse().ok()?
+            };
+            std::char::from_u32(code).filter(|c| *c != '\0')
+        }
+    }
+}
+
+/// Escape a string for use as XML text or as an attribute value.
+pub fn escape(input: &str) -> Cow<'_, str> {
+    if !input.contains(|c| matches!(c, '&' | '<' | '>' | '"' | '\'')) {
+        return Cow::Borrowed(input);
+    }
+
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&apos;"),
+            c => output.push(c),
+        }
+    }
+
+    Cow::Owned(output)
+}
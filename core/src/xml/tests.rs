@@ -223,3 +223,62 @@ fn ignore_white() {
         assert!(root.next().is_none());
     })
 }
+
+/// Tests that entities are decoded like Flash, leaving unknown ones alone.
+#[test]
+fn parse_entities() {
+    rootless_arena(|mc| {
+        let xml = XmlDocument::new(mc);
+        xml.as_node()
+            .replace_with_str(
+                mc,
+                "<test a=\"&quot;&apos;&#65;&#x42;&#X43;\" b=\"x & y\">\
+                 &lt;&amp;&gt;&nbsp;&foo; & &#0;</test>",
+                true,
+                false,
+            )
+            .expect("Parsed document");
+
+        let root = xml.as_node().children().next().expect("Should have root");
+        assert_eq!(
+            root.attribute_value(&XmlName::from_str("a")),
+            Some("\"'ABC".to_string())
+        );
+        assert_eq!(
+            root.attribute_value(&XmlName::from_str("b")),
+            Some("x & y".to_string())
+        );
+
+        let text = root.children().next().expect("Should have text");
+        assert_eq!(text.node_value(), Some("<&>\u{a0}&foo; & &#0;".to_string()));
+    })
+}
+
+/// Tests that text and attributes are escaped like Flash, but comments and
+/// CDATA sections are not.
+#[test]
+fn tostring_entities() {
+    rootless_arena(|mc| {
+        let xml = XmlDocument::new(mc);
+        xml.as_node()
+            .replace_with_str(
+                mc,
+                "<test a=\"&lt;'&amp;&#34;\">\
+                 <!-- a < b & c -->\"x\" &#38; 'y'<![CDATA[<&>]]></test>",
+                true,
+                false,
+            )
+            .expect("Parsed document");
+
+        let result = xml
+            .as_node()
+            .into_string(&mut |_| true)
+            .expect("Successful toString");
+
+        assert_eq!(
+            result,
+            "<test a=\"&lt;&apos;&amp;&quot;\">\
+             <!-- a < b & c -->&quot;x&quot; &amp; &apos;y&apos;&lt;&amp;&gt;</test>"
+        );
+    })
+}
@@ -4,6 +4,7 @@ use crate::avm1::object::xml_attributes_object::XmlAttributesObject;
 use crate::avm1::object::xml_object::XmlObject;
 use crate::avm1::{Object, TObject};
 use crate::xml;
+use crate::xml::entities::{escape, unescape};
 use crate::xml::{Error, Step, XmlDocument, XmlName};
 use gc_arena::{Collect, GcCell, MutationContext};
 use quick_xml::events::attributes::Attribute;
@@ -219,9 +220,9 @@ impl<'gc> XmlNode<'gc> {
     ///
     /// Also, this method does not yet actually remove existing node contents.
     ///
-    /// If `process_entity` is `true`, then entities will be processed by this
-    /// function. Invalid or unrecognized entities will cause parsing to fail
-    /// with an `Err`.
+    /// If `process_entity` is `true`, then entities in text and attribute
+    /// values will be processed by this function. Like Flash, unrecognized
+    /// entities are left as they are.
     pub fn replace_with_str(
         &mut self,
         mc: MutationContext<'gc, '_>,
@@ -241,15 +242,17 @@ impl<'gc> XmlNode<'gc> {
 
             document.process_event(mc, &event)?;
 
+            // CDATA sections never contain entities.
+            let is_cdata = matches!(event, Event::CData(_));
             match event {
                 Event::Start(bs) => {
-                    let child = XmlNode::from_start_event(mc, bs, document)?;
+                    let child = XmlNode::from_start_event(mc, bs, document, process_entity)?;
                     self.document().update_idmap(mc, child);
                     self.add_child_to_tree(mc, &mut open_tags, child)?;
                     open_tags.push(child);
                 }
                 Event::Empty(bs) => {
-                    let child = XmlNode::from_start_event(mc, bs, document)?;
+                    let child = XmlNode::from_start_event(mc, bs, document, process_entity)?;
                     self.document().update_idmap(mc, child);
                     self.add_child_to_tree(mc, &mut open_tags, child)?;
                 }
@@ -257,6 +260,7 @@ impl<'gc> XmlNode<'gc> {
                     open_tags.pop();
                 }
                 Event::Text(bt) | Event::CData(bt) => {
+                    let process_entity = process_entity && !is_cdata;
                     let child = XmlNode::text_from_text_event(mc, bt, document, process_entity)?;
                     if child.node_value().as_deref() != Some("")
                         && (!ignore_white || !child.is_whitespace_text())
@@ -292,16 +296,20 @@ impl<'gc> XmlNode<'gc> {
         mc: MutationContext<'gc, '_>,
         bs: BytesStart<'a>,
         document: XmlDocument<'gc>,
+        process_entity: bool,
     ) -> Result<Self, Error> {
         let tag_name = XmlName::from_bytes(bs.name())?;
         let mut attributes = BTreeMap::new();
 
         for a in bs.attributes() {
             let attribute = a?;
-            attributes.insert(
-                XmlName::from_bytes(attribute.key)?,
-                String::from_utf8(attribute.value.to_owned().to_vec())?,
-            );
+            let value = String::from_utf8(attribute.value.to_vec())?;
+            let value = if process_entity {
+                unescape(&value).into_owned()
+            } else {
+                value
+            };
+            attributes.insert(XmlName::from_bytes(attribute.key)?, value);
         }
 
         let children = Vec::new();
@@ -332,10 +340,11 @@ impl<'gc> XmlNode<'gc> {
         document: XmlDocument<'gc>,
         process_entity: bool,
     ) -> Result<Self, Error> {
+        let contents = String::from_utf8(bt.escaped().to_vec())?;
         let contents = if process_entity {
-            String::from_utf8(bt.unescaped()?.into_owned())?
+            unescape(&contents).into_owned()
         } else {
-            String::from_utf8(bt.escaped().to_vec())?
+            contents
         };
 
         Ok(XmlNode(GcCell::allocate(
@@ -369,7 +378,7 @@ impl<'gc> XmlNode<'gc> {
                 parent: None,
                 prev_sibling: None,
                 next_sibling: None,
-                contents: String::from_utf8(bt.unescaped()?.into_owned())?,
+                contents: String::from_utf8(bt.escaped().to_vec())?,
             },
         )))
     }
@@ -391,7 +400,7 @@ impl<'gc> XmlNode<'gc> {
                 parent: None,
                 prev_sibling: None,
                 next_sibling: None,
-                contents: String::from_utf8(bt.unescaped()?.into_owned())?,
+                contents: String::from_utf8(bt.escaped().to_vec())?,
             },
         )))
     }
@@ -1193,16 +1202,15 @@ impl<'gc> XmlNode<'gc> {
                 } else {
                     BytesStart::owned_name(format!("{} ", tag_name.node_name()))
                 };
-                let key_values: Vec<(Cow<str>, &str)> = attributes
+                let key_values: Vec<(Cow<str>, Cow<str>)> = attributes
                     .iter()
-                    .map(|(name, value)| (name.node_name(), value.as_str()))
+                    .map(|(name, value)| (name.node_name(), escape(value)))
                     .collect();
 
-                bs.extend_attributes(
-                    key_values
-                        .iter()
-                        .map(|(name, value)| Attribute::from((name.as_ref(), *value))),
-                );
+                bs.extend_attributes(key_values.iter().map(|(name, value)| Attribute {
+                    key: name.as_bytes(),
+                    value: Cow::Borrowed(value.as_bytes()),
+                }));
 
                 if children_len > 0 {
                     writer.write_event(&Event::Start(bs))
@@ -1210,14 +1218,15 @@ impl<'gc> XmlNode<'gc> {
                     writer.write_event(&Event::Empty(bs))
                 }
             }
-            XmlNodeData::Text { contents, .. } => {
-                writer.write_event(&Event::Text(BytesText::from_plain_str(contents.as_str())))
-            }
+            XmlNodeData::Text { contents, .. } => writer.write_event(&Event::Text(
+                BytesText::from_escaped_str(escape(contents.as_str())),
+            )),
+            // Comments and doctypes are written exactly as they were parsed.
             XmlNodeData::Comment { contents, .. } => writer.write_event(&Event::Comment(
-                BytesText::from_plain_str(contents.as_str()),
+                BytesText::from_escaped_str(contents.as_str()),
             )),
             XmlNodeData::DocType { contents, .. } => writer.write_event(&Event::DocType(
-                BytesText::from_plain_str(contents.as_str()),
+                BytesText::from_escaped_str(contents.as_str()),
             )),
         }?;
 
@@ -0,0 +1,615 @@
+//! Runs every SWF in a directory for a number of frames, and collects the
+//! panics and errors that they cause into a triage report.
+//!
+//! Each movie is run in a child process of the runner, so that a panic (which
+//! aborts the process in Ruffle builds) or a hang only takes down that movie.
+
+use clap::Clap;
+use indicatif::{ProgressBar, ProgressStyle};
+use path_slash::PathExt;
+use ruffle_core::backend::{
+    audio::NullAudioBackend,
+    locale::NullLocaleBackend,
+    log::NullLogBackend,
+    navigator::{NullExecutor, NullNavigatorBackend},
+    storage::MemoryStorageBackend,
+    ui::NullUiBackend,
+    video::SoftwareVideoBackend,
+};
+use ruffle_core::execution_position::ExecutionPosition;
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::Player;
+use ruffle_render_software::SoftwareRenderBackend;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use walkdir::{DirEntry, WalkDir};
+
+/// The prefix of the line that a movie's process prints when it panics.
+const PANIC_MARKER: &str = "RUFFLE_CORPUS_PANIC ";
+
+/// How long a single script may run before the player gives up on it.
+///
+/// This is the same as in release builds, so that infinite loops in scripts
+/// show up as errors instead of timeouts.
+const MAX_EXECUTION_DURATION: Duration = Duration::from_secs(15);
+
+/// How many example files are listed for each problem in the triage report.
+const MAX_EXAMPLES: usize = 5;
+
+#[derive(Clap, Debug)]
+#[clap(version, about, author)]
+struct Opt {
+    /// The directory (containing SWF files) to run
+    #[clap(name = "directory", parse(from_os_str))]
+    input_path: PathBuf,
+
+    /// The file to store results in CSV format
+    #[clap(name = "results", parse(from_os_str))]
+    output_path: Option<PathBuf>,
+
+    /// Number of frames to run each movie for
+    #[clap(short = 'f', long = "frames", default_value = "60")]
+    frames: u32,
+
+    /// Number of seconds a movie may run for before it's considered hung
+    #[clap(short = 't', long = "timeout", default_value = "60")]
+    timeout: u64,
+
+    /// Filenames to ignore
+    #[clap(short = 'i', long = "ignore")]
+    ignore: Vec<String>,
+
+    /// Run the single SWF given as the directory in this process, and print
+    /// the results as JSON. This is used to run each movie in its own process.
+    #[clap(long = "single", hidden = true)]
+    single: bool,
+}
+
+/// How running a movie went.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Outcome {
+    /// The movie ran for every frame without logging any errors.
+    Ok,
+
+    /// The movie ran for every frame, but logged errors.
+    Errors,
+
+    /// The movie couldn't be loaded.
+    LoadFailure,
+
+    /// The player panicked.
+    Panic,
+
+    /// The movie didn't finish running in time.
+    Timeout,
+}
+
+#[derive(Serialize, Debug)]
+struct FileResults {
+    name: String,
+    outcome: Outcome,
+    frames_run: u32,
+
+    /// The panic message, or the first error that was logged.
+    error: Option<String>,
+
+    /// The place in Ruffle's source code that the error came from.
+    source: Option<String>,
+
+    /// The frame of the movie that was running when the error happened.
+    error_frame: Option<u32>,
+
+    /// The tag of the movie that was running when the error happened.
+    tag: Option<String>,
+
+    /// The script of the movie that was running when the error happened.
+    script: Option<String>,
+
+    errors: usize,
+    warnings: usize,
+
+    /// The stack trace of a panic, or the output of a process that failed.
+    details: Option<String>,
+}
+
+/// What happened while running a single movie, as printed by its process.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct RunResults {
+    frames_run: u32,
+    errors: Vec<LoggedError>,
+    warnings: usize,
+}
+
+/// An error logged by the player, or a panic.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct LoggedError {
+    message: String,
+    source: Option<String>,
+    frame: u32,
+    tag: Option<String>,
+    script: Option<String>,
+}
+
+impl LoggedError {
+    /// Describe an error that happened just now.
+    fn new(message: String, source: Option<String>) -> Self {
+        let (tag, script) = describe_execution_position();
+        Self {
+            message,
+            source,
+            frame: CURRENT_FRAME.load(Ordering::Relaxed),
+            tag,
+            script,
+        }
+    }
+}
+
+/// The frame that is currently running in this process.
+static CURRENT_FRAME: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+    /// The movie that is running on this thread.
+    static MOVIE: RefCell<Option<Arc<SwfMovie>>> = RefCell::new(None);
+}
+
+/// Describe the tag and script of the movie that this thread is running, as
+/// offsets into the movie's decompressed data.
+fn describe_execution_position() -> (Option<String>, Option<String>) {
+    let position = MOVIE.with(|movie| {
+        movie
+            .try_borrow()
+            .ok()?
+            .as_deref()
+            .map(ExecutionPosition::current)
+    });
+    let position = match position {
+        Some(position) => position,
+        None => return (None, None),
+    };
+
+    let tag = position
+        .tag
+        .map(|(code, offset)| format!("{:?} at {:#x}", code, offset));
+    let script = match (position.avm1_action, position.avm2_op) {
+        (_, Some((method, offset))) => Some(format!("AVM2 method {} at {:#x}", method, offset)),
+        (Some(offset), None) => Some(format!("AVM1 action at {:#x}", offset)),
+        (None, None) => None,
+    };
+
+    (tag, script)
+}
+
+/// A logger that records the errors and counts the warnings that are logged
+/// while a movie runs.
+#[derive(Default)]
+struct ErrorLogger {
+    errors: Mutex<Vec<LoggedError>>,
+    warnings: AtomicUsize,
+}
+
+impl log::Log for ErrorLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        match record.level() {
+            log::Level::Error => self.errors.lock().unwrap().push(LoggedError::new(
+                record.args().to_string(),
+                source_location(record.file(), record.line()),
+            )),
+            log::Level::Warn => {
+                self.warnings.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn source_location(file: Option<&str>, line: Option<u32>) -> Option<String> {
+    file.map(|file| match line {
+        Some(line) => format!("{}:{}", file, line),
+        None => file.to_string(),
+    })
+}
+
+/// Report panics in a way that the runner can pick out of this process's
+/// output, followed by the usual panic message and stack trace.
+fn set_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "PANIC".to_string()
+        };
+        let report = LoggedError::new(
+            message,
+            info.location()
+                .and_then(|location| source_location(Some(location.file()), Some(location.line()))),
+        );
+        if let Ok(report) = serde_json::to_string(&report) {
+            eprintln!("{}{}", PANIC_MARKER, report);
+        }
+
+        default_hook(info);
+    }));
+}
+
+/// Run a movie in this process with software and null backends.
+fn run_single(swf_path: &Path, frames: u32) -> Result<RunResults, Box<dyn Error>> {
+    let logger: &'static ErrorLogger = Box::leak(Box::new(ErrorLogger::default()));
+    log::set_logger(logger)?;
+    log::set_max_level(log::LevelFilter::Warn);
+    set_panic_hook();
+
+    let base_path = swf_path.parent().unwrap_or_else(|| Path::new("."));
+    let (mut executor, channel) = NullExecutor::new();
+    let movie = Arc::new(SwfMovie::from_path(swf_path, None)?);
+    MOVIE.with(|current| *current.borrow_mut() = Some(movie.clone()));
+    let frame_time = 1000.0 / (movie.header().frame_rate as f64).max(1.0);
+    let width = movie.width();
+    let height = movie.height();
+
+    let player = Player::new(
+        Box::new(SoftwareRenderBackend::new(width, height)),
+        Box::new(NullAudioBackend::new()),
+        Box::new(NullNavigatorBackend::with_base_path(base_path, channel)),
+        Box::new(MemoryStorageBackend::default()),
+        Box::new(NullLocaleBackend::new()),
+        Box::new(SoftwareVideoBackend::new()),
+        Box::new(NullLogBackend::new()),
+        Box::new(NullUiBackend::new()),
+    )?;
+    {
+        let mut player = player.lock().unwrap();
+        player.set_viewport_dimensions(width, height, 1.0);
+        player.set_max_execution_duration(MAX_EXECUTION_DURATION);
+        player.set_root_movie(movie);
+    }
+
+    let mut frames_run = 0;
+    for frame in 1..=frames {
+        CURRENT_FRAME.store(frame, Ordering::Relaxed);
+        {
+            let mut player = player.lock().unwrap();
+            player.run_frame();
+            player.update_timers(frame_time);
+            player.render();
+        }
+        if let Err(e) = executor.poll_all() {
+            log::error!("Error while loading: {}", e);
+        }
+        frames_run = frame;
+    }
+
+    Ok(RunResults {
+        frames_run,
+        errors: std::mem::take(&mut *logger.errors.lock().unwrap()),
+        warnings: logger.warnings.load(Ordering::Relaxed),
+    })
+}
+
+fn find_files(root: &Path, ignore: &[String]) -> Vec<DirEntry> {
+    let progress = ProgressBar::new_spinner();
+    let mut results = Vec::new();
+
+    for entry in WalkDir::new(root)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let f_name = entry.file_name().to_string_lossy();
+
+        if f_name.ends_with(".swf") && !ignore.iter().any(|x| x == &f_name) {
+            results.push(entry);
+            progress.set_message(format!("Searching for swf files... {}", results.len()));
+        }
+    }
+
+    progress.finish_with_message(format!("Found {} swf files to run", results.len()));
+    results
+}
+
+/// Read everything from a child process's output on another thread, so that
+/// the child never blocks on a full pipe.
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    })
+}
+
+/// Run a movie in a child process, and work out what happened to it.
+fn run_file(file: &DirEntry, name: String, opt: &Opt) -> Result<FileResults, std::io::Error> {
+    let mut child = Command::new(std::env::current_exe()?)
+        .arg("--single")
+        .arg("--frames")
+        .arg(opt.frames.to_string())
+        .arg(file.path())
+        .env("RUST_BACKTRACE", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_secs(opt.timeout);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    let mut results = FileResults {
+        name,
+        outcome: Outcome::Ok,
+        frames_run: 0,
+        error: None,
+        source: None,
+        error_frame: None,
+        tag: None,
+        script: None,
+        errors: 0,
+        warnings: 0,
+        details: None,
+    };
+
+    let run: Option<RunResults> = serde_json::from_str(stdout.trim()).ok();
+    let panic: Option<LoggedError> = stderr.lines().find_map(|line| {
+        line.strip_prefix(PANIC_MARKER)
+            .and_then(|report| serde_json::from_str(report).ok())
+    });
+
+    if let Some(panic) = panic {
+        results.outcome = Outcome::Panic;
+        results.frames_run = panic.frame.saturating_sub(1);
+        results.error = Some(panic.message);
+        results.source = panic.source;
+        results.error_frame = Some(panic.frame);
+        results.tag = panic.tag;
+        results.script = panic.script;
+        results.details = Some(
+            stderr
+                .lines()
+                .filter(|line| !line.starts_with(PANIC_MARKER))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    } else if status.is_none() {
+        results.outcome = Outcome::Timeout;
+        results.error = Some(format!("Didn't finish within {} seconds", opt.timeout));
+    } else if let Some(run) = run {
+        results.outcome = if run.errors.is_empty() {
+            Outcome::Ok
+        } else {
+            Outcome::Errors
+        };
+        results.frames_run = run.frames_run;
+        results.errors = run.errors.len();
+        results.warnings = run.warnings;
+        if let Some(error) = run.errors.into_iter().next() {
+            results.error = Some(error.message);
+            results.source = error.source;
+            results.error_frame = Some(error.frame);
+            results.tag = error.tag;
+            results.script = error.script;
+        }
+    } else {
+        results.outcome = Outcome::LoadFailure;
+        results.error = stderr.lines().last().map(str::to_string);
+        results.details = Some(stderr);
+    }
+
+    Ok(results)
+}
+
+/// The key that failures are grouped by in the triage report.
+///
+/// Numbers in error messages are usually IDs or positions that differ between
+/// movies, so they are ignored, as are the tags and scripts that were running.
+fn triage_key(results: &FileResults) -> String {
+    let mut message = String::new();
+    let mut in_number = false;
+    for c in results.error.as_deref().unwrap_or_default().chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                message.push('#');
+            }
+            in_number = true;
+        } else {
+            message.push(c);
+            in_number = false;
+        }
+    }
+
+    format!(
+        "{:?} at {}: {}",
+        results.outcome,
+        results.source.as_deref().unwrap_or("unknown location"),
+        message
+    )
+}
+
+/// Print the failures grouped by cause, with the most common ones first.
+fn print_triage_report(failures: &[FileResults]) {
+    let mut groups: HashMap<String, Vec<&FileResults>> = HashMap::new();
+    for results in failures {
+        groups.entry(triage_key(results)).or_default().push(results);
+    }
+
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by(|(a_key, a), (b_key, b)| b.len().cmp(&a.len()).then(a_key.cmp(b_key)));
+
+    println!();
+    println!("Triage report ({} distinct problems):", groups.len());
+    for (key, files) in groups {
+        println!();
+        println!("{} movies: {}", files.len(), key);
+        for results in files.iter().take(MAX_EXAMPLES) {
+            let position: Vec<_> = results
+                .error_frame
+                .map(|frame| format!("frame {}", frame))
+                .into_iter()
+                .chain(results.tag.clone())
+                .chain(results.script.clone())
+                .collect();
+            if position.is_empty() {
+                println!("    {}", results.name);
+            } else {
+                println!("    {} ({})", results.name, position.join(", "));
+            }
+        }
+        if files.len() > MAX_EXAMPLES {
+            println!("    ...and {} more", files.len() - MAX_EXAMPLES);
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let opt = Opt::parse();
+
+    if opt.single {
+        match run_single(&opt.input_path, opt.frames) {
+            Ok(results) => println!("{}", serde_json::to_string(&results)?),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    let output_path = opt
+        .output_path
+        .clone()
+        .ok_or("An output path for the results is required")?;
+    let to_run = find_files(&opt.input_path, &opt.ignore);
+    let total = to_run.len() as u64;
+    let progress = ProgressBar::new(total);
+    let mut writer = csv::Writer::from_path(output_path)?;
+    let mut failures = Vec::new();
+
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "[{elapsed_precise}] {bar:40.cyan/blue} [{eta_precise}] {pos:>7}/{len:7} {msg}",
+            )
+            .progress_chars("##-"),
+    );
+
+    for file in to_run {
+        let name = file
+            .path()
+            .strip_prefix(&opt.input_path)
+            .unwrap_or_else(|_| file.path())
+            .to_slash_lossy();
+        progress.inc(1);
+        progress.set_message(name.clone());
+        let results = run_file(&file, name, &opt)?;
+
+        writer.serialize(&results)?;
+        if results.outcome != Outcome::Ok {
+            failures.push(results);
+        }
+    }
+    writer.flush()?;
+
+    progress.finish_with_message(format!(
+        "Ran {} swf files. {} ran cleanly, {} had problems",
+        total,
+        total - failures.len() as u64,
+        failures.len()
+    ));
+    print_triage_report(&failures);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(outcome: Outcome, error: Option<&str>, source: Option<&str>) -> FileResults {
+        FileResults {
+            name: "movie.swf".to_string(),
+            outcome,
+            frames_run: 0,
+            error: error.map(str::to_string),
+            source: source.map(str::to_string),
+            error_frame: None,
+            tag: None,
+            script: None,
+            errors: 0,
+            warnings: 0,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn triage_key_ignores_numbers_and_positions() {
+        let mut first = failure(
+            Outcome::Errors,
+            Some("Character 12 at depth 3 is missing"),
+            Some("core/src/display_object/movie_clip.rs:100"),
+        );
+        first.error_frame = Some(1);
+        first.tag = Some("PlaceObject2 at 0x1f".to_string());
+        let mut second = failure(
+            Outcome::Errors,
+            Some("Character 345 at depth 16384 is missing"),
+            Some("core/src/display_object/movie_clip.rs:100"),
+        );
+        second.error_frame = Some(20);
+        second.script = Some("AVM1 action at 0x200".to_string());
+
+        assert_eq!(triage_key(&first), triage_key(&second));
+        assert_eq!(
+            triage_key(&first),
+            "Errors at core/src/display_object/movie_clip.rs:100: Character # at depth # is missing"
+        );
+    }
+
+    #[test]
+    fn triage_key_separates_outcomes_and_sources() {
+        let error = Some("Oops");
+        let panic = failure(Outcome::Panic, error, Some("core/src/player.rs:1"));
+        let logged = failure(Outcome::Errors, error, Some("core/src/player.rs:1"));
+        let elsewhere = failure(Outcome::Panic, error, Some("core/src/player.rs:2"));
+
+        assert_ne!(triage_key(&panic), triage_key(&logged));
+        assert_ne!(triage_key(&panic), triage_key(&elsewhere));
+    }
+
+    #[test]
+    fn triage_key_without_details() {
+        let timeout = failure(Outcome::Timeout, None, None);
+        assert_eq!(triage_key(&timeout), "Timeout at unknown location: ");
+    }
+}
@@ -0,0 +1,82 @@
+//! `ExternalInterface` for the desktop player.
+//!
+//! There's no page for a movie to talk to on desktop. Instead, methods
+//! written in Rust can be registered under the names that movies call with
+//! `ExternalInterface.call`.
+
+use ruffle_core::context::UpdateContext;
+use ruffle_core::external::{
+    ExternalInterfaceMethod, ExternalInterfaceProvider, Value as ExternalValue,
+};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type Method = Rc<dyn Fn(&[ExternalValue]) -> ExternalValue>;
+
+/// The methods that movies can call with `ExternalInterface.call`.
+#[derive(Default)]
+pub struct DesktopExternalInterface {
+    methods: HashMap<String, Method>,
+}
+
+impl DesktopExternalInterface {
+    /// An interface with the `console` methods that many movies log through
+    /// when they are embedded in a page.
+    pub fn new() -> Self {
+        let mut interface = Self::default();
+        interface.register("console.log", |args| log_args(log::Level::Info, args));
+        interface.register("console.info", |args| log_args(log::Level::Info, args));
+        interface.register("console.warn", |args| log_args(log::Level::Warn, args));
+        interface.register("console.error", |args| log_args(log::Level::Error, args));
+        interface
+    }
+
+    /// Register a method, replacing any other method with the same name.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        method: impl Fn(&[ExternalValue]) -> ExternalValue + 'static,
+    ) {
+        self.methods.insert(name.into(), Rc::new(method));
+    }
+}
+
+fn log_args(level: log::Level, args: &[ExternalValue]) -> ExternalValue {
+    let message: Vec<String> = args
+        .iter()
+        .map(|arg| match arg {
+            ExternalValue::String(string) => string.clone(),
+            arg => format!("{:?}", arg),
+        })
+        .collect();
+    log::log!(level, "{}", message.join(" "));
+    ExternalValue::Null
+}
+
+struct RegisteredMethod(Method);
+
+impl ExternalInterfaceMethod for RegisteredMethod {
+    fn call(
+        &self,
+        _context: &mut UpdateContext<'_, '_, '_>,
+        args: &[ExternalValue],
+    ) -> ExternalValue {
+        (self.0)(args)
+    }
+}
+
+impl ExternalInterfaceProvider for DesktopExternalInterface {
+    fn get_method(&self, name: &str) -> Option<Box<dyn ExternalInterfaceMethod>> {
+        self.methods.get(name).map(|method| {
+            Box::new(RegisteredMethod(method.clone())) as Box<dyn ExternalInterfaceMethod>
+        })
+    }
+
+    fn on_callback_available(&self, name: &str) {
+        log::info!("Movie added ExternalInterface callback {}", name);
+    }
+
+    fn on_fs_command(&self, _command: &str, _args: &str) -> bool {
+        false
+    }
+}
@@ -0,0 +1,55 @@
+//! Watching the movie file for changes, for `--hot-reload`.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the movie file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls the modification time of a movie file.
+pub struct MovieWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    next_poll: Instant,
+}
+
+impl MovieWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        let modified = Self::modified_time(&path);
+        Self {
+            path,
+            modified,
+            next_poll: Instant::now() + POLL_INTERVAL,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` if the movie file has changed since it was last checked.
+    ///
+    /// The file system is only checked once every `POLL_INTERVAL`, so this may
+    /// be called every frame.
+    pub fn poll(&mut self) -> bool {
+        let now = Instant::now();
+        if now < self.next_poll {
+            return false;
+        }
+        self.next_poll = now + POLL_INTERVAL;
+
+        let modified = Self::modified_time(&self.path);
+        if modified.is_some() && modified != self.modified {
+            self.modified = modified;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
@@ -8,6 +8,8 @@
 mod audio;
 mod custom_event;
 mod executor;
+mod external_interface;
+mod hot_reload;
 mod locale;
 mod navigator;
 mod storage;
@@ -16,23 +18,36 @@ mod ui;
 
 use crate::custom_event::RuffleEvent;
 use crate::executor::GlutinAsyncExecutor;
+use crate::external_interface::DesktopExternalInterface;
 use clap::Clap;
 use isahc::{config::RedirectPolicy, prelude::*, HttpClient};
 use ruffle_core::{
-    backend::audio::AudioBackend, backend::video::NullVideoBackend, config::Letterbox, Player,
+    backend::audio::AudioBackend,
+    backend::render::{Bitmap, BitmapFormat},
+    backend::video::NullVideoBackend,
+    bitmap::decode::unmultiply_alpha_rgba,
+    config::Letterbox,
+    Player,
 };
-use ruffle_render_wgpu::WgpuRenderBackend;
+use ruffle_render_wgpu::{AtlasConfig, WgpuRenderBackend};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tinyfiledialogs::open_file_dialog;
 use url::Url;
 
+use ruffle_core::backend::log::{LogBackend, NullLogBackend, StdoutLogBackend};
+use ruffle_core::backend::navigator::rewrite::UrlRewriter;
 use ruffle_core::backend::video;
+use ruffle_core::config::{CrossDomainMode, DeterministicMode};
+use ruffle_core::input_recording::InputRecording;
+use ruffle_core::security::SandboxType;
 use ruffle_core::tag_utils::SwfMovie;
+use ruffle_projector::Projector;
 use ruffle_render_wgpu::clap::{GraphicsBackend, PowerPreference};
 use std::io::Read;
 use std::rc::Rc;
+use std::str::FromStr;
 use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
 use winit::event::{
     ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
@@ -93,8 +108,139 @@ struct Opt {
     #[clap(long, case_insensitive = true, takes_value = false)]
     upgrade_to_https: bool,
 
+    /// (Optional) File of URL rewrite rules to apply to every URL the movie loads or opens,
+    /// one rule per line, in the format of --rewrite-url.
+    #[clap(long, parse(from_os_str))]
+    url_rewrites: Option<PathBuf>,
+
+    /// A URL rewrite rule, applied after any rules from --url-rewrites.
+    /// This can be repeated multiple times. A rule is one of:
+    /// "host FROM TO" to replace a host (*.FROM also matches its subdomains),
+    /// "https HOST" to upgrade http URLs on a host (* for every host), or
+    /// "map PREFIX TARGET" to replace the start of a URL with another URL or a local directory.
+    #[clap(long = "rewrite-url", value_name = "RULE", number_of_values = 1)]
+    rewrite_urls: Vec<String>,
+
+    /// Which requests movies may make to other domains: "allow-all" to load from any domain,
+    /// "respect-policy" to only load data allowed by the crossdomain.xml policy file of the
+    /// other domain, as in Flash Player, or "deny-cross" to block every request to another
+    /// domain.
+    #[clap(long, value_name = "MODE", default_value = "allow-all")]
+    cross_domain: CrossDomainMode,
+
+    /// Security sandbox to play the movie in, instead of the one it asks for: "remote",
+    /// "local-with-file", "local-with-network" or "local-trusted".
+    /// Use "local-trusted" to let a local movie access both local files and the network.
+    #[clap(long, value_name = "SANDBOX")]
+    sandbox: Option<SandboxType>,
+
+    /// Seconds that a script can run for before asking whether to abort it.
+    /// Defaults to 15 seconds, or no limit in debug builds.
+    #[clap(long, value_name = "SECONDS")]
+    max_execution_duration: Option<f64>,
+
+    /// Number of ActionScript 1 and 2 actions that can run between two frames
+    /// before asking whether to abort the script. There is no limit by default.
+    #[clap(long, value_name = "ACTIONS")]
+    max_actions_per_update: Option<u64>,
+
+    /// Number of queued scripts, such as frame scripts and event handlers, that can run in a
+    /// single frame before the rest are deferred to the next frame. Defaults to 100000.
+    /// Set to 0 for no limit.
+    #[clap(long, value_name = "ACTIONS")]
+    max_queued_actions_per_frame: Option<usize>,
+
     #[clap(long, case_insensitive = true, takes_value = false)]
     timedemo: bool,
+
+    /// Watch the movie file and reload it whenever it changes.
+    /// Changed shapes, bitmaps and sounds are swapped into the running movie,
+    /// while any other change restarts it.
+    #[clap(long, case_insensitive = true, takes_value = false)]
+    hot_reload: bool,
+
+    /// Save a screenshot of each of the given frames of the main timeline, for example
+    /// --capture-frames 1,10,20. Each frame is saved once, as a PNG named after the movie.
+    #[clap(long, value_name = "FRAMES", use_delimiter = true)]
+    capture_frames: Vec<u16>,
+
+    /// Directory to save the screenshots of --capture-frames in.
+    /// Defaults to the current directory.
+    #[clap(long, parse(from_os_str))]
+    capture_path: Option<PathBuf>,
+
+    /// Outline the bounding box of every display object, color keyed by its type,
+    /// along with its depth and the extent of any masks.
+    #[clap(long, case_insensitive = true, takes_value = false)]
+    debug_overlay: bool,
+
+    /// Draw objects that scripts move smoothly between frames, for smoother motion on
+    /// displays with a higher refresh rate than the movie's frame rate.
+    /// Without this, the choice saved for the movie's site is used.
+    #[clap(long, case_insensitive = true, takes_value = false)]
+    frame_interpolation: bool,
+
+    /// Let movies call `console.log` and the other `console` methods through
+    /// ExternalInterface, logging what they pass. This makes `ExternalInterface.available`
+    /// true, which changes how some movies behave.
+    #[clap(long, case_insensitive = true, takes_value = false)]
+    external_interface: bool,
+
+    /// Width and height in pixels of the textures that small bitmaps are packed into.
+    /// Set to 0 to give every bitmap its own texture.
+    #[clap(long, value_name = "PIXELS", default_value = "2048")]
+    atlas_size: u32,
+
+    /// Start in fullscreen. Press Alt+Enter to toggle fullscreen, or Escape to leave it.
+    #[clap(long, case_insensitive = true, takes_value = false)]
+    fullscreen: bool,
+
+    /// Where the output of trace() goes: "stdout" to print it, or "log" to send it to the
+    /// log at the info level, under the avm_trace target.
+    #[clap(long, value_name = "OUTPUT", default_value = "log")]
+    trace_output: TraceOutput,
+
+    /// Save the input given to the movie to a file when the player is closed, to replay it
+    /// with --replay-input. The movie runs with a fixed random seed and a clock that only
+    /// advances with its frames, so that it plays the same way on every run.
+    #[clap(long, value_name = "FILE", parse(from_os_str))]
+    record_input: Option<PathBuf>,
+
+    /// Replay the input saved with --record-input. Input from the user is ignored until the
+    /// replay is over.
+    #[clap(
+        long,
+        value_name = "FILE",
+        parse(from_os_str),
+        conflicts_with = "record-input"
+    )]
+    replay_input: Option<PathBuf>,
+}
+
+/// The destinations of trace() output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TraceOutput {
+    Stdout,
+    Log,
+}
+
+impl FromStr for TraceOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "stdout" => Ok(TraceOutput::Stdout),
+            "log" => Ok(TraceOutput::Log),
+            _ => Err(format!("Unknown trace output: {}", s)),
+        }
+    }
+}
+
+fn log_backend(opt: &Opt) -> Box<dyn LogBackend> {
+    match opt.trace_output {
+        TraceOutput::Stdout => Box::new(StdoutLogBackend::new()),
+        TraceOutput::Log => Box::new(NullLogBackend::new()),
+    }
 }
 
 #[cfg(feature = "render_trace")]
@@ -124,12 +270,21 @@ fn main() {
 
     env_logger::init();
 
-    let opt = Opt::parse();
-
-    let ret = if opt.timedemo {
-        run_timedemo(opt)
-    } else {
-        run_player(opt)
+    let projector = Projector::from_current_exe().unwrap_or_else(|e| {
+        // An unreadable executable shouldn't stop Ruffle from running as usual.
+        log::warn!("Couldn't check for an embedded movie: {}", e);
+        None
+    });
+    let ret = match projector {
+        Some(projector) => run_projector(projector),
+        None => {
+            let opt = Opt::parse();
+            if opt.timedemo {
+                run_timedemo(opt)
+            } else {
+                run_player(opt)
+            }
+        }
     };
 
     if let Err(e) = ret {
@@ -144,6 +299,17 @@ fn main() {
     }
 }
 
+fn load_url_rewriter(opt: &Opt) -> Result<UrlRewriter, Box<dyn std::error::Error>> {
+    let mut rewriter = match &opt.url_rewrites {
+        Some(path) => UrlRewriter::parse(&std::fs::read_to_string(path)?)?,
+        None => UrlRewriter::new(),
+    };
+    for rule in &opt.rewrite_urls {
+        rewriter.add_rule(rule.parse()?);
+    }
+    Ok(rewriter)
+}
+
 fn load_movie_from_path(
     movie_url: Url,
     proxy: Option<&Url>,
@@ -177,6 +343,51 @@ fn set_movie_parameters(movie: &mut SwfMovie, parameters: &[String]) {
     movie.append_parameters(parameters)
 }
 
+/// Reload the movie after its file has changed, for `--hot-reload`.
+fn reload_movie(player: &mut Player, path: &Path, parameters: &[String]) {
+    let mut movie = match SwfMovie::from_path(path, None) {
+        Ok(movie) => movie,
+        Err(e) => {
+            log::error!("Unable to reload {}: {}", path.display(), e);
+            return;
+        }
+    };
+    set_movie_parameters(&mut movie, parameters);
+
+    if player.reload_root_movie_assets(&movie) {
+        log::info!("Reloaded assets of {}", path.display());
+    } else {
+        log::info!("Restarting {}", path.display());
+        player.set_root_movie(Arc::new(movie));
+    }
+}
+
+/// Save a screenshot of the current frame as a PNG, for `--capture-frames`.
+fn save_screenshot(player: &mut Player, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let bitmap = player
+        .capture_frame()
+        .ok_or("The renderer can't capture frames")?;
+    write_png(bitmap, path)
+}
+
+/// Save a bitmap with premultiplied alpha as a PNG.
+fn write_png(bitmap: Bitmap, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let (color_type, data) = match bitmap.data {
+        BitmapFormat::Rgba(mut rgba) => {
+            unmultiply_alpha_rgba(&mut rgba);
+            (png::ColorType::RGBA, rgba)
+        }
+        BitmapFormat::Rgb(rgb) => (png::ColorType::RGB, rgb),
+    };
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, bitmap.width, bitmap.height);
+    encoder.set_color(color_type);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(())
+}
+
 fn run_player(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
     let movie_url = match &opt.input_path {
         Some(path) => {
@@ -208,25 +419,98 @@ fn run_player(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
     let mut movie = load_movie_from_path(movie_url.to_owned(), opt.proxy.as_ref())?;
     set_movie_parameters(&mut movie, &opt.parameters);
 
+    let movie_name = movie_url
+        .path_segments()
+        .and_then(|segments| segments.last())
+        .unwrap_or_else(|| movie_url.as_str())
+        .to_string();
+    let window_title = format!("Ruffle - {}", movie_name);
+    start_player(opt, movie_url, movie, &movie_name, &window_title)
+}
+
+/// Play a movie from a projector: the desktop player with a movie appended to it.
+///
+/// Projectors have no command line. They're configured by the settings packaged with them, and
+/// are trusted to access both local files and the network, as in Flash Player.
+fn run_projector(projector: Projector) -> Result<(), Box<dyn std::error::Error>> {
+    let exe_path = std::env::current_exe()?;
+    // Files loaded by the movie are looked up next to the projector.
+    let movie_url =
+        Url::from_file_path(&exe_path).map_err(|_| "Projector path must be absolute")?;
+    let mut movie = SwfMovie::from_data(&projector.movie, Some(movie_url.to_string()), None)?;
+
+    let config = projector.config;
+    let mut opt = Opt::parse_from(&[env!("CARGO_PKG_NAME")]);
+    opt.width = config.width;
+    opt.height = config.height;
+    opt.fullscreen = config.fullscreen;
+    opt.sandbox = Some(SandboxType::LocalTrusted);
+    opt.parameters = config
+        .parameters
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    set_movie_parameters(&mut movie, &opt.parameters);
+
+    let movie_name = exe_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let window_title = config.title.unwrap_or_else(|| "Ruffle".to_string());
+    start_player(opt, movie_url, movie, &movie_name, &window_title)
+}
+
+fn start_player(
+    opt: Opt,
+    movie_url: Url,
+    movie: SwfMovie,
+    movie_name: &str,
+    window_title: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url_rewriter = load_url_rewriter(&opt)?;
+    let parameters = opt.parameters.clone();
+    let mut movie_watcher = if opt.hot_reload {
+        let path = movie_url.to_file_path().ok();
+        if path.is_none() {
+            log::warn!("Only movies loaded from a file can be hot-reloaded");
+        }
+        path.map(hot_reload::MovieWatcher::new)
+    } else {
+        None
+    };
+
     let icon_bytes = include_bytes!("../assets/favicon-32.rgba");
     let icon = Icon::from_rgba(icon_bytes.to_vec(), 32, 32)?;
 
     let event_loop: EventLoop<RuffleEvent> = EventLoop::with_user_event();
-    let window_title = movie_url
-        .path_segments()
-        .and_then(|segments| segments.last())
-        .unwrap_or_else(|| movie_url.as_str());
+
+    let input_recording = match &opt.replay_input {
+        Some(path) => Some(InputRecording::from_json(&std::fs::read_to_string(path)?)?),
+        None => None,
+    };
+    let record_input = opt.record_input.clone();
+
+    let mut capture_frames = opt.capture_frames.clone();
+    let capture_path = opt.capture_path.clone().unwrap_or_default();
+    let capture_name = Path::new(movie_name)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or("movie")
+        .to_string();
+
     let window = Rc::new(
         WindowBuilder::new()
-            .with_title(format!("Ruffle - {}", window_title))
+            .with_title(window_title)
             .with_window_icon(Some(icon))
             .with_max_inner_size(LogicalSize::new(i16::MAX, i16::MAX))
             .build(&event_loop)?,
     );
 
-    let movie_size = LogicalSize::new(movie.width(), movie.height())
-        .cast::<f64>()
-        .to_physical(window.scale_factor());
+    // Size the window to the stage of the movie, which is known from its header
+    // before the player runs its first frame.
+    let movie_info = movie.info();
+    let movie_size =
+        LogicalSize::new(movie_info.width, movie_info.height).to_physical(window.scale_factor());
     let window_width = opt
         .width
         .unwrap_or(movie_size.width * (opt.height.unwrap_or(movie_size.height) / movie_size.height))
@@ -237,16 +521,23 @@ fn run_player(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
         .max(1.0);
     let window_size = PhysicalSize::new(window_width, window_height);
     window.set_inner_size(window_size);
+    if opt.fullscreen {
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
     let viewport_size = window.inner_size();
     let viewport_scale_factor = window.scale_factor();
 
-    let renderer = Box::new(WgpuRenderBackend::for_window(
+    let mut renderer = Box::new(WgpuRenderBackend::for_window(
         window.as_ref(),
         (viewport_size.width, viewport_size.height),
         opt.graphics.into(),
         opt.power.into(),
         trace_path(&opt),
     )?);
+    renderer.set_atlas_config(AtlasConfig {
+        page_size: opt.atlas_size,
+        ..Default::default()
+    });
     let audio: Box<dyn AudioBackend> = match audio::CpalAudioBackend::new() {
         Ok(audio) => Box::new(audio),
         Err(e) => {
@@ -261,18 +552,41 @@ fn run_player(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
         event_loop.create_proxy(),
         opt.proxy,
         opt.upgrade_to_https,
+        url_rewriter,
     )); //TODO: actually implement this backend type
     let storage = Box::new(storage::DiskStorageBackend::new());
     let locale = Box::new(locale::DesktopLocaleBackend::new());
     let video = Box::new(video::SoftwareVideoBackend::new());
-    let log = Box::new(ruffle_core::backend::log::NullLogBackend::new());
+    let log = log_backend(&opt);
     let ui = Box::new(ui::DesktopUiBackend::new(window.clone()));
     let player = Player::new(renderer, audio, navigator, storage, locale, video, log, ui)?;
     {
         let mut player = player.lock().unwrap();
+        if let Some(recording) = input_recording {
+            player.replay_input(recording);
+        } else if record_input.is_some() {
+            player.set_deterministic_mode(Some(DeterministicMode::default()));
+            player.start_input_recording();
+        }
         player.set_root_movie(Arc::new(movie));
         player.set_is_playing(true); // Desktop player will auto-play.
         player.set_letterbox(Letterbox::On);
+        player.set_show_debug_overlay(opt.debug_overlay);
+        if opt.frame_interpolation {
+            player.set_frame_interpolation(Some(true));
+        }
+        player.set_cross_domain_mode(opt.cross_domain);
+        if opt.external_interface {
+            player.add_external_interface(Box::new(DesktopExternalInterface::new()));
+        }
+        player.set_sandbox_type_override(opt.sandbox);
+        if let Some(seconds) = opt.max_execution_duration {
+            player.set_max_execution_duration(Duration::from_secs_f64(seconds.max(0.0)));
+        }
+        player.set_max_actions_per_update(opt.max_actions_per_update);
+        if let Some(max_actions) = opt.max_queued_actions_per_frame {
+            player.set_max_queued_actions_per_frame(Some(max_actions).filter(|max| *max > 0));
+        }
         player.set_viewport_dimensions(
             viewport_size.width,
             viewport_size.height,
@@ -292,12 +606,28 @@ fn run_player(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
             #[allow(deprecated)]
             match event {
                 winit::event::Event::LoopDestroyed => {
-                    player.lock().unwrap().flush_shared_objects();
+                    let mut player_lock = player.lock().unwrap();
+                    player_lock.flush_shared_objects();
+                    if let (Some(path), Some(recording)) =
+                        (&record_input, player_lock.stop_input_recording())
+                    {
+                        match std::fs::write(path, recording.to_json()) {
+                            Ok(()) => log::info!("Saved input recording to {}", path.display()),
+                            Err(e) => log::error!("Unable to save input recording: {}", e),
+                        }
+                    }
                     return;
                 }
 
                 // Core loop
                 winit::event::Event::MainEventsCleared => {
+                    if let Some(watcher) = &mut movie_watcher {
+                        if watcher.poll() {
+                            reload_movie(&mut player.lock().unwrap(), watcher.path(), &parameters);
+                            window.request_redraw();
+                        }
+                    }
+
                     let new_time = Instant::now();
                     let dt = new_time.duration_since(time).as_micros();
                     if dt > 0 {
@@ -308,6 +638,16 @@ fn run_player(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
                         if player_lock.needs_render() {
                             window.request_redraw();
                         }
+
+                        let frame = player_lock.current_frame();
+                        if let Some(i) = capture_frames.iter().position(|f| Some(*f) == frame) {
+                            let frame = capture_frames.swap_remove(i);
+                            let path = capture_path.join(format!("{}_{}.png", capture_name, frame));
+                            match save_screenshot(&mut player_lock, &path) {
+                                Ok(()) => log::info!("Saved frame {} to {}", frame, path.display()),
+                                Err(e) => log::error!("Unable to save frame {}: {}", frame, e),
+                            }
+                        }
                     }
                 }
 
@@ -485,19 +825,23 @@ fn run_timedemo(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
     let viewport_height = 1080;
     let viewport_scale_factor = 1.0;
 
-    let renderer = Box::new(WgpuRenderBackend::for_offscreen(
+    let mut renderer = Box::new(WgpuRenderBackend::for_offscreen(
         (viewport_width, viewport_height),
         opt.graphics.into(),
         opt.power.into(),
         trace_path(&opt),
     )?);
+    renderer.set_atlas_config(AtlasConfig {
+        page_size: opt.atlas_size,
+        ..Default::default()
+    });
     let audio: Box<dyn AudioBackend> =
         Box::new(ruffle_core::backend::audio::NullAudioBackend::new());
     let navigator = Box::new(ruffle_core::backend::navigator::NullNavigatorBackend::new());
     let storage = Box::new(ruffle_core::backend::storage::MemoryStorageBackend::default());
     let locale = Box::new(locale::DesktopLocaleBackend::new());
     let video = Box::new(NullVideoBackend::new());
-    let log = Box::new(ruffle_core::backend::log::NullLogBackend::new());
+    let log = log_backend(&opt);
     let ui = Box::new(ruffle_core::backend::ui::NullUiBackend::new());
     let player = Player::new(renderer, audio, navigator, storage, locale, video, log, ui)?;
     player.lock().unwrap().set_root_movie(Arc::new(movie));
@@ -1,9 +1,10 @@
 //! Navigator backend for web
 
 use crate::custom_event::RuffleEvent;
-use isahc::{config::RedirectPolicy, prelude::*, AsyncReadResponseExt, HttpClient, Request};
+use futures_lite::AsyncReadExt;
+use isahc::{config::RedirectPolicy, prelude::*, HttpClient, Request};
 use ruffle_core::backend::navigator::{
-    NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions,
+    rewrite::UrlRewriter, NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions,
 };
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
@@ -15,6 +16,9 @@ use std::time::{Duration, Instant};
 use url::Url;
 use winit::event_loop::EventLoopProxy;
 
+/// How much of a response body is read at a time.
+const FETCH_CHUNK_SIZE: usize = 64 * 1024;
+
 /// Implementation of `NavigatorBackend` for non-web environments that can call
 /// out to a web browser.
 pub struct ExternalNavigatorBackend {
@@ -34,6 +38,9 @@ pub struct ExternalNavigatorBackend {
     client: Option<Rc<HttpClient>>,
 
     upgrade_to_https: bool,
+
+    /// Rules to rewrite URLs of servers that no longer exist.
+    url_rewriter: UrlRewriter,
 }
 
 impl ExternalNavigatorBackend {
@@ -45,6 +52,7 @@ impl ExternalNavigatorBackend {
         event_loop: EventLoopProxy<RuffleEvent>,
         proxy: Option<Url>,
         upgrade_to_https: bool,
+        url_rewriter: UrlRewriter,
     ) -> Self {
         let proxy = proxy.and_then(|url| url.as_str().parse().ok());
         let builder = HttpClient::builder()
@@ -60,6 +68,7 @@ impl ExternalNavigatorBackend {
             movie_url,
             start_time: Instant::now(),
             upgrade_to_https,
+            url_rewriter,
         }
     }
 }
@@ -127,8 +136,13 @@ impl NavigatorBackend for ExternalNavigatorBackend {
 
         match processed_url.scheme() {
             "file" => Box::pin(async move {
-                fs::read(processed_url.to_file_path().unwrap_or_default())
-                    .map_err(Error::NetworkError)
+                let data = fs::read(processed_url.to_file_path().unwrap_or_default())
+                    .map_err(Error::NetworkError)?;
+                if let Some(progress) = options.progress() {
+                    progress.receive(&data);
+                    progress.complete(data.len());
+                }
+                Ok(data)
             }),
             _ => Box::pin(async move {
                 let client = client.ok_or(Error::NetworkUnavailable)?;
@@ -155,11 +169,34 @@ impl NavigatorBackend for ExternalNavigatorBackend {
                     )));
                 }
 
+                if let Some(progress) = options.progress() {
+                    if let Some(length) = response.body().len() {
+                        progress.set_bytes_total(length as usize);
+                    }
+                }
+
+                // The body is read in chunks, so that progress can be reported
+                // as it arrives.
                 let mut buffer = vec![];
-                response
-                    .copy_to(&mut buffer)
-                    .await
-                    .map_err(|e| Error::FetchError(e.to_string()))?;
+                let mut chunk = vec![0; FETCH_CHUNK_SIZE];
+                loop {
+                    let len = response
+                        .body_mut()
+                        .read(&mut chunk)
+                        .await
+                        .map_err(|e| Error::FetchError(e.to_string()))?;
+                    if len == 0 {
+                        break;
+                    }
+                    if let Some(progress) = options.progress() {
+                        progress.receive(&chunk[..len]);
+                    }
+                    buffer.extend_from_slice(&chunk[..len]);
+                }
+
+                if let Some(progress) = options.progress() {
+                    progress.complete(buffer.len());
+                }
                 Ok(buffer)
             }),
         }
@@ -188,7 +225,8 @@ impl NavigatorBackend for ExternalNavigatorBackend {
         }
     }
 
-    fn pre_process_url(&self, mut url: Url) -> Url {
+    fn pre_process_url(&self, url: Url) -> Url {
+        let mut url = self.url_rewriter.rewrite(url);
         if self.upgrade_to_https && url.scheme() == "http" && url.set_scheme("https").is_err() {
             log::error!("Url::set_scheme failed on: {}", url);
         }
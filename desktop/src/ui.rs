@@ -1,15 +1,28 @@
 use clipboard::{ClipboardContext, ClipboardProvider};
-use ruffle_core::backend::ui::{MouseCursor, UiBackend};
+use ruffle_core::backend::render::Bitmap;
+use ruffle_core::backend::ui::{MouseCursor, MovieLifecycleEvent, UiBackend};
 use ruffle_core::events::{KeyCode, PlayerEvent};
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::path::Path;
 use std::rc::Rc;
-use tinyfiledialogs::{message_box_ok, MessageBoxIcon};
-use winit::event::{ElementState, ModifiersState, VirtualKeyCode, WindowEvent};
+use tinyfiledialogs::{
+    message_box_ok, message_box_yes_no, save_file_dialog_with_filter, MessageBoxIcon, YesNo,
+};
+use winit::event::{ElementState, ModifiersState, ScanCode, VirtualKeyCode, WindowEvent};
 use winit::window::Window;
 
 pub struct DesktopUiBackend {
     window: Rc<Window>,
-    keys_down: HashSet<VirtualKeyCode>,
+    /// The keys being held, by their scan code, and the key code each was
+    /// pressed as.
+    keys_down: HashMap<ScanCode, KeyCode>,
+
+    /// The characters typed by the keys being held, by their scan code.
+    key_chars: HashMap<ScanCode, char>,
+
+    /// The key that was pressed last, to which a following character belongs.
+    last_scancode: Option<ScanCode>,
+
     cursor_visible: bool,
     last_key: KeyCode,
     last_char: Option<char>,
@@ -20,7 +33,9 @@ impl DesktopUiBackend {
     pub fn new(window: Rc<Window>) -> Self {
         Self {
             window,
-            keys_down: HashSet::new(),
+            keys_down: HashMap::new(),
+            key_chars: HashMap::new(),
+            last_scancode: None,
             cursor_visible: true,
             last_key: KeyCode::Unknown,
             last_char: None,
@@ -33,35 +48,49 @@ impl DesktopUiBackend {
         // Allow KeyboardInput.modifiers (ModifiersChanged event not functional yet).
         #[allow(deprecated)]
         match event {
-            WindowEvent::KeyboardInput { input, .. } => match input.state {
-                ElementState::Pressed => {
-                    if let Some(key) = input.virtual_keycode {
-                        self.keys_down.insert(key);
-                        self.last_char =
-                            winit_key_to_char(key, input.modifiers.contains(ModifiersState::SHIFT));
-                        if let Some(key_code) = winit_to_ruffle_key_code(key) {
-                            self.last_key = key_code;
+            WindowEvent::KeyboardInput { input, .. } => {
+                let is_shift_down = input.modifiers.contains(ModifiersState::SHIFT);
+                let key_code = input
+                    .virtual_keycode
+                    .and_then(winit_to_ruffle_key_code)
+                    .unwrap_or(KeyCode::Unknown);
+                match input.state {
+                    ElementState::Pressed => {
+                        self.last_scancode = Some(input.scancode);
+                        self.last_char = input
+                            .virtual_keycode
+                            .and_then(|key| winit_key_to_char(key, is_shift_down));
+                        self.last_key = key_code;
+                        if key_code != KeyCode::Unknown {
+                            self.keys_down.insert(input.scancode, key_code);
                             return Some(PlayerEvent::KeyDown { key_code });
-                        } else {
-                            self.last_key = KeyCode::Unknown;
                         }
                     }
-                }
-                ElementState::Released => {
-                    if let Some(key) = input.virtual_keycode {
-                        self.keys_down.remove(&key);
-                        self.last_char =
-                            winit_key_to_char(key, input.modifiers.contains(ModifiersState::SHIFT));
-                        if let Some(key_code) = winit_to_ruffle_key_code(key) {
-                            self.last_key = key_code;
+                    ElementState::Released => {
+                        // Report the key as it was pressed, even if the
+                        // modifiers changed since.
+                        let key_code = self.keys_down.remove(&input.scancode).unwrap_or(key_code);
+                        self.last_char = self.key_chars.remove(&input.scancode).or_else(|| {
+                            input
+                                .virtual_keycode
+                                .and_then(|key| winit_key_to_char(key, is_shift_down))
+                        });
+                        self.last_key = key_code;
+                        if key_code != KeyCode::Unknown {
                             return Some(PlayerEvent::KeyUp { key_code });
-                        } else {
-                            self.last_key = KeyCode::Unknown;
                         }
                     }
                 }
-            },
+            }
             WindowEvent::ReceivedCharacter(codepoint) => {
+                // The character follows the key that typed it, and is what the
+                // key types on the user's keyboard layout.
+                if !codepoint.is_control() {
+                    self.last_char = Some(codepoint);
+                    if let Some(scancode) = self.last_scancode {
+                        self.key_chars.insert(scancode, codepoint);
+                    }
+                }
                 return Some(PlayerEvent::TextInput { codepoint });
             }
             _ => (),
@@ -77,115 +106,15 @@ This content is not yet supported by Ruffle and will likely not run as intended.
 See the following link for more info:
 https://github.com/ruffle-rs/ruffle/wiki/Frequently-Asked-Questions-For-Users";
 
+const SLOW_SCRIPT_MESSAGE: &str = "\
+A script in this movie is causing Ruffle to run slowly. If it continues to run, \
+Ruffle may become unresponsive.
+
+Do you want to abort the script?";
+
 impl UiBackend for DesktopUiBackend {
     fn is_key_down(&self, key: KeyCode) -> bool {
-        match key {
-            KeyCode::Unknown => false,
-            KeyCode::Backspace => self.keys_down.contains(&VirtualKeyCode::Back),
-            KeyCode::Tab => self.keys_down.contains(&VirtualKeyCode::Tab),
-            KeyCode::Return => self.keys_down.contains(&VirtualKeyCode::Return),
-            KeyCode::Shift => {
-                self.keys_down.contains(&VirtualKeyCode::LShift)
-                    || self.keys_down.contains(&VirtualKeyCode::RShift)
-            }
-            KeyCode::Control => {
-                self.keys_down.contains(&VirtualKeyCode::LControl)
-                    || self.keys_down.contains(&VirtualKeyCode::RControl)
-            }
-            KeyCode::Alt => {
-                self.keys_down.contains(&VirtualKeyCode::LAlt)
-                    || self.keys_down.contains(&VirtualKeyCode::RAlt)
-            }
-            KeyCode::CapsLock => self.keys_down.contains(&VirtualKeyCode::Capital),
-            KeyCode::Escape => self.keys_down.contains(&VirtualKeyCode::Escape),
-            KeyCode::Space => self.keys_down.contains(&VirtualKeyCode::Space),
-            KeyCode::Key0 => self.keys_down.contains(&VirtualKeyCode::Key0),
-            KeyCode::Key1 => self.keys_down.contains(&VirtualKeyCode::Key1),
-            KeyCode::Key2 => self.keys_down.contains(&VirtualKeyCode::Key2),
-            KeyCode::Key3 => self.keys_down.contains(&VirtualKeyCode::Key3),
-            KeyCode::Key4 => self.keys_down.contains(&VirtualKeyCode::Key4),
-            KeyCode::Key5 => self.keys_down.contains(&VirtualKeyCode::Key5),
-            KeyCode::Key6 => self.keys_down.contains(&VirtualKeyCode::Key6),
-            KeyCode::Key7 => self.keys_down.contains(&VirtualKeyCode::Key7),
-            KeyCode::Key8 => self.keys_down.contains(&VirtualKeyCode::Key8),
-            KeyCode::Key9 => self.keys_down.contains(&VirtualKeyCode::Key9),
-            KeyCode::A => self.keys_down.contains(&VirtualKeyCode::A),
-            KeyCode::B => self.keys_down.contains(&VirtualKeyCode::B),
-            KeyCode::C => self.keys_down.contains(&VirtualKeyCode::C),
-            KeyCode::D => self.keys_down.contains(&VirtualKeyCode::D),
-            KeyCode::E => self.keys_down.contains(&VirtualKeyCode::E),
-            KeyCode::F => self.keys_down.contains(&VirtualKeyCode::F),
-            KeyCode::G => self.keys_down.contains(&VirtualKeyCode::G),
-            KeyCode::H => self.keys_down.contains(&VirtualKeyCode::H),
-            KeyCode::I => self.keys_down.contains(&VirtualKeyCode::I),
-            KeyCode::J => self.keys_down.contains(&VirtualKeyCode::J),
-            KeyCode::K => self.keys_down.contains(&VirtualKeyCode::K),
-            KeyCode::L => self.keys_down.contains(&VirtualKeyCode::L),
-            KeyCode::M => self.keys_down.contains(&VirtualKeyCode::M),
-            KeyCode::N => self.keys_down.contains(&VirtualKeyCode::N),
-            KeyCode::O => self.keys_down.contains(&VirtualKeyCode::O),
-            KeyCode::P => self.keys_down.contains(&VirtualKeyCode::P),
-            KeyCode::Q => self.keys_down.contains(&VirtualKeyCode::Q),
-            KeyCode::R => self.keys_down.contains(&VirtualKeyCode::R),
-            KeyCode::S => self.keys_down.contains(&VirtualKeyCode::S),
-            KeyCode::T => self.keys_down.contains(&VirtualKeyCode::T),
-            KeyCode::U => self.keys_down.contains(&VirtualKeyCode::U),
-            KeyCode::V => self.keys_down.contains(&VirtualKeyCode::V),
-            KeyCode::W => self.keys_down.contains(&VirtualKeyCode::W),
-            KeyCode::X => self.keys_down.contains(&VirtualKeyCode::X),
-            KeyCode::Y => self.keys_down.contains(&VirtualKeyCode::Y),
-            KeyCode::Z => self.keys_down.contains(&VirtualKeyCode::Z),
-            KeyCode::Semicolon => self.keys_down.contains(&VirtualKeyCode::Semicolon),
-            KeyCode::Equals => self.keys_down.contains(&VirtualKeyCode::Equals),
-            KeyCode::Comma => self.keys_down.contains(&VirtualKeyCode::Comma),
-            KeyCode::Minus => self.keys_down.contains(&VirtualKeyCode::Minus),
-            KeyCode::Period => self.keys_down.contains(&VirtualKeyCode::Period),
-            KeyCode::Slash => self.keys_down.contains(&VirtualKeyCode::Slash),
-            KeyCode::Grave => self.keys_down.contains(&VirtualKeyCode::Grave),
-            KeyCode::LBracket => self.keys_down.contains(&VirtualKeyCode::LBracket),
-            KeyCode::Backslash => self.keys_down.contains(&VirtualKeyCode::Backslash),
-            KeyCode::RBracket => self.keys_down.contains(&VirtualKeyCode::RBracket),
-            KeyCode::Apostrophe => self.keys_down.contains(&VirtualKeyCode::Apostrophe),
-            KeyCode::Numpad0 => self.keys_down.contains(&VirtualKeyCode::Numpad0),
-            KeyCode::Numpad1 => self.keys_down.contains(&VirtualKeyCode::Numpad1),
-            KeyCode::Numpad2 => self.keys_down.contains(&VirtualKeyCode::Numpad2),
-            KeyCode::Numpad3 => self.keys_down.contains(&VirtualKeyCode::Numpad3),
-            KeyCode::Numpad4 => self.keys_down.contains(&VirtualKeyCode::Numpad4),
-            KeyCode::Numpad5 => self.keys_down.contains(&VirtualKeyCode::Numpad5),
-            KeyCode::Numpad6 => self.keys_down.contains(&VirtualKeyCode::Numpad6),
-            KeyCode::Numpad7 => self.keys_down.contains(&VirtualKeyCode::Numpad7),
-            KeyCode::Numpad8 => self.keys_down.contains(&VirtualKeyCode::Numpad8),
-            KeyCode::Numpad9 => self.keys_down.contains(&VirtualKeyCode::Numpad9),
-            KeyCode::Multiply => self.keys_down.contains(&VirtualKeyCode::NumpadMultiply),
-            KeyCode::Plus => self.keys_down.contains(&VirtualKeyCode::NumpadAdd),
-            KeyCode::NumpadMinus => self.keys_down.contains(&VirtualKeyCode::NumpadSubtract),
-            KeyCode::NumpadPeriod => self.keys_down.contains(&VirtualKeyCode::NumpadDecimal),
-            KeyCode::NumpadSlash => self.keys_down.contains(&VirtualKeyCode::NumpadDivide),
-            KeyCode::PgUp => self.keys_down.contains(&VirtualKeyCode::PageUp),
-            KeyCode::PgDown => self.keys_down.contains(&VirtualKeyCode::PageDown),
-            KeyCode::End => self.keys_down.contains(&VirtualKeyCode::End),
-            KeyCode::Home => self.keys_down.contains(&VirtualKeyCode::Home),
-            KeyCode::Left => self.keys_down.contains(&VirtualKeyCode::Left),
-            KeyCode::Up => self.keys_down.contains(&VirtualKeyCode::Up),
-            KeyCode::Right => self.keys_down.contains(&VirtualKeyCode::Right),
-            KeyCode::Down => self.keys_down.contains(&VirtualKeyCode::Down),
-            KeyCode::Insert => self.keys_down.contains(&VirtualKeyCode::Insert),
-            KeyCode::Delete => self.keys_down.contains(&VirtualKeyCode::Delete),
-            KeyCode::Pause => self.keys_down.contains(&VirtualKeyCode::Pause),
-            KeyCode::ScrollLock => self.keys_down.contains(&VirtualKeyCode::Scroll),
-            KeyCode::F1 => self.keys_down.contains(&VirtualKeyCode::F1),
-            KeyCode::F2 => self.keys_down.contains(&VirtualKeyCode::F2),
-            KeyCode::F3 => self.keys_down.contains(&VirtualKeyCode::F3),
-            KeyCode::F4 => self.keys_down.contains(&VirtualKeyCode::F4),
-            KeyCode::F5 => self.keys_down.contains(&VirtualKeyCode::F5),
-            KeyCode::F6 => self.keys_down.contains(&VirtualKeyCode::F6),
-            KeyCode::F7 => self.keys_down.contains(&VirtualKeyCode::F7),
-            KeyCode::F8 => self.keys_down.contains(&VirtualKeyCode::F8),
-            KeyCode::F9 => self.keys_down.contains(&VirtualKeyCode::F9),
-            KeyCode::F10 => self.keys_down.contains(&VirtualKeyCode::F10),
-            KeyCode::F11 => self.keys_down.contains(&VirtualKeyCode::F11),
-            KeyCode::F12 => self.keys_down.contains(&VirtualKeyCode::F12),
-        }
+        self.keys_down.values().any(|&down| down == key)
     }
 
     fn last_key_code(&self) -> KeyCode {
@@ -224,6 +153,10 @@ impl UiBackend for DesktopUiBackend {
         self.window.fullscreen().is_some()
     }
 
+    fn set_frame_anchor(&mut self, _anchor: &str) {}
+
+    fn movie_lifecycle_event(&mut self, _event: MovieLifecycleEvent) {}
+
     fn display_unsupported_message(&self) {
         message_box_ok(
             "Ruffle - Unsupported content",
@@ -232,6 +165,45 @@ impl UiBackend for DesktopUiBackend {
         );
     }
 
+    fn display_slow_script_prompt(&mut self) -> bool {
+        message_box_yes_no(
+            "Ruffle - Slow script",
+            SLOW_SCRIPT_MESSAGE,
+            MessageBoxIcon::Warning,
+            YesNo::Yes,
+        ) == YesNo::Yes
+    }
+
+    /// There's no printer to print to, so the pages are saved as PNGs instead.
+    /// Each page after the first is saved next to the first, numbered.
+    fn print(&mut self, pages: Vec<Bitmap>) {
+        let path = match save_file_dialog_with_filter(
+            "Ruffle - Save printed pages",
+            "page.png",
+            &["*.png"],
+            "PNG images",
+        ) {
+            Some(path) => path,
+            None => return,
+        };
+        let path = Path::new(&path).with_extension("png");
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        for (i, page) in pages.into_iter().enumerate() {
+            let page_path = if i == 0 {
+                path.clone()
+            } else {
+                path.with_file_name(format!("{}-{}.png", stem, i + 1))
+            };
+            if let Err(e) = crate::write_png(page, &page_path) {
+                log::error!("Couldn't save printed page {}: {}", page_path.display(), e);
+            }
+        }
+    }
+
     fn message(&self, message: &str) {
         message_box_ok("Ruffle", message, MessageBoxIcon::Info)
     }
@@ -243,7 +215,7 @@ fn winit_to_ruffle_key_code(key_code: VirtualKeyCode) -> Option<KeyCode> {
     Some(match key_code {
         VirtualKeyCode::Back => KeyCode::Backspace,
         VirtualKeyCode::Tab => KeyCode::Tab,
-        VirtualKeyCode::Return => KeyCode::Return,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => KeyCode::Return,
         VirtualKeyCode::LShift | VirtualKeyCode::RShift => KeyCode::Shift,
         VirtualKeyCode::LControl | VirtualKeyCode::RControl => KeyCode::Control,
         VirtualKeyCode::LAlt | VirtualKeyCode::RAlt => KeyCode::Alt,
@@ -323,6 +295,7 @@ fn winit_to_ruffle_key_code(key_code: VirtualKeyCode) -> Option<KeyCode> {
         VirtualKeyCode::Insert => KeyCode::Insert,
         VirtualKeyCode::Delete => KeyCode::Delete,
         VirtualKeyCode::Pause => KeyCode::Pause,
+        VirtualKeyCode::Numlock => KeyCode::NumLock,
         VirtualKeyCode::Scroll => KeyCode::ScrollLock,
         VirtualKeyCode::F1 => KeyCode::F1,
         VirtualKeyCode::F2 => KeyCode::F2,
@@ -336,6 +309,9 @@ fn winit_to_ruffle_key_code(key_code: VirtualKeyCode) -> Option<KeyCode> {
         VirtualKeyCode::F10 => KeyCode::F10,
         VirtualKeyCode::F11 => KeyCode::F11,
         VirtualKeyCode::F12 => KeyCode::F12,
+        VirtualKeyCode::F13 => KeyCode::F13,
+        VirtualKeyCode::F14 => KeyCode::F14,
+        VirtualKeyCode::F15 => KeyCode::F15,
         _ => return None,
     })
 }
@@ -345,8 +321,8 @@ fn winit_key_to_char(key_code: VirtualKeyCode, is_shift_down: bool) -> Option<ch
     // We need to know the character that a keypress outputs for both key down and key up events,
     // but the winit keyboard API does not provide a way to do this (winit/#753).
     // CharacterReceived events are insufficent because they only fire on key down, not on key up.
-    // This is a half-measure to map from keyboard keys back to a character, but does will not work fully
-    // for international layouts.
+    // This is a half-measure to map from keyboard keys back to a character, and only assumes a US
+    // layout; it is replaced by the actual character once a ReceivedCharacter event arrives.
     Some(match (key_code, is_shift_down) {
         (VirtualKeyCode::Space, _) => ' ',
         (VirtualKeyCode::Key0, _) => '0',
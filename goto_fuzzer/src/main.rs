@@ -0,0 +1,166 @@
+//! Jumps around the timeline of a movie at random, checking the display list
+//! after every step for states that should never happen.
+//!
+//! Gotos are one of the most intricate parts of the player, as every jump has
+//! to rebuild the display list that the target frame would have had if the
+//! movie had played up to it. This runs thousands of random `gotoAndStop`
+//! and `gotoAndPlay` jumps on the root timeline and on nested clips,
+//! interleaved with normal frames, and reports the first step after which
+//! `Player::check_display_list` finds a problem, along with the steps that
+//! led up to it.
+
+use clap::Clap;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use ruffle_core::test_utils::TestPlayer;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many of the steps before a failure are printed.
+const HISTORY_LENGTH: usize = 20;
+
+#[derive(Clap, Debug)]
+#[clap(version, about, author)]
+struct Opt {
+    /// The movie (swf) to fuzz
+    #[clap(name = "swf", parse(from_os_str))]
+    swf: PathBuf,
+
+    /// Number of random steps to take
+    #[clap(short = 's', long = "steps", default_value = "5000")]
+    steps: u32,
+
+    /// The seed of the random steps, to reproduce a failure.
+    /// Defaults to a random seed
+    #[clap(long = "seed")]
+    seed: Option<u64>,
+}
+
+/// A single thing done to the movie.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// Run a number of frames.
+    RunFrames(u32),
+
+    /// `gotoAndStop` or `gotoAndPlay` a frame of a movie clip. `clip` is the
+    /// index of the clip in `TestPlayer::movie_clip_frame_counts`, where 0 is
+    /// the root timeline.
+    Goto { clip: usize, frame: u16, stop: bool },
+}
+
+impl Step {
+    fn random(rng: &mut SmallRng, frame_counts: &[u16]) -> Self {
+        if frame_counts.is_empty() || rng.gen_range(0..2) == 0 {
+            return Step::RunFrames(rng.gen_range(1..=3));
+        }
+
+        // Favour the root timeline, as it rebuilds the most of the display list.
+        let clip = if rng.gen_bool(0.5) {
+            0
+        } else {
+            rng.gen_range(0..frame_counts.len())
+        };
+
+        // Jumps slightly past the end are included, as they are clamped.
+        let frame = rng.gen_range(1..=frame_counts[clip].saturating_add(1));
+        Step::Goto {
+            clip,
+            frame,
+            stop: rng.gen_bool(0.5),
+        }
+    }
+
+    fn run(self, player: &mut TestPlayer) -> Result<(), Box<dyn Error>> {
+        match self {
+            Step::RunFrames(num_frames) => player.run_frames(num_frames),
+            Step::Goto { clip, frame, stop } => player.goto_clip_frame(clip, frame, stop),
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::RunFrames(num_frames) => write!(f, "run {} frame(s)", num_frames),
+            Step::Goto { clip, frame, stop } => write!(
+                f,
+                "clip {}: {}({})",
+                clip,
+                if *stop { "gotoAndStop" } else { "gotoAndPlay" },
+                frame
+            ),
+        }
+    }
+}
+
+fn fuzz(swf: &Path, steps: u32, seed: u64) -> Result<(), Box<dyn Error>> {
+    let mut player = TestPlayer::from_path(swf)?;
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut history = VecDeque::with_capacity(HISTORY_LENGTH);
+
+    // Run the first frame, so that the root clip exists.
+    player.run_frames(1)?;
+
+    for step_number in 1..=steps {
+        // Clips come and go as the movie runs, so pick from the current ones.
+        let step = Step::random(&mut rng, &player.movie_clip_frame_counts());
+        if history.len() == HISTORY_LENGTH {
+            history.pop_front();
+        }
+        history.push_back(step);
+
+        let result = step
+            .run(&mut player)
+            .and_then(|_| player.check_display_list());
+        if let Err(e) = result {
+            let mut message = format!("Step {} failed: {}\n\nLast steps:\n", step_number, e);
+            for step in &history {
+                message.push_str(&format!("  {}\n", step));
+            }
+            message.push_str(&format!("\nDisplay list:\n{}", player.display_list()));
+            return Err(message.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// A seed that differs between runs, for when none is given.
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+fn main() {
+    let opt = Opt::parse();
+    let seed = opt.seed.unwrap_or_else(time_seed);
+    println!("Fuzzing {} with seed {}", opt.swf.display(), seed);
+
+    match fuzz(&opt.swf, opt.steps, seed) {
+        Ok(()) => println!("{} steps ran without problems", opt.steps),
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Rerun with --seed {} to reproduce", seed);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_seed_run() {
+        let swf = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/tests/swfs/avm1/goto_rewind1/test.swf");
+        if let Err(e) = fuzz(&swf, 500, 0x5eed) {
+            panic!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Standalone projectors: the desktop player with a movie appended to it.
+//!
+//! A projector is a copy of the desktop player executable, followed by the
+//! movie, its configuration as TOML, and a footer:
+//!
+//! ```text
+//! [player][movie][configuration][movie length][configuration length]["RUFFLEPJ"]
+//! ```
+//!
+//! Both lengths are little-endian `u64`s. When the desktop player starts, it
+//! looks for this footer at the end of its own executable, and if there is one
+//! plays the appended movie instead of reading the command line.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// The last bytes of every projector.
+const MAGIC: &[u8; 8] = b"RUFFLEPJ";
+
+/// The length of the footer, including `MAGIC`.
+const FOOTER_LENGTH: u64 = 24;
+
+type Error = Box<dyn std::error::Error>;
+
+/// The settings of a projector.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectorConfig {
+    /// The title of the window. Defaults to "Ruffle".
+    pub title: Option<String>,
+
+    /// Whether the movie starts in fullscreen.
+    pub fullscreen: bool,
+
+    /// The width of the window in pixels, if not the width of the movie.
+    pub width: Option<f64>,
+
+    /// The height of the window in pixels, if not the height of the movie.
+    pub height: Option<f64>,
+
+    /// The "flashvars" parameters given to the movie.
+    pub parameters: BTreeMap<String, String>,
+}
+
+impl Default for ProjectorConfig {
+    fn default() -> Self {
+        Self {
+            title: None,
+            fullscreen: true,
+            width: None,
+            height: None,
+            parameters: BTreeMap::new(),
+        }
+    }
+}
+
+impl ProjectorConfig {
+    /// Parse a configuration from TOML. Missing settings keep their defaults.
+    pub fn parse(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+}
+
+/// A movie packaged with the desktop player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Projector {
+    /// The data of the movie.
+    pub movie: Vec<u8>,
+
+    pub config: ProjectorConfig,
+}
+
+impl Projector {
+    /// Read the projector appended to the running executable, if any.
+    pub fn from_current_exe() -> Result<Option<Self>, Error> {
+        Self::from_path(std::env::current_exe()?)
+    }
+
+    /// Read the projector appended to the given file, if any.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Option<Self>, Error> {
+        Self::read(&mut File::open(path)?)
+    }
+
+    /// Read the projector at the end of the given data, if any.
+    pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Option<Self>, Error> {
+        let footer = match read_footer(reader)? {
+            Some(footer) => footer,
+            None => return Ok(None),
+        };
+
+        reader.seek(SeekFrom::Start(footer.player_length))?;
+        let mut movie = vec![0; footer.movie_length as usize];
+        reader.read_exact(&mut movie)?;
+        let mut config = vec![0; footer.config_length as usize];
+        reader.read_exact(&mut config)?;
+        let config = ProjectorConfig::parse(std::str::from_utf8(&config)?)?;
+
+        Ok(Some(Self { movie, config }))
+    }
+
+    /// Write a projector of the given player, movie and TOML configuration.
+    ///
+    /// If the player is already a projector, its movie is replaced.
+    pub fn write<W: Write>(
+        player: &[u8],
+        movie: &[u8],
+        config: &str,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        // Catch mistakes in the configuration now, rather than when the
+        // projector is run.
+        ProjectorConfig::parse(config)?;
+
+        let player_length = read_footer(&mut io::Cursor::new(player))?
+            .map_or(player.len() as u64, |footer| footer.player_length);
+        writer.write_all(&player[..player_length as usize])?;
+        writer.write_all(movie)?;
+        writer.write_all(config.as_bytes())?;
+        writer.write_all(&(movie.len() as u64).to_le_bytes())?;
+        writer.write_all(&(config.len() as u64).to_le_bytes())?;
+        writer.write_all(MAGIC)?;
+        Ok(())
+    }
+}
+
+/// Where the parts of a projector are.
+struct Footer {
+    player_length: u64,
+    movie_length: u64,
+    config_length: u64,
+}
+
+fn read_footer<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Footer>> {
+    let length = reader.seek(SeekFrom::End(0))?;
+    if length < FOOTER_LENGTH {
+        return Ok(None);
+    }
+
+    let mut footer = [0; FOOTER_LENGTH as usize];
+    reader.seek(SeekFrom::End(-(FOOTER_LENGTH as i64)))?;
+    reader.read_exact(&mut footer)?;
+    if &footer[16..] != MAGIC {
+        return Ok(None);
+    }
+
+    let movie_length = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+    let config_length = u64::from_le_bytes(footer[8..16].try_into().unwrap());
+    let player_length = (length - FOOTER_LENGTH)
+        .checked_sub(movie_length)
+        .and_then(|length| length.checked_sub(config_length))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Truncated projector"))?;
+
+    Ok(Some(Footer {
+        player_length,
+        movie_length,
+        config_length,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read() {
+        let config = "title = \"Test\"\nfullscreen = false\n\n[parameters]\nfoo = \"bar\"\n";
+        let mut projector = Vec::new();
+        Projector::write(b"player", b"FWS movie", config, &mut projector).unwrap();
+
+        let read = Projector::read(&mut io::Cursor::new(&projector))
+            .unwrap()
+            .unwrap();
+        assert_eq!(read.movie, b"FWS movie");
+        assert_eq!(read.config.title.as_deref(), Some("Test"));
+        assert!(!read.config.fullscreen);
+        assert_eq!(read.config.parameters["foo"], "bar");
+
+        // Packaging a projector again replaces its movie.
+        let mut repackaged = Vec::new();
+        Projector::write(&projector, b"FWS other", "", &mut repackaged).unwrap();
+        assert!(repackaged.starts_with(b"playerFWS other"));
+        let read = Projector::read(&mut io::Cursor::new(&repackaged))
+            .unwrap()
+            .unwrap();
+        assert_eq!(read.config, ProjectorConfig::default());
+    }
+
+    #[test]
+    fn not_a_projector() {
+        assert_eq!(
+            Projector::read(&mut io::Cursor::new(b"player")).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use clap::Clap;
+use ruffle_projector::Projector;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+#[derive(Clap, Debug)]
+#[clap(name = "Ruffle Projector", author, version)]
+struct Opt {
+    /// The movie (swf) to package
+    #[clap(name = "swf", parse(from_os_str))]
+    swf: PathBuf,
+
+    /// The projector to create.
+    /// Defaults to the name of the movie, with the extension of executables on this platform.
+    #[clap(name = "output", parse(from_os_str))]
+    output_path: Option<PathBuf>,
+
+    /// The desktop player to package the movie with.
+    /// Defaults to the ruffle_desktop executable next to this one.
+    #[clap(long, parse(from_os_str))]
+    player: Option<PathBuf>,
+
+    /// A TOML file of settings for the projector:
+    /// title, fullscreen (true by default), width, height, and a [parameters] table of flashvars.
+    #[clap(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+}
+
+fn package(opt: &Opt) -> Result<PathBuf, Box<dyn Error>> {
+    let player_path = match &opt.player {
+        Some(path) => path.clone(),
+        None => std::env::current_exe()?
+            .with_file_name(format!("ruffle_desktop{}", std::env::consts::EXE_SUFFIX)),
+    };
+    let player = std::fs::read(&player_path)
+        .map_err(|e| format!("Couldn't read player {}: {}", player_path.display(), e))?;
+
+    let movie = std::fs::read(&opt.swf)?;
+    if !matches!(movie.get(0..3), Some(b"FWS") | Some(b"CWS") | Some(b"ZWS")) {
+        return Err(format!("{} is not a swf", opt.swf.display()).into());
+    }
+
+    let config = match &opt.config {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => String::new(),
+    };
+
+    let output_path = opt
+        .output_path
+        .clone()
+        .unwrap_or_else(|| opt.swf.with_extension(std::env::consts::EXE_EXTENSION));
+    if output_path == opt.swf {
+        return Err("The projector would overwrite the movie".into());
+    }
+
+    let mut file = BufWriter::new(File::create(&output_path)?);
+    Projector::write(&player, &movie, &config, &mut file)?;
+    file.flush()?;
+    drop(file);
+
+    // The projector has to be executable, like the player it's made from.
+    std::fs::set_permissions(&output_path, std::fs::metadata(&player_path)?.permissions())?;
+
+    Ok(output_path)
+}
+
+fn main() {
+    let opt = Opt::parse();
+    match package(&opt) {
+        Ok(path) => println!("Created {}", path.display()),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
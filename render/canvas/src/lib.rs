@@ -1,17 +1,23 @@
 use ruffle_core::backend::render::{
     swf::{self, CharacterId, GradientInterpolation, GradientSpread},
-    Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, JpegTagFormat, MovieLibrary,
-    RenderBackend, ShapeHandle, Transform,
+    Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, BoundingBox, Color, JpegTagFormat,
+    MovieLibrary, RenderBackend, ShapeHandle, Transform,
+};
+use ruffle_core::bitmap::decode::{
+    decode_define_bits_jpeg, determine_jpeg_tag_format, premultiply_alpha_rgba,
+    remove_invalid_jpeg_data,
 };
 use ruffle_core::color_transform::ColorTransform;
+use ruffle_core::config::StageQuality;
+use ruffle_core::filters::apply_filters;
 use ruffle_core::shape_utils::{DistilledShape, DrawCommand};
 use ruffle_core::swf::Matrix;
 use ruffle_web_common::JsResult;
-use std::convert::TryInto;
-use wasm_bindgen::{JsCast, JsValue};
+use std::convert::TryFrom;
+use wasm_bindgen::{Clamped, JsCast, JsValue};
 use web_sys::{
     CanvasGradient, CanvasPattern, CanvasRenderingContext2d, Element, HtmlCanvasElement,
-    HtmlImageElement, Path2d, SvgsvgElement,
+    HtmlImageElement, ImageData, Path2d, SvgsvgElement,
 };
 
 type Error = Box<dyn std::error::Error>;
@@ -29,7 +35,33 @@ pub struct WebCanvasRenderBackend {
     viewport_height: u32,
     use_color_transform_hack: bool,
     pixelated_property_value: &'static str,
-    deactivating_mask: bool,
+    /// What is being drawn for each mask that is pushed, innermost last.
+    mask_states: Vec<MaskState>,
+    blend_modes: Vec<swf::BlendMode>,
+
+    /// The area of the canvas redrawn in this frame, if the rest is kept.
+    dirty_rect: Option<BoundingBox>,
+
+    /// Whether bitmaps are smoothed when scaled, which they aren't at low
+    /// quality.
+    smooth_bitmaps: bool,
+}
+
+/// The color that masks are drawn in.
+const MASK_COLOR: &str = "#000";
+
+/// What is being drawn into the render targets of a mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MaskState {
+    /// The mask itself, which only covers pixels and has no color.
+    DrawMask,
+
+    /// The content that the mask is applied to.
+    DrawMaskedContent,
+
+    /// The mask again, once the masked content is done. Nothing has to be
+    /// cleared on a canvas, so this is skipped.
+    ClearMask,
 }
 
 /// Canvas-drawable shape data extracted from an SWF file.
@@ -37,25 +69,16 @@ struct ShapeData(Vec<CanvasDrawCommand>);
 
 struct CanvasColor(String, u8, u8, u8, u8);
 
-/// Convert an f32 to a u8, clamping all out-of-range values to the `u8` range.
-fn clamped_u8_color(v: f32) -> u8 {
-    if v < 0.0 {
-        0
-    } else if v > 255.0 {
-        255
-    } else {
-        v as u8
-    }
-}
-
 impl CanvasColor {
     /// Apply a color transformation to this color.
     fn color_transform(&self, cxform: &ColorTransform) -> CanvasColor {
         let CanvasColor(_, r, g, b, a) = self;
-        let r = clamped_u8_color(*r as f32 * cxform.r_mult.to_f32() + (cxform.r_add as f32));
-        let g = clamped_u8_color(*g as f32 * cxform.g_mult.to_f32() + (cxform.g_add as f32));
-        let b = clamped_u8_color(*b as f32 * cxform.b_mult.to_f32() + (cxform.b_add as f32));
-        let a = clamped_u8_color(*a as f32 * cxform.a_mult.to_f32() + (cxform.a_add as f32));
+        let Color { r, g, b, a } = cxform.transform_color(&Color {
+            r: *r,
+            g: *g,
+            b: *b,
+            a: *a,
+        });
         let colstring = format!("rgba({},{},{},{})", r, g, b, f32::from(a) / 255.0);
         CanvasColor(colstring, r, g, b, a)
     }
@@ -211,7 +234,10 @@ impl WebCanvasRenderBackend {
             viewport_width: 0,
             viewport_height: 0,
             use_color_transform_hack: is_firefox,
-            deactivating_mask: false,
+            mask_states: Vec::new(),
+            blend_modes: Vec::new(),
+            dirty_rect: None,
+            smooth_bitmaps: true,
 
             // For rendering non-smoothed bitmaps.
             // crisp-edges works in Firefox, pixelated works in Chrome (and others)?
@@ -233,7 +259,7 @@ impl WebCanvasRenderBackend {
             encoder.set_depth(png::BitDepth::Eight);
             let data = match bitmap.data {
                 BitmapFormat::Rgba(mut data) => {
-                    ruffle_core::backend::render::unmultiply_alpha_rgba(&mut data[..]);
+                    ruffle_core::bitmap::decode::unmultiply_alpha_rgba(&mut data[..]);
                     encoder.set_color(png::ColorType::RGBA);
                     data
                 }
@@ -252,6 +278,18 @@ impl WebCanvasRenderBackend {
         ))
     }
 
+    /// Whether a mask is being drawn, in which case everything is drawn as
+    /// solid black, as only the pixels it covers matter.
+    fn is_drawing_mask(&self) -> bool {
+        self.mask_states.contains(&MaskState::DrawMask)
+    }
+
+    /// Whether a mask is being drawn a second time, once the masked content
+    /// is done, which is skipped.
+    fn is_clearing_mask(&self) -> bool {
+        self.mask_states.contains(&MaskState::ClearMask)
+    }
+
     // Pushes a fresh canvas onto the stack to use as a render target.
     fn push_render_target(&mut self) {
         self.cur_render_target += 1;
@@ -283,6 +321,9 @@ impl WebCanvasRenderBackend {
         canvas.set_height(self.viewport_height);
         self.canvas = canvas.clone();
         self.context = context.clone();
+        // Resizing the canvas resets the state of its context.
+        self.context
+            .set_image_smoothing_enabled(self.smooth_bitmaps);
         let width = self.canvas.width();
         let height = self.canvas.height();
         self.context
@@ -357,14 +398,25 @@ impl WebCanvasRenderBackend {
         }
     }
 
+    /// Draws everything in the mask color, and fully opaque wherever it
+    /// covers a pixel at all, as Flash ignores the colors and alpha of masks.
+    fn set_mask_filter(&self) {
+        self.color_matrix
+            .set_attribute("values", "0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 255 0")
+            .unwrap();
+        self.context.set_filter("url('#_cm')");
+    }
+
     #[inline]
     fn clear_color_filter(&self) {
         self.context.set_filter("none");
         self.context.set_global_alpha(1.0);
     }
 
+    /// Registers a JPEG without decoding it, by handing it to the browser in
+    /// an `<img>`, which is faster than decoding it in WebAssembly.
     fn register_bitmap_pure_jpeg(&mut self, data: &[u8]) -> Result<BitmapInfo, Error> {
-        let data = ruffle_core::backend::render::remove_invalid_jpeg_data(data);
+        let data = remove_invalid_jpeg_data(data);
         let mut decoder = jpeg_decoder::Decoder::new(&data[..]);
         decoder.read_info()?;
         let metadata = decoder.info().ok_or("Expected JPEG metadata")?;
@@ -386,28 +438,6 @@ impl WebCanvasRenderBackend {
             height: metadata.height,
         })
     }
-
-    fn register_bitmap_raw(&mut self, bitmap: Bitmap) -> Result<BitmapInfo, Error> {
-        let (width, height) = (bitmap.width, bitmap.height);
-        let png = Self::bitmap_to_png_data_uri(bitmap)?;
-
-        let image = HtmlImageElement::new().unwrap();
-        image.set_src(&png);
-
-        let handle = BitmapHandle(self.bitmaps.len());
-        self.bitmaps.push(BitmapData {
-            image,
-            width,
-            height,
-            data: png,
-        });
-
-        Ok(BitmapInfo {
-            handle,
-            width: width.try_into().expect("JPEG dimensions too large"),
-            height: height.try_into().expect("JPEG dimensions too large"),
-        })
-    }
 }
 
 impl RenderBackend for WebCanvasRenderBackend {
@@ -416,6 +446,12 @@ impl RenderBackend for WebCanvasRenderBackend {
         self.viewport_height = height;
     }
 
+    fn set_quality(&mut self, quality: StageQuality) {
+        // Shapes are always anti-aliased by the browser, so only bitmap
+        // smoothing can change.
+        self.smooth_bitmaps = quality != StageQuality::Low;
+    }
+
     fn register_shape(
         &mut self,
         shape: DistilledShape,
@@ -463,40 +499,10 @@ impl RenderBackend for WebCanvasRenderBackend {
         self.register_shape((&shape).into(), None)
     }
 
-    fn register_bitmap_jpeg(
-        &mut self,
-        data: &[u8],
-        jpeg_tables: Option<&[u8]>,
-    ) -> Result<BitmapInfo, Error> {
-        let data = ruffle_core::backend::render::glue_tables_to_jpeg(data, jpeg_tables);
-        self.register_bitmap_pure_jpeg(&data)
-    }
-
-    fn register_bitmap_jpeg_2(&mut self, data: &[u8]) -> Result<BitmapInfo, Error> {
-        if ruffle_core::backend::render::determine_jpeg_tag_format(data) == JpegTagFormat::Jpeg {
-            self.register_bitmap_pure_jpeg(data)
-        } else {
-            let bitmap = ruffle_core::backend::render::decode_define_bits_jpeg(data, None)?;
-            self.register_bitmap_raw(bitmap)
-        }
-    }
-
-    fn register_bitmap_jpeg_3(
-        &mut self,
-        jpeg_data: &[u8],
-        alpha_data: &[u8],
-    ) -> Result<BitmapInfo, Error> {
-        let bitmap =
-            ruffle_core::backend::render::decode_define_bits_jpeg(jpeg_data, Some(alpha_data))?;
-        self.register_bitmap_raw(bitmap)
-    }
-
-    fn register_bitmap_png(
-        &mut self,
-        swf_tag: &swf::DefineBitsLossless,
-    ) -> Result<BitmapInfo, Error> {
-        let bitmap = ruffle_core::backend::render::decode_define_bits_lossless(swf_tag)?;
-
+    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapInfo, Error> {
+        let (width, height) = (bitmap.width, bitmap.height);
+        let info_width = u16::try_from(width).map_err(|_| "Bitmap dimensions too large")?;
+        let info_height = u16::try_from(height).map_err(|_| "Bitmap dimensions too large")?;
         let png = Self::bitmap_to_png_data_uri(bitmap)?;
 
         let image = HtmlImageElement::new().unwrap();
@@ -505,42 +511,90 @@ impl RenderBackend for WebCanvasRenderBackend {
         let handle = BitmapHandle(self.bitmaps.len());
         self.bitmaps.push(BitmapData {
             image,
-            width: swf_tag.width.into(),
-            height: swf_tag.height.into(),
+            width,
+            height,
             data: png,
         });
+
         Ok(BitmapInfo {
             handle,
-            width: swf_tag.width,
-            height: swf_tag.height,
+            width: info_width,
+            height: info_height,
         })
     }
 
+    fn register_bitmap_jpeg(&mut self, data: &[u8]) -> Result<BitmapInfo, Error> {
+        if determine_jpeg_tag_format(data) == JpegTagFormat::Jpeg {
+            self.register_bitmap_pure_jpeg(data)
+        } else {
+            self.register_bitmap(decode_define_bits_jpeg(data, None)?)
+        }
+    }
+
     fn begin_frame(&mut self, clear: Color) {
         // Reset canvas transform in case it was left in a dirty state.
         self.context.reset_transform().unwrap();
+        // The canvas may have been resized since the last frame, which
+        // resets this.
+        self.context
+            .set_image_smoothing_enabled(self.smooth_bitmaps);
 
         let width = self.canvas.width();
         let height = self.canvas.height();
 
+        // Only the dirty part of the canvas is cleared and drawn, by clipping
+        // everything drawn in this frame to it.
+        let (x, y, width, height) = match &self.dirty_rect {
+            Some(dirty_rect) => {
+                let x = dirty_rect.x_min.to_pixels().floor();
+                let y = dirty_rect.y_min.to_pixels().floor();
+                let x_max = dirty_rect.x_max.to_pixels().ceil();
+                let y_max = dirty_rect.y_max.to_pixels().ceil();
+                (x, y, x_max - x, y_max - y)
+            }
+            None => (0.0, 0.0, width.into(), height.into()),
+        };
+        self.context.save();
+        self.context.begin_path();
+        self.context.rect(x, y, width, height);
+        self.context.clip();
+
         let color = format!("rgb({}, {}, {})", clear.r, clear.g, clear.b);
         self.context.set_fill_style(&color.into());
-        self.context
-            .fill_rect(0.0, 0.0, width.into(), height.into());
+        self.context.fill_rect(x, y, width, height);
 
-        self.deactivating_mask = false;
+        self.mask_states.clear();
     }
 
     fn end_frame(&mut self) {
-        // Noop
+        // Remove the clip to the dirty area.
+        self.context.restore();
+    }
+
+    fn supports_dirty_rects(&self) -> bool {
+        true
+    }
+
+    fn set_dirty_rect(&mut self, dirty_rect: Option<&BoundingBox>) {
+        self.dirty_rect = dirty_rect.cloned();
     }
 
     fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, _smoothing: bool) {
-        if self.deactivating_mask {
+        if self.is_clearing_mask() {
             return;
         }
 
         self.set_transform(&transform.matrix);
+        if self.is_drawing_mask() {
+            // Bitmaps mask with their whole rectangle.
+            if let Some(bitmap) = self.bitmaps.get(bitmap.0) {
+                self.context.set_fill_style(&MASK_COLOR.into());
+                self.context
+                    .fill_rect(0.0, 0.0, bitmap.width.into(), bitmap.height.into());
+            }
+            return;
+        }
+
         self.set_color_filter(transform);
         if let Some(bitmap) = self.bitmaps.get(bitmap.0) {
             let _ = self
@@ -551,11 +605,53 @@ impl RenderBackend for WebCanvasRenderBackend {
     }
 
     fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform) {
-        if self.deactivating_mask {
+        if self.is_clearing_mask() {
             return;
         }
 
         self.set_transform(&transform.matrix);
+        if self.is_drawing_mask() {
+            if let Some(shape) = self.shapes.get(shape.0) {
+                for command in shape.0.iter() {
+                    match command {
+                        CanvasDrawCommand::Fill { path, .. } => {
+                            self.context.set_fill_style(&MASK_COLOR.into());
+                            self.context.fill_with_path_2d(path);
+                        }
+                        CanvasDrawCommand::Stroke {
+                            path,
+                            line_width,
+                            line_cap,
+                            line_join,
+                            miter_limit,
+                            ..
+                        } => {
+                            self.context.set_line_width(*line_width);
+                            self.context.set_line_cap(&line_cap);
+                            self.context.set_line_join(&line_join);
+                            self.context.set_miter_limit(*miter_limit);
+                            self.context.set_stroke_style(&MASK_COLOR.into());
+                            self.context.stroke_with_path(&path);
+                        }
+                        CanvasDrawCommand::DrawImage {
+                            image,
+                            x_min,
+                            y_min,
+                        } => {
+                            // Fills that are drawn as an SVG image cover the
+                            // mask wherever the image has any alpha.
+                            self.set_mask_filter();
+                            let _ = self
+                                .context
+                                .draw_image_with_html_image_element(&image, *x_min, *y_min);
+                            self.clear_color_filter();
+                        }
+                    }
+                }
+            }
+            return;
+        }
+
         if let Some(shape) = self.shapes.get(shape.0) {
             for command in shape.0.iter() {
                 match command {
@@ -615,23 +711,27 @@ impl RenderBackend for WebCanvasRenderBackend {
     }
 
     fn draw_rect(&mut self, color: Color, matrix: &Matrix) {
-        if self.deactivating_mask {
+        if self.is_clearing_mask() {
             return;
         }
 
         self.set_transform(matrix);
         self.clear_color_filter();
 
-        self.context.set_fill_style(
-            &format!(
-                "rgba({},{},{},{})",
-                color.r,
-                color.g,
-                color.b,
-                f32::from(color.a) / 255.0
-            )
-            .into(),
-        );
+        if self.is_drawing_mask() {
+            self.context.set_fill_style(&MASK_COLOR.into());
+        } else {
+            self.context.set_fill_style(
+                &format!(
+                    "rgba({},{},{},{})",
+                    color.r,
+                    color.g,
+                    color.b,
+                    f32::from(color.a) / 255.0
+                )
+                .into(),
+            );
+        }
         self.context.fill_rect(0.0, 0.0, 1.0, 1.0);
 
         self.clear_color_filter();
@@ -641,16 +741,22 @@ impl RenderBackend for WebCanvasRenderBackend {
         // In the canvas backend, masks are implemented using two render targets.
         // We render the masker clips to the first render target.
         self.push_render_target();
+        self.mask_states.push(MaskState::DrawMask);
     }
     fn activate_mask(&mut self) {
         // We render the maskee clips to the second render target.
         self.push_render_target();
+        if let Some(state) = self.mask_states.last_mut() {
+            *state = MaskState::DrawMaskedContent;
+        }
     }
     fn deactivate_mask(&mut self) {
-        self.deactivating_mask = true;
+        if let Some(state) = self.mask_states.last_mut() {
+            *state = MaskState::ClearMask;
+        }
     }
     fn pop_mask(&mut self) {
-        self.deactivating_mask = false;
+        self.mask_states.pop();
 
         let (maskee_canvas, maskee_context) = self.pop_render_target();
         let (masker_canvas, _masker_context) = self.pop_render_target();
@@ -662,28 +768,17 @@ impl RenderBackend for WebCanvasRenderBackend {
 
         // We draw the masker onto the maskee using the "destination-in" blend mode.
         // This will filter out pixels where the maskee alpha == 0.
+        // The mask art was drawn in solid black, because Flash ignores the
+        // colors and alpha of masks.
         maskee_context
             .set_global_composite_operation("destination-in")
             .unwrap();
-
-        // Force alpha to 100% for the mask art, because Flash ignores alpha in masks.
-        // Otherwise canvas blend modes will draw the masked clip as transparent.
-        // TODO: Doesn't work on Safari because it doesn't support context.filter.
-        self.color_matrix
-            .set_attribute(
-                "values",
-                &"1.0 0 0 0 0 0 1.0 0 0 0 0 0 1.0 0 0 0 0 0 256.0 0",
-            )
-            .warn_on_error();
-
-        maskee_context.set_filter("url('#_cm')");
         maskee_context
             .draw_image_with_html_canvas_element(&masker_canvas, 0.0, 0.0)
             .unwrap();
         maskee_context
             .set_global_composite_operation("source-over")
             .unwrap();
-        maskee_context.set_filter("none");
 
         // Finally, we draw the finalized masked onto the main canvas.
         self.context.reset_transform().warn_on_error();
@@ -692,6 +787,151 @@ impl RenderBackend for WebCanvasRenderBackend {
             .unwrap();
     }
 
+    fn push_blend_mode(&mut self, blend_mode: swf::BlendMode) {
+        // Blended content is drawn to its own render target, which is then
+        // composited as a whole onto the content beneath it.
+        self.blend_modes.push(blend_mode);
+        self.push_render_target();
+    }
+
+    fn pop_blend_mode(&mut self) {
+        let (layer_canvas, _layer_context) = self.pop_render_target();
+        let blend_mode = self.blend_modes.pop().unwrap_or(swf::BlendMode::Normal);
+
+        // The alpha and erase modes only apply within a parent layer;
+        // otherwise, they are drawn normally.
+        let in_layer = !self.blend_modes.is_empty();
+        let composite_operation = match blend_mode {
+            swf::BlendMode::Multiply => "multiply",
+            swf::BlendMode::Screen => "screen",
+            swf::BlendMode::Lighten => "lighten",
+            swf::BlendMode::Darken => "darken",
+            swf::BlendMode::Difference => "difference",
+            swf::BlendMode::Add => "lighter",
+            swf::BlendMode::Overlay => "overlay",
+            swf::BlendMode::HardLight => "hard-light",
+            swf::BlendMode::Alpha if in_layer => "destination-in",
+            swf::BlendMode::Erase if in_layer => "destination-out",
+            // TODO: Subtract and invert have no canvas equivalent.
+            _ => "source-over",
+        };
+
+        self.context.reset_transform().warn_on_error();
+        self.context
+            .set_global_composite_operation(composite_operation)
+            .warn_on_error();
+        self.context
+            .draw_image_with_html_canvas_element(&layer_canvas, 0.0, 0.0)
+            .unwrap();
+        self.context
+            .set_global_composite_operation("source-over")
+            .warn_on_error();
+    }
+
+    fn push_filter_layer(&mut self) -> bool {
+        // The filtered object is drawn to its own render target, which is
+        // filtered on the CPU once it is popped.
+        self.push_render_target();
+        true
+    }
+
+    fn pop_filter_layer(&mut self, filters: &[swf::Filter], bounds: &BoundingBox) {
+        let (layer_canvas, layer_context) = self.pop_render_target();
+
+        // Only read back the area that the filtered object can cover. Without
+        // one, the layer is drawn unfiltered, so that nothing drawn into it is
+        // lost.
+        let x_min = bounds.x_min.to_pixels().floor().max(0.0);
+        let y_min = bounds.y_min.to_pixels().floor().max(0.0);
+        let x_max = bounds
+            .x_max
+            .to_pixels()
+            .ceil()
+            .min(self.viewport_width.into());
+        let y_max = bounds
+            .y_max
+            .to_pixels()
+            .ceil()
+            .min(self.viewport_height.into());
+        let (width, height) = (x_max - x_min, y_max - y_min);
+        let image_data = if bounds.valid && width > 0.0 && height > 0.0 {
+            layer_context.reset_transform().warn_on_error();
+            layer_context
+                .get_image_data(x_min, y_min, width, height)
+                .ok()
+        } else {
+            None
+        };
+        if let Some(image_data) = image_data {
+            // Canvas pixels are straight alpha, but the filters expect premultiplied alpha.
+            let mut rgba = image_data.data().to_vec();
+            for pixel in rgba.chunks_exact_mut(4) {
+                let alpha = u16::from(pixel[3]);
+                for channel in &mut pixel[0..3] {
+                    *channel = ((u16::from(*channel) * alpha + 127) / 255) as u8;
+                }
+            }
+
+            let mut bitmap = Bitmap {
+                width: width as u32,
+                height: height as u32,
+                data: BitmapFormat::Rgba(rgba),
+            };
+            apply_filters(&mut bitmap, filters);
+
+            if let BitmapFormat::Rgba(mut rgba) = bitmap.data {
+                for pixel in rgba.chunks_exact_mut(4) {
+                    let alpha = u16::from(pixel[3]);
+                    if alpha > 0 {
+                        for channel in &mut pixel[0..3] {
+                            *channel =
+                                ((u16::from(*channel) * 255 + alpha / 2) / alpha).min(255) as u8;
+                        }
+                    }
+                }
+                if let Ok(image_data) = ImageData::new_with_u8_clamped_array_and_sh(
+                    Clamped(&rgba[..]),
+                    width as u32,
+                    height as u32,
+                ) {
+                    layer_context
+                        .put_image_data(&image_data, x_min, y_min)
+                        .warn_on_error();
+                }
+            }
+        }
+
+        // Composite the filtered layer onto the parent render target.
+        self.context.reset_transform().warn_on_error();
+        self.context
+            .draw_image_with_html_canvas_element(&layer_canvas, 0.0, 0.0)
+            .unwrap();
+    }
+
+    fn begin_capture(&mut self) -> bool {
+        // The frame is drawn to an offscreen canvas, leaving the screen alone.
+        self.push_render_target();
+        true
+    }
+
+    fn end_capture(&mut self) -> Option<Bitmap> {
+        let (width, height) = (self.viewport_width, self.viewport_height);
+        self.context.reset_transform().warn_on_error();
+        let image_data = self
+            .context
+            .get_image_data(0.0, 0.0, width.into(), height.into());
+        self.pop_render_target();
+
+        // Canvas pixels are straight alpha.
+        let mut rgba = image_data.ok()?.data().to_vec();
+        premultiply_alpha_rgba(&mut rgba);
+        Some(Bitmap {
+            width,
+            height,
+            data: BitmapFormat::Rgba(rgba),
+        })
+    }
+
     fn get_bitmap_pixels(&mut self, bitmap: BitmapHandle) -> Option<Bitmap> {
         let window = web_sys::window().unwrap();
         let document = window.document().unwrap();
@@ -738,7 +978,7 @@ impl RenderBackend for WebCanvasRenderBackend {
         rgba: Vec<u8>,
     ) -> Result<BitmapHandle, Error> {
         Ok(self
-            .register_bitmap_raw(Bitmap {
+            .register_bitmap(Bitmap {
                 width,
                 height,
                 data: BitmapFormat::Rgba(rgba),
@@ -1451,3 +1691,122 @@ pub fn srgb_to_linear(mut color: swf::Color) -> swf::Color {
     color.b = to_linear_channel(color.b);
     color
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::shape_utils::DrawPath;
+    use ruffle_core::swf::Twips;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Reads the color of a pixel of the canvas, as `[r, g, b, a]`.
+    fn pixel(canvas: &HtmlCanvasElement, x: f64, y: f64) -> Vec<u8> {
+        let context: CanvasRenderingContext2d = canvas
+            .get_context("2d")
+            .unwrap()
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+        context.get_image_data(x, y, 1.0, 1.0).unwrap().data().0
+    }
+
+    #[wasm_bindgen_test]
+    async fn gradient_masks_cover_their_shape() {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas: HtmlCanvasElement = document
+            .create_element("canvas")
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+        canvas.set_width(40);
+        canvas.set_height(20);
+        document.body().unwrap().append_child(&canvas).unwrap();
+        let mut renderer = WebCanvasRenderBackend::new(&canvas).unwrap();
+        renderer.set_viewport_dimensions(40, 20);
+
+        // A 20x20 square on the left half, filled with a gradient, which is
+        // drawn as an SVG image.
+        let fill_style = swf::FillStyle::LinearGradient(swf::Gradient {
+            matrix: Matrix::identity(),
+            spread: GradientSpread::Pad,
+            interpolation: GradientInterpolation::Rgb,
+            records: vec![
+                swf::GradientRecord {
+                    ratio: 0,
+                    color: Color::from_rgb(0x00ff00, 255),
+                },
+                swf::GradientRecord {
+                    ratio: 255,
+                    color: Color::from_rgb(0x0000ff, 255),
+                },
+            ],
+        });
+        let size = Twips::from_pixels(20.0);
+        let bounds = BoundingBox {
+            x_min: Twips::new(0),
+            y_min: Twips::new(0),
+            x_max: size,
+            y_max: size,
+            valid: true,
+        };
+        let shape = renderer.register_shape(
+            DistilledShape {
+                paths: vec![DrawPath::Fill {
+                    style: &fill_style,
+                    commands: vec![
+                        DrawCommand::MoveTo {
+                            x: Twips::new(0),
+                            y: Twips::new(0),
+                        },
+                        DrawCommand::LineTo {
+                            x: size,
+                            y: Twips::new(0),
+                        },
+                        DrawCommand::LineTo { x: size, y: size },
+                        DrawCommand::LineTo {
+                            x: Twips::new(0),
+                            y: size,
+                        },
+                        DrawCommand::LineTo {
+                            x: Twips::new(0),
+                            y: Twips::new(0),
+                        },
+                    ],
+                }],
+                shape_bounds: bounds.clone(),
+                edge_bounds: bounds,
+                id: 1,
+            },
+            None,
+        );
+        match &renderer.shapes[shape.0].0[..] {
+            [CanvasDrawCommand::DrawImage { image, .. }] => {
+                JsFuture::from(image.decode()).await.unwrap();
+            }
+            _ => panic!("Expected the gradient to be drawn as an image"),
+        }
+
+        // Mask a red rectangle over the whole stage with the square.
+        let transform = Transform::default();
+        let stage = Matrix {
+            a: 40.0,
+            d: 20.0,
+            ..Matrix::identity()
+        };
+        renderer.begin_frame(Color::from_rgb(0xffffff, 255));
+        renderer.push_mask();
+        renderer.render_shape(shape, &transform);
+        renderer.activate_mask();
+        renderer.draw_rect(Color::from_rgb(0xff0000, 255), &stage);
+        renderer.deactivate_mask();
+        renderer.render_shape(shape, &transform);
+        renderer.pop_mask();
+        renderer.end_frame();
+
+        assert_eq!(pixel(&canvas, 10.0, 10.0), vec![255, 0, 0, 255]);
+        assert_eq!(pixel(&canvas, 30.0, 10.0), vec![255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,350 @@
+//! Packing of small bitmaps into shared atlas textures.
+//!
+//! Drawing many small bitmaps that each have their own texture means a
+//! texture bind for every draw. Instead, backends can pack them into a few
+//! large pages, and draw each bitmap from its region of a page.
+//!
+//! Pages are packed in shelves: rows of bitmaps of similar heights, which are
+//! filled from left to right. Every bitmap is surrounded by a pixel of padding,
+//! which backends should fill by repeating the bitmap's edge pixels, so that
+//! filtering doesn't bleed the neighbouring bitmaps into it.
+
+/// The padding around each bitmap in an atlas page, in pixels.
+pub const ATLAS_PADDING: u32 = 1;
+
+/// How bitmaps are packed into atlas pages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasConfig {
+    /// The width and height of each page, in pixels.
+    ///
+    /// A size of zero disables the atlas, so that every bitmap gets its own
+    /// texture.
+    pub page_size: u32,
+
+    /// The largest width or height of a bitmap that is packed into a page.
+    /// Larger bitmaps get their own texture.
+    pub max_bitmap_size: u32,
+}
+
+impl Default for AtlasConfig {
+    fn default() -> Self {
+        Self {
+            page_size: 2048,
+            max_bitmap_size: 256,
+        }
+    }
+}
+
+/// The region of an atlas page that a bitmap was packed into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasAllocation {
+    /// The index of the page.
+    pub page: usize,
+
+    /// The position of the bitmap in the page, not including its padding.
+    pub x: u32,
+    pub y: u32,
+
+    /// The size of the bitmap.
+    pub width: u32,
+    pub height: u32,
+}
+
+impl AtlasAllocation {
+    /// The bitmap's region of the page in texture coordinates, as the
+    /// position of its top left corner followed by its size.
+    pub fn uv_rect(&self, page_size: u32) -> [f32; 4] {
+        let page_size = page_size as f32;
+        [
+            self.x as f32 / page_size,
+            self.y as f32 / page_size,
+            self.width as f32 / page_size,
+            self.height as f32 / page_size,
+        ]
+    }
+}
+
+/// Allocates regions of atlas pages to bitmaps.
+///
+/// This only does the bookkeeping; backends are responsible for creating a
+/// texture for every page that is handed out.
+#[derive(Debug)]
+pub struct TextureAtlas {
+    config: AtlasConfig,
+    pages: Vec<Page>,
+}
+
+impl TextureAtlas {
+    pub fn new(config: AtlasConfig) -> Self {
+        Self {
+            config,
+            pages: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> AtlasConfig {
+        self.config
+    }
+
+    /// The number of pages that have been handed out so far.
+    pub fn num_pages(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Find a place for a bitmap of the given size, creating a new page if no
+    /// existing page has room for it.
+    ///
+    /// Returns `None` if the bitmap is too large, or empty, in which case it
+    /// should get a texture of its own.
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasAllocation> {
+        let max_size = self
+            .config
+            .max_bitmap_size
+            .min(self.config.page_size.saturating_sub(2 * ATLAS_PADDING));
+        if width == 0 || height == 0 || width > max_size || height > max_size {
+            return None;
+        }
+
+        let padded_width = width + 2 * ATLAS_PADDING;
+        let padded_height = height + 2 * ATLAS_PADDING;
+        let page_size = self.config.page_size;
+
+        let found = self.pages.iter_mut().enumerate().find_map(|(index, page)| {
+            page.allocate(padded_width, padded_height, page_size)
+                .map(|position| (index, position))
+        });
+        let (page, (x, y)) = match found {
+            Some(found) => found,
+            None => {
+                let mut page = Page::default();
+                let position = page.allocate(padded_width, padded_height, page_size)?;
+                self.pages.push(page);
+                (self.pages.len() - 1, position)
+            }
+        };
+
+        Some(AtlasAllocation {
+            page,
+            x: x + ATLAS_PADDING,
+            y: y + ATLAS_PADDING,
+            width,
+            height,
+        })
+    }
+
+    /// Give back the region of a bitmap that is no longer used.
+    ///
+    /// Once every bitmap on a page has been deallocated, the whole page is
+    /// free to be packed again.
+    pub fn deallocate(&mut self, allocation: &AtlasAllocation) {
+        if let Some(page) = self.pages.get_mut(allocation.page) {
+            page.deallocate(
+                allocation.x - ATLAS_PADDING,
+                allocation.y - ATLAS_PADDING,
+                allocation.width + 2 * ATLAS_PADDING,
+            );
+        }
+    }
+}
+
+/// A single atlas page, divided into shelves.
+#[derive(Debug, Default)]
+struct Page {
+    shelves: Vec<Shelf>,
+
+    /// The top of the unused space below the last shelf.
+    next_shelf_y: u32,
+
+    /// The number of bitmaps on this page.
+    num_allocations: usize,
+}
+
+impl Page {
+    fn allocate(&mut self, width: u32, height: u32, page_size: u32) -> Option<(u32, u32)> {
+        // Prefer the shortest shelf that the bitmap fits in, but don't waste
+        // more than half of a shelf's height on a short bitmap.
+        let mut shelves: Vec<&mut Shelf> = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && shelf.height <= height * 2)
+            .collect();
+        shelves.sort_by_key(|shelf| shelf.height);
+        let position = shelves
+            .into_iter()
+            .find_map(|shelf| shelf.allocate(width, page_size).map(|x| (x, shelf.y)));
+
+        let position = match position {
+            Some(position) => position,
+            None => {
+                if self.next_shelf_y + height > page_size {
+                    return None;
+                }
+
+                let mut shelf = Shelf::new(self.next_shelf_y, height);
+                let x = shelf.allocate(width, page_size)?;
+                self.next_shelf_y += height;
+                self.shelves.push(shelf);
+                (x, self.next_shelf_y - height)
+            }
+        };
+
+        self.num_allocations += 1;
+        Some(position)
+    }
+
+    fn deallocate(&mut self, x: u32, y: u32, width: u32) {
+        if let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == y) {
+            shelf.deallocate(x, width);
+            self.num_allocations = self.num_allocations.saturating_sub(1);
+        }
+
+        if self.num_allocations == 0 {
+            self.shelves.clear();
+            self.next_shelf_y = 0;
+        }
+    }
+}
+
+/// A row of bitmaps in an atlas page.
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+
+    /// The left edge of the unused space after the last bitmap.
+    next_x: u32,
+
+    /// Spans of deallocated space before `next_x`, as their position and
+    /// width, sorted by position.
+    free_spans: Vec<(u32, u32)>,
+}
+
+impl Shelf {
+    fn new(y: u32, height: u32) -> Self {
+        Self {
+            y,
+            height,
+            next_x: 0,
+            free_spans: Vec::new(),
+        }
+    }
+
+    fn allocate(&mut self, width: u32, page_size: u32) -> Option<u32> {
+        if let Some(index) = self
+            .free_spans
+            .iter()
+            .position(|(_, span_width)| *span_width >= width)
+        {
+            let (x, span_width) = self.free_spans[index];
+            if span_width == width {
+                self.free_spans.remove(index);
+            } else {
+                self.free_spans[index] = (x + width, span_width - width);
+            }
+            return Some(x);
+        }
+
+        if self.next_x + width > page_size {
+            return None;
+        }
+
+        let x = self.next_x;
+        self.next_x += width;
+        Some(x)
+    }
+
+    fn deallocate(&mut self, x: u32, width: u32) {
+        let index = self
+            .free_spans
+            .iter()
+            .position(|(span_x, _)| *span_x > x)
+            .unwrap_or(self.free_spans.len());
+        self.free_spans.insert(index, (x, width));
+
+        // Merge adjacent spans, and give back any space at the end.
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(self.free_spans.len());
+        for (span_x, span_width) in self.free_spans.drain(..) {
+            match merged.last_mut() {
+                Some((last_x, last_width)) if *last_x + *last_width == span_x => {
+                    *last_width += span_width;
+                }
+                _ => merged.push((span_x, span_width)),
+            }
+        }
+        if let Some((last_x, last_width)) = merged.last().copied() {
+            if last_x + last_width == self.next_x {
+                self.next_x = last_x;
+                merged.pop();
+            }
+        }
+        self.free_spans = merged;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atlas(page_size: u32, max_bitmap_size: u32) -> TextureAtlas {
+        TextureAtlas::new(AtlasConfig {
+            page_size,
+            max_bitmap_size,
+        })
+    }
+
+    #[test]
+    fn packs_bitmaps_into_shelves() {
+        let mut atlas = atlas(64, 32);
+        let first = atlas.allocate(10, 10).unwrap();
+        let second = atlas.allocate(10, 8).unwrap();
+        let third = atlas.allocate(10, 30).unwrap();
+
+        assert_eq!((first.page, first.x, first.y), (0, 1, 1));
+        // Similar heights share a shelf.
+        assert_eq!((second.page, second.x, second.y), (0, 13, 1));
+        // Much taller bitmaps start a new shelf.
+        assert_eq!((third.page, third.x, third.y), (0, 1, 13));
+        assert_eq!(
+            first.uv_rect(64),
+            [1.0 / 64.0, 1.0 / 64.0, 10.0 / 64.0, 10.0 / 64.0]
+        );
+    }
+
+    #[test]
+    fn rejects_large_and_empty_bitmaps() {
+        let mut atlas = atlas(64, 32);
+        assert_eq!(atlas.allocate(33, 10), None);
+        assert_eq!(atlas.allocate(0, 10), None);
+        assert_eq!(atlas.num_pages(), 0);
+
+        // Disabled atlases never pack anything.
+        assert_eq!(self::atlas(0, 32).allocate(1, 1), None);
+    }
+
+    #[test]
+    fn opens_new_pages_when_full() {
+        let mut atlas = atlas(64, 62);
+        let first = atlas.allocate(62, 62).unwrap();
+        let second = atlas.allocate(62, 62).unwrap();
+        assert_eq!(first.page, 0);
+        assert_eq!(second.page, 1);
+        assert_eq!(atlas.num_pages(), 2);
+    }
+
+    #[test]
+    fn reuses_deallocated_space() {
+        let mut atlas = atlas(64, 32);
+        let first = atlas.allocate(10, 10).unwrap();
+        let second = atlas.allocate(10, 10).unwrap();
+        atlas.deallocate(&first);
+
+        let third = atlas.allocate(6, 10).unwrap();
+        assert_eq!((third.x, third.y), (first.x, first.y));
+
+        // Emptying a page lets it be packed from scratch.
+        atlas.deallocate(&second);
+        atlas.deallocate(&third);
+        let fourth = atlas.allocate(30, 30).unwrap();
+        assert_eq!((fourth.page, fourth.x, fourth.y), (0, 1, 1));
+    }
+}
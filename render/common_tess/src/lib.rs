@@ -6,11 +6,17 @@ use lyon::tessellation::{
 };
 use lyon::tessellation::{FillOptions, StrokeOptions};
 use ruffle_core::backend::render::{srgb_to_linear, swf, BitmapHandle};
+use ruffle_core::config::StageQuality;
 use ruffle_core::shape_utils::{DistilledShape, DrawCommand, DrawPath};
 
+pub mod atlas;
+
 pub struct ShapeTessellator {
     fill_tess: FillTessellator,
     stroke_tess: StrokeTessellator,
+
+    /// How far the flattened curves may stray from the real ones, in pixels.
+    tolerance: f32,
 }
 
 impl ShapeTessellator {
@@ -18,14 +24,28 @@ impl ShapeTessellator {
         Self {
             fill_tess: FillTessellator::new(),
             stroke_tess: StrokeTessellator::new(),
+            tolerance: FillOptions::DEFAULT_TOLERANCE,
         }
     }
 
+    /// Flatten curves more coarsely at lower stage qualities, trading their
+    /// smoothness for fewer triangles. This only affects the shapes that are
+    /// tessellated afterwards.
+    pub fn set_quality(&mut self, quality: StageQuality) {
+        self.tolerance = match quality {
+            StageQuality::Low => 0.5,
+            StageQuality::Medium => 0.25,
+            StageQuality::High => FillOptions::DEFAULT_TOLERANCE,
+            StageQuality::Best => FillOptions::DEFAULT_TOLERANCE / 2.0,
+        };
+    }
+
     pub fn tessellate_shape<F>(&mut self, shape: DistilledShape, get_bitmap: F) -> Mesh
     where
         F: Fn(swf::CharacterId) -> Option<(u32, u32, BitmapHandle)>,
     {
         let mut mesh = Vec::new();
+        let fill_options = FillOptions::even_odd().with_tolerance(self.tolerance);
 
         let mut lyon_mesh: VertexBuffers<_, u32> = VertexBuffers::new();
 
@@ -55,7 +75,7 @@ impl ShapeTessellator {
 
                         if let Err(e) = self.fill_tess.tessellate_path(
                             &ruffle_path_to_lyon_path(commands, true),
-                            &FillOptions::even_odd(),
+                            &fill_options,
                             &mut buffers_builder,
                         ) {
                             // This may just be a degenerate path; skip it.
@@ -75,7 +95,7 @@ impl ShapeTessellator {
 
                         if let Err(e) = self.fill_tess.tessellate_path(
                             &ruffle_path_to_lyon_path(commands, true),
-                            &FillOptions::even_odd(),
+                            &fill_options,
                             &mut buffers_builder,
                         ) {
                             // This may just be a degenerate path; skip it.
@@ -105,7 +125,7 @@ impl ShapeTessellator {
 
                         if let Err(e) = self.fill_tess.tessellate_path(
                             &ruffle_path_to_lyon_path(commands, true),
-                            &FillOptions::even_odd(),
+                            &fill_options,
                             &mut buffers_builder,
                         ) {
                             // This may just be a degenerate path; skip it.
@@ -138,7 +158,7 @@ impl ShapeTessellator {
 
                         if let Err(e) = self.fill_tess.tessellate_path(
                             &ruffle_path_to_lyon_path(commands, true),
-                            &FillOptions::even_odd(),
+                            &fill_options,
                             &mut buffers_builder,
                         ) {
                             // This may just be a degenerate path; skip it.
@@ -173,7 +193,7 @@ impl ShapeTessellator {
 
                         if let Err(e) = self.fill_tess.tessellate_path(
                             &ruffle_path_to_lyon_path(commands, true),
-                            &FillOptions::even_odd(),
+                            &fill_options,
                             &mut buffers_builder,
                         ) {
                             // This may just be a degenerate path; skip it.
@@ -215,6 +235,7 @@ impl ShapeTessellator {
                     let width = (style.width.to_pixels() as f32).max(1.0);
 
                     let mut options = StrokeOptions::default()
+                        .with_tolerance(self.tolerance)
                         .with_line_width(width)
                         .with_start_cap(match style.start_cap {
                             swf::LineCapStyle::None => tessellation::LineCap::Butt,
@@ -0,0 +1,1140 @@
+//! A render backend that rasterizes everything on the CPU.
+//!
+//! Shapes are tessellated into triangles by `ruffle_render_common_tess`, just
+//! like the GPU backends, and are then filled into an RGBA framebuffer with
+//! premultiplied alpha. No GPU or windowing system is needed, which makes this
+//! backend suitable for headless rendering: screenshot tests, thumbnailing on
+//! a server, and machines without GL or WebGPU.
+//!
+//! Pixels are sampled once at their center, so edges are not anti-aliased.
+//! Masks use a stencil buffer in the same way as the WebGL backend.
+
+use ruffle_core::backend::render::{
+    Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, BoundingBox, Color, MovieLibrary,
+    RenderBackend, ShapeHandle, Transform,
+};
+use ruffle_core::config::StageQuality;
+use ruffle_core::filters::apply_filters;
+use ruffle_core::shape_utils::DistilledShape;
+use ruffle_core::swf;
+use ruffle_render_common_tess::{Draw, DrawType, Gradient, GradientType, ShapeTessellator, Vertex};
+
+type Error = Box<dyn std::error::Error>;
+
+/// The number of fractional bits kept in vertex positions when rasterizing.
+///
+/// Vertices are snapped to this grid so that the edge functions of adjacent
+/// triangles agree exactly, and pixels on a shared edge are drawn only once.
+const SUBPIXEL_BITS: u32 = 4;
+
+/// Vertex positions are clamped to this many pixels from the origin, so that
+/// the edge functions can't overflow.
+const MAX_COORDINATE: f32 = 16_777_216.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MaskState {
+    NoMask,
+    DrawMaskStencil,
+    DrawMaskedContent,
+    ClearMaskStencil,
+}
+
+pub struct SoftwareRenderBackend {
+    shape_tessellator: ShapeTessellator,
+    meshes: Vec<Mesh>,
+    textures: Vec<Texture>,
+    target: RenderTarget,
+
+    /// The bitmaps of any open offscreen targets, each with the target that
+    /// was being drawn to before it.
+    offscreen_targets: Vec<(BitmapHandle, RenderTarget)>,
+}
+
+impl SoftwareRenderBackend {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            shape_tessellator: ShapeTessellator::new(),
+            meshes: vec![],
+            textures: vec![],
+            target: RenderTarget::new(width, height),
+            offscreen_targets: vec![],
+        }
+    }
+
+    /// Returns the contents of the framebuffer, as drawn by the most recent
+    /// frame.
+    ///
+    /// The returned bitmap is always RGBA, with premultiplied alpha.
+    pub fn capture_frame(&self) -> Bitmap {
+        Bitmap {
+            width: self.target.width,
+            height: self.target.height,
+            data: BitmapFormat::Rgba(self.target.layers[0].clone()),
+        }
+    }
+
+    fn register_shape_internal(
+        &mut self,
+        shape: DistilledShape,
+        library: Option<&MovieLibrary<'_>>,
+    ) -> Mesh {
+        let textures = &self.textures;
+        let draws = self.shape_tessellator.tessellate_shape(shape, |id| {
+            library
+                .and_then(|lib| lib.get_bitmap(id))
+                .and_then(|bitmap| {
+                    let handle = bitmap.bitmap_handle();
+                    textures.get(handle.0).map(|texture| (texture, handle))
+                })
+                .map(|(texture, handle)| (texture.width, texture.height, handle))
+        });
+
+        Mesh { draws }
+    }
+}
+
+impl RenderBackend for SoftwareRenderBackend {
+    fn set_viewport_dimensions(&mut self, width: u32, height: u32) {
+        self.target.resize(width, height);
+    }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        self.shape_tessellator.set_quality(quality);
+    }
+
+    fn register_shape(
+        &mut self,
+        shape: DistilledShape,
+        library: Option<&MovieLibrary<'_>>,
+    ) -> ShapeHandle {
+        let handle = ShapeHandle(self.meshes.len());
+        let mesh = self.register_shape_internal(shape, library);
+        self.meshes.push(mesh);
+        handle
+    }
+
+    fn replace_shape(
+        &mut self,
+        shape: DistilledShape,
+        library: Option<&MovieLibrary<'_>>,
+        handle: ShapeHandle,
+    ) {
+        let mesh = self.register_shape_internal(shape, library);
+        self.meshes[handle.0] = mesh;
+    }
+
+    fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
+        let shape = ruffle_core::shape_utils::swf_glyph_to_shape(glyph);
+        let handle = ShapeHandle(self.meshes.len());
+        let mesh = self.register_shape_internal((&shape).into(), None);
+        self.meshes.push(mesh);
+        handle
+    }
+
+    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapInfo, Error> {
+        let handle = BitmapHandle(self.textures.len());
+        let texture = Texture::from_bitmap(bitmap);
+        let width = texture.width;
+        let height = texture.height;
+        self.textures.push(texture);
+
+        Ok(BitmapInfo {
+            handle,
+            width: width as u16,
+            height: height as u16,
+        })
+    }
+
+    fn begin_frame(&mut self, clear: Color) {
+        let target = &mut self.target;
+        target.layers.truncate(1);
+        target.mask_state = MaskState::NoMask;
+        target.num_masks = 0;
+
+        let clear = premultiply([
+            f32::from(clear.r) / 255.0,
+            f32::from(clear.g) / 255.0,
+            f32::from(clear.b) / 255.0,
+            f32::from(clear.a) / 255.0,
+        ]);
+        for pixel in target.layers[0].chunks_exact_mut(4) {
+            pixel.copy_from_slice(&clear);
+        }
+        for stencil in target.stencil.iter_mut() {
+            *stencil = 0;
+        }
+    }
+
+    fn end_frame(&mut self) {}
+
+    fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool) {
+        let texture = if let Some(texture) = self.textures.get(bitmap.0) {
+            texture
+        } else {
+            return;
+        };
+
+        let width = texture.width as f32;
+        let height = texture.height as f32;
+        let world = Affine::from_matrix(&transform.matrix);
+        let uv_matrix = match world.inverse() {
+            Some(inverse) => inverse.then(&Affine::scale(1.0 / width, 1.0 / height)),
+            None => return,
+        };
+
+        let mult = transform.color_transform.mult_rgba_normalized();
+        let add = transform.color_transform.add_rgba_normalized();
+        let corners = [
+            world.apply(0.0, 0.0),
+            world.apply(width, 0.0),
+            world.apply(width, height),
+            world.apply(0.0, height),
+        ];
+
+        for triangle in &[[0, 1, 2], [0, 2, 3]] {
+            self.target.fill_triangle(
+                [
+                    corners[triangle[0]],
+                    corners[triangle[1]],
+                    corners[triangle[2]],
+                ],
+                |x, y| {
+                    let (u, v) = uv_matrix.apply(x, y);
+                    let color = texture.sample(u, v, smoothing, false);
+                    transform_premultiplied(color, mult, add)
+                },
+            );
+        }
+    }
+
+    fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform) {
+        let Self {
+            meshes,
+            textures,
+            target,
+            ..
+        } = self;
+
+        let mesh = if let Some(mesh) = meshes.get(shape.0) {
+            mesh
+        } else {
+            return;
+        };
+
+        let world = Affine::from_matrix(&transform.matrix);
+        let local = match world.inverse() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+
+        let mult = transform.color_transform.mult_rgba_normalized();
+        let add = transform.color_transform.add_rgba_normalized();
+
+        for draw in &mesh.draws {
+            match &draw.draw_type {
+                DrawType::Color => {
+                    for_each_triangle(draw, &world, |points, vertex| {
+                        let color = vertex.color.clone();
+                        let color = premultiply(transform_color(
+                            [
+                                f32::from(color.r) / 255.0,
+                                f32::from(color.g) / 255.0,
+                                f32::from(color.b) / 255.0,
+                                f32::from(color.a) / 255.0,
+                            ],
+                            mult,
+                            add,
+                        ));
+                        target.fill_triangle(points, |_, _| color);
+                    });
+                }
+                DrawType::Gradient(gradient) => {
+                    let uv_matrix = local.then(&Affine::from_gl(&gradient.matrix));
+                    for_each_triangle(draw, &world, |points, _| {
+                        target.fill_triangle(points, |x, y| {
+                            let (u, v) = uv_matrix.apply(x, y);
+                            let color = sample_gradient(gradient, u, v);
+                            premultiply(transform_color(color, mult, add))
+                        });
+                    });
+                }
+                DrawType::Bitmap(bitmap) => {
+                    let texture = if let Some(texture) = textures.get(bitmap.bitmap.0) {
+                        texture
+                    } else {
+                        continue;
+                    };
+
+                    let uv_matrix = local.then(&Affine::from_gl(&bitmap.matrix));
+                    for_each_triangle(draw, &world, |points, _| {
+                        target.fill_triangle(points, |x, y| {
+                            let (u, v) = uv_matrix.apply(x, y);
+                            let color =
+                                texture.sample(u, v, bitmap.is_smoothed, bitmap.is_repeating);
+                            transform_premultiplied(color, mult, add)
+                        });
+                    });
+                }
+            }
+        }
+    }
+
+    fn draw_rect(&mut self, color: Color, matrix: &swf::Matrix) {
+        let world = Affine::from_matrix(matrix);
+        let color = premultiply([
+            f32::from(color.r) / 255.0,
+            f32::from(color.g) / 255.0,
+            f32::from(color.b) / 255.0,
+            f32::from(color.a) / 255.0,
+        ]);
+        let corners = [
+            world.apply(0.0, 0.0),
+            world.apply(1.0, 0.0),
+            world.apply(1.0, 1.0),
+            world.apply(0.0, 1.0),
+        ];
+
+        for triangle in &[[0, 1, 2], [0, 2, 3]] {
+            self.target.fill_triangle(
+                [
+                    corners[triangle[0]],
+                    corners[triangle[1]],
+                    corners[triangle[2]],
+                ],
+                |_, _| color,
+            );
+        }
+    }
+
+    fn push_blend_mode(&mut self, blend_mode: swf::BlendMode) {
+        self.target.blend_modes.push(blend_mode);
+    }
+
+    fn pop_blend_mode(&mut self) {
+        self.target.blend_modes.pop();
+    }
+
+    fn push_mask(&mut self) {
+        let target = &mut self.target;
+        debug_assert!(
+            target.mask_state == MaskState::NoMask
+                || target.mask_state == MaskState::DrawMaskedContent
+        );
+        target.num_masks += 1;
+        target.mask_state = MaskState::DrawMaskStencil;
+    }
+
+    fn activate_mask(&mut self) {
+        let target = &mut self.target;
+        debug_assert!(target.num_masks > 0 && target.mask_state == MaskState::DrawMaskStencil);
+        target.mask_state = MaskState::DrawMaskedContent;
+    }
+
+    fn deactivate_mask(&mut self) {
+        let target = &mut self.target;
+        debug_assert!(target.num_masks > 0 && target.mask_state == MaskState::DrawMaskedContent);
+        target.mask_state = MaskState::ClearMaskStencil;
+    }
+
+    fn pop_mask(&mut self) {
+        let target = &mut self.target;
+        debug_assert!(target.num_masks > 0 && target.mask_state == MaskState::ClearMaskStencil);
+        target.num_masks -= 1;
+        target.mask_state = if target.num_masks == 0 {
+            MaskState::NoMask
+        } else {
+            MaskState::DrawMaskedContent
+        };
+    }
+
+    fn push_filter_layer(&mut self) -> bool {
+        let len = self.target.layers[0].len();
+        self.target.layers.push(vec![0; len]);
+        true
+    }
+
+    fn pop_filter_layer(&mut self, filters: &[swf::Filter], bounds: &BoundingBox) {
+        if self.target.layers.len() < 2 {
+            return;
+        }
+
+        let layer = self.target.layers.pop().unwrap();
+
+        let target = &mut self.target;
+        let clamp_x = |x: swf::Twips, round: fn(f64) -> f64| {
+            round(x.to_pixels()).max(0.0).min(f64::from(target.width)) as u32
+        };
+        let clamp_y = |y: swf::Twips, round: fn(f64) -> f64| {
+            round(y.to_pixels()).max(0.0).min(f64::from(target.height)) as u32
+        };
+        let region = (
+            clamp_x(bounds.x_min, f64::floor),
+            clamp_y(bounds.y_min, f64::floor),
+            clamp_x(bounds.x_max, f64::ceil),
+            clamp_y(bounds.y_max, f64::ceil),
+        );
+
+        // Without a region to filter, the layer is drawn as it is, so that
+        // nothing drawn into it is lost.
+        let (x_min, y_min, x_max, y_max, filters) =
+            if bounds.valid && region.0 < region.2 && region.1 < region.3 {
+                (region.0, region.1, region.2, region.3, filters)
+            } else {
+                (0, 0, target.width, target.height, &[][..])
+            };
+        if x_min >= x_max || y_min >= y_max {
+            return;
+        }
+
+        // Cut the filtered region out of the layer.
+        let row_len = (x_max - x_min) as usize * 4;
+        let mut rgba = Vec::with_capacity(row_len * (y_max - y_min) as usize);
+        for y in y_min..y_max {
+            let start = (y * target.width + x_min) as usize * 4;
+            rgba.extend_from_slice(&layer[start..start + row_len]);
+        }
+
+        let mut bitmap = Bitmap {
+            width: x_max - x_min,
+            height: y_max - y_min,
+            data: BitmapFormat::Rgba(rgba),
+        };
+        apply_filters(&mut bitmap, filters);
+
+        // Composite it onto the layer beneath.
+        if let BitmapFormat::Rgba(rgba) = bitmap.data {
+            for (i, color) in rgba.chunks_exact(4).enumerate() {
+                if color[3] == 0 {
+                    continue;
+                }
+
+                let x = x_min + i as u32 % bitmap.width;
+                let y = y_min + i as u32 / bitmap.width;
+                target.draw_pixel(x, y, || [color[0], color[1], color[2], color[3]]);
+            }
+        }
+    }
+
+    fn supports_offscreen_targets(&self) -> bool {
+        true
+    }
+
+    fn push_offscreen_target(&mut self, bitmap: BitmapHandle) -> bool {
+        let texture = if let Some(texture) = self.textures.get(bitmap.0) {
+            texture
+        } else {
+            return false;
+        };
+
+        let target = RenderTarget::new(texture.width, texture.height);
+        let previous = std::mem::replace(&mut self.target, target);
+        self.offscreen_targets.push((bitmap, previous));
+        true
+    }
+
+    fn pop_offscreen_target(&mut self) {
+        let (bitmap, previous) = if let Some(offscreen) = self.offscreen_targets.pop() {
+            offscreen
+        } else {
+            return;
+        };
+
+        let mut target = std::mem::replace(&mut self.target, previous);
+        if let Some(texture) = self.textures.get_mut(bitmap.0) {
+            texture.rgba = target.layers.swap_remove(0);
+        }
+    }
+
+    fn begin_capture(&mut self) -> bool {
+        // Everything is drawn offscreen already.
+        true
+    }
+
+    fn end_capture(&mut self) -> Option<Bitmap> {
+        Some(self.capture_frame())
+    }
+
+    fn get_bitmap_pixels(&mut self, bitmap: BitmapHandle) -> Option<Bitmap> {
+        self.textures.get(bitmap.0).map(|texture| Bitmap {
+            width: texture.width,
+            height: texture.height,
+            data: BitmapFormat::Rgba(texture.rgba.clone()),
+        })
+    }
+
+    fn register_bitmap_raw(
+        &mut self,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    ) -> Result<BitmapHandle, Error> {
+        Ok(self
+            .register_bitmap(Bitmap {
+                data: BitmapFormat::Rgba(rgba),
+                width,
+                height,
+            })?
+            .handle)
+    }
+
+    fn update_texture(
+        &mut self,
+        handle: BitmapHandle,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    ) -> Result<BitmapHandle, Error> {
+        let texture = if let Some(texture) = self.textures.get_mut(handle.0) {
+            texture
+        } else {
+            return Err("update_texture: Bitmap is not registered".into());
+        };
+
+        *texture = Texture::from_bitmap(Bitmap {
+            data: BitmapFormat::Rgba(rgba),
+            width,
+            height,
+        });
+
+        Ok(handle)
+    }
+}
+
+/// The surface that everything is drawn to.
+struct RenderTarget {
+    width: u32,
+    height: u32,
+
+    /// The framebuffer of the stage, followed by any open filter layers.
+    ///
+    /// Each one holds premultiplied RGBA pixels. Drawing always happens on the
+    /// last layer.
+    layers: Vec<Vec<u8>>,
+
+    /// One stencil value per pixel, used for masking.
+    stencil: Vec<u8>,
+
+    mask_state: MaskState,
+    num_masks: u32,
+    blend_modes: Vec<swf::BlendMode>,
+}
+
+impl RenderTarget {
+    /// Creates a transparent target of the given size.
+    fn new(width: u32, height: u32) -> Self {
+        let mut target = Self {
+            width: 0,
+            height: 0,
+            layers: vec![vec![]],
+            stencil: vec![],
+            mask_state: MaskState::NoMask,
+            num_masks: 0,
+            blend_modes: vec![],
+        };
+        target.resize(width, height);
+        target
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        let len = width as usize * height as usize;
+        self.width = width;
+        self.height = height;
+        self.layers = vec![vec![0; len * 4]];
+        self.stencil = vec![0; len];
+    }
+
+    fn blend_mode(&self) -> swf::BlendMode {
+        self.blend_modes
+            .last()
+            .copied()
+            .unwrap_or(swf::BlendMode::Normal)
+    }
+
+    /// Fills a triangle, given in viewport pixel coordinates.
+    ///
+    /// `shade` is called with the center of each covered pixel, and returns
+    /// its premultiplied color.
+    fn fill_triangle(
+        &mut self,
+        points: [(f32, f32); 3],
+        mut shade: impl FnMut(f32, f32) -> [u8; 4],
+    ) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let mut fixed = [(0i64, 0i64); 3];
+        for (fixed, (x, y)) in fixed.iter_mut().zip(points.iter()) {
+            if !x.is_finite() || !y.is_finite() {
+                return;
+            }
+
+            *fixed = (to_fixed(*x), to_fixed(*y));
+        }
+
+        let [mut p0, mut p1, p2] = fixed;
+        let area = edge_function(p0, p1, p2);
+        if area == 0 {
+            return;
+        } else if area < 0 {
+            std::mem::swap(&mut p0, &mut p1);
+        }
+
+        let min_x = (p0.0.min(p1.0).min(p2.0) >> SUBPIXEL_BITS).max(0);
+        let max_x = (p0.0.max(p1.0).max(p2.0) >> SUBPIXEL_BITS).min(i64::from(self.width) - 1);
+        let min_y = (p0.1.min(p1.1).min(p2.1) >> SUBPIXEL_BITS).max(0);
+        let max_y = (p0.1.max(p1.1).max(p2.1) >> SUBPIXEL_BITS).min(i64::from(self.height) - 1);
+
+        let half_pixel = 1 << (SUBPIXEL_BITS - 1);
+        for y in min_y..=max_y {
+            let sample_y = (y << SUBPIXEL_BITS) + half_pixel;
+            for x in min_x..=max_x {
+                let sample = ((x << SUBPIXEL_BITS) + half_pixel, sample_y);
+                if covers(p0, p1, sample) && covers(p1, p2, sample) && covers(p2, p0, sample) {
+                    self.draw_pixel(x as u32, y as u32, || shade(x as f32 + 0.5, y as f32 + 0.5));
+                }
+            }
+        }
+    }
+
+    /// Draws a single pixel, subject to the current mask and blend mode.
+    ///
+    /// The color is only computed if the pixel is actually drawn.
+    fn draw_pixel(&mut self, x: u32, y: u32, color: impl FnOnce() -> [u8; 4]) {
+        let index = (y * self.width + x) as usize;
+        let stencil = &mut self.stencil[index];
+        match self.mask_state {
+            MaskState::NoMask => (),
+            MaskState::DrawMaskStencil => {
+                if u32::from(*stencil) + 1 == self.num_masks {
+                    *stencil = stencil.wrapping_add(1);
+                }
+                return;
+            }
+            MaskState::DrawMaskedContent => {
+                if u32::from(*stencil) != self.num_masks {
+                    return;
+                }
+            }
+            MaskState::ClearMaskStencil => {
+                if u32::from(*stencil) == self.num_masks {
+                    *stencil = stencil.wrapping_sub(1);
+                }
+                return;
+            }
+        }
+
+        let blend_mode = self.blend_mode();
+        let layer = self.layers.last_mut().unwrap();
+        blend_pixel(blend_mode, color(), &mut layer[index * 4..index * 4 + 4]);
+    }
+}
+
+struct Texture {
+    width: u32,
+    height: u32,
+
+    /// Premultiplied RGBA pixels.
+    rgba: Vec<u8>,
+}
+
+impl Texture {
+    fn from_bitmap(bitmap: Bitmap) -> Self {
+        let rgba = match bitmap.data {
+            BitmapFormat::Rgba(rgba) => rgba,
+            BitmapFormat::Rgb(rgb) => {
+                let mut rgba = Vec::with_capacity(rgb.len() / 3 * 4);
+                for rgb in rgb.chunks_exact(3) {
+                    rgba.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+                }
+                rgba
+            }
+        };
+
+        Self {
+            width: bitmap.width,
+            height: bitmap.height,
+            rgba,
+        }
+    }
+
+    /// Looks up the color of the texture at the given texture coordinates,
+    /// where `(0, 0)` is the top-left corner and `(1, 1)` the bottom-right.
+    ///
+    /// Coordinates outside of the texture are clamped to its edge, or wrapped
+    /// around if `repeating` is set.
+    fn sample(&self, u: f32, v: f32, smoothed: bool, repeating: bool) -> [u8; 4] {
+        if self.width == 0 || self.height == 0 || self.rgba.len() < self.texel_count() * 4 {
+            return [0; 4];
+        }
+
+        let x = u * self.width as f32;
+        let y = v * self.height as f32;
+        if !x.is_finite() || !y.is_finite() {
+            return [0; 4];
+        }
+
+        if !smoothed {
+            return self.texel(x.floor() as i64, y.floor() as i64, repeating);
+        }
+
+        // Bilinear filtering, weighting the four nearest texel centers.
+        let x = x - 0.5;
+        let y = y - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let texels = [
+            (self.texel(x0, y0, repeating), (1.0 - fx) * (1.0 - fy)),
+            (self.texel(x0 + 1, y0, repeating), fx * (1.0 - fy)),
+            (self.texel(x0, y0 + 1, repeating), (1.0 - fx) * fy),
+            (self.texel(x0 + 1, y0 + 1, repeating), fx * fy),
+        ];
+
+        let mut color = [0; 4];
+        for (i, channel) in color.iter_mut().enumerate() {
+            let value: f32 = texels
+                .iter()
+                .map(|(texel, weight)| f32::from(texel[i]) * weight)
+                .sum();
+            *channel = value.round().max(0.0).min(255.0) as u8;
+        }
+        color
+    }
+
+    fn texel(&self, x: i64, y: i64, repeating: bool) -> [u8; 4] {
+        let (width, height) = (i64::from(self.width), i64::from(self.height));
+        let (x, y) = if repeating {
+            (x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            (x.max(0).min(width - 1), y.max(0).min(height - 1))
+        };
+
+        let index = (y * width + x) as usize * 4;
+        let texel = &self.rgba[index..index + 4];
+        [texel[0], texel[1], texel[2], texel[3]]
+    }
+
+    fn texel_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+}
+
+struct Mesh {
+    draws: Vec<Draw>,
+}
+
+/// A 2D affine transform, mapping `(x, y)` to
+/// `(a * x + c * y + tx, b * x + d * y + ty)`.
+///
+/// Unlike `swf::Matrix`, the translation is kept in floating point pixels.
+#[derive(Debug, Clone, Copy)]
+struct Affine {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    tx: f32,
+    ty: f32,
+}
+
+impl Affine {
+    fn from_matrix(matrix: &swf::Matrix) -> Self {
+        Self {
+            a: matrix.a,
+            b: matrix.b,
+            c: matrix.c,
+            d: matrix.d,
+            tx: matrix.tx.to_pixels() as f32,
+            ty: matrix.ty.to_pixels() as f32,
+        }
+    }
+
+    /// Converts a column-major matrix, as generated by the tessellator for
+    /// gradient and bitmap fills.
+    fn from_gl(matrix: &[[f32; 3]; 3]) -> Self {
+        Self {
+            a: matrix[0][0],
+            b: matrix[0][1],
+            c: matrix[1][0],
+            d: matrix[1][1],
+            tx: matrix[2][0],
+            ty: matrix[2][1],
+        }
+    }
+
+    fn scale(x: f32, y: f32) -> Self {
+        Self {
+            a: x,
+            b: 0.0,
+            c: 0.0,
+            d: y,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        Some(Self {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            tx: (self.c * self.ty - self.d * self.tx) / det,
+            ty: (self.b * self.tx - self.a * self.ty) / det,
+        })
+    }
+
+    /// Returns the transform that applies `self`, followed by `next`.
+    fn then(&self, next: &Self) -> Self {
+        Self {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            tx: next.a * self.tx + next.c * self.ty + next.tx,
+            ty: next.b * self.tx + next.d * self.ty + next.ty,
+        }
+    }
+
+    fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.tx,
+            self.b * x + self.d * y + self.ty,
+        )
+    }
+}
+
+/// Calls `f` with the viewport coordinates and first vertex of each triangle
+/// in a tessellated draw.
+fn for_each_triangle(draw: &Draw, world: &Affine, mut f: impl FnMut([(f32, f32); 3], &Vertex)) {
+    for triangle in draw.indices.chunks_exact(3) {
+        let vertices = [
+            draw.vertices.get(triangle[0] as usize),
+            draw.vertices.get(triangle[1] as usize),
+            draw.vertices.get(triangle[2] as usize),
+        ];
+
+        if let [Some(v0), Some(v1), Some(v2)] = vertices {
+            f(
+                [
+                    world.apply(v0.x, v0.y),
+                    world.apply(v1.x, v1.y),
+                    world.apply(v2.x, v2.y),
+                ],
+                v0,
+            );
+        }
+    }
+}
+
+fn to_fixed(value: f32) -> i64 {
+    let value = value.max(-MAX_COORDINATE).min(MAX_COORDINATE);
+    (value * (1 << SUBPIXEL_BITS) as f32).round() as i64
+}
+
+/// Returns twice the signed area of the triangle `a`, `b`, `p`.
+///
+/// This is positive when `p` is on the inner side of the edge from `a` to `b`
+/// of a triangle with positive area.
+fn edge_function(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+/// Checks whether a sample point is inside of a triangle edge.
+///
+/// Samples that lie exactly on an edge are only considered to be inside of it
+/// if it is a top or left edge, so that pixels on an edge shared by two
+/// triangles are drawn exactly once.
+fn covers(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> bool {
+    let distance = edge_function(a, b, p);
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    distance > 0 || (distance == 0 && (dy < 0 || (dy == 0 && dx > 0)))
+}
+
+/// Applies a color transform to a normalized, non-premultiplied color.
+fn transform_color(color: [f32; 4], mult: [f32; 4], add: [f32; 4]) -> [f32; 4] {
+    let mut out = [0.0; 4];
+    for (out, ((color, mult), add)) in out
+        .iter_mut()
+        .zip(color.iter().zip(mult.iter()).zip(add.iter()))
+    {
+        *out = (color * mult + add).max(0.0).min(1.0);
+    }
+    out
+}
+
+/// Applies a color transform to a premultiplied color.
+///
+/// The color is unmultiplied first, as the transform applies to the straight
+/// color channels.
+fn transform_premultiplied(color: [u8; 4], mult: [f32; 4], add: [f32; 4]) -> [u8; 4] {
+    if color[3] == 0 {
+        return color;
+    }
+
+    let alpha = f32::from(color[3]) / 255.0;
+    let straight = [
+        f32::from(color[0]) / 255.0 / alpha,
+        f32::from(color[1]) / 255.0 / alpha,
+        f32::from(color[2]) / 255.0 / alpha,
+        alpha,
+    ];
+    premultiply(transform_color(straight, mult, add))
+}
+
+/// Converts a normalized, non-premultiplied color to premultiplied bytes.
+fn premultiply(color: [f32; 4]) -> [u8; 4] {
+    let alpha = color[3].max(0.0).min(1.0);
+    let channel = |value: f32| (value.max(0.0).min(1.0) * alpha * 255.0).round() as u8;
+    [
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2]),
+        (alpha * 255.0).round() as u8,
+    ]
+}
+
+/// Computes the color of a gradient at the given gradient coordinates.
+///
+/// This follows the gradient shader of the WebGL backend.
+fn sample_gradient(gradient: &Gradient, u: f32, v: f32) -> [f32; 4] {
+    let num_colors = gradient
+        .num_colors
+        .min(gradient.colors.len())
+        .min(gradient.ratios.len());
+    if num_colors == 0 {
+        return [0.0; 4];
+    }
+
+    let t = match gradient.gradient_type {
+        GradientType::Linear => u,
+        GradientType::Radial => {
+            let (x, y) = (u * 2.0 - 1.0, v * 2.0 - 1.0);
+            (x * x + y * y).sqrt()
+        }
+        GradientType::Focal => {
+            let focal_point = gradient.focal_point;
+            let (x, y) = (focal_point - (u * 2.0 - 1.0), -(v * 2.0 - 1.0));
+            let length = (x * x + y * y).sqrt();
+            let (x, y) = (x / length, y / length);
+            length / ((1.0 - focal_point * focal_point * y * y).sqrt() + focal_point * x)
+        }
+    };
+
+    let t = match gradient.repeat_mode {
+        swf::GradientSpread::Pad => t.max(0.0).min(1.0),
+        swf::GradientSpread::Repeat => t - t.floor(),
+        swf::GradientSpread::Reflect => {
+            let t = t.abs();
+            if (t % 2.0) < 1.0 {
+                t.fract()
+            } else {
+                1.0 - t.fract()
+            }
+        }
+    };
+
+    let ratios = &gradient.ratios[..num_colors];
+    let colors = &gradient.colors[..num_colors];
+    let mut color = colors[num_colors - 1];
+    if t.is_nan() || t <= ratios[0] {
+        color = colors[0];
+    } else {
+        for i in 1..num_colors {
+            if t <= ratios[i] {
+                let a = (t - ratios[i - 1]) / (ratios[i] - ratios[i - 1]);
+                for (channel, (start, end)) in color
+                    .iter_mut()
+                    .zip(colors[i - 1].iter().zip(colors[i].iter()))
+                {
+                    *channel = start + (end - start) * a;
+                }
+                break;
+            }
+        }
+    }
+
+    if gradient.interpolation == swf::GradientInterpolation::LinearRgb {
+        for channel in &mut color[..3] {
+            *channel = linear_to_srgb(*channel);
+        }
+    }
+
+    color
+}
+
+fn linear_to_srgb(linear: f32) -> f32 {
+    if linear < 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Blends a premultiplied color onto a framebuffer pixel.
+///
+/// The blend modes match the ones that the WebGL backend implements with
+/// fixed-function blending. The rest draw as `BlendMode::Normal`.
+fn blend_pixel(blend_mode: swf::BlendMode, src: [u8; 4], dst: &mut [u8]) {
+    fn mul(a: u32, b: u32) -> u32 {
+        (a * b + 127) / 255
+    }
+
+    let src_alpha = u32::from(src[3]);
+    for (src, dst) in src.iter().zip(dst.iter_mut()) {
+        let (s, d) = (u32::from(*src), u32::from(*dst));
+        let value = match blend_mode {
+            swf::BlendMode::Add => s + d,
+            swf::BlendMode::Subtract => d.saturating_sub(s),
+            swf::BlendMode::Multiply => mul(s, d) + mul(d, 255 - src_alpha),
+            swf::BlendMode::Screen => s + mul(d, 255 - s),
+            _ => s + mul(d, 255 - src_alpha),
+        };
+        *dst = value.min(255) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::Twips;
+
+    fn pixel(renderer: &SoftwareRenderBackend, x: u32, y: u32) -> [u8; 4] {
+        let index = (y * renderer.target.width + x) as usize * 4;
+        let pixel = &renderer.target.layers[0][index..index + 4];
+        [pixel[0], pixel[1], pixel[2], pixel[3]]
+    }
+
+    fn rect(x: f64, y: f64, width: f32, height: f32) -> swf::Matrix {
+        swf::Matrix {
+            a: width,
+            d: height,
+            tx: Twips::from_pixels(x),
+            ty: Twips::from_pixels(y),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn draw_rect_covers_pixel_centers() {
+        let mut renderer = SoftwareRenderBackend::new(8, 8);
+        renderer.begin_frame(Color::from_rgb(0x000000, 255));
+        renderer.draw_rect(Color::from_rgb(0xff0000, 255), &rect(2.0, 2.0, 4.0, 3.0));
+        renderer.end_frame();
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let expected = if (2..6).contains(&x) && (2..5).contains(&y) {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 0, 255]
+                };
+                assert_eq!(pixel(&renderer, x, y), expected, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_once() {
+        // Both triangles of the rectangle share a diagonal; a translucent
+        // fill would be darker along it if it were blended twice.
+        let mut renderer = SoftwareRenderBackend::new(16, 16);
+        renderer.begin_frame(Color::from_rgb(0x000000, 255));
+        renderer.draw_rect(Color::from_rgb(0xffffff, 128), &rect(0.0, 0.0, 16.0, 16.0));
+        renderer.end_frame();
+
+        let expected = pixel(&renderer, 0, 15);
+        for y in 0..16 {
+            for x in 0..16 {
+                assert_eq!(pixel(&renderer, x, y), expected, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn masks_clip_content() {
+        let mut renderer = SoftwareRenderBackend::new(8, 8);
+        renderer.begin_frame(Color::from_rgb(0x000000, 255));
+        renderer.push_mask();
+        renderer.draw_rect(Color::from_rgb(0xffffff, 255), &rect(0.0, 0.0, 4.0, 8.0));
+        renderer.activate_mask();
+        renderer.draw_rect(Color::from_rgb(0x00ff00, 255), &rect(0.0, 0.0, 8.0, 8.0));
+        renderer.deactivate_mask();
+        renderer.draw_rect(Color::from_rgb(0xffffff, 255), &rect(0.0, 0.0, 4.0, 8.0));
+        renderer.pop_mask();
+        renderer.end_frame();
+
+        assert_eq!(pixel(&renderer, 1, 1), [0, 255, 0, 255]);
+        assert_eq!(pixel(&renderer, 6, 1), [0, 0, 0, 255]);
+        assert!(renderer.target.stencil.iter().all(|stencil| *stencil == 0));
+    }
+
+    #[test]
+    fn offscreen_targets_draw_into_bitmaps() {
+        let mut renderer = SoftwareRenderBackend::new(8, 8);
+        let bitmap = renderer.register_bitmap_raw(4, 4, vec![0; 64]).unwrap();
+        renderer.begin_frame(Color::from_rgb(0x000000, 255));
+        assert!(renderer.push_offscreen_target(bitmap));
+        renderer.draw_rect(Color::from_rgb(0xff0000, 255), &rect(0.0, 0.0, 2.0, 4.0));
+        renderer.pop_offscreen_target();
+        renderer.end_frame();
+
+        let texture = &renderer.textures[bitmap.0];
+        assert_eq!(&texture.rgba[0..4], [255, 0, 0, 255]);
+        assert_eq!(&texture.rgba[8..12], [0, 0, 0, 0]);
+        assert_eq!(pixel(&renderer, 0, 0), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn filter_layers_without_bounds_are_drawn_unfiltered() {
+        let mut renderer = SoftwareRenderBackend::new(8, 8);
+        renderer.begin_frame(Color::from_rgb(0x000000, 255));
+        assert!(renderer.push_filter_layer());
+        renderer.draw_rect(Color::from_rgb(0xff0000, 255), &rect(0.0, 0.0, 2.0, 2.0));
+        renderer.pop_filter_layer(&[], &BoundingBox::default());
+        renderer.end_frame();
+
+        assert_eq!(pixel(&renderer, 1, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&renderer, 3, 3), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn captured_frames_are_read_back() {
+        let mut renderer = SoftwareRenderBackend::new(4, 2);
+        assert!(renderer.begin_capture());
+        renderer.begin_frame(Color::from_rgb(0x000000, 255));
+        renderer.draw_rect(Color::from_rgb(0x0000ff, 255), &rect(0.0, 0.0, 2.0, 2.0));
+        renderer.end_frame();
+
+        let frame = renderer.end_capture().unwrap();
+        assert_eq!((frame.width, frame.height), (4, 2));
+        match frame.data {
+            BitmapFormat::Rgba(rgba) => {
+                assert_eq!(&rgba[0..4], [0, 0, 255, 255]);
+                assert_eq!(&rgba[12..16], [0, 0, 0, 255]);
+            }
+            BitmapFormat::Rgb(_) => panic!("Frames are captured as RGBA"),
+        }
+    }
+
+    #[test]
+    fn blend_modes() {
+        let mut dst = [100, 100, 100, 255];
+        blend_pixel(swf::BlendMode::Add, [200, 50, 0, 255], &mut dst);
+        assert_eq!(dst, [255, 150, 100, 255]);
+
+        let mut dst = [100, 100, 100, 255];
+        blend_pixel(swf::BlendMode::Subtract, [200, 50, 0, 255], &mut dst);
+        assert_eq!(dst, [0, 50, 100, 0]);
+
+        let mut dst = [100, 100, 100, 255];
+        blend_pixel(swf::BlendMode::Normal, [0, 0, 0, 0], &mut dst);
+        assert_eq!(dst, [100, 100, 100, 255]);
+    }
+}
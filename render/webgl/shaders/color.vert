@@ -16,6 +16,6 @@ attribute vec4 color;
 varying vec4 frag_color;
 
 void main() {
-    frag_color = color * mult_color + add_color;
+    frag_color = clamp(color * mult_color + add_color, 0.0, 1.0);
     gl_Position = view_matrix * world_matrix * vec4(position, 0.0, 1.0);
 }
@@ -129,6 +129,6 @@ void main() {
         color = vec4(linear_to_srgb(vec3(color)), color.a);
     }
 
-    gl_FragColor = mult_color * color + add_color;
+    gl_FragColor = clamp(mult_color * color + add_color, 0.0, 1.0);
 }
 
@@ -3,6 +3,7 @@ use ruffle_core::backend::render::{
     Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, MovieLibrary, RenderBackend,
     ShapeHandle, Transform,
 };
+use ruffle_core::config::StageQuality;
 use ruffle_core::shape_utils::DistilledShape;
 use ruffle_core::swf;
 use ruffle_render_common_tess::{
@@ -67,6 +68,10 @@ pub struct WebGlRenderBackend {
     msaa_buffers: Option<MsaaBuffers>,
     msaa_sample_count: u32,
 
+    /// The MSAA sample count used at high quality, which is the most that
+    /// this device is given.
+    max_msaa_sample_count: u32,
+
     color_program: ShaderProgram,
     bitmap_program: ShaderProgram,
     gradient_program: ShaderProgram,
@@ -84,7 +89,8 @@ pub struct WebGlRenderBackend {
     mask_state_dirty: bool,
 
     active_program: *const ShaderProgram,
-    blend_func: (u32, u32),
+    blend_modes: Vec<swf::BlendMode>,
+    blend_func: (u32, u32, u32),
     mult_color: Option<[f32; 4]>,
     add_color: Option<[f32; 4]>,
 
@@ -95,6 +101,10 @@ pub struct WebGlRenderBackend {
     view_matrix: [[f32; 4]; 4],
 
     bitmap_registry: HashMap<BitmapHandle, Bitmap>,
+
+    /// Whether the current frame is being captured, in which case it is left
+    /// in the MSAA color framebuffer instead of being drawn to the screen.
+    capturing: bool,
 }
 
 const MAX_GRADIENT_COLORS: usize = 15;
@@ -215,6 +225,7 @@ impl WebGlRenderBackend {
 
             msaa_buffers: None,
             msaa_sample_count,
+            max_msaa_sample_count: msaa_sample_count,
 
             color_program,
             gradient_program,
@@ -237,10 +248,12 @@ impl WebGlRenderBackend {
             mask_state_dirty: true,
 
             active_program: std::ptr::null(),
-            blend_func: (Gl::SRC_ALPHA, Gl::ONE_MINUS_SRC_ALPHA),
+            blend_modes: Vec::new(),
+            blend_func: (Gl::SRC_ALPHA, Gl::ONE_MINUS_SRC_ALPHA, Gl::FUNC_ADD),
             mult_color: None,
             add_color: None,
             bitmap_registry: HashMap::new(),
+            capturing: false,
         };
 
         let color_quad_mesh = renderer.build_quad_mesh(&renderer.color_program)?;
@@ -355,16 +368,8 @@ impl WebGlRenderBackend {
     }
 
     fn build_msaa_buffers(&mut self, width: i32, height: i32) -> Result<(), Error> {
-        if self.gl2.is_none() || self.msaa_sample_count <= 1 {
-            self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
-            self.gl.bind_renderbuffer(Gl::RENDERBUFFER, None);
-            return Ok(());
-        }
-
-        let gl = self.gl2.as_ref().unwrap();
-
         // Delete previous buffers, if they exist.
-        if let Some(msaa_buffers) = self.msaa_buffers.take() {
+        if let (Some(gl), Some(msaa_buffers)) = (&self.gl2, self.msaa_buffers.take()) {
             gl.delete_renderbuffer(Some(&msaa_buffers.color_renderbuffer));
             gl.delete_renderbuffer(Some(&msaa_buffers.stencil_renderbuffer));
             gl.delete_framebuffer(Some(&msaa_buffers.render_framebuffer));
@@ -372,6 +377,14 @@ impl WebGlRenderBackend {
             gl.delete_texture(Some(&msaa_buffers.framebuffer_texture));
         }
 
+        if self.gl2.is_none() || self.msaa_sample_count <= 1 {
+            self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+            self.gl.bind_renderbuffer(Gl::RENDERBUFFER, None);
+            return Ok(());
+        }
+
+        let gl = self.gl2.as_ref().unwrap();
+
         // Create frame and render buffers.
         let render_framebuffer = gl
             .create_framebuffer()
@@ -626,6 +639,13 @@ impl WebGlRenderBackend {
         };
     }
 
+    fn blend_mode(&self) -> swf::BlendMode {
+        self.blend_modes
+            .last()
+            .copied()
+            .unwrap_or(swf::BlendMode::Normal)
+    }
+
     fn set_stencil_state(&mut self) {
         // Set stencil state for masking, if necessary.
         if self.mask_state_dirty {
@@ -656,6 +676,69 @@ impl WebGlRenderBackend {
             }
         }
     }
+}
+
+impl RenderBackend for WebGlRenderBackend {
+    fn set_viewport_dimensions(&mut self, width: u32, height: u32) {
+        self.view_width = width as i32;
+        self.view_height = height as i32;
+
+        // Build view matrix based on canvas size.
+        self.build_matrices();
+
+        // Setup GL viewport and renderbuffers clamped to reasonable sizes.
+        self.renderbuffer_width = self.view_width.max(1).min(self.gl.drawing_buffer_width());
+        self.renderbuffer_height = self.view_height.max(1).min(self.gl.drawing_buffer_height());
+
+        // Recreate framebuffers with the new size.
+        let _ = self.build_msaa_buffers(self.renderbuffer_width, self.renderbuffer_height);
+        self.gl
+            .viewport(0, 0, self.renderbuffer_width, self.renderbuffer_height);
+    }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        let msaa_sample_count = match quality {
+            StageQuality::Low => 1,
+            StageQuality::Medium => 2,
+            StageQuality::High | StageQuality::Best => self.max_msaa_sample_count,
+        }
+        .min(self.max_msaa_sample_count);
+        self.shape_tessellator.set_quality(quality);
+
+        if msaa_sample_count != self.msaa_sample_count {
+            self.msaa_sample_count = msaa_sample_count;
+            let _ = self.build_msaa_buffers(self.renderbuffer_width, self.renderbuffer_height);
+        }
+    }
+
+    fn register_shape(
+        &mut self,
+        shape: DistilledShape,
+        library: Option<&MovieLibrary<'_>>,
+    ) -> ShapeHandle {
+        let handle = ShapeHandle(self.meshes.len());
+        let mesh = self.register_shape_internal(shape, library);
+        self.meshes.push(mesh);
+        handle
+    }
+
+    fn replace_shape(
+        &mut self,
+        shape: DistilledShape,
+        library: Option<&MovieLibrary<'_>>,
+        handle: ShapeHandle,
+    ) {
+        let mesh = self.register_shape_internal(shape, library);
+        self.meshes[handle.0] = mesh;
+    }
+
+    fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
+        let shape = ruffle_core::shape_utils::swf_glyph_to_shape(glyph);
+        let handle = ShapeHandle(self.meshes.len());
+        let mesh = self.register_shape_internal((&shape).into(), None);
+        self.meshes.push(mesh);
+        handle
+    }
 
     fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapInfo, Error> {
         let texture = self.gl.create_texture().unwrap();
@@ -718,86 +801,6 @@ impl WebGlRenderBackend {
             height: height as u16,
         })
     }
-}
-
-impl RenderBackend for WebGlRenderBackend {
-    fn set_viewport_dimensions(&mut self, width: u32, height: u32) {
-        self.view_width = width as i32;
-        self.view_height = height as i32;
-
-        // Build view matrix based on canvas size.
-        self.build_matrices();
-
-        // Setup GL viewport and renderbuffers clamped to reasonable sizes.
-        self.renderbuffer_width = self.view_width.max(1).min(self.gl.drawing_buffer_width());
-        self.renderbuffer_height = self.view_height.max(1).min(self.gl.drawing_buffer_height());
-
-        // Recreate framebuffers with the new size.
-        let _ = self.build_msaa_buffers(self.renderbuffer_width, self.renderbuffer_height);
-        self.gl
-            .viewport(0, 0, self.renderbuffer_width, self.renderbuffer_height);
-    }
-
-    fn register_shape(
-        &mut self,
-        shape: DistilledShape,
-        library: Option<&MovieLibrary<'_>>,
-    ) -> ShapeHandle {
-        let handle = ShapeHandle(self.meshes.len());
-        let mesh = self.register_shape_internal(shape, library);
-        self.meshes.push(mesh);
-        handle
-    }
-
-    fn replace_shape(
-        &mut self,
-        shape: DistilledShape,
-        library: Option<&MovieLibrary<'_>>,
-        handle: ShapeHandle,
-    ) {
-        let mesh = self.register_shape_internal(shape, library);
-        self.meshes[handle.0] = mesh;
-    }
-
-    fn register_glyph_shape(&mut self, glyph: &swf::Glyph) -> ShapeHandle {
-        let shape = ruffle_core::shape_utils::swf_glyph_to_shape(glyph);
-        let handle = ShapeHandle(self.meshes.len());
-        let mesh = self.register_shape_internal((&shape).into(), None);
-        self.meshes.push(mesh);
-        handle
-    }
-
-    fn register_bitmap_jpeg(
-        &mut self,
-        data: &[u8],
-        jpeg_tables: Option<&[u8]>,
-    ) -> Result<BitmapInfo, Error> {
-        let data = ruffle_core::backend::render::glue_tables_to_jpeg(data, jpeg_tables);
-        self.register_bitmap_jpeg_2(&data[..])
-    }
-
-    fn register_bitmap_jpeg_2(&mut self, data: &[u8]) -> Result<BitmapInfo, Error> {
-        let bitmap = ruffle_core::backend::render::decode_define_bits_jpeg(data, None)?;
-        self.register_bitmap(bitmap)
-    }
-
-    fn register_bitmap_jpeg_3(
-        &mut self,
-        jpeg_data: &[u8],
-        alpha_data: &[u8],
-    ) -> Result<BitmapInfo, Error> {
-        let bitmap =
-            ruffle_core::backend::render::decode_define_bits_jpeg(jpeg_data, Some(alpha_data))?;
-        self.register_bitmap(bitmap)
-    }
-
-    fn register_bitmap_png(
-        &mut self,
-        swf_tag: &swf::DefineBitsLossless,
-    ) -> Result<BitmapInfo, Error> {
-        let bitmap = ruffle_core::backend::render::decode_define_bits_lossless(swf_tag)?;
-        self.register_bitmap(bitmap)
-    }
 
     fn begin_frame(&mut self, clear: Color) {
         self.active_program = std::ptr::null();
@@ -856,6 +859,9 @@ impl RenderBackend for WebGlRenderBackend {
 
             // Render the resolved framebuffer texture to a quad on the screen.
             gl.bind_framebuffer(Gl2::FRAMEBUFFER, None);
+            if self.capturing {
+                return;
+            }
 
             self.gl.viewport(
                 0,
@@ -970,11 +976,13 @@ impl RenderBackend for WebGlRenderBackend {
 
                 self.mult_color = None;
                 self.add_color = None;
+            }
 
-                if (src_blend, dst_blend) != self.blend_func {
-                    self.gl.blend_func(src_blend, dst_blend);
-                    self.blend_func = (src_blend, dst_blend);
-                }
+            let blend_func = blend_func_for_mode(self.blend_mode(), src_blend, dst_blend);
+            if blend_func != self.blend_func {
+                self.gl.blend_equation(blend_func.2);
+                self.gl.blend_func(blend_func.0, blend_func.1);
+                self.blend_func = blend_func;
             }
 
             program.uniform_matrix4fv(&self.gl, ShaderUniform::WorldMatrix, &world_matrix);
@@ -1061,11 +1069,13 @@ impl RenderBackend for WebGlRenderBackend {
 
                 self.mult_color = None;
                 self.add_color = None;
+            }
 
-                if (src_blend, dst_blend) != self.blend_func {
-                    self.gl.blend_func(src_blend, dst_blend);
-                    self.blend_func = (src_blend, dst_blend);
-                }
+            let blend_func = blend_func_for_mode(self.blend_mode(), src_blend, dst_blend);
+            if blend_func != self.blend_func {
+                self.gl.blend_equation(blend_func.2);
+                self.gl.blend_func(blend_func.0, blend_func.1);
+                self.blend_func = blend_func;
             }
 
             program.uniform_matrix4fv(&self.gl, ShaderUniform::WorldMatrix, &world_matrix);
@@ -1205,13 +1215,15 @@ impl RenderBackend for WebGlRenderBackend {
 
             self.mult_color = None;
             self.add_color = None;
-
-            if (src_blend, dst_blend) != self.blend_func {
-                self.gl.blend_func(src_blend, dst_blend);
-                self.blend_func = (src_blend, dst_blend);
-            }
         };
 
+        let blend_func = blend_func_for_mode(self.blend_mode(), src_blend, dst_blend);
+        if blend_func != self.blend_func {
+            self.gl.blend_equation(blend_func.2);
+            self.gl.blend_func(blend_func.0, blend_func.1);
+            self.blend_func = blend_func;
+        }
+
         self.color_program
             .uniform_matrix4fv(&self.gl, ShaderUniform::WorldMatrix, &world_matrix);
         if Some(mult_color) != self.mult_color {
@@ -1236,6 +1248,14 @@ impl RenderBackend for WebGlRenderBackend {
         );
     }
 
+    fn push_blend_mode(&mut self, blend_mode: swf::BlendMode) {
+        self.blend_modes.push(blend_mode);
+    }
+
+    fn pop_blend_mode(&mut self) {
+        self.blend_modes.pop();
+    }
+
     fn push_mask(&mut self) {
         debug_assert!(
             self.mask_state == MaskState::NoMask || self.mask_state == MaskState::DrawMaskedContent
@@ -1268,6 +1288,52 @@ impl RenderBackend for WebGlRenderBackend {
         self.mask_state_dirty = true;
     }
 
+    fn begin_capture(&mut self) -> bool {
+        // Frames can only be drawn offscreen through the MSAA framebuffers,
+        // which need WebGL2 and a quality that uses MSAA.
+        if self.msaa_buffers.is_none() {
+            return false;
+        }
+        self.capturing = true;
+        true
+    }
+
+    fn end_capture(&mut self) -> Option<Bitmap> {
+        self.capturing = false;
+        let (gl, msaa_buffers) = match (&self.gl2, &self.msaa_buffers) {
+            (Some(gl), Some(msaa_buffers)) => (gl, msaa_buffers),
+            _ => return None,
+        };
+
+        let (width, height) = (self.renderbuffer_width, self.renderbuffer_height);
+        let mut pixels = vec![0; width as usize * height as usize * 4];
+        gl.bind_framebuffer(Gl2::FRAMEBUFFER, Some(&msaa_buffers.color_framebuffer));
+        let result = gl.read_pixels_with_opt_u8_array(
+            0,
+            0,
+            width,
+            height,
+            Gl2::RGBA,
+            Gl2::UNSIGNED_BYTE,
+            Some(&mut pixels),
+        );
+        gl.bind_framebuffer(Gl2::FRAMEBUFFER, None);
+        result.ok()?;
+
+        // Rows are read from the bottom up.
+        let rgba = pixels
+            .chunks_exact(width as usize * 4)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+        Some(Bitmap {
+            width: width as u32,
+            height: height as u32,
+            data: BitmapFormat::Rgba(rgba),
+        })
+    }
+
     fn get_bitmap_pixels(&mut self, bitmap: BitmapHandle) -> Option<Bitmap> {
         self.bitmap_registry.get(&bitmap).cloned()
     }
@@ -1560,3 +1626,23 @@ impl GlExt for Gl2 {
         }
     }
 }
+
+/// Returns the blend function and equation, as `(src, dst, equation)`, used to
+/// draw with the given blend mode. `src_blend` and `dst_blend` are the factors
+/// used by the current shader for normal blending.
+///
+/// Blend modes are applied per draw call rather than to the composited object.
+fn blend_func_for_mode(
+    blend_mode: swf::BlendMode,
+    src_blend: u32,
+    dst_blend: u32,
+) -> (u32, u32, u32) {
+    match blend_mode {
+        swf::BlendMode::Add => (src_blend, Gl::ONE, Gl::FUNC_ADD),
+        swf::BlendMode::Subtract => (src_blend, Gl::ONE, Gl::FUNC_REVERSE_SUBTRACT),
+        swf::BlendMode::Multiply => (Gl::DST_COLOR, Gl::ONE_MINUS_SRC_ALPHA, Gl::FUNC_ADD),
+        swf::BlendMode::Screen => (Gl::ONE, Gl::ONE_MINUS_SRC_COLOR, Gl::FUNC_ADD),
+        // TODO: The remaining blend modes can't be expressed with fixed-function blending.
+        _ => (src_blend, dst_blend, Gl::FUNC_ADD),
+    }
+}
@@ -11,5 +11,5 @@ layout(push_constant) uniform FragmentPushConstants {
 layout(location=0) out vec4 out_color;
 
 void main() {
-    out_color = mult_color * frag_color + add_color;
+    out_color = clamp(mult_color * frag_color + add_color, 0.0, 1.0);
 }
\ No newline at end of file
@@ -85,5 +85,5 @@ void main() {
     if( u_interpolation != 0 ) {
         color = vec4(linear_to_srgb(vec3(color)), color.a);
     }
-    out_color = mult_color * color + add_color;
+    out_color = clamp(mult_color * color + add_color, 0.0, 1.0);
 }
\ No newline at end of file
@@ -2,6 +2,7 @@ use ruffle_core::backend::render::{
     Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, MovieLibrary, RenderBackend,
     ShapeHandle, Transform,
 };
+use ruffle_core::config::StageQuality;
 use ruffle_core::shape_utils::DistilledShape;
 use ruffle_core::swf;
 use std::{borrow::Cow, num::NonZeroU32};
@@ -12,13 +13,14 @@ use futures::executor::block_on;
 use raw_window_handle::HasRawWindowHandle;
 
 use crate::pipelines::Pipelines;
-use crate::target::{RenderTarget, RenderTargetFrame, SwapChainTarget};
+use crate::target::{RenderTarget, RenderTargetFrame, SwapChainTarget, TextureTargetFrame};
 use crate::utils::{create_buffer_with_data, format_list, get_backend_names};
 use enum_map::Enum;
 use ruffle_core::color_transform::ColorTransform;
+use ruffle_render_common_tess::atlas::{AtlasAllocation, TextureAtlas, ATLAS_PADDING};
 use ruffle_render_common_tess::{
-    DrawType as TessDrawType, Gradient as TessGradient, GradientType, ShapeTessellator,
-    Vertex as TessVertex,
+    Bitmap as TessBitmap, DrawType as TessDrawType, Gradient as TessGradient, GradientType,
+    ShapeTessellator, Vertex as TessVertex,
 };
 
 type Error = Box<dyn std::error::Error>;
@@ -36,10 +38,14 @@ pub mod clap;
 
 use crate::bitmaps::BitmapSamplers;
 use crate::globals::Globals;
+pub use ruffle_render_common_tess::atlas::AtlasConfig;
 use std::collections::HashMap;
 use std::path::Path;
 pub use wgpu;
 
+/// The MSAA sample count at high quality. Lower qualities use fewer samples.
+const DEFAULT_MSAA_SAMPLE_COUNT: u32 = 4;
+
 pub struct Descriptors {
     pub device: wgpu::Device,
     queue: wgpu::Queue,
@@ -51,8 +57,7 @@ pub struct Descriptors {
 
 impl Descriptors {
     pub fn new(device: wgpu::Device, queue: wgpu::Queue) -> Result<Self, Error> {
-        // TODO: Allow this to be set from command line/settings file.
-        let msaa_sample_count = 4;
+        let msaa_sample_count = DEFAULT_MSAA_SAMPLE_COUNT;
 
         let bitmap_samplers = BitmapSamplers::new(&device);
         let globals = Globals::new(&device);
@@ -80,10 +85,22 @@ pub struct WgpuRenderBackend<T: RenderTarget> {
     frame_buffer_view: wgpu::TextureView,
     depth_texture_view: wgpu::TextureView,
     current_frame: Option<Frame<'static, T>>,
+
+    /// The offscreen target that frames are drawn to between `begin_capture`
+    /// and `end_capture`.
+    capture_target: Option<TextureTarget>,
     meshes: Vec<Mesh>,
     mask_state: MaskState,
+    blend_modes: Vec<BlendType>,
     shape_tessellator: ShapeTessellator,
-    textures: Vec<Texture>,
+
+    /// The registered bitmaps, or `None` for bitmaps that have been freed.
+    textures: Vec<Option<Texture>>,
+
+    /// Small bitmaps are packed into shared atlas pages, so that drawing
+    /// them doesn't need a texture bind for every bitmap.
+    atlas: TextureAtlas,
+    atlas_pages: Vec<AtlasPage>,
     num_masks: u32,
     quad_vbo: wgpu::Buffer,
     quad_ibo: wgpu::Buffer,
@@ -93,7 +110,7 @@ pub struct WgpuRenderBackend<T: RenderTarget> {
 
 #[allow(dead_code)]
 struct Frame<'a, T: RenderTarget> {
-    frame_data: Box<(wgpu::CommandEncoder, T::Frame)>,
+    frame_data: Box<(wgpu::CommandEncoder, FrameOutput<T>)>,
 
     // TODO: This is a self-reference to the above, so we
     // use some unsafe to cast the lifetime away. We know this
@@ -111,6 +128,23 @@ impl<'a, T: RenderTarget> Frame<'static, T> {
     }
 }
 
+/// The texture that a frame is drawn to.
+enum FrameOutput<T: RenderTarget> {
+    Target(T::Frame),
+
+    /// The capture target, while a frame is being captured.
+    Capture(TextureTargetFrame),
+}
+
+impl<T: RenderTarget> FrameOutput<T> {
+    fn view(&self) -> &wgpu::TextureView {
+        match self {
+            FrameOutput::Target(frame) => frame.view(),
+            FrameOutput::Capture(frame) => frame.view(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
 pub enum MaskState {
     NoMask,
@@ -119,6 +153,33 @@ pub enum MaskState {
     ClearMaskStencil,
 }
 
+/// The blend modes that can be rendered directly with fixed-function
+/// blending. Any other blend mode is drawn as `Normal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum BlendType {
+    Normal,
+    Add,
+    Subtract,
+    Multiply,
+    Screen,
+    Lighten,
+    Darken,
+}
+
+impl BlendType {
+    pub fn from_blend_mode(blend_mode: swf::BlendMode) -> Self {
+        match blend_mode {
+            swf::BlendMode::Add => BlendType::Add,
+            swf::BlendMode::Subtract => BlendType::Subtract,
+            swf::BlendMode::Multiply => BlendType::Multiply,
+            swf::BlendMode::Screen => BlendType::Screen,
+            swf::BlendMode::Lighten => BlendType::Lighten,
+            swf::BlendMode::Darken => BlendType::Darken,
+            _ => BlendType::Normal,
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct Transforms {
@@ -288,36 +349,12 @@ impl WgpuRenderBackend<TextureTarget> {
 
 impl<T: RenderTarget> WgpuRenderBackend<T> {
     pub fn new(mut descriptors: Descriptors, target: T) -> Result<Self, Error> {
-        let extent = wgpu::Extent3d {
-            width: target.width(),
-            height: target.height(),
-            depth_or_array_layers: 1,
-        };
-
-        let frame_buffer_label = create_debug_label!("Framebuffer texture");
-        let frame_buffer = descriptors.device.create_texture(&wgpu::TextureDescriptor {
-            label: frame_buffer_label.as_deref(),
-            size: extent,
-            mip_level_count: 1,
-            sample_count: descriptors.msaa_sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: target.format(),
-            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-        });
-        let frame_buffer_view = frame_buffer.create_view(&Default::default());
-
-        let depth_label = create_debug_label!("Depth texture");
-        let depth_texture = descriptors.device.create_texture(&wgpu::TextureDescriptor {
-            label: depth_label.as_deref(),
-            size: extent,
-            mip_level_count: 1,
-            sample_count: descriptors.msaa_sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth24PlusStencil8,
-            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-        });
-
-        let depth_texture_view = depth_texture.create_view(&Default::default());
+        let (frame_buffer_view, depth_texture_view) = create_frame_buffers(
+            &descriptors,
+            target.format(),
+            target.width(),
+            target.height(),
+        );
 
         let (quad_vbo, quad_ibo, quad_tex_transforms) = create_quad_buffers(&descriptors.device);
 
@@ -331,12 +368,16 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             frame_buffer_view,
             depth_texture_view,
             current_frame: None,
+            capture_target: None,
             meshes: Vec::new(),
             shape_tessellator: ShapeTessellator::new(),
             textures: Vec::new(),
+            atlas: TextureAtlas::new(AtlasConfig::default()),
+            atlas_pages: Vec::new(),
 
             num_masks: 0,
             mask_state: MaskState::NoMask,
+            blend_modes: Vec::new(),
 
             quad_vbo,
             quad_ibo,
@@ -397,7 +438,10 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                 .and_then(|lib| lib.get_bitmap(id))
                 .and_then(|bitmap| {
                     let handle = bitmap.bitmap_handle();
-                    textures.get(handle.0).map(|texture| (texture, handle))
+                    textures
+                        .get(handle.0)
+                        .and_then(Option::as_ref)
+                        .map(|texture| (texture, handle))
                 })
                 .map(|(texture, handle)| (texture.width, texture.height, handle))
         });
@@ -514,14 +558,22 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                     }
                 }
                 TessDrawType::Bitmap(bitmap) => {
-                    let texture = self.textures.get(bitmap.bitmap.0).unwrap();
-                    let texture_view = texture.texture.create_view(&Default::default());
+                    let (texture_view, matrix) = match self.bitmap_fill(&bitmap, &vertices) {
+                        Some(fill) => fill,
+                        None => {
+                            log::warn!(
+                                "Shape {} is filled with a bitmap that isn't registered",
+                                shape_id
+                            );
+                            continue;
+                        }
+                    };
 
                     // TODO: Extract to function?
                     let mut texture_transform = [[0.0; 4]; 4];
-                    texture_transform[0][..3].copy_from_slice(&bitmap.matrix[0]);
-                    texture_transform[1][..3].copy_from_slice(&bitmap.matrix[1]);
-                    texture_transform[2][..3].copy_from_slice(&bitmap.matrix[2]);
+                    texture_transform[0][..3].copy_from_slice(&matrix[0]);
+                    texture_transform[1][..3].copy_from_slice(&matrix[1]);
+                    texture_transform[2][..3].copy_from_slice(&matrix[2]);
 
                     let tex_transforms_ubo = create_buffer_with_data(
                         &self.descriptors.device,
@@ -586,29 +638,91 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
         Mesh { draws }
     }
 
-    fn register_bitmap(&mut self, bitmap: Bitmap, debug_str: &str) -> BitmapInfo {
+    /// Register a bitmap, packing it into the atlas if it is small enough.
+    ///
+    /// Bitmaps whose pixels are updated later must set `allow_atlas` to
+    /// `false`, as only bitmaps with their own texture can be updated.
+    fn register_bitmap_internal(
+        &mut self,
+        bitmap: Bitmap,
+        debug_str: &str,
+        allow_atlas: bool,
+    ) -> BitmapInfo {
+        let handle = BitmapHandle(self.textures.len());
+        let width = bitmap.width;
+        let height = bitmap.height;
+
+        let atlas_allocation = if allow_atlas {
+            self.atlas.allocate(width, height)
+        } else {
+            None
+        };
+        let (texture, quad) = if let Some(allocation) = atlas_allocation {
+            self.write_to_atlas(&bitmap, &allocation);
+            let [u, v, uw, vh] = allocation.uv_rect(self.atlas.config().page_size);
+            let vertices = [
+                Vertex {
+                    position: [u, v],
+                    color: [1.0, 1.0, 1.0, 1.0],
+                },
+                Vertex {
+                    position: [u + uw, v],
+                    color: [1.0, 1.0, 1.0, 1.0],
+                },
+                Vertex {
+                    position: [u + uw, v + vh],
+                    color: [1.0, 1.0, 1.0, 1.0],
+                },
+                Vertex {
+                    position: [u, v + vh],
+                    color: [1.0, 1.0, 1.0, 1.0],
+                },
+            ];
+            let vertex_buffer = create_buffer_with_data(
+                &self.descriptors.device,
+                bytemuck::cast_slice(&vertices),
+                wgpu::BufferUsage::VERTEX,
+                create_debug_label!("Bitmap {} atlas vbo", handle.0),
+            );
+            (
+                None,
+                BitmapQuad::Atlas {
+                    allocation,
+                    vertex_buffer,
+                },
+            )
+        } else {
+            let texture = self.create_texture(&bitmap, debug_str);
+            let bind_group = self.create_quad_bind_group(
+                &texture,
+                create_debug_label!("Bitmap {} bind group", handle.0),
+            );
+            (Some(texture), BitmapQuad::Texture { bind_group })
+        };
+
+        self.bitmap_registry.insert(handle, bitmap);
+        self.textures.push(Some(Texture {
+            width,
+            height,
+            texture,
+            quad,
+        }));
+
+        BitmapInfo {
+            handle,
+            width: width as u16,
+            height: height as u16,
+        }
+    }
+
+    /// Create a texture containing only the given bitmap.
+    fn create_texture(&self, bitmap: &Bitmap, debug_str: &str) -> wgpu::Texture {
         let extent = wgpu::Extent3d {
             width: bitmap.width,
             height: bitmap.height,
             depth_or_array_layers: 1,
         };
 
-        let data: Cow<[u8]> = match &bitmap.data {
-            BitmapFormat::Rgba(data) => Cow::Borrowed(data),
-            BitmapFormat::Rgb(data) => {
-                // Expand to RGBA.
-                let mut as_rgba =
-                    Vec::with_capacity(extent.width as usize * extent.height as usize * 4);
-                for i in (0..data.len()).step_by(3) {
-                    as_rgba.push(data[i]);
-                    as_rgba.push(data[i + 1]);
-                    as_rgba.push(data[i + 2]);
-                    as_rgba.push(255);
-                }
-                Cow::Owned(as_rgba)
-            }
-        };
-
         let texture_label = create_debug_label!("{} Texture", debug_str);
         let texture = self
             .descriptors
@@ -629,7 +743,7 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                 mip_level: 0,
                 origin: Default::default(),
             },
-            &data,
+            &bitmap_to_rgba(bitmap),
             wgpu::ImageDataLayout {
                 offset: 0,
                 bytes_per_row: NonZeroU32::new(4 * extent.width),
@@ -638,14 +752,17 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             extent,
         );
 
-        let handle = BitmapHandle(self.textures.len());
-        let width = bitmap.width;
-        let height = bitmap.height;
+        texture
+    }
 
-        // Make bind group for bitmap quad.
+    /// Create the bind group used to draw the whole of a texture as a quad.
+    fn create_quad_bind_group(
+        &self,
+        texture: &wgpu::Texture,
+        label: Option<String>,
+    ) -> wgpu::BindGroup {
         let texture_view = texture.create_view(&Default::default());
-        let bind_group = self
-            .descriptors
+        self.descriptors
             .device
             .create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &self.descriptors.pipelines.bitmap_layout,
@@ -665,22 +782,167 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                         resource: wgpu::BindingResource::TextureView(&texture_view),
                     },
                 ],
-                label: create_debug_label!("Bitmap {} bind group", handle.0).as_deref(),
+                label: label.as_deref(),
+            })
+    }
+
+    /// Copy a bitmap into its region of an atlas page, creating the page if
+    /// it's new.
+    ///
+    /// The padding around the bitmap is filled with its edge pixels, so that
+    /// smoothing doesn't blend in the neighbouring bitmaps.
+    fn write_to_atlas(&mut self, bitmap: &Bitmap, allocation: &AtlasAllocation) {
+        let page_size = self.atlas.config().page_size;
+        while self.atlas_pages.len() <= allocation.page {
+            let label = create_debug_label!("Atlas page {}", self.atlas_pages.len());
+            let texture = self
+                .descriptors
+                .device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: label.as_deref(),
+                    size: wgpu::Extent3d {
+                        width: page_size,
+                        height: page_size,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+                });
+            let bind_group = self.create_quad_bind_group(
+                &texture,
+                create_debug_label!("Atlas page {} bind group", self.atlas_pages.len()),
+            );
+            self.atlas_pages.push(AtlasPage {
+                texture,
+                bind_group,
             });
+        }
 
-        self.bitmap_registry.insert(handle, bitmap);
-        self.textures.push(Texture {
-            width,
-            height,
-            texture,
-            bind_group,
-        });
+        let rgba = bitmap_to_rgba(bitmap);
+        let width = bitmap.width as usize;
+        let height = bitmap.height as usize;
+        let padding = ATLAS_PADDING as usize;
+        let padded_width = width + 2 * padding;
+        let padded_height = height + 2 * padding;
+        let mut padded = Vec::with_capacity(padded_width * padded_height * 4);
+        for y in 0..padded_height {
+            let src_y = y.saturating_sub(padding).min(height - 1);
+            for x in 0..padded_width {
+                let src_x = x.saturating_sub(padding).min(width - 1);
+                let i = (src_y * width + src_x) * 4;
+                padded.extend_from_slice(&rgba[i..i + 4]);
+            }
+        }
 
-        BitmapInfo {
-            handle,
-            width: width as u16,
-            height: height as u16,
+        self.descriptors.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.atlas_pages[allocation.page].texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: allocation.x - ATLAS_PADDING,
+                    y: allocation.y - ATLAS_PADDING,
+                    z: 0,
+                },
+            },
+            &padded,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * padded_width as u32),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: padded_width as u32,
+                height: padded_height as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// The texture that a bitmap fill samples from, along with the matrix
+    /// from shape coordinates to that texture's coordinates.
+    ///
+    /// Fills of bitmaps in the atlas are drawn from their atlas page, unless
+    /// the fill repeats the bitmap or reaches past its edges. Samplers can
+    /// only wrap or clamp at the edges of a whole texture, so those fills
+    /// fall back to a texture containing only the bitmap.
+    fn bitmap_fill(
+        &mut self,
+        bitmap: &TessBitmap,
+        vertices: &[Vertex],
+    ) -> Option<(wgpu::TextureView, [[f32; 3]; 3])> {
+        let texture = self.textures.get(bitmap.bitmap.0)?.as_ref()?;
+        if let BitmapQuad::Atlas { allocation, .. } = &texture.quad {
+            // The padding around the bitmap repeats its edge pixels, so a fill
+            // may reach that far past the edges before it shows neighbours.
+            let slack_u = ATLAS_PADDING as f32 / allocation.width as f32;
+            let slack_v = ATLAS_PADDING as f32 / allocation.height as f32;
+            let m = &bitmap.matrix;
+            let within_bitmap = vertices.iter().all(|vertex| {
+                let [x, y] = vertex.position;
+                let u = m[0][0] * x + m[1][0] * y + m[2][0];
+                let v = m[0][1] * x + m[1][1] * y + m[2][1];
+                (-slack_u..=1.0 + slack_u).contains(&u) && (-slack_v..=1.0 + slack_v).contains(&v)
+            });
+
+            if !bitmap.is_repeating && within_bitmap {
+                // Map the bitmap's texture coordinates onto its region of the page.
+                let [u, v, uw, vh] = allocation.uv_rect(self.atlas.config().page_size);
+                let remap = |column: [f32; 3]| {
+                    [
+                        column[0] * uw + column[2] * u,
+                        column[1] * vh + column[2] * v,
+                        column[2],
+                    ]
+                };
+                let matrix = [remap(m[0]), remap(m[1]), remap(m[2])];
+                let page = &self.atlas_pages[allocation.page];
+                return Some((page.texture.create_view(&Default::default()), matrix));
+            }
+        }
+
+        let texture_view = self
+            .standalone_texture(bitmap.bitmap)?
+            .create_view(&Default::default());
+        Some((texture_view, bitmap.matrix))
+    }
+
+    /// The texture containing only the given bitmap, creating it if the
+    /// bitmap was packed into the atlas.
+    fn standalone_texture(&mut self, handle: BitmapHandle) -> Option<&wgpu::Texture> {
+        let needs_texture = self.textures.get(handle.0)?.as_ref()?.texture.is_none();
+        if needs_texture {
+            let bitmap = self.bitmap_registry.get(&handle)?;
+            let texture = self.create_texture(bitmap, "Atlas fill");
+            self.textures[handle.0].as_mut()?.texture = Some(texture);
+        }
+
+        self.textures[handle.0].as_ref()?.texture.as_ref()
+    }
+
+    /// Set how small bitmaps are packed into atlas pages.
+    ///
+    /// This must be set before any bitmaps are registered. Pages larger
+    /// than the device's largest texture are shrunk to fit.
+    pub fn set_atlas_config(&mut self, mut config: AtlasConfig) {
+        if self.atlas.num_pages() > 0 {
+            log::warn!("Can't change the atlas configuration after bitmaps are registered");
+            return;
+        }
+
+        let max_size = self.descriptors.device.limits().max_texture_dimension_2d;
+        if config.page_size > max_size {
+            log::warn!(
+                "Atlas size {} is larger than the largest texture this device supports, using {}",
+                config.page_size,
+                max_size
+            );
+            config.page_size = max_size;
         }
+
+        self.atlas = TextureAtlas::new(config);
     }
 
     pub fn target(&self) -> &T {
@@ -690,6 +952,14 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
     pub fn device(&self) -> &wgpu::Device {
         &self.descriptors.device
     }
+
+    /// The blend mode that shapes and bitmaps should currently be drawn with.
+    fn blend_type(&self) -> BlendType {
+        self.blend_modes
+            .last()
+            .copied()
+            .unwrap_or(BlendType::Normal)
+    }
 }
 
 impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
@@ -700,46 +970,49 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
 
         self.target.resize(&self.descriptors.device, width, height);
 
-        let label = create_debug_label!("Framebuffer texture");
-        let frame_buffer = self
-            .descriptors
-            .device
-            .create_texture(&wgpu::TextureDescriptor {
-                label: label.as_deref(),
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: self.descriptors.msaa_sample_count,
-                dimension: wgpu::TextureDimension::D2,
-                format: self.target.format(),
-                usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-            });
-        self.frame_buffer_view = frame_buffer.create_view(&Default::default());
-
-        let label = create_debug_label!("Depth texture");
-        let depth_texture = self
-            .descriptors
-            .device
-            .create_texture(&wgpu::TextureDescriptor {
-                label: label.as_deref(),
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: self.descriptors.msaa_sample_count,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Depth24PlusStencil8,
-                usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-            });
-        self.depth_texture_view = depth_texture.create_view(&Default::default());
+        let (frame_buffer_view, depth_texture_view) =
+            create_frame_buffers(&self.descriptors, self.target.format(), width, height);
+        self.frame_buffer_view = frame_buffer_view;
+        self.depth_texture_view = depth_texture_view;
         self.descriptors.globals.set_resolution(width, height);
     }
 
+    fn set_quality(&mut self, quality: StageQuality) {
+        let msaa_sample_count = match quality {
+            StageQuality::Low => 1,
+            StageQuality::Medium => 2,
+            StageQuality::High | StageQuality::Best => DEFAULT_MSAA_SAMPLE_COUNT,
+        };
+        self.shape_tessellator.set_quality(quality);
+
+        if msaa_sample_count == self.descriptors.msaa_sample_count {
+            return;
+        }
+
+        // The pipelines and framebuffers are all created for one sample count.
+        let pipelines = Pipelines::new(
+            &self.descriptors.device,
+            msaa_sample_count,
+            self.descriptors.bitmap_samplers.layout(),
+            self.descriptors.globals.layout(),
+        );
+        match pipelines {
+            Ok(pipelines) => {
+                self.descriptors.pipelines = pipelines;
+                self.descriptors.msaa_sample_count = msaa_sample_count;
+                let (frame_buffer_view, depth_texture_view) = create_frame_buffers(
+                    &self.descriptors,
+                    self.target.format(),
+                    self.target.width(),
+                    self.target.height(),
+                );
+                self.frame_buffer_view = frame_buffer_view;
+                self.depth_texture_view = depth_texture_view;
+            }
+            Err(e) => log::error!("Couldn't change the MSAA sample count: {}", e),
+        }
+    }
+
     fn register_shape(
         &mut self,
         shape: DistilledShape,
@@ -769,53 +1042,35 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         handle
     }
 
-    fn register_bitmap_jpeg(
-        &mut self,
-        data: &[u8],
-        jpeg_tables: Option<&[u8]>,
-    ) -> Result<BitmapInfo, Error> {
-        let data = ruffle_core::backend::render::glue_tables_to_jpeg(data, jpeg_tables);
-        self.register_bitmap_jpeg_2(&data[..])
-    }
-
-    fn register_bitmap_jpeg_2(&mut self, data: &[u8]) -> Result<BitmapInfo, Error> {
-        let bitmap = ruffle_core::backend::render::decode_define_bits_jpeg(data, None)?;
-        Ok(self.register_bitmap(bitmap, "JPEG2"))
-    }
-
-    fn register_bitmap_jpeg_3(
-        &mut self,
-        jpeg_data: &[u8],
-        alpha_data: &[u8],
-    ) -> Result<BitmapInfo, Error> {
-        let bitmap =
-            ruffle_core::backend::render::decode_define_bits_jpeg(jpeg_data, Some(alpha_data))?;
-        Ok(self.register_bitmap(bitmap, "JPEG3"))
-    }
-
-    fn register_bitmap_png(
-        &mut self,
-        swf_tag: &swf::DefineBitsLossless,
-    ) -> Result<BitmapInfo, Error> {
-        let bitmap = ruffle_core::backend::render::decode_define_bits_lossless(swf_tag)?;
-        Ok(self.register_bitmap(bitmap, "PNG"))
+    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapInfo, Error> {
+        Ok(self.register_bitmap_internal(bitmap, "Bitmap", true))
     }
 
     fn begin_frame(&mut self, clear: Color) {
         self.mask_state = MaskState::NoMask;
         self.num_masks = 0;
 
-        let frame_output = match self.target.get_next_texture() {
-            Ok(frame) => frame,
-            Err(e) => {
-                log::warn!("Couldn't begin new render frame: {}", e);
-                // Attemp to recreate the swap chain in this case.
-                self.target.resize(
-                    &self.descriptors.device,
-                    self.target.width(),
-                    self.target.height(),
-                );
-                return;
+        let frame_output = if let Some(capture_target) = &mut self.capture_target {
+            match capture_target.get_next_texture() {
+                Ok(frame) => FrameOutput::Capture(frame),
+                Err(e) => {
+                    log::warn!("Couldn't begin new capture frame: {}", e);
+                    return;
+                }
+            }
+        } else {
+            match self.target.get_next_texture() {
+                Ok(frame) => FrameOutput::Target(frame),
+                Err(e) => {
+                    log::warn!("Couldn't begin new render frame: {}", e);
+                    // Attemp to recreate the swap chain in this case.
+                    self.target.resize(
+                        &self.descriptors.device,
+                        self.target.width(),
+                        self.target.height(),
+                    );
+                    return;
+                }
             }
         };
 
@@ -878,20 +1133,47 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 
     fn render_bitmap(&mut self, bitmap: BitmapHandle, transform: &Transform, smoothing: bool) {
-        if let Some(texture) = self.textures.get(bitmap.0) {
+        let blend_type = self.blend_type();
+        if let Some(texture) = self.textures.get(bitmap.0).and_then(Option::as_ref) {
             let frame = if let Some(frame) = &mut self.current_frame {
                 frame.get()
             } else {
                 return;
             };
 
-            let transform = Transform {
-                matrix: transform.matrix
-                    * swf::Matrix {
+            let (quad_matrix, bind_group, vertex_buffer) = match &texture.quad {
+                BitmapQuad::Texture { bind_group } => (
+                    swf::Matrix {
                         a: texture.width as f32,
                         d: texture.height as f32,
                         ..Default::default()
                     },
+                    bind_group,
+                    &self.quad_vbo,
+                ),
+                BitmapQuad::Atlas {
+                    allocation,
+                    vertex_buffer,
+                } => {
+                    // The quad is in the page's texture coordinates, so scale
+                    // it to pixels and move the bitmap's region to the origin.
+                    let page_size = self.atlas.config().page_size as f32;
+                    (
+                        swf::Matrix {
+                            a: page_size,
+                            d: page_size,
+                            tx: swf::Twips::from_pixels(-f64::from(allocation.x)),
+                            ty: swf::Twips::from_pixels(-f64::from(allocation.y)),
+                            ..Default::default()
+                        },
+                        &self.atlas_pages[allocation.page].bind_group,
+                        vertex_buffer,
+                    )
+                }
+            };
+
+            let transform = Transform {
+                matrix: transform.matrix * quad_matrix,
                 ..*transform
             };
 
@@ -911,7 +1193,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                 self.descriptors
                     .pipelines
                     .bitmap_pipelines
-                    .pipeline_for(self.mask_state),
+                    .pipeline_for(blend_type, self.mask_state),
             );
             frame.render_pass.set_push_constants(
                 wgpu::ShaderStage::VERTEX,
@@ -926,9 +1208,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             frame
                 .render_pass
                 .set_bind_group(0, self.descriptors.globals.bind_group(), &[]);
-            frame
-                .render_pass
-                .set_bind_group(1, &texture.bind_group, &[]);
+            frame.render_pass.set_bind_group(1, bind_group, &[]);
             frame.render_pass.set_bind_group(
                 2,
                 self.descriptors
@@ -938,7 +1218,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             );
             frame
                 .render_pass
-                .set_vertex_buffer(0, self.quad_vbo.slice(..));
+                .set_vertex_buffer(0, vertex_buffer.slice(..));
             frame
                 .render_pass
                 .set_index_buffer(self.quad_ibo.slice(..), wgpu::IndexFormat::Uint32);
@@ -960,6 +1240,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 
     fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform) {
+        let blend_type = self.blend_type();
         let frame = if let Some(frame) = &mut self.current_frame {
             frame.get()
         } else {
@@ -992,7 +1273,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                             .descriptors
                             .pipelines
                             .color_pipelines
-                            .pipeline_for(self.mask_state),
+                            .pipeline_for(blend_type, self.mask_state),
                     );
                 }
                 DrawType::Gradient { bind_group, .. } => {
@@ -1001,7 +1282,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                             .descriptors
                             .pipelines
                             .gradient_pipelines
-                            .pipeline_for(self.mask_state),
+                            .pipeline_for(blend_type, self.mask_state),
                     );
                     frame.render_pass.set_bind_group(1, bind_group, &[]);
                 }
@@ -1016,7 +1297,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                             .descriptors
                             .pipelines
                             .bitmap_pipelines
-                            .pipeline_for(self.mask_state),
+                            .pipeline_for(blend_type, self.mask_state),
                     );
                     frame.render_pass.set_bind_group(1, bind_group, &[]);
                     frame.render_pass.set_bind_group(
@@ -1063,6 +1344,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 
     fn draw_rect(&mut self, color: Color, matrix: &swf::Matrix) {
+        let blend_type = self.blend_type();
         let frame = if let Some(frame) = &mut self.current_frame {
             frame.get()
         } else {
@@ -1094,7 +1376,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                 .descriptors
                 .pipelines
                 .color_pipelines
-                .pipeline_for(self.mask_state),
+                .pipeline_for(blend_type, self.mask_state),
         );
 
         frame.render_pass.set_push_constants(
@@ -1142,11 +1424,20 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             drop(frame.render_pass);
 
             let draw_encoder = frame.frame_data.0;
-            self.target.submit(
-                &self.descriptors.device,
-                &self.descriptors.queue,
-                vec![draw_encoder.finish()],
-            );
+            let command_buffers = vec![draw_encoder.finish()];
+            if let Some(capture_target) = &self.capture_target {
+                capture_target.submit(
+                    &self.descriptors.device,
+                    &self.descriptors.queue,
+                    command_buffers,
+                );
+            } else {
+                self.target.submit(
+                    &self.descriptors.device,
+                    &self.descriptors.queue,
+                    command_buffers,
+                );
+            }
         }
     }
 
@@ -1178,6 +1469,51 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         };
     }
 
+    fn push_blend_mode(&mut self, blend_mode: swf::BlendMode) {
+        self.blend_modes
+            .push(BlendType::from_blend_mode(blend_mode));
+    }
+
+    fn pop_blend_mode(&mut self) {
+        self.blend_modes.pop();
+    }
+
+    fn begin_capture(&mut self) -> bool {
+        // The pipelines are built for the format of the main target, so the
+        // capture target has to match it.
+        if self.target.format() != wgpu::TextureFormat::Bgra8Unorm {
+            return false;
+        }
+
+        self.capture_target = Some(TextureTarget::new(
+            &self.descriptors.device,
+            (self.target.width(), self.target.height()),
+        ));
+        true
+    }
+
+    fn end_capture(&mut self) -> Option<Bitmap> {
+        let capture_target = self.capture_target.take()?;
+        let image = capture_target.capture(&self.descriptors.device)?;
+        Some(Bitmap {
+            width: image.width(),
+            height: image.height(),
+            data: BitmapFormat::Rgba(image.into_raw()),
+        })
+    }
+
+    fn unregister_bitmap(&mut self, bitmap: BitmapHandle) {
+        let texture = match self.textures.get_mut(bitmap.0).and_then(Option::take) {
+            Some(texture) => texture,
+            None => return,
+        };
+
+        if let BitmapQuad::Atlas { allocation, .. } = &texture.quad {
+            self.atlas.deallocate(allocation);
+        }
+        self.bitmap_registry.remove(&bitmap);
+    }
+
     fn get_bitmap_pixels(&mut self, bitmap: BitmapHandle) -> Option<Bitmap> {
         self.bitmap_registry.get(&bitmap).cloned()
     }
@@ -1189,13 +1525,14 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         rgba: Vec<u8>,
     ) -> Result<BitmapHandle, Error> {
         Ok(self
-            .register_bitmap(
+            .register_bitmap_internal(
                 Bitmap {
                     height,
                     width,
                     data: BitmapFormat::Rgba(rgba),
                 },
                 "RAW",
+                false,
             )
             .handle)
     }
@@ -1207,10 +1544,13 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         height: u32,
         rgba: Vec<u8>,
     ) -> Result<BitmapHandle, Error> {
-        let texture = if let Some(texture) = self.textures.get(handle.0) {
-            &texture.texture
-        } else {
-            return Err("update_texture: Bitmap not registered".into());
+        let texture = match self.textures.get(handle.0).and_then(Option::as_ref) {
+            Some(Texture {
+                texture: Some(texture),
+                ..
+            }) => texture,
+            Some(_) => return Err("update_texture: Bitmap is in the atlas".into()),
+            None => return Err("update_texture: Bitmap not registered".into()),
         };
 
         let extent = wgpu::Extent3d {
@@ -1238,6 +1578,48 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 }
 
+/// Creates the framebuffer and depth textures that frames are drawn to, with
+/// the current MSAA sample count.
+fn create_frame_buffers(
+    descriptors: &Descriptors,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> (wgpu::TextureView, wgpu::TextureView) {
+    let extent = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+
+    let label = create_debug_label!("Framebuffer texture");
+    let frame_buffer = descriptors.device.create_texture(&wgpu::TextureDescriptor {
+        label: label.as_deref(),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: descriptors.msaa_sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+    });
+
+    let label = create_debug_label!("Depth texture");
+    let depth_texture = descriptors.device.create_texture(&wgpu::TextureDescriptor {
+        label: label.as_deref(),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: descriptors.msaa_sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth24PlusStencil8,
+        usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+    });
+
+    (
+        frame_buffer.create_view(&Default::default()),
+        depth_texture.create_view(&Default::default()),
+    )
+}
+
 fn create_quad_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer, wgpu::Buffer) {
     let vertices = [
         Vertex {
@@ -1290,10 +1672,51 @@ fn create_quad_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer, wg
     (vbo, ibo, tex_transforms)
 }
 
+/// Expand a bitmap to RGBA, if it isn't already.
+fn bitmap_to_rgba(bitmap: &Bitmap) -> Cow<[u8]> {
+    match &bitmap.data {
+        BitmapFormat::Rgba(data) => Cow::Borrowed(data),
+        BitmapFormat::Rgb(data) => {
+            let mut as_rgba =
+                Vec::with_capacity(bitmap.width as usize * bitmap.height as usize * 4);
+            for i in (0..data.len()).step_by(3) {
+                as_rgba.push(data[i]);
+                as_rgba.push(data[i + 1]);
+                as_rgba.push(data[i + 2]);
+                as_rgba.push(255);
+            }
+            Cow::Owned(as_rgba)
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Texture {
     width: u32,
     height: u32,
+
+    /// The texture containing only this bitmap. Bitmaps in the atlas only
+    /// get one once a shape fill repeats them or reaches past their edges.
+    texture: Option<wgpu::Texture>,
+    quad: BitmapQuad,
+}
+
+/// How a bitmap is drawn on its own.
+#[derive(Debug)]
+enum BitmapQuad {
+    /// Drawn from its own texture, with the shared unit quad.
+    Texture { bind_group: wgpu::BindGroup },
+
+    /// Drawn from its region of an atlas page, with a quad covering that
+    /// region in texture coordinates.
+    Atlas {
+        allocation: AtlasAllocation,
+        vertex_buffer: wgpu::Buffer,
+    },
+}
+
+#[derive(Debug)]
+struct AtlasPage {
     texture: wgpu::Texture,
     bind_group: wgpu::BindGroup,
 }
@@ -1,10 +1,10 @@
-use crate::{Error, MaskState, Vertex};
+use crate::{BlendType, Error, MaskState, Vertex};
 use enum_map::{enum_map, EnumMap};
 use wgpu::vertex_attr_array;
 
 #[derive(Debug)]
 pub struct ShapePipeline {
-    pub mask_pipelines: EnumMap<MaskState, wgpu::RenderPipeline>,
+    pub pipelines: EnumMap<BlendType, EnumMap<MaskState, wgpu::RenderPipeline>>,
 }
 
 #[derive(Debug)]
@@ -19,8 +19,12 @@ pub struct Pipelines {
 }
 
 impl ShapePipeline {
-    pub fn pipeline_for(&self, mask_state: MaskState) -> &wgpu::RenderPipeline {
-        &self.mask_pipelines[mask_state]
+    pub fn pipeline_for(
+        &self,
+        blend_type: BlendType,
+        mask_state: MaskState,
+    ) -> &wgpu::RenderPipeline {
+        &self.pipelines[blend_type][mask_state]
     }
 }
 
@@ -227,150 +231,107 @@ fn create_color_pipelines(
         ],
     });
 
-    let mask_pipelines = enum_map! {
-        MaskState::NoMask => {
-            let (stencil, write_mask) = mask_render_state(MaskState::NoMask);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Color pipeline no mask").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-
-                        }
+    let pipelines = enum_map! {
+        blend_type => enum_map! {
+            MaskState::NoMask => {
+                let (stencil, write_mask) = mask_render_state(MaskState::NoMask);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Color pipeline no mask").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_description,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_description,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::DrawMaskStencil => {
-            let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskStencil);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Color pipeline draw mask stencil").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::DrawMaskStencil => {
+                let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskStencil);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Color pipeline draw mask stencil").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_description,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_description,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::DrawMaskedContent => {
-            let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskedContent);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Color pipeline draw masked content").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::DrawMaskedContent => {
+                let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskedContent);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Color pipeline draw masked content").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_description,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_description,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::ClearMaskStencil => {
-            let (stencil, write_mask) = mask_render_state(MaskState::ClearMaskStencil);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Color pipeline clear mask stencil").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::ClearMaskStencil => {
+                let (stencil, write_mask) = mask_render_state(MaskState::ClearMaskStencil);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Color pipeline clear mask stencil").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_description,
-                msaa_sample_count,
-            ))
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_description,
+                    msaa_sample_count,
+                ))
+            },
         },
     };
 
-    ShapePipeline { mask_pipelines }
+    ShapePipeline { pipelines }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -400,149 +361,107 @@ fn create_bitmap_pipeline(
         ],
     });
 
-    let mask_pipelines = enum_map! {
-        MaskState::NoMask => {
-            let (stencil, write_mask) = mask_render_state(MaskState::NoMask);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Bitmap pipeline no mask").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::One,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+    let pipelines = enum_map! {
+        blend_type => enum_map! {
+            MaskState::NoMask => {
+                let (stencil, write_mask) = mask_render_state(MaskState::NoMask);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Bitmap pipeline no mask").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::One)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::DrawMaskStencil => {
-            let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskStencil);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Bitmap pipeline draw mask stencil").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::DrawMaskStencil => {
+                let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskStencil);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Bitmap pipeline draw mask stencil").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::DrawMaskedContent => {
-            let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskedContent);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Bitmap pipeline draw masked content").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Equal,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::One,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::DrawMaskedContent => {
+                let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskedContent);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Bitmap pipeline draw masked content").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Equal,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::One)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::ClearMaskStencil => {
-            let (stencil, write_mask) = mask_render_state(MaskState::ClearMaskStencil);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Bitmap pipeline clear mask stencil").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::ClearMaskStencil => {
+                let (stencil, write_mask) = mask_render_state(MaskState::ClearMaskStencil);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Bitmap pipeline clear mask stencil").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        }
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            }
+        },
     };
 
-    ShapePipeline { mask_pipelines }
+    ShapePipeline { pipelines }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -571,150 +490,108 @@ fn create_gradient_pipeline(
         ],
     });
 
-    let mask_pipelines = enum_map! {
-        MaskState::NoMask => {
-            let (stencil, write_mask) = mask_render_state(MaskState::NoMask);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Gradient pipeline no mask").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+    let pipelines = enum_map! {
+        blend_type => enum_map! {
+            MaskState::NoMask => {
+                let (stencil, write_mask) = mask_render_state(MaskState::NoMask);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Gradient pipeline no mask").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::DrawMaskStencil => {
-            let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskStencil);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Gradient pipeline draw mask stencil").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::DrawMaskStencil => {
+                let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskStencil);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Gradient pipeline draw mask stencil").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            },
 
 
-        MaskState::DrawMaskedContent => {
-            let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskedContent);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Gradient pipeline draw masked content").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Equal,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::DrawMaskedContent => {
+                let (stencil, write_mask) = mask_render_state(MaskState::DrawMaskedContent);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Gradient pipeline draw masked content").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Equal,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        },
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            },
 
-        MaskState::ClearMaskStencil => {
-            let (stencil, write_mask) = mask_render_state(MaskState::ClearMaskStencil);
-            device.create_render_pipeline(&create_pipeline_descriptor(
-                create_debug_label!("Gradient pipeline clear mask stencil").as_deref(),
-                vertex_shader,
-                fragment_shader,
-                &pipeline_layout,
-                Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth24PlusStencil8,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Always,
-                    stencil,
-                    bias: Default::default(),
-                }),
-                &[wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8Unorm,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::SrcAlpha,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
+            MaskState::ClearMaskStencil => {
+                let (stencil, write_mask) = mask_render_state(MaskState::ClearMaskStencil);
+                device.create_render_pipeline(&create_pipeline_descriptor(
+                    create_debug_label!("Gradient pipeline clear mask stencil").as_deref(),
+                    vertex_shader,
+                    fragment_shader,
+                    &pipeline_layout,
+                    Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth24PlusStencil8,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Always,
+                        stencil,
+                        bias: Default::default(),
                     }),
-                    write_mask,
-                }],
-                vertex_buffers_layout,
-                msaa_sample_count,
-            ))
-        }
+                    &[wgpu::ColorTargetState {
+                        format: wgpu::TextureFormat::Bgra8Unorm,
+                        blend: Some(blend_state(blend_type, wgpu::BlendFactor::SrcAlpha)),
+                        write_mask,
+                    }],
+                    vertex_buffers_layout,
+                    msaa_sample_count,
+                ))
+            }
+        },
     };
 
-    ShapePipeline { mask_pipelines }
+    ShapePipeline { pipelines }
 }
 
 fn mask_render_state(state: MaskState) -> (wgpu::StencilState, wgpu::ColorWrite) {
@@ -767,3 +644,59 @@ fn mask_render_state(state: MaskState) -> (wgpu::StencilState, wgpu::ColorWrite)
         color_write,
     )
 }
+
+/// Returns the fixed-function blend state used to draw with the given blend
+/// mode. `src_color_factor` is the source factor used for normal blending,
+/// which differs for premultiplied bitmaps.
+fn blend_state(blend_type: BlendType, src_color_factor: wgpu::BlendFactor) -> wgpu::BlendState {
+    let (src_factor, dst_factor, operation) = match blend_type {
+        BlendType::Normal => (
+            src_color_factor,
+            wgpu::BlendFactor::OneMinusSrcAlpha,
+            wgpu::BlendOperation::Add,
+        ),
+        BlendType::Add => (
+            src_color_factor,
+            wgpu::BlendFactor::One,
+            wgpu::BlendOperation::Add,
+        ),
+        BlendType::Subtract => (
+            src_color_factor,
+            wgpu::BlendFactor::One,
+            wgpu::BlendOperation::ReverseSubtract,
+        ),
+        BlendType::Multiply => (
+            wgpu::BlendFactor::Dst,
+            wgpu::BlendFactor::OneMinusSrcAlpha,
+            wgpu::BlendOperation::Add,
+        ),
+        BlendType::Screen => (
+            wgpu::BlendFactor::One,
+            wgpu::BlendFactor::OneMinusSrc,
+            wgpu::BlendOperation::Add,
+        ),
+        BlendType::Lighten => (
+            wgpu::BlendFactor::One,
+            wgpu::BlendFactor::One,
+            wgpu::BlendOperation::Max,
+        ),
+        BlendType::Darken => (
+            wgpu::BlendFactor::One,
+            wgpu::BlendFactor::One,
+            wgpu::BlendOperation::Min,
+        ),
+    };
+
+    wgpu::BlendState {
+        color: wgpu::BlendComponent {
+            src_factor,
+            dst_factor,
+            operation,
+        },
+        alpha: wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::SrcAlpha,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+    }
+}
@@ -4,6 +4,7 @@ pub enum OpCode {
     AddI = 0xC5,
     AsType = 0x86,
     AsTypeLate = 0x87,
+    ApplyType = 0x53,
     BitAnd = 0xA8,
     BitNot = 0x97,
     BitOr = 0xa9,
@@ -19,7 +20,12 @@ pub enum OpCode {
     CheckFilter = 0x78,
     Coerce = 0x80,
     CoerceA = 0x82,
+    CoerceB = 0x81,
+    CoerceD = 0x84,
+    CoerceI = 0x83,
+    CoerceO = 0x89,
     CoerceS = 0x85,
+    CoerceU = 0x88,
     Construct = 0x42,
     ConstructProp = 0x4a,
     ConstructSuper = 0x49,
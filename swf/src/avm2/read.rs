@@ -171,6 +171,17 @@ impl<'a> Reader<'a> {
             0x1c => Multiname::MultinameLA {
                 namespace_set: self.read_index()?,
             },
+            0x1d => Multiname::TypeName {
+                base_type: self.read_index()?,
+                parameters: {
+                    let num_parameters = self.read_u30()?;
+                    let mut parameters = Vec::with_capacity(num_parameters as usize);
+                    for _ in 0..num_parameters {
+                        parameters.push(self.read_index()?);
+                    }
+                    parameters
+                },
+            },
             _ => return Err(Error::invalid_data("Invalid multiname kind")),
         })
     }
@@ -524,6 +535,9 @@ impl<'a> Reader<'a> {
                 type_name: self.read_index()?,
             },
             OpCode::AsTypeLate => Op::AsTypeLate,
+            OpCode::ApplyType => Op::ApplyType {
+                num_types: self.read_u30()?,
+            },
             OpCode::BitAnd => Op::BitAnd,
             OpCode::BitNot => Op::BitNot,
             OpCode::BitOr => Op::BitOr,
@@ -564,7 +578,12 @@ impl<'a> Reader<'a> {
                 index: self.read_index()?,
             },
             OpCode::CoerceA => Op::CoerceA,
+            OpCode::CoerceB => Op::CoerceB,
+            OpCode::CoerceD => Op::CoerceD,
+            OpCode::CoerceI => Op::CoerceI,
+            OpCode::CoerceO => Op::CoerceO,
             OpCode::CoerceS => Op::CoerceS,
+            OpCode::CoerceU => Op::CoerceU,
             OpCode::Construct => Op::Construct {
                 num_args: self.read_u30()?,
             },
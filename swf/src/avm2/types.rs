@@ -82,6 +82,10 @@ pub enum Multiname {
     MultinameLA {
         namespace_set: Index<NamespaceSet>,
     },
+    TypeName {
+        base_type: Index<Multiname>,
+        parameters: Vec<Index<Multiname>>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -233,6 +237,9 @@ pub enum Op {
         type_name: Index<Multiname>,
     },
     AsTypeLate,
+    ApplyType {
+        num_types: u32,
+    },
     BitAnd,
     BitNot,
     BitOr,
@@ -273,7 +280,12 @@ pub enum Op {
         index: Index<Multiname>,
     },
     CoerceA,
+    CoerceB,
+    CoerceD,
+    CoerceI,
+    CoerceO,
     CoerceS,
+    CoerceU,
     Construct {
         num_args: u32,
     },
@@ -312,6 +312,17 @@ impl<W: Write> Writer<W> {
                 self.write_u8(0x1c)?;
                 self.write_index(namespace_set)?;
             }
+            Multiname::TypeName {
+                ref base_type,
+                ref parameters,
+            } => {
+                self.write_u8(0x1d)?;
+                self.write_index(base_type)?;
+                self.write_u30(parameters.len() as u32)?;
+                for parameter in parameters {
+                    self.write_index(parameter)?;
+                }
+            }
         }
         Ok(())
     }
@@ -587,8 +598,7 @@ impl<W: Write> Writer<W> {
         Ok(())
     }
 
-    #[allow(dead_code)]
-    fn write_op(&mut self, op: &Op) -> Result<()> {
+    pub fn write_op(&mut self, op: &Op) -> Result<()> {
         match *op {
             Op::Add => self.write_opcode(OpCode::Add)?,
             Op::AddI => self.write_opcode(OpCode::AddI)?,
@@ -597,6 +607,10 @@ impl<W: Write> Writer<W> {
                 self.write_index(type_name)?;
             }
             Op::AsTypeLate => self.write_opcode(OpCode::AsTypeLate)?,
+            Op::ApplyType { num_types } => {
+                self.write_opcode(OpCode::ApplyType)?;
+                self.write_u30(num_types)?;
+            }
             Op::BitAnd => self.write_opcode(OpCode::BitAnd)?,
             Op::BitNot => self.write_opcode(OpCode::BitNot)?,
             Op::BitOr => self.write_opcode(OpCode::BitOr)?,
@@ -667,7 +681,12 @@ impl<W: Write> Writer<W> {
                 self.write_index(index)?;
             }
             Op::CoerceA => self.write_opcode(OpCode::CoerceA)?,
+            Op::CoerceB => self.write_opcode(OpCode::CoerceB)?,
+            Op::CoerceD => self.write_opcode(OpCode::CoerceD)?,
+            Op::CoerceI => self.write_opcode(OpCode::CoerceI)?,
+            Op::CoerceO => self.write_opcode(OpCode::CoerceO)?,
             Op::CoerceS => self.write_opcode(OpCode::CoerceS)?,
+            Op::CoerceU => self.write_opcode(OpCode::CoerceU)?,
             Op::Construct { num_args } => {
                 self.write_opcode(OpCode::Construct)?;
                 self.write_u30(num_args)?;
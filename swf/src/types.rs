@@ -175,6 +175,22 @@ impl Twips {
     pub const fn saturating_sub(self, rhs: Self) -> Self {
         Self(self.0.saturating_sub(rhs.0))
     }
+
+    /// Saturating integer addition. Computes `self + rhs`, saturating at the numeric bounds
+    /// of [`i32`] instead of overflowing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use swf::Twips;
+    ///
+    /// assert_eq!(Twips::new(40).saturating_add(Twips::new(20)), Twips::new(60));
+    /// assert_eq!(Twips::new(i32::MAX).saturating_add(Twips::new(5)), Twips::new(i32::MAX));
+    /// assert_eq!(Twips::new(i32::MIN).saturating_add(Twips::new(-100)), Twips::new(i32::MIN));
+    /// ```
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
 }
 
 impl std::ops::Add for Twips {
@@ -0,0 +1,275 @@
+//! Tests decoding the bitmaps of SWF tags and loaded images.
+//!
+//! Each folder in `tests/bitmaps` holds the image data of a tag. The pixels it
+//! should decode to are worked out here with the reference `jpeg-decoder`,
+//! `png`, `gif` and `flate2` crates, and premultiplied with floating point
+//! math, so that the expectations don't come from Ruffle's own decoder.
+
+use ruffle_core::backend::render::{swf, Bitmap, BitmapFormat};
+use ruffle_core::bitmap::decode::{
+    decode_define_bits_jpeg, decode_define_bits_jpeg_4, decode_define_bits_lossless,
+};
+use std::io::Read;
+
+type Error = Box<dyn std::error::Error>;
+
+/// The size of the blocks a JPEG is compressed in.
+const JPEG_BLOCK_SIZE: usize = 8;
+
+/// Multiplies a color channel by an alpha value.
+fn premultiply(channel: u8, alpha: u8) -> u8 {
+    (f32::from(channel) * f32::from(alpha) / 255.0).round() as u8
+}
+
+/// Decodes a JPEG with the reference decoder, returning its size and pixels.
+fn reference_jpeg(data: &[u8]) -> Result<(u32, u32, jpeg_decoder::PixelFormat, Vec<u8>), Error> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let pixels = decoder.decode()?;
+    let info = decoder.info().ok_or("No JPEG info")?;
+    Ok((
+        info.width.into(),
+        info.height.into(),
+        info.pixel_format,
+        pixels,
+    ))
+}
+
+/// Checks the size of a decoded bitmap, and that each of its channels is
+/// within `tolerance` of the expected one.
+fn assert_bitmap_eq(bitmap: Bitmap, width: u32, height: u32, expected: &Expected, tolerance: u8) {
+    assert_eq!((bitmap.width, bitmap.height), (width, height));
+    let (actual, expected) = match (bitmap.data, expected) {
+        (BitmapFormat::Rgb(actual), Expected::Rgb(expected)) => (actual, expected),
+        (BitmapFormat::Rgba(actual), Expected::Rgba(expected)) => (actual, expected),
+        (data, expected) => panic!("Decoded {:?}, expected {:?}", data, expected),
+    };
+    assert_eq!(actual.len(), expected.len());
+    for (i, (&actual, &expected)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            (i16::from(actual) - i16::from(expected)).abs() <= i16::from(tolerance),
+            "Channel {} is {}, expected {}",
+            i,
+            actual,
+            expected
+        );
+    }
+}
+
+#[derive(Debug)]
+enum Expected {
+    Rgb(Vec<u8>),
+    Rgba(Vec<u8>),
+}
+
+#[test]
+fn define_bits_jpeg3() -> Result<(), Error> {
+    let image = std::fs::read("tests/bitmaps/define_bits_jpeg3/image.jpg")?;
+    let alpha_data = std::fs::read("tests/bitmaps/define_bits_jpeg3/alpha.bin")?;
+
+    let (width, height, format, rgb) = reference_jpeg(&image)?;
+    assert_eq!(format, jpeg_decoder::PixelFormat::RGB24);
+    let mut alpha = Vec::new();
+    flate2::read::ZlibDecoder::new(&alpha_data[..]).read_to_end(&mut alpha)?;
+    let expected = rgb
+        .chunks_exact(3)
+        .zip(alpha)
+        .flat_map(|(rgb, a)| {
+            vec![
+                premultiply(rgb[0], a),
+                premultiply(rgb[1], a),
+                premultiply(rgb[2], a),
+                a,
+            ]
+        })
+        .collect();
+
+    let bitmap = decode_define_bits_jpeg(&image, Some(&alpha_data))?;
+    assert_bitmap_eq(bitmap, width, height, &Expected::Rgba(expected), 0);
+    Ok(())
+}
+
+#[test]
+fn cmyk_jpeg() -> Result<(), Error> {
+    let image = std::fs::read("tests/bitmaps/cmyk_jpeg/image.jpg")?;
+
+    // Each channel is the amount of an ink, and each ink absorbs its share
+    // of the light that the black ink lets through.
+    let (width, height, format, cmyk) = reference_jpeg(&image)?;
+    assert_eq!(format, jpeg_decoder::PixelFormat::CMYK32);
+    let expected = cmyk
+        .chunks_exact(4)
+        .flat_map(|cmyk| {
+            let white = f32::from(255 - cmyk[3]) / 255.0;
+            cmyk[..3]
+                .iter()
+                .map(|&ink| (f32::from(255 - ink) * white) as u8)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let bitmap = decode_define_bits_jpeg(&image, None)?;
+    assert_bitmap_eq(bitmap, width, height, &Expected::Rgb(expected), 1);
+    Ok(())
+}
+
+#[test]
+fn png_alpha() -> Result<(), Error> {
+    let image = std::fs::read("tests/bitmaps/png_alpha/image.png")?;
+    let (width, height, color_type, pixels) = reference_png(&image)?;
+    assert_eq!(color_type, png::ColorType::RGBA);
+    let expected = pixels
+        .chunks_exact(4)
+        .flat_map(|p| {
+            vec![
+                premultiply(p[0], p[3]),
+                premultiply(p[1], p[3]),
+                premultiply(p[2], p[3]),
+                p[3],
+            ]
+        })
+        .collect();
+
+    let bitmap = decode_define_bits_jpeg(&image, None)?;
+    assert_bitmap_eq(bitmap, width, height, &Expected::Rgba(expected), 0);
+    Ok(())
+}
+
+#[test]
+fn png_grayscale_alpha() -> Result<(), Error> {
+    let image = std::fs::read("tests/bitmaps/png_grayscale_alpha/image.png")?;
+    let (width, height, color_type, pixels) = reference_png(&image)?;
+    assert_eq!(color_type, png::ColorType::GrayscaleAlpha);
+    let expected = pixels
+        .chunks_exact(2)
+        .flat_map(|p| {
+            let l = premultiply(p[0], p[1]);
+            vec![l, l, l, p[1]]
+        })
+        .collect();
+
+    let bitmap = decode_define_bits_jpeg(&image, None)?;
+    assert_bitmap_eq(bitmap, width, height, &Expected::Rgba(expected), 0);
+    Ok(())
+}
+
+/// Decodes a PNG with the reference decoder, expanded to 8 bits per channel.
+fn reference_png(data: &[u8]) -> Result<(u32, u32, png::ColorType, Vec<u8>), Error> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let (info, mut reader) = decoder.read_info()?;
+    let mut pixels = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels)?;
+    Ok((info.width, info.height, info.color_type, pixels))
+}
+
+#[test]
+fn gif_transparent() -> Result<(), Error> {
+    let image = std::fs::read("tests/bitmaps/gif_transparent/image.gif")?;
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut reader = options.read_info(&image[..])?;
+    let frame = reader.read_next_frame()?.ok_or("No frames in GIF")?;
+    let (width, height) = (u32::from(frame.width), u32::from(frame.height));
+    let expected = frame
+        .buffer
+        .chunks_exact(4)
+        .flat_map(|p| {
+            vec![
+                premultiply(p[0], p[3]),
+                premultiply(p[1], p[3]),
+                premultiply(p[2], p[3]),
+                p[3],
+            ]
+        })
+        .collect();
+
+    let bitmap = decode_define_bits_jpeg(&image, None)?;
+    assert_bitmap_eq(bitmap, width, height, &Expected::Rgba(expected), 0);
+    Ok(())
+}
+
+#[test]
+fn define_bits_jpeg4_deblocking() -> Result<(), Error> {
+    let image = std::fs::read("tests/bitmaps/define_bits_jpeg4_deblocking/image.jpg")?;
+    let unfiltered = rgb_pixels(decode_define_bits_jpeg(&image, None)?);
+    let bitmap = decode_define_bits_jpeg_4(&image, None, 0x100)?;
+    let (width, height) = (bitmap.width as usize, bitmap.height as usize);
+    let deblocked = rgb_pixels(bitmap);
+
+    // Only the two pixels on either side of a block edge may change.
+    let near_edge = |i: usize| !(2..JPEG_BLOCK_SIZE - 2).contains(&(i % JPEG_BLOCK_SIZE));
+    for y in 0..height {
+        for x in 0..width {
+            if !near_edge(x) && !near_edge(y) {
+                let i = (y * width + x) * 3;
+                assert_eq!(
+                    unfiltered[i..i + 3],
+                    deblocked[i..i + 3],
+                    "Pixel ({}, {}) away from block edges changed",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    // The steps across the vertical block edges shrink overall.
+    let edge_steps = |rgb: &[u8]| -> i32 {
+        let mut sum = 0;
+        for y in 0..height {
+            for x in (JPEG_BLOCK_SIZE..width).step_by(JPEG_BLOCK_SIZE) {
+                let (p, q) = ((y * width + x - 1) * 3, (y * width + x) * 3);
+                for channel in 0..3 {
+                    sum += (i32::from(rgb[q + channel]) - i32::from(rgb[p + channel])).abs();
+                }
+            }
+        }
+        sum
+    };
+    assert!(edge_steps(&deblocked) < edge_steps(&unfiltered));
+    Ok(())
+}
+
+fn rgb_pixels(bitmap: Bitmap) -> Vec<u8> {
+    match bitmap.data {
+        BitmapFormat::Rgb(rgb) => rgb,
+        BitmapFormat::Rgba(_) => panic!("JPEG without alpha decoded to RGBA"),
+    }
+}
+
+#[test]
+fn deblocking_zero_is_unfiltered() -> Result<(), Error> {
+    let image = std::fs::read("tests/bitmaps/define_bits_jpeg4_deblocking/image.jpg")?;
+    let unfiltered = decode_define_bits_jpeg(&image, None)?;
+    let deblocked = decode_define_bits_jpeg_4(&image, None, 0)?;
+    assert_eq!(
+        Vec::<i32>::from(unfiltered.data),
+        Vec::<i32>::from(deblocked.data)
+    );
+    Ok(())
+}
+
+#[test]
+fn lossless2_colors_clamped_to_alpha() -> Result<(), Error> {
+    // Two ARGB pixels, whose colors are brighter than their alpha, in a zlib
+    // stream of a single uncompressed block.
+    let data = [
+        0x78, 0x01, 0x01, 0x08, 0x00, 0xf7, 0xff, 0x80, 0xff, 0x40, 0x00, 0x00, 0x10, 0x10, 0x10,
+        0x0c, 0xe1, 0x01, 0xf0,
+    ];
+    let bitmap = decode_define_bits_lossless(&swf::DefineBitsLossless {
+        version: 2,
+        id: 1,
+        format: swf::BitmapFormat::Rgb32,
+        width: 2,
+        height: 1,
+        num_colors: 0,
+        data: &data,
+    })?;
+    match bitmap.data {
+        BitmapFormat::Rgba(rgba) => assert_eq!(rgba, [0x80, 0x40, 0x00, 0x80, 0, 0, 0, 0]),
+        BitmapFormat::Rgb(_) => panic!("DefineBitsLossless2 decoded without alpha"),
+    }
+    Ok(())
+}
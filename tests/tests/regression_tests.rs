@@ -3,25 +3,20 @@
 //! Trace output can be compared with correct output from the official Flash Player.
 
 use approx::assert_relative_eq;
-use ruffle_core::backend::{
-    audio::NullAudioBackend,
-    locale::NullLocaleBackend,
-    log::LogBackend,
-    navigator::{NullExecutor, NullNavigatorBackend},
-    render::NullRenderer,
-    storage::{MemoryStorageBackend, StorageBackend},
-    ui::NullUiBackend,
-    video::NullVideoBackend,
-};
+use ruffle_core::backend::render::{swf, Bitmap};
+use ruffle_core::backend::storage::{MemoryStorageBackend, StorageBackend};
+use ruffle_core::backend::ui::{MouseCursor, MovieLifecycleEvent, NullUiBackend, UiBackend};
+use ruffle_core::config::{DeterministicMode, LoopPolicy, StageQuality};
 use ruffle_core::context::UpdateContext;
+use ruffle_core::events::{KeyCode, PlayerEvent};
 use ruffle_core::external::Value as ExternalValue;
 use ruffle_core::external::{ExternalInterfaceMethod, ExternalInterfaceProvider};
+use ruffle_core::site_settings::SiteSettings;
 use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::test_utils::TestPlayer;
 use ruffle_core::Player;
-use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::path::Path;
-use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -90,8 +85,11 @@ swf_tests! {
     (clip_events, "avm1/clip_events", 4),
     (unload_clip_event, "avm1/unload_clip_event", 2),
     (create_empty_movie_clip, "avm1/create_empty_movie_clip", 2),
+    (create_empty_movie_clip_replace_swf6, "avm1/create_empty_movie_clip_replace_swf6", 1),
+    (create_empty_movie_clip_replace_swf8, "avm1/create_empty_movie_clip_replace_swf8", 1),
     (empty_movieclip_can_attach_movies, "avm1/empty_movieclip_can_attach_movies", 1),
     (duplicate_movie_clip, "avm1/duplicate_movie_clip", 1),
+    (duplicate_empty_movie_clip_drawing, "avm1/duplicate_empty_movie_clip_drawing", 1),
     (mouse_listeners, "avm1/mouse_listeners", 1),
     (do_init_action, "avm1/do_init_action", 3),
     (execution_order1, "avm1/execution_order1", 3),
@@ -119,6 +117,8 @@ swf_tests! {
     (goto_rewind3, "avm1/goto_rewind3", 2),
     (goto_execution_order, "avm1/goto_execution_order", 3),
     (goto_execution_order2, "avm1/goto_execution_order2", 2),
+    (goto_scenes_and_labels, "avm1/goto_scenes_and_labels", 1),
+    (graphic_symbol_ratio_sync, "avm1/graphic_symbol_ratio_sync", 4),
     (greaterthan_swf5, "avm1/greaterthan_swf5", 1),
     (greaterthan_swf8, "avm1/greaterthan_swf8", 1),
     (strictly_equals, "avm1/strictly_equals", 1),
@@ -130,6 +130,9 @@ swf_tests! {
     (custom_clip_methods, "avm1/custom_clip_methods", 3),
     (delete, "avm1/delete", 3),
     (selection, "avm1/selection", 1),
+    (selection_focus_changes, "avm1/selection_focus_changes", 1),
+    (focusrect_swf5, "avm1/focusrect_swf5", 1),
+    (focusrect_swf6, "avm1/focusrect_swf6", 1),
     (default_names, "avm1/default_names", 6),
     (array_trivial, "avm1/array_trivial", 1),
     (array_concat, "avm1/array_concat", 1),
@@ -163,6 +166,7 @@ swf_tests! {
     (logical_ops_swf8, "avm1/logical_ops_swf8", 1),
     (movieclip_get_instance_at_depth, "avm1/movieclip_get_instance_at_depth", 1),
     (movieclip_depth_methods, "avm1/movieclip_depth_methods", 3),
+    (get_next_highest_depth_script_window, "avm1/get_next_highest_depth_script_window", 1),
     (get_variable_in_scope, "avm1/get_variable_in_scope", 1),
     (movieclip_init_object, "avm1/movieclip_init_object", 1),
     (greater_swf6, "avm1/greater_swf6", 1),
@@ -223,6 +227,8 @@ swf_tests! {
     (uncaught_exception, "avm1/uncaught_exception", 1),
     (uncaught_exception_bubbled, "avm1/uncaught_exception_bubbled", 1),
     (try_catch_finally, "avm1/try_catch_finally", 1),
+    (try_catch_nested, "avm1/try_catch_nested", 1),
+    (try_finally_halt, "avm1/try_finally_halt", 2),
     (try_finally_simple, "avm1/try_finally_simple", 1),
     (loadmovie, "avm1/loadmovie", 2),
     (loadmovienum, "avm1/loadmovienum", 2),
@@ -288,6 +294,8 @@ swf_tests! {
     (error, "avm1/error", 1),
     (color_transform, "avm1/color_transform", 1),
     (with, "avm1/with", 1),
+    (with_nesting_limit_swf5, "avm1/with_nesting_limit_swf5", 1),
+    (with_nesting_limit_swf6, "avm1/with_nesting_limit_swf6", 1),
     (arguments, "avm1/arguments", 1),
     (prototype_properties, "avm1/prototype_properties", 1),
     (stage_object_properties_get_var, "avm1/stage_object_properties_get_var", 1),
@@ -405,7 +413,15 @@ swf_tests! {
     (target_clip_swf5, "avm1/target_clip_swf5", 2),
     (target_clip_swf6, "avm1/target_clip_swf6", 2),
     (target_path, "avm1/target_path", 1),
+    (target_path_resolution, "avm1/target_path_resolution", 1),
+    (out_of_range_position, "avm1/out_of_range_position", 1),
+    (flash_8_display_properties_swf7, "avm1/flash_8_display_properties_swf7", 1),
+    (flash_8_display_properties_swf8, "avm1/flash_8_display_properties_swf8", 1),
+    (textfield_scroll_clamping, "avm1/textfield_scroll_clamping", 1),
+    (stage_quality, "avm1/stage_quality", 1),
+    (print_job_add_page, "avm1/print_job_add_page", 1),
     (remove_movie_clip, "avm1/remove_movie_clip", 2),
+    (remove_movie_clip_on_enter_frame, "avm1/remove_movie_clip_on_enter_frame", 3),
     (as3_add, "avm2/add", 1),
     (as3_bitor, "avm2/bitor", 1),
     (as3_bitand, "avm2/bitand", 1),
@@ -741,6 +757,1926 @@ fn stage_scale_mode() -> Result<(), Error> {
     )
 }
 
+#[test]
+fn debug_step_frame() -> Result<(), Error> {
+    set_logger();
+    // Frames are advanced entirely through the debugging API, which should
+    // produce the same output as running the movie normally.
+    test_swf_with_hooks(
+        "tests/swfs/avm1/goto_advance1/test.swf",
+        0,
+        "tests/swfs/avm1/goto_advance1/output.txt",
+        |_| Ok(()),
+        |player| {
+            let mut player = player.lock().unwrap();
+            player.pause();
+            assert!(!player.is_playing());
+            player.step_frame();
+            player.step_frame();
+
+            let display_list = player.dump_display_list();
+            assert!(display_list.starts_with("Stage"));
+            assert!(display_list.contains("MovieClip"));
+            assert!(player.dump_avm1_stack().is_empty());
+            Ok(())
+        },
+    )
+}
+
+/// A UI backend that records the lifecycle events of the root movie.
+struct LifecycleRecorder {
+    ui: NullUiBackend,
+    events: Arc<Mutex<Vec<MovieLifecycleEvent>>>,
+}
+
+impl UiBackend for LifecycleRecorder {
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        self.ui.is_key_down(key)
+    }
+
+    fn last_key_code(&self) -> KeyCode {
+        self.ui.last_key_code()
+    }
+
+    fn last_key_char(&self) -> Option<char> {
+        self.ui.last_key_char()
+    }
+
+    fn mouse_visible(&self) -> bool {
+        self.ui.mouse_visible()
+    }
+
+    fn set_mouse_visible(&mut self, visible: bool) {
+        self.ui.set_mouse_visible(visible)
+    }
+
+    fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+        self.ui.set_mouse_cursor(cursor)
+    }
+
+    fn set_clipboard_content(&mut self, content: String) {
+        self.ui.set_clipboard_content(content)
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.ui.is_fullscreen()
+    }
+
+    fn set_frame_anchor(&mut self, anchor: &str) {
+        self.ui.set_frame_anchor(anchor)
+    }
+
+    fn movie_lifecycle_event(&mut self, event: MovieLifecycleEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn display_unsupported_message(&self) {
+        self.ui.display_unsupported_message()
+    }
+
+    fn display_slow_script_prompt(&mut self) -> bool {
+        self.ui.display_slow_script_prompt()
+    }
+
+    fn print(&mut self, pages: Vec<Bitmap>) {
+        self.ui.print(pages)
+    }
+
+    fn message(&self, message: &str) {
+        self.ui.message(message)
+    }
+}
+
+/// A UI backend that records the named anchors that the root timeline
+/// reaches.
+struct AnchorRecorder {
+    ui: NullUiBackend,
+    anchors: Arc<Mutex<Vec<String>>>,
+}
+
+impl UiBackend for AnchorRecorder {
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        self.ui.is_key_down(key)
+    }
+
+    fn last_key_code(&self) -> KeyCode {
+        self.ui.last_key_code()
+    }
+
+    fn last_key_char(&self) -> Option<char> {
+        self.ui.last_key_char()
+    }
+
+    fn mouse_visible(&self) -> bool {
+        self.ui.mouse_visible()
+    }
+
+    fn set_mouse_visible(&mut self, visible: bool) {
+        self.ui.set_mouse_visible(visible)
+    }
+
+    fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+        self.ui.set_mouse_cursor(cursor)
+    }
+
+    fn set_clipboard_content(&mut self, content: String) {
+        self.ui.set_clipboard_content(content)
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.ui.is_fullscreen()
+    }
+
+    fn set_frame_anchor(&mut self, anchor: &str) {
+        self.anchors.lock().unwrap().push(anchor.to_string());
+    }
+
+    fn movie_lifecycle_event(&mut self, event: MovieLifecycleEvent) {
+        self.ui.movie_lifecycle_event(event)
+    }
+
+    fn display_unsupported_message(&self) {
+        self.ui.display_unsupported_message()
+    }
+
+    fn display_slow_script_prompt(&mut self) -> bool {
+        self.ui.display_slow_script_prompt()
+    }
+
+    fn print(&mut self, pages: Vec<Bitmap>) {
+        self.ui.print(pages)
+    }
+
+    fn message(&self, message: &str) {
+        self.ui.message(message)
+    }
+}
+
+/// A UI backend that answers the slow script prompt from a list of answers,
+/// and counts how many times it was asked.
+struct SlowScriptAnswerer {
+    ui: NullUiBackend,
+    answers: Vec<bool>,
+    prompts: Arc<Mutex<usize>>,
+}
+
+impl UiBackend for SlowScriptAnswerer {
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        self.ui.is_key_down(key)
+    }
+
+    fn last_key_code(&self) -> KeyCode {
+        self.ui.last_key_code()
+    }
+
+    fn last_key_char(&self) -> Option<char> {
+        self.ui.last_key_char()
+    }
+
+    fn mouse_visible(&self) -> bool {
+        self.ui.mouse_visible()
+    }
+
+    fn set_mouse_visible(&mut self, visible: bool) {
+        self.ui.set_mouse_visible(visible)
+    }
+
+    fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+        self.ui.set_mouse_cursor(cursor)
+    }
+
+    fn set_clipboard_content(&mut self, content: String) {
+        self.ui.set_clipboard_content(content)
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.ui.is_fullscreen()
+    }
+
+    fn set_frame_anchor(&mut self, anchor: &str) {
+        self.ui.set_frame_anchor(anchor)
+    }
+
+    fn movie_lifecycle_event(&mut self, event: MovieLifecycleEvent) {
+        self.ui.movie_lifecycle_event(event)
+    }
+
+    fn display_unsupported_message(&self) {
+        self.ui.display_unsupported_message()
+    }
+
+    fn display_slow_script_prompt(&mut self) -> bool {
+        let mut prompts = self.prompts.lock().unwrap();
+        let abort = self.answers.get(*prompts).copied().unwrap_or(true);
+        *prompts += 1;
+        abort
+    }
+
+    fn print(&mut self, pages: Vec<Bitmap>) {
+        self.ui.print(pages)
+    }
+
+    fn message(&self, message: &str) {
+        self.ui.message(message)
+    }
+}
+
+/// Builds a movie with one frame per entry of `frame_actions`, each frame
+/// running its bytecode, if any.
+fn movie_with_actions(frame_actions: &[Vec<u8>], frame_rate: f32) -> Result<SwfMovie, Error> {
+    let mut tags = Vec::new();
+    for actions in frame_actions {
+        if !actions.is_empty() {
+            tags.push(swf::Tag::DoAction(actions));
+        }
+        tags.push(swf::Tag::ShowFrame);
+    }
+    movie_from_tags(tags, 8, frame_actions.len() as u16, frame_rate)
+}
+
+/// Builds a movie of the given SWF version out of its tags.
+fn movie_from_tags(
+    tags: Vec<swf::Tag>,
+    version: u8,
+    num_frames: u16,
+    frame_rate: f32,
+) -> Result<SwfMovie, Error> {
+    let movie = swf::Swf {
+        header: swf::Header {
+            compression: swf::Compression::None,
+            version,
+            uncompressed_length: 0,
+            stage_size: swf::Rectangle {
+                x_min: swf::Twips::from_pixels(0.0),
+                x_max: swf::Twips::from_pixels(100.0),
+                y_min: swf::Twips::from_pixels(0.0),
+                y_max: swf::Twips::from_pixels(100.0),
+            },
+            frame_rate,
+            num_frames,
+        },
+        tags,
+    };
+    let mut data = Vec::new();
+    swf::write_swf(&movie, &mut data)?;
+    Ok(SwfMovie::from_data(&data, None, None)?)
+}
+
+/// Builds an ActionScript 3 movie of one frame, whose script traces the value
+/// that each of `cases` leaves on the stack. `trace` is added to the end of
+/// `constant_pool`, which the cases take their constants from.
+fn avm2_trace_movie(
+    mut constant_pool: swf::avm2::types::ConstantPool,
+    cases: &[Vec<swf::avm2::types::Op>],
+) -> Result<SwfMovie, Error> {
+    use swf::avm2::types::{Index, Multiname, Namespace, Op};
+
+    constant_pool.strings.push("".to_string());
+    constant_pool.strings.push("trace".to_string());
+    let num_strings = constant_pool.strings.len() as u32;
+    constant_pool
+        .namespaces
+        .push(Namespace::Package(Index::new(num_strings - 1)));
+    constant_pool.multinames.push(Multiname::QName {
+        namespace: Index::new(constant_pool.namespaces.len() as u32),
+        name: Index::new(num_strings),
+    });
+    let trace = Index::new(constant_pool.multinames.len() as u32);
+
+    let mut code = Vec::new();
+    let mut writer = swf::avm2::write::Writer::new(&mut code);
+    writer.write_op(&Op::GetLocal { index: 0 })?;
+    writer.write_op(&Op::PushScope)?;
+    for case in cases {
+        writer.write_op(&Op::FindPropStrict {
+            index: trace.clone(),
+        })?;
+        for op in case {
+            writer.write_op(op)?;
+        }
+        writer.write_op(&Op::CallPropVoid {
+            index: trace.clone(),
+            num_args: 1,
+        })?;
+    }
+    writer.write_op(&Op::ReturnVoid)?;
+
+    avm2_script_movie(constant_pool, code, Vec::new())
+}
+
+/// Builds an ActionScript 3 movie of one frame, whose script runs `code`
+/// with the given exception handlers.
+fn avm2_script_movie(
+    constant_pool: swf::avm2::types::ConstantPool,
+    code: Vec<u8>,
+    exceptions: Vec<swf::avm2::types::Exception>,
+) -> Result<SwfMovie, Error> {
+    use swf::avm2::types::{AbcFile, Index, Method, MethodBody, Script};
+
+    let abc_file = AbcFile {
+        major_version: 46,
+        minor_version: 16,
+        constant_pool,
+        methods: vec![Method {
+            name: Index::new(0),
+            params: Vec::new(),
+            return_type: Index::new(0),
+            needs_arguments_object: false,
+            needs_activation: false,
+            needs_rest: false,
+            needs_dxns: false,
+        }],
+        metadata: Vec::new(),
+        instances: Vec::new(),
+        classes: Vec::new(),
+        scripts: vec![Script {
+            init_method: Index::new(0),
+            traits: Vec::new(),
+        }],
+        method_bodies: vec![MethodBody {
+            method: Index::new(0),
+            max_stack: 8,
+            num_locals: 4,
+            init_scope_depth: 0,
+            max_scope_depth: 1,
+            code,
+            exceptions,
+            traits: Vec::new(),
+        }],
+    };
+    avm2_abc_movie(abc_file)
+}
+
+/// Builds an AVM2 movie that runs the scripts of an ABC file.
+fn avm2_abc_movie(abc_file: swf::avm2::types::AbcFile) -> Result<SwfMovie, Error> {
+    let mut abc = Vec::new();
+    swf::avm2::write::Writer::new(&mut abc).write(abc_file)?;
+
+    let tags = vec![
+        swf::Tag::FileAttributes(swf::FileAttributes {
+            use_direct_blit: false,
+            use_gpu: false,
+            has_metadata: false,
+            is_action_script_3: true,
+            use_network_sandbox: false,
+        }),
+        swf::Tag::DoAbc(swf::DoAbc {
+            name: swf::SwfStr::from_utf8_str(""),
+            is_lazy_initialize: false,
+            data: &abc,
+        }),
+        swf::Tag::ShowFrame,
+    ];
+    movie_from_tags(tags, 10, 1, 24.0)
+}
+
+/// Encodes AVM2 instructions onto the end of `code`, returning the offset
+/// that they end at.
+fn avm2_ops(code: &mut Vec<u8>, ops: &[swf::avm2::types::Op]) -> Result<u32, Error> {
+    let mut writer = swf::avm2::write::Writer::new(&mut *code);
+    for op in ops {
+        writer.write_op(op)?;
+    }
+    Ok(code.len() as u32)
+}
+
+/// Encodes AVM1 actions, followed by the end of the action list.
+fn avm1_actions(actions: &[swf::avm1::types::Action]) -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+    let mut writer = swf::avm1::write::Writer::new(&mut data, 8);
+    for action in actions {
+        writer.write_action(action)?;
+    }
+    data.push(0);
+    Ok(data)
+}
+
+/// Runs a movie of `num_frames` empty frames for twice as many frames, and
+/// returns how many times it was reported to have ended. The last frame runs
+/// `last_frame_actions`.
+fn count_ended_events(
+    num_frames: u16,
+    last_frame_actions: &[u8],
+    loop_policy: LoopPolicy,
+) -> Result<usize, Error> {
+    let mut frame_actions: Vec<_> = (1..num_frames).map(|_| Vec::new()).collect();
+    frame_actions.push(last_frame_actions.to_vec());
+    let movie = movie_with_actions(&frame_actions, 24.0)?;
+
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let events = Arc::new(Mutex::new(Vec::new()));
+    {
+        let player = player.player();
+        let mut player = player.lock().unwrap();
+        player.set_loop_policy(loop_policy);
+        *player.ui_mut() = Box::new(LifecycleRecorder {
+            ui: NullUiBackend::new(),
+            events: events.clone(),
+        });
+    }
+    player.run_frames(u32::from(num_frames) * 2)?;
+
+    let events = events.lock().unwrap();
+    Ok(events
+        .iter()
+        .filter(|&&event| event == MovieLifecycleEvent::Ended)
+        .count())
+}
+
+#[test]
+fn named_anchors_are_reported_and_navigated_to() -> Result<(), Error> {
+    use swf::SwfStr;
+
+    // The `Stop` action, then the end of the action list.
+    const STOP: &[u8] = &[0x07, 0x00];
+
+    let label = |label, is_anchor| {
+        swf::Tag::FrameLabel(swf::FrameLabel {
+            label: SwfStr::from_utf8_str(label),
+            is_anchor,
+        })
+    };
+    let tags = vec![
+        label("Start", true),
+        swf::Tag::DoAction(STOP),
+        swf::Tag::ShowFrame,
+        label("plain", false),
+        swf::Tag::ShowFrame,
+        label("Chapter", true),
+        swf::Tag::ShowFrame,
+    ];
+    let movie = movie_from_tags(tags, 8, 3, 24.0)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let anchors = Arc::new(Mutex::new(Vec::new()));
+    *player.player().lock().unwrap().ui_mut() = Box::new(AnchorRecorder {
+        ui: NullUiBackend::new(),
+        anchors: anchors.clone(),
+    });
+    player.run_frames(1)?;
+    assert_eq!(*anchors.lock().unwrap(), ["Start"]);
+
+    let player = player.player();
+    let mut player = player.lock().unwrap();
+
+    // Only anchors can be navigated to, and not other frame labels.
+    assert!(!player.goto_anchor("plain"));
+    assert!(!player.goto_anchor("missing"));
+    assert_eq!(player.current_frame(), Some(1));
+
+    // Anchors are matched without regard to case, and are reported with the
+    // case they were authored in.
+    assert!(player.goto_anchor("CHAPTER"));
+    assert_eq!(player.current_frame(), Some(3));
+    assert_eq!(*anchors.lock().unwrap(), ["Start", "Chapter"]);
+    Ok(())
+}
+
+#[test]
+fn movie_ends_once_on_its_last_frame() -> Result<(), Error> {
+    // The `Stop` action, then the end of the action list.
+    const STOP: &[u8] = &[0x07, 0x00];
+
+    assert_eq!(count_ended_events(3, &[0x00], LoopPolicy::Once)?, 1);
+    assert_eq!(count_ended_events(3, &[0x00], LoopPolicy::Loop)?, 0);
+    assert_eq!(count_ended_events(1, &[0x00], LoopPolicy::Once)?, 0);
+    assert_eq!(count_ended_events(3, STOP, LoopPolicy::Once)?, 0);
+    Ok(())
+}
+
+#[test]
+fn deterministic_mode_pins_dates_and_timers() -> Result<(), Error> {
+    use swf::avm1::types::{Action, Value};
+    use swf::SwfStr;
+
+    let s = SwfStr::from_utf8_str;
+    let trace =
+        |message| avm1_actions(&[Action::Push(vec![Value::Str(s(message))]), Action::Trace]);
+    let timer = trace("timer")?;
+    let mut first_frame = avm1_actions(&[
+        // trace(new Date().getHours());
+        Action::Push(vec![Value::Int(0), Value::Int(0), Value::Str(s("Date"))]),
+        Action::NewObject,
+        Action::Push(vec![Value::Str(s("getHours"))]),
+        Action::CallMethod,
+        Action::Trace,
+        // setInterval(function() { trace("timer"); }, 90);
+        Action::Push(vec![Value::Int(90)]),
+        Action::DefineFunction {
+            name: s(""),
+            params: vec![],
+            actions: &timer,
+        },
+        Action::Push(vec![Value::Int(2), Value::Str(s("setInterval"))]),
+        Action::CallFunction,
+        Action::Pop,
+    ])?;
+    // Drop the end of the action list before appending the next trace.
+    first_frame.pop();
+    first_frame.extend(trace("frame 1")?);
+    let movie = movie_with_actions(&[first_frame, trace("frame 2")?, trace("frame 3")?], 10.0)?;
+
+    let player = TestPlayer::from_movie(movie, Path::new(""))?;
+    {
+        let player = player.player();
+        let mut player = player.lock().unwrap();
+        player.set_deterministic_mode(Some(DeterministicMode::default()));
+        player.set_is_playing(true);
+        player.tick(300.0);
+    }
+
+    // The local time zone is UTC rather than that of the locale backend, and
+    // the interval fires after every frame, as the clock only advances with
+    // frames.
+    assert_eq!(
+        player.trace_output(),
+        "0\nframe 1\ntimer\nframe 2\ntimer\nframe 3\ntimer"
+    );
+    Ok(())
+}
+
+#[test]
+fn site_settings_keep_the_quality_set_by_the_movie() -> Result<(), Error> {
+    use swf::avm1::types::{Action, Value};
+    use swf::SwfStr;
+
+    let s = SwfStr::from_utf8_str;
+    let trace_quality = avm1_actions(&[
+        Action::Push(vec![Value::Str(s("_quality"))]),
+        Action::GetVariable,
+        Action::Trace,
+    ])?;
+    let set_low_quality = avm1_actions(&[
+        Action::Push(vec![Value::Str(s("_quality")), Value::Str(s("low"))]),
+        Action::SetVariable,
+    ])?;
+    let movie = movie_with_actions(
+        &[set_low_quality, trace_quality.clone(), trace_quality],
+        24.0,
+    )?;
+
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    // Changing another setting doesn't reset the quality...
+    {
+        let player = player.player();
+        let mut player = player.lock().unwrap();
+        let settings = SiteSettings {
+            volume: 0.5,
+            ..player.site_settings().clone()
+        };
+        player.set_site_settings(settings);
+    }
+    player.run_frames(1)?;
+
+    // ...but picking another quality does.
+    {
+        let player = player.player();
+        let mut player = player.lock().unwrap();
+        let settings = SiteSettings {
+            quality: StageQuality::Best,
+            ..player.site_settings().clone()
+        };
+        player.set_site_settings(settings);
+    }
+    player.run_frames(1)?;
+
+    assert_eq!(player.trace_output(), "LOW\nBEST");
+    Ok(())
+}
+
+#[test]
+fn set_property_ignores_values_that_throw() -> Result<(), Error> {
+    use swf::avm1::types::{Action, Value};
+    use swf::SwfStr;
+
+    let s = SwfStr::from_utf8_str;
+    let throw_error = avm1_actions(&[Action::Push(vec![Value::Str(s("error"))]), Action::Throw])?;
+    // setProperty("", _x, { valueOf: function() { throw "error"; } });
+    let set_x = avm1_actions(&[
+        Action::Push(vec![
+            Value::Str(s("")),
+            Value::Int(0),
+            Value::Str(s("valueOf")),
+        ]),
+        Action::DefineFunction {
+            name: s(""),
+            params: vec![],
+            actions: &throw_error,
+        },
+        Action::Push(vec![Value::Int(1)]),
+        Action::InitObject,
+        Action::SetProperty,
+        Action::Push(vec![Value::Str(s("continued"))]),
+        Action::Trace,
+        Action::Push(vec![Value::Str(s("")), Value::Int(0)]),
+        Action::GetProperty,
+        Action::Trace,
+    ])?;
+    let movie = movie_with_actions(&[set_x], 24.0)?;
+
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    assert_eq!(player.trace_output(), "continued\n0");
+    Ok(())
+}
+
+#[test]
+fn diagnostics_report_stage3d_requests() -> Result<(), Error> {
+    let movie = movie_with_actions(&[vec![]], 24.0)?;
+    let player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let player = player.player();
+    let mut player = player.lock().unwrap();
+    assert!(player.diagnostics().is_empty());
+
+    player.update(|context| context.stage.set_stage3d_requested(context.gc_context));
+    assert_eq!(
+        player.diagnostics(),
+        vec!["Stage3D was requested, but is not supported".to_string()]
+    );
+    Ok(())
+}
+
+#[test]
+fn capture_frame_needs_a_capturing_renderer() -> Result<(), Error> {
+    let movie = movie_with_actions(&[vec![]], 24.0)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+    assert!(player.player().lock().unwrap().capture_frame().is_none());
+    Ok(())
+}
+
+#[test]
+fn slow_scripts_are_aborted_when_the_user_asks() -> Result<(), Error> {
+    use swf::avm1::types::{Action, Value};
+    use swf::SwfStr;
+
+    let trace = |message| {
+        avm1_actions(&[
+            Action::Push(vec![Value::Str(SwfStr::from_utf8_str(message))]),
+            Action::Trace,
+        ])
+    };
+    let mut endless_loop = trace("start")?;
+    endless_loop.pop();
+    endless_loop.extend(avm1_actions(&[Action::Jump { offset: -5 }])?);
+    let movie = movie_with_actions(&[endless_loop, trace("next")?], 24.0)?;
+
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let prompts = Arc::new(Mutex::new(0));
+    {
+        let player = player.player();
+        let mut player = player.lock().unwrap();
+        player.set_max_actions_per_update(Some(100));
+        *player.ui_mut() = Box::new(SlowScriptAnswerer {
+            ui: NullUiBackend::new(),
+            answers: vec![false, true],
+            prompts: prompts.clone(),
+        });
+    }
+
+    // The script keeps running after the first prompt, and every script stops
+    // once it's aborted.
+    player.run_frames(2)?;
+    assert_eq!(*prompts.lock().unwrap(), 2);
+    assert_eq!(player.trace_output(), "start");
+    Ok(())
+}
+
+#[test]
+fn frontend_reads_and_sets_variables() -> Result<(), Error> {
+    use swf::avm1::types::{Action, Value};
+    use swf::SwfStr;
+
+    let s = SwfStr::from_utf8_str;
+    let set_score = avm1_actions(&[
+        Action::Push(vec![Value::Str(s("score")), Value::Int(5)]),
+        Action::SetVariable,
+    ])?;
+    let trace_name = avm1_actions(&[
+        Action::Push(vec![Value::Str(s("name"))]),
+        Action::GetVariable,
+        Action::Trace,
+    ])?;
+    let movie = movie_with_actions(&[set_score, trace_name], 24.0)?;
+
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+    {
+        let player = player.player();
+        let mut player = player.lock().unwrap();
+        assert_eq!(player.get_variable("score"), Some("5".to_string()));
+        assert_eq!(player.get_variable("_root.score"), Some("5".to_string()));
+        assert_eq!(player.get_variable("/:score"), Some("5".to_string()));
+        assert_eq!(player.get_variable("missing"), None);
+
+        // Values are always set as strings.
+        player.set_variable("name", "Ruffle");
+        player.set_variable("_root.score", "6");
+        assert_eq!(player.get_variable("score"), Some("6".to_string()));
+    }
+    player.run_frames(1)?;
+
+    assert_eq!(player.trace_output(), "Ruffle");
+    Ok(())
+}
+
+#[test]
+fn clicking_a_link_in_a_text_field_follows_it() -> Result<(), Error> {
+    use swf::avm1::types::{Action, Value};
+    use swf::SwfStr;
+
+    let s = SwfStr::from_utf8_str;
+    let create_text_field = avm1_actions(&[
+        Action::Push(vec![
+            Value::Int(100),
+            Value::Int(100),
+            Value::Int(0),
+            Value::Int(0),
+            Value::Int(1),
+            Value::Str(s("t")),
+            Value::Int(6),
+            Value::Str(s("_root")),
+        ]),
+        Action::GetVariable,
+        Action::Push(vec![Value::Str(s("createTextField"))]),
+        Action::CallMethod,
+        Action::Pop,
+        Action::Push(vec![Value::Str(s("t"))]),
+        Action::GetVariable,
+        Action::Push(vec![Value::Str(s("html")), Value::Bool(true)]),
+        Action::SetMember,
+        Action::Push(vec![Value::Str(s("t"))]),
+        Action::GetVariable,
+        Action::Push(vec![
+            Value::Str(s("htmlText")),
+            Value::Str(s("<a href=\"ASFUNCTION:trace,clicked, twice\">link</a>")),
+        ]),
+        Action::SetMember,
+    ])?;
+    let movie = movie_with_actions(&[create_text_field], 24.0)?;
+
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player
+        .player()
+        .lock()
+        .unwrap()
+        .set_viewport_dimensions(100, 100, 1.0);
+    player.run_frames(1)?;
+
+    let click = |x, y| {
+        let player = player.player();
+        let mut player = player.lock().unwrap();
+        player.handle_event(PlayerEvent::MouseMove { x, y });
+        player.handle_event(PlayerEvent::MouseDown { x, y });
+        player.handle_event(PlayerEvent::MouseUp { x, y });
+    };
+
+    // Clicking below the text doesn't find a link.
+    click(6.0, 60.0);
+    assert_eq!(player.trace_output(), "");
+
+    // The function is passed everything after the first comma.
+    click(6.0, 8.0);
+    assert_eq!(player.trace_output(), "clicked, twice");
+    Ok(())
+}
+
+/// Builds a movie of one frame that defines a square shape of the given size
+/// and traces a message.
+fn hot_reload_movie(size: f64, message: &str) -> Result<SwfMovie, Error> {
+    use swf::avm1::types::{Action, Value};
+
+    let bounds = swf::Rectangle {
+        x_min: swf::Twips::from_pixels(0.0),
+        x_max: swf::Twips::from_pixels(size),
+        y_min: swf::Twips::from_pixels(0.0),
+        y_max: swf::Twips::from_pixels(size),
+    };
+    let shape = swf::Shape {
+        version: 1,
+        id: 1,
+        shape_bounds: bounds.clone(),
+        edge_bounds: bounds,
+        has_fill_winding_rule: false,
+        has_non_scaling_strokes: false,
+        has_scaling_strokes: true,
+        styles: swf::ShapeStyles {
+            fill_styles: Vec::new(),
+            line_styles: Vec::new(),
+        },
+        shape: Vec::new(),
+    };
+    let actions = avm1_actions(&[
+        Action::Push(vec![Value::Str(swf::SwfStr::from_utf8_str(message))]),
+        Action::Trace,
+    ])?;
+    let tags = vec![
+        swf::Tag::DefineShape(shape),
+        swf::Tag::DoAction(&actions),
+        swf::Tag::ShowFrame,
+    ];
+    movie_from_tags(tags, 8, 1, 24.0)
+}
+
+#[test]
+fn hot_reload_only_replaces_assets() -> Result<(), Error> {
+    let mut player = TestPlayer::from_movie(hot_reload_movie(10.0, "a")?, Path::new(""))?;
+    player.run_frames(1)?;
+    let player = player.player();
+    let mut player = player.lock().unwrap();
+
+    // A changed shape is swapped in, and so is an unchanged movie.
+    assert!(player.reload_root_movie_assets(&hot_reload_movie(20.0, "a")?));
+    assert!(player.reload_root_movie_assets(&hot_reload_movie(10.0, "a")?));
+
+    // Changed scripts need a restart.
+    assert!(!player.reload_root_movie_assets(&hot_reload_movie(10.0, "b")?));
+    Ok(())
+}
+
+#[test]
+fn avm2_numeric_opcodes() -> Result<(), Error> {
+    use swf::avm2::types::{ConstantPool, Index, Op};
+
+    let constant_pool = ConstantPool {
+        ints: vec![i32::MAX, i32::MIN, 0x10000],
+        uints: vec![u32::MAX],
+        doubles: vec![-0.0, -1.0, 4294967301.0, -2.5],
+        strings: Vec::new(),
+        namespaces: Vec::new(),
+        namespace_sets: Vec::new(),
+        multinames: Vec::new(),
+    };
+    let (max, min, big) = (Index::new(1), Index::new(2), Index::new(3));
+    let max_uint = Index::new(1);
+    let (negative_zero, minus_one, wrapped_five, minus_two_and_a_half) =
+        (Index::new(1), Index::new(2), Index::new(3), Index::new(4));
+
+    let cases = vec![
+        // Integer arithmetic wraps around.
+        vec![Op::PushInt { value: max }, Op::IncrementI],
+        vec![Op::PushInt { value: min }, Op::DecrementI],
+        vec![
+            Op::PushInt { value: max },
+            Op::PushByte { value: 1 },
+            Op::AddI,
+        ],
+        vec![
+            Op::PushInt { value: min },
+            Op::PushByte { value: 1 },
+            Op::SubtractI,
+        ],
+        vec![
+            Op::PushInt { value: big },
+            Op::PushInt { value: big },
+            Op::MultiplyI,
+        ],
+        vec![Op::PushInt { value: min }, Op::NegateI],
+        vec![
+            Op::PushInt { value: max },
+            Op::SetLocal { index: 1 },
+            Op::IncLocalI { index: 1 },
+            Op::GetLocal { index: 1 },
+        ],
+        vec![
+            Op::PushInt { value: min },
+            Op::SetLocal { index: 1 },
+            Op::DecLocalI { index: 1 },
+            Op::GetLocal { index: 1 },
+        ],
+        // `coerce_d` keeps the sign of zero, while `coerce_i` drops it.
+        vec![
+            Op::PushByte { value: 1 },
+            Op::PushDouble {
+                value: negative_zero,
+            },
+            Op::CoerceD,
+            Op::Divide,
+        ],
+        vec![
+            Op::PushByte { value: 1 },
+            Op::PushDouble {
+                value: negative_zero,
+            },
+            Op::CoerceI,
+            Op::Divide,
+        ],
+        // Doubles are truncated and wrapped into 32 bits.
+        vec![Op::PushDouble { value: minus_one }, Op::CoerceU],
+        vec![
+            Op::PushDouble {
+                value: wrapped_five,
+            },
+            Op::CoerceI,
+        ],
+        vec![
+            Op::PushDouble {
+                value: minus_two_and_a_half,
+            },
+            Op::CoerceI,
+        ],
+        vec![
+            Op::PushDouble {
+                value: minus_two_and_a_half,
+            },
+            Op::CoerceU,
+        ],
+        vec![Op::PushUint { value: max_uint }, Op::CoerceI],
+        vec![Op::PushUint { value: max_uint }, Op::CoerceU],
+        vec![Op::PushNaN, Op::CoerceI],
+        vec![Op::PushNaN, Op::CoerceU],
+        vec![Op::PushNaN, Op::CoerceB],
+        vec![Op::PushDouble { value: minus_one }, Op::CoerceB],
+        vec![
+            Op::PushDouble {
+                value: negative_zero,
+            },
+            Op::CoerceB,
+        ],
+        // `coerce_o` only turns `undefined` into `null`.
+        vec![Op::PushUndefined, Op::CoerceO],
+        vec![Op::PushTrue, Op::CoerceO],
+    ];
+    let movie = avm2_trace_movie(constant_pool, &cases)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    let expected = [
+        "-2147483648",
+        "2147483647",
+        "-2147483648",
+        "2147483647",
+        "0",
+        "-2147483648",
+        "-2147483648",
+        "2147483647",
+        "-Infinity",
+        "Infinity",
+        "4294967295",
+        "5",
+        "-2",
+        "4294967294",
+        "-1",
+        "4294967295",
+        "0",
+        "0",
+        "false",
+        "true",
+        "false",
+        "null",
+        "true",
+    ];
+    assert_eq!(player.trace_output(), expected.join("\n"));
+    Ok(())
+}
+
+#[test]
+fn avm2_exceptions_are_caught_by_type() -> Result<(), Error> {
+    use swf::avm2::types::{ConstantPool, Exception, Index, Multiname, Namespace, Op};
+
+    let strings = ["", "trace", "Error", "boom", "caught as Error", "x"];
+    let constant_pool = ConstantPool {
+        ints: Vec::new(),
+        uints: Vec::new(),
+        doubles: Vec::new(),
+        strings: strings.iter().map(|s| s.to_string()).collect(),
+        namespaces: vec![Namespace::Package(Index::new(1))],
+        namespace_sets: Vec::new(),
+        multinames: [2, 3, 6]
+            .iter()
+            .map(|&name| Multiname::QName {
+                namespace: Index::new(1),
+                name: Index::new(name),
+            })
+            .collect(),
+    };
+    let (trace, error, x) = (Index::new(1), Index::new(2), Index::new(3));
+    let (boom, caught_as_error) = (Index::new(4), Index::new(5));
+
+    let bytes = |ops: &[Op]| -> Result<Vec<u8>, Error> {
+        let mut code = Vec::new();
+        avm2_ops(&mut code, ops)?;
+        Ok(code)
+    };
+    // Handlers start with only the caught value on the stack.
+    let trace_caught_value = bytes(&[
+        Op::GetLocal { index: 0 },
+        Op::PushScope,
+        Op::FindPropStrict {
+            index: trace.clone(),
+        },
+        Op::Swap,
+        Op::CallPropVoid {
+            index: trace.clone(),
+            num_args: 1,
+        },
+    ])?;
+    let trace_caught_as_error = bytes(&[
+        Op::Pop,
+        Op::GetLocal { index: 0 },
+        Op::PushScope,
+        Op::FindPropStrict {
+            index: trace.clone(),
+        },
+        Op::PushString {
+            value: caught_as_error,
+        },
+        Op::CallPropVoid {
+            index: trace,
+            num_args: 1,
+        },
+    ])?;
+    let jump_over = |handlers: &[&Vec<u8>]| Op::Jump {
+        offset: handlers.iter().map(|h| h.len() as i32 + 4).sum::<i32>() - 4,
+    };
+    let handler = |from_offset, to_offset, target_offset, type_name| Exception {
+        from_offset,
+        to_offset,
+        target_offset,
+        variable_name: Index::new(0),
+        type_name,
+    };
+    let mut exceptions = Vec::new();
+    let mut code = Vec::new();
+    let start = avm2_ops(&mut code, &[Op::GetLocal { index: 0 }, Op::PushScope])?;
+
+    // A thrown string isn't an `Error`, so it falls through to the untyped
+    // handler.
+    let end = avm2_ops(&mut code, &[Op::PushString { value: boom }, Op::Throw])?;
+    let first = avm2_ops(
+        &mut code,
+        &[jump_over(&[&trace_caught_as_error, &trace_caught_value])],
+    )?;
+    exceptions.push(handler(start, end, first, error.clone()));
+    code.extend_from_slice(&trace_caught_as_error);
+    let second = avm2_ops(&mut code, &[jump_over(&[&trace_caught_value])])?;
+    exceptions.push(handler(start, end, second, Index::new(0)));
+    code.extend_from_slice(&trace_caught_value);
+
+    // Errors raised by the interpreter are caught as `Error`s.
+    let start = code.len() as u32;
+    let end = avm2_ops(
+        &mut code,
+        &[Op::PushNull, Op::GetProperty { index: x }, Op::Pop],
+    )?;
+    let target = avm2_ops(&mut code, &[jump_over(&[&trace_caught_as_error])])?;
+    exceptions.push(handler(start, end, target, error));
+    code.extend_from_slice(&trace_caught_as_error);
+    avm2_ops(&mut code, &[Op::ReturnVoid])?;
+
+    let movie = avm2_script_movie(constant_pool, code, exceptions)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    assert_eq!(player.trace_output(), "boom\ncaught as Error");
+    Ok(())
+}
+
+#[test]
+fn avm2_class_names_and_definitions() -> Result<(), Error> {
+    use swf::avm2::types::{ConstantPool, Index, Multiname, Namespace, Op};
+
+    let strings = [
+        "flash.utils",
+        "getQualifiedClassName",
+        "getDefinitionByName",
+        "describeType",
+        "a",
+        "flash.events::Event",
+        "flash.events.Event",
+    ];
+    let constant_pool = ConstantPool {
+        ints: Vec::new(),
+        uints: Vec::new(),
+        doubles: vec![1.5],
+        strings: strings.iter().map(|s| s.to_string()).collect(),
+        namespaces: vec![Namespace::Package(Index::new(1))],
+        namespace_sets: Vec::new(),
+        multinames: [2, 3, 4]
+            .iter()
+            .map(|&name| Multiname::QName {
+                namespace: Index::new(1),
+                name: Index::new(name),
+            })
+            .collect(),
+    };
+    let (class_name, definition, describe_type) = (Index::new(1), Index::new(2), Index::new(3));
+    let call = |function: &Index<Multiname>, argument: Vec<Op>| {
+        let mut ops = vec![Op::FindPropStrict {
+            index: function.clone(),
+        }];
+        ops.extend(argument);
+        ops.push(Op::CallProperty {
+            index: function.clone(),
+            num_args: 1,
+        });
+        ops
+    };
+
+    let cases = vec![
+        call(&class_name, vec![Op::PushByte { value: 1 }]),
+        call(
+            &class_name,
+            vec![Op::PushDouble {
+                value: Index::new(1),
+            }],
+        ),
+        call(
+            &class_name,
+            vec![Op::PushString {
+                value: Index::new(5),
+            }],
+        ),
+        call(&class_name, vec![Op::PushNull]),
+        // Definitions can be named in either form, and classes are named
+        // after themselves.
+        call(
+            &class_name,
+            call(
+                &definition,
+                vec![Op::PushString {
+                    value: Index::new(6),
+                }],
+            ),
+        ),
+        call(
+            &class_name,
+            call(
+                &definition,
+                vec![Op::PushString {
+                    value: Index::new(7),
+                }],
+            ),
+        ),
+        call(&describe_type, vec![Op::PushNull]),
+    ];
+    let movie = avm2_trace_movie(constant_pool, &cases)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    assert_eq!(
+        player.trace_output(),
+        [
+            "int",
+            "Number",
+            "String",
+            "null",
+            "flash.events::Event",
+            "flash.events::Event",
+            "<type name=\"null\" isDynamic=\"false\" isFinal=\"true\" isStatic=\"false\"/>",
+        ]
+        .join("\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn avm2_namespaces_are_constructed() -> Result<(), Error> {
+    use swf::avm2::types::{ConstantPool, Index, Multiname, Namespace, Op};
+
+    let constant_pool = ConstantPool {
+        ints: Vec::new(),
+        uints: Vec::new(),
+        doubles: Vec::new(),
+        strings: [
+            "",
+            "Namespace",
+            "uri",
+            "prefix",
+            "http://a",
+            "p",
+            "http://b",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect(),
+        namespaces: vec![Namespace::Package(Index::new(1))],
+        namespace_sets: Vec::new(),
+        multinames: vec![
+            Multiname::QName {
+                namespace: Index::new(1),
+                name: Index::new(2),
+            },
+            Multiname::QName {
+                namespace: Index::new(1),
+                name: Index::new(3),
+            },
+            Multiname::QName {
+                namespace: Index::new(1),
+                name: Index::new(4),
+            },
+        ],
+        metadata: Vec::new(),
+    };
+    let namespace = Index::new(1);
+    let construct = |args: Vec<Op>, property: u32| {
+        let num_args = args.len() as u32;
+        let mut ops = vec![Op::FindPropStrict {
+            index: namespace.clone(),
+        }];
+        ops.extend(args);
+        ops.push(Op::ConstructProp {
+            index: namespace.clone(),
+            num_args,
+        });
+        ops.push(Op::GetProperty {
+            index: Index::new(property),
+        });
+        ops
+    };
+    let uri = 2;
+    let prefix = 3;
+    let cases = [
+        construct(
+            vec![Op::PushString {
+                value: Index::new(5),
+            }],
+            uri,
+        ),
+        construct(
+            vec![
+                Op::PushString {
+                    value: Index::new(6),
+                },
+                Op::PushString {
+                    value: Index::new(7),
+                },
+            ],
+            uri,
+        ),
+        construct(Vec::new(), uri),
+        // Namespaces are copied from other namespaces.
+        construct(
+            construct(
+                vec![Op::PushString {
+                    value: Index::new(5),
+                }],
+                uri,
+            ),
+            uri,
+        ),
+        construct(
+            vec![Op::PushString {
+                value: Index::new(1),
+            }],
+            prefix,
+        ),
+        construct(
+            vec![Op::PushString {
+                value: Index::new(5),
+            }],
+            prefix,
+        ),
+    ];
+    let movie = avm2_trace_movie(constant_pool, &cases)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    let expected = ["http://a", "http://b", "", "http://a", "", "undefined"];
+    assert_eq!(player.trace_output(), expected.join("\n"));
+    Ok(())
+}
+
+#[test]
+fn avm2_protected_traits_are_inherited() -> Result<(), Error> {
+    use swf::avm2::types::{
+        AbcFile, Class, ConstantPool, DefaultValue, Index, Instance, Method, MethodBody, Multiname,
+        Namespace, Op, Script, Trait, TraitKind,
+    };
+
+    // `B` extends `A`, and reads the protected `x` that `A` declares through
+    // its own protected namespace.
+    let constant_pool = ConstantPool {
+        ints: Vec::new(),
+        uints: Vec::new(),
+        doubles: Vec::new(),
+        strings: ["", "A", "B", "x", "inherited", "Object", "trace"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        namespaces: vec![
+            Namespace::Package(Index::new(1)),
+            Namespace::Protected(Index::new(2)),
+            Namespace::Protected(Index::new(3)),
+        ],
+        namespace_sets: Vec::new(),
+        multinames: [(1, 2), (1, 3), (2, 4), (3, 4), (1, 6), (1, 7)]
+            .iter()
+            .map(|&(namespace, name)| Multiname::QName {
+                namespace: Index::new(namespace),
+                name: Index::new(name),
+            })
+            .collect(),
+        metadata: Vec::new(),
+    };
+    let (a, b, a_x, b_x, object, trace) = (1, 2, 3, 4, 5, 6);
+
+    let mut script_code = Vec::new();
+    avm2_ops(
+        &mut script_code,
+        &[
+            Op::GetLocal { index: 0 },
+            Op::PushScope,
+            Op::FindPropStrict {
+                index: Index::new(trace),
+            },
+            Op::FindPropStrict {
+                index: Index::new(b),
+            },
+            Op::ConstructProp {
+                index: Index::new(b),
+                num_args: 0,
+            },
+            Op::GetProperty {
+                index: Index::new(b_x),
+            },
+            Op::CallPropVoid {
+                index: Index::new(trace),
+                num_args: 1,
+            },
+            Op::ReturnVoid,
+        ],
+    )?;
+    let mut iinit_code = Vec::new();
+    avm2_ops(
+        &mut iinit_code,
+        &[
+            Op::GetLocal { index: 0 },
+            Op::ConstructSuper { num_args: 0 },
+            Op::ReturnVoid,
+        ],
+    )?;
+    let mut cinit_code = Vec::new();
+    avm2_ops(&mut cinit_code, &[Op::ReturnVoid])?;
+
+    let method = Method {
+        name: Index::new(0),
+        params: Vec::new(),
+        return_type: Index::new(0),
+        needs_arguments_object: false,
+        needs_activation: false,
+        needs_rest: false,
+        needs_dxns: false,
+    };
+    let bodies = [
+        script_code,
+        iinit_code.clone(),
+        cinit_code.clone(),
+        iinit_code,
+        cinit_code,
+    ];
+    let instance = |name, super_name, protected_namespace, init_method, traits| Instance {
+        name: Index::new(name),
+        super_name: Index::new(super_name),
+        is_sealed: false,
+        is_final: false,
+        is_interface: false,
+        protected_namespace: Some(Index::new(protected_namespace)),
+        interfaces: Vec::new(),
+        init_method: Index::new(init_method),
+        traits,
+    };
+    let class_trait = |name, class| Trait {
+        name: Index::new(name),
+        kind: TraitKind::Class {
+            slot_id: 0,
+            class: Index::new(class),
+        },
+        metadata: Vec::new(),
+        is_final: false,
+        is_override: false,
+    };
+    let x = Trait {
+        name: Index::new(a_x),
+        kind: TraitKind::Slot {
+            slot_id: 0,
+            type_name: Index::new(0),
+            value: Some(DefaultValue::String(Index::new(5))),
+        },
+        metadata: Vec::new(),
+        is_final: false,
+        is_override: false,
+    };
+
+    let abc_file = AbcFile {
+        major_version: 46,
+        minor_version: 16,
+        constant_pool,
+        methods: vec![method; bodies.len()],
+        metadata: Vec::new(),
+        instances: vec![
+            instance(a, object, 2, 1, vec![x]),
+            instance(b, a, 3, 3, Vec::new()),
+        ],
+        classes: vec![
+            Class {
+                init_method: Index::new(2),
+                traits: Vec::new(),
+            },
+            Class {
+                init_method: Index::new(4),
+                traits: Vec::new(),
+            },
+        ],
+        scripts: vec![Script {
+            init_method: Index::new(0),
+            traits: vec![class_trait(a, 0), class_trait(b, 1)],
+        }],
+        method_bodies: bodies
+            .iter()
+            .enumerate()
+            .map(|(index, code)| MethodBody {
+                method: Index::new(index as u32),
+                max_stack: 8,
+                num_locals: 1,
+                init_scope_depth: 0,
+                max_scope_depth: 1,
+                code: code.clone(),
+                exceptions: Vec::new(),
+                traits: Vec::new(),
+            })
+            .collect(),
+    };
+    let movie = avm2_abc_movie(abc_file)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    assert_eq!(player.trace_output(), "inherited");
+    Ok(())
+}
+
+#[test]
+fn avm2_frame_events_run_in_order() -> Result<(), Error> {
+    use swf::avm2::types::{
+        AbcFile, ConstantPool, Index, Method, MethodBody, MethodParam, Multiname, Namespace, Op,
+        Script,
+    };
+
+    let constant_pool = ConstantPool {
+        ints: Vec::new(),
+        uints: Vec::new(),
+        doubles: Vec::new(),
+        strings: [
+            "",
+            "flash.display",
+            "Sprite",
+            "addEventListener",
+            "enterFrame",
+            "frameConstructed",
+            "exitFrame",
+            "render",
+            "Stage",
+            "prototype",
+            "invalidate",
+            "type",
+            "trace",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect(),
+        namespaces: vec![
+            Namespace::Package(Index::new(1)),
+            Namespace::Package(Index::new(2)),
+        ],
+        namespace_sets: Vec::new(),
+        multinames: [(2, 3), (1, 4), (2, 9), (1, 10), (1, 11), (1, 12), (1, 13)]
+            .iter()
+            .map(|&(namespace, name)| Multiname::QName {
+                namespace: Index::new(namespace),
+                name: Index::new(name),
+            })
+            .collect(),
+        metadata: Vec::new(),
+    };
+    let (sprite, add_event_listener, stage, prototype, invalidate, event_type, trace) =
+        (1, 2, 3, 4, 5, 6, 7);
+
+    // A sprite listens to each event with a handler that traces its type,
+    // and `Stage.invalidate` requests a `render` event.
+    let mut script_code = Vec::new();
+    let mut script_ops = vec![
+        Op::GetLocal { index: 0 },
+        Op::PushScope,
+        Op::FindPropStrict {
+            index: Index::new(sprite),
+        },
+        Op::ConstructProp {
+            index: Index::new(sprite),
+            num_args: 0,
+        },
+        Op::SetLocal { index: 1 },
+    ];
+    for event in 5..=8 {
+        script_ops.extend(vec![
+            Op::GetLocal { index: 1 },
+            Op::PushString {
+                value: Index::new(event),
+            },
+            Op::NewFunction {
+                index: Index::new(1),
+            },
+            Op::CallPropVoid {
+                index: Index::new(add_event_listener),
+                num_args: 2,
+            },
+        ]);
+    }
+    script_ops.extend(vec![
+        Op::GetLex {
+            index: Index::new(stage),
+        },
+        Op::GetProperty {
+            index: Index::new(prototype),
+        },
+        Op::CallPropVoid {
+            index: Index::new(invalidate),
+            num_args: 0,
+        },
+        Op::ReturnVoid,
+    ]);
+    avm2_ops(&mut script_code, &script_ops)?;
+
+    let mut handler_code = Vec::new();
+    avm2_ops(
+        &mut handler_code,
+        &[
+            Op::FindPropStrict {
+                index: Index::new(trace),
+            },
+            Op::GetLocal { index: 1 },
+            Op::GetProperty {
+                index: Index::new(event_type),
+            },
+            Op::CallPropVoid {
+                index: Index::new(trace),
+                num_args: 1,
+            },
+            Op::ReturnVoid,
+        ],
+    )?;
+
+    let method = |params| Method {
+        name: Index::new(0),
+        params,
+        return_type: Index::new(0),
+        needs_arguments_object: false,
+        needs_activation: false,
+        needs_rest: false,
+        needs_dxns: false,
+    };
+    let event_param = MethodParam {
+        name: None,
+        kind: Index::new(0),
+        default_value: None,
+    };
+    let abc_file = AbcFile {
+        major_version: 46,
+        minor_version: 16,
+        constant_pool,
+        methods: vec![method(Vec::new()), method(vec![event_param])],
+        metadata: Vec::new(),
+        instances: Vec::new(),
+        classes: Vec::new(),
+        scripts: vec![Script {
+            init_method: Index::new(0),
+            traits: Vec::new(),
+        }],
+        method_bodies: vec![script_code, handler_code]
+            .into_iter()
+            .enumerate()
+            .map(|(index, code)| MethodBody {
+                method: Index::new(index as u32),
+                max_stack: 8,
+                num_locals: 2,
+                init_scope_depth: 0,
+                max_scope_depth: 1,
+                code,
+                exceptions: Vec::new(),
+                traits: Vec::new(),
+            })
+            .collect(),
+    };
+    let movie = avm2_abc_movie(abc_file)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(2)?;
+
+    // `render` is broadcast once for each call to `invalidate`.
+    let expected = [
+        "enterFrame",
+        "frameConstructed",
+        "exitFrame",
+        "render",
+        "enterFrame",
+        "frameConstructed",
+        "exitFrame",
+    ];
+    assert_eq!(player.trace_output(), expected.join("\n"));
+    Ok(())
+}
+
+#[test]
+fn avm2_dictionary_object_keys() -> Result<(), Error> {
+    use swf::avm2::types::{ConstantPool, Index, Multiname, Namespace, Op};
+
+    let constant_pool = ConstantPool {
+        ints: Vec::new(),
+        uints: Vec::new(),
+        doubles: Vec::new(),
+        strings: [
+            "flash.utils",
+            "Dictionary",
+            "first",
+            "second",
+            "third",
+            "x",
+            "",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect(),
+        namespaces: vec![
+            Namespace::Package(Index::new(1)),
+            Namespace::Package(Index::new(7)),
+        ],
+        namespace_sets: vec![vec![Index::new(2)]],
+        multinames: vec![
+            Multiname::QName {
+                namespace: Index::new(1),
+                name: Index::new(2),
+            },
+            Multiname::MultinameL {
+                namespace_set: Index::new(1),
+            },
+            Multiname::QName {
+                namespace: Index::new(2),
+                name: Index::new(6),
+            },
+        ],
+    };
+    let (dictionary_class, late_name, x) = (Index::new(1), Index::new(2), Index::new(3));
+    let (first, second, third, x_string) =
+        (Index::new(3), Index::new(4), Index::new(5), Index::new(6));
+
+    // Local 1 is the dictionary, and locals 2 and 3 are two objects that
+    // stringify the same way.
+    let cases = vec![
+        vec![
+            Op::GetLex {
+                index: dictionary_class,
+            },
+            Op::Construct { num_args: 0 },
+            Op::SetLocal { index: 1 },
+            Op::NewObject { num_args: 0 },
+            Op::SetLocal { index: 2 },
+            Op::NewObject { num_args: 0 },
+            Op::SetLocal { index: 3 },
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 2 },
+            Op::PushString { value: first },
+            Op::SetProperty {
+                index: late_name.clone(),
+            },
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 3 },
+            Op::PushString { value: second },
+            Op::SetProperty {
+                index: late_name.clone(),
+            },
+            Op::GetLocal { index: 1 },
+            Op::PushString { value: third },
+            Op::SetProperty { index: x },
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 2 },
+            Op::GetProperty {
+                index: late_name.clone(),
+            },
+        ],
+        vec![
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 3 },
+            Op::GetProperty {
+                index: late_name.clone(),
+            },
+        ],
+        vec![Op::GetLocal { index: 2 }, Op::GetLocal { index: 1 }, Op::In],
+        vec![
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 2 },
+            Op::DeleteProperty {
+                index: late_name.clone(),
+            },
+            Op::Pop,
+            Op::GetLocal { index: 2 },
+            Op::GetLocal { index: 1 },
+            Op::In,
+        ],
+        // Object keys are enumerated as themselves, before any dynamic
+        // properties.
+        vec![
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 1 },
+            Op::PushByte { value: 0 },
+            Op::HasNext,
+            Op::NextName,
+            Op::GetLocal { index: 3 },
+            Op::StrictEquals,
+        ],
+        vec![
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 1 },
+            Op::PushByte { value: 0 },
+            Op::HasNext,
+            Op::NextValue,
+        ],
+        vec![
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 1 },
+            Op::GetLocal { index: 1 },
+            Op::PushByte { value: 0 },
+            Op::HasNext,
+            Op::HasNext,
+            Op::NextValue,
+        ],
+        // Primitive keys are ordinary property names.
+        vec![
+            Op::GetLocal { index: 1 },
+            Op::PushString { value: x_string },
+            Op::GetProperty { index: late_name },
+        ],
+    ];
+    let movie = avm2_trace_movie(constant_pool, &cases)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    let expected = [
+        "first", "second", "true", "false", "true", "second", "third", "third",
+    ];
+    assert_eq!(player.trace_output(), expected.join("\n"));
+    Ok(())
+}
+
+#[test]
+fn gotos_and_frame_calls_use_preloaded_frames() -> Result<(), Error> {
+    use swf::avm1::types::{Action, Value};
+    use swf::SwfStr;
+
+    let s = SwfStr::from_utf8_str;
+    let trace_type_of_a = [
+        Action::Push(vec![Value::Str(s("a"))]),
+        Action::GetVariable,
+        Action::TypeOf,
+        Action::Trace,
+    ];
+    let mut first_frame = vec![Action::Stop, Action::GotoFrame(3)];
+    first_frame.extend(trace_type_of_a.iter().cloned());
+    first_frame.push(Action::GotoFrame(5));
+    first_frame.extend(trace_type_of_a.iter().cloned());
+    first_frame.extend(vec![Action::Push(vec![Value::Str(s("3"))]), Action::Call]);
+    let first_frame = avm1_actions(&first_frame)?;
+    let third_frame = avm1_actions(&[Action::Push(vec![Value::Str(s("frame 3"))]), Action::Trace])?;
+
+    // `a` is placed on frame 2 and removed on frame 5, with frames in between
+    // that don't change the display list.
+    let tags = vec![
+        swf::Tag::DefineSprite(swf::Sprite {
+            id: 1,
+            num_frames: 1,
+            tags: vec![swf::Tag::ShowFrame],
+        }),
+        swf::Tag::DoAction(&first_frame),
+        swf::Tag::ShowFrame,
+        swf::Tag::PlaceObject(Box::new(swf::PlaceObject {
+            version: 2,
+            action: swf::PlaceObjectAction::Place(1),
+            depth: 1,
+            matrix: None,
+            color_transform: None,
+            ratio: None,
+            name: Some(s("a")),
+            clip_depth: None,
+            class_name: None,
+            filters: None,
+            background_color: None,
+            blend_mode: None,
+            clip_actions: None,
+            is_image: false,
+            is_bitmap_cached: None,
+            is_visible: None,
+            amf_data: None,
+        })),
+        swf::Tag::ShowFrame,
+        swf::Tag::DoAction(&third_frame),
+        swf::Tag::ShowFrame,
+        swf::Tag::ShowFrame,
+        swf::Tag::RemoveObject(swf::RemoveObject {
+            depth: 1,
+            character_id: None,
+        }),
+        swf::Tag::ShowFrame,
+        swf::Tag::ShowFrame,
+    ];
+    let movie = movie_from_tags(tags, 8, 6, 24.0)?;
+    let mut player = TestPlayer::from_movie(movie, Path::new(""))?;
+    player.run_frames(1)?;
+
+    assert_eq!(player.trace_output(), "movieclip\nundefined\nframe 3");
+    Ok(())
+}
+
+#[test]
+fn frame_rates_are_clamped() -> Result<(), Error> {
+    let movie = movie_with_actions(&[Vec::new()], 0.0)?;
+    let player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let player = player.player();
+    let mut player = player.lock().unwrap();
+    assert_relative_eq!(player.frame_rate(), 0.01);
+
+    player.set_frame_rate(5000.0);
+    assert_relative_eq!(player.frame_rate(), 1000.0);
+    player.set_frame_rate(f64::NAN);
+    assert_relative_eq!(player.frame_rate(), 1000.0);
+    player.set_frame_rate(-1.0);
+    assert_relative_eq!(player.frame_rate(), 0.01);
+    Ok(())
+}
+
+#[test]
+fn late_ticks_keep_their_phase_and_skip_one_render() -> Result<(), Error> {
+    let frame_actions: Vec<_> = (0..20).map(|_| Vec::new()).collect();
+    let movie = movie_with_actions(&frame_actions, 10.0)?;
+    let player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let player = player.player();
+    let mut player = player.lock().unwrap();
+    player.set_is_playing(true);
+
+    // Only five frames are caught up on, and the time into the next frame is
+    // kept.
+    player.tick(1050.0);
+    assert_eq!(player.current_frame(), Some(5));
+    assert_eq!(player.time_til_next_frame(), Duration::from_millis(50));
+    assert!(!player.needs_render());
+
+    // A second late tick in a row is still drawn.
+    player.tick(1050.0);
+    assert_eq!(player.current_frame(), Some(10));
+    assert!(player.needs_render());
+    Ok(())
+}
+
+#[test]
+fn skipped_renders_end_when_frames_stop_running() -> Result<(), Error> {
+    let frame_actions: Vec<_> = (0..20).map(|_| Vec::new()).collect();
+    let movie = movie_with_actions(&frame_actions, 10.0)?;
+    let player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let player = player.player();
+    let mut player = player.lock().unwrap();
+
+    // Pausing after a late tick draws the frame it skipped.
+    player.set_is_playing(true);
+    player.tick(1050.0);
+    assert!(!player.needs_render());
+    player.pause();
+    assert!(player.needs_render());
+
+    // So does stepping a frame.
+    player.resume();
+    player.tick(1050.0);
+    assert!(!player.needs_render());
+    player.step_frame();
+    assert!(player.needs_render());
+
+    // And so does a tick that runs no frames.
+    player.tick(1050.0);
+    assert!(!player.needs_render());
+    player.tick(10.0);
+    assert!(player.needs_render());
+    Ok(())
+}
+
+#[test]
+fn frame_interpolation_follows_the_site_unless_overridden() -> Result<(), Error> {
+    let movie = movie_with_actions(&[Vec::new()], 24.0)?;
+    let player = TestPlayer::from_movie(movie, Path::new(""))?;
+    let player = player.player();
+    let mut player = player.lock().unwrap();
+    assert!(!player.frame_interpolation());
+
+    let settings = SiteSettings {
+        frame_interpolation: true,
+        ..player.site_settings().clone()
+    };
+    player.set_site_settings(settings);
+    assert!(player.frame_interpolation());
+
+    player.set_frame_interpolation(Some(false));
+    assert!(!player.frame_interpolation());
+    player.set_frame_interpolation(None);
+    assert!(player.frame_interpolation());
+    Ok(())
+}
+
 /// Wrapper around string slice that makes debug output `{:?}` to print string same way as `{}`.
 /// Used in different `assert*!` macros in combination with `pretty_assertions` crate to make
 /// test failures to show nice diffs.
@@ -864,58 +2800,17 @@ fn run_swf(
     before_start: impl FnOnce(Arc<Mutex<Player>>) -> Result<(), Error>,
     before_end: impl FnOnce(Arc<Mutex<Player>>) -> Result<(), Error>,
 ) -> Result<String, Error> {
-    let base_path = Path::new(swf_path).parent().unwrap();
-    let (mut executor, channel) = NullExecutor::new();
-    let movie = SwfMovie::from_path(swf_path, None)?;
-    let frame_time = 1000.0 / movie.header().frame_rate as f64;
-    let trace_output = Rc::new(RefCell::new(Vec::new()));
-
-    let player = Player::new(
-        Box::new(NullRenderer),
-        Box::new(NullAudioBackend::new()),
-        Box::new(NullNavigatorBackend::with_base_path(base_path, channel)),
-        Box::new(MemoryStorageBackend::default()),
-        Box::new(NullLocaleBackend::new()),
-        Box::new(NullVideoBackend::new()),
-        Box::new(TestLogBackend::new(trace_output.clone())),
-        Box::new(NullUiBackend::new()),
-    )?;
-    player.lock().unwrap().set_root_movie(Arc::new(movie));
-    player
-        .lock()
-        .unwrap()
-        .set_max_execution_duration(Duration::from_secs(300));
-
-    before_start(player.clone())?;
+    let mut test_player = TestPlayer::from_path(swf_path)?;
 
-    for _ in 0..num_frames {
-        player.lock().unwrap().run_frame();
-        player.lock().unwrap().update_timers(frame_time);
-        executor.poll_all().unwrap();
-    }
+    before_start(test_player.player())?;
 
-    before_end(player)?;
+    test_player.run_frames(num_frames)?;
 
-    executor.block_all().unwrap();
+    before_end(test_player.player())?;
 
-    let trace = trace_output.borrow().join("\n");
-    Ok(trace)
-}
+    test_player.finish_loads()?;
 
-struct TestLogBackend {
-    trace_output: Rc<RefCell<Vec<String>>>,
-}
-
-impl TestLogBackend {
-    pub fn new(trace_output: Rc<RefCell<Vec<String>>>) -> Self {
-        Self { trace_output }
-    }
-}
-
-impl LogBackend for TestLogBackend {
-    fn avm_trace(&self, message: &str) {
-        self.trace_output.borrow_mut().push(message.to_string());
-    }
+    Ok(test_player.trace_output())
 }
 
 #[derive(Default)]
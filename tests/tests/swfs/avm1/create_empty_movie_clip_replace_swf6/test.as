@@ -0,0 +1,14 @@
+// Compiled as SWF6.
+// createEmptyMovieClip at an occupied depth unloads the old clip and
+// replaces it. Before SWF7, the replacing call returns undefined.
+
+_root.createEmptyMovieClip("a", 1);
+_root.a.onUnload = function() {
+	trace("unload " + this._name);
+};
+var r = _root.createEmptyMovieClip("b", 1);
+trace(r);
+trace(_root.a);
+trace(_root.b);
+var r = _root.createEmptyMovieClip("c", 2);
+trace(r);
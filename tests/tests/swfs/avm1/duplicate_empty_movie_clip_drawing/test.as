@@ -0,0 +1,14 @@
+// Clips without a symbol are duplicated along with their drawings.
+_root.createEmptyMovieClip("a", 1);
+a.beginFill(0xff0000);
+a.lineTo(10, 0);
+a.lineTo(10, 20);
+a.lineTo(0, 20);
+a.endFill();
+
+a.duplicateMovieClip("b", 2);
+trace(typeof b);
+trace(a._width);
+trace(a._height);
+trace(b._width);
+trace(b._height);
@@ -0,0 +1,15 @@
+// Before SWF8, the Flash 8 display properties don't exist, and setting them
+// makes ordinary variables.
+_root.createEmptyMovieClip("clip", 1);
+trace(typeof clip.filters);
+trace(typeof clip.blendMode);
+trace(typeof clip.cacheAsBitmap);
+trace(typeof clip.opaqueBackground);
+trace(typeof clip.scale9Grid);
+
+clip.cacheAsBitmap = "yes";
+trace(clip.cacheAsBitmap);
+clip.opaqueBackground = "255";
+trace(typeof clip.opaqueBackground);
+clip.scale9Grid = "grid";
+trace(clip.scale9Grid);
@@ -0,0 +1,19 @@
+_root.createEmptyMovieClip("clip", 1);
+trace(typeof clip.filters);
+trace(typeof clip.blendMode);
+trace(typeof clip.cacheAsBitmap);
+trace(typeof clip.opaqueBackground);
+trace(typeof clip.scale9Grid);
+
+// The setters convert what they're given.
+clip.cacheAsBitmap = "yes";
+trace(clip.cacheAsBitmap);
+clip.opaqueBackground = "255";
+trace(typeof clip.opaqueBackground);
+
+clip.scale9Grid = new flash.geom.Rectangle(10, 20, 30, 40);
+trace(clip.scale9Grid);
+// The getter makes a new rectangle each time.
+trace(clip.scale9Grid == clip.scale9Grid);
+clip.scale9Grid = null;
+trace(clip.scale9Grid);
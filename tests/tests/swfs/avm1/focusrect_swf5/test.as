@@ -0,0 +1,6 @@
+// "clip" is placed on the timeline at depth 1.
+trace(_root._focusrect);
+// Before SWF6, every object shares the stage's setting.
+clip._focusrect = false;
+trace(clip._focusrect);
+trace(_root._focusrect);
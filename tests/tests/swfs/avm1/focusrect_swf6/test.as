@@ -0,0 +1,11 @@
+// "clip" is placed on the timeline at depth 1.
+trace(_root._focusrect);
+// Objects have their own setting, which is unset at first.
+trace(clip._focusrect);
+clip._focusrect = false;
+trace(clip._focusrect);
+trace(_root._focusrect);
+
+// The root of a level sets the stage's setting.
+_root._focusrect = false;
+trace(_root._focusrect);
@@ -0,0 +1,13 @@
+// "timeline" is placed on the timeline at depth 1, which is negative in
+// ActionScript.
+trace(_root.getNextHighestDepth());
+
+_root.createEmptyMovieClip("attached", 10);
+trace(_root.getNextHighestDepth());
+
+// Clips past the highest depth that scripts can attach to are ignored,
+// but the highest depth itself still counts.
+_root.createEmptyMovieClip("above", 2130690045);
+trace(_root.getNextHighestDepth());
+_root.createEmptyMovieClip("highest", 2130690044);
+trace(_root.getNextHighestDepth());
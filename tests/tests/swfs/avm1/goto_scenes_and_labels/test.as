@@ -0,0 +1,30 @@
+// Scene "a" is frames 1 to 3, and scene "b" is frames 4 to 6.
+// Frames 2 and 5 are labelled "x", and frame 6 is labelled "only_b".
+if (!ran) {
+    ran = true;
+    _root.gotoAndStop("b", 2);
+    trace(_root._currentframe);
+    _root.gotoAndStop("a", "x");
+    trace(_root._currentframe);
+    _root.gotoAndStop("b", "X");
+    trace(_root._currentframe);
+    // Labels without a scene are looked up in the current scene first.
+    _root.gotoAndStop("b", 1);
+    trace(_root._currentframe);
+    _root.gotoAndStop("x");
+    trace(_root._currentframe);
+    _root.gotoAndStop("a", 1);
+    trace(_root._currentframe);
+    _root.gotoAndStop("x");
+    trace(_root._currentframe);
+    _root.gotoAndStop("ONLY_B");
+    trace(_root._currentframe);
+    // A label outside of the given scene doesn't move the playhead.
+    _root.gotoAndStop("a", "only_b");
+    trace(_root._currentframe);
+    // If the first argument doesn't name a scene, it is the frame.
+    _root.gotoAndStop(3, "b");
+    trace(_root._currentframe);
+    _root.gotoAndStop("only_b", 1);
+    trace(_root._currentframe);
+}
@@ -0,0 +1,16 @@
+// A four-frame sprite, "g", is placed on frame 1 with a ratio of 2, the way
+// exporters place graphic symbols that start partway into their timeline.
+// It then plays along with the root until the root rewinds.
+
+// Frame 1
+trace(g._currentframe);
+
+// Frame 2 has no script, so the rewind below doesn't run one.
+
+// Frame 3
+trace(g._currentframe);
+
+// Frame 4
+trace(g._currentframe);
+gotoAndStop(2);
+trace(g._currentframe);
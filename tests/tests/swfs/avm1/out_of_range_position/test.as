@@ -0,0 +1,12 @@
+_root.createEmptyMovieClip("clip", 1);
+
+// Positions out of the range of twips wrap around to the minimum.
+clip._x = 1e20;
+trace(clip._x);
+clip._x = NaN;
+trace(clip._x);
+
+// Huge scales don't break the bounds of the clip.
+clip._xscale = 1e300;
+clip._rotation = 45;
+trace(clip._width);
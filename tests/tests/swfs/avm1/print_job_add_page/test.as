@@ -0,0 +1,10 @@
+_root.createEmptyMovieClip("page", 1);
+var job = new PrintJob();
+var area = {xMin: 0, yMin: 0, xMax: 10, yMax: 10};
+
+// Pages can't be added before the job starts.
+trace(job.addPage(page, area));
+trace(job.start());
+
+// The test renderer can't capture frames, so no page is printed.
+trace(job.addPage(page, area));
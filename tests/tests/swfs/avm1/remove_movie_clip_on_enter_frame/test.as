@@ -0,0 +1,42 @@
+// Clips that remove themselves, their siblings or their parent from within
+// onEnterFrame must not have their handlers run once they are gone.
+
+class Main {
+	static function main() {
+		_root.stop();
+		_root.createEmptyMovieClip("a", 1);
+		_root.createEmptyMovieClip("b", 2);
+		_root.createEmptyMovieClip("c", 3);
+		_root.createEmptyMovieClip("p", 4);
+		_root.p.createEmptyMovieClip("q", 1);
+
+		_root.a.onEnterFrame = function() {
+			trace(this._name);
+			this.removeMovieClip();
+		};
+		_root.b.onEnterFrame = function() {
+			trace(this._name);
+			this.removeMovieClip();
+		};
+		_root.c.onEnterFrame = function() {
+			trace(this._name);
+			_root.a.removeMovieClip();
+			this.removeMovieClip();
+		};
+		_root.p.onEnterFrame = function() {
+			trace(this._name);
+		};
+		_root.p.q.onEnterFrame = function() {
+			trace(this._name);
+			this._parent.removeMovieClip();
+		};
+
+		_root.onEnterFrame = function() {
+			trace(this.a);
+			trace(this.b);
+			trace(this.c);
+			trace(this.p);
+			delete this.onEnterFrame;
+		};
+	}
+}
@@ -0,0 +1,29 @@
+// Remember the other object of a focus change.
+function remember_other(other) {
+    this.other = other;
+}
+
+_root.createTextField("a", 0, 0, 0, 100, 20);
+a.onSetFocus = remember_other;
+a.onKillFocus = remember_other;
+_root.createTextField("b", 1, 0, 0, 100, 20);
+b.onSetFocus = remember_other;
+b.onKillFocus = remember_other;
+a.text = "hello";
+
+// Objects can be focused by their path, and the focus is given back as a path.
+trace(Selection.setFocus("a"));
+trace(a.other);
+trace(Selection.getFocus());
+// Focusing a text field selects all of its text.
+trace(Selection.getEndIndex());
+
+// Moving the focus tells each object about the other.
+Selection.setFocus(b);
+trace(b.other == a);
+trace(a.other == b);
+trace(Selection.getFocus());
+
+trace(Selection.setFocus(null));
+trace(b.other);
+trace(Selection.getFocus());
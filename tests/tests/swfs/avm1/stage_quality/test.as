@@ -0,0 +1,31 @@
+trace(_quality);
+trace(_highquality);
+
+// _quality ignores case and unknown qualities.
+_quality = "medium";
+trace(_quality);
+trace(_highquality);
+_quality = "bogus";
+trace(_quality);
+trace(_highquality);
+_quality = "Best";
+trace(_quality);
+trace(_highquality);
+
+_highquality = 2;
+trace(_quality);
+_highquality = 1;
+trace(_quality);
+_highquality = 0;
+trace(_quality);
+_highquality = 5;
+trace(_quality);
+
+// toggleHighQuality switches between low and high quality.
+toggleHighQuality();
+trace(_quality);
+toggleHighQuality();
+trace(_quality);
+_quality = "best";
+toggleHighQuality();
+trace(_quality);
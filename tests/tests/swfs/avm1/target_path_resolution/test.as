@@ -0,0 +1,44 @@
+_root.createEmptyMovieClip("clip", 1);
+clip.createEmptyMovieClip("sub", 1);
+
+// Target paths mix slashes, dots and colons, and may start with a level.
+var paths = [
+    "/clip/sub",
+    "/clip/sub/",
+    "clip.sub",
+    "clip:sub",
+    "/clip.sub",
+    "clip/sub/..",
+    "clip.sub.../sub",
+    "_level0/clip",
+    "_level0.clip:sub",
+    "_level2/clip",
+    "/",
+    "/missing/sub",
+];
+for (var i = 0; i < paths.length; i++) {
+    trace(paths[i]);
+    trace(getProperty(paths[i], _name));
+}
+
+// Variables can be set and read through the same paths.
+set("/clip/sub:a", 1);
+set("clip.sub.b", 2);
+set("_level0/clip/sub:c", 3);
+set("clip/sub/../:d", 4);
+trace(clip.sub.a);
+trace(clip.sub.b);
+trace(clip.sub.c);
+trace(clip.d);
+
+var variables = [
+    "/clip/sub:a",
+    "_level0.clip.sub.b",
+    "clip.sub..:d",
+    "/:clip/sub:c",
+    "/missing:a",
+];
+for (var i = 0; i < variables.length; i++) {
+    trace(variables[i]);
+    trace(eval(variables[i]));
+}
@@ -0,0 +1,37 @@
+_root.createTextField("t", 1, 0, 0, 100, 44);
+t.multiline = true;
+t.text = "1\r2\r3\r4\r5\r6\r7\r8\r9\r10";
+
+// Only some of the lines fit.
+trace(t.scroll);
+var maxscroll = t.maxscroll;
+trace(maxscroll > 1);
+trace(maxscroll < 10);
+trace(t.bottomScroll < 10);
+
+// Scrolling is clamped to the lines that exist.
+t.scroll = 100;
+trace(t.scroll == maxscroll);
+t.scroll = -3;
+trace(t.scroll);
+t.scroll = NaN;
+trace(t.scroll);
+t.scroll = maxscroll;
+trace(t.bottomScroll);
+
+// Shortening the text scrolls back into range.
+t.text = "1";
+trace(t.scroll);
+trace(t.maxscroll);
+trace(t.maxhscroll);
+
+// The same goes for scrolling sideways.
+t.text = "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx";
+var maxhscroll = t.maxhscroll;
+trace(maxhscroll > 0);
+t.hscroll = 1000000;
+trace(t.hscroll == maxhscroll);
+t.hscroll = -5;
+trace(t.hscroll);
+t.hscroll = 2.5;
+trace(t.hscroll);
@@ -0,0 +1,23 @@
+_root.createEmptyMovieClip("clip", 1);
+
+// The inner finally block runs, then the outer try block catches the throw.
+try {
+    try {
+        throw "inner";
+    } finally {
+        trace("inner finally");
+    }
+} catch (e) {
+    trace("outer caught " + e);
+}
+
+// The catch block keeps the target of its try block.
+tellTarget ("clip") {
+    try {
+        throw "error";
+    } catch (e) {
+        trace(_name);
+    }
+}
+
+trace("done");
@@ -0,0 +1,16 @@
+// Going past the recursion limit halts every script in the movie, so the
+// finally block is skipped, as is everything after it.
+function recurse() {
+    recurse();
+}
+
+trace("before");
+try {
+    recurse();
+} finally {
+    trace("finally");
+}
+trace("after");
+
+// Frame 2
+trace("frame 2");
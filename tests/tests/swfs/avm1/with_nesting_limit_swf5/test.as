@@ -0,0 +1,41 @@
+// SWF5: `with` blocks may only be nested 7 deep. A block past the
+// limit is skipped along with its actions, and the script continues after it.
+var o = {};
+o.x = "found in the with scope";
+
+with (o) {
+    with (o) {
+        with (o) {
+            with (o) {
+                with (o) {
+                    with (o) {
+                        with (o) {
+                            trace("7");
+                            trace(x);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+with (o) {
+    with (o) {
+        with (o) {
+            with (o) {
+                with (o) {
+                    with (o) {
+                        with (o) {
+                            with (o) {
+                                trace("8");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+trace("done");
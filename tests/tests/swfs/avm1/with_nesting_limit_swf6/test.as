@@ -0,0 +1,73 @@
+// SWF6: `with` blocks may only be nested 15 deep. A block past the
+// limit is skipped along with its actions, and the script continues after it.
+var o = {};
+o.x = "found in the with scope";
+
+with (o) {
+    with (o) {
+        with (o) {
+            with (o) {
+                with (o) {
+                    with (o) {
+                        with (o) {
+                            with (o) {
+                                with (o) {
+                                    with (o) {
+                                        with (o) {
+                                            with (o) {
+                                                with (o) {
+                                                    with (o) {
+                                                        with (o) {
+                                                            trace("15");
+                                                            trace(x);
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+with (o) {
+    with (o) {
+        with (o) {
+            with (o) {
+                with (o) {
+                    with (o) {
+                        with (o) {
+                            with (o) {
+                                with (o) {
+                                    with (o) {
+                                        with (o) {
+                                            with (o) {
+                                                with (o) {
+                                                    with (o) {
+                                                        with (o) {
+                                                            with (o) {
+                                                                trace("16");
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+trace("done");
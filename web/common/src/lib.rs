@@ -1,6 +1,8 @@
 //! Utility functions for the web backend.
 use wasm_bindgen::JsValue;
 
+pub mod mixer;
+
 #[derive(Debug)]
 pub struct JsError {
     value: JsValue,
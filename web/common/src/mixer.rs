@@ -0,0 +1,210 @@
+//! Messages between the web audio backend and the mixer that runs in its
+//! audio worklet.
+//!
+//! The backend posts a `MixerCommand` for each change to the playing sounds,
+//! along with any sound or clip data that the command needs. The mixer
+//! periodically posts a `MixerReport` back, so that the backend can answer
+//! questions about the playing sounds without waiting for the audio thread.
+
+use serde::{Deserialize, Serialize};
+use swf::AudioCompression;
+
+/// A sound or sound instance handle of the backend, as the raw parts of its
+/// arena index.
+pub type HandleKey = (usize, u64);
+
+/// An identifier for the tag data of a MovieClip that contains a stream.
+pub type ClipId = u32;
+
+/// The number of output sample frames that the mixer mixes between reports.
+pub const REPORT_INTERVAL: usize = 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum MixerCommand {
+    /// Registers a sound. The sound data is sent along with the command.
+    RegisterSound {
+        sound: HandleKey,
+        format: SoundFormat,
+        num_samples: u32,
+    },
+
+    /// Replaces the data of a registered sound, which is sent along with the
+    /// command.
+    ReplaceSound {
+        sound: HandleKey,
+        format: SoundFormat,
+        num_samples: u32,
+    },
+
+    StartSound {
+        sound: HandleKey,
+        instance: HandleKey,
+        settings: SoundInfo,
+    },
+
+    /// Restarts a playing instance with a different sound or settings.
+    RestartSound {
+        instance: HandleKey,
+        sound: HandleKey,
+        settings: SoundInfo,
+    },
+
+    /// Registers the tag data of a MovieClip with a stream, which is sent
+    /// along with the command.
+    RegisterClip {
+        clip: ClipId,
+        swf_version: u8,
+    },
+
+    /// Forgets the tag data of a MovieClip that no longer exists.
+    ForgetClip {
+        clip: ClipId,
+    },
+
+    StartStream {
+        instance: HandleKey,
+        clip: ClipId,
+        clip_frame: u16,
+        format: SoundFormat,
+    },
+
+    SeekStream {
+        instance: HandleKey,
+        clip_frame: u16,
+    },
+
+    StopSound {
+        instance: HandleKey,
+    },
+
+    StopAllSounds,
+
+    /// Sets the transform of a playing instance, as its left-to-left,
+    /// left-to-right, right-to-left and right-to-right volumes.
+    SetSoundTransform {
+        instance: HandleKey,
+        transform: [f32; 4],
+    },
+
+    AdvanceFrameClock {
+        dt: f64,
+    },
+}
+
+/// The state of the playing sounds, as last mixed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MixerReport {
+    /// The position of each playing sound instance, in milliseconds.
+    pub positions: Vec<(HandleKey, u32)>,
+
+    /// The sound instances that have finished playing, or failed to start.
+    pub ended: Vec<HandleKey>,
+}
+
+/// A `swf::SoundFormat`.
+///
+/// Unlike a format read from a SWF, the sample rate may be any rate, as
+/// sounds decoded by the browser are resampled to the rate of the output.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SoundFormat {
+    /// The SWF code of the compression.
+    pub compression: u8,
+    pub sample_rate: u16,
+    pub is_stereo: bool,
+    pub is_16_bit: bool,
+}
+
+impl From<&swf::SoundFormat> for SoundFormat {
+    fn from(format: &swf::SoundFormat) -> Self {
+        let compression = match format.compression {
+            AudioCompression::UncompressedUnknownEndian => 0,
+            AudioCompression::Adpcm => 1,
+            AudioCompression::Mp3 => 2,
+            AudioCompression::Uncompressed => 3,
+            AudioCompression::Nellymoser16Khz => 4,
+            AudioCompression::Nellymoser8Khz => 5,
+            AudioCompression::Nellymoser => 6,
+            AudioCompression::Speex => 11,
+        };
+        Self {
+            compression,
+            sample_rate: format.sample_rate,
+            is_stereo: format.is_stereo,
+            is_16_bit: format.is_16_bit,
+        }
+    }
+}
+
+impl SoundFormat {
+    /// Returns the `swf::SoundFormat` that this was made from, or `None` if
+    /// the compression is unknown.
+    pub fn to_swf(&self) -> Option<swf::SoundFormat> {
+        let compression = match self.compression {
+            0 => AudioCompression::UncompressedUnknownEndian,
+            1 => AudioCompression::Adpcm,
+            2 => AudioCompression::Mp3,
+            3 => AudioCompression::Uncompressed,
+            4 => AudioCompression::Nellymoser16Khz,
+            5 => AudioCompression::Nellymoser8Khz,
+            6 => AudioCompression::Nellymoser,
+            11 => AudioCompression::Speex,
+            _ => return None,
+        };
+        Some(swf::SoundFormat {
+            compression,
+            sample_rate: self.sample_rate,
+            is_stereo: self.is_stereo,
+            is_16_bit: self.is_16_bit,
+        })
+    }
+}
+
+/// The settings of a `swf::SoundInfo` that the mixer uses.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SoundInfo {
+    pub in_sample: Option<u32>,
+    pub out_sample: Option<u32>,
+    pub num_loops: u16,
+
+    /// The sample, left volume and right volume of each envelope point.
+    pub envelope: Option<Vec<(u32, f32, f32)>>,
+}
+
+impl From<&swf::SoundInfo> for SoundInfo {
+    fn from(info: &swf::SoundInfo) -> Self {
+        Self {
+            in_sample: info.in_sample,
+            out_sample: info.out_sample,
+            num_loops: info.num_loops,
+            envelope: info.envelope.as_ref().map(|envelope| {
+                envelope
+                    .iter()
+                    .map(|point| (point.sample, point.left_volume, point.right_volume))
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl From<&SoundInfo> for swf::SoundInfo {
+    fn from(info: &SoundInfo) -> Self {
+        Self {
+            event: swf::SoundEvent::Event,
+            in_sample: info.in_sample,
+            out_sample: info.out_sample,
+            num_loops: info.num_loops,
+            envelope: info.envelope.as_ref().map(|envelope| {
+                envelope
+                    .iter()
+                    .map(
+                        |&(sample, left_volume, right_volume)| swf::SoundEnvelopePoint {
+                            sample,
+                            left_volume,
+                            right_volume,
+                        },
+                    )
+                    .collect()
+            }),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! The audio mixer of the Ruffle web player.
+//!
+//! This runs in an audio worklet, so that sounds are mixed on the audio
+//! rendering thread instead of the page's main thread. The web audio backend
+//! sends the worklet a `MixerCommand` for each change to the playing sounds,
+//! and the mixer sends back a `MixerReport` as it plays them.
+//!
+//! Where audio worklets are unavailable, the backend runs the same `Mixer` on
+//! the main thread instead. The worklet's bindings are only built with the
+//! `worklet` feature.
+
+use fnv::FnvHashMap;
+use ruffle_core::backend::audio::{
+    mixer::AudioMixer,
+    swf::{self, SoundStreamHead},
+    SoundHandle, SoundInstanceHandle, SoundTransform,
+};
+use ruffle_core::tag_utils::{SwfMovie, SwfSlice};
+use ruffle_web_common::mixer::{
+    ClipId, HandleKey, MixerCommand, MixerReport, SoundFormat, REPORT_INTERVAL,
+};
+use std::sync::Arc;
+
+#[cfg(feature = "worklet")]
+mod worklet;
+
+#[cfg(feature = "worklet")]
+pub use worklet::AudioWorkletMixer;
+
+pub type Error = Box<dyn std::error::Error>;
+
+/// Mixes the sounds of the web audio backend, as the backend's commands
+/// tell it to.
+pub struct Mixer {
+    mixer: AudioMixer,
+
+    /// The mixer's handle for each sound of the backend.
+    sounds: FnvHashMap<HandleKey, SoundHandle>,
+
+    /// The mixer's handle for each playing sound instance of the backend.
+    instances: FnvHashMap<HandleKey, SoundInstanceHandle>,
+
+    /// The tag data of the MovieClips that streams play from.
+    clips: FnvHashMap<ClipId, SwfSlice>,
+
+    /// The sound instances that failed to start since the last report.
+    ended: Vec<HandleKey>,
+
+    interleaved_samples: Vec<f32>,
+
+    /// The number of sample frames that have been mixed since the last report.
+    frames_since_report: usize,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            mixer: AudioMixer::new(2, sample_rate),
+            sounds: FnvHashMap::default(),
+            instances: FnvHashMap::default(),
+            clips: FnvHashMap::default(),
+            ended: vec![],
+            interleaved_samples: vec![],
+            frames_since_report: 0,
+        }
+    }
+
+    /// Mixes the next samples of the playing sounds into two channels.
+    ///
+    /// Every `REPORT_INTERVAL` sample frames, this returns a report for the
+    /// backend.
+    pub fn mix(&mut self, left: &mut [f32], right: &mut [f32]) -> Option<MixerReport> {
+        self.interleaved_samples.resize(2 * left.len(), 0.0);
+        self.mixer.mix::<f32>(&mut self.interleaved_samples);
+        let frames = self.interleaved_samples.chunks_exact(2);
+        for ((frame, left), right) in frames.zip(left.iter_mut()).zip(right.iter_mut()) {
+            *left = frame[0];
+            *right = frame[1];
+        }
+
+        self.frames_since_report += left.len();
+        if self.frames_since_report < REPORT_INTERVAL {
+            return None;
+        }
+        self.frames_since_report = 0;
+
+        let mut report = MixerReport {
+            positions: vec![],
+            ended: std::mem::take(&mut self.ended),
+        };
+        let mixer = &self.mixer;
+        self.instances.retain(
+            |&key, &mut instance| match mixer.get_sound_position(instance) {
+                Some(position) => {
+                    report.positions.push((key, position));
+                    true
+                }
+                None => {
+                    report.ended.push(key);
+                    false
+                }
+            },
+        );
+        Some(report)
+    }
+
+    /// Runs a command of the backend, with the data that was sent along with
+    /// it.
+    pub fn run_command(&mut self, command: MixerCommand, data: Option<&[u8]>) -> Result<(), Error> {
+        match command {
+            MixerCommand::RegisterSound {
+                sound,
+                format,
+                num_samples,
+            } => {
+                let data = data.ok_or("RegisterSound: Missing sound data")?;
+                let handle = self
+                    .mixer
+                    .register_sound(&swf_sound(&format, num_samples, data)?)?;
+                self.sounds.insert(sound, handle);
+            }
+            MixerCommand::ReplaceSound {
+                sound,
+                format,
+                num_samples,
+            } => {
+                let data = data.ok_or("ReplaceSound: Missing sound data")?;
+                let handle = *self.sounds.get(&sound).ok_or("Unregistered sound")?;
+                self.mixer
+                    .replace_sound(handle, &swf_sound(&format, num_samples, data)?)?;
+            }
+            MixerCommand::StartSound {
+                sound,
+                instance,
+                settings,
+            } => {
+                let handle = match self.sounds.get(&sound) {
+                    Some(&sound) => self.mixer.start_sound(sound, &(&settings).into()),
+                    None => Err("Unregistered sound".into()),
+                };
+                self.start_instance(instance, handle)?;
+            }
+            MixerCommand::RestartSound {
+                instance,
+                sound,
+                settings,
+            } => {
+                let sound = *self.sounds.get(&sound).ok_or("Unregistered sound")?;
+                if let Some(&handle) = self.instances.get(&instance) {
+                    if self
+                        .mixer
+                        .restart_sound(handle, sound, &(&settings).into())
+                        .is_err()
+                    {
+                        self.stop_sound(instance);
+                    }
+                }
+            }
+            MixerCommand::RegisterClip { clip, swf_version } => {
+                let data = data.ok_or("RegisterClip: Missing clip data")?;
+                let movie = SwfMovie::empty(swf_version);
+                let movie = movie.from_movie_and_subdata(data.to_vec(), &movie);
+                self.clips.insert(clip, SwfSlice::from(Arc::new(movie)));
+            }
+            MixerCommand::ForgetClip { clip } => {
+                self.clips.remove(&clip);
+            }
+            MixerCommand::StartStream {
+                instance,
+                clip,
+                clip_frame,
+                format,
+            } => {
+                let handle = match (self.clips.get(&clip), format.to_swf()) {
+                    (Some(clip_data), Some(format)) => {
+                        let stream_info = SoundStreamHead {
+                            stream_format: format.clone(),
+                            playback_format: format,
+                            num_samples_per_block: 0,
+                            latency_seek: 0,
+                        };
+                        self.mixer
+                            .start_stream(None, clip_frame, clip_data.clone(), &stream_info)
+                    }
+                    (None, _) => Err("Unregistered clip".into()),
+                    (_, None) => Err("Unknown sound format".into()),
+                };
+                self.start_instance(instance, handle)?;
+            }
+            MixerCommand::SeekStream {
+                instance,
+                clip_frame,
+            } => {
+                if let Some(&handle) = self.instances.get(&instance) {
+                    if !self.mixer.seek_stream(handle, clip_frame) {
+                        self.stop_sound(instance);
+                    }
+                }
+            }
+            MixerCommand::StopSound { instance } => self.stop_sound(instance),
+            MixerCommand::StopAllSounds => {
+                self.instances.clear();
+                self.mixer.stop_all_sounds();
+            }
+            MixerCommand::SetSoundTransform {
+                instance,
+                transform,
+            } => {
+                if let Some(&handle) = self.instances.get(&instance) {
+                    let [left_to_left, left_to_right, right_to_left, right_to_right] = transform;
+                    self.mixer.set_sound_transform(
+                        handle,
+                        SoundTransform {
+                            left_to_left,
+                            right_to_left,
+                            left_to_right,
+                            right_to_right,
+                        },
+                    );
+                }
+            }
+            MixerCommand::AdvanceFrameClock { dt } => self.mixer.advance_frame_clock(dt),
+        }
+
+        Ok(())
+    }
+
+    /// Tracks a sound instance that has been started, or reports that it has
+    /// ended if it couldn't be.
+    fn start_instance(
+        &mut self,
+        instance: HandleKey,
+        handle: Result<SoundInstanceHandle, Error>,
+    ) -> Result<(), Error> {
+        match handle {
+            Ok(handle) => {
+                self.instances.insert(instance, handle);
+                Ok(())
+            }
+            Err(e) => {
+                self.ended.push(instance);
+                Err(e)
+            }
+        }
+    }
+
+    /// Stops a sound instance. The next report says that it has ended.
+    fn stop_sound(&mut self, instance: HandleKey) {
+        if let Some(&handle) = self.instances.get(&instance) {
+            self.mixer.stop_sound(handle);
+        }
+    }
+}
+
+fn swf_sound<'a>(
+    format: &SoundFormat,
+    num_samples: u32,
+    data: &'a [u8],
+) -> Result<swf::Sound<'a>, Error> {
+    Ok(swf::Sound {
+        id: 0,
+        format: format.to_swf().ok_or("Unknown sound format")?,
+        num_samples,
+        data,
+    })
+}
@@ -0,0 +1,80 @@
+//! The bindings of the mixer for the audio worklet processor.
+
+use crate::Mixer;
+use ruffle_web_common::mixer::MixerCommand;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::{closure::Closure, prelude::*, JsCast};
+use web_sys::{MessageEvent, MessagePort};
+
+/// The mixer, as used by the audio worklet processor.
+#[wasm_bindgen]
+pub struct AudioWorkletMixer {
+    mixer: Rc<RefCell<Mixer>>,
+
+    /// The port that reports are sent to the backend through.
+    port: MessagePort,
+
+    /// Handles the commands that the backend sends through `port`.
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+#[wasm_bindgen]
+impl AudioWorkletMixer {
+    /// Creates a mixer that takes its commands from `port`, and mixes at the
+    /// sample rate of the worklet.
+    #[wasm_bindgen(constructor)]
+    pub fn new(sample_rate: f32, port: MessagePort) -> Self {
+        let mixer = Rc::new(RefCell::new(Mixer::new(sample_rate as u32)));
+
+        let weak_mixer = Rc::downgrade(&mixer);
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            if let Some(mixer) = weak_mixer.upgrade() {
+                handle_message(&mut mixer.borrow_mut(), &event.data());
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        port.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        Self {
+            mixer,
+            port,
+            _on_message: on_message,
+        }
+    }
+
+    /// Mixes the next samples of the playing sounds into the two channels of
+    /// the worklet's output.
+    pub fn mix(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let mut mixer = self.mixer.borrow_mut();
+        if let Some(report) = mixer.mix(left, right) {
+            match JsValue::from_serde(&report) {
+                Ok(report) => {
+                    if let Err(e) = self.port.post_message(&report) {
+                        log::warn!("Unable to report mixer state: {:?}", e);
+                    }
+                }
+                Err(e) => log::warn!("Unable to report mixer state: {}", e),
+            }
+        }
+    }
+}
+
+/// Runs a command that the backend posted to the worklet.
+fn handle_message(mixer: &mut Mixer, message: &JsValue) {
+    let command = js_sys::Reflect::get(message, &"command".into())
+        .ok()
+        .and_then(|command| command.into_serde::<MixerCommand>().ok());
+    let data = js_sys::Reflect::get(message, &"data".into())
+        .ok()
+        .and_then(|data| data.dyn_into::<js_sys::Uint8Array>().ok())
+        .map(|data| data.to_vec());
+
+    match command {
+        Some(command) => {
+            if let Err(e) = mixer.run_command(command, data.as_deref()) {
+                log::warn!("Unable to run mixer command: {}", e);
+            }
+        }
+        None => log::warn!("Received an invalid mixer command"),
+    }
+}
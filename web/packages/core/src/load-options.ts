@@ -65,6 +65,28 @@ export enum UnmuteOverlay {
     Hidden = "hidden",
 }
 
+/**
+ * Controls which requests movies may make to domains other than their own.
+ */
+export enum CrossDomain {
+    /**
+     * Movies may load data and movies from any domain.
+     */
+    AllowAll = "allowAll",
+
+    /**
+     * Movies may only load data from another domain if the
+     * `crossdomain.xml` policy file of that domain allows it,
+     * as in Flash Player.
+     */
+    RespectPolicy = "respectPolicy",
+
+    /**
+     * Movies may not load data or movies from any other domain.
+     */
+    DenyCross = "denyCross",
+}
+
 /**
  * Console logging level.
  */
@@ -145,6 +167,33 @@ export interface BaseLoadOptions {
      */
     upgradeToHttps?: boolean;
 
+    /**
+     * Rules to rewrite the URLs that the movie loads or navigates to,
+     * for archived content that references servers which no longer exist.
+     *
+     * Rules are applied in order, and each is one of:
+     * - `"host FROM TO"` to replace the host `FROM` with `TO`.
+     *   A `FROM` of `*.example.com` also matches its subdomains.
+     * - `"https HOST"` to upgrade `http` URLs on `HOST` to `https`,
+     *   or on every host if `HOST` is `*`.
+     * - `"map PREFIX TARGET"` to replace the start of a URL with another URL.
+     *
+     * @default []
+     */
+    urlRewrites?: string[];
+
+    /**
+     * Which requests movies may make to domains other than their own.
+     *
+     * Cross-origin requests are already limited by the browser, so movies
+     * may load from any domain by default. Use `CrossDomain.RespectPolicy`
+     * to also require the `crossdomain.xml` policy files that Flash Player
+     * required for loading data.
+     *
+     * @default CrossDomain.AllowAll
+     */
+    crossDomain?: CrossDomain;
+
     /**
      * Whether or not to display an overlay with a warning when
      * loading a movie with unsupported content.
@@ -160,6 +209,15 @@ export interface BaseLoadOptions {
      */
     logLevel?: LogLevel;
 
+    /**
+     * Whether or not to print the output of `trace()` to the browser
+     * console. Otherwise, it is logged at the info level, and so is only
+     * shown if `logLevel` is at least `LogLevel.Info`.
+     *
+     * @default false
+     */
+    traceToConsole?: boolean;
+
     /**
      * Whether or not to show a context menu when right-clicking
      * a Ruffle instance.
@@ -169,8 +227,49 @@ export interface BaseLoadOptions {
     contextMenu?: boolean;
 
     /**
-     * Maximum amount of time a script can take before scripting
-     * is disabled.
+     * Whether or not to show the built-in items of the context menu, such as
+     * "Play" and "Rewind". Movies can change this with `Stage.showMenu`.
+     *
+     * This is the `menu` parameter of `<object>` and `<embed>` tags.
+     *
+     * @default true
+     */
+    menu?: boolean;
+
+    /**
+     * Whether or not the movie starts over once it reaches its last frame.
+     * If not, it stops on its last frame instead.
+     *
+     * This is the `loop` parameter of `<object>` and `<embed>` tags.
+     *
+     * @default true
+     */
+    loop?: boolean;
+
+    /**
+     * The rendering quality the movie starts with, one of "low", "medium",
+     * "high" or "best". "autolow" and "autohigh" start at low and high
+     * quality. `null` uses the quality saved for the site.
+     *
+     * This is the `quality` parameter of `<object>` and `<embed>` tags.
+     *
+     * @default null
+     */
+    quality?: string | null;
+
+    /**
+     * Whether or not to draw objects that scripts move smoothly between
+     * frames. This makes motion in movies with a low frame rate look less
+     * choppy, but draws moving objects up to a frame late. `null` uses the
+     * choice saved for the site.
+     *
+     * @default null
+     */
+    frameInterpolation?: boolean | null;
+
+    /**
+     * Maximum amount of time a script can take before the user is
+     * asked whether to abort it. Aborting a script disables scripting.
      *
      * @default {"secs": 15, "nanos": 0}
      */
@@ -178,6 +277,15 @@ export interface BaseLoadOptions {
         secs: number;
         nanos: number;
     };
+
+    /**
+     * Maximum amount of ActionScript 1 and 2 actions that can be run
+     * between two frames before the user is asked whether to abort the
+     * script, or `null` for no limit.
+     *
+     * @default null
+     */
+    maxActionsPerUpdate?: number | null;
 }
 
 /**
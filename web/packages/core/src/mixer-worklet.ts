@@ -0,0 +1,57 @@
+/**
+ * The audio worklet processor that mixes Ruffle's sounds.
+ *
+ * This runs on the audio rendering thread, apart from the rest of Ruffle.
+ * The web audio backend sends the processor the compiled mixer and a port to
+ * send its commands through.
+ *
+ * @ignore
+ * @internal
+ */
+
+import "./worklet-polyfills";
+import init, { AudioWorkletMixer } from "../pkg/ruffle_web_mixer";
+
+// These are provided by the audio worklet scope, which TypeScript doesn't
+// know about.
+declare const sampleRate: number;
+declare class AudioWorkletProcessor {
+    readonly port: MessagePort;
+    constructor(options?: AudioWorkletNodeOptions);
+}
+declare function registerProcessor(
+    name: string,
+    processorCtor: new (
+        options: AudioWorkletNodeOptions
+    ) => AudioWorkletProcessor
+): void;
+
+/**
+ * Plays the output of the mixer.
+ *
+ * Silence is played until the mixer has been instantiated.
+ */
+class MixerProcessor extends AudioWorkletProcessor {
+    private mixer: AudioWorkletMixer | null = null;
+
+    constructor(options: AudioWorkletNodeOptions) {
+        super(options);
+        const module: WebAssembly.Module = options.processorOptions.module;
+        this.port.onmessage = (event: MessageEvent<MessagePort>) => {
+            const commandPort = event.data;
+            init(module).then(() => {
+                this.mixer = new AudioWorkletMixer(sampleRate, commandPort);
+            });
+        };
+    }
+
+    process(_inputs: Float32Array[][], outputs: Float32Array[][]): boolean {
+        const [left, right] = outputs[0];
+        if (this.mixer && left && right) {
+            this.mixer.mix(left, right);
+        }
+        return true;
+    }
+}
+
+registerProcessor("ruffle-mixer", MixerProcessor);
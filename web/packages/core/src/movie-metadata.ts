@@ -26,4 +26,25 @@ export interface MovieMetadata {
      * The SWF version of the movie.
      */
     readonly swfVersion: number;
+
+    /**
+     * The background color of the movie, as a hex string such as `#ff0000`,
+     * or `null` if the movie doesn't set one.
+     */
+    readonly backgroundColor: string | null;
+
+    /**
+     * Whether the movie is an ActionScript 3.0 movie.
+     */
+    readonly isActionScript3: boolean;
+
+    /**
+     * The size of the SWF file in bytes, as it was loaded.
+     */
+    readonly compressedLength: number;
+
+    /**
+     * The size of the SWF file in bytes after decompression.
+     */
+    readonly uncompressedLength: number;
 }
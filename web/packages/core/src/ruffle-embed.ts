@@ -39,6 +39,12 @@ export class RuffleEmbed extends RufflePlayer {
             const allowScriptAccess =
                 this.attributes.getNamedItem("allowScriptAccess")?.value ??
                 null;
+            const menu = this.attributes
+                .getNamedItem("menu")
+                ?.value.toLowerCase();
+            const loop = this.attributes
+                .getNamedItem("loop")
+                ?.value.toLowerCase();
 
             // Kick off the SWF download.
             this.load({
@@ -49,6 +55,9 @@ export class RuffleEmbed extends RufflePlayer {
                 ),
                 parameters: this.attributes.getNamedItem("flashvars")?.value,
                 backgroundColor: this.attributes.getNamedItem("bgcolor")?.value,
+                menu: menu === undefined ? undefined : menu !== "false",
+                loop: loop === undefined ? undefined : loop !== "false",
+                quality: this.attributes.getNamedItem("quality")?.value,
             });
         }
     }
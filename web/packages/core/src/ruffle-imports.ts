@@ -5,6 +5,34 @@
  * @internal
  */
 
+/**
+ * Loads the audio mixer into the audio worklet of the given context.
+ *
+ * The mixer is compiled here, as the worklet can't fetch it itself.
+ *
+ * @param context The audio context to mix the sounds of.
+ * @returns A promise that resolves to the compiled WebAssembly module of the
+ * mixer once the worklet is ready for it.
+ * @internal
+ */
+export async function addMixerWorklet(
+    context: AudioContext
+): Promise<WebAssembly.Module> {
+    // wasm files are set to be resource assets,
+    // so this import will resolve to the URL of the wasm file.
+    const mixerWasm = await import(
+        /* webpackMode: "eager" */
+        "../pkg/ruffle_web_mixer_bg.wasm"
+    );
+    const [module] = await Promise.all([
+        WebAssembly.compileStreaming(fetch(mixerWasm.default)),
+        context.audioWorklet.addModule(
+            new URL("./mixer-worklet.js", import.meta.url)
+        ),
+    ]);
+    return module;
+}
+
 /**
  * Copies data into the given audio channel.
  * This is necessary because Safari does not support `AudioBuffer.copyToChannel`.
@@ -17,12 +45,10 @@ export function copyToAudioBuffer(
     rightData: ArrayLike<number>
 ): void {
     if (leftData) {
-        const dstBuffer = audioBuffer.getChannelData(0);
-        dstBuffer.set(leftData);
+        audioBuffer.getChannelData(0).set(leftData);
     }
 
     if (rightData) {
-        const dstBuffer = audioBuffer.getChannelData(1);
-        dstBuffer.set(rightData);
+        audioBuffer.getChannelData(1).set(rightData);
     }
 }
@@ -112,6 +112,24 @@ export class RuffleObject extends RufflePlayer {
             this.getAttribute("bgcolor")
         );
 
+        const menu = findCaseInsensitive(
+            this.params,
+            "menu",
+            this.getAttribute("menu")
+        );
+
+        const loop = findCaseInsensitive(
+            this.params,
+            "loop",
+            this.getAttribute("loop")
+        );
+
+        const quality = findCaseInsensitive(
+            this.params,
+            "quality",
+            this.getAttribute("quality")
+        );
+
         if (url) {
             const options: URLLoadOptions = { url };
             options.allowScriptAccess = isScriptAccessAllowed(
@@ -124,6 +142,15 @@ export class RuffleObject extends RufflePlayer {
             if (backgroundColor) {
                 options.backgroundColor = backgroundColor;
             }
+            if (menu) {
+                options.menu = menu.toLowerCase() !== "false";
+            }
+            if (loop) {
+                options.loop = loop.toLowerCase() !== "false";
+            }
+            if (quality) {
+                options.quality = quality;
+            }
 
             // Kick off the SWF download.
             this.load(options);
@@ -143,6 +143,41 @@ export class RufflePlayer extends HTMLElement {
      */
     static LOADED_METADATA = "loadedmetadata";
 
+    /**
+     * Triggered when the header of the movie has been parsed.
+     *
+     * @event RufflePlayer#headerparsed
+     */
+    static HEADER_PARSED = "headerparsed";
+
+    /**
+     * Triggered when the movie has been preloaded and is ready to play.
+     *
+     * @event RufflePlayer#preloadcomplete
+     */
+    static PRELOAD_COMPLETE = "preloadcomplete";
+
+    /**
+     * Triggered when the first frame of the movie has been executed.
+     *
+     * @event RufflePlayer#firstframeexecuted
+     */
+    static FIRST_FRAME_EXECUTED = "firstframeexecuted";
+
+    /**
+     * Triggered when the first frame of the movie has been drawn.
+     *
+     * @event RufflePlayer#firstframerendered
+     */
+    static FIRST_FRAME_RENDERED = "firstframerendered";
+
+    /**
+     * Triggered when the main timeline of the movie stops on its last frame.
+     *
+     * @event RufflePlayer#ended
+     */
+    static ENDED = "ended";
+
     /**
      * A movie can communicate with the hosting page using fscommand
      * as long as script access is allowed.
@@ -153,6 +188,15 @@ export class RufflePlayer extends HTMLElement {
      */
     onFSCommand: ((command: string, args: string) => boolean) | null;
 
+    private fsCommandListeners: Array<
+        (command: string, args: string) => boolean | void
+    > = [];
+
+    /**
+     * Listens for changes to the page URL's fragment while a movie is loaded.
+     */
+    private readonly hashChangedListener = this.hashChanged.bind(this);
+
     /**
      * Any configuration that should apply to this specific player.
      * This will be defaulted with any global configuration.
@@ -407,6 +451,7 @@ export class RufflePlayer extends HTMLElement {
 
         this.instance = new ruffleConstructor(this.container, this, config);
         console.log("New Ruffle instance created.");
+        window.addEventListener("hashchange", this.hashChangedListener);
 
         // In Firefox, AudioContext.state is always "suspended" when the object has just been created.
         // It may change by itself to "running" some milliseconds later. So we need to wait a little
@@ -473,6 +518,7 @@ export class RufflePlayer extends HTMLElement {
      */
     private destroy(): void {
         if (this.instance) {
+            window.removeEventListener("hashchange", this.hashChangedListener);
             this.instance.destroy();
             this.instance = null;
             this._metadata = null;
@@ -600,6 +646,91 @@ export class RufflePlayer extends HTMLElement {
         }
     }
 
+    /**
+     * Jumps to a frame of the movie and stops there, as `gotoAndStop` would.
+     *
+     * @param frame The number of the frame, starting from 1.
+     * @throws {RangeError} If `frame` isn't a whole number from 1 to 65535.
+     */
+    gotoFrame(frame: number): void {
+        this.instance?.goto_frame(frame);
+    }
+
+    /**
+     * Reads a variable of the movie, as the `GetVariable` method of the
+     * Flash Player plugin does.
+     *
+     * Only movies using ActionScript 1 or 2 have variables that can be read.
+     *
+     * @param path The path of the variable, such as `score` or `_root.player.name`.
+     * @returns The value of the variable as a string, or `null` if it is undefined.
+     */
+    getVariable(path: string): string | null {
+        return this.instance?.get_variable(path) ?? null;
+    }
+
+    /**
+     * Sets a variable of the movie, as the `SetVariable` method of the
+     * Flash Player plugin does.
+     *
+     * @param path The path of the variable, such as `score` or `_root.player.name`.
+     * @param value The new value of the variable.
+     */
+    setVariable(path: string, value: string): void {
+        this.instance?.set_variable(path, String(value));
+    }
+
+    /**
+     * Adds a function to be called for every fscommand of the movie, in
+     * addition to `onFSCommand`.
+     *
+     * @param listener The function to call with the command and its arguments.
+     * It may return true if it handled the command.
+     */
+    addFSCommandListener(
+        listener: (command: string, args: string) => boolean | void
+    ): void {
+        this.fsCommandListeners.push(listener);
+    }
+
+    /**
+     * Removes a function added with `addFSCommandListener`.
+     *
+     * @param listener The function to remove.
+     */
+    removeFSCommandListener(
+        listener: (command: string, args: string) => boolean | void
+    ): void {
+        this.fsCommandListeners = this.fsCommandListeners.filter(
+            (other) => other !== listener
+        );
+    }
+
+    /**
+     * Called when the movie runs an fscommand.
+     *
+     * This should only be called by Ruffle itself and not by users.
+     *
+     * @param command The command.
+     * @param args The arguments of the command.
+     * @returns True if the command was handled.
+     *
+     * @internal
+     * @ignore
+     */
+    callFSCommand(command: string, args: string): boolean {
+        let handled = false;
+        if (this.onFSCommand) {
+            handled = this.onFSCommand(command, args);
+        }
+        for (const listener of this.fsCommandListeners) {
+            if (listener(command, args)) {
+                handled = true;
+            }
+        }
+        return handled;
+    }
+
     /**
      * Checks if this player is allowed to be fullscreen by the browser.
      *
@@ -665,6 +796,9 @@ export class RufflePlayer extends HTMLElement {
     private contextMenuItems(): Array<ContextMenuItem | null> {
         const CHECKMARK = String.fromCharCode(0x2713);
         const items = [];
+        // A movie that hides the built-in menu items only keeps its own
+        // items and the "About" item.
+        const showMenu = this.instance?.show_menu() ?? true;
 
         if (this.instance) {
             const customItems: InternalContextMenuItem[] = this.instance.prepare_context_menu();
@@ -682,7 +816,7 @@ export class RufflePlayer extends HTMLElement {
         }
         items.push(null);
 
-        if (this.fullscreenEnabled) {
+        if (this.fullscreenEnabled && showMenu) {
             if (this.isFullscreen) {
                 items.push({
                     text: "Exit fullscreen",
@@ -1142,6 +1276,25 @@ export class RufflePlayer extends HTMLElement {
         };
     }
 
+    /**
+     * Called by Ruffle when a script has been running for too long.
+     *
+     * This should only be called by Ruffle itself and not by users.
+     *
+     * @returns Whether the user chose to abort the script.
+     *
+     * @internal
+     * @ignore
+     */
+    displaySlowScriptPrompt(): boolean {
+        // This must block until answered, as the script is still running.
+        return window.confirm(
+            "A script in this movie is causing Ruffle to run slowly. " +
+                "If it continues to run, this page may become unresponsive.\n\n" +
+                "Do you want to abort the script?"
+        );
+    }
+
     displayMessage(message: string): void {
         // Show a dismissible message in front of the player
         const div = document.createElement("div");
@@ -1160,10 +1313,63 @@ export class RufflePlayer extends HTMLElement {
         };
     }
 
+    /**
+     * Called by Ruffle when the movie prints a `PrintJob`.
+     *
+     * This should only be called by Ruffle itself and not by users.
+     *
+     * @param pages The printed pages, as `data:` URLs of PNG images.
+     *
+     * @internal
+     * @ignore
+     */
+    printPages(pages: string[]): void {
+        // Print from a hidden frame, so that only the pages are printed.
+        const frame = document.createElement("iframe");
+        frame.style.position = "fixed";
+        frame.style.width = "0";
+        frame.style.height = "0";
+        frame.style.border = "none";
+        document.body.appendChild(frame);
+
+        const frameDocument = frame.contentDocument!;
+        const style = frameDocument.createElement("style");
+        style.textContent = `
+            body { margin: 0; }
+            img { display: block; max-width: 100%; page-break-after: always; }
+        `;
+        frameDocument.head.appendChild(style);
+
+        const loads = pages.map((page) => {
+            const img = frameDocument.createElement("img");
+            frameDocument.body.appendChild(img);
+            return new Promise((resolve) => {
+                img.onload = resolve;
+                img.onerror = resolve;
+                img.src = page;
+            });
+        });
+        Promise.all(loads).then(() => {
+            frame.contentWindow!.focus();
+            frame.contentWindow!.print();
+            frame.parentNode!.removeChild(frame);
+        });
+    }
+
     protected debugPlayerInfo(): string {
-        return `Allows script access: ${
+        let result = `Allows script access: ${
             this.options?.allowScriptAccess ?? false
         }\n`;
+        let diagnostics: string[] = [];
+        try {
+            diagnostics = this.instance?.diagnostics() ?? [];
+        } catch (e) {
+            // The instance may be unusable after a panic.
+        }
+        for (const diagnostic of diagnostics) {
+            result += `Diagnostic: ${diagnostic}\n`;
+        }
+        return result;
     }
 
     private setMetadata(metadata: MovieMetadata) {
@@ -1171,6 +1377,59 @@ export class RufflePlayer extends HTMLElement {
         // TODO: Switch this to ReadyState.Loading when we have streaming support.
         this._readyState = ReadyState.Loaded;
         this.dispatchEvent(new Event(RufflePlayer.LOADED_METADATA));
+        // The movie is installed right after its metadata is reported, so
+        // honor any anchor in the page URL once that has happened.
+        setTimeout(() => this.hashChanged(), 0);
+    }
+
+    /**
+     * Called by Ruffle when the movie reaches a milestone in its lifecycle.
+     *
+     * This should only be called by Ruffle itself and not by users.
+     *
+     * @param event The name of the lifecycle event, which is dispatched as a
+     * DOM event on this element.
+     *
+     * @internal
+     * @ignore
+     */
+    onLifecycleEvent(event: string): void {
+        this.dispatchEvent(new Event(event));
+    }
+
+    /**
+     * Called by Ruffle when the root timeline reaches a frame labelled as a
+     * named anchor. The anchor is added to the browser history, so that the
+     * back and forward buttons navigate between anchors.
+     *
+     * This should only be called by Ruffle itself and not by users.
+     *
+     * @param anchor The name of the anchor that was reached.
+     *
+     * @internal
+     * @ignore
+     */
+    setFrameAnchor(anchor: string): void {
+        const hash = `#${encodeURIComponent(anchor)}`;
+        if (window.location.hash !== hash) {
+            window.history.pushState(null, "", hash);
+        }
+    }
+
+    /**
+     * Navigates the movie to the named anchor in the page URL, if any.
+     */
+    private hashChanged(): void {
+        let anchor;
+        try {
+            anchor = decodeURIComponent(window.location.hash.substring(1));
+        } catch (e) {
+            // A malformed fragment can't name an anchor.
+            return;
+        }
+        if (anchor && this.instance) {
+            this.instance.goto_anchor(anchor);
+        }
     }
 }
 
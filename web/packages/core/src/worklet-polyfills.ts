@@ -0,0 +1,85 @@
+/**
+ * Polyfills for the audio worklet scope.
+ *
+ * The worklet scope has no `TextDecoder` or `TextEncoder`, which the
+ * WebAssembly bindings use to pass strings, so minimal UTF-8 versions are
+ * provided here. This module must be imported before the bindings.
+ *
+ * @ignore
+ * @internal
+ */
+
+/**
+ * Decodes UTF-8 text.
+ */
+class Utf8Decoder {
+    decode(bytes?: Uint8Array): string {
+        if (!bytes) {
+            return "";
+        }
+
+        let text = "";
+        let i = 0;
+        while (i < bytes.length) {
+            const byte = bytes[i++];
+            let codePoint;
+            if (byte < 0x80) {
+                codePoint = byte;
+            } else if (byte < 0xe0) {
+                codePoint = ((byte & 0x1f) << 6) | (bytes[i++] & 0x3f);
+            } else if (byte < 0xf0) {
+                codePoint =
+                    ((byte & 0x0f) << 12) |
+                    ((bytes[i++] & 0x3f) << 6) |
+                    (bytes[i++] & 0x3f);
+            } else {
+                codePoint =
+                    ((byte & 0x07) << 18) |
+                    ((bytes[i++] & 0x3f) << 12) |
+                    ((bytes[i++] & 0x3f) << 6) |
+                    (bytes[i++] & 0x3f);
+            }
+            text += String.fromCodePoint(codePoint);
+        }
+        return text;
+    }
+}
+
+/**
+ * Encodes text as UTF-8.
+ */
+class Utf8Encoder {
+    encode(text = ""): Uint8Array {
+        const bytes = [];
+        for (const char of text) {
+            const codePoint = char.codePointAt(0) ?? 0;
+            if (codePoint < 0x80) {
+                bytes.push(codePoint);
+            } else if (codePoint < 0x800) {
+                bytes.push(0xc0 | (codePoint >> 6), 0x80 | (codePoint & 0x3f));
+            } else if (codePoint < 0x10000) {
+                bytes.push(
+                    0xe0 | (codePoint >> 12),
+                    0x80 | ((codePoint >> 6) & 0x3f),
+                    0x80 | (codePoint & 0x3f)
+                );
+            } else {
+                bytes.push(
+                    0xf0 | (codePoint >> 18),
+                    0x80 | ((codePoint >> 12) & 0x3f),
+                    0x80 | ((codePoint >> 6) & 0x3f),
+                    0x80 | (codePoint & 0x3f)
+                );
+            }
+        }
+        return new Uint8Array(bytes);
+    }
+}
+
+if (typeof TextDecoder === "undefined") {
+    Object.defineProperty(globalThis, "TextDecoder", { value: Utf8Decoder });
+}
+
+if (typeof TextEncoder === "undefined") {
+    Object.defineProperty(globalThis, "TextEncoder", { value: Utf8Encoder });
+}
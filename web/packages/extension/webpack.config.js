@@ -61,6 +61,12 @@ module.exports = (env, argv) => {
             clean: true,
         },
         module: {
+            parser: {
+                javascript: {
+                    // Bundle the audio mixer worklet like a worker.
+                    worker: ["*context.audioWorklet.addModule()", "..."],
+                },
+            },
             rules: [
                 {
                     test: /\.ts$/i,
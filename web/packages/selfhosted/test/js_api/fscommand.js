@@ -0,0 +1,40 @@
+const { js_api_before } = require("../utils");
+const { expect, use } = require("chai");
+const chaiHtml = require("chai-html");
+
+use(chaiHtml);
+
+describe("RufflePlayer.callFSCommand", () => {
+    js_api_before("/test_assets/example.swf");
+
+    it("calls onFSCommand and every listener", () => {
+        const player = browser.$("<ruffle-player>");
+        const result = browser.execute((player) => {
+            const calls = [];
+            player.onFSCommand = (command, args) => {
+                calls.push(["onFSCommand", command, args]);
+                return false;
+            };
+            const listener = (command, args) => {
+                calls.push(["listener", command, args]);
+                return true;
+            };
+            player.addFSCommandListener(listener);
+            const handled = player.callFSCommand("quit", "now");
+
+            player.removeFSCommandListener(listener);
+            const handledAfterRemoval = player.callFSCommand("quit", "");
+            player.onFSCommand = null;
+            return { calls, handled, handledAfterRemoval };
+        }, player);
+        expect(result).to.eql({
+            calls: [
+                ["onFSCommand", "quit", "now"],
+                ["listener", "quit", "now"],
+                ["onFSCommand", "quit", ""],
+            ],
+            handled: true,
+            handledAfterRemoval: false,
+        });
+    });
+});
@@ -0,0 +1,34 @@
+const { js_api_before } = require("../utils");
+const { expect, use } = require("chai");
+const chaiHtml = require("chai-html");
+
+use(chaiHtml);
+
+describe("RufflePlayer.gotoFrame", () => {
+    js_api_before("/test_assets/example.swf");
+
+    it("rejects frames that can't exist", () => {
+        const player = browser.$("<ruffle-player>");
+        const errors = browser.execute((player) => {
+            return [0, 1.5, 65536, NaN].map((frame) => {
+                try {
+                    player.gotoFrame(frame);
+                    return null;
+                } catch (e) {
+                    return e.name;
+                }
+            });
+        }, player);
+        expect(errors).to.eql([
+            "RangeError",
+            "RangeError",
+            "RangeError",
+            "RangeError",
+        ]);
+    });
+
+    it("accepts the first frame", () => {
+        const player = browser.$("<ruffle-player>");
+        browser.execute((player) => player.gotoFrame(1), player);
+    });
+});
@@ -22,6 +22,12 @@ module.exports = (env, argv) => {
             clean: true,
         },
         module: {
+            parser: {
+                javascript: {
+                    // Bundle the audio mixer worklet like a worker.
+                    worker: ["*context.audioWorklet.addModule()", "..."],
+                },
+            },
             rules: [
                 {
                     test: /\.wasm$/i,
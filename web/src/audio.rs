@@ -1,40 +1,82 @@
 use fnv::FnvHashMap;
-use generational_arena::Arena;
+use generational_arena::{Arena, Index};
 use ruffle_core::backend::audio::{
-    decoders::{AdpcmDecoder, NellymoserDecoder},
     swf::{self, AudioCompression},
     AudioBackend, PreloadStreamHandle, SoundHandle, SoundInstanceHandle, SoundTransform,
 };
+use ruffle_core::tag_utils::{SwfMovie, SwfSlice};
+use ruffle_web_common::mixer::{ClipId, HandleKey, MixerCommand, MixerReport};
 use ruffle_web_common::JsResult;
+use ruffle_web_mixer::Mixer;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use std::sync::{Arc, Weak};
 use wasm_bindgen::{closure::Closure, prelude::*, JsCast};
-use web_sys::{AudioContext, GainNode};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    AudioContext, AudioWorkletNode, AudioWorkletNodeOptions, MessageChannel, MessageEvent,
+    MessagePort,
+};
+
+/// The name that the mixer's processor is registered under in the audio worklet.
+const MIXER_PROCESSOR: &str = "ruffle-mixer";
+
+/// The number of sample frames mixed into each output buffer, when mixing on
+/// the main thread.
+/// Smaller buffers lower the latency of sounds, at the cost of more
+/// frequent mixing (and possible underruns when the page is busy).
+const BUFFER_SIZE: u32 = 2048;
+
+/// The number of output buffers that are scheduled at the same time, when
+/// mixing on the main thread.
+/// While one buffer is playing, the other is waiting to play next.
+const NUM_BUFFERS: usize = 2;
 
 pub struct WebAudioBackend {
     context: AudioContext,
-    sounds: Arena<Sound>,
-    left_samples: Vec<f32>,
-    right_samples: Vec<f32>,
+
+    /// The mixer that commands are sent to.
+    output: MixerOutput,
+
+    /// The duration of each registered sound, in milliseconds.
+    sounds: Arena<u32>,
+
+    /// The playing sound instances, and their positions in milliseconds as
+    /// last reported by the mixer.
+    instances: Rc<RefCell<Arena<u32>>>,
+
+    /// The tag data of MovieClips that has been sent to the mixer to stream
+    /// from, keyed by the address of its movie and its range in the movie.
+    stream_clips: FnvHashMap<(usize, usize, usize), (Weak<SwfMovie>, ClipId)>,
+    next_clip_id: ClipId,
+
     frame_rate: f64,
-    min_sample_rate: u16,
     preload_stream_data: FnvHashMap<PreloadStreamHandle, StreamData>,
     next_stream_id: u32,
+
+    /// The frame numbers and sample counts for each segment of the MP3 stream
+    /// sounds.
+    stream_segments: FnvHashMap<SoundHandle, Vec<(u16, u32)>>,
+
+    /// The MP3 stream sound that each playing stream instance was started from.
+    stream_instances: FnvHashMap<SoundInstanceHandle, SoundHandle>,
+
+    /// MP3 sounds that have been decoded by the browser, and are waiting to
+    /// replace their sound in the mixer.
+    decoded_sounds: Rc<RefCell<Vec<(SoundHandle, DecodedSound)>>>,
 }
 
 thread_local! {
-    static SOUND_INSTANCES: RefCell<Arena<SoundInstance>> = RefCell::new(Arena::new());
     static NUM_SOUNDS_LOADING: Cell<u32> = Cell::new(0);
 }
 
-#[derive(Clone)]
+/// Stream data for an MP3 stream sound, which is decoded ahead of time by
+/// the browser.
 struct StreamData {
     format: swf::SoundFormat,
     audio_data: Vec<u8>,
     num_sample_frames: u32,
-    samples_per_block: u32,
     skip_sample_frames: u16,
-    adpcm_block_offsets: Vec<usize>,
 
     /// List of stream segments. Contains the frame they start on and the starting sample.
     /// Guaranteed to be in frame order.
@@ -44,257 +86,213 @@ struct StreamData {
     last_clip_frame: u16,
 }
 
-type AudioBufferPtr = Rc<RefCell<web_sys::AudioBuffer>>;
-
-// A sound can be either as a JS AudioBuffer and as a on--the-fly decoded stream using a ScriptProcessorNode.
-#[allow(dead_code)]
-enum SoundSource {
-    // Pre-decoded audio buffer.
-    AudioBuffer(AudioBufferPtr),
-
-    // Decode the audio data on the fly from a byte stream.
-    Decoder(Vec<u8>),
-}
-
-#[allow(dead_code)]
-struct Sound {
-    format: swf::SoundFormat,
-    source: SoundSource,
-
-    /// Number of samples in this audio.
-    /// This may be shorter than the actual length of the audio data to allow for seamless looping.
-    /// For example, MP3 encoder adds gaps from encoder delay.
+/// An MP3 sound that has been decoded to 16-bit stereo PCM.
+struct DecodedSound {
+    sample_rate: u16,
     num_sample_frames: u32,
-
-    /// Number of samples to skip encoder delay.
-    skip_sample_frames: u16,
-
-    /// If this is a stream sound, the frame numbers and sample counts for each segment of the stream.
-    stream_segments: Vec<(u16, u32)>,
+    data: Vec<u8>,
 }
 
-type Decoder = Box<dyn Iterator<Item = [i16; 2]>>;
-
-/// An actively playing instance of a sound.
-/// This sound can be either an event sound (`StartSound`) or
-/// a stream sound (`SoundStreamBlock`).
-struct SoundInstance {
-    /// Handle to the sound clip.
-    #[allow(dead_code)]
-    handle: Option<SoundHandle>,
-
-    /// Format of the sound.
-    format: swf::SoundFormat,
-
-    /// On web, sounds can be played via different methods:
-    /// either decoded on the fly with Decoder, or pre-decoded
-    /// and played with and AudioBufferSourceNode.
-    instance_type: SoundInstanceType,
-}
+/// Where the mixer runs, and how commands reach it.
+enum MixerOutput {
+    /// The mixer runs in an audio worklet, which starts asynchronously.
+    /// Commands that are sent before it starts wait in the port's queue.
+    Worklet {
+        /// The port that commands are sent to the mixer through.
+        port: MessagePort,
 
-/// The Drop impl ensures that the sound is stopped and remove from the audio context,
-/// and any event listeners are removed.
-impl Drop for SoundInstance {
-    fn drop(&mut self) {
-        if let SoundInstanceType::AudioBuffer(instance) = &self.instance_type {
-            let _ = instance.buffer_source_node.set_onended(None);
-            let _ = instance.node.disconnect();
-        }
-    }
-}
+        /// Applies the reports that the mixer sends back through `port`.
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+    },
 
-#[allow(dead_code)]
-enum SoundInstanceType {
-    Decoder(Decoder),
-    AudioBuffer(AudioBufferInstance),
+    /// The mixer runs on the main thread, and its output is played through
+    /// buffers that are queued one after another.
+    ///
+    /// This is for browsers without audio worklets. Worklets are also only
+    /// available to secure (https) pages.
+    MainThread {
+        mixer: Rc<RefCell<Mixer>>,
+        _buffers: Vec<Rc<RefCell<Buffer>>>,
+    },
 }
 
-/// A sound instance that is playing from an AudioBuffersource node.
-struct AudioBufferInstance {
-    /// The node that is connected to the output.
-    node: web_sys::AudioNode,
+impl MixerOutput {
+    /// Start the mixer in the audio worklet of `context`.
+    fn worklet(context: &AudioContext, instances: &Rc<RefCell<Arena<u32>>>) -> Result<Self, Error> {
+        let channel = MessageChannel::new().into_js_result()?;
+        let port = channel.port1();
+        let weak_instances = Rc::downgrade(instances);
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            if let Some(instances) = weak_instances.upgrade() {
+                match event.data().into_serde::<MixerReport>() {
+                    Ok(report) => apply_report(&mut instances.borrow_mut(), report),
+                    Err(e) => log::warn!("Received an invalid report from the mixer: {}", e),
+                }
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        port.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        let mixer_context = context.clone();
+        let mixer_port = channel.port2();
+        spawn_local(async move {
+            if let Err(e) = start_mixer(&mixer_context, mixer_port).await {
+                log::error!("Unable to start the audio mixer: {}", e);
+            }
+        });
 
-    /// The buffer node containing the audio data.
-    /// This is often the same as `node`, but will be different
-    /// if there is a custom envelope on this sound.
-    sound_transform_nodes: SoundTransformNodes,
+        Ok(MixerOutput::Worklet {
+            port,
+            _on_message: on_message,
+        })
+    }
 
-    /// The audio node with envelopes applied.
-    envelope_node: web_sys::AudioNode,
+    /// Start the mixer on the main thread, and play its output.
+    fn main_thread(
+        context: &AudioContext,
+        instances: &Rc<RefCell<Arena<u32>>>,
+    ) -> Result<Self, Error> {
+        let mixer = Rc::new(RefCell::new(Mixer::new(context.sample_rate() as u32)));
+        let buffer_timestamp = Rc::new(Cell::new(0.0));
+        let mut buffers = Vec::with_capacity(NUM_BUFFERS);
+        for _ in 0..NUM_BUFFERS {
+            let audio_buffer = context
+                .create_buffer(2, BUFFER_SIZE, context.sample_rate())
+                .into_js_result()?;
+            let buffer = Rc::new(RefCell::new(Buffer {
+                context: context.clone(),
+                mixer: Rc::clone(&mixer),
+                instances: Rc::downgrade(instances),
+                audio_buffer,
+                left_samples: vec![0.0; BUFFER_SIZE as usize],
+                right_samples: vec![0.0; BUFFER_SIZE as usize],
+                buffer_timestamp: Rc::clone(&buffer_timestamp),
+                node: None,
+                on_ended: None,
+            }));
+
+            let weak_buffer = Rc::downgrade(&buffer);
+            let on_ended = Closure::wrap(Box::new(move || {
+                if let Some(buffer) = weak_buffer.upgrade() {
+                    if let Err(e) = buffer.borrow_mut().play() {
+                        log::error!("Unable to play audio buffer: {}", e);
+                    }
+                }
+            }) as Box<dyn FnMut()>);
+            buffer.borrow_mut().on_ended = Some(on_ended);
 
-    /// Whether the output of `envelope_node` is mono or stereo.
-    envelope_is_stereo: bool,
+            buffer.borrow_mut().play()?;
+            buffers.push(buffer);
+        }
 
-    /// The buffer node containing the audio data.
-    /// This is often the same as `envelope_node`, but will be different
-    /// if there is a custom envelope on this sound.
-    buffer_source_node: web_sys::AudioBufferSourceNode,
-}
+        Ok(MixerOutput::MainThread {
+            mixer,
+            _buffers: buffers,
+        })
+    }
 
-impl AudioBufferInstance {
-    #[allow(clippy::float_cmp)]
-    fn set_transform(&mut self, context: &AudioContext, transform: &SoundTransform) {
-        let is_full_transform = transform.left_to_right != 0.0
-            || transform.right_to_left != 0.0
-            || transform.left_to_left != transform.right_to_right;
-
-        // Lazily instantiate gain nodes, depending on the type of transform.
-        match &self.sound_transform_nodes {
-            SoundTransformNodes::None => {
-                if is_full_transform {
-                    let _ = self.create_full_transform(context);
-                } else if transform.left_to_left != 1.0 || transform.right_to_right != 1.0 {
-                    let _ = self.create_volume_transform(context);
+    /// Send a command to the mixer, along with the data that it needs.
+    fn send(&self, command: MixerCommand, data: Option<&[u8]>) {
+        match self {
+            MixerOutput::Worklet { port, .. } => {
+                let message = js_sys::Object::new();
+                let result =
+                    JsValue::from_serde(&command)
+                        .map_err(Error::from)
+                        .and_then(|command| {
+                            js_sys::Reflect::set(&message, &"command".into(), &command)
+                                .into_js_result()?;
+                            if let Some(data) = data {
+                                let data = js_sys::Uint8Array::from(data);
+                                js_sys::Reflect::set(&message, &"data".into(), &data)
+                                    .into_js_result()?;
+                            }
+                            port.post_message(&message).into_js_result()?;
+                            Ok(())
+                        });
+                if let Err(e) = result {
+                    log::error!("Unable to send a command to the audio mixer: {}", e);
                 }
             }
-            SoundTransformNodes::Volume { .. } => {
-                if is_full_transform {
-                    let _ = self.create_full_transform(context);
+            MixerOutput::MainThread { mixer, .. } => {
+                if let Err(e) = mixer.borrow_mut().run_command(command, data) {
+                    log::warn!("Unable to run mixer command: {}", e);
                 }
             }
-            SoundTransformNodes::Transform { .. } => (),
-        }
-
-        match &self.sound_transform_nodes {
-            SoundTransformNodes::None => (),
-            SoundTransformNodes::Volume { gain } => {
-                // Assumes right_to_right is matching.
-                gain.gain().set_value(transform.left_to_left);
-            }
-            SoundTransformNodes::Transform {
-                left_to_left_gain,
-                left_to_right_gain,
-                right_to_left_gain,
-                right_to_right_gain,
-            } => {
-                left_to_left_gain.gain().set_value(transform.left_to_left);
-                left_to_right_gain.gain().set_value(transform.left_to_right);
-                right_to_left_gain.gain().set_value(transform.right_to_left);
-                right_to_right_gain
-                    .gain()
-                    .set_value(transform.right_to_right);
-            }
         }
     }
+}
 
-    /// Adds a gain node to this sound instance, allowing the volume to be adjusted.
-    fn create_volume_transform(
-        &mut self,
-        context: &AudioContext,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Create the gain node to control the volume.
-        let gain = context.create_gain().into_js_result()?;
-
-        // Wire up the nodes.
-        // Note that for mono tracks, we want to use channel 0 (left) for both the left and right.
-        self.node.disconnect().warn_on_error();
-        self.envelope_node.disconnect().warn_on_error();
-        self.envelope_node
-            .connect_with_audio_node(&gain)
-            .into_js_result()?;
+/// One of the buffers that the output of the mixer is copied into, when
+/// mixing on the main thread.
+///
+/// When a buffer finishes playing, it is refilled from the mixer and
+/// scheduled to play after the buffer that is currently playing.
+struct Buffer {
+    context: AudioContext,
+    mixer: Rc<RefCell<Mixer>>,
 
-        gain.connect_with_audio_node(&context.destination())
-            .warn_on_error();
+    /// The playing sound instances of the backend, which the mixer's reports
+    /// are applied to.
+    instances: std::rc::Weak<RefCell<Arena<u32>>>,
 
-        self.node = gain.clone().into();
-        self.sound_transform_nodes = SoundTransformNodes::Volume { gain };
-        Ok(())
-    }
+    audio_buffer: web_sys::AudioBuffer,
+    left_samples: Vec<f32>,
+    right_samples: Vec<f32>,
 
-    /// Adds a bunch of gain nodes to this sound instance, allowing a SoundTransform
-    /// to be applied to it.
-    fn create_full_transform(
-        &mut self,
-        context: &AudioContext,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Split the left and right channels.
-        let splitter = context
-            .create_channel_splitter_with_number_of_outputs(2)
-            .into_js_result()?;
+    /// The time at which the last scheduled buffer stops playing.
+    /// This is shared between all buffers.
+    buffer_timestamp: Rc<Cell<f64>>,
 
-        // Create the envelope gain nodes for the left and right channels.
-        let left_to_left_gain = context.create_gain().into_js_result()?;
-        let left_to_right_gain = context.create_gain().into_js_result()?;
-        let right_to_left_gain = context.create_gain().into_js_result()?;
-        let right_to_right_gain = context.create_gain().into_js_result()?;
-
-        let merger: web_sys::AudioNode = context
-            .create_channel_merger_with_number_of_inputs(2)
-            .into_js_result()?
-            .into();
-
-        // Wire up the nodes.
-        // Note that for mono tracks, we want to use channel 0 (left) for both the left and right.
-        self.node.disconnect().warn_on_error();
-        self.envelope_node.disconnect().warn_on_error();
-        self.envelope_node
-            .connect_with_audio_node(&splitter)
-            .into_js_result()?;
-        splitter
-            .connect_with_audio_node_and_output(&left_to_left_gain, 0)
-            .into_js_result()?;
-        splitter
-            .connect_with_audio_node_and_output(&left_to_right_gain, 0)
-            .into_js_result()?;
-        splitter
-            .connect_with_audio_node_and_output(
-                &right_to_left_gain,
-                if self.envelope_is_stereo { 1 } else { 0 },
-            )
-            .into_js_result()?;
-        splitter
-            .connect_with_audio_node_and_output(
-                &right_to_right_gain,
-                if self.envelope_is_stereo { 1 } else { 0 },
-            )
-            .into_js_result()?;
+    /// The node that is currently playing this buffer.
+    node: Option<web_sys::AudioBufferSourceNode>,
 
-        left_to_left_gain
-            .connect_with_audio_node_and_output_and_input(&merger, 0, 0)
-            .into_js_result()?;
-        left_to_right_gain
-            .connect_with_audio_node_and_output_and_input(&merger, 0, 1)
-            .into_js_result()?;
-        right_to_left_gain
-            .connect_with_audio_node_and_output_and_input(&merger, 0, 0)
-            .into_js_result()?;
-        right_to_right_gain
-            .connect_with_audio_node_and_output_and_input(&merger, 0, 1)
+    /// Called when the node has finished playing, to play the next samples.
+    on_ended: Option<Closure<dyn FnMut()>>,
+}
+
+impl Buffer {
+    /// Mix the next samples and schedule them to play.
+    fn play(&mut self) -> Result<(), Error> {
+        let report = self
+            .mixer
+            .borrow_mut()
+            .mix(&mut self.left_samples, &mut self.right_samples);
+        if let (Some(report), Some(instances)) = (report, self.instances.upgrade()) {
+            apply_report(&mut instances.borrow_mut(), report);
+        }
+        copy_to_audio_buffer(
+            &self.audio_buffer,
+            Some(&self.left_samples),
+            Some(&self.right_samples),
+        );
+
+        let node = self.context.create_buffer_source().into_js_result()?;
+        node.set_buffer(Some(&self.audio_buffer));
+        node.connect_with_audio_node(&self.context.destination())
             .into_js_result()?;
+        if let Some(on_ended) = &self.on_ended {
+            node.set_onended(Some(on_ended.as_ref().unchecked_ref()));
+        }
 
-        merger
-            .connect_with_audio_node(&context.destination())
-            .warn_on_error();
-
-        self.node = merger;
-        self.envelope_is_stereo = true;
-        self.sound_transform_nodes = SoundTransformNodes::Transform {
-            left_to_left_gain,
-            left_to_right_gain,
-            right_to_left_gain,
-            right_to_right_gain,
-        };
+        // Play right after the previous buffer, unless we've fallen behind.
+        let start_time = self.buffer_timestamp.get().max(self.context.current_time());
+        node.start_with_when(start_time).into_js_result()?;
+        self.buffer_timestamp
+            .set(start_time + self.audio_buffer.duration());
+
+        self.node = Some(node);
         Ok(())
     }
 }
 
-/// The gain nodes controlling the sound transform for this sound.
-/// Because most sounds will be untransformed, we lazily instantiate
-/// this only when necessary to play a transformed sound.
-enum SoundTransformNodes {
-    /// No transform is applied to this sound.
-    None,
-
-    /// This sound has volume applied to it.
-    Volume { gain: GainNode },
-
-    /// This sound has a full transform applied to it.
-    Transform {
-        left_to_left_gain: GainNode,
-        left_to_right_gain: GainNode,
-        right_to_left_gain: GainNode,
-        right_to_right_gain: GainNode,
-    },
+/// Stops the current node from calling back into the dropped buffer.
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        if let Some(node) = &self.node {
+            node.set_onended(None);
+            let _ = node.disconnect();
+        }
+    }
 }
 
 type Error = Box<dyn std::error::Error>;
@@ -302,27 +300,27 @@ type Error = Box<dyn std::error::Error>;
 impl WebAudioBackend {
     pub fn new() -> Result<Self, Error> {
         let context = AudioContext::new().map_err(|_| "Unable to create AudioContext")?;
-
-        // Deduce the minimum sample rate for this browser.
-        let mut min_sample_rate = 44100;
-        while min_sample_rate > 5512
-            && context
-                .create_buffer(1, 1, (min_sample_rate >> 1) as f32)
-                .is_ok()
-        {
-            min_sample_rate >>= 1;
-        }
-        log::info!("Minimum audio buffer sample rate: {}", min_sample_rate);
+        let instances = Rc::new(RefCell::new(Arena::new()));
+        let output = if js_sys::Reflect::has(&context, &"audioWorklet".into()).unwrap_or(false) {
+            MixerOutput::worklet(&context, &instances)?
+        } else {
+            log::info!("AudioWorklet is unavailable, so audio will be mixed on the main thread");
+            MixerOutput::main_thread(&context, &instances)?
+        };
 
         Ok(Self {
             context,
+            output,
             sounds: Arena::new(),
+            instances,
+            stream_clips: FnvHashMap::default(),
+            next_clip_id: 0,
+            frame_rate: 1.0,
             preload_stream_data: FnvHashMap::default(),
             next_stream_id: 0,
-            left_samples: vec![],
-            right_samples: vec![],
-            frame_rate: 1.0,
-            min_sample_rate,
+            stream_segments: FnvHashMap::default(),
+            stream_instances: FnvHashMap::default(),
+            decoded_sounds: Rc::new(RefCell::new(vec![])),
         })
     }
 
@@ -331,381 +329,91 @@ impl WebAudioBackend {
         &self.context
     }
 
-    fn start_sound_internal(
-        &mut self,
-        handle: SoundHandle,
-        settings: Option<&swf::SoundInfo>,
-    ) -> Result<SoundInstanceHandle, Error> {
-        let sound = self.sounds.get(handle).unwrap();
-        let handle = match &sound.source {
-            SoundSource::AudioBuffer(audio_buffer) => {
-                let audio_buffer = audio_buffer.borrow();
-                let node = self.context.create_buffer_source().unwrap();
-                node.set_buffer(Some(&*audio_buffer));
-
-                let buffer_source_node = node.clone();
-
-                let sound_sample_rate = f64::from(sound.format.sample_rate);
-                let mut is_stereo = sound.format.is_stereo;
-                let node: web_sys::AudioNode = match settings {
-                    Some(settings)
-                        if sound.skip_sample_frames > 0
-                            || settings.num_loops > 1
-                            || settings.in_sample.is_some()
-                            || settings.out_sample.is_some()
-                            || settings.envelope.is_some() =>
-                    {
-                        // Event sound with non-default parameters.
-                        // Note that start/end values are in 44.1kHZ samples regardless of the sound's sample rate.
-                        let start_sample_frame = f64::from(settings.in_sample.unwrap_or(0))
-                            / 44100.0
-                            + f64::from(sound.skip_sample_frames) / sound_sample_rate;
-                        node.set_loop(settings.num_loops > 1);
-                        node.set_loop_start(start_sample_frame);
-                        node.start_with_when_and_grain_offset(0.0, start_sample_frame)
-                            .warn_on_error();
-
-                        let current_time = self.context.current_time();
-
-                        // The length of the sound in the swf, or by the script playing it, doesn't
-                        // always line up with the actual length of the sound.
-                        // Always set a custom end point to make sure we're correct.
-                        let end_sample_frame = if let Some(out_sample) = settings.out_sample {
-                            f64::from(out_sample) / 44100.0
-                        } else {
-                            f64::from(sound.num_sample_frames + u32::from(sound.skip_sample_frames))
-                                / sound_sample_rate
-                        };
-                        // `AudioSourceBufferNode.loop` is a bool, so we have to stop the loop at the proper time.
-                        // `start_with_when_and_grain_offset_and_grain_duration` unfortunately doesn't work
-                        // as you might expect with loops, so we use `stop_with_when` to stop the loop.
-                        let total_len =
-                            (end_sample_frame - start_sample_frame) * f64::from(settings.num_loops);
-                        node.set_loop_end(end_sample_frame);
-                        node.stop_with_when(current_time + total_len)
-                            .warn_on_error();
-
-                        // For envelopes, we rig the node up to some splitter/gain nodes.
-                        if let Some(envelope) = &settings.envelope {
-                            is_stereo = true;
-                            self.create_sound_envelope(
-                                node.into(),
-                                envelope,
-                                sound.format.is_stereo,
-                                current_time,
-                            )
-                            .unwrap()
-                        } else {
-                            node.into()
-                        }
-                    }
-                    _ => {
-                        // Default event sound or stream.
-                        node.start().warn_on_error();
-                        node.into()
-                    }
-                };
-
-                node.connect_with_audio_node(&self.context.destination())
-                    .warn_on_error();
-
-                // Create the sound instance and add it to the active instances list.
-                let instance = SoundInstance {
-                    handle: Some(handle),
-                    format: sound.format.clone(),
-                    instance_type: SoundInstanceType::AudioBuffer(AudioBufferInstance {
-                        envelope_node: node.clone(),
-                        envelope_is_stereo: is_stereo,
-                        node,
-                        buffer_source_node: buffer_source_node.clone(),
-                        sound_transform_nodes: SoundTransformNodes::None,
-                    }),
-                };
-                let instance_handle = SOUND_INSTANCES.with(|instances| {
-                    let mut instances = instances.borrow_mut();
-                    instances.insert(instance)
-                });
-
-                // Create the listener to remove the sound when it ends.
-                let ended_handler = move || {
-                    SOUND_INSTANCES.with(|instances| {
-                        let mut instances = instances.borrow_mut();
-                        instances.remove(instance_handle)
-                    });
-                };
-                let closure = Closure::once_into_js(Box::new(ended_handler) as Box<dyn FnMut()>);
-                // Note that we add the ended event to the AudioBufferSourceNode; an audio envelope adds more nodes
-                // in the graph, but these nodes don't fire the ended event.
-                let _ = buffer_source_node.set_onended(Some(closure.as_ref().unchecked_ref()));
-
-                instance_handle
-            }
-            SoundSource::Decoder(audio_data) => {
-                let decoder: Decoder = match sound.format.compression {
-                    AudioCompression::Adpcm => Box::new(AdpcmDecoder::new(
-                        std::io::Cursor::new(audio_data.to_vec()),
-                        sound.format.is_stereo,
-                        sound.format.sample_rate,
-                    )),
-                    AudioCompression::Nellymoser => Box::new(NellymoserDecoder::new(
-                        std::io::Cursor::new(audio_data.to_vec()),
-                        sound.format.sample_rate.into(),
-                    )),
-                    compression => {
-                        return Err(format!("Unimplemented codec: {:?}", compression).into())
-                    }
-                };
-
-                let decoder: Decoder =
-                    if sound.format.sample_rate != self.context.sample_rate() as u16 {
-                        Box::new(resample(
-                            decoder,
-                            sound.format.sample_rate,
-                            self.context.sample_rate() as u16,
-                        ))
-                    } else {
-                        decoder
-                    };
-
-                let instance = SoundInstance {
-                    handle: Some(handle),
-                    format: sound.format.clone(),
-                    instance_type: SoundInstanceType::Decoder(decoder),
-                };
-                SOUND_INSTANCES.with(|instances| {
-                    let mut instances = instances.borrow_mut();
-                    let instance_handle = instances.insert(instance);
-                    let script_processor_node = self.context.create_script_processor_with_buffer_size_and_number_of_input_channels_and_number_of_output_channels(4096, 0, if sound.format.is_stereo { 2 } else { 1 }).unwrap();
-                    let script_node = script_processor_node.clone();
-                    let closure = Closure::wrap(Box::new(move |event| {
-                            SOUND_INSTANCES.with(|instances| {
-                                let mut instances = instances.borrow_mut();
-                                let instance = instances.get_mut(instance_handle).unwrap();
-                                let complete = WebAudioBackend::update_script_processor(instance, event);
-                                if complete {
-                                    instances.remove(instance_handle);
-                                    script_node.disconnect().unwrap();
-                                }
-                            })
-                        }) as Box<dyn FnMut(web_sys::AudioProcessingEvent)>);
-                        script_processor_node.set_onaudioprocess(Some(closure.as_ref().unchecked_ref()));
-                        // TODO: This will leak memory per playing sound. Remember and properly drop the closure.
-                        closure.forget();
-
-                    instance_handle
-                })
-            }
-        };
-        Ok(handle)
+    /// Send a command to the mixer, along with the data that it needs.
+    fn send(&self, command: MixerCommand, data: Option<&[u8]>) {
+        self.output.send(command, data);
     }
 
-    /// Wires up the envelope for Flash event sounds using `ChannelSplitter`, `Gain`, and `ChannelMerger` nodes.
-    fn create_sound_envelope(
-        &self,
-        node: web_sys::AudioNode,
-        envelope: &[swf::SoundEnvelopePoint],
-        is_stereo: bool,
-        start_time: f64,
-    ) -> Result<web_sys::AudioNode, Box<dyn std::error::Error>> {
-        // Split the left and right channels.
-        let splitter = self
-            .context
-            .create_channel_splitter_with_number_of_outputs(2)
-            .into_js_result()?;
-
-        // Create the envelope gain nodes for the left and right channels.
-        let left_gain = self.context.create_gain().into_js_result()?;
-        let right_gain = self.context.create_gain().into_js_result()?;
-
-        // Initial volume is clamped to first envelope point.
-        if let Some(point) = envelope.get(0) {
-            left_gain
-                .gain()
-                .set_value_at_time(point.left_volume, 0.0)
-                .warn_on_error();
-            right_gain
-                .gain()
-                .set_value_at_time(point.right_volume, 0.0)
-                .warn_on_error();
-        }
-
-        // Add volume lerps for envelope points.
-        for point in envelope {
-            left_gain
-                .gain()
-                .linear_ramp_to_value_at_time(
-                    point.left_volume,
-                    start_time + f64::from(point.sample) / 44100.0,
-                )
-                .warn_on_error();
-            right_gain
-                .gain()
-                .linear_ramp_to_value_at_time(
-                    point.right_volume,
-                    start_time + f64::from(point.sample) / 44100.0,
-                )
-                .warn_on_error();
-        }
-
-        // Merge the channels back together.
-        let merger: web_sys::AudioNode = self
-            .context
-            .create_channel_merger_with_number_of_inputs(2)
-            .into_js_result()?
-            .into();
-
-        // Wire up the nodes.
-        node.connect_with_audio_node(&splitter).into_js_result()?;
-        splitter
-            .connect_with_audio_node_and_output(&left_gain, 0)
-            .into_js_result()?;
-        // Note that for mono tracks, we want to use channel 0 (left) for both the left and right.
-        splitter
-            .connect_with_audio_node_and_output(&right_gain, if is_stereo { 1 } else { 0 })
-            .into_js_result()?;
-        left_gain
-            .connect_with_audio_node_and_output_and_input(&merger, 0, 0)
-            .into_js_result()?;
-        right_gain
-            .connect_with_audio_node_and_output_and_input(&merger, 0, 1)
-            .into_js_result()?;
-
-        Ok(merger)
+    /// Register a sound with the mixer.
+    fn register_sound_data(
+        &mut self,
+        format: &swf::SoundFormat,
+        num_samples: u32,
+        data: &[u8],
+    ) -> SoundHandle {
+        let handle = self.sounds.insert(sound_duration(format, num_samples));
+        self.send(
+            MixerCommand::RegisterSound {
+                sound: handle_key(handle),
+                format: format.into(),
+                num_samples,
+            },
+            Some(data),
+        );
+        handle
     }
 
-    fn decompress_to_audio_buffer(
+    /// Start playing a registered sound.
+    fn start_mixer_sound(
         &mut self,
-        format: &swf::SoundFormat,
-        audio_data: &[u8],
-        num_sample_frames: u32,
-        adpcm_block_offsets: Option<&[usize]>,
-    ) -> Result<AudioBufferPtr, Error> {
-        if format.compression == AudioCompression::Mp3 {
-            return Ok(self.decompress_mp3_to_audio_buffer(format, audio_data, num_sample_frames));
+        sound: SoundHandle,
+        sound_info: &swf::SoundInfo,
+    ) -> Result<SoundInstanceHandle, Error> {
+        if !self.sounds.contains(sound) {
+            return Err("start_sound: Unregistered sound".into());
         }
 
-        self.left_samples.clear();
-        self.right_samples.clear();
-
-        match format.compression {
-            AudioCompression::Uncompressed | AudioCompression::UncompressedUnknownEndian => {
-                use byteorder::{LittleEndian, ReadBytesExt};
-                let mut audio_data = audio_data;
+        let instance = self.instances.borrow_mut().insert(0);
+        self.send(
+            MixerCommand::StartSound {
+                sound: handle_key(sound),
+                instance: handle_key(instance),
+                settings: sound_info.into(),
+            },
+            None,
+        );
+        Ok(instance)
+    }
 
-                let read_sample = |audio_data: &mut &[u8]| {
-                    if format.is_16_bit {
-                        f32::from(audio_data.read_i16::<LittleEndian>().unwrap_or(0)) / 32767.0
-                    } else {
-                        f32::from(audio_data.read_u8().unwrap_or(0)) / 128.0 - 1.0
-                    }
-                };
-                while !audio_data.is_empty() {
-                    self.left_samples.push(read_sample(&mut audio_data));
-                    if format.is_stereo {
-                        self.right_samples.push(read_sample(&mut audio_data));
-                    }
-                }
-            }
-            AudioCompression::Adpcm => {
-                // For stream sounds, the ADPCM header is included in each block,
-                // so we must recreate the decoder for each block.
-                // Event sounds don't have this issue.
-                let full = [0, audio_data.len()];
-                let adpcm_block_offsets = adpcm_block_offsets.unwrap_or(&full);
-                for block in adpcm_block_offsets.windows(2) {
-                    let start = block[0];
-                    let end = block[1];
-                    let mut decoder = AdpcmDecoder::new(
-                        &audio_data[start..end],
-                        format.is_stereo,
-                        format.sample_rate,
-                    );
-                    if format.is_stereo {
-                        while let Some(frame) = decoder.next() {
-                            let (l, r) = (frame[0], frame[1]);
-                            self.left_samples.push(f32::from(l) / 32767.0);
-                            self.right_samples.push(f32::from(r) / 32767.0);
-                        }
-                    } else {
-                        self.left_samples
-                            .extend(decoder.map(|n| f32::from(n[0]) / 32767.0));
-                    }
-                }
-            }
-            AudioCompression::Nellymoser => {
-                let decoder = NellymoserDecoder::new(audio_data, format.sample_rate.into());
-                for frame in decoder {
-                    let (l, r) = (frame[0], frame[1]);
-                    self.left_samples.push(f32::from(l) / 32767.0);
-                    self.right_samples.push(f32::from(r) / 32767.0);
-                }
+    /// Returns the mixer's identifier for the tag data of a MovieClip, sending
+    /// the data to the mixer the first time a stream plays from it.
+    fn clip_id(&mut self, clip_data: &SwfSlice) -> ClipId {
+        let key = (
+            Arc::as_ptr(&clip_data.movie) as usize,
+            clip_data.start,
+            clip_data.end,
+        );
+        if let Some((movie, clip)) = self.stream_clips.get(&key) {
+            if movie.strong_count() > 0 {
+                return *clip;
             }
-            compression => return Err(format!("Unimplemented codec: {:?}", compression).into()),
         }
 
-        // This sucks. Firefox and Safari don't like low sample rates,
-        // so manually multiply the samples.
-        let sample_rate = if format.sample_rate < self.min_sample_rate {
-            let sample_multiplier = self.min_sample_rate / format.sample_rate;
-            let mut samples = Vec::with_capacity(self.left_samples.len() * 2);
-            for sample in &self.left_samples {
-                for _ in 0..sample_multiplier {
-                    samples.push(*sample);
-                }
-            }
-            self.left_samples = samples;
-
-            if format.is_stereo {
-                let mut samples = Vec::with_capacity(self.right_samples.len() * 2);
-                for sample in &self.right_samples {
-                    for _ in 0..sample_multiplier {
-                        samples.push(*sample);
-                    }
-                }
-                self.right_samples = samples;
-            }
-
-            self.min_sample_rate
-        } else {
-            format.sample_rate
-        };
-
-        let num_sample_frames = self.left_samples.len() as u32;
-        let audio_buffer = self
-            .context
-            .create_buffer(
-                if format.is_stereo { 2 } else { 1 },
-                num_sample_frames,
-                f32::from(sample_rate),
-            )
-            .unwrap();
-
-        copy_to_audio_buffer(
-            &audio_buffer,
-            Some(&self.left_samples),
-            if format.is_stereo {
-                Some(&self.right_samples)
-            } else {
-                None
+        let clip = self.next_clip_id;
+        self.next_clip_id = self.next_clip_id.wrapping_add(1);
+        self.send(
+            MixerCommand::RegisterClip {
+                clip,
+                swf_version: clip_data.version(),
             },
+            Some(clip_data.data()),
         );
-
-        Ok(Rc::new(RefCell::new(audio_buffer)))
+        let movie = Arc::downgrade(&clip_data.movie);
+        if let Some((_, old_clip)) = self.stream_clips.insert(key, (movie, clip)) {
+            self.send(MixerCommand::ForgetClip { clip: old_clip }, None);
+        }
+        clip
     }
 
-    fn decompress_mp3_to_audio_buffer(
-        &mut self,
-        format: &swf::SoundFormat,
-        audio_data: &[u8],
-        _num_sample_frames: u32,
-    ) -> AudioBufferPtr {
-        // We use the Web decodeAudioData API to decode MP3 data.
-        // TODO: Is it possible we finish loading before the MP3 is decoding?
-        let audio_buffer = self
-            .context
-            .create_buffer(1, 1, self.context.sample_rate())
-            .unwrap();
-        let audio_buffer = Rc::new(RefCell::new(audio_buffer));
+    /// Register an MP3 sound, and have the browser decode it.
+    ///
+    /// The MP3 data is registered with the mixer until decoding finishes,
+    /// at which point it is replaced by the decoded PCM data.
+    fn register_mp3_sound(&mut self, sound: &swf::Sound) -> Result<SoundHandle, Error> {
+        let handle = self.register_sound_data(&sound.format, sound.num_samples, sound.data);
+        let skip_sample_frames = u16::from(sound.data[0]) | (u16::from(sound.data[1]) << 8);
+        let mp3_sample_rate = sound.format.sample_rate;
+        let num_sample_frames = sound.num_samples;
 
         // Clone the audio data into an ArrayBuffer
         // SAFETY: (compare with the docs for `Uint8Array::view`)
@@ -713,7 +421,7 @@ impl WebAudioBackend {
         // - We don't mutate `data_array`
         // - Since we clone the buffer, its lifetime is correctly disconnected from `audio_data`
         let array_buffer = {
-            let data_array = unsafe { js_sys::Uint8Array::view(audio_data) };
+            let data_array = unsafe { js_sys::Uint8Array::view(&sound.data[2..]) };
             data_array.buffer().slice_with_end(
                 data_array.byte_offset(),
                 data_array.byte_offset() + data_array.byte_length(),
@@ -722,10 +430,15 @@ impl WebAudioBackend {
 
         NUM_SOUNDS_LOADING.with(|n| n.set(n.get() + 1));
 
-        let _num_channels = if format.is_stereo { 2 } else { 1 };
-        let buffer_ptr = Rc::clone(&audio_buffer);
+        let decoded_sounds = Rc::clone(&self.decoded_sounds);
         let success_closure = Closure::wrap(Box::new(move |buffer: web_sys::AudioBuffer| {
-            *buffer_ptr.borrow_mut() = buffer;
+            let sound = decode_audio_buffer(
+                &buffer,
+                mp3_sample_rate,
+                skip_sample_frames,
+                num_sample_frames,
+            );
+            decoded_sounds.borrow_mut().push((handle, sound));
             NUM_SOUNDS_LOADING.with(|n| n.set(n.get() - 1));
         }) as Box<dyn FnMut(web_sys::AudioBuffer)>);
         let error_closure = Closure::wrap(Box::new(move || {
@@ -739,51 +452,71 @@ impl WebAudioBackend {
                 success_closure.as_ref().unchecked_ref(),
                 error_closure.as_ref().unchecked_ref(),
             )
-            .unwrap();
+            .into_js_result()?;
 
         // TODO: This will leak memory (once per decompressed MP3).
         // Not a huge deal as there are probably not many MP3s in an SWF.
         success_closure.forget();
         error_closure.forget();
 
-        audio_buffer
+        Ok(handle)
     }
 
-    fn update_script_processor(
-        instance: &mut SoundInstance,
-        event: web_sys::AudioProcessingEvent,
-    ) -> bool {
-        let mut complete = false;
-        let mut left_samples = vec![];
-        let mut right_samples = vec![];
-        if let SoundInstanceType::Decoder(ref mut decoder) = &mut instance.instance_type {
-            let output_buffer = event.output_buffer().unwrap();
-            let num_frames = output_buffer.length() as usize;
-
-            for _ in 0..num_frames {
-                if let Some(frame) = decoder.next() {
-                    let (l, r) = (frame[0], frame[1]);
-                    left_samples.push(f32::from(l) / 32767.0);
-                    if instance.format.is_stereo {
-                        right_samples.push(f32::from(r) / 32767.0);
+    /// Returns the settings to play a preloaded MP3 stream from the beginning of
+    /// the given frame.
+    fn stream_sound_info(&self, stream: SoundHandle, clip_frame: u16) -> swf::SoundInfo {
+        // Figure out the frame and sample where this stream segment first starts.
+        let mut in_sample = None;
+        if clip_frame > 1 {
+            if let Some(stream_segments) = self.stream_segments.get(&stream) {
+                let start_pos = match stream_segments.binary_search_by(|(f, _)| f.cmp(&clip_frame))
+                {
+                    Ok(i) => stream_segments[i].1,
+                    Err(i) => {
+                        if i > 0 {
+                            let (segment_frame, segment_sample) = stream_segments[i - 1];
+                            let frames_skipped = clip_frame.saturating_sub(segment_frame);
+                            let samples_per_frame = 44100.0 / self.frame_rate;
+                            segment_sample + u32::from(frames_skipped) * (samples_per_frame as u32)
+                        } else {
+                            0
+                        }
                     }
-                } else {
-                    complete = true;
-                    break;
-                }
+                };
+                in_sample = Some(start_pos);
             }
-            copy_to_audio_buffer(
-                &output_buffer,
-                Some(&left_samples),
-                if instance.format.is_stereo {
-                    Some(&right_samples)
-                } else {
-                    None
-                },
-            );
         }
 
-        complete
+        swf::SoundInfo {
+            event: swf::SoundEvent::Event,
+            in_sample,
+            out_sample: None,
+            num_loops: 1,
+            envelope: None,
+        }
+    }
+
+    /// Replace MP3 sounds in the mixer with their decoded data.
+    fn register_decoded_sounds(&mut self) {
+        for (handle, sound) in self.decoded_sounds.borrow_mut().drain(..) {
+            let format = swf::SoundFormat {
+                compression: AudioCompression::Uncompressed,
+                sample_rate: sound.sample_rate,
+                is_stereo: true,
+                is_16_bit: true,
+            };
+            if let Some(duration) = self.sounds.get_mut(handle) {
+                *duration = sound_duration(&format, sound.num_sample_frames);
+                self.send(
+                    MixerCommand::ReplaceSound {
+                        sound: handle_key(handle),
+                        format: (&format).into(),
+                        num_samples: sound.num_sample_frames,
+                    },
+                    Some(&sound.data),
+                );
+            }
+        }
     }
 }
 
@@ -793,33 +526,25 @@ impl AudioBackend for WebAudioBackend {
     }
 
     fn register_sound(&mut self, sound: &swf::Sound) -> Result<SoundHandle, Error> {
-        // Slice off latency seek for MP3 data.
-        let (skip_sample_frames, data) = if sound.format.compression == AudioCompression::Mp3 {
-            let skip_sample_frames = u16::from(sound.data[0]) | (u16::from(sound.data[1]) << 8);
-            (skip_sample_frames, &sound.data[2..])
+        if sound.format.compression != AudioCompression::Mp3 {
+            Ok(self.register_sound_data(&sound.format, sound.num_samples, sound.data))
+        } else if sound.data.len() >= 2 {
+            self.register_mp3_sound(sound)
         } else {
-            (0, sound.data)
-        };
-
-        let sound = Sound {
-            format: sound.format.clone(),
-            source: SoundSource::AudioBuffer(self.decompress_to_audio_buffer(
-                &sound.format,
-                data,
-                sound.num_samples,
-                None,
-            )?),
-            num_sample_frames: sound.num_samples,
-            skip_sample_frames,
-            stream_segments: vec![],
-        };
-        Ok(self.sounds.insert(sound))
+            Err("register_sound: MP3 sound is missing its latency seek".into())
+        }
     }
 
     fn preload_sound_stream_head(
         &mut self,
         stream_info: &swf::SoundStreamHead,
     ) -> Option<PreloadStreamHandle> {
+        // Only MP3 streams are decoded ahead of time. The mixer decodes other
+        // streams on the fly from the clip data.
+        if stream_info.stream_format.compression != AudioCompression::Mp3 {
+            return None;
+        }
+
         let stream_id = self.next_stream_id;
         self.next_stream_id = self.next_stream_id.wrapping_add(1);
         self.preload_stream_data
@@ -828,9 +553,7 @@ impl AudioBackend for WebAudioBackend {
                 format: stream_info.stream_format.clone(),
                 audio_data: vec![],
                 num_sample_frames: 0,
-                samples_per_block: stream_info.num_samples_per_block.into(),
                 skip_sample_frames: stream_info.latency_seek as u16,
-                adpcm_block_offsets: vec![],
                 stream_segments: vec![],
                 last_clip_frame: 0,
             });
@@ -846,81 +569,55 @@ impl AudioBackend for WebAudioBackend {
         if let Some(stream) = self.preload_stream_data.get_mut(&stream_id) {
             // Handle gaps in streaming audio. Store the offsets for each stream segment.
             if stream.audio_data.is_empty() || stream.last_clip_frame + 1 != clip_frame {
-                let sample_mult = 44100 / stream.format.sample_rate;
-                let start_sample = stream.num_sample_frames * u32::from(sample_mult);
-                stream.stream_segments.push((clip_frame, start_sample));
+                // Stream segments start at a sample position in 44.1KHz, like `SoundInfo`.
+                let start_sample = u64::from(stream.num_sample_frames) * 44100
+                    / u64::from(stream.format.sample_rate);
+                stream
+                    .stream_segments
+                    .push((clip_frame, start_sample as u32));
             }
             stream.last_clip_frame = clip_frame;
 
-            match stream.format.compression {
-                AudioCompression::Uncompressed | AudioCompression::UncompressedUnknownEndian => {
-                    let frame_len = if stream.format.is_stereo { 2 } else { 1 }
-                        * if stream.format.is_16_bit { 2 } else { 1 };
-                    stream.num_sample_frames += (audio_data.len() as u32) / frame_len;
-                    stream.audio_data.extend_from_slice(audio_data);
-                }
-                AudioCompression::Mp3 => {
-                    // Sometimes you may get blocks with zero samples; this may be because
-                    // previous blocks had more samples than necessary, or because the stream
-                    // is stopping (silence).
-                    if audio_data.len() >= 4 {
-                        let num_sample_frames =
-                            u32::from(audio_data[0]) | (u32::from(audio_data[1]) << 8);
-                        stream.num_sample_frames += num_sample_frames;
-                        // MP3 streaming data:
-                        // First two bytes = number of samples
-                        // Second two bytes = 'latency seek' (amount to skip when seeking to this frame)
-                        stream.audio_data.extend_from_slice(&audio_data[4..]);
-                    }
-                }
-                AudioCompression::Adpcm => {
-                    // For ADPCM data, we must keep track of where each block starts,
-                    // so that we read the header in each block.
-                    stream.num_sample_frames += stream.samples_per_block;
-                    stream.adpcm_block_offsets.push(stream.audio_data.len());
-                    stream.audio_data.extend_from_slice(audio_data);
-                }
-                AudioCompression::Nellymoser => {
-                    stream.num_sample_frames += stream.samples_per_block;
-                    stream.audio_data.extend_from_slice(audio_data);
-                }
-                _ => {
-                    // TODO: This is a guess and will vary slightly from block to block!
-                    stream.num_sample_frames += stream.samples_per_block;
-                }
+            // Sometimes you may get blocks with zero samples; this may be because
+            // previous blocks had more samples than necessary, or because the stream
+            // is stopping (silence).
+            if audio_data.len() >= 4 {
+                let num_sample_frames = u32::from(audio_data[0]) | (u32::from(audio_data[1]) << 8);
+                stream.num_sample_frames += num_sample_frames;
+                // MP3 streaming data:
+                // First two bytes = number of samples
+                // Second two bytes = 'latency seek' (amount to skip when seeking to this frame)
+                stream.audio_data.extend_from_slice(&audio_data[4..]);
             }
         }
     }
 
     fn preload_sound_stream_end(&mut self, stream_id: PreloadStreamHandle) -> Option<SoundHandle> {
-        let stream_data = self.preload_stream_data.remove(&stream_id);
-
-        if let Some(mut stream) = stream_data {
-            if !stream.audio_data.is_empty() {
-                if let Ok(audio_buffer) = self.decompress_to_audio_buffer(
-                    &stream.format,
-                    &stream.audio_data[..],
-                    stream.num_sample_frames,
-                    if stream.format.compression == AudioCompression::Adpcm {
-                        stream.adpcm_block_offsets.push(stream.audio_data.len());
-                        Some(&stream.adpcm_block_offsets[..])
-                    } else {
-                        None
-                    },
-                ) {
-                    let handle = self.sounds.insert(Sound {
-                        format: stream.format,
-                        source: SoundSource::AudioBuffer(audio_buffer),
-                        num_sample_frames: stream.num_sample_frames,
-                        skip_sample_frames: stream.skip_sample_frames,
-                        stream_segments: stream.stream_segments,
-                    });
-                    return Some(handle);
-                }
-            }
+        let stream = self.preload_stream_data.remove(&stream_id)?;
+        if stream.audio_data.is_empty() {
+            return None;
         }
 
-        None
+        // Register the stream as an MP3 event sound, with its latency seek in
+        // front of the data.
+        let mut data = stream.skip_sample_frames.to_le_bytes().to_vec();
+        data.extend_from_slice(&stream.audio_data);
+        let sound = swf::Sound {
+            id: 0,
+            format: stream.format,
+            num_samples: stream.num_sample_frames,
+            data: &data,
+        };
+        match self.register_mp3_sound(&sound) {
+            Ok(handle) => {
+                self.stream_segments.insert(handle, stream.stream_segments);
+                Some(handle)
+            }
+            Err(e) => {
+                log::error!("Unable to register MP3 stream: {}", e);
+                None
+            }
+        }
     }
 
     fn start_sound(
@@ -928,62 +625,80 @@ impl AudioBackend for WebAudioBackend {
         sound: SoundHandle,
         sound_info: &swf::SoundInfo,
     ) -> Result<SoundInstanceHandle, Error> {
-        let handle = self.start_sound_internal(sound, Some(sound_info))?;
-        Ok(handle)
+        self.register_decoded_sounds();
+        self.start_mixer_sound(sound, sound_info)
     }
 
     fn start_stream(
         &mut self,
         stream_handle: Option<SoundHandle>,
         clip_frame: u16,
-        _clip_data: ruffle_core::tag_utils::SwfSlice,
-        _stream_info: &swf::SoundStreamHead,
+        clip_data: SwfSlice,
+        stream_info: &swf::SoundStreamHead,
     ) -> Result<SoundInstanceHandle, Error> {
-        if let Some(stream) = stream_handle {
-            let mut sound_info = None;
-            if clip_frame > 1 {
-                if let Some(sound) = self.sounds.get(stream) {
-                    // Figure out the frame and sample where this stream segment first starts.
-                    let start_pos = match sound
-                        .stream_segments
-                        .binary_search_by(|(f, _)| f.cmp(&clip_frame))
-                    {
-                        Ok(i) => sound.stream_segments[i].1,
-                        Err(i) => {
-                            if i > 0 {
-                                let (segment_frame, segment_sample) = sound.stream_segments[i - 1];
-                                let frames_skipped = clip_frame.saturating_sub(segment_frame);
-                                let samples_per_frame = 44100.0 / self.frame_rate;
-                                segment_sample
-                                    + u32::from(frames_skipped) * (samples_per_frame as u32)
-                            } else {
-                                0
-                            }
-                        }
-                    };
-                    sound_info = Some(swf::SoundInfo {
-                        event: swf::SoundEvent::Event,
-                        in_sample: Some(start_pos),
-                        out_sample: None,
-                        num_loops: 1,
-                        envelope: None,
-                    });
-                }
-            }
-            let instance = self.start_sound_internal(stream, sound_info.as_ref())?;
-            Ok(instance)
+        let stream = if let Some(stream) = stream_handle {
+            stream
+        } else {
+            let clip = self.clip_id(&clip_data);
+            let instance = self.instances.borrow_mut().insert(0);
+            self.send(
+                MixerCommand::StartStream {
+                    instance: handle_key(instance),
+                    clip,
+                    clip_frame,
+                    format: (&stream_info.stream_format).into(),
+                },
+                None,
+            );
+            return Ok(instance);
+        };
+
+        self.register_decoded_sounds();
+        let sound_info = self.stream_sound_info(stream, clip_frame);
+        let instance = self.start_mixer_sound(stream, &sound_info)?;
+        self.stream_instances.insert(instance, stream);
+        Ok(instance)
+    }
+
+    /// Seeks a playing stream. If the mixer can't seek it, the mixer stops the
+    /// stream instead.
+    fn seek_stream(&mut self, instance: SoundInstanceHandle, clip_frame: u16) -> bool {
+        if !self.instances.borrow().contains(instance) {
+            return false;
+        }
+
+        if let Some(&stream) = self.stream_instances.get(&instance) {
+            self.register_decoded_sounds();
+            let sound_info = self.stream_sound_info(stream, clip_frame);
+            self.send(
+                MixerCommand::RestartSound {
+                    instance: handle_key(instance),
+                    sound: handle_key(stream),
+                    settings: (&sound_info).into(),
+                },
+                None,
+            );
         } else {
-            let msg = format!("Missing stream for sound ID {:?}", stream_handle);
-            log::error!("{}", msg);
-            Err(msg.into())
+            self.send(
+                MixerCommand::SeekStream {
+                    instance: handle_key(instance),
+                    clip_frame,
+                },
+                None,
+            );
         }
+        true
     }
 
     fn stop_sound(&mut self, sound: SoundInstanceHandle) {
-        SOUND_INSTANCES.with(|instances| {
-            let mut instances = instances.borrow_mut();
-            instances.remove(sound);
-        })
+        self.stream_instances.remove(&sound);
+        self.instances.borrow_mut().remove(sound);
+        self.send(
+            MixerCommand::StopSound {
+                instance: handle_key(sound),
+            },
+            None,
+        );
     }
 
     fn is_loading_complete(&self) -> bool {
@@ -1001,53 +716,178 @@ impl AudioBackend for WebAudioBackend {
     }
 
     fn stop_all_sounds(&mut self) {
-        SOUND_INSTANCES.with(|instances| {
-            let mut instances = instances.borrow_mut();
-            // This is a workaround for a bug in generational-arena:
-            // Arena::clear does not properly bump the generational index, allowing for stale references
-            // to continue to work (this caused #1315). Arena::remove will force a generation bump.
-            // See https://github.com/fitzgen/generational-arena/issues/30
-            if let Some((i, _)) = instances.iter().next() {
-                instances.remove(i);
-            }
-            instances.clear();
-        })
+        self.stream_instances.clear();
+        let mut instances = self.instances.borrow_mut();
+        // `Arena::clear` doesn't bump the generational index, so remove an
+        // instance first, to keep stale handles from working. See the mixer's
+        // `stop_all_sounds`.
+        if let Some((i, _)) = instances.iter().next() {
+            instances.remove(i);
+        }
+        instances.clear();
+        drop(instances);
+        self.send(MixerCommand::StopAllSounds, None);
     }
 
     fn get_sound_position(&self, instance: SoundInstanceHandle) -> Option<u32> {
-        SOUND_INSTANCES.with(|instances| {
-            let instances = instances.borrow();
-            // TODO: Return actual position
-            instances.get(instance).map(|_| 0)
-        })
+        self.instances.borrow().get(instance).copied()
     }
 
     fn get_sound_duration(&self, sound: SoundHandle) -> Option<u32> {
-        if let Some(sound) = self.sounds.get(sound) {
-            // AS duration does not subtract `skip_sample_frames`.
-            let num_sample_frames: f64 = sound.num_sample_frames.into();
-            let sample_rate: f64 = sound.format.sample_rate.into();
-            let ms = (num_sample_frames * 1000.0 / sample_rate).round();
-            Some(ms as u32)
-        } else {
-            None
-        }
+        self.sounds.get(sound).copied()
     }
 
     fn set_sound_transform(&mut self, instance: SoundInstanceHandle, transform: SoundTransform) {
-        SOUND_INSTANCES.with(|instances| {
-            let mut instances = instances.borrow_mut();
-            if let Some(instance) = instances.get_mut(instance) {
-                if let SoundInstanceType::AudioBuffer(sound) = &mut instance.instance_type {
-                    sound.set_transform(&self.context, &transform);
-                }
+        self.send(
+            MixerCommand::SetSoundTransform {
+                instance: handle_key(instance),
+                transform: [
+                    transform.left_to_left,
+                    transform.left_to_right,
+                    transform.right_to_left,
+                    transform.right_to_right,
+                ],
+            },
+            None,
+        );
+    }
+
+    fn advance_frame_clock(&mut self, dt: f64) {
+        self.send(MixerCommand::AdvanceFrameClock { dt }, None);
+    }
+
+    fn tick(&mut self) {
+        self.register_decoded_sounds();
+
+        // Forget about streams that have finished playing.
+        let instances = self.instances.borrow();
+        self.stream_instances
+            .retain(|instance, _| instances.contains(*instance));
+        drop(instances);
+
+        // Have the mixer forget the clips of movies that have been unloaded.
+        let mut unloaded_clips = vec![];
+        self.stream_clips.retain(|_, (movie, clip)| {
+            let is_loaded = movie.strong_count() > 0;
+            if !is_loaded {
+                unloaded_clips.push(*clip);
             }
-        })
+            is_loaded
+        });
+        for clip in unloaded_clips {
+            self.send(MixerCommand::ForgetClip { clip }, None);
+        }
+    }
+}
+
+/// Convert an `AudioBuffer` decoded from an MP3 sound into 16-bit stereo PCM,
+/// removing the encoder delay from the start of the sound.
+fn decode_audio_buffer(
+    buffer: &web_sys::AudioBuffer,
+    mp3_sample_rate: u16,
+    skip_sample_frames: u16,
+    num_sample_frames: u32,
+) -> DecodedSound {
+    // The sample rate of a sound has to fit in a `u16`, so drop samples from
+    // sounds that were decoded at a higher rate.
+    let mut sample_rate = buffer.sample_rate() as u32;
+    let mut step = 1;
+    while sample_rate > u32::from(u16::MAX) {
+        sample_rate /= 2;
+        step *= 2;
+    }
+    let sample_rate = sample_rate as u16;
+    let scale =
+        |n: u32| (u64::from(n) * u64::from(sample_rate) / u64::from(mp3_sample_rate)) as u32;
+
+    let left = buffer.get_channel_data(0).unwrap_or_default();
+    let right = if buffer.number_of_channels() > 1 {
+        buffer.get_channel_data(1).unwrap_or_default()
+    } else {
+        left.clone()
+    };
+    let num_frames = left.len() / step;
+
+    let start = (scale(skip_sample_frames.into()) as usize).min(num_frames);
+    let end = (start + scale(num_sample_frames) as usize).min(num_frames);
+    let mut data = Vec::with_capacity((end - start) * 4);
+    let frames = left.iter().zip(&right).step_by(step);
+    for (l, r) in frames.skip(start).take(end - start) {
+        let l = (l.max(-1.0).min(1.0) * 32767.0) as i16;
+        let r = (r.max(-1.0).min(1.0) * 32767.0) as i16;
+        data.extend_from_slice(&l.to_le_bytes());
+        data.extend_from_slice(&r.to_le_bytes());
+    }
+
+    DecodedSound {
+        sample_rate,
+        num_sample_frames: (end - start) as u32,
+        data,
     }
 }
 
+/// The raw parts of a sound or sound instance handle, which identify it to the
+/// mixer.
+fn handle_key(handle: Index) -> HandleKey {
+    handle.into_raw_parts()
+}
+
+/// The duration of a sound in milliseconds, as ActionScript reports it.
+fn sound_duration(format: &swf::SoundFormat, num_samples: u32) -> u32 {
+    let num_samples: f64 = num_samples.into();
+    let sample_rate: f64 = format.sample_rate.into();
+    (num_samples * 1000.0 / sample_rate).round() as u32
+}
+
+/// Update the positions of the playing sound instances from a report of the
+/// mixer, and forget the instances that have ended.
+fn apply_report(instances: &mut Arena<u32>, report: MixerReport) {
+    for ((index, generation), position) in report.positions {
+        if let Some(instance) = instances.get_mut(Index::from_raw_parts(index, generation)) {
+            *instance = position;
+        }
+    }
+    for (index, generation) in report.ended {
+        instances.remove(Index::from_raw_parts(index, generation));
+    }
+}
+
+/// Load the mixer into the audio worklet of `context`, and play its output.
+///
+/// The mixer takes its commands from `port`.
+async fn start_mixer(context: &AudioContext, port: MessagePort) -> Result<(), Error> {
+    let module = JsFuture::from(add_mixer_worklet(context))
+        .await
+        .into_js_result()?;
+
+    let processor_options = js_sys::Object::new();
+    js_sys::Reflect::set(&processor_options, &"module".into(), &module).into_js_result()?;
+    let mut options = AudioWorkletNodeOptions::new();
+    options
+        .number_of_inputs(0)
+        .output_channel_count(&js_sys::Array::of1(&2.into()))
+        .processor_options(Some(&processor_options));
+    let node =
+        AudioWorkletNode::new_with_options(context, MIXER_PROCESSOR, &options).into_js_result()?;
+    node.connect_with_audio_node(&context.destination())
+        .into_js_result()?;
+
+    // Hand the mixer its end of the command channel.
+    node.port()
+        .into_js_result()?
+        .post_message_with_transferable(&port, &js_sys::Array::of1(&port))
+        .into_js_result()?;
+    Ok(())
+}
+
 #[wasm_bindgen(raw_module = "./ruffle-imports.js")]
 extern "C" {
+    /// Imported JS method to load the mixer into the audio worklet of an
+    /// `AudioContext`. Resolves to the mixer's compiled WebAssembly module,
+    /// which the worklet instantiates.
+    #[wasm_bindgen(js_name = "addMixerWorklet")]
+    fn add_mixer_worklet(context: &AudioContext) -> js_sys::Promise;
+
     /// Imported JS method to copy data into an `AudioBuffer`.
     /// We'd prefer to use `AudioBuffer.copyToChannel`, but this isn't supported
     /// on Safari.
@@ -1058,54 +898,3 @@ extern "C" {
         right_data: Option<&[f32]>,
     );
 }
-
-// Janky resmapling code.
-// TODO: Clean this up.
-#[allow(unused_assignments)]
-fn resample(
-    mut input: impl Iterator<Item = [i16; 2]>,
-    input_sample_rate: u16,
-    output_sample_rate: u16,
-) -> impl Iterator<Item = [i16; 2]> {
-    let (mut left0, mut right0) = if let Some(frame) = input.next() {
-        (Some(frame[0]), Some(frame[1]))
-    } else {
-        (None, None)
-    };
-    let (mut left1, mut right1) = if let Some(frame) = input.next() {
-        (Some(frame[0]), Some(frame[1]))
-    } else {
-        (None, None)
-    };
-    let (mut left, mut right) = (left0.unwrap(), right0.unwrap());
-    let dt_input = 1.0 / f64::from(input_sample_rate);
-    let dt_output = 1.0 / f64::from(output_sample_rate);
-    let mut t = 0.0;
-    std::iter::from_fn(move || {
-        if let (Some(l0), Some(r0), Some(l1), Some(r1)) = (left0, right0, left1, right1) {
-            let a = t / dt_input;
-            let l0 = f64::from(l0);
-            let l1 = f64::from(l1);
-            let r0 = f64::from(r0);
-            let r1 = f64::from(r1);
-            left = (l0 + (l1 - l0) * a) as i16;
-            right = (r0 + (r1 - r0) * a) as i16;
-            t += dt_output;
-            while t >= dt_input {
-                t -= dt_input;
-                left0 = left1;
-                right0 = right1;
-                if let Some(frame) = input.next() {
-                    left1 = Some(frame[0]);
-                    right1 = Some(frame[1]);
-                } else {
-                    left1 = None;
-                    right1 = None;
-                }
-            }
-            Some([left, right])
-        } else {
-            None
-        }
-    })
-}
@@ -6,6 +6,7 @@
 
 //! Ruffle web frontend.
 mod audio;
+mod local_connection;
 mod locale;
 mod log_adapter;
 mod navigator;
@@ -13,22 +14,23 @@ mod storage;
 mod ui;
 
 use generational_arena::{Arena, Index};
-use js_sys::{Array, Function, Object, Uint8Array};
+use js_sys::{Array, Function, Object, RangeError, Uint8Array};
 use ruffle_core::backend::{
     audio::{AudioBackend, NullAudioBackend},
+    navigator::rewrite::UrlRewriter,
     render::RenderBackend,
     storage::{MemoryStorageBackend, StorageBackend},
     ui::UiBackend,
     video::SoftwareVideoBackend,
 };
-use ruffle_core::config::Letterbox;
+use ruffle_core::config::{CrossDomainMode, Letterbox, LoopPolicy, StageQuality};
 use ruffle_core::context::UpdateContext;
 use ruffle_core::events::{KeyCode, MouseWheelDelta};
 use ruffle_core::external::{
     ExternalInterfaceMethod, ExternalInterfaceProvider, Value as ExternalValue, Value,
 };
-use ruffle_core::tag_utils::SwfMovie;
-use ruffle_core::{Color, Player, PlayerEvent};
+use ruffle_core::tag_utils::{MovieInfo, SwfMovie};
+use ruffle_core::{Color, Player, PlayerEvent, StageOrientation};
 use ruffle_web_common::JsResult;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -76,6 +78,7 @@ struct RuffleInstance {
     key_down_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
     key_up_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
     unload_callback: Option<Closure<dyn FnMut(Event)>>,
+    orientation_change_callback: Option<Closure<dyn FnMut(Event)>>,
     has_focus: bool,
     trace_observer: Arc<RefCell<JsValue>>,
 }
@@ -98,7 +101,7 @@ extern "C" {
     #[wasm_bindgen(method, js_name = "onCallbackAvailable")]
     fn on_callback_available(this: &JavascriptPlayer, name: &str);
 
-    #[wasm_bindgen(method, catch, js_name = "onFSCommand")]
+    #[wasm_bindgen(method, catch, js_name = "callFSCommand")]
     fn on_fs_command(this: &JavascriptPlayer, command: &str, args: &str) -> Result<bool, JsValue>;
 
     #[wasm_bindgen(method)]
@@ -107,14 +110,26 @@ extern "C" {
     #[wasm_bindgen(method, js_name = "displayUnsupportedMessage")]
     fn display_unsupported_message(this: &JavascriptPlayer);
 
+    #[wasm_bindgen(method, js_name = "displaySlowScriptPrompt")]
+    fn display_slow_script_prompt(this: &JavascriptPlayer) -> bool;
+
     #[wasm_bindgen(method, js_name = "displayMessage")]
     fn display_message(this: &JavascriptPlayer, message: &str);
 
+    #[wasm_bindgen(method, js_name = "printPages")]
+    fn print_pages(this: &JavascriptPlayer, pages: &Array);
+
     #[wasm_bindgen(method, getter, js_name = "isFullscreen")]
     fn is_fullscreen(this: &JavascriptPlayer) -> bool;
 
     #[wasm_bindgen(method, js_name = "setMetadata")]
     fn set_metadata(this: &JavascriptPlayer, metadata: JsValue);
+
+    #[wasm_bindgen(method, js_name = "setFrameAnchor")]
+    fn set_frame_anchor(this: &JavascriptPlayer, anchor: &str);
+
+    #[wasm_bindgen(method, js_name = "onLifecycleEvent")]
+    fn on_lifecycle_event(this: &JavascriptPlayer, event: &str);
 }
 
 struct JavascriptInterface {
@@ -135,6 +150,12 @@ pub struct Config {
     #[serde(rename = "upgradeToHttps")]
     upgrade_to_https: bool,
 
+    #[serde(rename = "urlRewrites")]
+    url_rewrites: Vec<String>,
+
+    #[serde(rename = "crossDomain")]
+    cross_domain: CrossDomainMode,
+
     #[serde(rename = "warnOnUnsupportedContent")]
     warn_on_unsupported_content: bool,
 
@@ -143,6 +164,22 @@ pub struct Config {
 
     #[serde(rename = "maxExecutionDuration")]
     max_execution_duration: Duration,
+
+    #[serde(rename = "maxActionsPerUpdate")]
+    max_actions_per_update: Option<u64>,
+
+    menu: bool,
+
+    #[serde(rename = "loop")]
+    loop_movie: bool,
+
+    quality: Option<String>,
+
+    #[serde(rename = "frameInterpolation")]
+    frame_interpolation: Option<bool>,
+
+    #[serde(rename = "traceToConsole")]
+    trace_to_console: bool,
 }
 
 impl Default for Config {
@@ -152,9 +189,17 @@ impl Default for Config {
             background_color: Default::default(),
             letterbox: Default::default(),
             upgrade_to_https: true,
+            url_rewrites: Default::default(),
+            cross_domain: Default::default(),
             warn_on_unsupported_content: true,
             log_level: log::Level::Error,
             max_execution_duration: Duration::from_secs(15),
+            max_actions_per_update: None,
+            menu: true,
+            loop_movie: true,
+            quality: None,
+            frame_interpolation: None,
+            trace_to_console: false,
         }
     }
 }
@@ -170,6 +215,33 @@ struct MovieMetadata {
     num_frames: u16,
     #[serde(rename = "swfVersion")]
     swf_version: u8,
+    #[serde(rename = "backgroundColor")]
+    background_color: Option<String>,
+    #[serde(rename = "isActionScript3")]
+    is_action_script_3: bool,
+    #[serde(rename = "compressedLength")]
+    compressed_length: usize,
+    #[serde(rename = "uncompressedLength")]
+    uncompressed_length: u32,
+}
+
+/// A new size of the viewport, to be applied to the canvas and the player.
+struct ViewportDimensions {
+    canvas: HtmlCanvasElement,
+    width: u32,
+    height: u32,
+    device_pixel_ratio: f64,
+}
+
+impl ViewportDimensions {
+    fn apply(&self, core: &mut Player) {
+        self.canvas.set_width(self.width);
+        self.canvas.set_height(self.height);
+
+        core.set_viewport_dimensions(self.width, self.height, self.device_pixel_ratio);
+        core.renderer_mut()
+            .set_viewport_dimensions(self.width, self.height);
+    }
 }
 
 /// An opaque handle to a `RuffleInstance` inside the pool.
@@ -207,8 +279,8 @@ impl Ruffle {
             let parameters_to_load = parse_movie_parameters(&parameters);
 
             let ruffle = *self;
-            let on_metadata = move |swf_header: &ruffle_core::swf::Header| {
-                ruffle.on_metadata(swf_header);
+            let on_metadata = move |movie_info: &MovieInfo| {
+                ruffle.on_metadata(movie_info);
             };
 
             core.fetch_root_movie(movie_url, parameters_to_load, Box::new(on_metadata));
@@ -229,7 +301,7 @@ impl Ruffle {
             movie
         });
 
-        self.on_metadata(movie.header());
+        self.on_metadata(&movie.info());
 
         let _ = self.with_core_mut(move |core| {
             core.set_root_movie(movie);
@@ -254,6 +326,40 @@ impl Ruffle {
         self.with_core(|core| core.is_playing()).unwrap_or_default()
     }
 
+    /// Jump the root timeline to a frame and stop, as `gotoAndStop` would.
+    /// `frame` is 1-based.
+    ///
+    /// Throws a `RangeError` if `frame` isn't a whole number that a timeline
+    /// could have a frame for.
+    pub fn goto_frame(&mut self, frame: f64) -> Result<(), JsValue> {
+        if frame.fract() != 0.0 || !(1.0..=f64::from(u16::MAX)).contains(&frame) {
+            return Err(RangeError::new(&format!("Invalid frame number: {}", frame)).into());
+        }
+
+        let _ = self.with_core_mut(|core| core.goto_root_frame(frame as u16, true));
+        Ok(())
+    }
+
+    /// Read a variable of the movie by its path, converted to a string.
+    pub fn get_variable(&mut self, path: &str) -> Option<String> {
+        self.with_core_mut(|core| core.get_variable(path))
+            .ok()
+            .flatten()
+    }
+
+    /// Set a variable of the movie by its path.
+    pub fn set_variable(&mut self, path: &str, value: &str) {
+        let _ = self.with_core_mut(|core| core.set_variable(path, value));
+    }
+
+    /// Navigate the root timeline to a named anchor.
+    ///
+    /// Returns `false` if the movie has no such anchor.
+    pub fn goto_anchor(&mut self, anchor: &str) -> bool {
+        self.with_core_mut(|core| core.goto_anchor(anchor))
+            .unwrap_or_default()
+    }
+
     // after the context menu is closed, remember to call `clear_custom_menu_items`!
     pub fn prepare_context_menu(&mut self) -> JsValue {
         self.with_core_mut(|core| {
@@ -263,6 +369,21 @@ impl Ruffle {
         .unwrap_or(JsValue::UNDEFINED)
     }
 
+    /// Whether the movie wants the built-in context menu items, which it can
+    /// turn off with `Stage.showMenu`.
+    pub fn show_menu(&mut self) -> bool {
+        self.with_core_mut(|core| core.show_menu()).unwrap_or(true)
+    }
+
+    /// Notes on unsupported features that the movie used, for bug reports.
+    /// Returns an array of strings.
+    pub fn diagnostics(&mut self) -> JsValue {
+        self.with_core_mut(|core| {
+            JsValue::from_serde(&core.diagnostics()).unwrap_or(JsValue::UNDEFINED)
+        })
+        .unwrap_or(JsValue::UNDEFINED)
+    }
+
     pub fn run_context_menu_callback(&mut self, index: usize) {
         let _ = self.with_core_mut(|core| core.run_context_menu_callback(index));
     }
@@ -272,6 +393,8 @@ impl Ruffle {
     }
 
     pub fn destroy(&mut self) {
+        local_connection::remove_instance(self.0);
+
         // Remove instance from the active list.
         if let Ok(mut instance) = self.remove_instance() {
             instance.canvas.remove();
@@ -374,6 +497,16 @@ impl Ruffle {
                     .warn_on_error();
                 instance.unload_callback = None;
             }
+            if let Some(orientation_change_callback) = &instance.orientation_change_callback {
+                instance
+                    .window
+                    .remove_event_listener_with_callback(
+                        "orientationchange",
+                        orientation_change_callback.as_ref().unchecked_ref(),
+                    )
+                    .warn_on_error();
+                instance.orientation_change_callback = None;
+            }
 
             // Cancel the animation handler, if it's still active.
             if let Some(id) = instance.animation_handler_id {
@@ -447,9 +580,17 @@ impl Ruffle {
             log::error!("Unable to create audio backend. No audio will be played.");
             Box::new(NullAudioBackend::new())
         };
+        let mut url_rewriter = UrlRewriter::new();
+        for rule in &config.url_rewrites {
+            match rule.parse() {
+                Ok(rule) => url_rewriter.add_rule(rule),
+                Err(e) => log::error!("{}", e),
+            }
+        }
         let navigator = Box::new(navigator::WebNavigatorBackend::new(
             allow_script_access,
             config.upgrade_to_https,
+            url_rewriter,
         ));
         let storage = match window.local_storage() {
             Ok(Some(s)) => {
@@ -463,7 +604,10 @@ impl Ruffle {
         let locale = Box::new(locale::WebLocaleBackend::new());
         let trace_observer = Arc::new(RefCell::new(JsValue::UNDEFINED));
         let video = Box::new(SoftwareVideoBackend::new());
-        let log = Box::new(log_adapter::WebLogBackend::new(trace_observer.clone()));
+        let log = Box::new(log_adapter::WebLogBackend::new(
+            trace_observer.clone(),
+            config.trace_to_console,
+        ));
         let ui = Box::new(ui::WebUiBackend::new(js_player.clone(), &canvas));
 
         let core =
@@ -474,8 +618,18 @@ impl Ruffle {
                 core.set_background_color(Some(color));
             }
             core.set_letterbox(config.letterbox);
+            core.set_initial_quality(config.quality.as_deref().and_then(parse_quality));
             core.set_warn_on_unsupported_content(config.warn_on_unsupported_content);
             core.set_max_execution_duration(config.max_execution_duration);
+            core.set_max_actions_per_update(config.max_actions_per_update);
+            core.set_cross_domain_mode(config.cross_domain);
+            core.set_show_menu(config.menu);
+            core.set_loop_policy(if config.loop_movie {
+                LoopPolicy::Loop
+            } else {
+                LoopPolicy::Once
+            });
+            core.set_frame_interpolation(config.frame_interpolation);
 
             // Create the external interface.
             if allow_script_access {
@@ -503,6 +657,7 @@ impl Ruffle {
             key_down_callback: None,
             key_up_callback: None,
             unload_callback: None,
+            orientation_change_callback: None,
             timestamp: None,
             has_focus: false,
             trace_observer,
@@ -517,6 +672,13 @@ impl Ruffle {
         // Register the instance and create the animation frame closure.
         let mut ruffle = Ruffle::add_instance(instance)?;
 
+        // Connect its `LocalConnection`s to those of the other instances.
+        ruffle.with_core_mut(|core| {
+            core.set_local_connection_bridge(Box::new(
+                local_connection::WebLocalConnectionBridge::new(ruffle.0),
+            ));
+        })?;
+
         // Create the animation frame closure.
         ruffle.with_instance_mut(|instance| {
             instance.animation_handler = Some(Closure::wrap(Box::new(move |timestamp: f64| {
@@ -765,8 +927,24 @@ impl Ruffle {
                 )
                 .warn_on_error();
             instance.unload_callback = Some(unload_callback);
+
+            let orientation_change_callback = Closure::wrap(Box::new(move |_| {
+                ruffle.on_orientation_change();
+            })
+                as Box<dyn FnMut(Event)>);
+
+            window
+                .add_event_listener_with_callback(
+                    "orientationchange",
+                    orientation_change_callback.as_ref().unchecked_ref(),
+                )
+                .warn_on_error();
+            instance.orientation_change_callback = Some(orientation_change_callback);
         })?;
 
+        // Report the orientation the device is already in.
+        ruffle.on_orientation_change();
+
         // Set initial timestamp and do initial tick to start animation loop.
         ruffle.tick(0.0);
 
@@ -902,32 +1080,7 @@ impl Ruffle {
         let mut dt = 0.0;
         let mut new_dimensions = None;
         let _ = self.with_instance_mut(|instance| {
-            // Check for canvas resize.
-            let canvas_width = instance.canvas.client_width();
-            let canvas_height = instance.canvas.client_height();
-            let device_pixel_ratio = instance.window.device_pixel_ratio(); // Changes via user zooming.
-            if instance.canvas_width != canvas_width
-                || instance.canvas_height != canvas_height
-                || (instance.device_pixel_ratio - device_pixel_ratio).abs() >= f64::EPSILON
-            {
-                // If a canvas resizes, its drawing context will get scaled. You must reset
-                // the width and height attributes of the canvas element to recreate the context.
-                // (NOT the CSS width/height!)
-                instance.canvas_width = canvas_width;
-                instance.canvas_height = canvas_height;
-                instance.device_pixel_ratio = device_pixel_ratio;
-
-                // The actual viewport is scaled by DPI, bigger than CSS pixels.
-                let viewport_width = (f64::from(canvas_width) * device_pixel_ratio) as u32;
-                let viewport_height = (f64::from(canvas_height) * device_pixel_ratio) as u32;
-
-                new_dimensions = Some((
-                    instance.canvas.clone(),
-                    viewport_width,
-                    viewport_height,
-                    device_pixel_ratio,
-                ));
-            }
+            new_dimensions = instance.check_for_resize();
 
             // Request next animation frame.
             if let Some(handler) = &instance.animation_handler {
@@ -956,15 +1109,12 @@ impl Ruffle {
 
         // Tick the Ruffle core.
         let _ = self.with_core_mut(|core| {
-            if let Some((ref canvas, viewport_width, viewport_height, device_pixel_ratio)) =
-                new_dimensions
-            {
-                canvas.set_width(viewport_width);
-                canvas.set_height(viewport_height);
-
-                core.set_viewport_dimensions(viewport_width, viewport_height, device_pixel_ratio);
-                core.renderer_mut()
-                    .set_viewport_dimensions(viewport_width, viewport_height);
+            if let Some(dimensions) = &new_dimensions {
+                dimensions.apply(core);
+            }
+
+            for message in local_connection::take_messages(self.0) {
+                core.receive_local_connection_message(message);
             }
 
             core.tick(dt);
@@ -976,16 +1126,45 @@ impl Ruffle {
         });
     }
 
-    fn on_metadata(&self, swf_header: &ruffle_core::swf::Header) {
+    /// Report the orientation of the device to the player, and resize it
+    /// right away.
+    ///
+    /// Rotating a device resizes the canvas, which would otherwise only be
+    /// noticed on the next frame. Until then, touches would be mapped onto the
+    /// stage as if it still had its old size.
+    fn on_orientation_change(&self) {
+        let mut orientation = StageOrientation::Unknown;
+        let mut new_dimensions = None;
+        let _ = self.with_instance_mut(|instance| {
+            orientation = orientation_of(&instance.window);
+            new_dimensions = instance.check_for_resize();
+        });
+
+        let _ = self.with_core_mut(|core| {
+            if core.viewport_orientation() != orientation {
+                core.set_viewport_orientation(orientation);
+            }
+            if let Some(dimensions) = &new_dimensions {
+                dimensions.apply(core);
+            }
+        });
+    }
+
+    fn on_metadata(&self, movie_info: &MovieInfo) {
         let _ = self.with_instance(|instance| {
-            let width = swf_header.stage_size.x_max - swf_header.stage_size.x_min;
-            let height = swf_header.stage_size.y_max - swf_header.stage_size.y_min;
             let metadata = MovieMetadata {
-                width: width.to_pixels(),
-                height: height.to_pixels(),
-                frame_rate: swf_header.frame_rate,
-                num_frames: swf_header.num_frames,
-                swf_version: swf_header.version,
+                width: movie_info.width,
+                height: movie_info.height,
+                frame_rate: movie_info.frame_rate,
+                num_frames: movie_info.num_frames,
+                swf_version: movie_info.swf_version,
+                background_color: movie_info
+                    .background_color
+                    .as_ref()
+                    .map(|color| format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)),
+                is_action_script_3: movie_info.is_action_script_3,
+                compressed_length: movie_info.compressed_length,
+                uncompressed_length: movie_info.uncompressed_length,
             };
 
             if let Ok(value) = JsValue::from_serde(&metadata) {
@@ -996,6 +1175,35 @@ impl Ruffle {
 }
 
 impl RuffleInstance {
+    /// Check whether the canvas was resized, or the page zoomed, since this
+    /// was last called, and if so, the new size of the viewport.
+    fn check_for_resize(&mut self) -> Option<ViewportDimensions> {
+        let canvas_width = self.canvas.client_width();
+        let canvas_height = self.canvas.client_height();
+        let device_pixel_ratio = self.window.device_pixel_ratio(); // Changes via user zooming.
+        if self.canvas_width != canvas_width
+            || self.canvas_height != canvas_height
+            || (self.device_pixel_ratio - device_pixel_ratio).abs() >= f64::EPSILON
+        {
+            // If a canvas resizes, its drawing context will get scaled. You must reset
+            // the width and height attributes of the canvas element to recreate the context.
+            // (NOT the CSS width/height!)
+            self.canvas_width = canvas_width;
+            self.canvas_height = canvas_height;
+            self.device_pixel_ratio = device_pixel_ratio;
+
+            // The actual viewport is scaled by DPI, bigger than CSS pixels.
+            Some(ViewportDimensions {
+                canvas: self.canvas.clone(),
+                width: (f64::from(canvas_width) * device_pixel_ratio) as u32,
+                height: (f64::from(canvas_height) * device_pixel_ratio) as u32,
+                device_pixel_ratio,
+            })
+        } else {
+            None
+        }
+    }
+
     #[allow(dead_code)]
     fn with_core<F, O>(&self, f: F) -> Result<O, RuffleInstanceError>
     where
@@ -1272,6 +1480,38 @@ fn parse_movie_parameters(input: &JsValue) -> Vec<(String, String)> {
     params
 }
 
+/// The orientation of the device's screen.
+///
+/// Safari doesn't have `screen.orientation`, so the older `window.orientation`
+/// is used when it's missing.
+fn orientation_of(window: &Window) -> StageOrientation {
+    let get = |object: &JsValue, key: &str| {
+        js_sys::Reflect::get(object, &JsValue::from_str(key))
+            .ok()
+            .filter(|value| !value.is_undefined() && !value.is_null())
+    };
+    let window: &JsValue = window.as_ref();
+    let angle = get(window, "screen")
+        .and_then(|screen| get(&screen, "orientation"))
+        .and_then(|orientation| get(&orientation, "angle"))
+        .or_else(|| get(window, "orientation"))
+        .and_then(|angle| angle.as_f64());
+    match angle {
+        Some(angle) => StageOrientation::from_angle(angle as i32),
+        None => StageOrientation::Unknown,
+    }
+}
+
+/// Parse the `quality` embed parameter. The automatic qualities start at the
+/// quality they're named after.
+fn parse_quality(quality: &str) -> Option<StageQuality> {
+    match quality.to_ascii_lowercase().as_str() {
+        "autolow" => Some(StageQuality::Low),
+        "autohigh" => Some(StageQuality::High),
+        quality => quality.parse().ok(),
+    }
+}
+
 fn parse_html_color(color: impl AsRef<str>) -> Option<Color> {
     // Parse classic HTML hex color (XXXXXX or #XXXXXX), attempting to match browser behavior.
     // Optional leading #.
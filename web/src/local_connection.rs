@@ -0,0 +1,95 @@
+//! `LocalConnection` messages between the Ruffle instances on a page.
+
+use generational_arena::Index;
+use ruffle_core::local_connection::{LocalConnectionBridge, LocalConnectionMessage};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+thread_local! {
+    /// The instance listening on each connection name.
+    static CONNECTIONS: RefCell<HashMap<String, Index>> = RefCell::new(HashMap::new());
+
+    /// The messages that haven't been delivered yet, and the instance each one
+    /// is for.
+    static MESSAGES: RefCell<Vec<(Index, LocalConnectionMessage)>> = RefCell::new(Vec::new());
+}
+
+/// Connects the `LocalConnection`s of an instance to the other instances.
+///
+/// Messages are queued, and delivered when the receiving instance next ticks.
+pub struct WebLocalConnectionBridge {
+    instance: Index,
+}
+
+impl WebLocalConnectionBridge {
+    pub fn new(instance: Index) -> Self {
+        Self { instance }
+    }
+}
+
+impl LocalConnectionBridge for WebLocalConnectionBridge {
+    fn connect(&mut self, connection_name: &str) -> bool {
+        CONNECTIONS.with(|connections| {
+            match connections.borrow_mut().entry(connection_name.to_string()) {
+                Entry::Occupied(_) => false,
+                Entry::Vacant(entry) => {
+                    entry.insert(self.instance);
+                    true
+                }
+            }
+        })
+    }
+
+    fn close(&mut self, connection_name: &str) {
+        CONNECTIONS.with(|connections| {
+            let mut connections = connections.borrow_mut();
+            if connections.get(connection_name) == Some(&self.instance) {
+                connections.remove(connection_name);
+            }
+        });
+    }
+
+    fn send(&mut self, message: LocalConnectionMessage) -> bool {
+        let receiver = CONNECTIONS
+            .with(|connections| connections.borrow().get(&message.connection_name).copied());
+        match receiver {
+            Some(receiver) if receiver != self.instance => {
+                MESSAGES.with(|messages| messages.borrow_mut().push((receiver, message)));
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Take the messages that were sent to an instance.
+pub fn take_messages(instance: Index) -> Vec<LocalConnectionMessage> {
+    MESSAGES.with(|messages| {
+        let mut messages = messages.borrow_mut();
+        let mut taken = Vec::new();
+        let mut i = 0;
+        while i < messages.len() {
+            if messages[i].0 == instance {
+                taken.push(messages.remove(i).1);
+            } else {
+                i += 1;
+            }
+        }
+        taken
+    })
+}
+
+/// Forget the connections of, and messages to, an instance that was destroyed.
+pub fn remove_instance(instance: Index) {
+    CONNECTIONS.with(|connections| {
+        connections
+            .borrow_mut()
+            .retain(|_, receiver| *receiver != instance)
+    });
+    MESSAGES.with(|messages| {
+        messages
+            .borrow_mut()
+            .retain(|(receiver, _)| *receiver != instance)
+    });
+}
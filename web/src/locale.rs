@@ -1,11 +1,16 @@
-use chrono::{DateTime, FixedOffset, Local, Offset, Utc};
-use ruffle_core::backend::locale::LocaleBackend;
+use chrono::{DateTime, Local, Offset, TimeZone, Utc};
+use ruffle_core::backend::locale::{LocalTimeZone, LocaleBackend};
 
-pub struct WebLocaleBackend();
+pub struct WebLocaleBackend {
+    timezone: LocalTimeZone,
+}
 
 impl WebLocaleBackend {
     pub fn new() -> Self {
-        Self()
+        let timezone = LocalTimeZone::from_host(Utc::now(), |time| {
+            Local.offset_from_utc_datetime(&time.naive_utc()).fix()
+        });
+        Self { timezone }
     }
 }
 
@@ -14,7 +19,7 @@ impl LocaleBackend for WebLocaleBackend {
         Utc::now()
     }
 
-    fn get_timezone(&self) -> FixedOffset {
-        Local::now().offset().fix()
+    fn get_timezone(&self) -> LocalTimeZone {
+        self.timezone
     }
 }
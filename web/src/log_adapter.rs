@@ -6,17 +6,27 @@ use wasm_bindgen::{JsCast, JsValue};
 
 pub struct WebLogBackend {
     trace_observer: Arc<RefCell<JsValue>>,
+
+    /// Whether traces are printed to the browser console, instead of the log.
+    trace_to_console: bool,
 }
 
 impl WebLogBackend {
-    pub fn new(trace_observer: Arc<RefCell<JsValue>>) -> Self {
-        Self { trace_observer }
+    pub fn new(trace_observer: Arc<RefCell<JsValue>>, trace_to_console: bool) -> Self {
+        Self {
+            trace_observer,
+            trace_to_console,
+        }
     }
 }
 
 impl LogBackend for WebLogBackend {
     fn avm_trace(&self, message: &str) {
-        log::info!(target: "avm_trace", "{}", message);
+        if self.trace_to_console {
+            web_sys::console::log_1(&JsValue::from_str(message));
+        } else {
+            log::info!(target: "avm_trace", "{}", message);
+        }
         if let Some(function) = self.trace_observer.borrow().dyn_ref::<Function>() {
             let _ = function.call1(&function, &JsValue::from_str(message));
         }
@@ -1,7 +1,8 @@
 //! Navigator backend for web
-use js_sys::{Array, ArrayBuffer, Uint8Array};
+use js_sys::{Array, ArrayBuffer, Function, Object, Promise, Reflect, Uint8Array};
 use ruffle_core::backend::navigator::{
-    url_from_relative_url, NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions,
+    rewrite::UrlRewriter, url_from_relative_url, FetchProgress, NavigationMethod, NavigatorBackend,
+    OwnedFuture, RequestOptions,
 };
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
@@ -10,17 +11,24 @@ use std::time::Duration;
 use url::Url;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::{spawn_local, JsFuture};
-use web_sys::{window, Blob, BlobPropertyBag, Performance, Request, RequestInit, Response};
+use web_sys::{
+    window, Blob, BlobPropertyBag, Performance, ReadableStream, Request, RequestInit, Response,
+};
 
 pub struct WebNavigatorBackend {
     performance: Performance,
     start_time: f64,
     allow_script_access: bool,
     upgrade_to_https: bool,
+    url_rewriter: UrlRewriter,
 }
 
 impl WebNavigatorBackend {
-    pub fn new(allow_script_access: bool, upgrade_to_https: bool) -> Self {
+    pub fn new(
+        allow_script_access: bool,
+        upgrade_to_https: bool,
+        url_rewriter: UrlRewriter,
+    ) -> Self {
         let window = web_sys::window().expect("window()");
         let performance = window.performance().expect("window.performance()");
 
@@ -33,6 +41,7 @@ impl WebNavigatorBackend {
             performance,
             allow_script_access,
             upgrade_to_https,
+            url_rewriter,
         }
     }
 }
@@ -53,7 +62,7 @@ impl NavigatorBackend for WebNavigatorBackend {
             let document = window.document().expect("Could not get document");
             let url = if let Ok(Some(base_uri)) = document.base_uri() {
                 if let Ok(new_url) = url_from_relative_url(&base_uri, &url) {
-                    new_url
+                    self.pre_process_url(new_url)
                 } else {
                     return;
                 }
@@ -70,7 +79,7 @@ impl NavigatorBackend for WebNavigatorBackend {
             //TODO: Should we return a result for failed opens? Does Flash care?
             match (vars_method, window_spec) {
                 (Some((navmethod, formvars)), window_spec) => {
-                    let form_url = self.pre_process_url(url).to_string();
+                    let form_url = url.to_string();
 
                     let body = match document.body() {
                         Some(body) => body,
@@ -184,14 +193,24 @@ impl NavigatorBackend for WebNavigatorBackend {
                 )));
             }
 
-            let data: ArrayBuffer = JsFuture::from(resp.array_buffer().unwrap())
-                .await
-                .unwrap()
-                .dyn_into()
-                .unwrap();
-            let jsarray = Uint8Array::new(&data);
-            let mut rust_array = vec![0; jsarray.length() as usize];
-            jsarray.copy_to(&mut rust_array);
+            if let Some(progress) = options.progress() {
+                if let Ok(Some(length)) = resp.headers().get("Content-Length") {
+                    if let Ok(length) = length.parse() {
+                        progress.set_bytes_total(length);
+                    }
+                }
+            }
+
+            let rust_array = match resp.body() {
+                // The body is read in chunks, so that progress can be
+                // reported as it arrives.
+                Some(body) => read_body(&body, options.progress().map(|p| &**p)).await?,
+                None => Vec::new(),
+            };
+
+            if let Some(progress) = options.progress() {
+                progress.complete(rust_array.len());
+            }
 
             Ok(rust_array)
         })
@@ -218,10 +237,48 @@ impl NavigatorBackend for WebNavigatorBackend {
         url.into()
     }
 
-    fn pre_process_url(&self, mut url: Url) -> Url {
+    fn pre_process_url(&self, url: Url) -> Url {
+        let mut url = self.url_rewriter.rewrite(url);
         if self.upgrade_to_https && url.scheme() == "http" && url.set_scheme("https").is_err() {
             log::error!("Url::set_scheme failed on: {}", url);
         }
         url
     }
 }
+
+/// Read a response body as it arrives, reporting each chunk to `progress`.
+async fn read_body(
+    body: &ReadableStream,
+    progress: Option<&FetchProgress>,
+) -> Result<Vec<u8>, Error> {
+    let read_error = || Error::FetchError("Could not read the response body".to_string());
+    let reader: Object = body.get_reader().unchecked_into();
+    let read: Function = Reflect::get(&reader, &"read".into())
+        .and_then(|read| read.dyn_into())
+        .map_err(|_| read_error())?;
+
+    let mut data = Vec::new();
+    loop {
+        let promise: Promise = read
+            .call0(&reader)
+            .and_then(|promise| promise.dyn_into())
+            .map_err(|_| read_error())?;
+        let result = JsFuture::from(promise).await.map_err(|_| read_error())?;
+        let done = Reflect::get(&result, &"done".into())
+            .map_err(|_| read_error())?
+            .as_bool()
+            .unwrap_or(true);
+        if done {
+            return Ok(data);
+        }
+
+        let chunk: Uint8Array = Reflect::get(&result, &"value".into())
+            .and_then(|value| value.dyn_into())
+            .map_err(|_| read_error())?;
+        let chunk = chunk.to_vec();
+        if let Some(progress) = progress {
+            progress.receive(&chunk);
+        }
+        data.extend_from_slice(&chunk);
+    }
+}
@@ -1,16 +1,22 @@
 use super::JavascriptPlayer;
-use ruffle_core::backend::ui::{MouseCursor, UiBackend};
+use js_sys::Array;
+use ruffle_core::backend::render::{Bitmap, BitmapFormat};
+use ruffle_core::backend::ui::{MouseCursor, MovieLifecycleEvent, UiBackend};
+use ruffle_core::bitmap::decode::unmultiply_alpha_rgba;
 use ruffle_core::events::KeyCode;
 use ruffle_web_common::JsResult;
-use std::collections::HashSet;
-use web_sys::{HtmlCanvasElement, KeyboardEvent};
+use std::collections::HashMap;
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, KeyboardEvent};
 
 /// An implementation of `UiBackend` utilizing `web_sys` bindings to input
 /// APIs.
 pub struct WebUiBackend {
     js_player: JavascriptPlayer,
     canvas: HtmlCanvasElement,
-    keys_down: HashSet<String>,
+    /// The keys being held, by their `KeyboardEvent.code`, and the key code
+    /// each was pressed as.
+    keys_down: HashMap<String, KeyCode>,
     cursor_visible: bool,
     cursor: MouseCursor,
     last_key: KeyCode,
@@ -22,7 +28,7 @@ impl WebUiBackend {
         Self {
             js_player,
             canvas: canvas.clone(),
-            keys_down: HashSet::new(),
+            keys_down: HashMap::new(),
             cursor_visible: true,
             cursor: MouseCursor::Arrow,
             last_key: KeyCode::Unknown,
@@ -32,17 +38,20 @@ impl WebUiBackend {
 
     /// Register a key press for a given code string.
     pub fn keydown(&mut self, event: &KeyboardEvent) {
-        let code = event.code();
-        self.last_key = web_to_ruffle_key_code(&code).unwrap_or(KeyCode::Unknown);
-        self.keys_down.insert(code);
+        self.last_key = web_key_event_to_ruffle_key_code(event);
+        if self.last_key != KeyCode::Unknown {
+            self.keys_down.insert(event.code(), self.last_key);
+        }
         self.last_char = web_key_to_codepoint(&event.key());
     }
 
     /// Register a key release for a given code string.
     pub fn keyup(&mut self, event: &KeyboardEvent) {
-        let code = event.code();
-        self.last_key = web_to_ruffle_key_code(&code).unwrap_or(KeyCode::Unknown);
-        self.keys_down.remove(&code);
+        // Report the key as it was pressed, even if the modifiers changed since.
+        self.last_key = self
+            .keys_down
+            .remove(&event.code())
+            .unwrap_or_else(|| web_key_event_to_ruffle_key_code(event));
         self.last_char = web_key_to_codepoint(&event.key());
     }
 
@@ -66,110 +75,7 @@ impl WebUiBackend {
 
 impl UiBackend for WebUiBackend {
     fn is_key_down(&self, key: KeyCode) -> bool {
-        match key {
-            KeyCode::Unknown => false,
-            KeyCode::Backspace => self.keys_down.contains("Backspace"),
-            KeyCode::Tab => self.keys_down.contains("Tab"),
-            KeyCode::Return => self.keys_down.contains("Enter"),
-            KeyCode::Shift => {
-                self.keys_down.contains("ShiftLeft") || self.keys_down.contains("ShiftRight")
-            }
-            KeyCode::Control => {
-                self.keys_down.contains("ControlLeft") || self.keys_down.contains("ControlRight")
-            }
-            KeyCode::Alt => {
-                self.keys_down.contains("AltLeft") || self.keys_down.contains("AltRight")
-            }
-            KeyCode::CapsLock => self.keys_down.contains("CapsLock"),
-            KeyCode::Escape => self.keys_down.contains("Escape"),
-            KeyCode::Space => self.keys_down.contains("Space"),
-            KeyCode::Key0 => self.keys_down.contains("Digit0"),
-            KeyCode::Key1 => self.keys_down.contains("Digit1"),
-            KeyCode::Key2 => self.keys_down.contains("Digit2"),
-            KeyCode::Key3 => self.keys_down.contains("Digit3"),
-            KeyCode::Key4 => self.keys_down.contains("Digit4"),
-            KeyCode::Key5 => self.keys_down.contains("Digit5"),
-            KeyCode::Key6 => self.keys_down.contains("Digit6"),
-            KeyCode::Key7 => self.keys_down.contains("Digit7"),
-            KeyCode::Key8 => self.keys_down.contains("Digit8"),
-            KeyCode::Key9 => self.keys_down.contains("Digit9"),
-            KeyCode::A => self.keys_down.contains("KeyA"),
-            KeyCode::B => self.keys_down.contains("KeyB"),
-            KeyCode::C => self.keys_down.contains("KeyC"),
-            KeyCode::D => self.keys_down.contains("KeyD"),
-            KeyCode::E => self.keys_down.contains("KeyE"),
-            KeyCode::F => self.keys_down.contains("KeyF"),
-            KeyCode::G => self.keys_down.contains("KeyG"),
-            KeyCode::H => self.keys_down.contains("KeyH"),
-            KeyCode::I => self.keys_down.contains("KeyI"),
-            KeyCode::J => self.keys_down.contains("KeyJ"),
-            KeyCode::K => self.keys_down.contains("KeyK"),
-            KeyCode::L => self.keys_down.contains("KeyL"),
-            KeyCode::M => self.keys_down.contains("KeyM"),
-            KeyCode::N => self.keys_down.contains("KeyN"),
-            KeyCode::O => self.keys_down.contains("KeyO"),
-            KeyCode::P => self.keys_down.contains("KeyP"),
-            KeyCode::Q => self.keys_down.contains("KeyQ"),
-            KeyCode::R => self.keys_down.contains("KeyR"),
-            KeyCode::S => self.keys_down.contains("KeyS"),
-            KeyCode::T => self.keys_down.contains("KeyT"),
-            KeyCode::U => self.keys_down.contains("KeyU"),
-            KeyCode::V => self.keys_down.contains("KeyV"),
-            KeyCode::W => self.keys_down.contains("KeyW"),
-            KeyCode::X => self.keys_down.contains("KeyX"),
-            KeyCode::Y => self.keys_down.contains("KeyY"),
-            KeyCode::Z => self.keys_down.contains("KeyZ"),
-            KeyCode::Semicolon => self.keys_down.contains("Semicolon"),
-            KeyCode::Equals => self.keys_down.contains("Equal"),
-            KeyCode::Comma => self.keys_down.contains("Comma"),
-            KeyCode::Minus => self.keys_down.contains("Minus"),
-            KeyCode::Period => self.keys_down.contains("Period"),
-            KeyCode::Slash => self.keys_down.contains("Slash"),
-            KeyCode::Grave => self.keys_down.contains("Backquote"),
-            KeyCode::LBracket => self.keys_down.contains("BracketLeft"),
-            KeyCode::Backslash => self.keys_down.contains("Backslash"),
-            KeyCode::RBracket => self.keys_down.contains("BracketRight"),
-            KeyCode::Apostrophe => self.keys_down.contains("Quote"),
-            KeyCode::Numpad0 => self.keys_down.contains("Numpad0"),
-            KeyCode::Numpad1 => self.keys_down.contains("Numpad1"),
-            KeyCode::Numpad2 => self.keys_down.contains("Numpad2"),
-            KeyCode::Numpad3 => self.keys_down.contains("Numpad3"),
-            KeyCode::Numpad4 => self.keys_down.contains("Numpad4"),
-            KeyCode::Numpad5 => self.keys_down.contains("Numpad5"),
-            KeyCode::Numpad6 => self.keys_down.contains("Numpad6"),
-            KeyCode::Numpad7 => self.keys_down.contains("Numpad7"),
-            KeyCode::Numpad8 => self.keys_down.contains("Numpad8"),
-            KeyCode::Numpad9 => self.keys_down.contains("Numpad9"),
-            KeyCode::Multiply => self.keys_down.contains("NumpadMultiply"),
-            KeyCode::Plus => self.keys_down.contains("NumpadAdd"),
-            KeyCode::NumpadMinus => self.keys_down.contains("NumpadSubtract"),
-            KeyCode::NumpadPeriod => self.keys_down.contains("NumpadDecimal"),
-            KeyCode::NumpadSlash => self.keys_down.contains("NumpadDivide"),
-            KeyCode::PgUp => self.keys_down.contains("PageUp"),
-            KeyCode::PgDown => self.keys_down.contains("PageDown"),
-            KeyCode::End => self.keys_down.contains("End"),
-            KeyCode::Home => self.keys_down.contains("Home"),
-            KeyCode::Left => self.keys_down.contains("ArrowLeft"),
-            KeyCode::Up => self.keys_down.contains("ArrowUp"),
-            KeyCode::Right => self.keys_down.contains("ArrowRight"),
-            KeyCode::Down => self.keys_down.contains("ArrowDown"),
-            KeyCode::Insert => self.keys_down.contains("Insert"),
-            KeyCode::Delete => self.keys_down.contains("Delete"),
-            KeyCode::Pause => self.keys_down.contains("Pause"),
-            KeyCode::ScrollLock => self.keys_down.contains("ScrollLock"),
-            KeyCode::F1 => self.keys_down.contains("F1"),
-            KeyCode::F2 => self.keys_down.contains("F2"),
-            KeyCode::F3 => self.keys_down.contains("F3"),
-            KeyCode::F4 => self.keys_down.contains("F4"),
-            KeyCode::F5 => self.keys_down.contains("F5"),
-            KeyCode::F6 => self.keys_down.contains("F6"),
-            KeyCode::F7 => self.keys_down.contains("F7"),
-            KeyCode::F8 => self.keys_down.contains("F8"),
-            KeyCode::F9 => self.keys_down.contains("F9"),
-            KeyCode::F10 => self.keys_down.contains("F10"),
-            KeyCode::F11 => self.keys_down.contains("F11"),
-            KeyCode::F12 => self.keys_down.contains("F12"),
-        }
+        self.keys_down.values().any(|&down| down == key)
     }
 
     fn last_key_code(&self) -> KeyCode {
@@ -202,13 +108,92 @@ impl UiBackend for WebUiBackend {
         self.js_player.is_fullscreen()
     }
 
+    fn set_frame_anchor(&mut self, anchor: &str) {
+        self.js_player.set_frame_anchor(anchor);
+    }
+
+    fn movie_lifecycle_event(&mut self, event: MovieLifecycleEvent) {
+        let event = match event {
+            MovieLifecycleEvent::HeaderParsed => "headerparsed",
+            MovieLifecycleEvent::PreloadComplete => "preloadcomplete",
+            MovieLifecycleEvent::FirstFrameExecuted => "firstframeexecuted",
+            MovieLifecycleEvent::FirstFrameRendered => "firstframerendered",
+            MovieLifecycleEvent::Ended => "ended",
+        };
+        self.js_player.on_lifecycle_event(event);
+    }
+
     fn display_unsupported_message(&self) {
         self.js_player.display_unsupported_message()
     }
 
+    fn display_slow_script_prompt(&mut self) -> bool {
+        self.js_player.display_slow_script_prompt()
+    }
+
     fn message(&self, message: &str) {
         self.js_player.display_message(message);
     }
+
+    fn print(&mut self, pages: Vec<Bitmap>) {
+        let urls = Array::new();
+        for page in pages {
+            match page_to_data_url(page) {
+                Ok(url) => {
+                    urls.push(&url.into());
+                }
+                Err(e) => log::error!("Couldn't draw printed page: {:?}", e),
+            }
+        }
+        if urls.length() > 0 {
+            self.js_player.print_pages(&urls);
+        }
+    }
+}
+
+/// Draw a printed page onto a canvas, and return it as a `data:` URL.
+fn page_to_data_url(page: Bitmap) -> Result<String, JsValue> {
+    let mut rgba = match page.data {
+        BitmapFormat::Rgba(rgba) => rgba,
+        BitmapFormat::Rgb(rgb) => {
+            let mut rgba = Vec::with_capacity(rgb.len() / 3 * 4);
+            for rgb in rgb.chunks_exact(3) {
+                rgba.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+            }
+            rgba
+        }
+    };
+    unmultiply_alpha_rgba(&mut rgba);
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("No document"))?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+    canvas.set_width(page.width);
+    canvas.set_height(page.height);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("No 2d context"))?
+        .dyn_into()?;
+    let image_data =
+        ImageData::new_with_u8_clamped_array_and_sh(Clamped(&rgba[..]), page.width, page.height)?;
+    context.put_image_data(&image_data, 0.0, 0.0)?;
+    canvas.to_data_url()
+}
+
+/// Find the Ruffle `KeyCode` of a web `KeyboardEvent`.
+///
+/// Letter keys are identified by the letter they type on the user's keyboard
+/// layout, as in Flash Player. Other keys are identified by their position.
+pub fn web_key_event_to_ruffle_key_code(event: &KeyboardEvent) -> KeyCode {
+    let key = event.key();
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if let Some(key_code) = KeyCode::from_letter(c) {
+            return key_code;
+        }
+    }
+    web_to_ruffle_key_code(&event.code()).unwrap_or(KeyCode::Unknown)
 }
 
 /// Convert a web `KeyboardEvent.code` value into a Ruffle `KeyCode`.
@@ -217,7 +202,7 @@ pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
     Some(match key_code {
         "Backspace" => KeyCode::Backspace,
         "Tab" => KeyCode::Tab,
-        "Enter" => KeyCode::Return,
+        "Enter" | "NumpadEnter" => KeyCode::Return,
         "ShiftLeft" | "ShiftRight" => KeyCode::Shift,
         "ControlLeft" | "ControlRight" => KeyCode::Control,
         "AltLeft" | "AltRight" => KeyCode::Alt,
@@ -297,6 +282,7 @@ pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
         "Insert" => KeyCode::Insert,
         "Delete" => KeyCode::Delete,
         "Pause" => KeyCode::Pause,
+        "NumLock" => KeyCode::NumLock,
         "ScrollLock" => KeyCode::ScrollLock,
         "F1" => KeyCode::F1,
         "F2" => KeyCode::F2,
@@ -310,6 +296,9 @@ pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
         "F10" => KeyCode::F10,
         "F11" => KeyCode::F11,
         "F12" => KeyCode::F12,
+        "F13" => KeyCode::F13,
+        "F14" => KeyCode::F14,
+        "F15" => KeyCode::F15,
         _ => return None,
     })
 }